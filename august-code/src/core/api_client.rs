@@ -0,0 +1,276 @@
+//! 类型化 REST API 客户端示例模块
+//!
+//! 在 `AsyncHttpClient` 之上为一个简单的用户接口封装类型化客户端：
+//! - 使用 serde 模型描述请求和响应
+//! - 把 HTTP 状态码映射为 `AppError`
+//! - 自动翻页获取全部数据
+//! - 只对网络和超时错误自动重试
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::http_client::{AsyncHttpClient, TextResponse};
+use crate::utils::error::{AppError, ErrorHandler, RetryConfig, RetryStrategy};
+use crate::utils::test_server::TestServer;
+
+/// 接口返回的用户
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiUser {
+    pub id: u64,
+    pub name: String,
+    pub email: String,
+}
+
+/// 创建用户的请求体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewUser {
+    pub name: String,
+    pub email: String,
+}
+
+/// 分页响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: usize,
+    pub per_page: usize,
+    pub total: usize,
+}
+
+impl<T> Page<T> {
+    /// 是否还有下一页
+    pub fn has_next(&self) -> bool {
+        self.page * self.per_page < self.total
+    }
+}
+
+/// 用户接口的类型化客户端
+#[derive(Clone)]
+pub struct ApiClient {
+    http: AsyncHttpClient,
+    base_url: String,
+    retry: RetryConfig,
+}
+
+impl ApiClient {
+    /// 创建客户端，`base_url` 为服务根地址，例如 `http://127.0.0.1:8080`
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            http: AsyncHttpClient::with_timeout(Duration::from_secs(5)),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            retry: RetryConfig {
+                max_attempts: 3,
+                strategy: RetryStrategy::Exponential(Duration::from_millis(50), 2.0),
+                timeout: Some(Duration::from_secs(10)),
+                budget: None,
+            },
+        }
+    }
+
+    /// 设置重试配置
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// 获取一页用户
+    pub async fn list_users(&self, page: usize, per_page: usize) -> Result<Page<ApiUser>> {
+        let url = format!("{}/api/users?page={}&per_page={}", self.base_url, page, per_page);
+        let response = self.send(|| self.http.get_text(&url)).await?;
+        decode(&response)
+    }
+
+    /// 自动翻页获取全部用户
+    pub async fn list_all_users(&self, per_page: usize) -> Result<Vec<ApiUser>> {
+        let mut users = Vec::new();
+        let mut page = 1;
+        loop {
+            let current = self.list_users(page, per_page).await?;
+            let has_next = current.has_next() && !current.items.is_empty();
+            users.extend(current.items);
+            if !has_next {
+                return Ok(users);
+            }
+            page += 1;
+        }
+    }
+
+    /// 按 id 获取用户，不存在时返回 `None`
+    pub async fn get_user(&self, id: u64) -> Result<Option<ApiUser>> {
+        let url = format!("{}/api/users/{}", self.base_url, id);
+        let response = self.send(|| self.http.get_text(&url)).await?;
+        if response.status == 404 {
+            return Ok(None);
+        }
+        decode(&response).map(Some)
+    }
+
+    /// 创建用户
+    pub async fn create_user(&self, user: &NewUser) -> Result<ApiUser> {
+        let url = format!("{}/api/users", self.base_url);
+        let response = self.send(|| self.http.post_json(&url, user)).await?;
+        decode(&response)
+    }
+
+    /// 发送请求并映射错误，网络和超时错误按重试配置自动重试
+    ///
+    /// 404 原样返回，由调用方决定含义。
+    async fn send<F, Fut>(&self, request: F) -> Result<TextResponse>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<TextResponse>>,
+    {
+        let request = &request;
+        ErrorHandler::with_retry_if(
+            move || async move {
+                let response = request().await.map_err(to_app_error)?;
+                check_status(response)
+            },
+            self.retry.clone(),
+            is_retryable,
+        )
+        .await
+    }
+}
+
+/// 把状态码映射为 `AppError`
+fn check_status(response: TextResponse) -> Result<TextResponse> {
+    match response.status {
+        200..=299 | 404 => Ok(response),
+        400 | 409 | 422 => Err(AppError::Business(error_message(&response)).into()),
+        408 | 429 => Err(AppError::Timeout(error_message(&response)).into()),
+        500..=599 => Err(AppError::Network(error_message(&response)).into()),
+        _ => Err(AppError::Unknown(error_message(&response)).into()),
+    }
+}
+
+/// 从响应体的 `error` 字段中取出错误信息
+fn error_message(response: &TextResponse) -> String {
+    let detail = serde_json::from_str::<serde_json::Value>(&response.body)
+        .ok()
+        .and_then(|body| body["error"].as_str().map(str::to_string))
+        .unwrap_or_else(|| response.body.clone());
+    format!("HTTP {}: {}", response.status, detail)
+}
+
+/// 把底层错误转换为 `AppError`，已经是 `AppError` 的保持不变
+fn to_app_error(error: anyhow::Error) -> anyhow::Error {
+    if error.is::<AppError>() {
+        return error;
+    }
+    ErrorHandler::categorize_error(&error).into()
+}
+
+/// 只有网络和超时错误值得重试
+fn is_retryable(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<AppError>(),
+        Some(AppError::Network(_)) | Some(AppError::Timeout(_))
+    )
+}
+
+fn decode<T: DeserializeOwned>(response: &TextResponse) -> Result<T> {
+    serde_json::from_str(&response.body)
+        .map_err(|e| AppError::Unknown(format!("响应解析失败: {}", e)).into())
+}
+
+/// 类型化 API 客户端示例：创建、查询、翻页和故障重试
+pub async fn api_client_example() -> Result<()> {
+    println!("\n=== 类型化API客户端示例 ===");
+
+    let server = TestServer::start().await?;
+    let client = ApiClient::new(&server.base_url());
+
+    for (name, email) in [("张三", "zhangsan@example.com"), ("李四", "lisi@example.com"), ("王五", "wangwu@example.com")] {
+        let user = client.create_user(&NewUser { name: name.to_string(), email: email.to_string() }).await?;
+        println!("创建用户: {:?}", user);
+    }
+
+    // 参数不合法时得到业务错误
+    let invalid = NewUser { name: String::new(), email: "invalid".to_string() };
+    if let Err(e) = client.create_user(&invalid).await {
+        println!("创建失败: {}", e);
+    }
+
+    // 每页 2 条，自动翻页
+    let users = client.list_all_users(2).await?;
+    println!("全部用户 ({} 个): {:?}", users.len(), users.iter().map(|u| &u.name).collect::<Vec<_>>());
+
+    println!("查询用户 2: {:?}", client.get_user(2).await?);
+    println!("查询用户 99: {:?}", client.get_user(99).await?);
+
+    // 服务端连续失败两次，客户端自动重试后成功
+    server.fail_next(2);
+    println!("重试后查询用户 1: {:?}", client.get_user(1).await?);
+
+    // 不重试的客户端遇到一次失败就直接返回错误
+    let no_retry = ApiClient::new(&server.base_url()).with_retry(RetryConfig {
+        max_attempts: 1,
+        ..RetryConfig::default()
+    });
+    server.fail_next(1);
+    if let Err(e) = no_retry.get_user(1).await {
+        println!("不重试的客户端: {}", e);
+    }
+
+    server.shutdown().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_user(name: &str) -> NewUser {
+        NewUser { name: name.to_string(), email: format!("{}@example.com", name) }
+    }
+
+    #[tokio::test]
+    async fn test_create_get_and_paginate() {
+        let server = TestServer::start().await.unwrap();
+        let client = ApiClient::new(&server.base_url());
+
+        for name in ["a", "b", "c", "d", "e"] {
+            client.create_user(&new_user(name)).await.unwrap();
+        }
+
+        let page = client.list_users(2, 2).await.unwrap();
+        assert_eq!(page.total, 5);
+        assert!(page.has_next());
+        assert_eq!(page.items[0].name, "c");
+
+        let all = client.list_all_users(2).await.unwrap();
+        let names: Vec<&str> = all.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c", "d", "e"]);
+
+        assert_eq!(client.get_user(3).await.unwrap().unwrap().name, "c");
+        assert!(client.get_user(42).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_error_mapping() {
+        let server = TestServer::start().await.unwrap();
+        let client = ApiClient::new(&server.base_url());
+
+        let error = client.create_user(&new_user("")).await.unwrap_err();
+        assert!(matches!(error.downcast_ref::<AppError>(), Some(AppError::Business(_))));
+
+        // 重试次数用完后返回网络错误
+        server.fail_next(5);
+        let error = client.get_user(1).await.unwrap_err();
+        assert!(matches!(error.downcast_ref::<AppError>(), Some(AppError::Network(_))));
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_failures() {
+        let server = TestServer::start().await.unwrap();
+        let client = ApiClient::new(&server.base_url());
+        client.create_user(&new_user("a")).await.unwrap();
+
+        server.fail_next(2);
+        let user = client.get_user(1).await.unwrap().unwrap();
+        assert_eq!(user.name, "a");
+    }
+}
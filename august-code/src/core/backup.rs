@@ -0,0 +1,326 @@
+//! 数据库备份和恢复模块
+//!
+//! 备份文件是 gzip 压缩的 JSON：
+//! - 外层记录格式名、格式版本、创建时间和校验和
+//! - 内层快照（结构版本、全部用户和过期时间）以字符串形式保存，校验和基于这段原始字节计算，
+//!   不受 `HashMap` 重新序列化时顺序变化的影响
+//! - 恢复时先完整校验再替换数据，格式版本不支持或校验和不一致时拒绝恢复
+
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::{Compression, Crc};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use super::database::{AsyncDatabase, User};
+use super::wal::Snapshot;
+
+/// 备份文件格式名
+pub const BACKUP_FORMAT: &str = "august-code-backup";
+
+/// 当前写入的备份格式版本
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// 备份错误
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum BackupError {
+    #[error("不是有效的备份文件: {0}")]
+    InvalidFormat(String),
+
+    #[error("不支持的备份格式版本 {found}，当前支持到 {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+
+    #[error("备份校验失败：期望校验和 {expected:08x}，实际 {actual:08x}")]
+    ChecksumMismatch { expected: u32, actual: u32 },
+}
+
+/// 备份文件外层结构
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupFile {
+    format: String,
+    format_version: u32,
+    created_at: String,
+    /// `payload` 的 CRC32
+    checksum: u32,
+    /// 序列化后的 `Snapshot`
+    payload: String,
+}
+
+/// 备份的概要信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupInfo {
+    pub format_version: u32,
+    pub created_at: String,
+    pub schema_version: u32,
+    pub users: usize,
+    /// 压缩后的文件大小
+    pub bytes: u64,
+}
+
+impl BackupInfo {
+    /// 打印备份信息
+    pub fn print(&self) {
+        println!(
+            "备份格式 v{}，创建于 {}，结构版本 {}，{} 个用户，{} 字节",
+            self.format_version, self.created_at, self.schema_version, self.users, self.bytes
+        );
+    }
+}
+
+fn checksum(bytes: &[u8]) -> u32 {
+    let mut crc = Crc::new();
+    crc.update(bytes);
+    crc.sum()
+}
+
+fn compress(file: &BackupFile) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&serde_json::to_vec(file)?)?;
+    Ok(encoder.finish()?)
+}
+
+fn decompress(bytes: &[u8]) -> Result<BackupFile> {
+    let mut json = Vec::new();
+    GzDecoder::new(bytes)
+        .read_to_end(&mut json)
+        .map_err(|e| BackupError::InvalidFormat(format!("解压失败: {}", e)))?;
+    serde_json::from_slice(&json).map_err(|e| BackupError::InvalidFormat(e.to_string()).into())
+}
+
+/// 把快照编码为备份文件内容
+pub fn encode(snapshot: &Snapshot) -> Result<(Vec<u8>, BackupInfo)> {
+    let payload = serde_json::to_string(snapshot)?;
+    let file = BackupFile {
+        format: BACKUP_FORMAT.to_string(),
+        format_version: BACKUP_FORMAT_VERSION,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        checksum: checksum(payload.as_bytes()),
+        payload,
+    };
+    let bytes = compress(&file)?;
+    let info = BackupInfo {
+        format_version: file.format_version,
+        created_at: file.created_at,
+        schema_version: snapshot.schema_version,
+        users: snapshot.users.len(),
+        bytes: bytes.len() as u64,
+    };
+    Ok((bytes, info))
+}
+
+/// 解码并校验备份文件内容
+pub fn decode(bytes: &[u8]) -> Result<(Snapshot, BackupInfo)> {
+    let file = decompress(bytes)?;
+    if file.format != BACKUP_FORMAT {
+        return Err(BackupError::InvalidFormat(format!("未知格式 {}", file.format)).into());
+    }
+    if file.format_version == 0 || file.format_version > BACKUP_FORMAT_VERSION {
+        return Err(BackupError::UnsupportedVersion {
+            found: file.format_version,
+            supported: BACKUP_FORMAT_VERSION,
+        }
+        .into());
+    }
+    let actual = checksum(file.payload.as_bytes());
+    if actual != file.checksum {
+        return Err(BackupError::ChecksumMismatch {
+            expected: file.checksum,
+            actual,
+        }
+        .into());
+    }
+
+    let snapshot: Snapshot = serde_json::from_str(&file.payload)
+        .map_err(|e| BackupError::InvalidFormat(format!("快照内容无法解析: {}", e)))?;
+    let info = BackupInfo {
+        format_version: file.format_version,
+        created_at: file.created_at,
+        schema_version: snapshot.schema_version,
+        users: snapshot.users.len(),
+        bytes: bytes.len() as u64,
+    };
+    Ok((snapshot, info))
+}
+
+/// 把快照写入备份文件，先写临时文件再重命名
+pub async fn write_backup(path: &Path, snapshot: &Snapshot) -> Result<BackupInfo> {
+    let (bytes, info) = encode(snapshot)?;
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let temp_path = path.with_extension("tmp");
+    tokio::fs::write(&temp_path, bytes).await?;
+    tokio::fs::rename(&temp_path, path).await?;
+    Ok(info)
+}
+
+/// 读取并校验备份文件
+pub async fn read_backup(path: &Path) -> Result<(Snapshot, BackupInfo)> {
+    let bytes = tokio::fs::read(path)
+        .await
+        .with_context(|| format!("无法读取备份文件 {}", path.display()))?;
+    decode(&bytes).with_context(|| format!("备份文件 {} 无法恢复", path.display()))
+}
+
+/// 备份和恢复示例
+pub async fn backup_example() -> Result<()> {
+    println!("\n=== 数据库备份和恢复示例 ===");
+
+    let path = std::env::temp_dir().join(format!("august_backup_{}.json.gz", std::process::id()));
+    let db = AsyncDatabase::new();
+    for i in 1..=100 {
+        db.create_user(User {
+            id: i.to_string(),
+            name: format!("用户{}", i),
+            email: format!("user{}@example.com", i),
+            created_at: 1234567890,
+            version: 0,
+        })
+        .await?;
+    }
+
+    let info = db.export_snapshot(&path).await?;
+    print!("导出: ");
+    info.print();
+
+    let restored = AsyncDatabase::new();
+    let info = restored.import_snapshot(&path).await?;
+    print!("导入: ");
+    info.print();
+    println!("恢复后用户 42: {:?}", restored.find_user("42").await?.map(|user| user.name));
+
+    // 篡改内容后校验失败
+    let mut bytes = tokio::fs::read(&path).await?;
+    let last = bytes.len() - 12;
+    bytes[last] ^= 0xff;
+    tokio::fs::write(&path, bytes).await?;
+    match restored.import_snapshot(&path).await {
+        Ok(_) => println!("损坏的备份竟然被接受了"),
+        Err(e) => println!("拒绝损坏的备份: {:#}", e),
+    }
+
+    let _ = tokio::fs::remove_file(&path).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn snapshot() -> Snapshot {
+        let users: HashMap<String, User> = (1..=3)
+            .map(|i| {
+                let user = User {
+                    id: i.to_string(),
+                    name: format!("用户{}", i),
+                    email: format!("user{}@example.com", i),
+                    created_at: 0,
+                    version: i,
+                };
+                (user.id.clone(), user)
+            })
+            .collect();
+        Snapshot { schema_version: 2, users, ..Default::default() }
+    }
+
+    fn temp_backup(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("august-code-backup-{}-{}.json.gz", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let (bytes, written) = encode(&snapshot()).unwrap();
+        let (decoded, read) = decode(&bytes).unwrap();
+        assert_eq!(decoded, snapshot());
+        assert_eq!(written, read);
+        assert_eq!(read.users, 3);
+    }
+
+    #[test]
+    fn test_decode_rejects_tampered_or_unsupported_files() {
+        let mut file = decompress(&encode(&snapshot()).unwrap().0).unwrap();
+        file.payload = file.payload.replace("用户1", "用户X");
+        let error = decode(&compress(&file).unwrap()).unwrap_err();
+        assert!(matches!(error.downcast_ref(), Some(BackupError::ChecksumMismatch { .. })));
+
+        let mut file = decompress(&encode(&snapshot()).unwrap().0).unwrap();
+        file.format_version = BACKUP_FORMAT_VERSION + 1;
+        let error = decode(&compress(&file).unwrap()).unwrap_err();
+        assert!(matches!(error.downcast_ref(), Some(BackupError::UnsupportedVersion { .. })));
+
+        let error = decode(b"not gzip").unwrap_err();
+        assert!(matches!(error.downcast_ref(), Some(BackupError::InvalidFormat(_))));
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import_snapshot() {
+        let path = temp_backup("round-trip");
+        let source = AsyncDatabase::new();
+        for user in snapshot().users.into_values() {
+            source.create_user(user).await.unwrap();
+        }
+        source.export_snapshot(&path).await.unwrap();
+
+        let target = AsyncDatabase::new();
+        target.create_user(snapshot().users["1"].clone()).await.unwrap();
+        target
+            .create_user(User {
+                id: "stale".to_string(),
+                name: "旧数据".to_string(),
+                email: "stale@example.com".to_string(),
+                created_at: 0,
+                version: 0,
+            })
+            .await
+            .unwrap();
+
+        let info = target.import_snapshot(&path).await.unwrap();
+        assert_eq!(info.users, 3);
+        assert!(target.find_user("stale").await.unwrap().is_none());
+        assert!(target.find_user("3").await.unwrap().is_some());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_keeps_expiry_and_skips_expired_records() {
+        let path = temp_backup("ttl");
+        let users = snapshot().users;
+        let source = AsyncDatabase::new();
+        source.create_user(users["1"].clone()).await.unwrap();
+        source.create_user_with_ttl(users["2"].clone(), Duration::from_millis(150)).await.unwrap();
+        source.create_user_with_ttl(users["3"].clone(), Duration::from_millis(1)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let info = source.export_snapshot(&path).await.unwrap();
+        assert_eq!(info.users, 2);
+
+        let target = AsyncDatabase::new();
+        target.import_snapshot(&path).await.unwrap();
+        assert!(target.find_user("3").await.unwrap().is_none());
+        assert!(target.expires_at("1").await.is_none());
+        assert!(target.expires_at("2").await.is_some());
+        assert!(target.find_user("2").await.unwrap().is_some());
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(target.find_user("2").await.unwrap().is_none());
+        assert_eq!(target.reap_expired().await, 1);
+        assert!(target.find_user("1").await.unwrap().is_some());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_import_leaves_data_untouched() {
+        let path = temp_backup("corrupt");
+        std::fs::write(&path, b"corrupt").unwrap();
+
+        let db = AsyncDatabase::new();
+        db.create_user(snapshot().users["1"].clone()).await.unwrap();
+        assert!(db.import_snapshot(&path).await.is_err());
+        assert!(db.find_user("1").await.unwrap().is_some());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,186 @@
+//! 网络连通性检测和离线模拟模块
+//!
+//! 启动时探测外部网络是否可达，不可达时切换到离线模式：
+//! - `detect_offline_mode` 探测连通性并设置全局离线标志
+//! - 离线模式下 `AsyncHttpClient` 和 `AsyncWebServer` 访问外部地址时改用 `MockHttpClient`
+//! - `MockHttpClient` 模拟 httpbin 的常用路径，示例无需修改即可运行
+
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::net::TcpStream;
+
+/// 探测连通性时连接的地址
+pub const PROBE_ADDR: &str = "httpbin.org:443";
+
+/// 强制离线模式的环境变量，取值 1 或 true 时不再探测
+pub const OFFLINE_ENV: &str = "AUGUST_OFFLINE";
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// 当前是否处于离线模式
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::SeqCst)
+}
+
+/// 手动设置离线模式
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::SeqCst);
+}
+
+/// 在超时时间内尝试建立 TCP 连接，判断地址是否可达
+pub async fn probe(addr: &str, timeout: Duration) -> bool {
+    matches!(tokio::time::timeout(timeout, TcpStream::connect(addr)).await, Ok(Ok(_)))
+}
+
+/// 探测网络并设置离线模式，返回是否离线
+///
+/// 设置了 `AUGUST_OFFLINE=1` 时直接进入离线模式，不再探测。
+pub async fn detect_offline_mode() -> bool {
+    let forced = std::env::var(OFFLINE_ENV)
+        .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    let offline = forced || !probe(PROBE_ADDR, Duration::from_secs(2)).await;
+    set_offline(offline);
+
+    if forced {
+        println!("已通过 {} 强制进入离线模式，HTTP 请求将使用模拟客户端", OFFLINE_ENV);
+    } else if offline {
+        println!("无法连接 {}，进入离线模式，HTTP 请求将使用模拟客户端", PROBE_ADDR);
+    }
+    offline
+}
+
+/// 是否为本机地址，本机地址在离线模式下仍然可以直接访问
+pub fn is_local_url(url: &str) -> bool {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = without_scheme.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = host.rsplit_once(':').map_or(host, |(host, _)| host);
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+/// 模拟响应
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// 模拟 HTTP 客户端，按 httpbin 的约定生成响应
+///
+/// - `/status/{code}` 返回对应状态码
+/// - `/delay/{n}` 等待 n 秒（最多 10 秒）后返回
+/// - 其他路径返回包含请求 URL 的 JSON
+#[derive(Debug, Clone)]
+pub struct MockHttpClient {
+    latency: Duration,
+}
+
+impl MockHttpClient {
+    /// 创建模拟客户端，每个请求有 20ms 的模拟延迟
+    pub fn new() -> Self {
+        Self::with_latency(Duration::from_millis(20))
+    }
+
+    /// 创建指定模拟延迟的客户端
+    pub fn with_latency(latency: Duration) -> Self {
+        Self { latency }
+    }
+
+    /// 模拟 GET 请求
+    pub async fn get(&self, url: &str) -> Result<MockResponse> {
+        let path = url_path(url);
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+        let status = match segments.as_slice() {
+            ["status", code] => code
+                .parse::<u16>()
+                .map_err(|_| anyhow::anyhow!("无效的状态码: {}", code))?,
+            ["delay", seconds] => {
+                let seconds = seconds
+                    .parse::<u64>()
+                    .map_err(|_| anyhow::anyhow!("无效的延迟: {}", seconds))?;
+                tokio::time::sleep(Duration::from_secs(seconds.min(10))).await;
+                200
+            }
+            _ => 200,
+        };
+        tokio::time::sleep(self.latency).await;
+
+        let body = if status == 200 {
+            serde_json::json!({ "url": url, "mock": true }).to_string()
+        } else {
+            String::new()
+        };
+        Ok(MockResponse { status, body })
+    }
+
+    /// 模拟 POST 请求，按 httpbin `/post` 的格式回显请求体
+    pub async fn post(&self, url: &str, body: &str) -> Result<MockResponse> {
+        tokio::time::sleep(self.latency).await;
+        let parsed = serde_json::from_str::<serde_json::Value>(body).unwrap_or(serde_json::Value::Null);
+        let body = serde_json::json!({ "url": url, "data": body, "json": parsed, "mock": true });
+        Ok(MockResponse { status: 200, body: body.to_string() })
+    }
+}
+
+impl Default for MockHttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 去掉协议、主机和查询参数，只保留路径
+fn url_path(url: &str) -> &str {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let path = without_scheme.find('/').map_or("/", |index| &without_scheme[index..]);
+    path.split(['?', '#']).next().unwrap_or("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_path() {
+        assert_eq!(url_path("https://httpbin.org/status/404"), "/status/404");
+        assert_eq!(url_path("https://httpbin.org"), "/");
+        assert_eq!(url_path("http://localhost:8080/get?a=1"), "/get");
+        assert_eq!(url_path("/delay/1"), "/delay/1");
+    }
+
+    #[test]
+    fn test_is_local_url() {
+        assert!(is_local_url("http://127.0.0.1:8080/get"));
+        assert!(is_local_url("http://localhost/get"));
+        assert!(is_local_url("http://[::1]:3000"));
+        assert!(!is_local_url("https://httpbin.org/get"));
+        assert!(!is_local_url("https://localhost.example.com/get"));
+    }
+
+    #[tokio::test]
+    async fn test_mock_client_emulates_httpbin() {
+        let client = MockHttpClient::with_latency(Duration::from_millis(1));
+
+        let response = client.get("https://httpbin.org/get").await.unwrap();
+        assert_eq!(response.status, 200);
+        let body: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(body["url"], "https://httpbin.org/get");
+
+        let response = client.get("https://httpbin.org/status/503").await.unwrap();
+        assert_eq!(response.status, 503);
+        assert!(response.body.is_empty());
+
+        assert!(client.get("https://httpbin.org/status/abc").await.is_err());
+
+        let response = client.post("https://httpbin.org/post", r#"{"a": 1}"#).await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(body["json"]["a"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_probe_unreachable_address() {
+        // 端口 0 不可连接
+        assert!(!probe("127.0.0.1:0", Duration::from_millis(200)).await);
+    }
+}
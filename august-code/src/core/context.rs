@@ -0,0 +1,241 @@
+//! 截止时间传播上下文模块
+//!
+//! `Ctx` 携带整体截止时间和取消令牌，沿调用链向下传递：
+//! - 子上下文的截止时间不会晚于父上下文
+//! - 取消父上下文会同时取消所有子上下文
+//! - `run` 在截止时间到达或被取消时提前结束操作
+
+use anyhow::Result;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use super::database::AsyncDatabase;
+use super::http_client::AsyncHttpClient;
+use super::scheduler::{AsyncTaskScheduler, TaskPriority};
+use crate::utils::test_server::TestServer;
+
+/// 上下文结束的原因
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum CtxError {
+    #[error("已超过截止时间")]
+    DeadlineExceeded,
+
+    #[error("操作已取消")]
+    Cancelled,
+}
+
+/// 携带截止时间和取消令牌的调用上下文
+#[derive(Debug, Clone)]
+pub struct Ctx {
+    deadline: Option<Instant>,
+    token: CancellationToken,
+}
+
+impl Ctx {
+    /// 没有截止时间的根上下文
+    pub fn background() -> Self {
+        Self {
+            deadline: None,
+            token: CancellationToken::new(),
+        }
+    }
+
+    /// 从现在起 `timeout` 后到期的根上下文
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            deadline: Some(Instant::now() + timeout),
+            token: CancellationToken::new(),
+        }
+    }
+
+    /// 派生子上下文，继承截止时间，随父上下文一起取消
+    pub fn child(&self) -> Self {
+        Self {
+            deadline: self.deadline,
+            token: self.token.child_token(),
+        }
+    }
+
+    /// 派生子上下文，截止时间取父上下文和 `timeout` 中较早的一个
+    pub fn child_with_timeout(&self, timeout: Duration) -> Self {
+        let deadline = Instant::now() + timeout;
+        Self {
+            deadline: Some(self.deadline.map_or(deadline, |parent| parent.min(deadline))),
+            token: self.token.child_token(),
+        }
+    }
+
+    /// 截止时间
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// 距截止时间的剩余时间，没有截止时间时返回 `None`
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// 取消当前上下文及其所有子上下文
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// 是否已被取消
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// 是否已超过截止时间
+    pub fn is_expired(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// 检查上下文是否仍然有效
+    pub fn check(&self) -> Result<(), CtxError> {
+        if self.is_cancelled() {
+            Err(CtxError::Cancelled)
+        } else if self.is_expired() {
+            Err(CtxError::DeadlineExceeded)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 在上下文约束下执行操作，截止时间到达或被取消时返回 `CtxError`
+    pub async fn run<F, T>(&self, operation: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        self.check()?;
+        let deadline = async {
+            match self.deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            result = operation => result,
+            _ = self.token.cancelled() => Err(CtxError::Cancelled.into()),
+            _ = deadline => Err(CtxError::DeadlineExceeded.into()),
+        }
+    }
+}
+
+impl Default for Ctx {
+    fn default() -> Self {
+        Self::background()
+    }
+}
+
+/// 截止时间传播示例：嵌套的 HTTP、数据库和调度调用共享同一个整体时限
+pub async fn context_example() -> Result<()> {
+    println!("\n=== 截止时间传播示例 ===");
+
+    let server = TestServer::start().await?;
+    let ctx = Ctx::with_timeout(Duration::from_millis(1500));
+    let client = AsyncHttpClient::new();
+    let database = AsyncDatabase::new();
+
+    // 数据库查询使用子上下文，仍受整体时限约束
+    let db_ctx = ctx.child_with_timeout(Duration::from_millis(500));
+    println!("子上下文的截止时间早于父上下文: {}", db_ctx.deadline() < ctx.deadline());
+    let connection = database.get_connection_ctx(&db_ctx).await?;
+    let users = connection.query_ctx(&db_ctx, "SELECT * FROM users").await?;
+    let missing = database.find_user_ctx(&db_ctx, "1").await?;
+    println!("数据库查询完成: {} 条记录，用户 1 {}，剩余时间 {:?}",
+        users.len(), if missing.is_some() { "存在" } else { "不存在" }, ctx.remaining());
+
+    // 慢请求超出整体时限，被提前结束
+    match client.fetch_url_ctx(&ctx, &server.url("/delay/3")).await {
+        Ok(response) => println!("HTTP 请求完成: {}", response.status),
+        Err(e) => println!("HTTP 请求提前结束: {}", e),
+    }
+
+    // 取消父上下文后，尚未执行的调度任务不会再运行
+    let scheduler = AsyncTaskScheduler::new();
+    let parent = Ctx::background();
+    let task_id = scheduler
+        .add_one_time_task_ctx(
+            &parent.child(),
+            "延迟任务",
+            Duration::from_millis(300),
+            || println!("这条消息不应该出现"),
+            TaskPriority::Normal,
+        )
+        .await?;
+    parent.cancel();
+    scheduler.wait_for_all().await;
+    if let Some(info) = scheduler.get_task_info(&task_id).await {
+        println!("任务 {} 状态: {:?}", info.name, info.status);
+    }
+
+    server.shutdown().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_child_deadline_never_exceeds_parent() {
+        let parent = Ctx::with_timeout(Duration::from_millis(100));
+        let longer = parent.child_with_timeout(Duration::from_secs(10));
+        let shorter = parent.child_with_timeout(Duration::from_millis(10));
+
+        assert_eq!(longer.deadline(), parent.deadline());
+        assert!(shorter.deadline() < parent.deadline());
+        assert!(Ctx::background().remaining().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_propagates_to_children_only() {
+        let parent = Ctx::background();
+        let child = parent.child();
+        let grandchild = child.child();
+
+        child.cancel();
+        assert!(!parent.is_cancelled());
+        assert!(grandchild.is_cancelled());
+        assert_eq!(grandchild.check(), Err(CtxError::Cancelled));
+
+        parent.cancel();
+        assert!(parent.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_at_deadline() {
+        let ctx = Ctx::with_timeout(Duration::from_millis(20));
+        let result = ctx
+            .run(async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(())
+            })
+            .await;
+        assert_eq!(result.unwrap_err().downcast_ref::<CtxError>(), Some(&CtxError::DeadlineExceeded));
+        assert!(ctx.is_expired());
+
+        let ctx = Ctx::with_timeout(Duration::from_secs(5));
+        assert_eq!(ctx.run(async { Ok(42) }).await.unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_when_cancelled() {
+        let ctx = Ctx::background();
+        let child = ctx.child();
+        let handle = tokio::spawn(async move {
+            child
+                .run(async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    Ok(())
+                })
+                .await
+        });
+        ctx.cancel();
+        let result = handle.await.unwrap();
+        assert_eq!(result.unwrap_err().downcast_ref::<CtxError>(), Some(&CtxError::Cancelled));
+    }
+}
@@ -0,0 +1,394 @@
+//! 礼貌爬取模块
+//!
+//! 在 `AsyncHttpClient` 之上实现一个负责任的并发爬虫：
+//! - 每个主机的请求之间至少间隔 `per_host_delay`，robots.txt 声明了更长的 Crawl-delay 时以它为准
+//! - 每个主机第一次访问前获取 robots.txt，跳过被禁止的路径
+//! - 按列表轮换 User-Agent
+//! - 按层广度优先抓取，同一层的页面并发请求，但受上面的主机间隔约束
+
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use reqwest::Url;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+use tokio::time::{Duration, Instant};
+
+use super::http_client::AsyncHttpClient;
+use super::web_server::normalize_url;
+use crate::utils::html;
+use crate::utils::test_server::TestServer;
+
+/// 爬取配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrawlConfig {
+    /// 同一主机两次请求之间的最小间隔
+    pub per_host_delay: Duration,
+    /// 是否获取并遵守 robots.txt
+    pub respect_robots: bool,
+    /// 在 robots.txt 中匹配规则组时使用的爬虫名称
+    pub robots_agent: String,
+    /// 轮换使用的 User-Agent，为空时使用客户端默认值
+    pub user_agents: Vec<String>,
+    /// 同时进行的请求数
+    pub concurrency: usize,
+    /// 最多抓取的页面数
+    pub max_pages: usize,
+    /// 只跟随与种子 URL 同主机的链接
+    pub same_host_only: bool,
+}
+
+impl Default for CrawlConfig {
+    fn default() -> Self {
+        Self {
+            per_host_delay: Duration::from_millis(500),
+            respect_robots: true,
+            robots_agent: "august-crawler".to_string(),
+            user_agents: vec![
+                "august-crawler/0.1 (+https://github.com/liangyingnan/rust-study)".to_string(),
+                "Mozilla/5.0 (compatible; august-crawler/0.1)".to_string(),
+            ],
+            concurrency: 4,
+            max_pages: 100,
+            same_host_only: true,
+        }
+    }
+}
+
+/// 某个爬虫适用的 robots.txt 规则
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RobotsRules {
+    /// (是否允许, 路径前缀)
+    rules: Vec<(bool, String)>,
+    pub crawl_delay: Option<Duration>,
+}
+
+impl RobotsRules {
+    /// 解析 robots.txt，选出名称匹配 `agent` 的规则组，没有时使用 `*` 组
+    pub fn parse(text: &str, agent: &str) -> Self {
+        struct Group {
+            agents: Vec<String>,
+            rules: RobotsRules,
+        }
+
+        let mut groups: Vec<Group> = Vec::new();
+        let mut collecting_agents = false;
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let (key, value) = (key.trim().to_ascii_lowercase(), value.trim());
+            match key.as_str() {
+                "user-agent" => {
+                    if !collecting_agents {
+                        groups.push(Group { agents: Vec::new(), rules: RobotsRules::default() });
+                        collecting_agents = true;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.agents.push(value.to_ascii_lowercase());
+                    }
+                }
+                "allow" | "disallow" => {
+                    collecting_agents = false;
+                    // 空的 Disallow 表示不限制
+                    if let (Some(group), false) = (groups.last_mut(), value.is_empty()) {
+                        group.rules.rules.push((key == "allow", value.to_string()));
+                    }
+                }
+                "crawl-delay" => {
+                    collecting_agents = false;
+                    if let (Some(group), Ok(secs)) = (groups.last_mut(), value.parse::<f64>()) {
+                        group.rules.crawl_delay = Some(Duration::from_secs_f64(secs.max(0.0)));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let agent = agent.to_ascii_lowercase();
+        let specific = groups
+            .iter()
+            .find(|group| group.agents.iter().any(|name| name != "*" && agent.contains(name.as_str())));
+        specific
+            .or_else(|| groups.iter().find(|group| group.agents.iter().any(|name| name == "*")))
+            .map(|group| group.rules.clone())
+            .unwrap_or_default()
+    }
+
+    /// 路径是否允许抓取：取最长匹配的规则，长度相同时 Allow 优先
+    pub fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(_, prefix)| path.starts_with(prefix.as_str()))
+            .max_by_key(|(allow, prefix)| (prefix.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+}
+
+fn host_of(url: &Url) -> String {
+    match url.port_or_known_default() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or(""), port),
+        None => url.host_str().unwrap_or("").to_string(),
+    }
+}
+
+/// 抓取到的页面
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrawledPage {
+    pub url: String,
+    pub depth: usize,
+    pub status: u16,
+    pub title: Option<String>,
+    pub links: Vec<String>,
+    pub user_agent: Option<String>,
+}
+
+/// 爬取结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CrawlReport {
+    pub pages: Vec<CrawledPage>,
+    /// 被 robots.txt 禁止而跳过的 URL
+    pub blocked_by_robots: Vec<String>,
+    /// 请求失败的 URL 和错误信息
+    pub failed: Vec<(String, String)>,
+}
+
+enum Outcome {
+    Page(CrawledPage),
+    Blocked(String),
+    Failed(String, String),
+}
+
+/// 礼貌爬虫
+pub struct Crawler {
+    client: AsyncHttpClient,
+    config: CrawlConfig,
+    robots: Mutex<HashMap<String, Arc<OnceCell<RobotsRules>>>>,
+    /// 每个主机下一次允许请求的时间
+    next_slot: Mutex<HashMap<String, Instant>>,
+    next_agent: AtomicUsize,
+}
+
+impl Crawler {
+    pub fn new(client: AsyncHttpClient, config: CrawlConfig) -> Self {
+        Self {
+            client,
+            config,
+            robots: Mutex::new(HashMap::new()),
+            next_slot: Mutex::new(HashMap::new()),
+            next_agent: AtomicUsize::new(0),
+        }
+    }
+
+    /// 从种子 URL 开始广度优先爬取，`depth` 为 0 时只抓取种子页面
+    pub async fn crawl(&self, seed_urls: &[String], depth: usize) -> Result<CrawlReport> {
+        let mut seeds = Vec::new();
+        for seed in seed_urls {
+            seeds.push(Url::parse(seed).map_err(|e| anyhow::anyhow!("无效的种子 URL {}: {}", seed, e))?);
+        }
+        let seed_hosts: HashSet<String> = seeds.iter().map(host_of).collect();
+
+        let mut report = CrawlReport::default();
+        let mut visited: HashSet<String> = HashSet::new();
+        let mut frontier: Vec<Url> = seeds.into_iter().filter(|url| visited.insert(normalize_url(url.as_str()))).collect();
+
+        for level in 0..=depth {
+            if frontier.is_empty() {
+                break;
+            }
+            let budget = self.config.max_pages.saturating_sub(report.pages.len());
+            frontier.truncate(budget);
+
+            let outcomes: Vec<Outcome> = stream::iter(frontier.drain(..))
+                .map(|url| self.visit(url, level))
+                .buffer_unordered(self.config.concurrency.max(1))
+                .collect()
+                .await;
+
+            for outcome in outcomes {
+                match outcome {
+                    Outcome::Page(page) => {
+                        if level < depth {
+                            for link in &page.links {
+                                let Ok(url) = Url::parse(link) else {
+                                    continue;
+                                };
+                                if self.config.same_host_only && !seed_hosts.contains(&host_of(&url)) {
+                                    continue;
+                                }
+                                if visited.insert(normalize_url(link)) {
+                                    frontier.push(url);
+                                }
+                            }
+                        }
+                        report.pages.push(page);
+                    }
+                    Outcome::Blocked(url) => report.blocked_by_robots.push(url),
+                    Outcome::Failed(url, error) => report.failed.push((url, error)),
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    async fn visit(&self, url: Url, depth: usize) -> Outcome {
+        let host = host_of(&url);
+        let rules = if self.config.respect_robots { Some(self.robots_for(&url).await) } else { None };
+        if rules.as_ref().is_some_and(|rules| !rules.allows(url.path())) {
+            return Outcome::Blocked(url.to_string());
+        }
+
+        let crawl_delay = rules.and_then(|rules| rules.crawl_delay).unwrap_or_default();
+        self.wait_turn(&host, self.config.per_host_delay.max(crawl_delay)).await;
+
+        let user_agent = self.next_user_agent();
+        let headers: Vec<(&str, &str)> = user_agent.iter().map(|ua| ("user-agent", ua.as_str())).collect();
+        match self.client.get_text_with_headers(url.as_str(), &headers).await {
+            Ok(response) => {
+                let (title, links) = if (200..300).contains(&response.status) {
+                    (html::extract_title(&response.body), html::extract_links(&response.body, &url))
+                } else {
+                    (None, Vec::new())
+                };
+                Outcome::Page(CrawledPage { url: url.to_string(), depth, status: response.status, title, links, user_agent })
+            }
+            Err(e) => Outcome::Failed(url.to_string(), e.to_string()),
+        }
+    }
+
+    /// 获取主机的 robots.txt 规则，每个主机只请求一次；获取失败或不存在时不限制
+    async fn robots_for(&self, url: &Url) -> RobotsRules {
+        let cell = {
+            let mut robots = self.robots.lock().unwrap();
+            robots.entry(host_of(url)).or_default().clone()
+        };
+        cell.get_or_init(|| async {
+            let Ok(robots_url) = url.join("/robots.txt") else {
+                return RobotsRules::default();
+            };
+            match self.client.get_text(robots_url.as_str()).await {
+                Ok(response) if response.status == 200 => RobotsRules::parse(&response.body, &self.config.robots_agent),
+                _ => RobotsRules::default(),
+            }
+        })
+        .await
+        .clone()
+    }
+
+    /// 占用主机的下一个请求时间段，并等到该时间
+    async fn wait_turn(&self, host: &str, delay: Duration) {
+        let at = {
+            let mut slots = self.next_slot.lock().unwrap();
+            let now = Instant::now();
+            let at = slots.get(host).copied().unwrap_or(now).max(now);
+            slots.insert(host.to_string(), at + delay);
+            at
+        };
+        tokio::time::sleep_until(at).await;
+    }
+
+    fn next_user_agent(&self) -> Option<String> {
+        if self.config.user_agents.is_empty() {
+            return None;
+        }
+        let index = self.next_agent.fetch_add(1, Ordering::Relaxed) % self.config.user_agents.len();
+        Some(self.config.user_agents[index].clone())
+    }
+}
+
+/// 爬取示例：礼貌地爬取内置测试服务器上的演示站点
+pub async fn crawl_example() -> Result<()> {
+    println!("\n=== 礼貌爬取示例 ===");
+
+    let server = TestServer::start().await?;
+    let config = CrawlConfig { per_host_delay: Duration::from_millis(50), ..Default::default() };
+    let crawler = Crawler::new(AsyncHttpClient::with_timeout(Duration::from_secs(5)), config);
+
+    let start = Instant::now();
+    let report = crawler.crawl(&[server.url("/site/index.html")], 2).await?;
+    for page in &report.pages {
+        println!(
+            "深度 {} [{}] {}（{}），{} 个链接，UA: {}",
+            page.depth,
+            page.status,
+            page.url,
+            page.title.as_deref().unwrap_or("无标题"),
+            page.links.len(),
+            page.user_agent.as_deref().unwrap_or("-")
+        );
+    }
+    for url in &report.blocked_by_robots {
+        println!("robots.txt 禁止: {}", url);
+    }
+    println!("抓取 {} 个页面，耗时 {:?}", report.pages.len(), start.elapsed());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_robots_group_selection_and_matching() {
+        let text = "\
+            User-agent: *\n\
+            Disallow: /\n\
+            \n\
+            User-agent: other-bot\n\
+            User-agent: august-crawler # 我们\n\
+            Disallow: /private\n\
+            Allow: /private/public\n\
+            Crawl-delay: 1.5\n";
+
+        let rules = RobotsRules::parse(text, "august-crawler");
+        assert!(rules.allows("/index.html"));
+        assert!(!rules.allows("/private/a"));
+        assert!(rules.allows("/private/public/a"));
+        assert_eq!(rules.crawl_delay, Some(Duration::from_millis(1500)));
+
+        let fallback = RobotsRules::parse(text, "someone-else");
+        assert!(!fallback.allows("/index.html"));
+        assert!(RobotsRules::parse("", "august-crawler").allows("/anything"));
+        assert!(RobotsRules::parse("User-agent: *\nDisallow:\n", "august-crawler").allows("/"));
+    }
+
+    #[tokio::test]
+    async fn test_crawl_respects_depth_robots_and_delay() {
+        let server = TestServer::start().await.unwrap();
+        let config = CrawlConfig {
+            per_host_delay: Duration::from_millis(30),
+            user_agents: vec!["ua-1".to_string(), "ua-2".to_string()],
+            ..Default::default()
+        };
+        let crawler = Crawler::new(AsyncHttpClient::new(), config);
+
+        let start = Instant::now();
+        let report = crawler.crawl(&[server.url("/site/index.html")], 2).await.unwrap();
+
+        let mut pages: Vec<&str> = report.pages.iter().map(|page| page.url.rsplit('/').next().unwrap()).collect();
+        pages.sort();
+        assert_eq!(pages, vec!["about.html", "blog.html", "contact.html", "index.html", "post-1.html", "post-2.html"]);
+        assert_eq!(report.blocked_by_robots, vec![server.url("/site/private/secret.html")]);
+        assert!(report.failed.is_empty());
+        let index = report.pages.iter().find(|page| page.depth == 0).unwrap();
+        assert_eq!(index.title.as_deref(), Some("首页"));
+
+        // 同一主机 6 个页面之间至少 5 个间隔
+        assert!(start.elapsed() >= Duration::from_millis(150), "{:?}", start.elapsed());
+        let agents: HashSet<_> = report.pages.iter().filter_map(|page| page.user_agent.as_deref()).collect();
+        assert_eq!(agents, HashSet::from(["ua-1", "ua-2"]));
+    }
+
+    #[tokio::test]
+    async fn test_crawl_max_pages_and_ignoring_robots() {
+        let server = TestServer::start().await.unwrap();
+        let config = CrawlConfig { per_host_delay: Duration::ZERO, respect_robots: false, max_pages: 3, ..Default::default() };
+        let crawler = Crawler::new(AsyncHttpClient::new(), config);
+
+        let report = crawler.crawl(&[server.url("/site/index.html")], 5).await.unwrap();
+        assert_eq!(report.pages.len(), 3);
+        assert!(report.blocked_by_robots.is_empty());
+    }
+}
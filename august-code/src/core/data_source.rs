@@ -0,0 +1,499 @@
+//! 异步数据源抽象模块
+//!
+//! 用 `async_trait` 定义统一的数据源接口，包括：
+//! - `DataSource` trait：单条获取、批量获取、健康检查
+//! - HTTP、数据库、文件系统三种实现
+//! - `MultiSource`：并发查询多个数据源并合并结果
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::future::join_all;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use super::database::AsyncDatabase;
+use super::http_client::AsyncHttpClient;
+use crate::utils::test_server::TestServer;
+
+/// 数据源返回的一条记录
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DataRecord {
+    pub source: String,
+    pub key: String,
+    pub payload: serde_json::Value,
+}
+
+/// 数据源健康状态
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceHealth {
+    pub source: String,
+    pub healthy: bool,
+    pub latency_ms: u64,
+    pub detail: Option<String>,
+}
+
+impl SourceHealth {
+    fn from_result(source: &str, start: Instant, result: Result<()>) -> Self {
+        Self {
+            source: source.to_string(),
+            healthy: result.is_ok(),
+            latency_ms: start.elapsed().as_millis() as u64,
+            detail: result.err().map(|e| e.to_string()),
+        }
+    }
+}
+
+/// 异步数据源
+#[async_trait]
+pub trait DataSource: Send + Sync {
+    /// 数据源名称，会写入返回的记录中
+    fn name(&self) -> &str;
+
+    /// 获取单条记录，不存在时返回 `None`
+    async fn fetch(&self, key: &str) -> Result<Option<DataRecord>>;
+
+    /// 批量获取，默认逐个调用 `fetch` 并跳过不存在的键
+    async fn fetch_batch(&self, keys: &[String]) -> Result<Vec<DataRecord>> {
+        let mut records = Vec::new();
+        for key in keys {
+            if let Some(record) = self.fetch(key).await? {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// 健康检查
+    async fn health(&self) -> SourceHealth;
+}
+
+/// 基于 `AsyncHttpClient` 的数据源：键拼接在基础 URL 之后
+pub struct HttpSource {
+    name: String,
+    base_url: String,
+    client: AsyncHttpClient,
+}
+
+impl HttpSource {
+    pub fn new(name: &str, base_url: &str, timeout: Duration) -> Self {
+        Self {
+            name: name.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: AsyncHttpClient::with_timeout(timeout),
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for HttpSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn fetch(&self, key: &str) -> Result<Option<DataRecord>> {
+        let url = format!("{}/{}", self.base_url, key);
+        let response = self.client.fetch_url(&url).await?;
+        match response.status {
+            404 => Ok(None),
+            status if status >= 400 => Err(anyhow::anyhow!("{} 返回状态码 {}", url, status)),
+            _ => Ok(Some(DataRecord {
+                source: self.name.clone(),
+                key: key.to_string(),
+                payload: serde_json::to_value(&response)?,
+            })),
+        }
+    }
+
+    /// 并发请求所有键
+    async fn fetch_batch(&self, keys: &[String]) -> Result<Vec<DataRecord>> {
+        let results = join_all(keys.iter().map(|key| self.fetch(key))).await;
+        let mut records = Vec::new();
+        for result in results {
+            if let Some(record) = result? {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    async fn health(&self) -> SourceHealth {
+        let start = Instant::now();
+        let result = match self.client.fetch_url(&self.base_url).await {
+            Ok(response) if response.status < 500 => Ok(()),
+            Ok(response) => Err(anyhow::anyhow!("状态码 {}", response.status)),
+            Err(e) => Err(e),
+        };
+        SourceHealth::from_result(&self.name, start, result)
+    }
+}
+
+/// 基于 `AsyncDatabase` 的数据源：键为用户 ID
+pub struct DatabaseSource {
+    name: String,
+    database: AsyncDatabase,
+}
+
+impl DatabaseSource {
+    pub fn new(name: &str, database: AsyncDatabase) -> Self {
+        Self {
+            name: name.to_string(),
+            database,
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for DatabaseSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn fetch(&self, key: &str) -> Result<Option<DataRecord>> {
+        match self.database.find_user(key).await? {
+            Some(user) => Ok(Some(DataRecord {
+                source: self.name.clone(),
+                key: key.to_string(),
+                payload: serde_json::to_value(&user)?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    async fn health(&self) -> SourceHealth {
+        let start = Instant::now();
+        let result = self.database.get_connection().await.map(|_| ());
+        SourceHealth::from_result(&self.name, start, result)
+    }
+}
+
+/// 基于文件系统的数据源：键为目录下的文件名
+///
+/// 文件内容是合法 JSON 时按 JSON 返回，否则作为字符串返回
+pub struct FileSource {
+    name: String,
+    dir: PathBuf,
+}
+
+impl FileSource {
+    pub fn new(name: &str, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.to_string(),
+            dir: dir.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for FileSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn fetch(&self, key: &str) -> Result<Option<DataRecord>> {
+        // 不允许通过键访问目录之外的文件
+        if key.contains(['/', '\\']) || key == ".." {
+            return Err(anyhow::anyhow!("非法的文件键: {}", key));
+        }
+        let content = match tokio::fs::read_to_string(self.dir.join(key)).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let payload = serde_json::from_str(&content)
+            .unwrap_or(serde_json::Value::String(content));
+        Ok(Some(DataRecord {
+            source: self.name.clone(),
+            key: key.to_string(),
+            payload,
+        }))
+    }
+
+    async fn health(&self) -> SourceHealth {
+        let start = Instant::now();
+        let result = match tokio::fs::metadata(&self.dir).await {
+            Ok(meta) if meta.is_dir() => Ok(()),
+            Ok(_) => Err(anyhow::anyhow!("{} 不是目录", self.dir.display())),
+            Err(e) => Err(e.into()),
+        };
+        SourceHealth::from_result(&self.name, start, result)
+    }
+}
+
+/// 多个数据源的查询结果：成功的记录与失败的数据源
+#[derive(Debug, Default)]
+pub struct MultiFetch {
+    pub records: Vec<DataRecord>,
+    /// (数据源名称, 错误信息)
+    pub errors: Vec<(String, String)>,
+}
+
+/// 并发查询多个数据源并合并结果
+///
+/// 数据源按添加顺序排定优先级；作为 `DataSource` 使用时，
+/// 同一个键只保留优先级最高的数据源返回的记录。
+pub struct MultiSource {
+    name: String,
+    sources: Vec<Arc<dyn DataSource>>,
+}
+
+impl MultiSource {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            sources: Vec::new(),
+        }
+    }
+
+    /// 添加数据源，先添加的优先级更高
+    pub fn with_source(mut self, source: Arc<dyn DataSource>) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// 从所有数据源获取同一个键，返回全部结果（按优先级排列）
+    pub async fn fetch_all(&self, key: &str) -> MultiFetch {
+        let results = join_all(self.sources.iter().map(|source| source.fetch(key))).await;
+        let mut merged = MultiFetch::default();
+        for (source, result) in self.sources.iter().zip(results) {
+            match result {
+                Ok(Some(record)) => merged.records.push(record),
+                Ok(None) => {}
+                Err(e) => merged.errors.push((source.name().to_string(), e.to_string())),
+            }
+        }
+        merged
+    }
+
+    /// 从所有数据源批量获取，返回全部结果（按优先级排列）
+    pub async fn fetch_batch_all(&self, keys: &[String]) -> MultiFetch {
+        let results = join_all(self.sources.iter().map(|source| source.fetch_batch(keys))).await;
+        let mut merged = MultiFetch::default();
+        for (source, result) in self.sources.iter().zip(results) {
+            match result {
+                Ok(records) => merged.records.extend(records),
+                Err(e) => merged.errors.push((source.name().to_string(), e.to_string())),
+            }
+        }
+        merged
+    }
+
+    /// 检查所有数据源的健康状态
+    pub async fn health_all(&self) -> Vec<SourceHealth> {
+        join_all(self.sources.iter().map(|source| source.health())).await
+    }
+}
+
+#[async_trait]
+impl DataSource for MultiSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 返回优先级最高的记录；所有数据源都失败时才返回错误
+    async fn fetch(&self, key: &str) -> Result<Option<DataRecord>> {
+        let merged = self.fetch_all(key).await;
+        if merged.records.is_empty() && merged.errors.len() == self.sources.len() && !merged.errors.is_empty() {
+            return Err(anyhow::anyhow!("所有数据源都失败: {:?}", merged.errors));
+        }
+        Ok(merged.records.into_iter().next())
+    }
+
+    /// 合并所有数据源的结果，同一个键保留优先级最高的记录
+    async fn fetch_batch(&self, keys: &[String]) -> Result<Vec<DataRecord>> {
+        let merged = self.fetch_batch_all(keys).await;
+        if merged.errors.len() == self.sources.len() && !merged.errors.is_empty() {
+            return Err(anyhow::anyhow!("所有数据源都失败: {:?}", merged.errors));
+        }
+        let mut seen = HashSet::new();
+        Ok(merged
+            .records
+            .into_iter()
+            .filter(|record| seen.insert(record.key.clone()))
+            .collect())
+    }
+
+    /// 至少一个数据源健康即视为健康
+    async fn health(&self) -> SourceHealth {
+        let start = Instant::now();
+        let checks = self.health_all().await;
+        let unhealthy: Vec<&str> = checks
+            .iter()
+            .filter(|check| !check.healthy)
+            .map(|check| check.source.as_str())
+            .collect();
+        SourceHealth {
+            source: self.name.clone(),
+            healthy: checks.iter().any(|check| check.healthy),
+            latency_ms: start.elapsed().as_millis() as u64,
+            detail: (!unhealthy.is_empty()).then(|| format!("不健康的数据源: {}", unhealthy.join(", "))),
+        }
+    }
+}
+
+/// 数据源抽象示例
+pub async fn data_source_example() -> Result<()> {
+    println!("\n=== 异步数据源抽象示例 ===");
+
+    let database = AsyncDatabase::new();
+    database.create_user(super::database::User {
+        id: "1".to_string(),
+        name: "张三".to_string(),
+        email: "zhangsan@example.com".to_string(),
+        created_at: 1234567890,
+        version: 0,
+    }).await?;
+
+    let server = TestServer::start().await?;
+    let dir = std::env::temp_dir().join("august-code-data-source");
+    tokio::fs::create_dir_all(&dir).await?;
+    tokio::fs::write(dir.join("1"), r#"{"note": "文件中的记录"}"#).await?;
+    tokio::fs::write(dir.join("2"), "纯文本内容").await?;
+
+    let multi = MultiSource::new("聚合")
+        .with_source(Arc::new(DatabaseSource::new("数据库", database)))
+        .with_source(Arc::new(FileSource::new("文件", &dir)))
+        .with_source(Arc::new(HttpSource::new("HTTP", &server.url("/anything"), Duration::from_secs(5))));
+
+    for health in multi.health_all().await {
+        println!("健康检查 {}: {} ({}ms) {:?}", health.source, health.healthy, health.latency_ms, health.detail);
+    }
+
+    let merged = multi.fetch_all("1").await;
+    for record in &merged.records {
+        println!("[{}] {} => {}", record.source, record.key, record.payload);
+    }
+    for (source, error) in &merged.errors {
+        println!("[{}] 失败: {}", source, error);
+    }
+
+    // MultiSource 本身也是 DataSource，可以和单个数据源一样使用
+    let sources: Vec<Box<dyn DataSource>> = vec![
+        Box::new(FileSource::new("文件", &dir)),
+        Box::new(multi),
+    ];
+    let keys = vec!["1".to_string(), "2".to_string(), "3".to_string()];
+    for source in &sources {
+        match source.fetch_batch(&keys).await {
+            Ok(records) => {
+                let found: Vec<String> = records.iter().map(|r| format!("{}@{}", r.key, r.source)).collect();
+                println!("{} 批量获取: {:?}", source.name(), found);
+            }
+            Err(e) => println!("{} 批量获取失败: {}", source.name(), e),
+        }
+    }
+
+    tokio::fs::remove_dir_all(&dir).await?;
+    server.shutdown().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::database::User;
+
+    /// 总是失败的数据源
+    struct FailingSource;
+
+    #[async_trait]
+    impl DataSource for FailingSource {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        async fn fetch(&self, _key: &str) -> Result<Option<DataRecord>> {
+            Err(anyhow::anyhow!("不可用"))
+        }
+
+        async fn health(&self) -> SourceHealth {
+            SourceHealth::from_result("failing", Instant::now(), Err(anyhow::anyhow!("不可用")))
+        }
+    }
+
+    async fn database_with_user(id: &str) -> AsyncDatabase {
+        let database = AsyncDatabase::new();
+        database.create_user(User {
+            id: id.to_string(),
+            name: "测试".to_string(),
+            email: "test@example.com".to_string(),
+            created_at: 0,
+            version: 0,
+        }).await.unwrap();
+        database
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("august-code-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_database_and_file_sources() {
+        let db = DatabaseSource::new("db", database_with_user("1").await);
+        let record = db.fetch("1").await.unwrap().unwrap();
+        assert_eq!(record.payload["name"], "测试");
+        assert!(db.fetch("2").await.unwrap().is_none());
+        assert!(db.health().await.healthy);
+
+        let dir = temp_dir("file-source");
+        std::fs::write(dir.join("a"), r#"{"n": 1}"#).unwrap();
+        std::fs::write(dir.join("b"), "text").unwrap();
+        let files = FileSource::new("files", &dir);
+        let keys = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let records = files.fetch_batch(&keys).await.unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].payload["n"], 1);
+        assert_eq!(records[1].payload, "text");
+        assert!(files.fetch("../a").await.is_err());
+        assert!(files.health().await.healthy);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(!files.health().await.healthy);
+    }
+
+    #[tokio::test]
+    async fn test_multi_source_merges_by_priority() {
+        let dir = temp_dir("multi-source");
+        std::fs::write(dir.join("1"), "from file").unwrap();
+        std::fs::write(dir.join("2"), "from file").unwrap();
+
+        let multi = MultiSource::new("multi")
+            .with_source(Arc::new(DatabaseSource::new("db", database_with_user("1").await)))
+            .with_source(Arc::new(FailingSource))
+            .with_source(Arc::new(FileSource::new("files", &dir)));
+
+        let merged = multi.fetch_all("1").await;
+        assert_eq!(merged.records.len(), 2);
+        assert_eq!(merged.errors, vec![("failing".to_string(), "不可用".to_string())]);
+
+        assert_eq!(multi.fetch("1").await.unwrap().unwrap().source, "db");
+        assert_eq!(multi.fetch("2").await.unwrap().unwrap().source, "files");
+        assert!(multi.fetch("3").await.unwrap().is_none());
+
+        let keys: Vec<String> = ["1", "2", "3"].iter().map(|k| k.to_string()).collect();
+        let records = multi.fetch_batch(&keys).await.unwrap();
+        let found: Vec<(&str, &str)> = records.iter().map(|r| (r.key.as_str(), r.source.as_str())).collect();
+        assert_eq!(found, vec![("1", "db"), ("2", "files")]);
+
+        let health = multi.health().await;
+        assert!(health.healthy);
+        assert_eq!(health.detail.as_deref(), Some("不健康的数据源: failing"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_multi_source_fails_when_every_source_fails() {
+        let multi = MultiSource::new("multi")
+            .with_source(Arc::new(FailingSource))
+            .with_source(Arc::new(FailingSource));
+        assert!(multi.fetch("1").await.is_err());
+        assert!(multi.fetch_batch(&["1".to_string()]).await.is_err());
+        assert!(!multi.health().await.healthy);
+    }
+}
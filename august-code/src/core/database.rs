@@ -5,21 +5,160 @@
 //! - 事务处理
 //! - 批量操作
 //! - 连接池管理
+//! - 截止时间传播
+//! - 结构版本和迁移
+//! - 基于游标的分页查询
+//! - 可过期记录和后台清理任务
+//! - 变更事件（CDC）订阅
+//! - 预写日志持久化和快照压缩
+//! - 基于版本号的乐观锁
+//! - 一次加锁的批量插入
+//! - 压缩备份的导出和导入
+//! - 有上限且先到先得的连接池
+//!
+//! 旧的 `async_db` 模块只是本模块的重新导出，已弃用。
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::future::Future;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, OwnedSemaphorePermit, RwLock, Semaphore};
+use tokio::task::JoinHandle;
 use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use super::backup::{self, BackupInfo};
+use super::context::Ctx;
+use super::migrations::{MigrationReport, Migrator};
+use super::wal::{Snapshot, WalEntry, WriteAheadLog};
 
 /// 用户实体
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct User {
     pub id: String,
     pub name: String,
     pub email: String,
     pub created_at: u64,
+    /// 乐观锁版本号，首次创建时为 0，每次更新或重新创建加 1
+    #[serde(default)]
+    pub version: u64,
+}
+
+/// 数据库错误
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum DatabaseError {
+    #[error("用户不存在: {0}")]
+    NotFound(String),
+
+    #[error("用户 {id} 的版本冲突：期望版本 {expected}，当前版本 {actual}")]
+    Conflict { id: String, expected: u64, actual: u64 },
+
+    #[error("等待 {waited:?} 后仍未获取到连接（上限 {max_connections} 个）")]
+    PoolTimeout { max_connections: usize, waited: Duration },
+}
+
+/// 批量插入模式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BulkMode {
+    /// 任意一条校验失败则全部不插入
+    AllOrNothing,
+    /// 插入校验通过的记录，逐条报告失败原因
+    PerItem,
+}
+
+/// 批量插入中失败的一条记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkFailure {
+    /// 在输入中的下标
+    pub index: usize,
+    pub id: String,
+    pub reason: String,
+}
+
+/// 批量插入结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BulkInsertReport {
+    pub inserted: usize,
+    pub failures: Vec<BulkFailure>,
+}
+
+/// 删除原因
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeleteReason {
+    /// 调用 `delete_user` 删除
+    Explicit,
+    /// 到期后被清理任务删除
+    Expired,
+}
+
+/// 数据变更事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeEvent {
+    Created(User),
+    Updated(User),
+    Deleted { id: String, reason: DeleteReason },
+}
+
+/// 变更事件通道容量，订阅者落后太多时会丢失最早的事件
+const CHANGE_CHANNEL_CAPACITY: usize = 256;
+
+/// 未指定 `LIMIT` 时单次查询返回的最大行数
+pub const DEFAULT_QUERY_LIMIT: usize = 100;
+
+/// 分页查询结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// 下一页的游标，没有更多数据时为 `None`
+    pub next_cursor: Option<String>,
+}
+
+/// 连接池默认上限，与 `DatabaseConfig` 的默认值一致
+pub const DEFAULT_MAX_CONNECTIONS: usize = 10;
+
+/// `get_connection` 默认的最长等待时间
+pub const DEFAULT_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 游标格式版本前缀
+const CURSOR_PREFIX: &str = "u1:";
+
+/// 把最后一条记录的 id 编码为不透明游标
+fn encode_cursor(last_id: &str) -> String {
+    format!("{}{}", CURSOR_PREFIX, last_id)
+        .bytes()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// 解码游标，返回最后一条记录的 id
+fn decode_cursor(cursor: &str) -> Result<String> {
+    let invalid = || anyhow::anyhow!("无效的游标: {}", cursor);
+    if !cursor.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    let bytes = (0..cursor.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&cursor[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| invalid())?;
+    let decoded = String::from_utf8(bytes).map_err(|_| invalid())?;
+    decoded
+        .strip_prefix(CURSOR_PREFIX)
+        .map(str::to_string)
+        .ok_or_else(invalid)
+}
+
+/// 从 SQL 中提取 `LIMIT n`
+fn parse_limit(sql: &str) -> Option<usize> {
+    let words: Vec<&str> = sql.split_whitespace().collect();
+    words
+        .windows(2)
+        .find(|pair| pair[0].eq_ignore_ascii_case("limit"))
+        .and_then(|pair| pair[1].trim_end_matches(';').parse().ok())
 }
 
 /// 数据库操作类型
@@ -34,14 +173,133 @@ pub enum DatabaseOperation {
 #[derive(Debug, Clone)]
 pub struct AsyncDatabase {
     data: Arc<RwLock<HashMap<String, User>>>,
-    connection_pool: Arc<RwLock<Vec<Connection>>>,
+    connection_pool: Arc<PoolState>,
+    /// 已应用的最高迁移版本，0 表示尚未迁移
+    schema_version: Arc<RwLock<u32>>,
+    /// 记录的过期时间，没有条目的记录永不过期
+    expirations: Arc<RwLock<HashMap<String, Instant>>>,
+    changes: broadcast::Sender<ChangeEvent>,
+    /// 预写日志，只有通过 `recover` 打开的数据库才会持久化
+    wal: Option<Arc<WriteAheadLog>>,
 }
 
 #[derive(Debug, Clone)]
 struct Connection {
     id: String,
-    created_at: Instant,
-    is_active: bool,
+}
+
+/// 连接池统计
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PoolStats {
+    pub max_connections: usize,
+    /// 已创建的连接数，不会超过 `max_connections`
+    pub open: usize,
+    pub idle: usize,
+    pub in_use: usize,
+    /// 正在排队等待的请求数
+    pub waiting: usize,
+    pub acquired: u64,
+    pub timeouts: u64,
+    /// 成功获取连接前的累计等待时间
+    pub total_wait: Duration,
+    pub max_wait: Duration,
+}
+
+impl PoolStats {
+    /// 平均等待时间
+    pub fn average_wait(&self) -> Duration {
+        if self.acquired == 0 {
+            Duration::ZERO
+        } else {
+            self.total_wait.div_f64(self.acquired as f64)
+        }
+    }
+}
+
+/// 连接池的内部状态
+///
+/// 信号量许可数等于连接上限，持有许可才能取出或创建连接；
+/// tokio 的信号量按请求顺序分配许可，所以等待者先到先得。
+#[derive(Debug)]
+struct PoolState {
+    max_connections: usize,
+    acquire_timeout: Duration,
+    permits: Arc<Semaphore>,
+    idle: Mutex<Vec<Connection>>,
+    stats: Mutex<PoolStats>,
+}
+
+/// 离开作用域时把等待计数减 1，获取连接的 future 被取消时也会执行
+struct WaitingGuard<'a>(&'a Mutex<PoolStats>);
+
+impl Drop for WaitingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.lock().unwrap().waiting -= 1;
+    }
+}
+
+impl PoolState {
+    fn new(max_connections: usize, acquire_timeout: Duration) -> Self {
+        let max_connections = max_connections.max(1);
+        Self {
+            max_connections,
+            acquire_timeout,
+            permits: Arc::new(Semaphore::new(max_connections)),
+            idle: Mutex::new(Vec::new()),
+            stats: Mutex::new(PoolStats {
+                max_connections,
+                ..Default::default()
+            }),
+        }
+    }
+
+    async fn acquire(&self, timeout: Duration) -> Result<(Connection, OwnedSemaphorePermit)> {
+        let start = Instant::now();
+        self.stats.lock().unwrap().waiting += 1;
+        let guard = WaitingGuard(&self.stats);
+        let permit = tokio::time::timeout(timeout, self.permits.clone().acquire_owned()).await;
+        drop(guard);
+
+        let waited = start.elapsed();
+        let mut stats = self.stats.lock().unwrap();
+        let permit = match permit {
+            Ok(permit) => permit.expect("连接池的信号量不会被关闭"),
+            Err(_) => {
+                stats.timeouts += 1;
+                return Err(DatabaseError::PoolTimeout {
+                    max_connections: self.max_connections,
+                    waited,
+                }
+                .into());
+            }
+        };
+        stats.acquired += 1;
+        stats.total_wait += waited;
+        stats.max_wait = stats.max_wait.max(waited);
+
+        // 持有许可时空闲连接数 + 使用中连接数 < 上限，需要时可以安全地创建新连接
+        let connection = match self.idle.lock().unwrap().pop() {
+            Some(connection) => connection,
+            None => {
+                stats.open += 1;
+                Connection {
+                    id: format!("conn_{}", stats.open),
+                }
+            }
+        };
+        Ok((connection, permit))
+    }
+
+    fn release(&self, connection: Connection) {
+        self.idle.lock().unwrap().push(connection);
+    }
+
+    fn stats(&self) -> PoolStats {
+        let mut stats = self.stats.lock().unwrap().clone();
+        stats.idle = self.idle.lock().unwrap().len();
+        stats.in_use = self.max_connections - self.permits.available_permits();
+        stats
+    }
 }
 
 impl AsyncDatabase {
@@ -49,83 +307,518 @@ impl AsyncDatabase {
     pub fn new() -> Self {
         Self {
             data: Arc::new(RwLock::new(HashMap::new())),
-            connection_pool: Arc::new(RwLock::new(Vec::new())),
+            connection_pool: Arc::new(PoolState::new(DEFAULT_MAX_CONNECTIONS, DEFAULT_ACQUIRE_TIMEOUT)),
+            schema_version: Arc::new(RwLock::new(0)),
+            expirations: Arc::new(RwLock::new(HashMap::new())),
+            changes: broadcast::channel(CHANGE_CHANNEL_CAPACITY).0,
+            wal: None,
         }
     }
     
-    /// 异步获取数据库连接
-    pub async fn get_connection(&self) -> Result<DatabaseConnection> {
-        let mut pool = self.connection_pool.write().await;
-        
-        // 查找可用连接
-        for conn in pool.iter_mut() {
-            if conn.is_active {
-                conn.is_active = false; // 标记为使用中
-                return Ok(DatabaseConnection {
-                    id: conn.id.clone(),
-                    database: self.clone(),
-                });
-            }
+    /// 设置连接池上限和 `get_connection` 的最长等待时间，上限至少为 1
+    ///
+    /// 需要在获取连接之前调用，已借出的连接仍属于原来的连接池。
+    pub fn with_pool(mut self, max_connections: usize, acquire_timeout: Duration) -> Self {
+        self.connection_pool = Arc::new(PoolState::new(max_connections, acquire_timeout));
+        self
+    }
+    
+    /// 从预写日志恢复数据库，之后的写操作都会先记录到日志
+    ///
+    /// 依次加载快照、重放日志；日志中有内容时立即压缩一次，
+    /// 保证新的日志从干净的文件开始追加。
+    pub async fn recover(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let recovered = WriteAheadLog::load(path).await?;
+        let wal = Arc::new(WriteAheadLog::open(path).await?);
+        if recovered.replayed + recovered.skipped > 0 {
+            wal.compact(&recovered.snapshot).await?;
         }
         
-        // 创建新连接
-        let conn_id = format!("conn_{}", pool.len() + 1);
-        let connection = Connection {
-            id: conn_id.clone(),
-            created_at: Instant::now(),
-            is_active: false,
+        let mut db = Self::new();
+        *db.expirations.write().await = recovered
+            .snapshot
+            .expirations
+            .into_iter()
+            .filter_map(|(id, expires_at)| from_wall_clock(expires_at).map(|expires_at| (id, expires_at)))
+            .collect();
+        *db.data.write().await = recovered.snapshot.users;
+        *db.schema_version.write().await = recovered.snapshot.schema_version;
+        db.wal = Some(wal);
+        Ok(db)
+    }
+    
+    /// 是否启用了持久化
+    pub fn is_persistent(&self) -> bool {
+        self.wal.is_some()
+    }
+    
+    /// 把当前数据写入快照并清空日志，未启用持久化时什么都不做
+    pub async fn compact(&self) -> Result<()> {
+        let data = self.data.write().await;
+        let version = self.schema_version.read().await;
+        let expirations = self.expirations.read().await;
+        self.write_snapshot(&data, &expirations, *version).await
+    }
+    
+    /// 启动后台压缩任务，日志有新条目时每隔 `interval` 压缩一次
+    pub fn start_compaction(&self, interval: Duration) -> BackgroundHandle {
+        let database = self.clone();
+        spawn_periodic(interval, move || {
+            let database = database.clone();
+            async move {
+                let dirty = database.wal.as_ref().is_some_and(|wal| wal.entries_since_compaction() > 0);
+                if dirty {
+                    if let Err(e) = database.compact().await {
+                        eprintln!("压缩预写日志失败: {}", e);
+                    }
+                }
+            }
+        })
+    }
+    
+    async fn write_snapshot(
+        &self,
+        data: &HashMap<String, User>,
+        expirations: &HashMap<String, Instant>,
+        schema_version: u32,
+    ) -> Result<()> {
+        if let Some(wal) = &self.wal {
+            let snapshot = Snapshot {
+                schema_version,
+                users: data.clone(),
+                expirations: expirations
+                    .iter()
+                    .map(|(id, expires_at)| (id.clone(), to_wall_clock(*expires_at)))
+                    .collect(),
+            };
+            wal.compact(&snapshot).await?;
+        }
+        Ok(())
+    }
+    
+    /// 把当前数据导出为压缩备份文件
+    pub async fn export_snapshot(&self, path: impl AsRef<Path>) -> Result<BackupInfo> {
+        let snapshot = {
+            let data = self.data.read().await;
+            let version = self.schema_version.read().await;
+            let expirations = self.expirations.read().await;
+            let now = Instant::now();
+            // 已过期但还没被清理的记录不导出
+            Snapshot {
+                schema_version: *version,
+                users: data
+                    .iter()
+                    .filter(|(id, _)| !is_expired(&expirations, id, now))
+                    .map(|(id, user)| (id.clone(), user.clone()))
+                    .collect(),
+                expirations: expirations
+                    .iter()
+                    .filter(|(id, expires_at)| **expires_at > now && data.contains_key(*id))
+                    .map(|(id, expires_at)| (id.clone(), to_wall_clock(*expires_at)))
+                    .collect(),
+            }
         };
-        
-        pool.push(connection);
-        
+        backup::write_backup(path.as_ref(), &snapshot).await
+    }
+    
+    /// 从备份文件恢复，替换当前全部数据
+    ///
+    /// 备份校验通过后才会修改数据；备份中的过期时间一并恢复，恢复前已经过去的视为立即过期。
+    /// 启用持久化时立即写入快照，变更订阅者会收到删除和创建事件。
+    pub async fn import_snapshot(&self, path: impl AsRef<Path>) -> Result<BackupInfo> {
+        let (snapshot, info) = backup::read_backup(path.as_ref()).await?;
+        let restored: HashMap<String, Instant> = snapshot
+            .expirations
+            .into_iter()
+            .filter(|(id, _)| snapshot.users.contains_key(id))
+            .filter_map(|(id, expires_at)| from_wall_clock(expires_at).map(|expires_at| (id, expires_at)))
+            .collect();
+
+        let mut data = self.data.write().await;
+        let mut version = self.schema_version.write().await;
+        let mut expirations = self.expirations.write().await;
+        self.write_snapshot(&snapshot.users, &restored, snapshot.schema_version).await?;
+
+        let previous = std::mem::replace(&mut *data, snapshot.users);
+        *version = snapshot.schema_version;
+        *expirations = restored;
+        for id in previous.into_keys().filter(|id| !data.contains_key(id)) {
+            self.emit(ChangeEvent::Deleted {
+                id,
+                reason: DeleteReason::Explicit,
+            });
+        }
+        for user in data.values() {
+            self.emit(ChangeEvent::Created(user.clone()));
+        }
+        Ok(info)
+    }
+    
+    /// 先把操作写入预写日志，未启用持久化时什么都不做
+    async fn log(&self, entry: WalEntry) -> Result<()> {
+        match &self.wal {
+            Some(wal) => wal.append(&entry).await,
+            None => Ok(()),
+        }
+    }
+    
+    /// 订阅数据变更事件
+    pub fn subscribe_changes(&self) -> broadcast::Receiver<ChangeEvent> {
+        self.changes.subscribe()
+    }
+    
+    fn emit(&self, event: ChangeEvent) {
+        // 没有订阅者时发送失败，忽略即可
+        let _ = self.changes.send(event);
+    }
+    
+    /// 创建数据库并在启动时执行全部迁移
+    pub async fn open_with_migrations(migrator: &Migrator) -> Result<Self> {
+        let db = Self::new();
+        db.migrate(migrator, false).await?;
+        Ok(db)
+    }
+    
+    /// 当前结构版本
+    pub async fn schema_version(&self) -> u32 {
+        *self.schema_version.read().await
+    }
+    
+    /// 执行尚未应用的迁移，`dry_run` 为 true 时只报告待执行的步骤
+    ///
+    /// 迁移期间持有写锁，其他读写操作会等待迁移完成。
+    pub async fn migrate(&self, migrator: &Migrator, dry_run: bool) -> Result<MigrationReport> {
+        let mut data = self.data.write().await;
+        let mut version = self.schema_version.write().await;
+        let (report, error) = migrator.apply(&mut data, *version, dry_run);
+        if !dry_run {
+            *version = report.to_version;
+            // 迁移直接改写整张表，用快照持久化结果
+            if !report.applied.is_empty() {
+                let expirations = self.expirations.read().await;
+                self.write_snapshot(&data, &expirations, *version).await?;
+            }
+        }
+        match error {
+            Some(e) => Err(e),
+            None => Ok(report),
+        }
+    }
+    
+    /// 异步获取数据库连接
+    ///
+    /// 连接数达到上限时排队等待，超过连接池的默认等待时间后返回 `DatabaseError::PoolTimeout`
+    pub async fn get_connection(&self) -> Result<DatabaseConnection> {
+        self.acquire_timeout(self.connection_pool.acquire_timeout).await
+    }
+    
+    /// 获取数据库连接，最多等待 `timeout`
+    pub async fn acquire_timeout(&self, timeout: Duration) -> Result<DatabaseConnection> {
+        let (connection, permit) = self.connection_pool.acquire(timeout).await?;
         Ok(DatabaseConnection {
-            id: conn_id,
+            id: connection.id.clone(),
             database: self.clone(),
+            connection: Some(connection),
+            _permit: permit,
         })
     }
     
-    /// 释放连接
-    async fn release_connection(&self, conn_id: &str) {
-        let mut pool = self.connection_pool.write().await;
-        for conn in pool.iter_mut() {
-            if conn.id == conn_id {
-                conn.is_active = true;
-                break;
-            }
-        }
+    /// 连接池统计
+    pub fn pool_stats(&self) -> PoolStats {
+        self.connection_pool.stats()
+    }
+    
+    /// 在调用方上下文内获取数据库连接
+    pub async fn get_connection_ctx(&self, ctx: &Ctx) -> Result<DatabaseConnection> {
+        ctx.run(self.get_connection()).await
     }
     
-    /// 异步查询用户
+    
+    /// 异步查询用户，已过期但尚未被清理的记录视为不存在
     pub async fn find_user(&self, id: &str) -> Result<Option<User>> {
         let data = self.data.read().await;
+        let expirations = self.expirations.read().await;
+        if is_expired(&expirations, id, Instant::now()) {
+            return Ok(None);
+        }
         Ok(data.get(id).cloned())
     }
     
-    /// 异步创建用户
+    /// 按 id 升序分页列出用户
+    ///
+    /// `cursor` 为上一页返回的 `next_cursor`，第一页传 `None`。
+    /// 翻页期间新增或删除的用户不会导致已返回的用户重复出现。
+    pub async fn list_users(&self, limit: usize, cursor: Option<&str>) -> Result<Page<User>> {
+        if limit == 0 {
+            return Err(anyhow::anyhow!("分页大小必须大于 0"));
+        }
+        let after = cursor.map(decode_cursor).transpose()?;
+
+        let data = self.data.read().await;
+        let expirations = self.expirations.read().await;
+        let now = Instant::now();
+        let mut ids: Vec<&String> = data
+            .keys()
+            .filter(|id| match &after {
+                Some(after) => id.as_str() > after.as_str(),
+                None => true,
+            })
+            .filter(|id| !is_expired(&expirations, id, now))
+            .collect();
+        // 多取一条用于判断是否还有下一页
+        let has_more = ids.len() > limit;
+        if has_more {
+            ids.select_nth_unstable(limit);
+            ids.truncate(limit);
+        }
+        ids.sort();
+
+        let items: Vec<User> = ids.iter().map(|id| data[*id].clone()).collect();
+        let next_cursor = if has_more {
+            items.last().map(|user| encode_cursor(&user.id))
+        } else {
+            None
+        };
+        Ok(Page { items, next_cursor })
+    }
+    
+    /// 在调用方上下文内查询用户
+    pub async fn find_user_ctx(&self, ctx: &Ctx, id: &str) -> Result<Option<User>> {
+        ctx.run(self.find_user(id)).await
+    }
+    
+    /// 异步创建用户，同 id 的旧记录及其过期时间会被覆盖
+    ///
+    /// 覆盖已有记录时版本号在旧版本上加 1 而不是归零，
+    /// 持有旧版本的调用方不会因为重新创建而通过版本检查。
     pub async fn create_user(&self, user: User) -> Result<()> {
+        self.insert_with_expiry(user, None).await
+    }
+    
+    /// 创建 `ttl` 后过期的用户
+    pub async fn create_user_with_ttl(&self, user: User, ttl: Duration) -> Result<()> {
+        self.insert_with_expiry(user, Some(Instant::now() + ttl)).await
+    }
+    
+    async fn insert_with_expiry(&self, mut user: User, expires_at: Option<Instant>) -> Result<()> {
         let mut data = self.data.write().await;
-        data.insert(user.id.clone(), user);
+        let mut expirations = self.expirations.write().await;
+        user.version = data.get(&user.id).map_or(0, |current| current.version + 1);
+        self.log(WalEntry::Create {
+            user: user.clone(),
+            expires_at: expires_at.map(to_wall_clock),
+        })
+        .await?;
+        match expires_at {
+            Some(expires_at) => expirations.insert(user.id.clone(), expires_at),
+            None => expirations.remove(&user.id),
+        };
+        data.insert(user.id.clone(), user.clone());
+        self.emit(ChangeEvent::Created(user));
         Ok(())
     }
     
-    /// 异步更新用户
+    /// 设置或清除记录的过期时间，记录不存在时返回错误
+    pub async fn set_expiry(&self, id: &str, expires_at: Option<Instant>) -> Result<()> {
+        let data = self.data.read().await;
+        if !data.contains_key(id) {
+            return Err(DatabaseError::NotFound(id.to_string()).into());
+        }
+        let mut expirations = self.expirations.write().await;
+        self.log(WalEntry::Expire {
+            id: id.to_string(),
+            expires_at: expires_at.map(to_wall_clock),
+        })
+        .await?;
+        match expires_at {
+            Some(expires_at) => expirations.insert(id.to_string(), expires_at),
+            None => expirations.remove(id),
+        };
+        Ok(())
+    }
+    
+    /// 记录的过期时间，永不过期或不存在时返回 `None`
+    pub async fn expires_at(&self, id: &str) -> Option<Instant> {
+        self.expirations.read().await.get(id).copied()
+    }
+    
+    /// 异步更新用户，保留原有的过期时间
+    ///
+    /// `user.version` 必须等于当前存储的版本，否则返回 `DatabaseError::Conflict`，
+    /// 防止基于旧数据的更新覆盖别人的修改。
     pub async fn update_user(&self, user: User) -> Result<()> {
+        let id = user.id.clone();
+        let expected = user.version;
+        self.compare_and_swap(&id, expected, user).await.map(|_| ())
+    }
+    
+    /// 当前版本等于 `expected_version` 时用 `new` 替换用户，返回版本号加 1 后的用户
+    ///
+    /// 已过期但还没被清理的记录和查询时一样视为不存在。
+    pub async fn compare_and_swap(&self, id: &str, expected_version: u64, mut new: User) -> Result<User> {
         let mut data = self.data.write().await;
-        if data.contains_key(&user.id) {
-            data.insert(user.id.clone(), user);
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!("用户不存在"))
+        let expirations = self.expirations.read().await;
+        let current = data
+            .get(id)
+            .filter(|_| !is_expired(&expirations, id, Instant::now()))
+            .ok_or_else(|| DatabaseError::NotFound(id.to_string()))?;
+        if current.version != expected_version {
+            return Err(DatabaseError::Conflict {
+                id: id.to_string(),
+                expected: expected_version,
+                actual: current.version,
+            }
+            .into());
+        }
+        
+        new.id = id.to_string();
+        new.version = expected_version + 1;
+        self.log(WalEntry::Update { user: new.clone() }).await?;
+        data.insert(new.id.clone(), new.clone());
+        self.emit(ChangeEvent::Updated(new.clone()));
+        Ok(new)
+    }
+    
+    /// 读取-修改-写入，遇到版本冲突时重新读取并重试，最多尝试 `max_attempts` 次
+    pub async fn update_with<F>(&self, id: &str, max_attempts: u32, modify: F) -> Result<User>
+    where
+        F: Fn(&mut User),
+    {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let mut user = self
+                .find_user(id)
+                .await?
+                .ok_or_else(|| DatabaseError::NotFound(id.to_string()))?;
+            let expected = user.version;
+            modify(&mut user);
+            match self.compare_and_swap(id, expected, user).await {
+                Err(e) if attempt < max_attempts
+                    && matches!(e.downcast_ref::<DatabaseError>(), Some(DatabaseError::Conflict { .. })) =>
+                {
+                    tokio::task::yield_now().await;
+                }
+                result => return result,
+            }
         }
     }
     
     /// 异步删除用户
     pub async fn delete_user(&self, id: &str) -> Result<()> {
         let mut data = self.data.write().await;
-        data.remove(id);
+        let mut expirations = self.expirations.write().await;
+        if data.contains_key(id) {
+            self.log(WalEntry::Delete { id: id.to_string() }).await?;
+            data.remove(id);
+            self.emit(ChangeEvent::Deleted {
+                id: id.to_string(),
+                reason: DeleteReason::Explicit,
+            });
+        }
+        expirations.remove(id);
         Ok(())
     }
     
+    /// 清理一次已过期的记录，返回删除的数量
+    ///
+    /// 写入预写日志失败时停止本次清理，剩余记录留到下一次
+    pub async fn reap_expired(&self) -> usize {
+        let mut data = self.data.write().await;
+        let mut expirations = self.expirations.write().await;
+        let now = Instant::now();
+        let expired: Vec<String> = expirations
+            .iter()
+            .filter(|(_, expires_at)| **expires_at <= now)
+            .map(|(id, _)| id.clone())
+            .collect();
+        
+        let mut reaped = 0;
+        for id in expired {
+            if data.contains_key(&id) {
+                if let Err(e) = self.log(WalEntry::Delete { id: id.clone() }).await {
+                    eprintln!("记录过期删除失败: {}", e);
+                    break;
+                }
+                data.remove(&id);
+                self.emit(ChangeEvent::Deleted {
+                    id: id.clone(),
+                    reason: DeleteReason::Expired,
+                });
+            }
+            expirations.remove(&id);
+            reaped += 1;
+        }
+        reaped
+    }
+    
+    /// 启动后台清理任务，每隔 `sweep_interval` 清理一次过期记录
+    pub fn start_reaper(&self, sweep_interval: Duration) -> BackgroundHandle {
+        let database = self.clone();
+        spawn_periodic(sweep_interval, move || {
+            let database = database.clone();
+            async move {
+                database.reap_expired().await;
+            }
+        })
+    }
+    
+    /// 批量插入用户，整批只获取一次写锁、写一次预写日志
+    ///
+    /// 插入前先校验全部记录：id 不能为空、邮箱必须包含 `@`、
+    /// id 不能与已有用户或同批的其他记录重复。
+    pub async fn create_users_bulk(&self, users: Vec<User>, mode: BulkMode) -> Result<BulkInsertReport> {
+        let mut data = self.data.write().await;
+        let mut expirations = self.expirations.write().await;
+        
+        let mut seen = std::collections::HashSet::new();
+        let mut failures = Vec::new();
+        let mut valid = Vec::with_capacity(users.len());
+        for (index, mut user) in users.into_iter().enumerate() {
+            let reason = if user.id.is_empty() {
+                Some("id 不能为空".to_string())
+            } else if !user.email.contains('@') {
+                Some(format!("邮箱格式无效: {}", user.email))
+            } else if data.contains_key(&user.id) {
+                Some("用户已存在".to_string())
+            } else if !seen.insert(user.id.clone()) {
+                Some("同一批次中 id 重复".to_string())
+            } else {
+                None
+            };
+            match reason {
+                Some(reason) => failures.push(BulkFailure { index, id: user.id, reason }),
+                None => {
+                    user.version = 0;
+                    valid.push(user);
+                }
+            }
+        }
+        
+        if mode == BulkMode::AllOrNothing && !failures.is_empty() {
+            return Ok(BulkInsertReport { inserted: 0, failures });
+        }
+        
+        if let Some(wal) = &self.wal {
+            let entries: Vec<WalEntry> = valid
+                .iter()
+                .map(|user| WalEntry::Create {
+                    user: user.clone(),
+                    expires_at: None,
+                })
+                .collect();
+            wal.append_all(&entries).await?;
+        }
+        let inserted = valid.len();
+        for user in valid {
+            expirations.remove(&user.id);
+            data.insert(user.id.clone(), user.clone());
+            self.emit(ChangeEvent::Created(user));
+        }
+        Ok(BulkInsertReport { inserted, failures })
+    }
+    
     /// 异步批量操作
     pub async fn batch_operations(&self, operations: Vec<DatabaseOperation>) -> Result<Vec<Result<()>>> {
         let mut results = Vec::new();
@@ -155,23 +848,104 @@ impl AsyncDatabase {
     }
 }
 
-/// 数据库连接包装器
+/// 记录是否已过期
+fn is_expired(expirations: &HashMap<String, Instant>, id: &str, now: Instant) -> bool {
+    expirations.get(id).is_some_and(|expires_at| *expires_at <= now)
+}
+
+/// 把过期时刻换算成墙上时间，用于写入日志和快照
+fn to_wall_clock(expires_at: Instant) -> DateTime<Utc> {
+    let remaining = expires_at.saturating_duration_since(Instant::now());
+    chrono::Duration::from_std(remaining)
+        .ok()
+        .and_then(|remaining| Utc::now().checked_add_signed(remaining))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+/// 把墙上时间换算回过期时刻，已经过去的时间视为立即过期，太远的时间视为永不过期
+fn from_wall_clock(expires_at: DateTime<Utc>) -> Option<Instant> {
+    let remaining = (expires_at - Utc::now()).to_std().unwrap_or(Duration::ZERO);
+    Instant::now().checked_add(remaining)
+}
+
+/// 每隔 `period` 执行一次 `job`，第一次立即执行
+fn spawn_periodic<F, Fut>(period: Duration, job: F) -> BackgroundHandle
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let token = CancellationToken::new();
+    let stopped = token.clone();
+    let handle = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = stopped.cancelled() => break,
+                _ = interval.tick() => job().await,
+            }
+        }
+    });
+    BackgroundHandle {
+        token,
+        handle: Some(handle),
+    }
+}
+
+/// 后台清理或压缩任务的句柄，被丢弃时停止任务
+pub struct BackgroundHandle {
+    token: CancellationToken,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl BackgroundHandle {
+    /// 停止任务并等待其退出
+    pub async fn stop(mut self) {
+        self.token.cancel();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.await;
+        }
+    }
+}
+
+impl Drop for BackgroundHandle {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+/// 数据库连接包装器，离开作用域时归还连接池
 pub struct DatabaseConnection {
     id: String,
     database: AsyncDatabase,
+    connection: Option<Connection>,
+    /// 在连接放回空闲列表之后才释放许可
+    _permit: OwnedSemaphorePermit,
 }
 
 impl DatabaseConnection {
     /// 异步执行查询
+    ///
+    /// 按 id 升序返回第一页，页大小取 SQL 中的 `LIMIT`，未指定时为 `DEFAULT_QUERY_LIMIT`
     pub async fn query(&self, sql: &str) -> Result<Vec<User>> {
+        let limit = parse_limit(sql).unwrap_or(DEFAULT_QUERY_LIMIT);
+        Ok(self.query_page(sql, limit, None).await?.items)
+    }
+    
+    /// 异步执行分页查询，`cursor` 为上一页返回的 `next_cursor`
+    pub async fn query_page(&self, sql: &str, limit: usize, cursor: Option<&str>) -> Result<Page<User>> {
         // 模拟查询延迟
         tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
         
-        let data = self.database.data.read().await;
-        let users: Vec<User> = data.values().cloned().collect();
+        let page = self.database.list_users(limit, cursor).await?;
         
-        println!("连接 {} 执行查询: {}", self.id, sql);
-        Ok(users)
+        println!("连接 {} 执行查询: {}（返回 {} 条）", self.id, sql, page.items.len());
+        Ok(page)
+    }
+    
+    /// 在调用方上下文内执行查询
+    pub async fn query_ctx(&self, ctx: &Ctx, sql: &str) -> Result<Vec<User>> {
+        ctx.run(self.query(sql)).await
     }
     
     /// 异步执行更新
@@ -186,11 +960,51 @@ impl DatabaseConnection {
 
 impl Drop for DatabaseConnection {
     fn drop(&mut self) {
-        let database = self.database.clone();
-        let conn_id = self.id.clone();
-        tokio::spawn(async move {
-            database.release_connection(&conn_id).await;
-        });
+        if let Some(connection) = self.connection.take() {
+            self.database.connection_pool.release(connection);
+        }
+    }
+}
+
+/// 连接池句柄
+///
+/// 持有一份独立上限的连接池，数据与创建它的 `AsyncDatabase` 共享；
+/// 克隆出的句柄共用同一个连接池。
+#[derive(Debug, Clone)]
+pub struct ConnectionPool {
+    database: AsyncDatabase,
+}
+
+impl ConnectionPool {
+    /// 创建最多 `max_connections` 个连接的连接池，等待时间为 `DEFAULT_ACQUIRE_TIMEOUT`
+    pub fn new(database: AsyncDatabase, max_connections: usize) -> Self {
+        Self {
+            database: database.with_pool(max_connections, DEFAULT_ACQUIRE_TIMEOUT),
+        }
+    }
+    
+    /// 从连接池获取连接，离开作用域时自动归还
+    pub async fn get_connection(&self) -> Result<PooledConnection> {
+        Ok(PooledConnection {
+            connection: self.database.get_connection().await?,
+        })
+    }
+    
+    /// 连接池统计
+    pub fn stats(&self) -> PoolStats {
+        self.database.pool_stats()
+    }
+}
+
+/// 从 `ConnectionPool` 借出的连接
+pub struct PooledConnection {
+    connection: DatabaseConnection,
+}
+
+impl PooledConnection {
+    /// 获取连接引用
+    pub fn connection(&self) -> &DatabaseConnection {
+        &self.connection
     }
 }
 
@@ -247,6 +1061,7 @@ pub async fn database_operations_example() -> Result<()> {
         name: "张三".to_string(),
         email: "zhangsan@example.com".to_string(),
         created_at: 1234567890,
+        version: 0,
     };
     
     let user2 = User {
@@ -254,6 +1069,7 @@ pub async fn database_operations_example() -> Result<()> {
         name: "李四".to_string(),
         email: "lisi@example.com".to_string(),
         created_at: 1234567891,
+        version: 0,
     };
     
     // 异步创建用户
@@ -271,28 +1087,163 @@ pub async fn database_operations_example() -> Result<()> {
     db.update_user(updated_user).await?;
     println!("用户更新完成");
     
+    // 乐观锁：基于旧版本的更新会被拒绝
+    if let Err(e) = db.update_user(user1.clone()).await {
+        println!("过期的更新被拒绝: {}", e);
+    }
+    
+    // 读取-修改-写入，冲突时自动重试
+    let updated = db.update_with("1", 3, |user| user.email = "zhangsan@example.org".to_string()).await?;
+    println!("重试更新后: 版本 {}，邮箱 {}", updated.version, updated.email);
+    
     // 异步事务
     db.transaction(|tx| {
-        async move {
-            tx.add_operation(DatabaseOperation::Create(User {
-                id: "3".to_string(),
-                name: "王五".to_string(),
-                email: "wangwu@example.com".to_string(),
-                created_at: 1234567892,
-            }));
-            
-            tx.add_operation(DatabaseOperation::Update(User {
-                id: "2".to_string(),
-                name: "李四（事务更新）".to_string(),
-                email: "lisi@example.com".to_string(),
-                created_at: 1234567891,
-            }));
-            
-            Ok(())
-        }
+        tx.add_operation(DatabaseOperation::Create(User {
+            id: "3".to_string(),
+            name: "王五".to_string(),
+            email: "wangwu@example.com".to_string(),
+            created_at: 1234567892,
+            version: 0,
+        }));
+        
+        tx.add_operation(DatabaseOperation::Update(User {
+            id: "2".to_string(),
+            name: "李四（事务更新）".to_string(),
+            email: "lisi@example.com".to_string(),
+            created_at: 1234567891,
+            version: 0,
+        }));
+        
+        async { Ok(()) }
     }).await?;
     
     println!("事务执行完成");
+    
+    // 批量操作：逐个执行，单个失败不影响其他操作
+    let results = db.batch_operations(vec![
+        DatabaseOperation::Create(User {
+            id: "4".to_string(),
+            name: "赵六".to_string(),
+            email: "zhaoliu@example.com".to_string(),
+            created_at: 1234567894,
+            version: 0,
+        }),
+        DatabaseOperation::Update(User {
+            id: "missing".to_string(),
+            name: "不存在".to_string(),
+            email: "missing@example.com".to_string(),
+            created_at: 0,
+            version: 0,
+        }),
+        DatabaseOperation::Delete("4".to_string()),
+    ]).await?;
+    let failed = results.iter().filter(|result| result.is_err()).count();
+    println!("批量操作完成: {} 个成功，{} 个失败", results.len() - failed, failed);
+    
+    // 批量插入：逐条校验，跳过重复的 id
+    let batch = ["5", "1", "6"].map(|id| User {
+        id: id.to_string(),
+        name: format!("批量用户{}", id),
+        email: format!("batch{}@example.com", id),
+        created_at: 1234567895,
+        version: 0,
+    });
+    let report = db.create_users_bulk(batch.to_vec(), BulkMode::PerItem).await?;
+    println!("批量插入 {} 条，失败 {:?}", report.inserted, report.failures.iter().map(|f| &f.id).collect::<Vec<_>>());
+    
+    // 分页查询：每页 2 条，用游标逐页读取
+    let connection = db.get_connection().await?;
+    let mut cursor = None;
+    let mut page_number = 1;
+    loop {
+        let page = connection
+            .query_page("SELECT * FROM users ORDER BY id", 2, cursor.as_deref())
+            .await?;
+        let names: Vec<&str> = page.items.iter().map(|user| user.name.as_str()).collect();
+        println!("第 {} 页: {:?}", page_number, names);
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+        page_number += 1;
+    }
+    let affected = connection.execute("UPDATE users SET name = TRIM(name)").await?;
+    println!("更新影响 {} 行", affected);
+    drop(connection);
+    let stats = db.pool_stats();
+    println!(
+        "连接池: 上限 {}，已创建 {}，累计获取 {} 次，平均等待 {:?}",
+        stats.max_connections,
+        stats.open,
+        stats.acquired,
+        stats.average_wait()
+    );
+    
+    // 可过期记录：临时用户到期后被后台清理任务删除，并产生变更事件
+    let mut changes = db.subscribe_changes();
+    let reaper = db.start_reaper(Duration::from_millis(50));
+    db.create_user_with_ttl(User {
+        id: "temp".to_string(),
+        name: "临时用户".to_string(),
+        email: "temp@example.com".to_string(),
+        created_at: 1234567893,
+        version: 0,
+    }, Duration::from_millis(120)).await?;
+    if let Some(expires_at) = db.expires_at("temp").await {
+        println!("临时用户将在 {:?} 后过期", expires_at.saturating_duration_since(Instant::now()));
+    }
+    // 用户 3 先设置过期时间，随后取消，不会被清理
+    db.set_expiry("3", Some(Instant::now() + Duration::from_millis(60))).await?;
+    db.set_expiry("3", None).await?;
+    loop {
+        match changes.recv().await? {
+            ChangeEvent::Deleted { id, reason } => {
+                println!("用户 {} 已删除，原因: {:?}", id, reason);
+                break;
+            }
+            event => println!("变更事件: {:?}", event),
+        }
+    }
+    reaper.stop().await;
+    
+    // 预写日志持久化：重新打开数据库后数据仍然存在
+    let wal_path = std::env::temp_dir().join(format!("august_users_{}.wal", std::process::id()));
+    {
+        let persistent = AsyncDatabase::recover(&wal_path).await?;
+        println!("是否持久化: 内存数据库 {}，恢复的数据库 {}", db.is_persistent(), persistent.is_persistent());
+        let compaction = persistent.start_compaction(Duration::from_millis(20));
+        persistent.create_user(user1.clone()).await?;
+        persistent.create_user(user2.clone()).await?;
+        // 后台任务把前两条日志压缩进快照
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        compaction.stop().await;
+        persistent.delete_user("2").await?;
+        persistent.compact().await?;
+    }
+    let reopened = AsyncDatabase::recover(&wal_path).await?;
+    println!(
+        "重新打开后的用户: {:?}",
+        reopened.list_users(10, None).await?.items.iter().map(|user| &user.name).collect::<Vec<_>>()
+    );
+    let _ = tokio::fs::remove_file(&wal_path).await;
+    let _ = tokio::fs::remove_file(WriteAheadLog::snapshot_path_for(&wal_path)).await;
+    
+    // 独立的连接池：与 db 共享数据，连接上限单独计算
+    let pool = ConnectionPool::new(db.clone(), 2);
+    let mut handles = Vec::new();
+    for i in 0..3 {
+        let pool = pool.clone();
+        handles.push(tokio::spawn(async move {
+            let conn = pool.get_connection().await?;
+            let users = conn.connection().query(&format!("SELECT * FROM users WHERE id = {}", i + 1)).await?;
+            println!("连接池任务 {} 查询结果: {} 个用户", i + 1, users.len());
+            Ok::<_, anyhow::Error>(())
+        }));
+    }
+    for handle in handles {
+        handle.await??;
+    }
+    println!("连接池统计: 已创建 {} 个连接，累计获取 {} 次", pool.stats().open, pool.stats().acquired);
     Ok(())
 }
 
@@ -300,6 +1251,22 @@ pub async fn database_operations_example() -> Result<()> {
 mod tests {
     use super::*;
     
+    #[tokio::test]
+    #[allow(deprecated)]
+    async fn test_async_db_compat_path() {
+        // 旧的 async_db 路径仍然可用，且与 core::database 是同一组类型
+        let db: crate::async_db::AsyncDatabase = AsyncDatabase::new();
+        let user = crate::async_db::User {
+            id: "compat".to_string(),
+            name: "旧路径".to_string(),
+            email: "compat@example.com".to_string(),
+            created_at: 0,
+            version: 0,
+        };
+        db.create_user(user).await.unwrap();
+        assert!(db.find_user("compat").await.unwrap().is_some());
+    }
+
     #[tokio::test]
     async fn test_database_operations() {
         let db = AsyncDatabase::new();
@@ -309,6 +1276,7 @@ mod tests {
             name: "测试用户".to_string(),
             email: "test@example.com".to_string(),
             created_at: 1234567890,
+            version: 0,
         };
         
         // 测试创建
@@ -329,4 +1297,308 @@ mod tests {
         let found = db.find_user("test").await.unwrap();
         assert!(found.is_none());
     }
+    
+    fn numbered_user(i: usize) -> User {
+        User {
+            id: format!("{:03}", i),
+            name: format!("用户{}", i),
+            email: format!("user{}@example.com", i),
+            created_at: i as u64,
+            version: 0,
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_list_users_pages_in_stable_order() {
+        let db = AsyncDatabase::new();
+        for i in (1..=7).rev() {
+            db.create_user(numbered_user(i)).await.unwrap();
+        }
+        
+        let mut ids = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = db.list_users(3, cursor.as_deref()).await.unwrap();
+            assert!(page.items.len() <= 3);
+            ids.extend(page.items.into_iter().map(|user| user.id));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        let expected: Vec<String> = (1..=7).map(|i| format!("{:03}", i)).collect();
+        assert_eq!(ids, expected);
+        
+        // 恰好整页时没有下一页
+        let page = db.list_users(7, None).await.unwrap();
+        assert_eq!(page.items.len(), 7);
+        assert!(page.next_cursor.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_list_users_cursor_survives_concurrent_changes() {
+        let db = AsyncDatabase::new();
+        for i in 1..=4 {
+            db.create_user(numbered_user(i)).await.unwrap();
+        }
+        
+        let first = db.list_users(2, None).await.unwrap();
+        db.delete_user("001").await.unwrap();
+        db.create_user(numbered_user(0)).await.unwrap();
+        
+        let second = db.list_users(2, first.next_cursor.as_deref()).await.unwrap();
+        let ids: Vec<&str> = second.items.iter().map(|user| user.id.as_str()).collect();
+        assert_eq!(ids, vec!["003", "004"]);
+    }
+    
+    #[tokio::test]
+    async fn test_invalid_cursor_and_query_limit() {
+        let db = AsyncDatabase::new();
+        for i in 1..=5 {
+            db.create_user(numbered_user(i)).await.unwrap();
+        }
+        assert!(db.list_users(2, Some("not-a-cursor")).await.is_err());
+        assert!(db.list_users(2, Some(&encode_cursor("x")[2..])).await.is_err());
+        assert!(db.list_users(0, None).await.is_err());
+        
+        let connection = db.get_connection().await.unwrap();
+        let users = connection.query("SELECT * FROM users LIMIT 2;").await.unwrap();
+        assert_eq!(users.len(), 2);
+        assert_eq!(connection.query("SELECT * FROM users").await.unwrap().len(), 5);
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_expired_records_are_hidden_then_reaped() {
+        let db = AsyncDatabase::new();
+        db.create_user_with_ttl(numbered_user(1), Duration::from_secs(10)).await.unwrap();
+        db.create_user(numbered_user(2)).await.unwrap();
+        
+        tokio::time::advance(Duration::from_secs(9)).await;
+        assert!(db.find_user("001").await.unwrap().is_some());
+        
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert!(db.find_user("001").await.unwrap().is_none());
+        assert_eq!(db.list_users(10, None).await.unwrap().items.len(), 1);
+        
+        assert_eq!(db.reap_expired().await, 1);
+        assert_eq!(db.reap_expired().await, 0);
+        assert!(db.expires_at("001").await.is_none());
+        assert!(db.find_user("002").await.unwrap().is_some());
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_reaper_emits_delete_events_on_schedule() {
+        let db = AsyncDatabase::new();
+        let mut changes = db.subscribe_changes();
+        let reaper = db.start_reaper(Duration::from_secs(5));
+        
+        db.create_user_with_ttl(numbered_user(1), Duration::from_secs(7)).await.unwrap();
+        assert_eq!(changes.recv().await.unwrap(), ChangeEvent::Created(numbered_user(1)));
+        let created_at = Instant::now();
+        
+        // 7 秒到期，下一次清理在第 10 秒
+        let event = changes.recv().await.unwrap();
+        assert_eq!(event, ChangeEvent::Deleted {
+            id: "001".to_string(),
+            reason: DeleteReason::Expired,
+        });
+        assert_eq!(created_at.elapsed(), Duration::from_secs(10));
+        assert!(db.find_user("001").await.unwrap().is_none());
+        
+        reaper.stop().await;
+    }
+    
+    #[tokio::test]
+    async fn test_recreate_and_clear_expiry() {
+        let db = AsyncDatabase::new();
+        db.create_user_with_ttl(numbered_user(1), Duration::from_millis(1)).await.unwrap();
+        db.set_expiry("001", None).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert!(db.find_user("001").await.unwrap().is_some());
+        
+        db.create_user_with_ttl(numbered_user(2), Duration::from_secs(60)).await.unwrap();
+        db.create_user(numbered_user(2)).await.unwrap();
+        assert!(db.expires_at("002").await.is_none());
+        assert!(db.set_expiry("missing", Some(Instant::now())).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_stale_update_is_rejected() {
+        let db = AsyncDatabase::new();
+        db.create_user(numbered_user(1)).await.unwrap();
+        
+        // 两个调用方读到同一个版本
+        let mut first = db.find_user("001").await.unwrap().unwrap();
+        let mut second = first.clone();
+        first.name = "第一次修改".to_string();
+        second.name = "第二次修改".to_string();
+        
+        db.update_user(first).await.unwrap();
+        let error = db.update_user(second).await.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<DatabaseError>(),
+            Some(&DatabaseError::Conflict { id: "001".to_string(), expected: 0, actual: 1 })
+        );
+        
+        let stored = db.find_user("001").await.unwrap().unwrap();
+        assert_eq!(stored.name, "第一次修改");
+        assert_eq!(stored.version, 1);
+        assert!(matches!(
+            db.update_user(numbered_user(9)).await.unwrap_err().downcast_ref::<DatabaseError>(),
+            Some(DatabaseError::NotFound(_))
+        ));
+    }
+    
+    #[tokio::test]
+    async fn test_recreate_keeps_counting_versions() {
+        let db = AsyncDatabase::new();
+        db.create_user(numbered_user(1)).await.unwrap();
+        let stale = db.find_user("001").await.unwrap().unwrap();
+        db.update_user(stale.clone()).await.unwrap();
+        
+        // 重新创建后版本继续递增，读到版本 0 的调用方仍然冲突
+        db.create_user(numbered_user(1)).await.unwrap();
+        assert_eq!(db.find_user("001").await.unwrap().unwrap().version, 2);
+        let error = db.update_user(stale).await.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<DatabaseError>(),
+            Some(&DatabaseError::Conflict { id: "001".to_string(), expected: 0, actual: 2 })
+        );
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_update_of_expired_record_is_not_found() {
+        let db = AsyncDatabase::new();
+        db.create_user_with_ttl(numbered_user(1), Duration::from_secs(10)).await.unwrap();
+        tokio::time::advance(Duration::from_secs(10)).await;
+        
+        // 过期但还没被清理的记录不能再被更新
+        assert!(matches!(
+            db.update_user(numbered_user(1)).await.unwrap_err().downcast_ref::<DatabaseError>(),
+            Some(DatabaseError::NotFound(_))
+        ));
+        assert_eq!(db.reap_expired().await, 1);
+    }
+    
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_updates_do_not_lose_writes() {
+        let db = AsyncDatabase::new();
+        db.create_user(numbered_user(0)).await.unwrap();
+        
+        // 每个任务把 created_at 加 1，没有乐观锁时并发的读-改-写会丢失更新
+        let mut handles = Vec::new();
+        for _ in 0..20 {
+            let db = db.clone();
+            handles.push(tokio::spawn(async move {
+                db.update_with("000", 100, |user| user.created_at += 1).await
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+        
+        let stored = db.find_user("000").await.unwrap().unwrap();
+        assert_eq!(stored.created_at, 20);
+        assert_eq!(stored.version, 20);
+    }
+    
+    #[tokio::test]
+    async fn test_bulk_insert_all_or_nothing() {
+        let db = AsyncDatabase::new();
+        db.create_user(numbered_user(1)).await.unwrap();
+        
+        let mut invalid = numbered_user(3);
+        invalid.email = "invalid".to_string();
+        let batch = vec![numbered_user(1), numbered_user(2), invalid, numbered_user(2)];
+        
+        let report = db.create_users_bulk(batch.clone(), BulkMode::AllOrNothing).await.unwrap();
+        assert_eq!(report.inserted, 0);
+        let failed: Vec<usize> = report.failures.iter().map(|f| f.index).collect();
+        assert_eq!(failed, vec![0, 2, 3]);
+        assert!(db.find_user("002").await.unwrap().is_none());
+        
+        let report = db.create_users_bulk(batch, BulkMode::PerItem).await.unwrap();
+        assert_eq!(report.inserted, 1);
+        assert_eq!(report.failures.len(), 3);
+        assert!(db.find_user("002").await.unwrap().is_some());
+        
+        let report = db
+            .create_users_bulk((10..20).map(numbered_user).collect(), BulkMode::AllOrNothing)
+            .await
+            .unwrap();
+        assert_eq!(report, BulkInsertReport { inserted: 10, failures: Vec::new() });
+        assert_eq!(db.list_users(100, None).await.unwrap().items.len(), 12);
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_acquire_times_out_when_pool_is_exhausted() {
+        let db = AsyncDatabase::new().with_pool(1, Duration::from_secs(1));
+        let held = db.get_connection().await.unwrap();
+        
+        let error = db.acquire_timeout(Duration::from_millis(100)).await.err().unwrap();
+        assert!(matches!(
+            error.downcast_ref::<DatabaseError>(),
+            Some(DatabaseError::PoolTimeout { max_connections: 1, .. })
+        ));
+        assert_eq!(db.pool_stats().timeouts, 1);
+        
+        drop(held);
+        let again = db.acquire_timeout(Duration::from_millis(100)).await.unwrap();
+        assert_eq!(again.id, "conn_1");
+        let stats = db.pool_stats();
+        assert_eq!((stats.open, stats.in_use, stats.waiting), (1, 1, 0));
+    }
+    
+    #[tokio::test]
+    async fn test_waiters_are_served_in_arrival_order() {
+        let db = AsyncDatabase::new().with_pool(1, Duration::from_secs(5));
+        let held = db.get_connection().await.unwrap();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        
+        let mut handles = Vec::new();
+        for i in 0..3 {
+            let worker = db.clone();
+            let order = order.clone();
+            handles.push(tokio::spawn(async move {
+                let _connection = worker.get_connection().await.unwrap();
+                order.lock().unwrap().push(i);
+            }));
+            // 等上一个请求进入队列后再发起下一个
+            while db.pool_stats().waiting < i + 1 {
+                tokio::task::yield_now().await;
+            }
+        }
+        
+        drop(held);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2]);
+        assert_eq!(db.pool_stats().open, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_connection_pool_shares_data_with_separate_limit() {
+        let db = AsyncDatabase::new();
+        db.create_user(User {
+            id: "1".to_string(),
+            name: "张三".to_string(),
+            email: "zhangsan@example.com".to_string(),
+            created_at: 1234567890,
+            version: 0,
+        }).await.unwrap();
+        
+        let pool = ConnectionPool::new(db.clone(), 1);
+        {
+            let conn = pool.get_connection().await.unwrap();
+            assert_eq!(conn.connection().query("SELECT * FROM users").await.unwrap().len(), 1);
+            assert_eq!(pool.stats().in_use, 1);
+            // 原数据库的连接池不受影响
+            assert_eq!(db.pool_stats().in_use, 0);
+        }
+        
+        let _again = pool.get_connection().await.unwrap();
+        let stats = pool.stats();
+        assert_eq!((stats.max_connections, stats.open, stats.acquired), (1, 1, 2));
+    }
 }
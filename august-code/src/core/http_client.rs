@@ -5,13 +5,24 @@
 //! - 并发请求处理
 //! - 错误处理和重试
 //! - 超时管理
+//! - 截止时间传播
+//! - 离线模式下使用模拟客户端
+//! - 可选的 DoH 域名解析器
+//! - 可开关的 gzip/brotli 透明解压和响应大小限制
+//! - `fetch_json` 直接反序列化响应，错误区分网络故障和解码失败
 
 use anyhow::Result;
 use reqwest::Client;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tokio::time::Instant;
 
+use super::connectivity::{self, MockHttpClient};
+use super::context::Ctx;
+use super::resolver::DohResolver;
+use crate::utils::tasks::TaskSet;
+
 /// HTTP响应信息
 #[derive(Debug, Deserialize, Serialize)]
 pub struct HttpResponse {
@@ -21,33 +32,178 @@ pub struct HttpResponse {
     pub content_length: Option<usize>,
 }
 
+/// 带响应体的HTTP响应
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// 默认的响应体大小上限：10MB
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
+
+/// HTTP客户端配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpClientConfig {
+    pub timeout: Duration,
+    /// 发送 `Accept-Encoding: gzip`，并透明解压 `Content-Encoding: gzip` 的响应
+    pub gzip: bool,
+    /// 发送 `Accept-Encoding: br`，并透明解压 `Content-Encoding: br` 的响应
+    pub brotli: bool,
+    /// 响应体（解压后）的最大字节数，`None` 表示不限制
+    pub max_response_bytes: Option<usize>,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            gzip: true,
+            brotli: true,
+            max_response_bytes: Some(DEFAULT_MAX_RESPONSE_BYTES),
+        }
+    }
+}
+
+impl HttpClientConfig {
+    fn builder(&self) -> reqwest::ClientBuilder {
+        Client::builder().gzip(self.gzip).brotli(self.brotli)
+    }
+}
+
+/// `fetch_json` 等方法的错误，区分网络故障和响应内容的问题
+#[derive(Debug, thiserror::Error)]
+pub enum FetchError {
+    /// 连接失败、连接中断等网络故障
+    #[error("请求 {url} 失败: {source}")]
+    Network {
+        url: String,
+        #[source]
+        source: anyhow::Error,
+    },
+
+    #[error("请求 {url} 超时")]
+    Timeout { url: String },
+
+    /// 服务器返回了非 2xx 状态码
+    #[error("{url} 返回状态码 {status}")]
+    Status { url: String, status: u16 },
+
+    #[error("{url} 的响应超过 {limit} 字节上限")]
+    TooLarge { url: String, limit: usize },
+
+    /// 响应体解压失败或不是期望的 JSON
+    #[error("无法解码 {url} 的响应: {source}")]
+    Decode {
+        url: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+impl FetchError {
+    fn from_reqwest(url: &str, error: reqwest::Error) -> Self {
+        let url = url.to_string();
+        if error.is_timeout() {
+            FetchError::Timeout { url }
+        } else if error.is_decode() {
+            FetchError::Decode { url, source: Box::new(error) }
+        } else {
+            FetchError::Network { url, source: error.into() }
+        }
+    }
+
+    /// 是否为网络故障（包括超时），这类错误通常值得重试
+    pub fn is_network(&self) -> bool {
+        matches!(self, FetchError::Network { .. } | FetchError::Timeout { .. })
+    }
+
+    /// 是否为解码失败，重试通常无济于事
+    pub fn is_decode(&self) -> bool {
+        matches!(self, FetchError::Decode { .. })
+    }
+}
+
 /// 异步HTTP客户端
+///
+/// 离线模式下创建的客户端访问外部地址时使用 `MockHttpClient`，不会发起真实请求
+#[derive(Clone)]
 pub struct AsyncHttpClient {
     client: Client,
     timeout: Duration,
+    max_response_bytes: Option<usize>,
+    mock: Option<MockHttpClient>,
 }
 
 impl AsyncHttpClient {
     /// 创建新的HTTP客户端
     pub fn new() -> Self {
-        Self {
-            client: Client::new(),
-            timeout: Duration::from_secs(30),
-        }
+        Self::with_timeout(Duration::from_secs(30))
     }
     
     /// 创建带超时的HTTP客户端
     pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_config(HttpClientConfig { timeout, ..Default::default() })
+    }
+    
+    /// 按配置创建HTTP客户端
+    pub fn with_config(config: HttpClientConfig) -> Self {
+        let client = config.builder().build().expect("HTTP客户端配置无效");
+        Self {
+            client,
+            timeout: config.timeout,
+            max_response_bytes: config.max_response_bytes,
+            mock: connectivity::is_offline().then(MockHttpClient::new),
+        }
+    }
+    
+    /// 创建使用 DoH 解析器解析域名的HTTP客户端
+    pub fn with_resolver(resolver: DohResolver, timeout: Duration) -> Self {
+        let config = HttpClientConfig { timeout, ..Default::default() };
+        let client = config
+            .builder()
+            .dns_resolver(std::sync::Arc::new(resolver))
+            .build()
+            .expect("HTTP客户端配置无效");
+        Self {
+            client,
+            timeout,
+            max_response_bytes: config.max_response_bytes,
+            mock: connectivity::is_offline().then(MockHttpClient::new),
+        }
+    }
+    
+    /// 创建总是使用模拟客户端的HTTP客户端
+    pub fn with_mock(mock: MockHttpClient, timeout: Duration) -> Self {
         Self {
             client: Client::new(),
             timeout,
+            max_response_bytes: Some(DEFAULT_MAX_RESPONSE_BYTES),
+            mock: Some(mock),
         }
     }
     
+    /// 是否使用模拟客户端
+    pub fn is_mock(&self) -> bool {
+        self.mock.is_some()
+    }
+    
     /// 异步获取单个URL的数据
     pub async fn fetch_url(&self, url: &str) -> Result<HttpResponse> {
         let start = Instant::now();
         
+        if let Some(mock) = self.mock.as_ref().filter(|_| !connectivity::is_local_url(url)) {
+            let response = tokio::time::timeout(self.timeout, mock.get(url))
+                .await
+                .map_err(|_| anyhow::anyhow!("请求超时: {}", url))??;
+            return Ok(HttpResponse {
+                url: url.to_string(),
+                status: response.status,
+                response_time_ms: start.elapsed().as_millis() as u64,
+                content_length: Some(response.body.len()),
+            });
+        }
+        
         let response = self.client
             .get(url)
             .timeout(self.timeout)
@@ -69,83 +225,119 @@ impl AsyncHttpClient {
         })
     }
     
-    /// 并发获取多个URL的数据
-    pub async fn fetch_multiple_urls(&self, urls: Vec<String>) -> Result<Vec<HttpResponse>> {
-        let mut handles = Vec::new();
+    /// 在调用方上下文内获取单个URL的数据，截止时间到达或被取消时提前结束
+    pub async fn fetch_url_ctx(&self, ctx: &Ctx, url: &str) -> Result<HttpResponse> {
+        ctx.run(self.fetch_url(url)).await
+    }
+    
+    /// 发起 GET 请求并读取响应体，响应体超过大小上限时返回错误
+    pub async fn get_text(&self, url: &str) -> Result<TextResponse> {
+        let (status, body) = self.get_bytes(url).await?;
+        Ok(TextResponse { status, body: String::from_utf8_lossy(&body).into_owned() })
+    }
+    
+    /// 带请求头发起 GET 请求并读取响应体
+    pub async fn get_text_with_headers(&self, url: &str, headers: &[(&str, &str)]) -> Result<TextResponse> {
+        let (status, body) = self.get_bytes_with_headers(url, headers).await?;
+        Ok(TextResponse { status, body: String::from_utf8_lossy(&body).into_owned() })
+    }
+    
+    /// 发起 GET 请求并读取（解压后的）响应体，返回状态码和响应体
+    pub async fn get_bytes(&self, url: &str) -> std::result::Result<(u16, Vec<u8>), FetchError> {
+        self.get_bytes_with_headers(url, &[]).await
+    }
+    
+    /// 带请求头发起 GET 请求并读取响应体，模拟客户端忽略请求头
+    pub async fn get_bytes_with_headers(&self, url: &str, headers: &[(&str, &str)]) -> std::result::Result<(u16, Vec<u8>), FetchError> {
+        if let Some(mock) = self.mock.as_ref().filter(|_| !connectivity::is_local_url(url)) {
+            let response = tokio::time::timeout(self.timeout, mock.get(url))
+                .await
+                .map_err(|_| FetchError::Timeout { url: url.to_string() })?
+                .map_err(|source| FetchError::Network { url: url.to_string(), source })?;
+            self.check_size(url, response.body.len())?;
+            return Ok((response.status, response.body.into_bytes()));
+        }
         
-        // 为每个URL创建异步任务
-        for url in urls {
-            let client = self.client.clone();
-            let timeout = self.timeout;
-            let handle = tokio::spawn(async move {
-                let start = Instant::now();
-                let response = client
-                    .get(&url)
-                    .timeout(timeout)
-                    .send()
-                    .await?;
-                
-                let status = response.status().as_u16();
-                let content_length = response.content_length().map(|len| len as usize);
-                let response_time = start.elapsed().as_millis() as u64;
-                let _body = response.text().await?;
-                
-                Ok::<HttpResponse, anyhow::Error>(HttpResponse {
-                    url,
-                    status,
-                    response_time_ms: response_time,
-                    content_length,
-                })
-            });
-            handles.push(handle);
+        let mut request = self.client
+            .get(url)
+            .timeout(self.timeout);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
         }
+        let mut response = request
+            .send()
+            .await
+            .map_err(|e| FetchError::from_reqwest(url, e))?;
+        let status = response.status().as_u16();
         
-        // 等待所有任务完成
-        let mut results = Vec::new();
-        for handle in handles {
-            match handle.await? {
-                Ok(response) => results.push(response),
-                Err(e) => eprintln!("请求失败: {}", e),
-            }
+        // 未压缩的响应可以根据 Content-Length 提前拒绝，压缩过的只能边读边数
+        if let Some(length) = response.content_length() {
+            self.check_size(url, length as usize)?;
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| FetchError::from_reqwest(url, e))? {
+            body.extend_from_slice(&chunk);
+            self.check_size(url, body.len())?;
+        }
+        Ok((status, body))
+    }
+    
+    fn check_size(&self, url: &str, size: usize) -> std::result::Result<(), FetchError> {
+        match self.max_response_bytes {
+            Some(limit) if size > limit => Err(FetchError::TooLarge { url: url.to_string(), limit }),
+            _ => Ok(()),
+        }
+    }
+    
+    /// 发起 GET 请求并把响应体反序列化为 `T`
+    ///
+    /// 非 2xx 状态码返回 [`FetchError::Status`]，响应体不是期望的 JSON 时返回 [`FetchError::Decode`]
+    pub async fn fetch_json<T: DeserializeOwned>(&self, url: &str) -> std::result::Result<T, FetchError> {
+        let (status, body) = self.get_bytes(url).await?;
+        if !(200..300).contains(&status) {
+            return Err(FetchError::Status { url: url.to_string(), status });
+        }
+        serde_json::from_slice(&body).map_err(|e| FetchError::Decode { url: url.to_string(), source: Box::new(e) })
+    }
+    
+    /// 以 JSON 请求体发起 POST 请求并读取响应体
+    pub async fn post_json<T: Serialize + ?Sized>(&self, url: &str, body: &T) -> Result<TextResponse> {
+        let payload = serde_json::to_string(body)?;
+        if let Some(mock) = self.mock.as_ref().filter(|_| !connectivity::is_local_url(url)) {
+            let response = tokio::time::timeout(self.timeout, mock.post(url, &payload))
+                .await
+                .map_err(|_| anyhow::anyhow!("请求超时: {}", url))??;
+            return Ok(TextResponse { status: response.status, body: response.body });
         }
         
-        Ok(results)
+        let response = self.client
+            .post(url)
+            .timeout(self.timeout)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(payload)
+            .send()
+            .await?;
+        let status = response.status().as_u16();
+        let body = response.text().await?;
+        Ok(TextResponse { status, body })
     }
     
-    /// 使用join!宏并发执行多个异步操作
-    pub async fn concurrent_requests(&self, urls: Vec<&str>) -> Result<Vec<HttpResponse>> {
-        let mut handles = Vec::new();
+    /// 并发获取多个URL的数据
+    pub async fn fetch_multiple_urls(&self, urls: Vec<String>) -> Result<Vec<HttpResponse>> {
+        let mut tasks = TaskSet::new();
         
+        // 为每个URL创建异步任务
         for url in urls {
-            let client = self.client.clone();
-            let timeout = self.timeout;
-            let url = url.to_string();
-            let handle = tokio::spawn(async move {
-                let start = Instant::now();
-                let response = client
-                    .get(&url)
-                    .timeout(timeout)
-                    .send()
-                    .await?;
-                
-                let status = response.status().as_u16();
-                let content_length = response.content_length().map(|len| len as usize);
-                let response_time = start.elapsed().as_millis() as u64;
-                let _body = response.text().await?;
-                
-                Ok::<HttpResponse, anyhow::Error>(HttpResponse {
-                    url,
-                    status,
-                    response_time_ms: response_time,
-                    content_length,
-                })
+            let client = self.clone();
+            tasks.spawn_tracked(url.clone(), async move {
+                client.fetch_url(&url).await
             });
-            handles.push(handle);
         }
         
+        // 等待所有任务完成
         let mut results = Vec::new();
-        for handle in handles {
-            match handle.await? {
+        for result in tasks.join_all().await.into_result()? {
+            match result {
                 Ok(response) => results.push(response),
                 Err(e) => eprintln!("请求失败: {}", e),
             }
@@ -154,6 +346,11 @@ impl AsyncHttpClient {
         Ok(results)
     }
     
+    /// 并发获取多个URL的数据，接受字符串切片
+    pub async fn concurrent_requests(&self, urls: Vec<&str>) -> Result<Vec<HttpResponse>> {
+        self.fetch_multiple_urls(urls.into_iter().map(str::to_string).collect()).await
+    }
+    
     /// 带重试的HTTP请求
     pub async fn fetch_with_retry(&self, url: &str, max_retries: u32) -> Result<HttpResponse> {
         let mut last_error = None;
@@ -184,6 +381,7 @@ impl Default for AsyncHttpClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::test_server::TestServer;
     
     #[tokio::test]
     async fn test_http_client_creation() {
@@ -197,4 +395,71 @@ mod tests {
         let client = AsyncHttpClient::with_timeout(timeout);
         assert_eq!(client.timeout, timeout);
     }
+    
+    #[tokio::test]
+    async fn test_mock_client_fetch() {
+        let client = AsyncHttpClient::with_mock(
+            MockHttpClient::with_latency(Duration::from_millis(1)),
+            Duration::from_secs(5),
+        );
+        assert!(client.is_mock());
+        
+        let urls = vec![
+            "https://httpbin.org/get".to_string(),
+            "https://httpbin.org/status/404".to_string(),
+        ];
+        let results = client.fetch_multiple_urls(urls).await.unwrap();
+        let statuses: Vec<u16> = results.iter().map(|r| r.status).collect();
+        assert_eq!(statuses, vec![200, 404]);
+    }
+    
+    #[derive(Debug, Deserialize)]
+    struct GzipBody {
+        gzipped: bool,
+    }
+    
+    #[tokio::test]
+    async fn test_fetch_json_decompresses_gzip() {
+        let server = TestServer::start().await.unwrap();
+        let client = AsyncHttpClient::with_config(HttpClientConfig::default());
+        let body: GzipBody = client.fetch_json(&server.url("/gzip")).await.unwrap();
+        assert!(body.gzipped);
+        
+        // 关闭 gzip 后拿到的是压缩过的字节，解码失败而不是网络错误
+        let plain = AsyncHttpClient::with_config(HttpClientConfig { gzip: false, ..Default::default() });
+        let error = plain.fetch_json::<GzipBody>(&server.url("/gzip")).await.unwrap_err();
+        assert!(error.is_decode(), "{}", error);
+        assert!(!error.is_network());
+    }
+    
+    #[tokio::test]
+    async fn test_fetch_json_error_kinds() {
+        let server = TestServer::start().await.unwrap();
+        let client = AsyncHttpClient::with_config(HttpClientConfig {
+            max_response_bytes: Some(1024),
+            ..Default::default()
+        });
+        
+        let error = client.fetch_json::<serde_json::Value>(&server.url("/status/500")).await.unwrap_err();
+        assert!(matches!(error, FetchError::Status { status: 500, .. }));
+        
+        let error = client.get_bytes(&server.url("/bytes/4096")).await.unwrap_err();
+        assert!(matches!(error, FetchError::TooLarge { limit: 1024, .. }));
+        assert_eq!(client.get_bytes(&server.url("/bytes/512")).await.unwrap().1.len(), 512);
+        
+        let error = client.fetch_json::<GzipBody>(&server.url("/get")).await.unwrap_err();
+        assert!(error.is_decode());
+        
+        // 服务器关闭后连接被拒绝
+        let url = server.url("/get");
+        server.shutdown().await;
+        let error = client.fetch_json::<serde_json::Value>(&url).await.unwrap_err();
+        assert!(error.is_network(), "{}", error);
+    }
+    
+    #[tokio::test]
+    async fn test_mock_client_respects_timeout() {
+        let client = AsyncHttpClient::with_mock(MockHttpClient::new(), Duration::from_millis(50));
+        assert!(client.fetch_url("https://httpbin.org/delay/1").await.is_err());
+    }
 }
@@ -0,0 +1,281 @@
+//! 数据库结构版本和迁移模块
+//!
+//! 为 `AsyncDatabase` 提供按版本号注册的迁移步骤：
+//! - 每个步骤是一个变换用户表的闭包，版本号从 1 开始且不能重复
+//! - 启动时按版本顺序执行尚未应用的步骤，并记录当前结构版本
+//! - 某一步失败时停在上一个成功的版本，失败步骤不会留下部分修改
+//! - 演练模式只报告待执行的步骤，不修改数据
+
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use super::database::{AsyncDatabase, User};
+
+/// 迁移步骤：原地变换用户表
+pub type MigrationFn = Arc<dyn Fn(&mut HashMap<String, User>) -> Result<()> + Send + Sync>;
+
+/// 迁移步骤的描述信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationInfo {
+    pub version: u32,
+    pub description: String,
+}
+
+/// 一次迁移的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    /// 已执行（演练模式下为待执行）的步骤
+    pub applied: Vec<MigrationInfo>,
+    pub dry_run: bool,
+}
+
+impl MigrationReport {
+    /// 打印迁移结果
+    pub fn print(&self) {
+        let action = if self.dry_run { "待执行" } else { "已执行" };
+        println!(
+            "结构版本 {} -> {}，{} {} 个迁移",
+            self.from_version,
+            self.to_version,
+            action,
+            self.applied.len()
+        );
+        for info in &self.applied {
+            println!("  v{}: {}", info.version, info.description);
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Migration {
+    description: String,
+    apply: MigrationFn,
+}
+
+/// 迁移注册表
+#[derive(Clone, Default)]
+pub struct Migrator {
+    migrations: BTreeMap<u32, Migration>,
+}
+
+impl Migrator {
+    /// 创建空的迁移注册表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册迁移步骤，版本号为 0 或重复时返回错误
+    pub fn register<F>(&mut self, version: u32, description: &str, apply: F) -> Result<&mut Self>
+    where
+        F: Fn(&mut HashMap<String, User>) -> Result<()> + Send + Sync + 'static,
+    {
+        if version == 0 {
+            return Err(anyhow::anyhow!("迁移版本号必须从 1 开始"));
+        }
+        if self.migrations.contains_key(&version) {
+            return Err(anyhow::anyhow!("迁移版本 {} 重复注册", version));
+        }
+        self.migrations.insert(version, Migration {
+            description: description.to_string(),
+            apply: Arc::new(apply),
+        });
+        Ok(self)
+    }
+
+    /// 已注册的最高版本
+    pub fn latest_version(&self) -> u32 {
+        self.migrations.keys().next_back().copied().unwrap_or(0)
+    }
+
+    /// 相对 `current_version` 尚未执行的步骤
+    pub fn pending(&self, current_version: u32) -> Vec<MigrationInfo> {
+        self.migrations
+            .range(current_version + 1..)
+            .map(|(version, migration)| MigrationInfo {
+                version: *version,
+                description: migration.description.clone(),
+            })
+            .collect()
+    }
+
+    /// 在用户表上依次执行待执行的步骤，返回执行后的版本
+    ///
+    /// 每一步先在副本上执行，成功后才替换原表，所以失败时数据停留在上一个成功的版本。
+    pub(crate) fn apply(
+        &self,
+        users: &mut HashMap<String, User>,
+        current_version: u32,
+        dry_run: bool,
+    ) -> (MigrationReport, Option<anyhow::Error>) {
+        let mut report = MigrationReport {
+            from_version: current_version,
+            to_version: current_version,
+            applied: Vec::new(),
+            dry_run,
+        };
+
+        for info in self.pending(current_version) {
+            if !dry_run {
+                let migration = &self.migrations[&info.version];
+                let mut working = users.clone();
+                if let Err(e) = (migration.apply)(&mut working) {
+                    let error = e.context(format!("迁移 v{}（{}）失败", info.version, info.description));
+                    return (report, Some(error));
+                }
+                *users = working;
+            }
+            report.to_version = info.version;
+            report.applied.push(info);
+        }
+        (report, None)
+    }
+}
+
+/// 数据库迁移示例：首次启动执行全部迁移，再次执行时没有待执行步骤
+pub async fn migrations_example() -> Result<()> {
+    println!("\n=== 数据库迁移示例 ===");
+
+    let db = AsyncDatabase::new();
+    for (id, name, email) in [("1", " 张三 ", "ZhangSan@Example.com"), ("2", "李四", "lisi@example.com")] {
+        db.create_user(User {
+            id: id.to_string(),
+            name: name.to_string(),
+            email: email.to_string(),
+            created_at: 0,
+            version: 0,
+        })
+        .await?;
+    }
+
+    let mut migrator = Migrator::new();
+    migrator
+        .register(1, "邮箱统一转为小写", |users| {
+            for user in users.values_mut() {
+                user.email = user.email.to_lowercase();
+            }
+            Ok(())
+        })?
+        .register(2, "去掉用户名首尾空白", |users| {
+            for user in users.values_mut() {
+                user.name = user.name.trim().to_string();
+            }
+            Ok(())
+        })?
+        .register(3, "补齐缺失的创建时间", |users| {
+            for user in users.values_mut().filter(|user| user.created_at == 0) {
+                user.created_at = 1234567890;
+            }
+            Ok(())
+        })?;
+
+    // 演练模式只报告，不修改数据
+    db.migrate(&migrator, true).await?.print();
+    println!("演练后结构版本: {}", db.schema_version().await);
+
+    db.migrate(&migrator, false).await?.print();
+    println!("迁移后的用户: {:?}", db.find_user("1").await?);
+
+    // 再次启动时没有待执行的迁移
+    db.migrate(&migrator, false).await?.print();
+
+    // 新建的数据库在启动时直接迁移到最新版本
+    let fresh = AsyncDatabase::open_with_migrations(&migrator).await?;
+    println!("新数据库结构版本: {}（最新版本 {}）", fresh.schema_version().await, migrator.latest_version());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: &str, email: &str) -> User {
+        User {
+            id: id.to_string(),
+            name: format!("用户{}", id),
+            email: email.to_string(),
+            created_at: 0,
+            version: 0,
+        }
+    }
+
+    fn migrator() -> Migrator {
+        let mut migrator = Migrator::new();
+        migrator
+            .register(2, "邮箱转小写", |users| {
+                for user in users.values_mut() {
+                    user.email = user.email.to_lowercase();
+                }
+                Ok(())
+            })
+            .unwrap()
+            .register(1, "设置创建时间", |users| {
+                for user in users.values_mut() {
+                    user.created_at = 1;
+                }
+                Ok(())
+            })
+            .unwrap();
+        migrator
+    }
+
+    #[test]
+    fn test_register_rejects_invalid_versions() {
+        let mut migrator = migrator();
+        assert!(migrator.register(0, "无效", |_| Ok(())).is_err());
+        assert!(migrator.register(2, "重复", |_| Ok(())).is_err());
+        assert_eq!(migrator.latest_version(), 2);
+
+        let pending: Vec<u32> = migrator.pending(1).iter().map(|info| info.version).collect();
+        assert_eq!(pending, vec![2]);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_applies_pending_in_order() {
+        let db = AsyncDatabase::new();
+        db.create_user(user("1", "A@Example.com")).await.unwrap();
+        let migrator = migrator();
+
+        let dry_run = db.migrate(&migrator, true).await.unwrap();
+        assert_eq!(dry_run.to_version, 2);
+        assert_eq!(dry_run.applied.len(), 2);
+        assert_eq!(db.schema_version().await, 0);
+        assert_eq!(db.find_user("1").await.unwrap().unwrap().created_at, 0);
+
+        let report = db.migrate(&migrator, false).await.unwrap();
+        assert_eq!((report.from_version, report.to_version), (0, 2));
+        let migrated = db.find_user("1").await.unwrap().unwrap();
+        assert_eq!(migrated.email, "a@example.com");
+        assert_eq!(migrated.created_at, 1);
+
+        let again = db.migrate(&migrator, false).await.unwrap();
+        assert!(again.applied.is_empty());
+        assert_eq!(db.schema_version().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_migration_stops_at_last_good_version() {
+        let db = AsyncDatabase::new();
+        db.create_user(user("1", "a@example.com")).await.unwrap();
+
+        let mut migrator = migrator();
+        migrator
+            .register(3, "半途失败", |users| {
+                users.clear();
+                Err(anyhow::anyhow!("数据不合法"))
+            })
+            .unwrap();
+
+        assert!(db.migrate(&migrator, false).await.is_err());
+        assert_eq!(db.schema_version().await, 2);
+        assert!(db.find_user("1").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_open_with_migrations() {
+        let db = AsyncDatabase::open_with_migrations(&migrator()).await.unwrap();
+        assert_eq!(db.schema_version().await, 2);
+    }
+}
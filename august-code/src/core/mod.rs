@@ -2,11 +2,39 @@
 //! 
 //! 这个模块包含了异步编程的核心功能，包括：
 //! - 异步HTTP客户端
+//! - HTTP请求录制与回放
+//! - 限流的优先级出站请求队列
+//! - 遵守 robots.txt 的礼貌爬虫
 //! - 异步数据库操作
+//! - 数据库结构版本和迁移
+//! - 数据库预写日志持久化
+//! - 数据库备份和恢复
+//! - 读副本和一致性模式
+//! - 带请求合并的用户查询缓存
 //! - 异步Web服务器
 //! - 异步任务调度
+//! - 异步数据源抽象
+//! - 截止时间传播上下文
+//! - 网络连通性检测和离线模拟
+//! - 类型化REST API客户端
+//! - 带自动重连的WebSocket客户端
+//! - 带缓存和系统回退的DoH解析器
 
 pub mod http_client;
+pub mod recording;
+pub mod outbound;
+pub mod crawler;
 pub mod database;
+pub mod migrations;
+pub mod wal;
+pub mod backup;
+pub mod replication;
+pub mod user_cache;
 pub mod web_server;
 pub mod scheduler;
+pub mod data_source;
+pub mod context;
+pub mod connectivity;
+pub mod api_client;
+pub mod ws_client;
+pub mod resolver;
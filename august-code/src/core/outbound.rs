@@ -0,0 +1,395 @@
+//! 限流的优先级出站请求队列模块
+//!
+//! `OutboundQueue` 把限流器和任务队列结合起来：
+//! - 调用方按优先级提交请求，立即拿到一个 oneshot 接收端，请求完成后从中取结果
+//! - 后台分发任务总是先发送优先级最高的请求，同优先级按提交顺序
+//! - 全局令牌桶限制每秒发出的请求数
+//! - 每个主机同时进行的请求数有上限，某个主机占满时先发送其他主机的请求
+
+use anyhow::Result;
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{oneshot, Notify, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+use super::http_client::{AsyncHttpClient, TextResponse};
+use super::scheduler::TaskPriority;
+use crate::utils::test_server::TestServer;
+
+/// 出站队列配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutboundConfig {
+    /// 全局每秒最多发出的请求数
+    pub requests_per_second: f64,
+    /// 令牌桶容量，允许短时间内连续发出的请求数
+    pub burst: u32,
+    /// 每个主机同时进行的请求数上限
+    pub per_host_concurrency: usize,
+    /// 排队请求数上限，`None` 表示不限制
+    pub max_pending: Option<usize>,
+}
+
+impl Default for OutboundConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 10.0,
+            burst: 5,
+            per_host_concurrency: 2,
+            max_pending: Some(1000),
+        }
+    }
+}
+
+/// 出站请求
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutboundRequest {
+    Get(String),
+    /// URL 和 JSON 请求体
+    Post(String, serde_json::Value),
+}
+
+impl OutboundRequest {
+    pub fn url(&self) -> &str {
+        match self {
+            OutboundRequest::Get(url) | OutboundRequest::Post(url, _) => url,
+        }
+    }
+}
+
+/// 请求所属的主机，带端口，无法解析时使用整个 URL
+pub fn host_key(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) => match (parsed.host_str(), parsed.port_or_known_default()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            _ => url.to_string(),
+        },
+        Err(_) => url.to_string(),
+    }
+}
+
+/// 出站队列统计
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutboundStats {
+    pub enqueued: u64,
+    pub completed: u64,
+    pub failed: u64,
+    /// 每个主机出现过的最大并发数
+    pub peak_in_flight: HashMap<String, usize>,
+}
+
+struct Job {
+    priority: TaskPriority,
+    seq: u64,
+    host: String,
+    request: OutboundRequest,
+    reply: oneshot::Sender<Result<TextResponse>>,
+}
+
+// 优先级高的先出堆，同优先级序号小的先出堆
+impl Ord for Job {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority).then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Job {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Job {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Job {}
+
+#[derive(Default)]
+struct Inner {
+    pending: BinaryHeap<Job>,
+    next_seq: u64,
+    hosts: HashMap<String, Arc<Semaphore>>,
+    stats: OutboundStats,
+}
+
+impl Inner {
+    fn host_semaphore(&mut self, host: &str, limit: usize) -> Arc<Semaphore> {
+        self.hosts.entry(host.to_string()).or_insert_with(|| Arc::new(Semaphore::new(limit))).clone()
+    }
+
+    /// 取出优先级最高且主机还有空闲名额的请求
+    fn take_runnable(&mut self, limit: usize) -> Option<(Job, OwnedSemaphorePermit)> {
+        let mut skipped = Vec::new();
+        let mut found = None;
+        while let Some(job) = self.pending.pop() {
+            match self.host_semaphore(&job.host, limit).try_acquire_owned() {
+                Ok(permit) => {
+                    found = Some((job, permit));
+                    break;
+                }
+                Err(_) => skipped.push(job),
+            }
+        }
+        self.pending.extend(skipped);
+        found
+    }
+}
+
+struct Shared {
+    config: OutboundConfig,
+    client: AsyncHttpClient,
+    limiter: common::RateLimiter,
+    inner: Mutex<Inner>,
+    /// 有新请求入队或有请求完成时通知分发任务
+    changed: Notify,
+}
+
+/// 限流的优先级出站请求队列，被丢弃时停止分发，未发送的请求收到通道关闭错误
+pub struct OutboundQueue {
+    shared: Arc<Shared>,
+    dispatcher: JoinHandle<()>,
+}
+
+impl OutboundQueue {
+    /// 创建队列并启动后台分发任务
+    pub fn new(client: AsyncHttpClient, config: OutboundConfig) -> Self {
+        let shared = Arc::new(Shared {
+            limiter: common::RateLimiter::new(config.burst.max(1), config.requests_per_second),
+            config,
+            client,
+            inner: Mutex::new(Inner::default()),
+            changed: Notify::new(),
+        });
+        let dispatcher = tokio::spawn(dispatch(Arc::clone(&shared)));
+        Self { shared, dispatcher }
+    }
+
+    /// 提交请求，返回接收结果的通道；队列已满时返回错误
+    pub fn enqueue(&self, priority: TaskPriority, request: OutboundRequest) -> Result<oneshot::Receiver<Result<TextResponse>>> {
+        let (reply, receiver) = oneshot::channel();
+        {
+            let mut inner = self.shared.inner.lock().unwrap();
+            if let Some(max) = self.shared.config.max_pending {
+                if inner.pending.len() >= max {
+                    return Err(anyhow::anyhow!("出站队列已满（{} 个请求排队）", max));
+                }
+            }
+            let seq = inner.next_seq;
+            inner.next_seq += 1;
+            inner.stats.enqueued += 1;
+            inner.pending.push(Job {
+                priority,
+                seq,
+                host: host_key(request.url()),
+                request,
+                reply,
+            });
+        }
+        self.shared.changed.notify_one();
+        Ok(receiver)
+    }
+
+    /// 提交请求并等待结果
+    pub async fn send(&self, priority: TaskPriority, request: OutboundRequest) -> Result<TextResponse> {
+        self.enqueue(priority, request)?
+            .await
+            .map_err(|_| anyhow::anyhow!("出站队列已关闭"))?
+    }
+
+    /// 排队中的请求数
+    pub fn pending(&self) -> usize {
+        self.shared.inner.lock().unwrap().pending.len()
+    }
+
+    pub fn stats(&self) -> OutboundStats {
+        self.shared.inner.lock().unwrap().stats.clone()
+    }
+}
+
+impl Drop for OutboundQueue {
+    fn drop(&mut self) {
+        self.dispatcher.abort();
+    }
+}
+
+async fn dispatch(shared: Arc<Shared>) {
+    const GLOBAL_KEY: &str = "outbound";
+    let limit = shared.config.per_host_concurrency.max(1);
+    loop {
+        // 先等到有可以发送的请求，再申请令牌，避免令牌被空等消耗
+        let has_runnable = {
+            let mut inner = shared.inner.lock().unwrap();
+            match inner.take_runnable(limit) {
+                Some((job, permit)) => {
+                    drop(permit);
+                    inner.pending.push(job);
+                    true
+                }
+                None => false,
+            }
+        };
+        if !has_runnable {
+            shared.changed.notified().await;
+            continue;
+        }
+
+        while let Err(retry_after) = shared.limiter.check(GLOBAL_KEY) {
+            tokio::time::sleep(retry_after.max(Duration::from_millis(1))).await;
+        }
+
+        // 等令牌期间可能来了优先级更高的请求，重新挑选
+        let Some((job, permit)) = shared.inner.lock().unwrap().take_runnable(limit) else {
+            continue;
+        };
+        {
+            let mut inner = shared.inner.lock().unwrap();
+            let in_flight = limit - inner.host_semaphore(&job.host, limit).available_permits();
+            let peak = inner.stats.peak_in_flight.entry(job.host.clone()).or_insert(0);
+            *peak = (*peak).max(in_flight);
+        }
+
+        let shared = Arc::clone(&shared);
+        tokio::spawn(async move {
+            let result = match &job.request {
+                OutboundRequest::Get(url) => shared.client.get_text(url).await,
+                OutboundRequest::Post(url, body) => shared.client.post_json(url, body).await,
+            };
+            drop(permit);
+            {
+                let mut inner = shared.inner.lock().unwrap();
+                if result.is_ok() {
+                    inner.stats.completed += 1;
+                } else {
+                    inner.stats.failed += 1;
+                }
+            }
+            // 调用方可能已经不再等待结果
+            let _ = job.reply.send(result);
+            shared.changed.notify_one();
+        });
+    }
+}
+
+/// 出站队列示例：两个主机、不同优先级的请求经过同一个限流队列
+pub async fn outbound_example() -> Result<()> {
+    println!("\n=== 出站请求队列示例 ===");
+
+    let first = TestServer::start().await?;
+    let second = TestServer::start().await?;
+    let queue = OutboundQueue::new(
+        AsyncHttpClient::with_timeout(Duration::from_secs(5)),
+        OutboundConfig { requests_per_second: 20.0, burst: 2, per_host_concurrency: 2, max_pending: Some(100) },
+    );
+
+    // 按完成顺序打印，最后提交的 Critical 请求会插到排队的 Normal 请求前面
+    let start = tokio::time::Instant::now();
+    let mut pending = FuturesUnordered::new();
+    for i in 0..6 {
+        let server = if i % 2 == 0 { &first } else { &second };
+        let priority = if i == 5 { TaskPriority::Critical } else { TaskPriority::Normal };
+        let receiver = queue.enqueue(priority, OutboundRequest::Get(server.url(&format!("/anything/{}", i))))?;
+        pending.push(async move { (i, priority, receiver.await) });
+    }
+    println!("已提交 6 个请求，排队中 {} 个", queue.pending());
+    while let Some((i, priority, result)) = pending.next().await {
+        let response = result??;
+        println!("请求 {} ({:?}) -> {}，{:?} 后完成", i, priority, response.status, start.elapsed());
+    }
+
+    // 提交 POST 请求并直接等待结果
+    let response = queue
+        .send(TaskPriority::High, OutboundRequest::Post(first.url("/post"), serde_json::json!({ "name": "august" })))
+        .await?;
+    println!("POST 请求 -> {}", response.status);
+
+    let stats = queue.stats();
+    println!("已完成 {} 个，失败 {} 个，各主机最大并发: {:?}", stats.completed, stats.failed, stats.peak_in_flight);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::tasks::TaskSet;
+    use tokio::time::Instant;
+
+    fn fast_config() -> OutboundConfig {
+        OutboundConfig { requests_per_second: 1000.0, burst: 100, per_host_concurrency: 1, max_pending: None }
+    }
+
+    #[test]
+    fn test_host_key_includes_port() {
+        assert_eq!(host_key("http://127.0.0.1:8080/get"), "127.0.0.1:8080");
+        assert_eq!(host_key("https://httpbin.org/get"), "httpbin.org:443");
+    }
+
+    #[tokio::test]
+    async fn test_higher_priority_dispatched_first() {
+        let server = TestServer::start().await.unwrap();
+        let queue = OutboundQueue::new(AsyncHttpClient::new(), fast_config());
+
+        // 同一主机只允许一个并发，完成顺序就是分发顺序；分发任务在第一次 await 前不会运行
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut tasks = TaskSet::new();
+        for (name, priority) in [("low", TaskPriority::Low), ("normal", TaskPriority::Normal), ("critical", TaskPriority::Critical), ("high", TaskPriority::High)] {
+            let receiver = queue.enqueue(priority, OutboundRequest::Get(server.url("/get"))).unwrap();
+            let order = Arc::clone(&order);
+            tasks.spawn_tracked(name, async move {
+                receiver.await.unwrap().unwrap();
+                order.lock().unwrap().push(name);
+            });
+        }
+        tasks.join_all().await.into_result().unwrap();
+        assert_eq!(*order.lock().unwrap(), vec!["critical", "high", "normal", "low"]);
+        assert_eq!(queue.stats().completed, 4);
+    }
+
+    #[tokio::test]
+    async fn test_global_rate_limit() {
+        let server = TestServer::start().await.unwrap();
+        let config = OutboundConfig { requests_per_second: 20.0, burst: 1, per_host_concurrency: 10, max_pending: None };
+        let queue = OutboundQueue::new(AsyncHttpClient::new(), config);
+
+        let start = Instant::now();
+        let receivers: Vec<_> = (0..5)
+            .map(|_| queue.enqueue(TaskPriority::Normal, OutboundRequest::Get(server.url("/get"))).unwrap())
+            .collect();
+        for receiver in receivers {
+            receiver.await.unwrap().unwrap();
+        }
+        // 第一个请求用掉唯一的令牌，其余四个每 50ms 补充一个
+        assert!(start.elapsed() >= Duration::from_millis(190), "{:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_per_host_cap_and_queue_limit() {
+        let server = TestServer::start().await.unwrap();
+        let config = OutboundConfig { per_host_concurrency: 2, ..fast_config() };
+        let queue = OutboundQueue::new(AsyncHttpClient::new(), config);
+
+        let body = serde_json::json!({ "n": 1 });
+        let results = futures::future::join_all((0..8).map(|i| {
+            let request = if i % 2 == 0 {
+                OutboundRequest::Get(server.url("/get"))
+            } else {
+                OutboundRequest::Post(server.url("/post"), body.clone())
+            };
+            queue.send(TaskPriority::Normal, request)
+        }))
+        .await;
+        assert!(results.iter().all(|r| r.as_ref().is_ok_and(|response| response.status == 200)));
+        let stats = queue.stats();
+        assert_eq!(stats.completed, 8);
+        assert!(stats.peak_in_flight.values().all(|&peak| (1..=2).contains(&peak)), "{:?}", stats.peak_in_flight);
+
+        let limited = OutboundQueue::new(AsyncHttpClient::new(), OutboundConfig { max_pending: Some(1), ..fast_config() });
+        limited.enqueue(TaskPriority::Normal, OutboundRequest::Get(server.url("/get"))).unwrap();
+        assert!(limited.enqueue(TaskPriority::Normal, OutboundRequest::Get(server.url("/get"))).is_err());
+    }
+}
@@ -0,0 +1,339 @@
+//! HTTP 请求录制与回放模块
+//!
+//! `RecordingClient` 包装 `AsyncHttpClient`，第一次运行时把请求和响应录制到
+//! 磁带文件（JSON），之后按顺序回放，依赖网络的示例和测试可以快速、离线、确定地运行：
+//! - 按方法、URL 和请求体匹配录制的交互，同一请求多次出现时按录制顺序依次回放
+//! - 本地地址（127.0.0.1、localhost）匹配时忽略端口，测试服务器每次换端口也能回放
+//! - 模式由 `AUGUST_VCR` 环境变量控制：`auto`（默认）、`record`、`replay`、`off`
+//! - 内部客户端处于离线模拟状态时不录制，避免把模拟响应写进磁带
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use super::http_client::{AsyncHttpClient, TextResponse};
+use crate::utils::test_server::TestServer;
+
+/// 控制录制模式的环境变量
+pub const VCR_ENV: &str = "AUGUST_VCR";
+
+/// 录制模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecordMode {
+    /// 磁带中有就回放，没有就发起真实请求并录制
+    #[default]
+    Auto,
+    /// 忽略已有磁带，全部重新录制
+    Record,
+    /// 只回放，磁带中没有的请求返回错误，不访问网络
+    Replay,
+    /// 直接透传给内部客户端，不读也不写磁带
+    Off,
+}
+
+impl FromStr for RecordMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(RecordMode::Auto),
+            "record" => Ok(RecordMode::Record),
+            "replay" => Ok(RecordMode::Replay),
+            "off" => Ok(RecordMode::Off),
+            other => Err(anyhow::anyhow!("未知的录制模式: {}（可选 auto、record、replay、off）", other)),
+        }
+    }
+}
+
+impl RecordMode {
+    /// 从 `AUGUST_VCR` 读取模式，未设置或无效时使用 `Auto`
+    pub fn from_env() -> Self {
+        std::env::var(VCR_ENV).ok().and_then(|value| value.parse().ok()).unwrap_or_default()
+    }
+}
+
+/// 一次录制的请求和响应
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    /// 归一化后的 URL，见 [`normalize_url`]
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<String>,
+    pub status: u16,
+    pub response_body: String,
+}
+
+impl Interaction {
+    fn matches(&self, method: &str, url: &str, body: Option<&str>) -> bool {
+        self.method == method && self.url == url && self.request_body.as_deref() == body
+    }
+}
+
+/// 磁带文件
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    /// 加载磁带，文件不存在时返回空磁带
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).with_context(|| format!("磁带文件 {} 已损坏", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 保存磁带，先写临时文件再重命名
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let temp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&temp_path, serde_json::to_vec_pretty(self)?).await?;
+        tokio::fs::rename(&temp_path, path).await?;
+        Ok(())
+    }
+
+    /// 第 `nth` 个（从 0 开始）匹配的交互
+    fn find(&self, method: &str, url: &str, body: Option<&str>, nth: usize) -> Option<&Interaction> {
+        self.interactions.iter().filter(|i| i.matches(method, url, body)).nth(nth)
+    }
+
+    /// 最后一个匹配的交互
+    fn find_last(&self, method: &str, url: &str, body: Option<&str>) -> Option<&Interaction> {
+        self.interactions.iter().rev().find(|i| i.matches(method, url, body))
+    }
+}
+
+/// 归一化 URL：本地地址去掉端口，其余原样保留
+pub fn normalize_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) if super::connectivity::is_local_url(url) => {
+            let _ = parsed.set_port(None);
+            parsed.to_string()
+        }
+        _ => url.to_string(),
+    }
+}
+
+/// 录制和回放的次数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecordingStats {
+    pub recorded: usize,
+    pub replayed: usize,
+    /// 透传给内部客户端但没有录制的请求
+    pub passed_through: usize,
+}
+
+struct State {
+    cassette: Cassette,
+    /// 每个请求已经回放到第几个
+    cursors: HashMap<(String, String, Option<String>), usize>,
+    stats: RecordingStats,
+}
+
+/// 录制和回放 HTTP 请求的客户端
+#[derive(Clone)]
+pub struct RecordingClient {
+    inner: AsyncHttpClient,
+    path: PathBuf,
+    mode: RecordMode,
+    state: Arc<Mutex<State>>,
+}
+
+impl RecordingClient {
+    /// 创建客户端，`Record` 模式下从空磁带开始，其余模式加载已有磁带
+    pub fn new(inner: AsyncHttpClient, path: impl Into<PathBuf>, mode: RecordMode) -> Result<Self> {
+        let path = path.into();
+        let cassette = match mode {
+            RecordMode::Record | RecordMode::Off => Cassette::default(),
+            RecordMode::Auto | RecordMode::Replay => Cassette::load(&path)?,
+        };
+        Ok(Self {
+            inner,
+            path,
+            mode,
+            state: Arc::new(Mutex::new(State {
+                cassette,
+                cursors: HashMap::new(),
+                stats: RecordingStats::default(),
+            })),
+        })
+    }
+
+    pub fn mode(&self) -> RecordMode {
+        self.mode
+    }
+
+    /// 磁带文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn stats(&self) -> RecordingStats {
+        self.state.lock().unwrap().stats
+    }
+
+    /// 发起 GET 请求
+    pub async fn get_text(&self, url: &str) -> Result<TextResponse> {
+        self.execute("GET", url, None, || self.inner.get_text(url)).await
+    }
+
+    /// 以 JSON 请求体发起 POST 请求
+    pub async fn post_json<T: Serialize + ?Sized>(&self, url: &str, body: &T) -> Result<TextResponse> {
+        let payload = serde_json::to_string(body)?;
+        self.execute("POST", url, Some(payload), || self.inner.post_json(url, body)).await
+    }
+
+    async fn execute<F, Fut>(&self, method: &str, url: &str, body: Option<String>, send: F) -> Result<TextResponse>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<TextResponse>>,
+    {
+        if self.mode == RecordMode::Off {
+            self.state.lock().unwrap().stats.passed_through += 1;
+            return send().await;
+        }
+
+        let key = (method.to_string(), normalize_url(url), body);
+        if let Some(response) = self.replay(&key) {
+            return Ok(response);
+        }
+        if self.mode == RecordMode::Replay {
+            return Err(anyhow::anyhow!("磁带 {} 中没有 {} {} 的录制", self.path.display(), method, url));
+        }
+
+        let response = send().await?;
+        // 离线时外部地址拿到的是模拟响应，不能写进磁带
+        if self.inner.is_mock() && !super::connectivity::is_local_url(url) {
+            self.state.lock().unwrap().stats.passed_through += 1;
+            return Ok(response);
+        }
+        self.record(key, &response).await?;
+        Ok(response)
+    }
+
+    /// 回放下一个匹配的交互；`Replay` 模式下录制用完后重复最后一个
+    fn replay(&self, key: &(String, String, Option<String>)) -> Option<TextResponse> {
+        let mut state = self.state.lock().unwrap();
+        let State { cassette, cursors, stats } = &mut *state;
+        let (method, url, body) = key;
+        let cursor = cursors.entry(key.clone()).or_insert(0);
+        let interaction = match cassette.find(method, url, body.as_deref(), *cursor) {
+            Some(interaction) => interaction,
+            None if self.mode == RecordMode::Replay => cassette.find_last(method, url, body.as_deref())?,
+            None => return None,
+        };
+        *cursor += 1;
+        stats.replayed += 1;
+        Some(TextResponse { status: interaction.status, body: interaction.response_body.clone() })
+    }
+
+    /// 追加录制并立即写回磁带文件
+    async fn record(&self, key: (String, String, Option<String>), response: &TextResponse) -> Result<()> {
+        let snapshot = {
+            let mut state = self.state.lock().unwrap();
+            let (method, url, request_body) = key.clone();
+            state.cassette.interactions.push(Interaction {
+                method,
+                url,
+                request_body,
+                status: response.status,
+                response_body: response.body.clone(),
+            });
+            // 新录制的交互算作已回放，避免同一次运行中被重复使用
+            *state.cursors.entry(key).or_insert(0) += 1;
+            state.stats.recorded += 1;
+            state.cassette.clone()
+        };
+        snapshot.save(&self.path).await
+    }
+}
+
+/// 录制回放示例：第一次运行访问本地测试服务器并录制，之后从磁带回放
+pub async fn recording_example() -> Result<()> {
+    println!("\n=== HTTP录制回放示例 ===");
+
+    let server = TestServer::start().await?;
+    let path = std::env::temp_dir().join("august-code-cassettes").join("test_server.json");
+    let client = RecordingClient::new(AsyncHttpClient::new(), &path, RecordMode::from_env())?;
+    println!("磁带: {}，模式: {:?}", client.path().display(), client.mode());
+
+    let response = client.get_text(&server.url("/get")).await?;
+    println!("GET /get -> {}（{} 字节）", response.status, response.body.len());
+    let response = client.post_json(&server.url("/post"), &serde_json::json!({ "name": "Rust Async" })).await?;
+    println!("POST /post -> {}（{} 字节）", response.status, response.body.len());
+
+    let stats = client.stats();
+    println!("录制 {} 次，回放 {} 次，透传 {} 次", stats.recorded, stats.replayed, stats.passed_through);
+    server.shutdown().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cassette(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("august-code-vcr-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("cassette.json")
+    }
+
+    #[test]
+    fn test_normalize_url_drops_local_port_only() {
+        assert_eq!(normalize_url("http://127.0.0.1:34567/get?a=1"), "http://127.0.0.1/get?a=1");
+        assert_eq!(normalize_url("https://httpbin.org:8443/get"), "https://httpbin.org:8443/get");
+        assert_eq!("Replay".parse::<RecordMode>().unwrap(), RecordMode::Replay);
+        assert!("tape".parse::<RecordMode>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay_without_server() {
+        let path = temp_cassette("replay");
+        let server = TestServer::start().await.unwrap();
+        let recorder = RecordingClient::new(AsyncHttpClient::new(), &path, RecordMode::Auto).unwrap();
+        let first = recorder.post_json(&server.url("/api/users"), &serde_json::json!({ "name": "张三", "email": "a@b.c" })).await.unwrap();
+        let second = recorder.post_json(&server.url("/api/users"), &serde_json::json!({ "name": "张三", "email": "a@b.c" })).await.unwrap();
+        let listed = recorder.get_text(&server.url("/api/users")).await.unwrap();
+        assert_eq!(recorder.stats().recorded, 3);
+        server.shutdown().await;
+
+        // 服务器已关闭，换一个端口也能按顺序回放
+        let player = RecordingClient::new(AsyncHttpClient::new(), &path, RecordMode::Replay).unwrap();
+        let url = "http://127.0.0.1:1/api/users";
+        let body = serde_json::json!({ "name": "张三", "email": "a@b.c" });
+        assert_eq!(player.post_json(url, &body).await.unwrap(), first);
+        assert_eq!(player.post_json(url, &body).await.unwrap(), second);
+        assert_ne!(first.body, second.body);
+        assert_eq!(player.get_text(url).await.unwrap(), listed);
+        assert_eq!(player.stats(), RecordingStats { recorded: 0, replayed: 3, passed_through: 0 });
+
+        // 回放模式下没有录制的请求直接报错
+        assert!(player.get_text("http://127.0.0.1:1/get").await.is_err());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_record_mode_overwrites_cassette() {
+        let path = temp_cassette("overwrite");
+        let server = TestServer::start().await.unwrap();
+        for _ in 0..2 {
+            let client = RecordingClient::new(AsyncHttpClient::new(), &path, RecordMode::Record).unwrap();
+            client.get_text(&server.url("/get")).await.unwrap();
+        }
+        assert_eq!(Cassette::load(&path).unwrap().interactions.len(), 1);
+
+        // auto 模式命中磁带时不再访问服务器
+        server.shutdown().await;
+        let client = RecordingClient::new(AsyncHttpClient::new(), &path, RecordMode::Auto).unwrap();
+        assert_eq!(client.get_text("http://127.0.0.1:9/get").await.unwrap().status, 200);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,399 @@
+//! 读副本和一致性模式模块
+//!
+//! 用一个主库和若干异步复制的只读副本演示一致性取舍：
+//! - 所有写操作都在主库上按顺序执行，每次写入得到一个递增的 `WriteToken`
+//! - 副本按各自的复制延迟异步应用写入，读副本可能读到旧数据
+//! - 读偏好：主库（强一致、慢）、最近副本（快、可能过期）、
+//!   轮询副本（最终一致）、读己之写（副本跟上写入位置才读副本，否则读主库）
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::time::Instant;
+
+use super::database::{AsyncDatabase, User};
+
+/// 写入位置，读己之写时用来判断副本是否已经追上
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct WriteToken(pub u64);
+
+/// 读偏好
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReadPreference {
+    /// 总是读主库
+    Primary,
+    /// 读访问延迟最低的副本
+    Nearest,
+    /// 在副本间轮询
+    Eventual,
+    /// 读最近的、已经应用到该写入位置的副本，都没追上时读主库
+    ReadYourWrites(WriteToken),
+}
+
+/// 副本配置
+#[derive(Debug, Clone)]
+pub struct ReplicaConfig {
+    pub name: String,
+    /// 读取该副本的网络延迟
+    pub latency: Duration,
+    /// 写入复制到该副本的延迟
+    pub lag: Duration,
+}
+
+impl ReplicaConfig {
+    pub fn new(name: &str, latency: Duration, lag: Duration) -> Self {
+        Self {
+            name: name.to_string(),
+            latency,
+            lag,
+        }
+    }
+}
+
+/// 读取结果及实际提供数据的节点
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadResult {
+    pub user: Option<User>,
+    pub served_by: String,
+}
+
+/// 副本复制状态
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplicaStatus {
+    pub name: String,
+    pub applied: WriteToken,
+    /// 落后主库的写入次数
+    pub behind: u64,
+}
+
+/// 复制到副本的变更
+#[derive(Debug, Clone)]
+enum Change {
+    Upsert(User),
+    Delete(String),
+}
+
+struct Replica {
+    config: ReplicaConfig,
+    data: RwLock<HashMap<String, User>>,
+    applied: AtomicU64,
+}
+
+/// 主库加异步复制副本
+pub struct ReplicatedDatabase {
+    primary: AsyncDatabase,
+    primary_latency: Duration,
+    replicas: Vec<Arc<Replica>>,
+    senders: Vec<mpsc::UnboundedSender<(u64, Instant, Change)>>,
+    /// 已提交的写入位置，持有锁期间执行写入以保证顺序
+    committed: Mutex<u64>,
+    next_replica: AtomicUsize,
+}
+
+impl ReplicatedDatabase {
+    /// 创建主库和副本，并为每个副本启动复制任务
+    pub fn new(primary_latency: Duration, replicas: Vec<ReplicaConfig>) -> Self {
+        let mut handles = Vec::new();
+        let mut senders = Vec::new();
+        for config in replicas {
+            let replica = Arc::new(Replica {
+                config,
+                data: RwLock::new(HashMap::new()),
+                applied: AtomicU64::new(0),
+            });
+            let (sender, receiver) = mpsc::unbounded_channel();
+            tokio::spawn(replicate(replica.clone(), receiver));
+            handles.push(replica);
+            senders.push(sender);
+        }
+
+        Self {
+            primary: AsyncDatabase::new(),
+            primary_latency,
+            replicas: handles,
+            senders,
+            committed: Mutex::new(0),
+            next_replica: AtomicUsize::new(0),
+        }
+    }
+
+    /// 主库
+    pub fn primary(&self) -> &AsyncDatabase {
+        &self.primary
+    }
+
+    /// 在主库创建用户
+    pub async fn create_user(&self, user: User) -> Result<WriteToken> {
+        let id = user.id.clone();
+        let mut committed = self.committed.lock().await;
+        self.primary.create_user(user).await?;
+        let stored = self
+            .primary
+            .find_user(&id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("用户 {} 写入后不存在", id))?;
+        Ok(self.publish(&mut committed, Change::Upsert(stored)))
+    }
+
+    /// 在主库更新用户，版本冲突时返回错误
+    pub async fn update_user(&self, user: User) -> Result<WriteToken> {
+        let mut committed = self.committed.lock().await;
+        let id = user.id.clone();
+        let stored = self.primary.compare_and_swap(&id, user.version, user).await?;
+        Ok(self.publish(&mut committed, Change::Upsert(stored)))
+    }
+
+    /// 在主库删除用户
+    pub async fn delete_user(&self, id: &str) -> Result<WriteToken> {
+        let mut committed = self.committed.lock().await;
+        self.primary.delete_user(id).await?;
+        Ok(self.publish(&mut committed, Change::Delete(id.to_string())))
+    }
+
+    /// 分配写入位置并发送给所有副本
+    fn publish(&self, committed: &mut u64, change: Change) -> WriteToken {
+        *committed += 1;
+        let now = Instant::now();
+        for sender in &self.senders {
+            let _ = sender.send((*committed, now, change.clone()));
+        }
+        WriteToken(*committed)
+    }
+
+    /// 按读偏好查询用户
+    pub async fn find_user(&self, id: &str, preference: ReadPreference) -> Result<ReadResult> {
+        let replica = match preference {
+            ReadPreference::Primary => None,
+            ReadPreference::Nearest => self.nearest(|_| true),
+            ReadPreference::Eventual if self.replicas.is_empty() => None,
+            ReadPreference::Eventual => {
+                let index = self.next_replica.fetch_add(1, Ordering::Relaxed) % self.replicas.len();
+                Some(self.replicas[index].clone())
+            }
+            ReadPreference::ReadYourWrites(token) => {
+                self.nearest(|replica| replica.applied.load(Ordering::SeqCst) >= token.0)
+            }
+        };
+
+        match replica {
+            Some(replica) => {
+                tokio::time::sleep(replica.config.latency).await;
+                let user = replica.data.read().await.get(id).cloned();
+                Ok(ReadResult {
+                    user,
+                    served_by: replica.config.name.clone(),
+                })
+            }
+            None => {
+                tokio::time::sleep(self.primary_latency).await;
+                Ok(ReadResult {
+                    user: self.primary.find_user(id).await?,
+                    served_by: "primary".to_string(),
+                })
+            }
+        }
+    }
+
+    fn nearest(&self, eligible: impl Fn(&Replica) -> bool) -> Option<Arc<Replica>> {
+        self.replicas
+            .iter()
+            .filter(|replica| eligible(replica))
+            .min_by_key(|replica| replica.config.latency)
+            .cloned()
+    }
+
+    /// 各副本的复制状态
+    pub async fn replica_status(&self) -> Vec<ReplicaStatus> {
+        let committed = *self.committed.lock().await;
+        self.replicas
+            .iter()
+            .map(|replica| {
+                let applied = replica.applied.load(Ordering::SeqCst);
+                ReplicaStatus {
+                    name: replica.config.name.clone(),
+                    applied: WriteToken(applied),
+                    behind: committed.saturating_sub(applied),
+                }
+            })
+            .collect()
+    }
+
+    /// 等待所有副本应用到 `token`，超时返回错误
+    pub async fn wait_for_replication(&self, token: WriteToken, timeout: Duration) -> Result<()> {
+        tokio::time::timeout(timeout, async {
+            while self
+                .replicas
+                .iter()
+                .any(|replica| replica.applied.load(Ordering::SeqCst) < token.0)
+            {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .map_err(|_| anyhow::anyhow!("等待副本复制到 {:?} 超时", token))
+    }
+}
+
+/// 复制任务：按写入顺序在 `lag` 之后应用变更，发送端全部丢弃时退出
+async fn replicate(replica: Arc<Replica>, mut changes: mpsc::UnboundedReceiver<(u64, Instant, Change)>) {
+    while let Some((seq, committed_at, change)) = changes.recv().await {
+        tokio::time::sleep_until(committed_at + replica.config.lag).await;
+        let mut data = replica.data.write().await;
+        match change {
+            Change::Upsert(user) => {
+                data.insert(user.id.clone(), user);
+            }
+            Change::Delete(id) => {
+                data.remove(&id);
+            }
+        }
+        replica.applied.store(seq, Ordering::SeqCst);
+    }
+}
+
+/// 读副本示例：最近副本读到旧数据，读己之写总能看到自己的修改
+pub async fn replication_example() -> Result<()> {
+    println!("\n=== 读副本与一致性示例 ===");
+
+    let db = ReplicatedDatabase::new(Duration::from_millis(20), vec![
+        ReplicaConfig::new("replica-near", Duration::from_millis(2), Duration::from_millis(150)),
+        ReplicaConfig::new("replica-far", Duration::from_millis(10), Duration::from_millis(50)),
+    ]);
+
+    let token = db
+        .create_user(User {
+            id: "1".to_string(),
+            name: "张三".to_string(),
+            email: "zhangsan@example.com".to_string(),
+            created_at: 1234567890,
+            version: 0,
+        })
+        .await?;
+
+    for preference in [
+        ReadPreference::Primary,
+        ReadPreference::Nearest,
+        ReadPreference::Eventual,
+        ReadPreference::ReadYourWrites(token),
+    ] {
+        let start = Instant::now();
+        let result = db.find_user("1", preference).await?;
+        println!(
+            "{:?}: 由 {} 提供，{}，耗时 {:?}",
+            preference,
+            result.served_by,
+            if result.user.is_some() { "读到新数据" } else { "读到旧数据" },
+            start.elapsed()
+        );
+    }
+    println!("复制状态: {:?}", db.replica_status().await);
+
+    // 较远的副本先追上，读己之写改为读它
+    tokio::time::sleep(Duration::from_millis(80)).await;
+    let result = db.find_user("1", ReadPreference::ReadYourWrites(token)).await?;
+    println!("80ms 后读己之写由 {} 提供", result.served_by);
+
+    db.wait_for_replication(token, Duration::from_secs(1)).await?;
+    let result = db.find_user("1", ReadPreference::Nearest).await?;
+    println!("全部副本追上后最近副本读到: {:?}", result.user.map(|user| user.name));
+
+    // 更新和删除同样先写主库，再异步复制到副本
+    let mut user = db
+        .primary()
+        .find_user("1")
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("用户 1 不存在"))?;
+    user.name = "张三（已改名）".to_string();
+    let token = db.update_user(user).await?;
+    db.wait_for_replication(token, Duration::from_secs(1)).await?;
+    let result = db.find_user("1", ReadPreference::Nearest).await?;
+    println!("更新复制完成后最近副本读到: {:?}", result.user.map(|user| user.name));
+
+    let token = db.delete_user("1").await?;
+    db.wait_for_replication(token, Duration::from_secs(1)).await?;
+    let result = db.find_user("1", ReadPreference::Eventual).await?;
+    println!("删除复制完成后 {} 上{}", result.served_by, if result.user.is_some() { "仍有用户 1" } else { "已没有用户 1" });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: &str) -> User {
+        User {
+            id: id.to_string(),
+            name: format!("用户{}", id),
+            email: format!("{}@example.com", id),
+            created_at: 0,
+            version: 0,
+        }
+    }
+
+    fn database() -> ReplicatedDatabase {
+        ReplicatedDatabase::new(Duration::from_millis(20), vec![
+            ReplicaConfig::new("near", Duration::from_millis(1), Duration::from_millis(100)),
+            ReplicaConfig::new("far", Duration::from_millis(5), Duration::from_millis(30)),
+        ])
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stale_reads_versus_read_your_writes() {
+        let db = database();
+        let token = db.create_user(user("1")).await.unwrap();
+
+        let nearest = db.find_user("1", ReadPreference::Nearest).await.unwrap();
+        assert_eq!(nearest.served_by, "near");
+        assert!(nearest.user.is_none());
+
+        let own = db.find_user("1", ReadPreference::ReadYourWrites(token)).await.unwrap();
+        assert_eq!(own.served_by, "primary");
+        assert!(own.user.is_some());
+
+        // far 副本 30ms 后追上，near 副本仍然落后
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        let own = db.find_user("1", ReadPreference::ReadYourWrites(token)).await.unwrap();
+        assert_eq!(own.served_by, "far");
+        assert!(own.user.is_some());
+        let status = db.replica_status().await;
+        assert_eq!(status[0].behind, 1);
+        assert_eq!(status[1].behind, 0);
+
+        db.wait_for_replication(token, Duration::from_secs(1)).await.unwrap();
+        assert!(db.find_user("1", ReadPreference::Nearest).await.unwrap().user.is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_replicas_apply_changes_in_order() {
+        let db = database();
+        db.create_user(user("1")).await.unwrap();
+        let mut updated = user("1");
+        updated.name = "已更新".to_string();
+        db.update_user(updated).await.unwrap();
+        db.create_user(user("2")).await.unwrap();
+        let token = db.delete_user("2").await.unwrap();
+        assert_eq!(token, WriteToken(4));
+
+        db.wait_for_replication(token, Duration::from_secs(1)).await.unwrap();
+        for _ in 0..2 {
+            let result = db.find_user("1", ReadPreference::Eventual).await.unwrap();
+            let replicated = result.user.unwrap();
+            assert_eq!(replicated.name, "已更新");
+            assert_eq!(replicated.version, 1);
+            assert!(db.find_user("2", ReadPreference::Eventual).await.unwrap().user.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_without_replicas_reads_go_to_primary() {
+        let db = ReplicatedDatabase::new(Duration::ZERO, Vec::new());
+        db.create_user(user("1")).await.unwrap();
+        for preference in [ReadPreference::Nearest, ReadPreference::Eventual] {
+            let result = db.find_user("1", preference).await.unwrap();
+            assert_eq!(result.served_by, "primary");
+        }
+    }
+}
@@ -0,0 +1,466 @@
+//! DNS-over-HTTPS 解析器模块
+//!
+//! 通过 DoH JSON 接口解析主机名，演示分层的异步服务：
+//! - 第一层：带 TTL 的内存缓存
+//! - 第二层：可配置的 DoH 服务商（Cloudflare、Google 或自定义地址）
+//! - 第三层：DoH 失败或离线时回退到系统解析
+//! - 实现 `reqwest::dns::Resolve`，可作为 `AsyncHttpClient` 的解析器
+
+use anyhow::Result;
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use super::connectivity;
+use super::http_client::AsyncHttpClient;
+use crate::utils::test_server::TestServer;
+
+/// A 记录类型
+const RECORD_A: u16 = 1;
+/// AAAA 记录类型
+const RECORD_AAAA: u16 = 28;
+
+/// DoH 服务商
+#[derive(Debug, Clone, PartialEq)]
+pub struct DohProvider {
+    pub name: String,
+    /// JSON 查询接口地址
+    pub url: String,
+}
+
+impl DohProvider {
+    pub fn cloudflare() -> Self {
+        Self::custom("Cloudflare", "https://cloudflare-dns.com/dns-query")
+    }
+
+    pub fn google() -> Self {
+        Self::custom("Google", "https://dns.google/resolve")
+    }
+
+    /// 自定义服务商，`url` 需要支持 `?name=&type=` 形式的 JSON 查询
+    pub fn custom(name: &str, url: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            url: url.to_string(),
+        }
+    }
+}
+
+/// 解析器配置
+#[derive(Debug, Clone)]
+pub struct ResolverConfig {
+    pub provider: DohProvider,
+    /// 单次 DoH 查询超时
+    pub timeout: Duration,
+    /// 缓存时间下限，避免 TTL 为 0 的记录每次都重新查询
+    pub min_ttl: Duration,
+    /// 缓存时间上限
+    pub max_ttl: Duration,
+    /// 系统解析结果的缓存时间
+    pub system_ttl: Duration,
+    /// DoH 失败时是否回退到系统解析
+    pub fallback_to_system: bool,
+}
+
+impl Default for ResolverConfig {
+    fn default() -> Self {
+        Self {
+            provider: DohProvider::cloudflare(),
+            timeout: Duration::from_secs(3),
+            min_ttl: Duration::from_secs(5),
+            max_ttl: Duration::from_secs(3600),
+            system_ttl: Duration::from_secs(60),
+            fallback_to_system: true,
+        }
+    }
+}
+
+/// 解析结果的来源
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResolvedBy {
+    Literal,
+    Cache,
+    Doh,
+    System,
+}
+
+/// 解析结果
+#[derive(Debug, Clone)]
+pub struct Resolution {
+    pub addrs: Vec<IpAddr>,
+    pub source: ResolvedBy,
+    /// 剩余缓存时间
+    pub ttl: Duration,
+}
+
+/// 解析器统计
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResolverStats {
+    pub cache_hits: u64,
+    pub doh_queries: u64,
+    pub doh_failures: u64,
+    pub system_fallbacks: u64,
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    addrs: Vec<IpAddr>,
+    expires_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    cache_hits: AtomicU64,
+    doh_queries: AtomicU64,
+    doh_failures: AtomicU64,
+    system_fallbacks: AtomicU64,
+}
+
+/// DoH JSON 响应
+#[derive(Debug, Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Status")]
+    status: u32,
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DohAnswer {
+    #[serde(rename = "type")]
+    record_type: u16,
+    #[serde(rename = "TTL")]
+    ttl: u64,
+    data: String,
+}
+
+/// 带缓存和系统回退的 DoH 解析器，克隆后共享缓存
+#[derive(Debug, Clone)]
+pub struct DohResolver {
+    config: ResolverConfig,
+    /// 查询 DoH 使用的客户端，本身使用系统解析，避免递归
+    http: reqwest::Client,
+    cache: Arc<Mutex<HashMap<String, CacheEntry>>>,
+    counters: Arc<Counters>,
+}
+
+impl DohResolver {
+    /// 使用默认配置（Cloudflare）创建解析器
+    pub fn new() -> Self {
+        Self::with_config(ResolverConfig::default())
+    }
+
+    /// 使用指定配置创建解析器
+    pub fn with_config(config: ResolverConfig) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            counters: Arc::new(Counters::default()),
+        }
+    }
+
+    /// 当前服务商
+    pub fn provider(&self) -> &DohProvider {
+        &self.config.provider
+    }
+
+    /// 解析主机名：IP 字面量直接返回，然后依次尝试缓存、DoH 和系统解析
+    pub async fn resolve_host(&self, host: &str) -> Result<Resolution> {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        let literal = host.trim_start_matches('[').trim_end_matches(']');
+        if let Ok(ip) = literal.parse::<IpAddr>() {
+            return Ok(Resolution {
+                addrs: vec![ip],
+                source: ResolvedBy::Literal,
+                ttl: Duration::MAX,
+            });
+        }
+
+        if let Some(resolution) = self.cached(&host) {
+            self.counters.cache_hits.fetch_add(1, Ordering::SeqCst);
+            return Ok(resolution);
+        }
+
+        // 离线时 DoH 服务商不可达，直接使用系统解析
+        let doh_error = if connectivity::is_offline() {
+            anyhow::anyhow!("离线模式下跳过 DoH 查询")
+        } else {
+            self.counters.doh_queries.fetch_add(1, Ordering::SeqCst);
+            match self.query_doh(&host).await {
+                Ok((addrs, ttl)) => return Ok(self.store(&host, addrs, ttl, ResolvedBy::Doh)),
+                Err(e) => {
+                    self.counters.doh_failures.fetch_add(1, Ordering::SeqCst);
+                    e
+                }
+            }
+        };
+
+        if !self.config.fallback_to_system {
+            return Err(doh_error.context(format!("DoH 解析 {} 失败", host)));
+        }
+        self.counters.system_fallbacks.fetch_add(1, Ordering::SeqCst);
+        let addrs = system_lookup(&host)
+            .await
+            .map_err(|e| e.context(format!("DoH 失败（{}）后系统解析 {} 也失败", doh_error, host)))?;
+        Ok(self.store(&host, addrs, self.config.system_ttl, ResolvedBy::System))
+    }
+
+    /// 同时查询 A 和 AAAA 记录，返回地址和最小 TTL
+    async fn query_doh(&self, host: &str) -> Result<(Vec<IpAddr>, Duration)> {
+        let (v4, v6) = tokio::join!(
+            self.query_record(host, RECORD_A),
+            self.query_record(host, RECORD_AAAA)
+        );
+        // A 记录是主要结果，AAAA 查询失败不影响整体
+        let (mut addrs, mut ttl) = v4?;
+        if let Ok((v6_addrs, v6_ttl)) = v6 {
+            if !v6_addrs.is_empty() {
+                ttl = ttl.min(v6_ttl);
+                addrs.extend(v6_addrs);
+            }
+        }
+        if addrs.is_empty() {
+            return Err(anyhow::anyhow!("{} 没有 A/AAAA 记录", host));
+        }
+        let ttl = ttl.clamp(self.config.min_ttl, self.config.max_ttl);
+        Ok((addrs, ttl))
+    }
+
+    async fn query_record(&self, host: &str, record_type: u16) -> Result<(Vec<IpAddr>, Duration)> {
+        let response = self
+            .http
+            .get(&self.config.provider.url)
+            .query(&[("name", host), ("type", &record_type.to_string())])
+            .header(reqwest::header::ACCEPT, "application/dns-json")
+            .timeout(self.config.timeout)
+            .send()
+            .await?
+            .error_for_status()?;
+        parse_doh_response(&response.text().await?, record_type)
+    }
+
+    fn cached(&self, host: &str) -> Option<Resolution> {
+        let mut cache = self.cache.lock().unwrap();
+        let entry = cache.get(host)?;
+        let now = Instant::now();
+        if entry.expires_at <= now {
+            cache.remove(host);
+            return None;
+        }
+        Some(Resolution {
+            addrs: entry.addrs.clone(),
+            source: ResolvedBy::Cache,
+            ttl: entry.expires_at - now,
+        })
+    }
+
+    fn store(&self, host: &str, addrs: Vec<IpAddr>, ttl: Duration, source: ResolvedBy) -> Resolution {
+        self.cache.lock().unwrap().insert(
+            host.to_string(),
+            CacheEntry {
+                addrs: addrs.clone(),
+                expires_at: Instant::now() + ttl,
+            },
+        );
+        Resolution { addrs, source, ttl }
+    }
+
+    /// 清空缓存
+    pub fn clear_cache(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    /// 获取统计快照
+    pub fn stats(&self) -> ResolverStats {
+        ResolverStats {
+            cache_hits: self.counters.cache_hits.load(Ordering::SeqCst),
+            doh_queries: self.counters.doh_queries.load(Ordering::SeqCst),
+            doh_failures: self.counters.doh_failures.load(Ordering::SeqCst),
+            system_fallbacks: self.counters.system_fallbacks.load(Ordering::SeqCst),
+        }
+    }
+}
+
+impl Default for DohResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Resolve for DohResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let resolution = resolver
+                .resolve_host(name.as_str())
+                .await
+                .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { e.into() })?;
+            // 端口由 reqwest 根据 URL 填充
+            let addrs: Addrs = Box::new(resolution.addrs.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
+
+/// 解析 DoH JSON 响应，只保留指定类型的记录，返回地址和最小 TTL
+fn parse_doh_response(body: &str, record_type: u16) -> Result<(Vec<IpAddr>, Duration)> {
+    let response: DohResponse = serde_json::from_str(body)?;
+    if response.status != 0 {
+        return Err(anyhow::anyhow!("DoH 查询失败，响应码 {}", response.status));
+    }
+
+    let mut addrs = Vec::new();
+    let mut ttl = u64::MAX;
+    // CNAME 等其他类型的记录直接跳过
+    for answer in response.answer.iter().filter(|a| a.record_type == record_type) {
+        addrs.push(answer.data.parse::<IpAddr>()?);
+        ttl = ttl.min(answer.ttl);
+    }
+    let ttl = if addrs.is_empty() { Duration::ZERO } else { Duration::from_secs(ttl) };
+    Ok((addrs, ttl))
+}
+
+/// 使用系统解析器解析主机名
+async fn system_lookup(host: &str) -> Result<Vec<IpAddr>> {
+    let addrs: Vec<IpAddr> = tokio::net::lookup_host((host, 0)).await?.map(|addr| addr.ip()).collect();
+    if addrs.is_empty() {
+        return Err(anyhow::anyhow!("系统解析 {} 没有返回地址", host));
+    }
+    Ok(addrs)
+}
+
+/// DoH 解析器示例：缓存命中、系统回退，以及作为 HTTP 客户端的解析器
+pub async fn resolver_example() -> Result<()> {
+    println!("\n=== DoH 解析器示例 ===");
+
+    // 本地测试服务器提供 DoH 接口，`.test` 域名都解析到 127.0.0.1
+    let server = TestServer::start().await?;
+    let resolver = DohResolver::with_config(ResolverConfig {
+        provider: DohProvider::custom("本地", &server.url("/dns-query")),
+        ..Default::default()
+    });
+    let public: Vec<String> = [DohProvider::cloudflare(), DohProvider::google()].into_iter().map(|p| p.name).collect();
+    println!("使用服务商: {}（公共服务商: {}）", resolver.provider().name, public.join("、"));
+
+    for host in ["api.test", "api.test", "localhost", "127.0.0.1"] {
+        match resolver.resolve_host(host).await {
+            Ok(resolution) => println!(
+                "{} -> {:?}（来源 {:?}，TTL {:?}）",
+                host, resolution.addrs, resolution.source, resolution.ttl
+            ),
+            Err(e) => println!("{} 解析失败: {}", host, e),
+        }
+    }
+
+    // HTTP 客户端通过 DoH 解析 api.test，请求实际发往本地测试服务器
+    let client = AsyncHttpClient::with_resolver(resolver.clone(), Duration::from_secs(5));
+    let url = format!("http://api.test:{}/get", server.addr().port());
+    let response = client.fetch_url(&url).await?;
+    println!("通过 DoH 解析访问 {}: 状态 {}", url, response.status);
+
+    // 清空缓存后再次解析会重新发起 DoH 查询
+    resolver.clear_cache();
+    if let Err(e) = resolver.resolve_host("api.test").await {
+        println!("清空缓存后 api.test 解析失败: {}", e);
+    }
+    println!("解析器统计: {:?}", resolver.stats());
+    server.shutdown().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local_resolver(server: &TestServer) -> DohResolver {
+        DohResolver::with_config(ResolverConfig {
+            provider: DohProvider::custom("本地", &server.url("/dns-query")),
+            min_ttl: Duration::from_millis(0),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_parse_doh_response() {
+        let body = r#"{"Status":0,"Answer":[
+            {"name":"a.example","type":5,"TTL":30,"data":"b.example."},
+            {"name":"b.example","type":1,"TTL":120,"data":"93.184.216.34"},
+            {"name":"b.example","type":1,"TTL":60,"data":"93.184.216.35"}]}"#;
+        let (addrs, ttl) = parse_doh_response(body, RECORD_A).unwrap();
+        assert_eq!(addrs.len(), 2);
+        assert_eq!(ttl, Duration::from_secs(60));
+
+        assert!(parse_doh_response(r#"{"Status":3}"#, RECORD_A).is_err());
+        let (addrs, _) = parse_doh_response(r#"{"Status":0}"#, RECORD_AAAA).unwrap();
+        assert!(addrs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resolves_via_doh_and_caches() {
+        let server = TestServer::start().await.unwrap();
+        let resolver = local_resolver(&server);
+
+        let first = resolver.resolve_host("API.test.").await.unwrap();
+        assert_eq!(first.source, ResolvedBy::Doh);
+        assert_eq!(first.addrs, vec!["127.0.0.1".parse::<IpAddr>().unwrap()]);
+
+        let second = resolver.resolve_host("api.test").await.unwrap();
+        assert_eq!(second.source, ResolvedBy::Cache);
+        assert!(second.ttl <= Duration::from_secs(60));
+
+        let literal = resolver.resolve_host("[::1]").await.unwrap();
+        assert_eq!(literal.source, ResolvedBy::Literal);
+
+        let stats = resolver.stats();
+        assert_eq!(stats.doh_queries, 1);
+        assert_eq!(stats.cache_hits, 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_are_refreshed() {
+        let server = TestServer::start().await.unwrap();
+        let resolver = local_resolver(&server);
+        resolver.store("api.test", vec![], Duration::ZERO, ResolvedBy::Doh);
+
+        let resolution = resolver.resolve_host("api.test").await.unwrap();
+        assert_eq!(resolution.source, ResolvedBy::Doh);
+        assert_eq!(resolver.stats().cache_hits, 0);
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_system_resolver() {
+        let server = TestServer::start().await.unwrap();
+        let resolver = local_resolver(&server);
+
+        // 本地 DoH 对非 .test 域名返回 NXDOMAIN，回退到系统解析
+        let resolution = resolver.resolve_host("localhost").await.unwrap();
+        assert_eq!(resolution.source, ResolvedBy::System);
+        assert!(resolution.addrs.iter().all(|ip| ip.is_loopback()));
+        assert_eq!(resolver.stats().system_fallbacks, 1);
+
+        let strict = DohResolver::with_config(ResolverConfig {
+            fallback_to_system: false,
+            ..resolver.config.clone()
+        });
+        assert!(strict.resolve_host("localhost").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_http_client_uses_resolver() {
+        let server = TestServer::start().await.unwrap();
+        let resolver = local_resolver(&server);
+        let client = AsyncHttpClient::with_resolver(resolver.clone(), Duration::from_secs(5));
+
+        let url = format!("http://service.test:{}/get", server.addr().port());
+        let response = client.fetch_url(&url).await.unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(resolver.stats().doh_queries, 1);
+    }
+}
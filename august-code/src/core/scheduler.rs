@@ -1,15 +1,22 @@
 //! 异步任务调度器模块
 //! 
 //! 提供异步任务调度功能，包括：
-//! - 周期性任务调度
+//! - 周期性任务调度，支持按名称添加后不再关心结果的任务
+//! - 任务取消和关闭
 //! - 一次性任务调度
 //! - 任务队列管理
 //! - 任务优先级管理
+//! - 截止时间和取消传播
+//! - 按注入的时钟计时，测试中可以使用虚拟时间
 
 use anyhow::Result;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
-use tokio::time::{Duration, Instant};
+
+use super::context::Ctx;
+use crate::utils::clock::{system_clock, Clock};
+use crate::utils::tasks::{TaskResults, TaskSet};
 
 /// 任务优先级
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -45,17 +52,24 @@ pub struct TaskInfo {
 /// 异步任务调度器
 pub struct AsyncTaskScheduler {
     tasks: Arc<RwLock<Vec<TaskInfo>>>,
-    running_tasks: Arc<RwLock<Vec<tokio::task::JoinHandle<()>>>>,
+    running_tasks: Arc<RwLock<TaskSet<()>>>,
     task_counter: Arc<RwLock<u64>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl AsyncTaskScheduler {
     /// 创建新的任务调度器
     pub fn new() -> Self {
+        Self::with_clock(system_clock())
+    }
+    
+    /// 创建按指定时钟计时的任务调度器
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
         Self {
             tasks: Arc::new(RwLock::new(Vec::new())),
-            running_tasks: Arc::new(RwLock::new(Vec::new())),
+            running_tasks: Arc::new(RwLock::new(TaskSet::new())),
             task_counter: Arc::new(RwLock::new(0)),
+            clock,
         }
     }
     
@@ -67,6 +81,29 @@ impl AsyncTaskScheduler {
         task: F,
         priority: TaskPriority,
     ) -> Result<String>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        Ok(self.spawn_periodic_with_priority(name, interval, task, priority).await)
+    }
+    
+    /// 添加普通优先级的周期性任务，不关心结果，返回任务 ID
+    ///
+    /// 任务会一直运行，直到调用 `cancel_task` 或 `shutdown`
+    pub async fn spawn_periodic<F>(&self, name: &str, interval: Duration, task: F) -> String
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.spawn_periodic_with_priority(name, interval, task, TaskPriority::Normal).await
+    }
+    
+    async fn spawn_periodic_with_priority<F>(
+        &self,
+        name: &str,
+        interval: Duration,
+        task: F,
+        priority: TaskPriority,
+    ) -> String
     where
         F: Fn() + Send + Sync + 'static,
     {
@@ -76,7 +113,7 @@ impl AsyncTaskScheduler {
             name: name.to_string(),
             priority,
             status: TaskStatus::Pending,
-            created_at: Instant::now(),
+            created_at: self.clock.now(),
             started_at: None,
             completed_at: None,
         };
@@ -89,19 +126,19 @@ impl AsyncTaskScheduler {
         
         // 启动任务
         let tasks = Arc::clone(&self.tasks);
-        let running_tasks = Arc::clone(&self.running_tasks);
         let task_id_clone = task_id.clone();
         let name = name.to_string();
+        let clock = Arc::clone(&self.clock);
         
-        let handle = tokio::spawn(async move {
-            let mut interval_timer = tokio::time::interval(interval);
+        let task = async move {
+            let mut interval_timer = clock.interval(interval);
             
             // 更新任务状态为运行中
             {
                 let mut tasks = tasks.write().await;
                 if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id_clone) {
                     task.status = TaskStatus::Running;
-                    task.started_at = Some(Instant::now());
+                    task.started_at = Some(clock.now());
                 }
             }
             
@@ -110,15 +147,12 @@ impl AsyncTaskScheduler {
                 println!("执行周期性任务: {} (ID: {})", name, task_id_clone);
                 task();
             }
-        });
+        };
         
         // 添加到运行中的任务列表
-        {
-            let mut running_tasks = running_tasks.write().await;
-            running_tasks.push(handle);
-        }
+        self.running_tasks.write().await.spawn_tracked(task_id.clone(), task);
         
-        Ok(task_id)
+        task_id
     }
     
     /// 添加一次性任务
@@ -129,6 +163,23 @@ impl AsyncTaskScheduler {
         task: F,
         priority: TaskPriority,
     ) -> Result<String>
+    where
+        F: FnOnce() + Send + Sync + 'static,
+    {
+        self.add_one_time_task_ctx(&Ctx::background(), name, delay, task, priority).await
+    }
+    
+    /// 在调用方上下文内添加一次性任务
+    ///
+    /// 延迟结束前上下文被取消或超过截止时间时，任务不会执行并被标记为已取消
+    pub async fn add_one_time_task_ctx<F>(
+        &self,
+        ctx: &Ctx,
+        name: &str,
+        delay: Duration,
+        task: F,
+        priority: TaskPriority,
+    ) -> Result<String>
     where
         F: FnOnce() + Send + Sync + 'static,
     {
@@ -138,7 +189,7 @@ impl AsyncTaskScheduler {
             name: name.to_string(),
             priority,
             status: TaskStatus::Pending,
-            created_at: Instant::now(),
+            created_at: self.clock.now(),
             started_at: None,
             completed_at: None,
         };
@@ -151,19 +202,34 @@ impl AsyncTaskScheduler {
         
         // 启动任务
         let tasks = Arc::clone(&self.tasks);
-        let running_tasks = Arc::clone(&self.running_tasks);
         let task_id_clone = task_id.clone();
         let name = name.to_string();
         
-        let handle = tokio::spawn(async move {
-            tokio::time::sleep(delay).await;
+        let ctx = ctx.clone();
+        let clock = Arc::clone(&self.clock);
+        
+        let task = async move {
+            let waited = ctx.run(async {
+                clock.sleep(delay).await;
+                Ok(())
+            }).await;
+            
+            if let Err(e) = waited {
+                println!("一次性任务 {} (ID: {}) 未执行: {}", name, task_id_clone, e);
+                let mut tasks = tasks.write().await;
+                if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id_clone) {
+                    task.status = TaskStatus::Cancelled;
+                    task.completed_at = Some(clock.now());
+                }
+                return;
+            }
             
             // 更新任务状态为运行中
             {
                 let mut tasks = tasks.write().await;
                 if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id_clone) {
                     task.status = TaskStatus::Running;
-                    task.started_at = Some(Instant::now());
+                    task.started_at = Some(clock.now());
                 }
             }
             
@@ -175,16 +241,13 @@ impl AsyncTaskScheduler {
                 let mut tasks = tasks.write().await;
                 if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id_clone) {
                     task.status = TaskStatus::Completed;
-                    task.completed_at = Some(Instant::now());
+                    task.completed_at = Some(clock.now());
                 }
             }
-        });
+        };
         
         // 添加到运行中的任务列表
-        {
-            let mut running_tasks = running_tasks.write().await;
-            running_tasks.push(handle);
-        }
+        self.running_tasks.write().await.spawn_tracked(task_id.clone(), task);
         
         Ok(task_id)
     }
@@ -217,11 +280,49 @@ impl AsyncTaskScheduler {
         tasks.iter().filter(|t| t.status == TaskStatus::Running).count()
     }
     
-    /// 等待所有任务完成
-    pub async fn wait_for_all(&self) {
-        let running_tasks = self.running_tasks.read().await;
-        for handle in running_tasks.iter() {
-            let _ = handle.await;
+    /// 等待所有任务完成，返回各任务的结果，panic 的任务被标记为失败
+    ///
+    /// 周期性任务不会自行结束，需要先调用 `cancel_task` 或 `shutdown`
+    pub async fn wait_for_all(&self) -> TaskResults<()> {
+        let running = std::mem::take(&mut *self.running_tasks.write().await);
+        let results = running.join_all().await;
+        let mut tasks = self.tasks.write().await;
+        for failed in &results.failed {
+            if let Some(task) = tasks.iter_mut().find(|t| t.id == failed.name) {
+                if task.status == TaskStatus::Running {
+                    task.status = TaskStatus::Failed;
+                    task.completed_at = Some(self.clock.now());
+                }
+            }
+        }
+        results
+    }
+    
+    /// 取消任务，任务不存在或已经结束时返回 false
+    pub async fn cancel_task(&self, task_id: &str) -> bool {
+        let cancelled = self.running_tasks.write().await.abort(task_id);
+        if cancelled {
+            self.mark_cancelled(task_id).await;
+        }
+        cancelled
+    }
+    
+    /// 取消所有未结束的任务，返回取消的数量
+    pub async fn shutdown(&self) -> usize {
+        let cancelled = self.running_tasks.write().await.shutdown().await;
+        for task_id in &cancelled {
+            self.mark_cancelled(task_id).await;
+        }
+        cancelled.len()
+    }
+    
+    async fn mark_cancelled(&self, task_id: &str) {
+        let mut tasks = self.tasks.write().await;
+        if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id) {
+            if !matches!(task.status, TaskStatus::Completed | TaskStatus::Failed) {
+                task.status = TaskStatus::Cancelled;
+                task.completed_at = Some(self.clock.now());
+            }
         }
     }
     
@@ -290,8 +391,8 @@ impl TaskQueue {
             return None;
         }
         
-        // 按优先级排序，优先级高的先执行
-        queue.sort_by(|a, b| b.priority.cmp(&a.priority));
+        // 按优先级升序排序，从末尾取出优先级最高的任务
+        queue.sort_by_key(|task| task.priority);
         queue.pop()
     }
     
@@ -314,9 +415,74 @@ impl Default for TaskQueue {
     }
 }
 
+/// 任务调度器示例
+pub async fn scheduler_example() -> Result<()> {
+    println!("\n=== 任务调度器示例 ===");
+    let scheduler = AsyncTaskScheduler::new();
+
+    scheduler.spawn_periodic(
+        "清理任务",
+        Duration::from_secs(1),
+        || {
+            println!("执行清理任务...");
+        },
+    ).await;
+    let heartbeat = scheduler
+        .add_periodic_task("心跳", Duration::from_millis(700), || println!("发送心跳..."), TaskPriority::High)
+        .await?;
+    scheduler
+        .add_one_time_task("生成报表", Duration::from_millis(1500), || println!("报表已生成"), TaskPriority::Low)
+        .await?;
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    let high: Vec<String> = scheduler
+        .get_tasks_by_priority(TaskPriority::High)
+        .await
+        .into_iter()
+        .map(|task| task.name)
+        .collect();
+    println!("运行中的任务: {} 个，高优先级任务: {:?}", scheduler.get_running_task_count().await, high);
+    println!("取消心跳任务: {}", scheduler.cancel_task(&heartbeat).await);
+
+    // 运行调度器 3 秒后停止周期性任务
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let cancelled = scheduler.shutdown().await;
+    println!("已停止 {} 个周期性任务", cancelled);
+    let tasks = scheduler.get_all_tasks().await;
+    for task in &tasks {
+        println!("  {} ({:?}): {:?}，创建于 {:?} 前", task.name, task.priority, task.status, task.created_at.elapsed());
+    }
+
+    // 有上限的任务队列，按优先级出队
+    let queue = TaskQueue::with_max_size(2);
+    for task in tasks {
+        if let Err(e) = queue.enqueue(task.clone()).await {
+            println!("任务 {} 未入队: {}", task.name, e);
+        }
+    }
+    println!("队列中有 {} 个任务", queue.size().await);
+    if let Some(task) = queue.dequeue_by_priority().await {
+        println!("优先级最高的任务: {}", task.name);
+    }
+    while !queue.is_empty().await {
+        if let Some(task) = queue.dequeue().await {
+            println!("取出任务: {}", task.name);
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::clock::MockClock;
+    
+    /// 让出执行权，使被唤醒的任务运行到下一次等待
+    async fn settle() {
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
     
     #[tokio::test]
     async fn test_task_scheduler_creation() {
@@ -347,4 +513,123 @@ mod tests {
         assert!(dequeued.is_some());
         assert!(queue.is_empty().await);
     }
+    
+    #[tokio::test]
+    async fn test_task_queue_dequeues_highest_priority_first() {
+        let queue = TaskQueue::with_max_size(3);
+        for (id, priority) in [("a", TaskPriority::Normal), ("b", TaskPriority::Critical), ("c", TaskPriority::Low)] {
+            queue.enqueue(TaskInfo {
+                id: id.to_string(),
+                name: id.to_string(),
+                priority,
+                status: TaskStatus::Pending,
+                created_at: Instant::now(),
+                started_at: None,
+                completed_at: None,
+            }).await.unwrap();
+        }
+        
+        let order: Vec<TaskPriority> = [
+            queue.dequeue_by_priority().await,
+            queue.dequeue_by_priority().await,
+            queue.dequeue_by_priority().await,
+        ].into_iter().map(|task| task.unwrap().priority).collect();
+        assert_eq!(order, vec![TaskPriority::Critical, TaskPriority::Normal, TaskPriority::Low]);
+        assert!(queue.dequeue_by_priority().await.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_one_time_task_cancelled_by_ctx() {
+        let scheduler = AsyncTaskScheduler::new();
+        let ctx = Ctx::background();
+        let ran = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let ran_clone = Arc::clone(&ran);
+        
+        let task_id = scheduler.add_one_time_task_ctx(
+            &ctx.child(),
+            "取消测试",
+            Duration::from_millis(200),
+            move || ran_clone.store(true, std::sync::atomic::Ordering::SeqCst),
+            TaskPriority::Normal,
+        ).await.unwrap();
+        ctx.cancel();
+        scheduler.wait_for_all().await;
+        
+        assert!(!ran.load(std::sync::atomic::Ordering::SeqCst));
+        let info = scheduler.get_task_info(&task_id).await.unwrap();
+        assert_eq!(info.status, TaskStatus::Cancelled);
+    }
+    
+    #[tokio::test]
+    async fn test_one_time_task_respects_deadline() {
+        let scheduler = AsyncTaskScheduler::new();
+        let ctx = Ctx::with_timeout(Duration::from_millis(20));
+        
+        let late = scheduler.add_one_time_task_ctx(
+            &ctx, "超时任务", Duration::from_millis(200), || {}, TaskPriority::Low,
+        ).await.unwrap();
+        let early = scheduler.add_one_time_task_ctx(
+            &ctx, "及时任务", Duration::from_millis(1), || {}, TaskPriority::High,
+        ).await.unwrap();
+        scheduler.wait_for_all().await;
+        
+        assert_eq!(scheduler.get_task_info(&late).await.unwrap().status, TaskStatus::Cancelled);
+        assert_eq!(scheduler.get_task_info(&early).await.unwrap().status, TaskStatus::Completed);
+    }
+    
+    #[tokio::test]
+    async fn test_tasks_follow_mock_clock() {
+        let clock = MockClock::new();
+        let scheduler = AsyncTaskScheduler::with_clock(Arc::new(clock.clone()));
+        let ticks = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let counter = Arc::clone(&ticks);
+        
+        scheduler.add_periodic_task("心跳", Duration::from_secs(30), move || {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }, TaskPriority::Normal).await.unwrap();
+        let once = scheduler.add_one_time_task("报表", Duration::from_secs(3600), || {}, TaskPriority::Low).await.unwrap();
+        settle().await;
+        assert_eq!(ticks.load(std::sync::atomic::Ordering::SeqCst), 1);
+        
+        // 一小时的虚拟时间立即过去
+        clock.advance(Duration::from_secs(3600));
+        settle().await;
+        assert_eq!(ticks.load(std::sync::atomic::Ordering::SeqCst), 121);
+        let info = scheduler.get_task_info(&once).await.unwrap();
+        assert_eq!(info.status, TaskStatus::Completed);
+        assert_eq!(info.completed_at.unwrap() - info.created_at, Duration::from_secs(3600));
+    }
+    
+    #[tokio::test]
+    async fn test_spawn_periodic_runs_until_cancelled() {
+        let clock = MockClock::new();
+        let scheduler = AsyncTaskScheduler::with_clock(Arc::new(clock.clone()));
+        let ticks = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let counter = Arc::clone(&ticks);
+        
+        let cleanup = scheduler.spawn_periodic("清理任务", Duration::from_secs(1), move || {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }).await;
+        let heartbeat = scheduler.spawn_periodic("心跳", Duration::from_secs(5), || {}).await;
+        settle().await;
+        assert_eq!(scheduler.get_running_task_count().await, 2);
+        assert_eq!(scheduler.get_task_info(&cleanup).await.unwrap().priority, TaskPriority::Normal);
+        
+        clock.advance(Duration::from_secs(2));
+        settle().await;
+        assert_eq!(ticks.load(std::sync::atomic::Ordering::SeqCst), 3);
+        
+        assert!(scheduler.cancel_task(&cleanup).await);
+        assert!(!scheduler.cancel_task(&cleanup).await);
+        clock.advance(Duration::from_secs(10));
+        settle().await;
+        assert_eq!(ticks.load(std::sync::atomic::Ordering::SeqCst), 3);
+        assert_eq!(scheduler.get_task_info(&cleanup).await.unwrap().status, TaskStatus::Cancelled);
+        
+        // 关闭后 wait_for_all 不会被周期性任务卡住
+        assert_eq!(scheduler.shutdown().await, 1);
+        scheduler.wait_for_all().await;
+        assert_eq!(scheduler.get_task_info(&heartbeat).await.unwrap().status, TaskStatus::Cancelled);
+        assert_eq!(scheduler.get_running_task_count().await, 0);
+    }
 }
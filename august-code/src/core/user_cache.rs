@@ -0,0 +1,382 @@
+//! 用户查询缓存模块
+//!
+//! `CachedUserStore` 装饰 `AsyncDatabase`，为 `find_user` 加一层缓存：
+//! - 查询结果（包括“不存在”）按 TTL 缓存
+//! - 订阅数据库变更事件，任何途径的写入都会让对应条目失效
+//! - 同一个 key 的并发未命中只查询一次数据库，其余请求等待同一个结果
+//! - 命中、未命中、合并、失效次数可以写入运行报告
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::OnceCell;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use super::database::{AsyncDatabase, ChangeEvent, User};
+use crate::utils::report;
+
+/// 缓存统计
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// 加入了其他请求正在进行的查询
+    pub coalesced: u64,
+    pub invalidations: u64,
+}
+
+impl CacheStats {
+    /// 命中率，合并的请求也算作命中
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses + self.coalesced;
+        if total == 0 {
+            0.0
+        } else {
+            (self.hits + self.coalesced) as f64 / total as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    user: Option<User>,
+    expires_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    coalesced: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+type InFlight = Arc<OnceCell<Option<User>>>;
+
+#[derive(Debug, Default)]
+struct Shared {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    inflight: Mutex<HashMap<String, InFlight>>,
+    /// 每次失效加 1，查询期间发生过失效的结果不写入缓存
+    generation: AtomicU64,
+    counters: Counters,
+}
+
+impl Shared {
+    fn invalidate(&self, id: &str) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.entries.lock().unwrap().remove(id);
+        self.counters.invalidations.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn invalidate_all(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.entries.lock().unwrap().clear();
+        self.counters.invalidations.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// 带缓存和请求合并的用户查询
+pub struct CachedUserStore {
+    db: AsyncDatabase,
+    ttl: Duration,
+    shared: Arc<Shared>,
+    invalidator: JoinHandle<()>,
+}
+
+impl CachedUserStore {
+    /// 包装数据库，查询结果缓存 `ttl`
+    pub fn new(db: AsyncDatabase, ttl: Duration) -> Self {
+        let shared = Arc::new(Shared::default());
+        let mut changes = db.subscribe_changes();
+        let listener = shared.clone();
+        let invalidator = tokio::spawn(async move {
+            loop {
+                match changes.recv().await {
+                    Ok(ChangeEvent::Created(user)) | Ok(ChangeEvent::Updated(user)) => listener.invalidate(&user.id),
+                    Ok(ChangeEvent::Deleted { id, .. }) => listener.invalidate(&id),
+                    // 错过了部分事件，无法知道哪些条目过期，全部清空
+                    Err(RecvError::Lagged(_)) => listener.invalidate_all(),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        Self {
+            db,
+            ttl,
+            shared,
+            invalidator,
+        }
+    }
+
+    /// 被包装的数据库
+    pub fn database(&self) -> &AsyncDatabase {
+        &self.db
+    }
+
+    /// 查询用户，优先使用缓存
+    pub async fn find_user(&self, id: &str) -> Result<Option<User>> {
+        if let Some(user) = self.cached(id) {
+            self.shared.counters.hits.fetch_add(1, Ordering::SeqCst);
+            return Ok(user);
+        }
+
+        let cell = self
+            .shared
+            .inflight
+            .lock()
+            .unwrap()
+            .entry(id.to_string())
+            .or_default()
+            .clone();
+
+        let mut loaded = false;
+        let result = cell
+            .get_or_try_init(|| async {
+                loaded = true;
+                let generation = self.shared.generation.load(Ordering::SeqCst);
+                let user = self.db.find_user(id).await?;
+                if self.shared.generation.load(Ordering::SeqCst) == generation {
+                    self.shared.entries.lock().unwrap().insert(id.to_string(), CacheEntry {
+                        user: user.clone(),
+                        expires_at: Instant::now() + self.ttl,
+                    });
+                }
+                Ok::<_, anyhow::Error>(user)
+            })
+            .await
+            .cloned();
+
+        if loaded {
+            self.shared.counters.misses.fetch_add(1, Ordering::SeqCst);
+            // 查询结束，后续请求走缓存或重新查询
+            let mut inflight = self.shared.inflight.lock().unwrap();
+            if inflight.get(id).is_some_and(|current| Arc::ptr_eq(current, &cell)) {
+                inflight.remove(id);
+            }
+        } else {
+            self.shared.counters.coalesced.fetch_add(1, Ordering::SeqCst);
+        }
+        result
+    }
+
+    fn cached(&self, id: &str) -> Option<Option<User>> {
+        let mut entries = self.shared.entries.lock().unwrap();
+        let entry = entries.get(id)?;
+        if entry.expires_at <= Instant::now() {
+            entries.remove(id);
+            return None;
+        }
+        Some(entry.user.clone())
+    }
+
+    /// 创建用户并立即让缓存失效
+    pub async fn create_user(&self, user: User) -> Result<()> {
+        let id = user.id.clone();
+        self.db.create_user(user).await?;
+        self.shared.invalidate(&id);
+        Ok(())
+    }
+
+    /// 更新用户并立即让缓存失效
+    pub async fn update_user(&self, user: User) -> Result<()> {
+        let id = user.id.clone();
+        self.db.update_user(user).await?;
+        self.shared.invalidate(&id);
+        Ok(())
+    }
+
+    /// 删除用户并立即让缓存失效
+    pub async fn delete_user(&self, id: &str) -> Result<()> {
+        self.db.delete_user(id).await?;
+        self.shared.invalidate(id);
+        Ok(())
+    }
+
+    /// 获取统计快照
+    pub fn stats(&self) -> CacheStats {
+        let counters = &self.shared.counters;
+        CacheStats {
+            hits: counters.hits.load(Ordering::SeqCst),
+            misses: counters.misses.load(Ordering::SeqCst),
+            coalesced: counters.coalesced.load(Ordering::SeqCst),
+            invalidations: counters.invalidations.load(Ordering::SeqCst),
+        }
+    }
+
+    /// 把统计写入运行报告的 `section`
+    pub fn report_metrics(&self, section: &str) {
+        let stats = self.stats();
+        report::metric(section, "缓存命中", stats.hits as f64);
+        report::metric(section, "缓存未命中", stats.misses as f64);
+        report::metric(section, "合并请求", stats.coalesced as f64);
+        report::metric(section, "失效次数", stats.invalidations as f64);
+        report::metric(section, "命中率", stats.hit_rate());
+    }
+}
+
+impl Drop for CachedUserStore {
+    fn drop(&mut self) {
+        self.invalidator.abort();
+    }
+}
+
+/// 用户缓存示例：并发未命中合并为一次查询，写入后缓存失效
+pub async fn user_cache_example() -> Result<()> {
+    println!("\n=== 用户查询缓存示例 ===");
+
+    let db = AsyncDatabase::new();
+    db.create_user(User {
+        id: "1".to_string(),
+        name: "张三".to_string(),
+        email: "zhangsan@example.com".to_string(),
+        created_at: 1234567890,
+        version: 0,
+    })
+    .await?;
+    let store = Arc::new(CachedUserStore::new(db.clone(), Duration::from_secs(30)));
+
+    // 10 个并发请求同时未命中
+    let handles: Vec<_> = (0..10)
+        .map(|_| {
+            let store = store.clone();
+            tokio::spawn(async move { store.find_user("1").await })
+        })
+        .collect();
+    for handle in handles {
+        handle.await??;
+    }
+    println!("并发查询后: {:?}", store.stats());
+
+    store.find_user("1").await?;
+    println!("再次查询后: {:?}", store.stats());
+
+    // 绕过缓存直接写数据库，变更事件让缓存失效
+    let mut user = db.find_user("1").await?.unwrap();
+    user.name = "张三（已更新）".to_string();
+    let before = store.stats().invalidations;
+    db.update_user(user).await?;
+    while store.stats().invalidations == before {
+        tokio::task::yield_now().await;
+    }
+    println!("更新后查询: {:?}", store.find_user("1").await?.map(|user| user.name));
+
+    // 通过缓存写入时同步失效，不必等待变更事件
+    store.find_user("2").await?;
+    store.create_user(User {
+        id: "2".to_string(),
+        name: "李四".to_string(),
+        email: "lisi@example.com".to_string(),
+        created_at: 1234567890,
+        version: 0,
+    })
+    .await?;
+    println!("创建后查询: {:?}", store.find_user("2").await?.map(|user| user.name));
+    let mut user = store
+        .database()
+        .find_user("2")
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("用户 2 不存在"))?;
+    user.name = "李四（已更新）".to_string();
+    store.update_user(user).await?;
+    println!("更新后查询: {:?}", store.find_user("2").await?.map(|user| user.name));
+    store.delete_user("2").await?;
+    println!("删除后查询: {:?}", store.find_user("2").await?.map(|user| user.name));
+
+    let stats = store.stats();
+    println!("命中率: {:.0}%", stats.hit_rate() * 100.0);
+    store.report_metrics("用户缓存示例");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: &str, name: &str) -> User {
+        User {
+            id: id.to_string(),
+            name: name.to_string(),
+            email: format!("{}@example.com", id),
+            created_at: 0,
+            version: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hits_misses_and_negative_caching() {
+        let db = AsyncDatabase::new();
+        db.create_user(user("1", "张三")).await.unwrap();
+        let store = CachedUserStore::new(db, Duration::from_secs(60));
+
+        assert!(store.find_user("1").await.unwrap().is_some());
+        assert!(store.find_user("1").await.unwrap().is_some());
+        assert!(store.find_user("404").await.unwrap().is_none());
+        assert!(store.find_user("404").await.unwrap().is_none());
+
+        let stats = store.stats();
+        assert_eq!((stats.hits, stats.misses), (2, 2));
+        assert_eq!(stats.hit_rate(), 0.5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_entries_expire_after_ttl() {
+        let db = AsyncDatabase::new();
+        db.create_user(user("1", "张三")).await.unwrap();
+        let store = CachedUserStore::new(db, Duration::from_secs(10));
+
+        store.find_user("1").await.unwrap();
+        tokio::time::advance(Duration::from_secs(11)).await;
+        store.find_user("1").await.unwrap();
+        assert_eq!(store.stats().misses, 2);
+    }
+
+    #[tokio::test]
+    async fn test_change_events_invalidate_entries() {
+        let db = AsyncDatabase::new();
+        db.create_user(user("1", "张三")).await.unwrap();
+        let store = CachedUserStore::new(db.clone(), Duration::from_secs(60));
+        assert_eq!(store.find_user("1").await.unwrap().unwrap().name, "张三");
+
+        db.update_user(user("1", "李四")).await.unwrap();
+        while store.stats().invalidations == 0 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(store.find_user("1").await.unwrap().unwrap().name, "李四");
+
+        store.delete_user("1").await.unwrap();
+        assert!(store.find_user("1").await.unwrap().is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_misses_are_coalesced() {
+        let db = AsyncDatabase::new();
+        db.create_user(user("1", "张三")).await.unwrap();
+        let store = Arc::new(CachedUserStore::new(db, Duration::from_secs(60)));
+        let barrier = Arc::new(tokio::sync::Barrier::new(20));
+
+        let handles: Vec<_> = (0..20)
+            .map(|_| {
+                let store = store.clone();
+                let barrier = barrier.clone();
+                tokio::spawn(async move {
+                    barrier.wait().await;
+                    store.find_user("1").await.unwrap()
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.await.unwrap().is_some());
+        }
+
+        let stats = store.stats();
+        assert_eq!(stats.hits + stats.misses + stats.coalesced, 20);
+        // 每次数据库查询结束后才会移除进行中的记录，之后的请求命中缓存
+        assert_eq!(stats.misses, 1);
+    }
+}
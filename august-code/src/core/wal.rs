@@ -0,0 +1,386 @@
+//! 预写日志持久化模块
+//!
+//! 让内存数据库在重启后恢复数据：
+//! - 每次创建、更新、删除先以一行 JSON 追加到日志文件，再修改内存
+//! - 启动时先加载快照，再按顺序重放日志
+//! - 压缩时把当前数据写入快照文件并清空日志
+//! - 日志最后一行不完整（写入途中崩溃）时忽略该行
+//!
+//! 过期时间以墙上时间（UTC）写入日志和快照，重启后按剩余时长恢复。
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use super::database::User;
+
+/// 日志条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum WalEntry {
+    /// 创建用户，`expires_at` 为空表示永不过期
+    Create {
+        user: User,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expires_at: Option<DateTime<Utc>>,
+    },
+    /// 更新用户，保留原有的过期时间
+    Update { user: User },
+    Delete { id: String },
+    /// 设置或清除已有用户的过期时间
+    Expire {
+        id: String,
+        expires_at: Option<DateTime<Utc>>,
+    },
+}
+
+impl WalEntry {
+    /// 把条目应用到快照
+    pub fn apply(self, snapshot: &mut Snapshot) {
+        match self {
+            WalEntry::Create { user, expires_at } => {
+                set_expiry(&mut snapshot.expirations, &user.id, expires_at);
+                snapshot.users.insert(user.id.clone(), user);
+            }
+            WalEntry::Update { user } => {
+                snapshot.users.insert(user.id.clone(), user);
+            }
+            WalEntry::Delete { id } => {
+                snapshot.users.remove(&id);
+                snapshot.expirations.remove(&id);
+            }
+            WalEntry::Expire { id, expires_at } => {
+                if snapshot.users.contains_key(&id) {
+                    set_expiry(&mut snapshot.expirations, &id, expires_at);
+                }
+            }
+        }
+    }
+}
+
+fn set_expiry(expirations: &mut HashMap<String, DateTime<Utc>>, id: &str, expires_at: Option<DateTime<Utc>>) {
+    match expires_at {
+        Some(expires_at) => expirations.insert(id.to_string(), expires_at),
+        None => expirations.remove(id),
+    };
+}
+
+/// 快照文件内容
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub schema_version: u32,
+    pub users: HashMap<String, User>,
+    /// 会过期的用户及其过期时间
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub expirations: HashMap<String, DateTime<Utc>>,
+}
+
+/// 恢复结果
+#[derive(Debug, Clone, Default)]
+pub struct Recovered {
+    pub snapshot: Snapshot,
+    /// 从日志重放的条目数
+    pub replayed: usize,
+    /// 因不完整被忽略的末尾行数（0 或 1）
+    pub skipped: usize,
+}
+
+/// 追加写入的预写日志
+#[derive(Debug)]
+pub struct WriteAheadLog {
+    snapshot_path: PathBuf,
+    file: Mutex<File>,
+    /// 上次压缩后追加的条目数
+    appended: AtomicU64,
+}
+
+impl WriteAheadLog {
+    /// 日志文件对应的快照文件路径，例如 `users.wal` 对应 `users.snapshot.json`
+    pub fn snapshot_path_for(path: &Path) -> PathBuf {
+        path.with_extension("snapshot.json")
+    }
+
+    /// 以追加模式打开日志文件，不存在时创建
+    pub async fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .with_context(|| format!("无法打开预写日志 {}", path.display()))?;
+        Ok(Self {
+            snapshot_path: Self::snapshot_path_for(path),
+            file: Mutex::new(file),
+            appended: AtomicU64::new(0),
+        })
+    }
+
+    /// 上次压缩后追加的条目数
+    pub fn entries_since_compaction(&self) -> u64 {
+        self.appended.load(Ordering::SeqCst)
+    }
+
+    /// 追加一条日志并同步到磁盘，返回后即使断电也不会丢失
+    pub async fn append(&self, entry: &WalEntry) -> Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        let mut file = self.file.lock().await;
+        write_or_rollback(&mut file, line.as_bytes()).await?;
+        self.appended.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// 一次写入多条日志，整批只同步一次磁盘
+    pub async fn append_all(&self, entries: &[WalEntry]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let mut buffer = String::new();
+        for entry in entries {
+            buffer.push_str(&serde_json::to_string(entry)?);
+            buffer.push('\n');
+        }
+        let mut file = self.file.lock().await;
+        write_or_rollback(&mut file, buffer.as_bytes()).await?;
+        self.appended.fetch_add(entries.len() as u64, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// 加载快照并重放日志，文件都不存在时返回空数据
+    pub async fn load(path: &Path) -> Result<Recovered> {
+        let snapshot_path = Self::snapshot_path_for(path);
+        let snapshot = match tokio::fs::read_to_string(&snapshot_path).await {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("快照文件 {} 已损坏", snapshot_path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Snapshot::default(),
+            Err(e) => return Err(e.into()),
+        };
+
+        // 按字节读取：崩溃可能截断在多字节字符中间，整体按 UTF-8 读取会直接失败
+        let log = match tokio::fs::read(path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut recovered = Recovered { snapshot, ..Default::default() };
+        let lines: Vec<&[u8]> = log
+            .split(|&byte| byte == b'\n')
+            .filter(|line| !line.trim_ascii().is_empty())
+            .collect();
+        for (index, line) in lines.iter().enumerate() {
+            match serde_json::from_slice::<WalEntry>(line) {
+                Ok(entry) => {
+                    entry.apply(&mut recovered.snapshot);
+                    recovered.replayed += 1;
+                }
+                // 只有最后一行可能因崩溃而不完整
+                Err(_) if index + 1 == lines.len() => recovered.skipped += 1,
+                Err(e) => {
+                    return Err(anyhow::anyhow!("预写日志第 {} 行已损坏: {}", index + 1, e));
+                }
+            }
+        }
+        Ok(recovered)
+    }
+
+    /// 写入快照并清空日志
+    ///
+    /// 快照先写入临时文件并同步到磁盘再重命名，重命名也同步到目录之后才清空日志，
+    /// 压缩途中崩溃时要么留下旧快照和完整日志，要么留下新快照。
+    /// 调用方需要保证压缩期间没有新的写入。
+    pub async fn compact(&self, snapshot: &Snapshot) -> Result<()> {
+        let temp_path = self.snapshot_path.with_extension("json.tmp");
+        let mut temp = File::create(&temp_path).await?;
+        temp.write_all(&serde_json::to_vec(snapshot)?).await?;
+        temp.sync_all().await?;
+        drop(temp);
+        tokio::fs::rename(&temp_path, &self.snapshot_path).await?;
+        sync_parent_dir(&self.snapshot_path).await?;
+
+        let file = self.file.lock().await;
+        file.set_len(0).await?;
+        self.appended.store(0, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+/// 写入并同步到磁盘，失败时把文件截断回写入前的长度，
+/// 避免残缺的半行和下一次追加拼在一起，出现在日志中间
+async fn write_or_rollback(file: &mut File, bytes: &[u8]) -> Result<()> {
+    let len = file.metadata().await?.len();
+    let written = async {
+        file.write_all(bytes).await?;
+        file.flush().await?;
+        file.sync_data().await
+    }
+    .await;
+    if let Err(e) = written {
+        file.set_len(len).await?;
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// 同步文件所在的目录，让重命名在断电后依然生效
+#[cfg(unix)]
+async fn sync_parent_dir(path: &Path) -> Result<()> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(dir).await?.sync_all().await?;
+    Ok(())
+}
+
+/// 其他平台无法打开目录进行同步
+#[cfg(not(unix))]
+async fn sync_parent_dir(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::database::AsyncDatabase;
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    fn temp_wal(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("august-code-wal-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("users.wal")
+    }
+
+    fn user(id: &str, name: &str) -> User {
+        User {
+            id: id.to_string(),
+            name: name.to_string(),
+            email: format!("{}@example.com", id),
+            created_at: 1234567890,
+            version: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recover_replays_log_after_restart() {
+        let path = temp_wal("replay");
+        {
+            let db = AsyncDatabase::recover(&path).await.unwrap();
+            db.create_user(user("1", "张三")).await.unwrap();
+            db.create_user(user("2", "李四")).await.unwrap();
+            db.update_user(user("1", "张三（已更新）")).await.unwrap();
+            db.delete_user("2").await.unwrap();
+        }
+
+        let db = AsyncDatabase::recover(&path).await.unwrap();
+        assert_eq!(db.find_user("1").await.unwrap().unwrap().name, "张三（已更新）");
+        assert!(db.find_user("2").await.unwrap().is_none());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_compaction_writes_snapshot_file() {
+        let path = temp_wal("compact");
+        let db = AsyncDatabase::recover(&path).await.unwrap();
+        db.create_user(user("1", "张三")).await.unwrap();
+        db.create_user(user("2", "李四")).await.unwrap();
+        db.compact().await.unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+
+        db.create_user(user("3", "王五")).await.unwrap();
+        drop(db);
+
+        // 磁盘上的快照文件只有压缩前的两条
+        let snapshot_file: Snapshot =
+            serde_json::from_slice(&std::fs::read(WriteAheadLog::snapshot_path_for(&path)).unwrap()).unwrap();
+        assert_eq!(snapshot_file.users.len(), 2);
+
+        // load() 在快照之上重放日志，第三条来自日志
+        let loaded = WriteAheadLog::load(&path).await.unwrap();
+        assert_eq!(loaded.snapshot.users.len(), 3);
+        assert_eq!(loaded.replayed, 1);
+
+        let db = AsyncDatabase::recover(&path).await.unwrap();
+        assert_eq!(db.list_users(10, None).await.unwrap().items.len(), 3);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_torn_last_line_is_ignored() {
+        let path = temp_wal("torn");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let complete = serde_json::to_string(&WalEntry::Create { user: user("1", "张三"), expires_at: None }).unwrap();
+        std::fs::write(&path, format!("{}\n{{\"op\":\"create\",\"user\":{{\"id\"", complete)).unwrap();
+
+        let recovered = WriteAheadLog::load(&path).await.unwrap();
+        assert_eq!(recovered.replayed, 1);
+        assert_eq!(recovered.skipped, 1);
+
+        // 中间行损坏说明日志不可信，直接报错
+        std::fs::write(&path, format!("not json\n{}\n", complete)).unwrap();
+        assert!(WriteAheadLog::load(&path).await.is_err());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_torn_multibyte_last_line_is_ignored() {
+        let path = temp_wal("torn-utf8");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let complete = serde_json::to_string(&WalEntry::Create { user: user("1", "张三"), expires_at: None }).unwrap();
+        let torn = serde_json::to_string(&WalEntry::Create { user: user("2", "李四"), expires_at: None }).unwrap();
+        // 截断在“李”的三个字节中间
+        let cut = torn.find("李").unwrap() + 1;
+        let mut content = format!("{}\n", complete).into_bytes();
+        content.extend_from_slice(&torn.as_bytes()[..cut]);
+        std::fs::write(&path, content).unwrap();
+
+        let recovered = WriteAheadLog::load(&path).await.unwrap();
+        assert_eq!(recovered.replayed, 1);
+        assert_eq!(recovered.skipped, 1);
+        assert!(recovered.snapshot.users.contains_key("1"));
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_expirations_survive_recover_and_compaction() {
+        let path = temp_wal("expiry");
+        {
+            let db = AsyncDatabase::recover(&path).await.unwrap();
+            db.create_user_with_ttl(user("1", "张三"), Duration::from_secs(3600)).await.unwrap();
+            db.create_user_with_ttl(user("2", "李四"), Duration::from_millis(20)).await.unwrap();
+            db.create_user(user("3", "王五")).await.unwrap();
+            db.set_expiry("3", Some(Instant::now() + Duration::from_secs(60))).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // 第一次恢复重放日志并压缩，第二次只从快照恢复
+        for _ in 0..2 {
+            let db = AsyncDatabase::recover(&path).await.unwrap();
+            let remaining = db.expires_at("1").await.unwrap().saturating_duration_since(Instant::now());
+            assert!(remaining > Duration::from_secs(3500) && remaining <= Duration::from_secs(3600));
+            assert!(db.expires_at("3").await.is_some());
+            // 停机期间到期的记录恢复后立即视为过期
+            assert!(db.find_user("2").await.unwrap().is_none());
+        }
+
+        let db = AsyncDatabase::recover(&path).await.unwrap();
+        assert_eq!(db.reap_expired().await, 1);
+        db.set_expiry("3", None).await.unwrap();
+        drop(db);
+        let db = AsyncDatabase::recover(&path).await.unwrap();
+        assert!(db.expires_at("3").await.is_none());
+        assert!(db.find_user("3").await.unwrap().is_some());
+        assert_eq!(db.reap_expired().await, 0);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -1,47 +1,370 @@
 //! 异步Web服务器模块
 //! 
 //! 提供异步Web服务器功能，包括：
-//! - 带缓存的HTTP请求处理
+//! - 带缓存的HTTP请求处理，缓存键为规范化 URL 和 Vary 请求头的哈希，相同响应体只保存一份
+//! - 可选的磁盘层：内存缓存超出字节预算时按 LRU 溢出到磁盘，访问时读回内存
 //! - 并发请求管理
 //! - 限流器实现
-//! - 任务调度器
+//! - 离线模式下使用模拟客户端
+//! - 缓存过期和限流按注入的时钟计时，测试中可以使用虚拟时间
 
 use anyhow::Result;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::time::{Duration, Instant};
 
-/// 缓存条目
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use sha2::{Digest, Sha256};
+
+use super::connectivity::{self, MockHttpClient};
+use crate::utils::clock::{system_clock, Clock};
+use crate::utils::tasks::TaskSet;
+
+/// 缓存条目，只记录内容哈希，响应体按哈希存放在 [`CacheStore`] 中
+#[derive(Debug, Clone)]
 struct CacheEntry {
-    data: String,
-    timestamp: u64,
-    ttl: u64,
+    content_hash: String,
+    stored_at: std::time::Instant,
+    ttl: Duration,
+}
+
+impl CacheEntry {
+    fn is_valid(&self, now: std::time::Instant) -> bool {
+        now.saturating_duration_since(self.stored_at) < self.ttl
+    }
+}
+
+/// 按内容寻址的响应体，多个缓存条目内容相同时共用一份
+#[derive(Debug)]
+struct StoredBody {
+    /// `None` 表示已经溢出到磁盘
+    data: Option<String>,
+    len: usize,
+    refs: usize,
+    /// 最近一次访问时的逻辑时钟，用于 LRU
+    last_used: u64,
+}
+
+/// 磁盘层配置：内存中的响应体超过字节预算时，把最久未使用的写入磁盘目录
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiskTierConfig {
+    pub memory_budget_bytes: usize,
+    pub dir: PathBuf,
+}
+
+/// 磁盘索引中的一项，对应目录下的 `<哈希>.body` 文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DiskIndexEntry {
+    bytes: usize,
+    spilled_at: u64,
+}
+
+/// 磁盘层：溢出的响应体文件和描述它们的 `index.json`
+#[derive(Debug)]
+struct DiskTier {
+    config: DiskTierConfig,
+    index: HashMap<String, DiskIndexEntry>,
+}
+
+impl DiskTier {
+    const INDEX_FILE: &'static str = "index.json";
+
+    /// 打开磁盘目录，上次运行留下的溢出文件没有对应的内存条目，按索引删除
+    fn open(config: DiskTierConfig) -> Result<Self> {
+        fs::create_dir_all(&config.dir)?;
+        let index_path = config.dir.join(Self::INDEX_FILE);
+        if let Ok(text) = fs::read_to_string(&index_path) {
+            let stale: HashMap<String, DiskIndexEntry> = serde_json::from_str(&text).unwrap_or_default();
+            for hash in stale.keys() {
+                let _ = fs::remove_file(config.dir.join(format!("{}.body", hash)));
+            }
+        }
+        let tier = Self { config, index: HashMap::new() };
+        tier.save_index()?;
+        Ok(tier)
+    }
+
+    fn body_path(&self, hash: &str) -> PathBuf {
+        self.config.dir.join(format!("{}.body", hash))
+    }
+
+    fn save_index(&self) -> Result<()> {
+        let text = serde_json::to_string_pretty(&self.index)?;
+        fs::write(self.config.dir.join(Self::INDEX_FILE), text)?;
+        Ok(())
+    }
+
+    fn write(&mut self, hash: &str, data: &str) -> Result<()> {
+        fs::write(self.body_path(hash), data)?;
+        self.index.insert(hash.to_string(), DiskIndexEntry { bytes: data.len(), spilled_at: unix_now() });
+        self.save_index()
+    }
+
+    fn read(&self, hash: &str) -> Result<String> {
+        Ok(fs::read_to_string(self.body_path(hash))?)
+    }
+
+    fn remove(&mut self, hash: &str) {
+        if self.index.remove(hash).is_some() {
+            let _ = fs::remove_file(self.body_path(hash));
+            if let Err(e) = self.save_index() {
+                eprintln!("写入缓存索引失败: {}", e);
+            }
+        }
+    }
+}
+
+/// 缓存存储：缓存键 -> 条目，内容哈希 -> 响应体
+///
+/// 配置了磁盘层时，内存中的响应体总字节数超过预算后按 LRU 溢出到磁盘，
+/// 再次访问时读回内存。磁盘读写都很小，直接在持锁期间同步完成。
+#[derive(Debug, Default)]
+struct CacheStore {
+    entries: HashMap<String, CacheEntry>,
+    bodies: HashMap<String, StoredBody>,
+    disk: Option<DiskTier>,
+    clock: u64,
+    memory_bytes: usize,
+    memory_hits: u64,
+    disk_hits: u64,
+    misses: u64,
+    spilled: u64,
+}
+
+impl CacheStore {
+    fn with_disk_tier(config: DiskTierConfig) -> Result<Self> {
+        Ok(Self { disk: Some(DiskTier::open(config)?), ..Self::default() })
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn get(&mut self, key: &str, now: std::time::Instant) -> Option<String> {
+        let Some(hash) = self.entries.get(key)
+            .filter(|entry| entry.is_valid(now))
+            .map(|entry| entry.content_hash.clone())
+        else {
+            self.misses += 1;
+            return None;
+        };
+
+        let tick = self.tick();
+        let body = self.bodies.get_mut(&hash)?;
+        body.last_used = tick;
+        if let Some(data) = &body.data {
+            self.memory_hits += 1;
+            return Some(data.clone());
+        }
+
+        // 磁盘命中：读回内存并删除磁盘文件
+        let disk = self.disk.as_mut()?;
+        match disk.read(&hash) {
+            Ok(data) => {
+                disk.remove(&hash);
+                self.disk_hits += 1;
+                self.memory_bytes += data.len();
+                body.data = Some(data.clone());
+                self.enforce_budget(&hash);
+                Some(data)
+            }
+            Err(e) => {
+                eprintln!("读取磁盘缓存失败: {}", e);
+                self.misses += 1;
+                if let Some(entry) = self.entries.remove(key) {
+                    self.release(&entry.content_hash);
+                }
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, key: String, data: &str, stored_at: std::time::Instant, ttl: Duration) {
+        let content_hash = sha256_hex(data.as_bytes());
+        let tick = self.tick();
+        let mut added = 0;
+        let body = self.bodies.entry(content_hash.clone()).or_insert_with(|| {
+            added = data.len();
+            StoredBody { data: Some(data.to_string()), len: data.len(), refs: 0, last_used: tick }
+        });
+        body.refs += 1;
+        body.last_used = tick;
+        self.memory_bytes += added;
+        if let Some(old) = self.entries.insert(key, CacheEntry { content_hash: content_hash.clone(), stored_at, ttl }) {
+            self.release(&old.content_hash);
+        }
+        self.enforce_budget(&content_hash);
+    }
+
+    /// 内存超出预算时，把最久未使用的响应体写入磁盘，`keep` 是刚访问的响应体，不溢出
+    fn enforce_budget(&mut self, keep: &str) {
+        let Some(disk) = self.disk.as_mut() else {
+            return;
+        };
+        while self.memory_bytes > disk.config.memory_budget_bytes {
+            let Some(hash) = self.bodies.iter()
+                .filter(|(hash, body)| body.data.is_some() && hash.as_str() != keep)
+                .min_by_key(|(_, body)| body.last_used)
+                .map(|(hash, _)| hash.clone())
+            else {
+                break;
+            };
+            let body = self.bodies.get_mut(&hash).expect("刚找到的响应体");
+            let data = body.data.take().expect("内存中的响应体");
+            if let Err(e) = disk.write(&hash, &data) {
+                eprintln!("缓存溢出到磁盘失败: {}", e);
+                body.data = Some(data);
+                break;
+            }
+            self.memory_bytes -= body.len;
+            self.spilled += 1;
+        }
+    }
+
+    /// 减少响应体的引用计数，没有条目引用时删除（包括磁盘上的文件）
+    fn release(&mut self, content_hash: &str) {
+        if let Some(body) = self.bodies.get_mut(content_hash) {
+            body.refs -= 1;
+            if body.refs == 0 {
+                let in_memory = body.data.is_some();
+                let len = body.len;
+                self.bodies.remove(content_hash);
+                if in_memory {
+                    self.memory_bytes -= len;
+                } else if let Some(disk) = self.disk.as_mut() {
+                    disk.remove(content_hash);
+                }
+            }
+        }
+    }
+
+    fn retain_valid(&mut self, now: std::time::Instant) {
+        let expired: Vec<String> = self.entries.iter()
+            .filter(|(_, entry)| !entry.is_valid(now))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired {
+            if let Some(entry) = self.entries.remove(&key) {
+                self.release(&entry.content_hash);
+            }
+        }
+    }
+}
+
+/// 缓存统计信息
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub total_entries: usize,
+    pub valid_entries: usize,
+    /// 实际保存的不同响应体个数
+    pub unique_bodies: usize,
+    /// 实际保存的响应体字节数
+    pub stored_bytes: usize,
+    /// 相同响应体只保存一份所节省的字节数
+    pub dedup_saved_bytes: usize,
+    /// 内存中和磁盘上的响应体字节数
+    pub memory_bytes: usize,
+    pub disk_bytes: usize,
+    pub memory_hits: u64,
+    pub disk_hits: u64,
+    pub misses: u64,
+    /// 累计溢出到磁盘的次数
+    pub spilled_to_disk: u64,
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// 规范化 URL：主机名小写、去掉默认端口和片段、查询参数按名称排序
+pub fn normalize_url(url: &str) -> String {
+    let Ok(mut parsed) = reqwest::Url::parse(url) else {
+        return url.to_string();
+    };
+    parsed.set_fragment(None);
+    let mut pairs: Vec<(String, String)> = parsed.query_pairs().into_owned().collect();
+    if pairs.is_empty() {
+        parsed.set_query(None);
+    } else {
+        pairs.sort();
+        parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    parsed.to_string()
+}
+
+/// 缓存键：规范化 URL 加上参与区分缓存的请求头（Vary），取 SHA-256
+pub fn cache_key(url: &str, vary: &[(&str, &str)]) -> String {
+    let mut headers: Vec<(String, &str)> = vary.iter()
+        .map(|(name, value)| (name.to_ascii_lowercase(), value.trim()))
+        .collect();
+    headers.sort();
+
+    let mut material = normalize_url(url);
+    for (name, value) in headers {
+        material.push('\n');
+        material.push_str(&name);
+        material.push(':');
+        material.push_str(value);
+    }
+    sha256_hex(material.as_bytes())
 }
 
 /// 异步Web服务器
 #[derive(Debug, Clone)]
 pub struct AsyncWebServer {
     client: Client,
-    cache: Arc<RwLock<HashMap<String, CacheEntry>>>,
+    cache: Arc<RwLock<CacheStore>>,
+    mock: Option<MockHttpClient>,
+    clock: Arc<dyn Clock>,
 }
 
 impl AsyncWebServer {
-    /// 创建新的Web服务器
+    /// 创建新的Web服务器，离线模式下使用模拟客户端
     pub fn new() -> Self {
         Self {
             client: Client::new(),
-            cache: Arc::new(RwLock::new(HashMap::new())),
+            cache: Arc::new(RwLock::new(CacheStore::default())),
+            mock: connectivity::is_offline().then(MockHttpClient::new),
+            clock: system_clock(),
         }
     }
+
+    /// 使用指定的时钟判断缓存是否过期
+    pub fn with_clock(self, clock: Arc<dyn Clock>) -> Self {
+        Self { clock, ..self }
+    }
+
+    /// 创建带磁盘层的Web服务器，内存缓存超出预算的部分溢出到磁盘目录
+    pub fn with_disk_tier(config: DiskTierConfig) -> Result<Self> {
+        Ok(Self {
+            cache: Arc::new(RwLock::new(CacheStore::with_disk_tier(config)?)),
+            ..Self::new()
+        })
+    }
     
     /// 异步获取数据，带缓存
     pub async fn fetch_with_cache(&self, url: &str) -> Result<String> {
+        self.fetch_with_headers(url, &[]).await
+    }
+
+    /// 带请求头获取数据，请求头参与缓存键，头不同的请求分别缓存
+    pub async fn fetch_with_headers(&self, url: &str, headers: &[(&str, &str)]) -> Result<String> {
+        let key = cache_key(url, headers);
+
         // 检查缓存
-        if let Some(cached) = self.get_from_cache(url).await {
+        if let Some(cached) = self.get_from_cache(&key).await {
             println!("从缓存获取: {}", url);
             return Ok(cached);
         }
@@ -50,71 +373,53 @@ impl AsyncWebServer {
         println!("发起网络请求: {}", url);
         let start = Instant::now();
         
-        let response = self.client
-            .get(url)
-            .timeout(Duration::from_secs(10))
-            .send()
-            .await?;
-        
+        let content = match self.mock.as_ref().filter(|_| !connectivity::is_local_url(url)) {
+            Some(mock) => mock.get(url).await?.body,
+            None => {
+                let mut request = self.client
+                    .get(url)
+                    .timeout(Duration::from_secs(10));
+                for (name, value) in headers {
+                    request = request.header(*name, *value);
+                }
+                request.send().await?.text().await?
+            }
+        };
         let response_time = start.elapsed();
-        let content = response.text().await?;
         
         // 存储到缓存
-        self.store_in_cache(url, &content, 300).await; // 5分钟 TTL
+        self.store_in_cache(&key, &content, 300).await; // 5分钟 TTL
         
         println!("请求完成: {} (耗时: {:?})", url, response_time);
         Ok(content)
     }
     
     /// 从缓存获取数据
-    async fn get_from_cache(&self, url: &str) -> Option<String> {
-        let cache = self.cache.read().await;
-        if let Some(entry) = cache.get(url) {
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
-            
-            if now - entry.timestamp < entry.ttl {
-                return Some(entry.data.clone());
-            }
-        }
-        None
+    async fn get_from_cache(&self, key: &str) -> Option<String> {
+        self.cache.write().await.get(key, self.clock.now())
     }
     
-    /// 存储数据到缓存
-    async fn store_in_cache(&self, url: &str, data: &str, ttl: u64) {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        
-        let entry = CacheEntry {
-            data: data.to_string(),
-            timestamp: now,
-            ttl,
-        };
-        
+    /// 存储数据到缓存，内容相同的响应体只保存一份，`ttl` 以秒计
+    async fn store_in_cache(&self, key: &str, data: &str, ttl: u64) {
         let mut cache = self.cache.write().await;
-        cache.insert(url.to_string(), entry);
+        cache.insert(key.to_string(), data, self.clock.now(), Duration::from_secs(ttl));
     }
     
     /// 并发处理多个请求
     pub async fn process_multiple_requests(&self, urls: Vec<&str>) -> Result<Vec<String>> {
-        let mut handles = Vec::new();
+        let mut tasks = TaskSet::new();
         
         for url in urls {
             let server = self.clone();
             let url = url.to_string();
-            let handle = tokio::spawn(async move {
+            tasks.spawn_tracked(url.clone(), async move {
                 server.fetch_with_cache(&url).await
             });
-            handles.push(handle);
         }
         
         let mut results = Vec::new();
-        for handle in handles {
-            match handle.await? {
+        for result in tasks.join_all().await.into_result()? {
+            match result {
                 Ok(content) => results.push(content),
                 Err(e) => {
                     eprintln!("请求失败: {}", e);
@@ -128,115 +433,75 @@ impl AsyncWebServer {
     
     /// 清理过期缓存
     pub async fn cleanup_cache(&self) {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        
         let mut cache = self.cache.write().await;
-        cache.retain(|_, entry| now - entry.timestamp < entry.ttl);
+        cache.retain_valid(self.clock.now());
         
-        println!("缓存清理完成，剩余条目: {}", cache.len());
+        println!("缓存清理完成，剩余条目: {}", cache.entries.len());
     }
     
     /// 获取缓存统计信息
-    pub async fn cache_stats(&self) -> (usize, usize) {
+    pub async fn cache_stats(&self) -> CacheStats {
         let cache = self.cache.read().await;
-        let total_entries = cache.len();
-        
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let now = self.clock.now();
         
-        let valid_entries = cache.values()
-            .filter(|entry| now - entry.timestamp < entry.ttl)
+        let valid_entries = cache.entries.values()
+            .filter(|entry| entry.is_valid(now))
             .count();
+        let stored_bytes: usize = cache.bodies.values().map(|body| body.len).sum();
+        let dedup_saved_bytes = cache.bodies.values()
+            .map(|body| (body.refs - 1) * body.len)
+            .sum();
         
-        (total_entries, valid_entries)
-    }
-}
-
-/// 异步任务调度器
-pub struct TaskScheduler {
-    tasks: Arc<RwLock<Vec<tokio::task::JoinHandle<()>>>>,
-}
-
-impl TaskScheduler {
-    /// 创建新的任务调度器
-    pub fn new() -> Self {
-        Self {
-            tasks: Arc::new(RwLock::new(Vec::new())),
-        }
-    }
-    
-    /// 添加周期性任务
-    pub async fn add_periodic_task<F>(&self, name: &str, interval: Duration, task: F)
-    where
-        F: Fn() + Send + Sync + 'static,
-    {
-        let name = name.to_string();
-        let tasks = Arc::clone(&self.tasks);
-        
-        let handle = tokio::spawn(async move {
-            let mut interval_timer = tokio::time::interval(interval);
-            loop {
-                interval_timer.tick().await;
-                println!("执行周期性任务: {}", name);
-                task();
-            }
-        });
-        
-        let mut tasks_guard = tasks.write().await;
-        tasks_guard.push(handle);
-    }
-    
-    /// 等待所有任务完成
-    pub async fn wait_for_all(&self) {
-        let tasks = self.tasks.read().await;
-        for handle in tasks.iter() {
-            let _ = handle.await;
+        CacheStats {
+            total_entries: cache.entries.len(),
+            valid_entries,
+            unique_bodies: cache.bodies.len(),
+            stored_bytes,
+            dedup_saved_bytes,
+            memory_bytes: cache.memory_bytes,
+            disk_bytes: stored_bytes - cache.memory_bytes,
+            memory_hits: cache.memory_hits,
+            disk_hits: cache.disk_hits,
+            misses: cache.misses,
+            spilled_to_disk: cache.spilled,
         }
     }
 }
 
 /// 异步限流器
+///
+/// 基于 common 中的令牌桶，`time_window` 内最多放行 `max_requests` 个请求
 pub struct RateLimiter {
-    requests: Arc<RwLock<Vec<Instant>>>,
-    max_requests: usize,
-    time_window: Duration,
+    limiter: common::RateLimiter,
+    clock: Arc<dyn Clock>,
 }
 
 impl RateLimiter {
+    /// 限流器只有一个调用方，所有请求共用同一个键
+    const KEY: &'static str = "default";
+
     /// 创建新的限流器
     pub fn new(max_requests: usize, time_window: Duration) -> Self {
+        Self::with_clock(max_requests, time_window, system_clock())
+    }
+
+    /// 创建按指定时钟计时的限流器
+    pub fn with_clock(max_requests: usize, time_window: Duration, clock: Arc<dyn Clock>) -> Self {
         Self {
-            requests: Arc::new(RwLock::new(Vec::new())),
-            max_requests,
-            time_window,
+            limiter: common::RateLimiter::per_window(max_requests as u32, time_window),
+            clock,
         }
     }
     
     /// 检查是否允许请求
     pub async fn allow_request(&self) -> bool {
-        let now = Instant::now();
-        let mut requests = self.requests.write().await;
-        
-        // 清理过期的请求记录
-        requests.retain(|&time| now.duration_since(time) < self.time_window);
-        
-        if requests.len() < self.max_requests {
-            requests.push(now);
-            true
-        } else {
-            false
-        }
+        self.limiter.check_at(Self::KEY, self.clock.now()).is_ok()
     }
     
     /// 等待直到允许请求
     pub async fn wait_for_permission(&self) {
-        while !self.allow_request().await {
-            tokio::time::sleep(Duration::from_millis(100)).await;
+        while let Err(retry_after) = self.limiter.check_at(Self::KEY, self.clock.now()) {
+            self.clock.sleep(retry_after).await;
         }
     }
 }
@@ -244,6 +509,14 @@ impl RateLimiter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::clock::MockClock;
+    
+    /// 让出执行权，使被唤醒的任务运行到下一次等待
+    async fn settle() {
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
     
     #[tokio::test]
     async fn test_cache_functionality() {
@@ -256,9 +529,137 @@ mod tests {
         assert_eq!(cached, Some("test_data".to_string()));
         
         // 测试缓存统计
-        let (total, valid) = server.cache_stats().await;
-        assert_eq!(total, 1);
-        assert_eq!(valid, 1);
+        let stats = server.cache_stats().await;
+        assert_eq!(stats.total_entries, 1);
+        assert_eq!(stats.valid_entries, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_identical_bodies_deduplicated() {
+        let server = AsyncWebServer::new();
+        let body = "x".repeat(100);
+        
+        server.store_in_cache(&cache_key("http://a.test/one", &[]), &body, 60).await;
+        server.store_in_cache(&cache_key("http://a.test/two", &[]), &body, 60).await;
+        server.store_in_cache(&cache_key("http://a.test/three", &[]), "other", 60).await;
+        
+        let stats = server.cache_stats().await;
+        assert_eq!(stats.total_entries, 3);
+        assert_eq!(stats.unique_bodies, 2);
+        assert_eq!(stats.stored_bytes, 105);
+        assert_eq!(stats.dedup_saved_bytes, 100);
+        
+        // 覆盖一个条目后旧响应体少一个引用
+        server.store_in_cache(&cache_key("http://a.test/two", &[]), "other", 60).await;
+        let stats = server.cache_stats().await;
+        assert_eq!(stats.unique_bodies, 2);
+        assert_eq!(stats.dedup_saved_bytes, 5);
+    }
+    
+    #[tokio::test]
+    async fn test_cleanup_releases_bodies() {
+        let server = AsyncWebServer::new();
+        server.store_in_cache("expired", "shared", 0).await;
+        server.store_in_cache("alive", "shared", 60).await;
+        server.store_in_cache("gone", "only", 0).await;
+        
+        server.cleanup_cache().await;
+        let stats = server.cache_stats().await;
+        assert_eq!(stats.total_entries, 1);
+        assert_eq!(stats.unique_bodies, 1);
+        assert_eq!(stats.dedup_saved_bytes, 0);
+        assert_eq!(server.get_from_cache("alive").await.as_deref(), Some("shared"));
+    }
+    
+    fn disk_tier(name: &str, budget: usize) -> DiskTierConfig {
+        let dir = std::env::temp_dir().join(format!("august-code-cache-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        DiskTierConfig { memory_budget_bytes: budget, dir }
+    }
+    
+    #[tokio::test]
+    async fn test_disk_tier_spills_lru_and_promotes() {
+        let config = disk_tier("spill", 250);
+        let server = AsyncWebServer::with_disk_tier(config.clone()).unwrap();
+        let bodies: Vec<String> = ["a", "b", "c"].iter().map(|c| c.repeat(100)).collect();
+        
+        server.store_in_cache("a", &bodies[0], 60).await;
+        server.store_in_cache("b", &bodies[1], 60).await;
+        // 访问 a 之后 b 成为最久未使用的
+        assert_eq!(server.get_from_cache("a").await.as_deref(), Some(bodies[0].as_str()));
+        server.store_in_cache("c", &bodies[2], 60).await;
+        
+        let stats = server.cache_stats().await;
+        assert_eq!((stats.memory_bytes, stats.disk_bytes, stats.spilled_to_disk), (200, 100, 1));
+        let index: HashMap<String, DiskIndexEntry> =
+            serde_json::from_str(&fs::read_to_string(config.dir.join("index.json")).unwrap()).unwrap();
+        assert_eq!(index.keys().collect::<Vec<_>>(), vec![&sha256_hex(bodies[1].as_bytes())]);
+        
+        // 读回 b 会把此时最久未使用的 a 挤到磁盘
+        assert_eq!(server.get_from_cache("b").await.as_deref(), Some(bodies[1].as_str()));
+        assert_eq!(server.get_from_cache("missing").await, None);
+        let stats = server.cache_stats().await;
+        assert_eq!((stats.memory_hits, stats.disk_hits, stats.misses), (1, 1, 1));
+        assert_eq!((stats.memory_bytes, stats.disk_bytes, stats.spilled_to_disk), (200, 100, 2));
+        assert!(!config.dir.join(format!("{}.body", sha256_hex(bodies[1].as_bytes()))).exists());
+        
+        let _ = fs::remove_dir_all(&config.dir);
+    }
+    
+    #[tokio::test]
+    async fn test_disk_tier_cleanup_removes_files() {
+        let config = disk_tier("cleanup", 0);
+        let server = AsyncWebServer::with_disk_tier(config.clone()).unwrap();
+        server.store_in_cache("old", "old body", 0).await;
+        server.store_in_cache("new", "new body", 60).await;
+        assert_eq!(server.cache_stats().await.disk_bytes, 8);
+        
+        server.cleanup_cache().await;
+        let files = fs::read_dir(&config.dir).unwrap().count();
+        assert_eq!(files, 1, "只剩 index.json");
+        
+        // 重新打开同一目录时清掉上次留下的溢出文件
+        server.store_in_cache("spill", "spilled body", 60).await;
+        server.store_in_cache("latest", "latest body", 60).await;
+        assert_eq!(fs::read_dir(&config.dir).unwrap().count(), 3);
+        let reopened = AsyncWebServer::with_disk_tier(config.clone()).unwrap();
+        assert_eq!(fs::read_dir(&config.dir).unwrap().count(), 1);
+        assert_eq!(reopened.cache_stats().await.unique_bodies, 0);
+        
+        let _ = fs::remove_dir_all(&config.dir);
+    }
+    
+    #[test]
+    fn test_cache_key_normalization() {
+        assert_eq!(
+            normalize_url("HTTP://Example.COM:80/path?b=2&a=1#frag"),
+            "http://example.com/path?a=1&b=2"
+        );
+        assert_eq!(
+            cache_key("http://example.com/p?b=2&a=1", &[]),
+            cache_key("http://example.com:80/p?a=1&b=2", &[])
+        );
+        assert_eq!(
+            cache_key("http://example.com/p", &[("Accept", "text/html"), ("X-Lang", "zh")]),
+            cache_key("http://example.com/p", &[("x-lang", " zh "), ("accept", "text/html")])
+        );
+        assert_ne!(
+            cache_key("http://example.com/p", &[("Accept", "text/html")]),
+            cache_key("http://example.com/p", &[("Accept", "application/json")])
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_vary_headers_cached_separately() {
+        let test_server = crate::utils::test_server::TestServer::start().await.unwrap();
+        let server = AsyncWebServer::new();
+        let url = test_server.url("/headers");
+        
+        let zh = server.fetch_with_headers(&url, &[("X-Lang", "zh")]).await.unwrap();
+        let en = server.fetch_with_headers(&url, &[("X-Lang", "en")]).await.unwrap();
+        assert_ne!(zh, en);
+        assert_eq!(server.fetch_with_headers(&url, &[("x-lang", "zh")]).await.unwrap(), zh);
+        assert_eq!(server.cache_stats().await.total_entries, 2);
     }
     
     #[tokio::test]
@@ -272,4 +673,45 @@ mod tests {
         // 第三个请求应该被限制
         assert!(!limiter.allow_request().await);
     }
+    
+    #[tokio::test]
+    async fn test_rate_limiter_with_mock_clock() {
+        let clock = MockClock::new();
+        let limiter = Arc::new(RateLimiter::with_clock(2, Duration::from_secs(10), Arc::new(clock.clone())));
+        assert!(limiter.allow_request().await);
+        assert!(limiter.allow_request().await);
+        assert!(!limiter.allow_request().await);
+        
+        // 每 5 秒恢复一个令牌
+        let waiter = tokio::spawn({
+            let limiter = Arc::clone(&limiter);
+            async move { limiter.wait_for_permission().await }
+        });
+        clock.advance(Duration::from_secs(4));
+        settle().await;
+        assert!(!waiter.is_finished());
+        clock.advance(Duration::from_secs(1));
+        settle().await;
+        assert!(waiter.is_finished());
+        assert!(!limiter.allow_request().await);
+    }
+    
+    #[tokio::test]
+    async fn test_cache_ttl_with_mock_clock() {
+        let clock = MockClock::new();
+        let server = AsyncWebServer::new().with_clock(Arc::new(clock.clone()));
+        server.store_in_cache("short", "a", 60).await;
+        server.store_in_cache("long", "b", 300).await;
+        
+        clock.advance(Duration::from_secs(59));
+        assert_eq!(server.get_from_cache("short").await.as_deref(), Some("a"));
+        
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(server.get_from_cache("short").await, None);
+        assert_eq!(server.get_from_cache("long").await.as_deref(), Some("b"));
+        assert_eq!(server.cache_stats().await.valid_entries, 1);
+        
+        server.cleanup_cache().await;
+        assert_eq!(server.cache_stats().await.total_entries, 1);
+    }
 }
@@ -0,0 +1,364 @@
+//! WebSocket 客户端模块
+//!
+//! 基于 tokio-tungstenite 的长连接客户端：
+//! - 通过通道发送和接收消息，连接在后台任务中维护
+//! - 定时发送 ping，长时间收不到 pong 视为连接失效
+//! - 连接断开后按指数退避自动重连
+//! - 断线期间发送的消息在重连后继续发出
+
+use anyhow::Result;
+use futures::{SinkExt, StreamExt};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tokio_util::sync::CancellationToken;
+
+use crate::utils::test_server::WsEchoServer;
+
+/// WebSocket 客户端配置
+#[derive(Debug, Clone)]
+pub struct WsConfig {
+    /// 发送 ping 的间隔
+    pub heartbeat_interval: Duration,
+    /// 超过一个心跳间隔后再等待 pong 的时间
+    pub pong_timeout: Duration,
+    /// 第一次重连前的等待时间
+    pub initial_backoff: Duration,
+    /// 重连等待时间上限
+    pub max_backoff: Duration,
+    /// 最多连续重连次数，`None` 表示一直重连
+    pub max_reconnects: Option<u32>,
+}
+
+impl Default for WsConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: Duration::from_secs(15),
+            pong_timeout: Duration::from_secs(5),
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            max_reconnects: None,
+        }
+    }
+}
+
+impl WsConfig {
+    /// 第 `attempt` 次重连前的等待时间，从 1 开始，每次翻倍，不超过上限
+    pub fn backoff_delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// 客户端事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum WsEvent {
+    /// 连接（或重连）成功
+    Connected,
+    /// 收到文本消息
+    Text(String),
+    /// 收到二进制消息
+    Binary(Vec<u8>),
+    /// 连接断开及原因
+    Disconnected(String),
+    /// 即将进行第 `attempt` 次重连
+    Reconnecting { attempt: u32, delay: Duration },
+    /// 重连次数用完，后台任务退出
+    GaveUp,
+}
+
+/// 带自动重连的 WebSocket 客户端
+pub struct WsClient {
+    outgoing: mpsc::Sender<Message>,
+    events: mpsc::UnboundedReceiver<WsEvent>,
+    token: CancellationToken,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl WsClient {
+    /// 在后台开始连接 `url`，连接结果通过事件通知
+    pub fn connect(url: &str, config: WsConfig) -> Self {
+        let (outgoing, outgoing_rx) = mpsc::channel(64);
+        let (events_tx, events) = mpsc::unbounded_channel();
+        let token = CancellationToken::new();
+
+        let handle = tokio::spawn(run_connection(
+            url.to_string(),
+            config,
+            outgoing_rx,
+            events_tx,
+            token.clone(),
+        ));
+
+        Self {
+            outgoing,
+            events,
+            token,
+            handle: Some(handle),
+        }
+    }
+
+    /// 发送文本消息，未连接时排队等待重连
+    pub async fn send_text(&self, text: &str) -> Result<()> {
+        self.send(Message::Text(text.to_string())).await
+    }
+
+    /// 发送二进制消息，未连接时排队等待重连
+    pub async fn send_binary(&self, data: Vec<u8>) -> Result<()> {
+        self.send(Message::Binary(data)).await
+    }
+
+    async fn send(&self, message: Message) -> Result<()> {
+        self.outgoing
+            .send(message)
+            .await
+            .map_err(|_| anyhow::anyhow!("WebSocket 客户端已停止"))
+    }
+
+    /// 接收下一个事件，后台任务退出后返回 `None`
+    pub async fn recv(&mut self) -> Option<WsEvent> {
+        self.events.recv().await
+    }
+
+    /// 在超时时间内等待下一条文本消息，跳过其他事件
+    pub async fn recv_text(&mut self, timeout: Duration) -> Result<String> {
+        tokio::time::timeout(timeout, async {
+            loop {
+                match self.recv().await {
+                    Some(WsEvent::Text(text)) => return Ok(text),
+                    Some(WsEvent::GaveUp) | None => return Err(anyhow::anyhow!("WebSocket 连接已放弃重连")),
+                    Some(_) => {}
+                }
+            }
+        })
+        .await
+        .map_err(|_| anyhow::anyhow!("等待 WebSocket 消息超时"))?
+    }
+
+    /// 在超时时间内等待指定事件，跳过其他事件
+    pub async fn wait_for(&mut self, expected: &WsEvent, timeout: Duration) -> Result<()> {
+        tokio::time::timeout(timeout, async {
+            while let Some(event) = self.recv().await {
+                if &event == expected {
+                    return Ok(());
+                }
+            }
+            Err(anyhow::anyhow!("WebSocket 客户端已停止"))
+        })
+        .await
+        .map_err(|_| anyhow::anyhow!("等待事件 {:?} 超时", expected))?
+    }
+
+    /// 发送关闭帧并停止后台任务
+    pub async fn close(mut self) {
+        self.token.cancel();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.await;
+        }
+    }
+}
+
+impl Drop for WsClient {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+/// 后台连接循环：连接、收发消息、断开后退避重连
+async fn run_connection(
+    url: String,
+    config: WsConfig,
+    mut outgoing: mpsc::Receiver<Message>,
+    events: mpsc::UnboundedSender<WsEvent>,
+    token: CancellationToken,
+) {
+    let mut attempt = 0;
+    loop {
+        let connected = tokio::select! {
+            _ = token.cancelled() => return,
+            connected = tokio_tungstenite::connect_async(url.as_str()) => connected,
+        };
+
+        match connected {
+            Ok((socket, _)) => {
+                attempt = 0;
+                let _ = events.send(WsEvent::Connected);
+                match run_session(socket, &config, &mut outgoing, &events, &token).await {
+                    Some(reason) => {
+                        let _ = events.send(WsEvent::Disconnected(reason));
+                    }
+                    None => return,
+                }
+            }
+            Err(e) => {
+                let _ = events.send(WsEvent::Disconnected(e.to_string()));
+            }
+        }
+
+        attempt += 1;
+        if config.max_reconnects.is_some_and(|max| attempt > max) {
+            let _ = events.send(WsEvent::GaveUp);
+            return;
+        }
+        let delay = config.backoff_delay(attempt);
+        let _ = events.send(WsEvent::Reconnecting { attempt, delay });
+        tokio::select! {
+            _ = token.cancelled() => return,
+            _ = tokio::time::sleep(delay) => {}
+        }
+    }
+}
+
+/// 维护一次连接，返回断开原因；主动关闭时返回 `None`
+async fn run_session(
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    config: &WsConfig,
+    outgoing: &mut mpsc::Receiver<Message>,
+    events: &mpsc::UnboundedSender<WsEvent>,
+    token: &CancellationToken,
+) -> Option<String> {
+    let (mut write, mut read) = socket.split();
+    let mut heartbeat = tokio::time::interval_at(
+        Instant::now() + config.heartbeat_interval,
+        config.heartbeat_interval,
+    );
+    let mut last_pong = Instant::now();
+
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => {
+                let _ = write.send(Message::Close(None)).await;
+                return None;
+            }
+            message = outgoing.recv() => match message {
+                Some(message) => {
+                    if let Err(e) = write.send(message).await {
+                        return Some(format!("发送失败: {}", e));
+                    }
+                }
+                // 所有发送端都已丢弃，客户端不再使用
+                None => {
+                    let _ = write.send(Message::Close(None)).await;
+                    return None;
+                }
+            },
+            incoming = read.next() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    let _ = events.send(WsEvent::Text(text));
+                }
+                Some(Ok(Message::Binary(data))) => {
+                    let _ = events.send(WsEvent::Binary(data));
+                }
+                Some(Ok(Message::Pong(_))) => last_pong = Instant::now(),
+                // ping 由 tungstenite 自动回复 pong
+                Some(Ok(Message::Ping(_))) | Some(Ok(Message::Frame(_))) => {}
+                Some(Ok(Message::Close(frame))) => {
+                    return Some(format!("服务端关闭连接: {:?}", frame));
+                }
+                Some(Err(e)) => return Some(format!("接收失败: {}", e)),
+                None => return Some("连接已断开".to_string()),
+            },
+            _ = heartbeat.tick() => {
+                if last_pong.elapsed() > config.heartbeat_interval + config.pong_timeout {
+                    return Some("心跳超时".to_string());
+                }
+                if let Err(e) = write.send(Message::Ping(Vec::new())).await {
+                    return Some(format!("发送心跳失败: {}", e));
+                }
+            }
+        }
+    }
+}
+
+/// WebSocket 客户端示例：收发消息、服务端断线后自动重连
+pub async fn ws_client_example() -> Result<()> {
+    println!("\n=== WebSocket 客户端示例 ===");
+
+    let server = WsEchoServer::start().await?;
+    let config = WsConfig {
+        heartbeat_interval: Duration::from_millis(500),
+        ..Default::default()
+    };
+    let mut client = WsClient::connect(&server.url(), config);
+    client.wait_for(&WsEvent::Connected, Duration::from_secs(2)).await?;
+    println!("已连接到 {}", server.url());
+
+    for i in 1..=3 {
+        client.send_text(&format!("消息 {}", i)).await?;
+        println!("收到回显: {}", client.recv_text(Duration::from_secs(2)).await?);
+    }
+
+    client.send_binary(vec![0xde, 0xad, 0xbe, 0xef]).await?;
+    match tokio::time::timeout(Duration::from_secs(2), client.recv()).await? {
+        Some(WsEvent::Binary(data)) => println!("收到二进制回显: {} 字节", data.len()),
+        event => println!("意外事件: {:?}", event),
+    }
+
+    // 模拟网络中断，客户端自动重连后继续收发
+    server.drop_connections();
+    loop {
+        match client.recv().await {
+            Some(WsEvent::Disconnected(reason)) => println!("连接断开: {}", reason),
+            Some(WsEvent::Reconnecting { attempt, delay }) => println!("第 {} 次重连，等待 {:?}", attempt, delay),
+            Some(WsEvent::Connected) => {
+                println!("重连成功");
+                break;
+            }
+            Some(event) => println!("事件: {:?}", event),
+            None => return Err(anyhow::anyhow!("WebSocket 客户端意外停止")),
+        }
+    }
+
+    client.send_text("重连后的消息").await?;
+    println!("收到回显: {}", client.recv_text(Duration::from_secs(2)).await?);
+
+    client.close().await;
+    server.shutdown().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay_doubles_until_max() {
+        let config = WsConfig {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(1000),
+            ..Default::default()
+        };
+        assert_eq!(config.backoff_delay(1), Duration::from_millis(100));
+        assert_eq!(config.backoff_delay(2), Duration::from_millis(200));
+        assert_eq!(config.backoff_delay(4), Duration::from_millis(800));
+        assert_eq!(config.backoff_delay(5), Duration::from_millis(1000));
+        assert_eq!(config.backoff_delay(100), Duration::from_millis(1000));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_reconnects() {
+        // 先占用端口再释放，保证连接被拒绝
+        let addr = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let config = WsConfig {
+            initial_backoff: Duration::from_millis(5),
+            max_reconnects: Some(2),
+            ..Default::default()
+        };
+        let mut client = WsClient::connect(&format!("ws://{}", addr), config);
+
+        let mut reconnects = 0;
+        while let Some(event) = client.recv().await {
+            match event {
+                WsEvent::Reconnecting { .. } => reconnects += 1,
+                WsEvent::GaveUp => break,
+                _ => {}
+            }
+        }
+        assert_eq!(reconnects, 2);
+        assert!(client.send_text("ignored").await.is_err());
+    }
+}
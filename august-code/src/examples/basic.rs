@@ -6,6 +6,7 @@
 //! - 异步定时器
 //! - 异步互斥锁
 //! - 异步文件操作
+//! - 任务池，任务中的 panic 计入错误统计
 
 use anyhow::Result;
 use std::sync::Arc;
@@ -13,6 +14,9 @@ use std::time::Duration;
 use tokio::sync::Mutex;
 use tokio::time::Instant;
 
+use crate::utils::error::{catch_panic_async, ErrorStats};
+use crate::utils::tasks::TaskSet;
+
 /// 简单的异步示例，不依赖网络
 pub async fn simple_async_examples() -> Result<()> {
     println!("\n=== 简单异步示例（无网络依赖）===");
@@ -69,25 +73,25 @@ async fn async_task(name: &str, delay_ms: u64) -> String {
 /// 异步流处理示例
 async fn stream_processing_example() -> Result<()> {
     let numbers = (1..=10).collect::<Vec<_>>();
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
-    for chunk in numbers.chunks(3) {
+    for (i, chunk) in numbers.chunks(3).enumerate() {
         let chunk = chunk.to_vec();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("分块{}", i + 1), catch_panic_async(async move {
             let mut results = Vec::new();
             for n in chunk {
                 tokio::time::sleep(Duration::from_millis(50)).await;
                 results.push(n * n);
             }
             results
-        });
-        handles.push(handle);
+        }));
     }
     
     let mut all_results = Vec::new();
-    for handle in handles {
-        if let Ok(chunk_results) = handle.await {
-            all_results.extend(chunk_results);
+    for result in tasks.join_all().await.into_result()? {
+        match result {
+            Ok(chunk_results) => all_results.extend(chunk_results),
+            Err(e) => eprintln!("分块处理失败: {}", e),
         }
     }
     
@@ -174,32 +178,37 @@ async fn file_operations_example() -> Result<()> {
     Ok(())
 }
 
-/// 异步任务池示例
+/// 异步任务池示例，任务 4 故意 panic，panic 被转换为错误计入错误统计
 pub async fn task_pool_example() -> Result<()> {
     println!("\n=== 异步任务池示例 ===");
     
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
     // 创建多个异步任务
     for i in 1..=5 {
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("任务{}", i), catch_panic_async(async move {
             let duration = Duration::from_millis(i * 100);
             tokio::time::sleep(duration).await;
+            if i == 4 {
+                panic!("任务 {} 遇到了无法处理的数据", i);
+            }
             println!("任务 {} 完成", i);
             i * i
-        });
-        handles.push(handle);
+        }));
     }
     
     // 等待所有任务完成并收集结果
     let mut results = Vec::new();
-    for handle in handles {
-        if let Ok(result) = handle.await {
-            results.push(result);
+    let mut stats = ErrorStats::default();
+    for result in tasks.join_all().await.into_result()? {
+        match result {
+            Ok(result) => results.push(result),
+            Err(e) => stats.record_error(&e),
         }
     }
     
     println!("所有任务结果: {:?}", results);
+    stats.print_stats();
     Ok(())
 }
 
@@ -229,12 +238,12 @@ pub async fn mutex_example() -> Result<()> {
     println!("\n=== 异步互斥锁示例 ===");
     
     let counter = Arc::new(Mutex::new(0));
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
     // 创建多个任务并发修改计数器
     for i in 0..5 {
         let counter = Arc::clone(&counter);
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("任务{}", i), async move {
             for _ in 0..10 {
                 let mut count = counter.lock().await;
                 *count += 1;
@@ -243,13 +252,10 @@ pub async fn mutex_example() -> Result<()> {
                 tokio::time::sleep(Duration::from_millis(10)).await;
             }
         });
-        handles.push(handle);
     }
     
     // 等待所有任务完成
-    for handle in handles {
-        handle.await?;
-    }
+    tasks.join_all().await.into_result()?;
     
     let final_count = *counter.lock().await;
     println!("最终计数: {}", final_count);
@@ -291,24 +297,21 @@ pub async fn resource_management_example() -> Result<()> {
     println!("创建了 5 个资源");
     
     // 异步处理资源
-    let handles: Vec<_> = (0..5)
-        .map(|i| {
-            let resources = Arc::clone(&resources);
-            tokio::spawn(async move {
-                tokio::time::sleep(Duration::from_millis(200)).await;
-                
-                let mut resources = resources.lock().await;
-                if let Some(resource) = resources.pop() {
-                    println!("处理资源: {} (数据: {})", resource.id, resource.data);
-                }
-            })
-        })
-        .collect();
+    let mut tasks = TaskSet::new();
+    for i in 0..5 {
+        let resources = Arc::clone(&resources);
+        tasks.spawn_tracked(format!("处理者{}", i), async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            
+            let mut resources = resources.lock().await;
+            if let Some(resource) = resources.pop() {
+                println!("处理资源: {} (数据: {})", resource.id, resource.data);
+            }
+        });
+    }
     
     // 等待所有处理完成
-    for handle in handles {
-        handle.await?;
-    }
+    tasks.join_all().await.into_result()?;
     
     println!("所有资源处理完成");
     Ok(())
@@ -7,9 +7,14 @@
 //! - 批处理监控
 
 use anyhow::Result;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::Instant;
 
+use crate::utils::logging::{AsyncLogger, LogConfig, PerformanceLogger};
+use crate::utils::tasks::TaskSet;
+use crate::utils::time::Stopwatch;
+
 /// 简化的异步批处理示例
 pub async fn simple_batch_example() -> Result<()> {
     println!("\n=== 简化异步批处理示例 ===");
@@ -20,11 +25,11 @@ pub async fn simple_batch_example() -> Result<()> {
     println!("开始批处理 {} 个项目，批次大小: {}", items.len(), batch_size);
     
     let start = Instant::now();
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
-    for chunk in items.chunks(batch_size) {
+    for (i, chunk) in items.chunks(batch_size).enumerate() {
         let chunk = chunk.to_vec();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("批次{}", i + 1), async move {
             let mut results = Vec::new();
             for item in chunk {
                 tokio::time::sleep(Duration::from_millis(100)).await;
@@ -32,14 +37,11 @@ pub async fn simple_batch_example() -> Result<()> {
             }
             results
         });
-        handles.push(handle);
     }
     
     let mut all_results = Vec::new();
-    for handle in handles {
-        if let Ok(batch_results) = handle.await {
-            all_results.extend(batch_results);
-        }
+    for batch_results in tasks.join_all().await.into_result()? {
+        all_results.extend(batch_results);
     }
     
     let total_time = start.elapsed();
@@ -61,7 +63,7 @@ pub async fn dynamic_batch_example() -> Result<()> {
     
     let start = Instant::now();
     let mut current_batch = Vec::new();
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
     for item in items {
         current_batch.push(item);
@@ -69,7 +71,7 @@ pub async fn dynamic_batch_example() -> Result<()> {
         // 当批次达到指定大小时处理
         if current_batch.len() >= batch_size {
             let batch = current_batch.clone();
-            let handle = tokio::spawn(async move {
+            tasks.spawn_tracked(format!("批次{}", tasks.len() + 1), async move {
                 let mut results = Vec::new();
                 for item in batch {
                     // 模拟处理时间
@@ -78,7 +80,6 @@ pub async fn dynamic_batch_example() -> Result<()> {
                 }
                 results
             });
-            handles.push(handle);
             
             current_batch.clear();
             total_processed += batch_size;
@@ -94,7 +95,7 @@ pub async fn dynamic_batch_example() -> Result<()> {
     // 处理剩余的批次
     if !current_batch.is_empty() {
         let batch = current_batch.clone();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("批次{}", tasks.len() + 1), async move {
             let mut results = Vec::new();
             for item in batch {
                 tokio::time::sleep(Duration::from_millis(50)).await;
@@ -102,15 +103,17 @@ pub async fn dynamic_batch_example() -> Result<()> {
             }
             results
         });
-        handles.push(handle);
+    }
+    
+    if tasks.is_empty() {
+        println!("没有需要处理的数据");
+        return Ok(());
     }
     
     let mut all_results = Vec::new();
-    let batch_count = handles.len();
-    for handle in handles {
-        if let Ok(batch_results) = handle.await {
-            all_results.extend(batch_results);
-        }
+    let batch_count = tasks.len();
+    for batch_results in tasks.join_all().await.into_result()? {
+        all_results.extend(batch_results);
     }
     
     let total_time = start.elapsed();
@@ -131,13 +134,13 @@ pub async fn optimized_batch_example() -> Result<()> {
     println!("开始优化批处理 {} 个项目，最优批次大小: {}", items.len(), optimal_batch_size);
     
     let start = Instant::now();
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
-    for chunk in items.chunks(optimal_batch_size) {
+    for (i, chunk) in items.chunks(optimal_batch_size).enumerate() {
         let chunk = chunk.to_vec();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("批次{}", i + 1), async move {
             let mut results = Vec::new();
-            let mut batch_start = Instant::now();
+            let batch_start = Instant::now();
             
             for item in chunk {
                 // 模拟处理时间
@@ -149,15 +152,12 @@ pub async fn optimized_batch_example() -> Result<()> {
             println!("批次处理完成，耗时: {:?}", batch_time);
             results
         });
-        handles.push(handle);
     }
     
     let mut all_results = Vec::new();
-    let batch_count = handles.len();
-    for handle in handles {
-        if let Ok(batch_results) = handle.await {
-            all_results.extend(batch_results);
-        }
+    let batch_count = tasks.len();
+    for batch_results in tasks.join_all().await.into_result()? {
+        all_results.extend(batch_results);
     }
     
     let total_time = start.elapsed();
@@ -178,14 +178,14 @@ pub async fn monitored_batch_example() -> Result<()> {
     println!("开始监控批处理 {} 个项目，批次大小: {}", items.len(), batch_size);
     
     let start = Instant::now();
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     let mut batch_stats = Vec::new();
     
     for (batch_idx, chunk) in items.chunks(batch_size).enumerate() {
         let chunk = chunk.to_vec();
         let batch_idx = batch_idx + 1;
         
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("批次{}", batch_idx), async move {
             let batch_start = Instant::now();
             let mut results = Vec::new();
             let mut success_count = 0;
@@ -216,20 +216,17 @@ pub async fn monitored_batch_example() -> Result<()> {
             
             (results, stats)
         });
-        handles.push(handle);
     }
     
     let mut all_results = Vec::new();
     let mut total_success = 0;
     let mut total_errors = 0;
     
-    for handle in handles {
-        if let Ok((batch_results, stats)) = handle.await {
-            all_results.extend(batch_results);
-            total_success += stats.success_count;
-            total_errors += stats.error_count;
-            batch_stats.push(stats);
-        }
+    for (batch_results, stats) in tasks.join_all().await.into_result()? {
+        all_results.extend(batch_results);
+        total_success += stats.success_count;
+        total_errors += stats.error_count;
+        batch_stats.push(stats);
     }
     
     let total_time = start.elapsed();
@@ -244,8 +241,8 @@ pub async fn monitored_batch_example() -> Result<()> {
     
     // 打印每个批次的统计
     for stats in &batch_stats {
-        println!("  批次 {}: 成功={}, 失败={}, 耗时={:?}", 
-                stats.batch_idx, stats.success_count, stats.error_count, stats.processing_time);
+        println!("  批次 {}: 成功={}, 失败={}, 结果={}, 耗时={:?}", 
+                stats.batch_idx, stats.success_count, stats.error_count, stats.result_count, stats.processing_time);
     }
     
     Ok(())
@@ -271,21 +268,18 @@ async fn find_optimal_batch_size() -> usize {
         let test_items = (1..=30).collect::<Vec<_>>();
         let start = Instant::now();
         
-        let mut handles = Vec::new();
-        for chunk in test_items.chunks(size) {
+        let mut tasks = TaskSet::new();
+        for (i, chunk) in test_items.chunks(size).enumerate() {
             let chunk = chunk.to_vec();
-            let handle = tokio::spawn(async move {
+            tasks.spawn_tracked(format!("批次{}", i + 1), async move {
                 for item in chunk {
                     tokio::time::sleep(Duration::from_millis(10)).await;
                     let _ = item * item;
                 }
             });
-            handles.push(handle);
         }
         
-        for handle in handles {
-            let _ = handle.await;
-        }
+        tasks.join_all().await;
         
         let elapsed = start.elapsed();
         if elapsed < best_time {
@@ -308,19 +302,34 @@ pub async fn batch_pipeline_example() -> Result<()> {
     
     println!("开始批处理管道 {} 个项目，批次大小: {}", items.len(), batch_size);
     
-    let start = Instant::now();
+    // 结束时把各阶段耗时记录为性能指标
+    let logger = Arc::new(AsyncLogger::new(LogConfig::default()));
+    let mut stopwatch = Stopwatch::start("批处理管道")
+        .with_logger(Arc::new(PerformanceLogger::new(Arc::clone(&logger))));
     
     // 第一阶段：数据预处理
     let preprocessed = preprocess_batch(items, batch_size).await?;
+    stopwatch.lap("预处理");
     
     // 第二阶段：数据转换
     let transformed = transform_batch(preprocessed, batch_size).await?;
+    stopwatch.lap("转换");
+    
+    // 检查中间结果的时间不计入管道耗时
+    stopwatch.pause();
+    println!("  转换结果 {} 个，秒表运行中: {}", transformed.len(), stopwatch.is_running());
+    stopwatch.resume();
     
     // 第三阶段：数据后处理
     let final_results = postprocess_batch(transformed, batch_size).await?;
+    stopwatch.lap("后处理");
     
-    let total_time = start.elapsed();
-    println!("批处理管道完成，总耗时: {:?}", total_time);
+    stopwatch.print_summary();
+    if let Some(slowest) = stopwatch.laps().iter().max_by_key(|lap| lap.duration) {
+        println!("最慢的阶段: {}", slowest.name);
+    }
+    stopwatch.finish().await;
+    logger.flush().await;
     println!("最终结果（前10个）: {:?}", &final_results[..10]);
     
     Ok(())
@@ -329,11 +338,11 @@ pub async fn batch_pipeline_example() -> Result<()> {
 /// 预处理批次
 async fn preprocess_batch(items: Vec<i32>, batch_size: usize) -> Result<Vec<i32>> {
     println!("  阶段1: 数据预处理");
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
-    for chunk in items.chunks(batch_size) {
+    for (i, chunk) in items.chunks(batch_size).enumerate() {
         let chunk = chunk.to_vec();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("批次{}", i + 1), async move {
             let mut results = Vec::new();
             for item in chunk {
                 tokio::time::sleep(Duration::from_millis(20)).await;
@@ -341,14 +350,11 @@ async fn preprocess_batch(items: Vec<i32>, batch_size: usize) -> Result<Vec<i32>
             }
             results
         });
-        handles.push(handle);
     }
     
     let mut all_results = Vec::new();
-    for handle in handles {
-        if let Ok(batch_results) = handle.await {
-            all_results.extend(batch_results);
-        }
+    for batch_results in tasks.join_all().await.into_result()? {
+        all_results.extend(batch_results);
     }
     
     Ok(all_results)
@@ -357,11 +363,11 @@ async fn preprocess_batch(items: Vec<i32>, batch_size: usize) -> Result<Vec<i32>
 /// 转换批次
 async fn transform_batch(items: Vec<i32>, batch_size: usize) -> Result<Vec<i32>> {
     println!("  阶段2: 数据转换");
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
-    for chunk in items.chunks(batch_size) {
+    for (i, chunk) in items.chunks(batch_size).enumerate() {
         let chunk = chunk.to_vec();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("批次{}", i + 1), async move {
             let mut results = Vec::new();
             for item in chunk {
                 tokio::time::sleep(Duration::from_millis(30)).await;
@@ -369,14 +375,11 @@ async fn transform_batch(items: Vec<i32>, batch_size: usize) -> Result<Vec<i32>>
             }
             results
         });
-        handles.push(handle);
     }
     
     let mut all_results = Vec::new();
-    for handle in handles {
-        if let Ok(batch_results) = handle.await {
-            all_results.extend(batch_results);
-        }
+    for batch_results in tasks.join_all().await.into_result()? {
+        all_results.extend(batch_results);
     }
     
     Ok(all_results)
@@ -385,11 +388,11 @@ async fn transform_batch(items: Vec<i32>, batch_size: usize) -> Result<Vec<i32>>
 /// 后处理批次
 async fn postprocess_batch(items: Vec<i32>, batch_size: usize) -> Result<Vec<i32>> {
     println!("  阶段3: 数据后处理");
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
-    for chunk in items.chunks(batch_size) {
+    for (i, chunk) in items.chunks(batch_size).enumerate() {
         let chunk = chunk.to_vec();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("批次{}", i + 1), async move {
             let mut results = Vec::new();
             for item in chunk {
                 tokio::time::sleep(Duration::from_millis(25)).await;
@@ -397,14 +400,11 @@ async fn postprocess_batch(items: Vec<i32>, batch_size: usize) -> Result<Vec<i32
             }
             results
         });
-        handles.push(handle);
     }
     
     let mut all_results = Vec::new();
-    for handle in handles {
-        if let Ok(batch_results) = handle.await {
-            all_results.extend(batch_results);
-        }
+    for batch_results in tasks.join_all().await.into_result()? {
+        all_results.extend(batch_results);
     }
     
     Ok(all_results)
@@ -0,0 +1,260 @@
+//! 通道示例
+//!
+//! 用一个订单处理流程串起 tokio 的各种通道，不依赖网络连接：
+//! - mpsc（有界）：多个下单方把订单交给处理器，缓冲区满时产生背压
+//! - mpsc（无界）：处理器写审计日志，永远不会因为日志阻塞
+//! - watch：运行中下发最新配置，以及广播关闭信号
+//! - broadcast：每个处理结果推送给多个订阅者
+//! - oneshot：处理器退出时把最终统计交回给协调者
+//!
+//! 关闭时先关闭订单通道拒绝新订单，再把缓冲区里已接收的订单处理完才退出。
+
+use anyhow::Result;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
+
+use crate::utils::tasks::TaskSet;
+
+/// 订单
+#[derive(Debug, Clone)]
+pub struct Order {
+    pub id: u32,
+    pub amount: u32,
+}
+
+/// 运行中可以修改的处理配置
+#[derive(Debug, Clone, Copy)]
+pub struct PipelineConfig {
+    /// 折扣百分比，100 表示不打折
+    pub discount_percent: u32,
+}
+
+/// 推送给订阅者的事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderEvent {
+    Processed { id: u32, total: u32 },
+    Drained { id: u32, total: u32 },
+}
+
+/// 处理器退出时交回的统计
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct WorkerStats {
+    /// 正常运行期间处理的订单
+    pub processed: usize,
+    /// 收到关闭信号后从缓冲区排空的订单
+    pub drained: usize,
+    pub revenue: u64,
+}
+
+/// 整个流程的运行结果
+#[derive(Debug)]
+pub struct PipelineReport {
+    /// 成功进入订单通道的订单数
+    pub accepted: usize,
+    /// 因通道关闭被拒绝的订单数
+    pub rejected: usize,
+    pub stats: WorkerStats,
+    /// 每个 broadcast 订阅者收到的事件数
+    pub events_seen: Vec<usize>,
+    pub audit_log: Vec<String>,
+}
+
+/// 处理单个订单：按当前配置计算金额，推送事件并写审计日志
+async fn handle_order(
+    order: Order,
+    draining: bool,
+    config: &watch::Receiver<PipelineConfig>,
+    events: &broadcast::Sender<OrderEvent>,
+    audit: &mpsc::UnboundedSender<String>,
+    stats: &mut WorkerStats,
+    work: Duration,
+) {
+    tokio::time::sleep(work).await;
+    // borrow() 总是读到最新的配置值
+    let total = order.amount * config.borrow().discount_percent / 100;
+    stats.revenue += total as u64;
+
+    let event = if draining {
+        stats.drained += 1;
+        OrderEvent::Drained { id: order.id, total }
+    } else {
+        stats.processed += 1;
+        OrderEvent::Processed { id: order.id, total }
+    };
+    // 没有订阅者时发送失败，可以忽略
+    let _ = events.send(event);
+    let _ = audit.send(format!("订单 {} 金额 {} -> {}", order.id, order.amount, total));
+}
+
+/// 订单处理器
+async fn order_worker(
+    mut orders: mpsc::Receiver<Order>,
+    config: watch::Receiver<PipelineConfig>,
+    mut shutdown: watch::Receiver<bool>,
+    events: broadcast::Sender<OrderEvent>,
+    audit: mpsc::UnboundedSender<String>,
+    done: oneshot::Sender<WorkerStats>,
+    work: Duration,
+) {
+    let mut stats = WorkerStats::default();
+
+    loop {
+        tokio::select! {
+            // 优先检查关闭信号
+            biased;
+            _ = shutdown.changed() => break,
+            order = orders.recv() => match order {
+                Some(order) => {
+                    handle_order(order, false, &config, &events, &audit, &mut stats, work).await;
+                }
+                // 所有下单方都已退出
+                None => break,
+            },
+        }
+    }
+
+    // 排空：close 之后 send 会失败，但已经在缓冲区里的订单仍然可以 recv 到
+    orders.close();
+    while let Some(order) = orders.recv().await {
+        handle_order(order, true, &config, &events, &audit, &mut stats, work).await;
+    }
+
+    let _ = done.send(stats);
+}
+
+/// broadcast 订阅者：统计收到的事件，通道关闭后返回数量
+async fn event_listener(name: &'static str, mut events: broadcast::Receiver<OrderEvent>) -> usize {
+    let mut seen = 0;
+    loop {
+        match events.recv().await {
+            Ok(_) => seen += 1,
+            // 订阅者处理太慢时会跳过部分旧事件
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                println!("[{}] 落后，跳过 {} 条事件", name, skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+    seen
+}
+
+/// 运行订单流程
+///
+/// `producers` 个下单方各自提交 `orders_per_producer` 个订单，
+/// 经过 `run_for` 后发出关闭信号，处理器排空缓冲区后退出。
+pub async fn run_order_pipeline(
+    producers: u32,
+    orders_per_producer: u32,
+    capacity: usize,
+    work: Duration,
+    run_for: Duration,
+) -> Result<PipelineReport> {
+    let (order_tx, order_rx) = mpsc::channel(capacity);
+    let (audit_tx, mut audit_rx) = mpsc::unbounded_channel();
+    let (config_tx, config_rx) = watch::channel(PipelineConfig { discount_percent: 100 });
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let (event_tx, _) = broadcast::channel(64);
+    let (done_tx, done_rx) = oneshot::channel();
+
+    let mut listeners = TaskSet::new();
+    listeners.spawn_tracked("日志", event_listener("日志", event_tx.subscribe()));
+    listeners.spawn_tracked("统计", event_listener("统计", event_tx.subscribe()));
+    tokio::spawn(order_worker(order_rx, config_rx, shutdown_rx, event_tx, audit_tx, done_tx, work));
+
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let rejected = Arc::new(AtomicUsize::new(0));
+    let mut producer_tasks = TaskSet::new();
+    for p in 0..producers {
+        let order_tx = order_tx.clone();
+        let accepted = Arc::clone(&accepted);
+        let rejected = Arc::clone(&rejected);
+        producer_tasks.spawn_tracked(format!("下单方{}", p), async move {
+            for i in 0..orders_per_producer {
+                let order = Order { id: p * 1000 + i, amount: 100 + i * 10 };
+                // 缓冲区满时在这里等待；通道关闭后发送失败
+                if order_tx.send(order).await.is_ok() {
+                    accepted.fetch_add(1, Ordering::SeqCst);
+                } else {
+                    rejected.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        });
+    }
+    // 只保留下单方持有的发送端，全部退出后处理器能收到 None
+    drop(order_tx);
+
+    // 运行一半时打八折；处理器可能已经退出，send_replace 不关心有没有接收方
+    tokio::time::sleep(run_for / 2).await;
+    config_tx.send_replace(PipelineConfig { discount_percent: 80 });
+    tokio::time::sleep(run_for / 2).await;
+    shutdown_tx.send_replace(true);
+
+    // 处理器排空后才会交回统计
+    let stats = done_rx.await?;
+    producer_tasks.join_all().await.into_result()?;
+    let events_seen = listeners.join_all().await.into_result()?;
+    // 处理器退出时丢弃了发送端，这里能读完所有日志
+    let mut audit_log = Vec::new();
+    while let Some(line) = audit_rx.recv().await {
+        audit_log.push(line);
+    }
+
+    Ok(PipelineReport {
+        accepted: accepted.load(Ordering::SeqCst),
+        rejected: rejected.load(Ordering::SeqCst),
+        stats,
+        events_seen,
+        audit_log,
+    })
+}
+
+/// 通道示例入口
+pub async fn channels_example() -> Result<()> {
+    println!("\n=== 通道示例 ===");
+
+    let report = run_order_pipeline(3, 20, 8, Duration::from_millis(10), Duration::from_millis(200)).await?;
+    println!(
+        "进入通道 {} 个订单，关闭后被拒绝 {} 个",
+        report.accepted, report.rejected
+    );
+    println!(
+        "正常处理 {} 个，关闭时排空 {} 个，营收 {}",
+        report.stats.processed, report.stats.drained, report.stats.revenue
+    );
+    println!("各订阅者收到的事件数: {:?}", report.events_seen);
+    println!("审计日志共 {} 条，最后一条: {:?}", report.audit_log.len(), report.audit_log.last());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain_on_shutdown_processes_every_accepted_order() {
+        let report = run_order_pipeline(3, 50, 8, Duration::from_millis(5), Duration::from_millis(60))
+            .await
+            .unwrap();
+        let handled = report.stats.processed + report.stats.drained;
+
+        assert_eq!(report.accepted + report.rejected, 150);
+        assert_eq!(handled, report.accepted);
+        assert!(report.rejected > 0, "关闭时应该还有未提交的订单");
+        assert!(report.stats.drained > 0, "缓冲区里应该还有待排空的订单");
+        assert_eq!(report.events_seen, vec![handled, handled]);
+        assert_eq!(report.audit_log.len(), handled);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_finishes_when_producers_exit() {
+        let report = run_order_pipeline(2, 5, 4, Duration::from_millis(1), Duration::from_millis(200))
+            .await
+            .unwrap();
+        assert_eq!(report.accepted, 10);
+        assert_eq!(report.rejected, 0);
+        assert_eq!(report.stats.processed, 10);
+        assert_eq!(report.stats.drained, 0);
+    }
+}
@@ -5,8 +5,12 @@
 //! - 流处理示例
 //! - 批处理示例
 //! - 离线示例
+//! - select! 与超时示例
+//! - 通道示例
 
 pub mod basic;
 pub mod stream;
 pub mod batch;
 pub mod offline;
+pub mod select;
+pub mod channels;
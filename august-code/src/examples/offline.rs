@@ -12,6 +12,10 @@ use std::time::Duration;
 use tokio::sync::Mutex;
 use tokio::time::Instant;
 
+use crate::core::connectivity::MockHttpClient;
+use crate::core::http_client::AsyncHttpClient;
+use crate::utils::tasks::TaskSet;
+
 /// 离线版本的异步编程示例
 pub async fn offline_async_examples() -> Result<()> {
     println!("\n=== 离线异步编程示例 ===");
@@ -75,9 +79,9 @@ async fn filesystem_operations() -> Result<()> {
     println!("创建测试目录: test_data");
     
     // 异步写入多个文件
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     for i in 1..=5 {
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("file_{}.txt", i), async move {
             let filename = format!("test_data/file_{}.txt", i);
             let content = format!("这是文件 {} 的内容\n时间: {}", 
                                 i, chrono::Utc::now().format("%Y-%m-%d %H:%M:%S"));
@@ -86,12 +90,11 @@ async fn filesystem_operations() -> Result<()> {
             println!("写入文件: {}", filename);
             Ok::<(), anyhow::Error>(())
         });
-        handles.push(handle);
     }
     
-    // 等待所有文件写入完成
-    for handle in handles {
-        handle.await??;
+    // 等待所有文件写入完成，最多等 5 秒
+    for written in tasks.join_all_with_timeout(Duration::from_secs(5)).await.into_result()? {
+        written?;
     }
     
     // 异步读取文件
@@ -133,7 +136,8 @@ async fn local_data_processing() -> Result<()> {
     
     // 异步数据聚合
     let aggregated = async_aggregate_data(transformed_data).await?;
-    println!("聚合结果: {:?}", aggregated);
+    println!("聚合结果: 总和 {}，{} 个，最小 {}，最大 {}，平均 {:.2}",
+            aggregated.sum, aggregated.count, aggregated.min, aggregated.max, aggregated.average);
     
     Ok(())
 }
@@ -149,26 +153,37 @@ async fn simulated_network_operations() -> Result<()> {
         "http://localhost:8080/api/orders",
     ];
     
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     for url in urls {
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(url, async move {
             simulate_http_request(url).await
         });
-        handles.push(handle);
     }
     
-    let mut results = Vec::new();
-    for handle in handles {
-        if let Ok(result) = handle.await {
-            results.push(result);
+    // 超时的请求被取消，只输出按时完成的结果
+    let finished = tasks.join_all_with_timeout(Duration::from_secs(1)).await;
+    if !finished.is_ok() {
+        for failed in &finished.failed {
+            println!("  {} 未完成: {}", failed.name, failed.failure);
         }
     }
+    let results = finished.into_values();
     
     println!("模拟网络请求结果:");
     for result in results {
         println!("  {}: 状态={}, 耗时={:?}", result.url, result.status, result.duration);
     }
     
+    // 模拟客户端按 httpbin 的约定生成响应，不发起真实请求
+    let mock_client = AsyncHttpClient::with_mock(
+        MockHttpClient::with_latency(Duration::from_millis(50)),
+        Duration::from_secs(1),
+    );
+    for url in ["https://httpbin.org/get", "https://httpbin.org/status/503"] {
+        let response = mock_client.fetch_url(url).await?;
+        println!("模拟客户端: {} -> 状态={}, 内容长度={:?}", url, response.status, response.content_length);
+    }
+    
     // 模拟数据库操作
     let db_operations = vec![
         "SELECT * FROM users WHERE active = true",
@@ -205,18 +220,17 @@ async fn async_resource_management() -> Result<()> {
     println!("创建了 5 个资源");
     
     // 异步使用资源
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     for i in 0..10 {
         let pool = Arc::clone(&resource_pool);
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("使用者{}", i), async move {
             use_resource(pool, i).await
         });
-        handles.push(handle);
     }
     
     // 等待所有任务完成
-    for handle in handles {
-        handle.await?;
+    for used in tasks.join_all().await.into_result()? {
+        used?;
     }
     
     // 检查资源状态
@@ -246,11 +260,11 @@ async fn async_calculation(n: i32) -> i32 {
 
 /// 异步数据过滤
 async fn async_filter_data(data: Vec<i32>) -> Result<Vec<i32>> {
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
-    for chunk in data.chunks(100) {
+    for (i, chunk) in data.chunks(100).enumerate() {
         let chunk = chunk.to_vec();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("分块{}", i + 1), async move {
             let mut results = Vec::new();
             for item in chunk {
                 tokio::time::sleep(Duration::from_millis(1)).await;
@@ -260,14 +274,11 @@ async fn async_filter_data(data: Vec<i32>) -> Result<Vec<i32>> {
             }
             results
         });
-        handles.push(handle);
     }
     
     let mut all_results = Vec::new();
-    for handle in handles {
-        if let Ok(chunk_results) = handle.await {
-            all_results.extend(chunk_results);
-        }
+    for chunk_results in tasks.join_all().await.into_result()? {
+        all_results.extend(chunk_results);
     }
     
     Ok(all_results)
@@ -275,11 +286,11 @@ async fn async_filter_data(data: Vec<i32>) -> Result<Vec<i32>> {
 
 /// 异步数据转换
 async fn async_transform_data(data: Vec<i32>) -> Result<Vec<i32>> {
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
-    for chunk in data.chunks(100) {
+    for (i, chunk) in data.chunks(100).enumerate() {
         let chunk = chunk.to_vec();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("分块{}", i + 1), async move {
             let mut results = Vec::new();
             for item in chunk {
                 tokio::time::sleep(Duration::from_millis(1)).await;
@@ -287,14 +298,11 @@ async fn async_transform_data(data: Vec<i32>) -> Result<Vec<i32>> {
             }
             results
         });
-        handles.push(handle);
     }
     
     let mut all_results = Vec::new();
-    for handle in handles {
-        if let Ok(chunk_results) = handle.await {
-            all_results.extend(chunk_results);
-        }
+    for chunk_results in tasks.join_all().await.into_result()? {
+        all_results.extend(chunk_results);
     }
     
     Ok(all_results)
@@ -302,11 +310,11 @@ async fn async_transform_data(data: Vec<i32>) -> Result<Vec<i32>> {
 
 /// 异步数据聚合
 async fn async_aggregate_data(data: Vec<i32>) -> Result<DataAggregate> {
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
-    for chunk in data.chunks(100) {
+    for (i, chunk) in data.chunks(100).enumerate() {
         let chunk = chunk.to_vec();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("分块{}", i + 1), async move {
             let mut sum = 0;
             let mut count = 0;
             let mut min = i32::MAX;
@@ -322,7 +330,6 @@ async fn async_aggregate_data(data: Vec<i32>) -> Result<DataAggregate> {
             
             (sum, count, min, max)
         });
-        handles.push(handle);
     }
     
     let mut total_sum = 0;
@@ -330,13 +337,11 @@ async fn async_aggregate_data(data: Vec<i32>) -> Result<DataAggregate> {
     let mut global_min = i32::MAX;
     let mut global_max = i32::MIN;
     
-    for handle in handles {
-        if let Ok((sum, count, min, max)) = handle.await {
-            total_sum += sum;
-            total_count += count;
-            global_min = global_min.min(min);
-            global_max = global_max.max(max);
-        }
+    for (sum, count, min, max) in tasks.join_all().await.into_result()? {
+        total_sum += sum;
+        total_count += count;
+        global_min = global_min.min(min);
+        global_max = global_max.max(max);
     }
     
     Ok(DataAggregate {
@@ -391,8 +396,9 @@ async fn use_resource(pool: Arc<Mutex<Vec<Resource>>>, task_id: usize) -> Result
         pool.pop()
     };
     
-    if let Some(mut resource) = resource {
-        println!("任务 {} 使用资源: {}", task_id, resource.name);
+    if let Some(resource) = resource {
+        println!("任务 {} 使用资源: {}（ID {}，已创建 {:?}）",
+                task_id, resource.name, resource.id, resource.created_at.elapsed());
         
         // 模拟使用资源
         tokio::time::sleep(Duration::from_millis(100)).await;
@@ -0,0 +1,236 @@
+//! select! 与超时示例
+//!
+//! 演示 `tokio::select!` 的常见用法，不依赖网络连接：
+//! - 取消安全：哪些分支被丢弃后会丢数据，以及如何避免
+//! - 有偏选择：`biased;` 按书写顺序检查分支
+//! - 超时与真实工作赛跑
+//! - `race_ok`：从多个镜像中取第一个成功的响应
+
+use anyhow::Result;
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+/// 并发执行所有 future，返回第一个成功的结果
+///
+/// 一旦有 future 成功，其余未完成的 future 会被直接丢弃（即被取消）。
+/// 全部失败时按完成顺序返回所有错误；传入空集合时返回空的错误列表。
+pub async fn race_ok<I, F, T, E>(futures: I) -> std::result::Result<T, Vec<E>>
+where
+    I: IntoIterator<Item = F>,
+    F: Future<Output = std::result::Result<T, E>>,
+{
+    let mut pending: FuturesUnordered<F> = futures.into_iter().collect();
+    let mut errors = Vec::new();
+    while let Some(result) = pending.next().await {
+        match result {
+            Ok(value) => return Ok(value),
+            Err(e) => errors.push(e),
+        }
+    }
+    Err(errors)
+}
+
+/// select! 示例入口
+pub async fn select_example() -> Result<()> {
+    println!("\n=== select! 示例 ===");
+
+    println!("1. 取消安全");
+    cancellation_safety_example().await;
+
+    println!("\n2. 有偏选择");
+    biased_select_example().await;
+
+    println!("\n3. 超时与真实工作赛跑");
+    timeout_race_example().await;
+
+    println!("\n4. 多镜像取第一个成功响应");
+    mirrors_example().await;
+
+    Ok(())
+}
+
+/// 连续读取两条消息组成一对
+///
+/// 不是取消安全的：第一条读到之后如果被 select! 丢弃，这条消息就丢了
+async fn read_pair(rx: &mut mpsc::Receiver<u32>) -> Option<(u32, u32)> {
+    let first = rx.recv().await?;
+    let second = rx.recv().await?;
+    Some((first, second))
+}
+
+/// 每条消息间隔 30ms 发送
+fn spawn_producer(count: u32) -> mpsc::Receiver<u32> {
+    let (tx, rx) = mpsc::channel(16);
+    tokio::spawn(async move {
+        for i in 1..=count {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            if tx.send(i).await.is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// 在循环里 select!，每轮都会重新创建未完成的分支
+async fn cancellation_safety_example() {
+    // 错误用法：每轮重新调用 read_pair，定时器触发时读了一半的消息被丢弃
+    let mut rx = spawn_producer(6);
+    let mut ticker = tokio::time::interval(Duration::from_millis(40));
+    let mut received = Vec::new();
+    loop {
+        tokio::select! {
+            pair = read_pair(&mut rx) => match pair {
+                Some((a, b)) => received.extend([a, b]),
+                None => break,
+            },
+            _ = ticker.tick() => {}
+        }
+    }
+    println!("每轮重建 read_pair: 收到 {:?}（消息可能丢失）", received);
+
+    // 正确用法：`recv()` 本身是取消安全的，每次只取一条，
+    // 未完成的配对状态保存在 select! 之外
+    let mut rx = spawn_producer(6);
+    let mut ticker = tokio::time::interval(Duration::from_millis(40));
+    let mut received = Vec::new();
+    let mut pending = None;
+    loop {
+        tokio::select! {
+            msg = rx.recv() => match (msg, pending.take()) {
+                (Some(b), Some(a)) => received.extend([a, b]),
+                (Some(a), None) => pending = Some(a),
+                (None, _) => break,
+            },
+            _ = ticker.tick() => {}
+        }
+    }
+    println!("只 select 取消安全的 recv(): 收到 {:?}", received);
+}
+
+/// 关闭信号与任务同时就绪时，biased 保证先处理关闭信号
+async fn biased_select_example() {
+    let (shutdown_tx, mut shutdown_rx) = mpsc::channel::<()>(1);
+    let (job_tx, mut job_rx) = mpsc::channel(16);
+    for i in 1..=5 {
+        job_tx.send(i).await.expect("通道容量足够");
+    }
+    shutdown_tx.send(()).await.expect("通道容量足够");
+
+    // 默认随机选择分支：关闭前可能还会再处理几个任务
+    // 有偏选择：按书写顺序检查，关闭信号总是最先被处理
+    let mut processed = Vec::new();
+    loop {
+        tokio::select! {
+            biased;
+            _ = shutdown_rx.recv() => {
+                println!("收到关闭信号，剩余任务不再处理");
+                break;
+            }
+            Some(job) = job_rx.recv() => processed.push(job),
+        }
+    }
+    println!("关闭前处理的任务: {:?}", processed);
+}
+
+/// 模拟耗时的真实工作
+async fn compute(label: &str, work_ms: u64) -> String {
+    tokio::time::sleep(Duration::from_millis(work_ms)).await;
+    format!("{} 完成", label)
+}
+
+/// 工作在截止时间前完成则使用结果，否则放弃
+async fn timeout_race_example() {
+    for (label, work_ms) in [("快速计算", 50), ("慢速计算", 300)] {
+        let start = Instant::now();
+        tokio::select! {
+            result = compute(label, work_ms) => {
+                println!("{}，耗时 {:?}", result, start.elapsed());
+            }
+            _ = tokio::time::sleep(Duration::from_millis(100)) => {
+                println!("{} 超时，已取消（耗时 {:?}）", label, start.elapsed());
+            }
+        }
+    }
+
+    // tokio::time::timeout 是上面写法的简化版本
+    match tokio::time::timeout(Duration::from_millis(100), compute("timeout 包装", 20)).await {
+        Ok(result) => println!("{}", result),
+        Err(_) => println!("timeout 包装 超时"),
+    }
+}
+
+/// 模拟镜像请求：指定延迟后成功或失败
+async fn fetch_mirror(name: &'static str, delay_ms: u64, ok: bool) -> std::result::Result<String, String> {
+    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+    if ok {
+        Ok(format!("来自 {} 的响应", name))
+    } else {
+        Err(format!("{} 不可用", name))
+    }
+}
+
+async fn mirrors_example() {
+    let start = Instant::now();
+    let result = race_ok([
+        fetch_mirror("mirror-a", 50, false),
+        fetch_mirror("mirror-b", 120, true),
+        fetch_mirror("mirror-c", 80, true),
+    ])
+    .await;
+    match result {
+        Ok(response) => println!("{}，耗时 {:?}", response, start.elapsed()),
+        Err(errors) => println!("全部镜像失败: {:?}", errors),
+    }
+
+    let result = race_ok([
+        fetch_mirror("mirror-a", 30, false),
+        fetch_mirror("mirror-b", 10, false),
+    ])
+    .await;
+    if let Err(errors) = result {
+        println!("全部镜像失败: {:?}", errors);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_race_ok_returns_first_success() {
+        let start = Instant::now();
+        let result = race_ok([
+            fetch_mirror("a", 10, false),
+            fetch_mirror("b", 500, true),
+            fetch_mirror("c", 30, true),
+        ])
+        .await;
+        assert_eq!(result, Ok("来自 c 的响应".to_string()));
+        // 慢的镜像被取消，不需要等它完成
+        assert!(start.elapsed() < Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn test_race_ok_collects_all_errors() {
+        let result = race_ok([
+            fetch_mirror("a", 30, false),
+            fetch_mirror("b", 10, false),
+        ])
+        .await;
+        assert_eq!(result, Err(vec!["b 不可用".to_string(), "a 不可用".to_string()]));
+
+        let empty: Vec<std::future::Ready<std::result::Result<u32, String>>> = Vec::new();
+        assert_eq!(race_ok(empty).await, Err(Vec::new()));
+    }
+
+    #[tokio::test]
+    async fn test_read_pair() {
+        let mut rx = spawn_producer(3);
+        assert_eq!(read_pair(&mut rx).await, Some((1, 2)));
+        assert_eq!(read_pair(&mut rx).await, None);
+    }
+}
@@ -8,7 +8,8 @@
 
 use anyhow::Result;
 use std::time::Duration;
-use tokio::time::Instant;
+
+use crate::utils::tasks::TaskSet;
 
 /// 简化的异步流处理示例
 pub async fn simple_stream_example() -> Result<()> {
@@ -16,11 +17,11 @@ pub async fn simple_stream_example() -> Result<()> {
     
     // 使用基本的并发处理而不是复杂的流
     let numbers = (1..=10).collect::<Vec<_>>();
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
-    for chunk in numbers.chunks(3) {
+    for (i, chunk) in numbers.chunks(3).enumerate() {
         let chunk = chunk.to_vec();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("分块{}", i + 1), async move {
             let mut results = Vec::new();
             for n in chunk {
                 tokio::time::sleep(Duration::from_millis(50)).await;
@@ -28,14 +29,11 @@ pub async fn simple_stream_example() -> Result<()> {
             }
             results
         });
-        handles.push(handle);
     }
     
     let mut all_results = Vec::new();
-    for handle in handles {
-        if let Ok(chunk_results) = handle.await {
-            all_results.extend(chunk_results);
-        }
+    for chunk_results in tasks.join_all().await.into_result()? {
+        all_results.extend(chunk_results);
     }
     
     all_results.sort();
@@ -47,20 +45,20 @@ pub async fn simple_stream_example() -> Result<()> {
 pub async fn stream_transform_example() -> Result<()> {
     println!("\n=== 异步流转换示例 ===");
     
-    let numbers = (1..=20).collect::<Vec<_>>();
-    let mut handles = Vec::new();
+    let numbers = (1..=20).collect::<Vec<i32>>();
+    let mut tasks = TaskSet::new();
     
     // 将数字分组并异步处理
-    for chunk in numbers.chunks(5) {
+    for (i, chunk) in numbers.chunks(5).enumerate() {
         let chunk = chunk.to_vec();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("分块{}", i + 1), async move {
             let mut results = Vec::new();
             for n in chunk {
                 // 模拟异步转换
                 tokio::time::sleep(Duration::from_millis(20)).await;
                 
                 // 应用多种转换
-                let transformed = (n as i32)
+                let transformed = n
                     .saturating_mul(2)  // 乘以2
                     .saturating_add(10) // 加10
                     .pow(2); // 平方
@@ -69,14 +67,11 @@ pub async fn stream_transform_example() -> Result<()> {
             }
             results
         });
-        handles.push(handle);
     }
     
     let mut all_results = Vec::new();
-    for handle in handles {
-        if let Ok(chunk_results) = handle.await {
-            all_results.extend(chunk_results);
-        }
+    for chunk_results in tasks.join_all().await.into_result()? {
+        all_results.extend(chunk_results);
     }
     
     all_results.sort();
@@ -91,12 +86,12 @@ pub async fn stream_filter_example() -> Result<()> {
     println!("\n=== 异步流过滤示例 ===");
     
     let numbers = (1..=100).collect::<Vec<_>>();
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
     // 分组处理并过滤
-    for chunk in numbers.chunks(10) {
+    for (i, chunk) in numbers.chunks(10).enumerate() {
         let chunk = chunk.to_vec();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("分块{}", i + 1), async move {
             let mut results = Vec::new();
             for n in chunk {
                 // 模拟异步处理
@@ -109,14 +104,11 @@ pub async fn stream_filter_example() -> Result<()> {
             }
             results
         });
-        handles.push(handle);
     }
     
     let mut all_results = Vec::new();
-    for handle in handles {
-        if let Ok(chunk_results) = handle.await {
-            all_results.extend(chunk_results);
-        }
+    for chunk_results in tasks.join_all().await.into_result()? {
+        all_results.extend(chunk_results);
     }
     
     all_results.sort();
@@ -131,12 +123,12 @@ pub async fn stream_aggregate_example() -> Result<()> {
     println!("\n=== 异步流聚合示例 ===");
     
     let numbers = (1..=1000).collect::<Vec<_>>();
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
     // 分组处理并计算统计信息
-    for chunk in numbers.chunks(100) {
+    for (i, chunk) in numbers.chunks(100).enumerate() {
         let chunk = chunk.to_vec();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("分块{}", i + 1), async move {
             let mut sum = 0;
             let mut count = 0;
             let mut min = i32::MAX;
@@ -154,7 +146,6 @@ pub async fn stream_aggregate_example() -> Result<()> {
             
             (sum, count, min, max)
         });
-        handles.push(handle);
     }
     
     let mut total_sum = 0;
@@ -162,13 +153,11 @@ pub async fn stream_aggregate_example() -> Result<()> {
     let mut global_min = i32::MAX;
     let mut global_max = i32::MIN;
     
-    for handle in handles {
-        if let Ok((sum, count, min, max)) = handle.await {
-            total_sum += sum;
-            total_count += count;
-            global_min = global_min.min(min);
-            global_max = global_max.max(max);
-        }
+    for (sum, count, min, max) in tasks.join_all().await.into_result()? {
+        total_sum += sum;
+        total_count += count;
+        global_min = global_min.min(min);
+        global_max = global_max.max(max);
     }
     
     let average = if total_count > 0 {
@@ -196,12 +185,12 @@ pub async fn stream_merge_example() -> Result<()> {
     let source2 = (11..=20).collect::<Vec<_>>();
     let source3 = (21..=30).collect::<Vec<_>>();
     
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
     // 处理每个数据源
     for (i, source) in [source1, source2, source3].iter().enumerate() {
         let source = source.clone();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("源{}", i + 1), async move {
             let mut results = Vec::new();
             for n in source {
                 // 模拟异步处理
@@ -210,15 +199,12 @@ pub async fn stream_merge_example() -> Result<()> {
             }
             results
         });
-        handles.push(handle);
     }
     
     // 合并所有结果
     let mut all_results = Vec::new();
-    for handle in handles {
-        if let Ok(chunk_results) = handle.await {
-            all_results.extend(chunk_results);
-        }
+    for chunk_results in tasks.join_all().await.into_result()? {
+        all_results.extend(chunk_results);
     }
     
     all_results.sort();
@@ -233,11 +219,11 @@ pub async fn stream_error_handling_example() -> Result<()> {
     println!("\n=== 异步流错误处理示例 ===");
     
     let numbers = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
-    let mut handles = Vec::new();
+    let mut tasks = TaskSet::new();
     
-    for chunk in numbers.chunks(2) {
+    for (i, chunk) in numbers.chunks(2).enumerate() {
         let chunk = chunk.to_vec();
-        let handle = tokio::spawn(async move {
+        tasks.spawn_tracked(format!("分块{}", i + 1), async move {
             let mut results = Vec::new();
             for n in chunk {
                 // 模拟可能失败的操作
@@ -252,29 +238,29 @@ pub async fn stream_error_handling_example() -> Result<()> {
             }
             Ok(results)
         });
-        handles.push(handle);
     }
     
     let mut success_count = 0;
     let mut error_count = 0;
     let mut all_results = Vec::new();
     
-    for handle in handles {
-        match handle.await {
-            Ok(Ok(results)) => {
+    let results = tasks.join_all().await;
+    for (name, result) in results.completed {
+        match result {
+            Ok(results) => {
                 success_count += 1;
                 all_results.extend(results);
             }
-            Ok(Err(e)) => {
-                error_count += 1;
-                println!("处理失败: {}", e);
-            }
             Err(e) => {
                 error_count += 1;
-                println!("任务失败: {}", e);
+                println!("{} 处理失败: {}", name, e);
             }
         }
     }
+    for failed in results.failed {
+        error_count += 1;
+        println!("任务 {} 失败: {}", failed.name, failed.failure);
+    }
     
     all_results.sort();
     println!("成功处理: {} 个批次", success_count);
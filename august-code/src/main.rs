@@ -1,30 +1,60 @@
 use anyhow::Result;
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tokio::time::Instant;
 
 // 模块声明
 mod core;
+#[deprecated(note = "请改用 core::database")]
+mod async_db;
 mod examples;
 mod utils;
 mod tests;
 
 // 导入核心模块
 use core::http_client::AsyncHttpClient;
-use core::database::database_operations_example;
-use core::web_server::{AsyncWebServer, TaskScheduler, RateLimiter};
+use core::recording::recording_example;
+use core::outbound::outbound_example;
+use core::crawler::crawl_example;
+use core::database::{database_operations_example, AsyncDatabase};
+use core::backup::backup_example;
+use core::migrations::migrations_example;
+use core::replication::replication_example;
+use core::user_cache::user_cache_example;
+use core::data_source::data_source_example;
+use core::context::context_example;
+use core::connectivity;
+use core::api_client::api_client_example;
+use core::ws_client::ws_client_example;
+use core::resolver::resolver_example;
+use core::web_server::{AsyncWebServer, DiskTierConfig, RateLimiter};
+use core::scheduler::scheduler_example;
 
 // 导入示例模块
-use examples::basic::{simple_async_examples, timer_example, mutex_example};
-use examples::stream::{simple_stream_example, stream_transform_example};
-use examples::batch::{simple_batch_example, dynamic_batch_example};
+use examples::basic::{simple_async_examples, timer_example, mutex_example, resource_management_example};
+use examples::stream::{
+    simple_stream_example, stream_transform_example, stream_filter_example, stream_aggregate_example,
+    stream_merge_example, stream_error_handling_example,
+};
+use examples::batch::{
+    simple_batch_example, dynamic_batch_example, optimized_batch_example, monitored_batch_example, batch_pipeline_example,
+};
 use examples::offline::offline_async_examples;
+use examples::select::select_example;
+use examples::channels::channels_example;
 
 // 导入工具模块
 use utils::time::time_utils_example;
 use utils::config::config_utils_example;
+use utils::error::error_handling_example;
 use utils::logging::logging_utils_example;
+use utils::bulkhead::bulkhead_example;
+use utils::clock::MockClock;
+use utils::html;
+use utils::report;
+use utils::test_server::TestServer;
 
 // 导入测试模块
 use tests::performance::performance_test_example;
@@ -32,340 +62,429 @@ use tests::error_handling::error_handling_test_example;
 use tests::concurrency::concurrency_test_example;
 use tests::integration::integration_test_example;
 
-#[derive(Debug, Deserialize, Serialize)]
-struct ApiResponse {
-    url: String,
-    status: u16,
-    response_time_ms: u64,
-    content_length: Option<usize>,
+/// Rust 异步编程示例程序
+#[derive(Parser)]
+#[command(name = "august-code")]
+#[command(about = "按模块运行 Rust 异步编程示例")]
+struct Cli {
+    /// 不探测外部网络，直接以离线模式运行（外部地址使用模拟客户端）
+    #[arg(long, global = true)]
+    skip_network: bool,
+
+    /// 把运行结果写入报告，目前支持 json
+    #[arg(long, global = true, value_enum, env = "AUGUST_REPORT")]
+    report: Option<ReportFormat>,
+
+    /// 报告文件路径，单独指定时也会开启 JSON 报告
+    #[arg(long, global = true, env = "AUGUST_REPORT_FILE")]
+    report_file: Option<PathBuf>,
+
+    /// 把性能测试结果记录到基准测试数据库（SQLite），用 `benchdb report` 查看趋势
+    #[arg(long, global = true, env = "BENCHDB")]
+    benchdb: Option<PathBuf>,
+
+    /// 覆盖配置项，例如 `--set server.port=9999`，可以重复使用，优先级高于配置文件和环境变量
+    #[arg(long = "set", value_name = "KEY=VALUE", global = true)]
+    set: Vec<String>,
+
+    /// 要运行的示例模块，默认运行全部
+    #[command(subcommand)]
+    command: Option<Commands>,
 }
 
-#[derive(Debug, Deserialize)]
-struct HttpBinResponse {
-    url: String,
-    headers: serde_json::Value,
-    origin: String,
+/// 运行报告格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    /// 机器可读的 JSON
+    Json,
 }
 
-/// 异步获取单个 URL 的数据
-async fn fetch_url(client: &Client, url: &str) -> Result<ApiResponse> {
-    let start = Instant::now();
-    
-    let response = client
-        .get(url)
-        .timeout(Duration::from_secs(10))
-        .send()
-        .await?;
-    
-    let status = response.status().as_u16();
-    let content_length = response.content_length().map(|len| len as usize);
-    let response_time = start.elapsed().as_millis() as u64;
-    
-    // 读取响应体（可选）
-    let _body = response.text().await?;
-    
-    Ok(ApiResponse {
-        url: url.to_string(),
-        status,
-        response_time_ms: response_time,
-        content_length,
-    })
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+enum Commands {
+    /// 基础异步、流、select! 和通道示例
+    Basics,
+    /// HTTP 客户端、Web 服务器和限流器示例
+    Http,
+    /// 数据库和数据源示例
+    Db,
+    /// 任务调度和截止时间传播示例
+    Scheduler,
+    /// 批处理示例
+    Batch,
+    /// 时间、错误、配置、日志和隔舱工具示例
+    Utils,
+    /// 不依赖网络的模拟示例和集成测试
+    Offline,
+    /// 性能和并发测试
+    Bench,
+    /// 依次运行所有示例
+    All,
+    /// 把持久化数据库导出为压缩备份
+    Backup {
+        /// 数据库预写日志路径
+        #[arg(long)]
+        wal: PathBuf,
+        /// 备份文件路径
+        #[arg(long, short)]
+        output: PathBuf,
+    },
+    /// 校验备份并恢复到持久化数据库，覆盖现有数据
+    Restore {
+        /// 备份文件路径
+        #[arg(long, short)]
+        input: PathBuf,
+        /// 数据库预写日志路径
+        #[arg(long)]
+        wal: PathBuf,
+    },
 }
 
-/// 并发获取多个 URL 的数据
-async fn fetch_multiple_urls(urls: Vec<String>) -> Result<Vec<ApiResponse>> {
-    let client = Client::new();
-    let mut handles = Vec::new();
-    
-    // 为每个 URL 创建异步任务
-    for url in urls {
-        let client_clone = client.clone();
-        let handle = tokio::spawn(async move {
-            fetch_url(&client_clone, &url).await
-        });
-        handles.push(handle);
-    }
-    
-    // 等待所有任务完成
-    let mut results = Vec::new();
-    for handle in handles {
-        match handle.await? {
-            Ok(response) => results.push(response),
-            Err(e) => eprintln!("请求失败: {}", e),
+impl Cli {
+    /// 开启报告时返回报告文件路径
+    fn report_path(&self) -> Option<PathBuf> {
+        match (self.report, &self.report_file) {
+            (_, Some(path)) => Some(path.clone()),
+            (Some(ReportFormat::Json), None) => Some(PathBuf::from(report::DEFAULT_REPORT_FILE)),
+            (None, None) => None,
         }
     }
-    
-    Ok(results)
 }
 
-/// 使用 join! 宏并发执行多个异步操作
-async fn concurrent_operations() -> Result<()> {
-    println!("\n=== 使用 join! 宏并发执行 ===");
-    
-    let start = Instant::now();
-    
-    let client1 = Client::new();
-    let client2 = Client::new();
-    let client3 = Client::new();
-    let (result1, result2, result3) = tokio::join!(
-        fetch_url(&client1, "https://httpbin.org/delay/1"),
-        fetch_url(&client2, "https://httpbin.org/delay/2"),
-        fetch_url(&client3, "https://httpbin.org/delay/1")
-    );
-    
-    let total_time = start.elapsed();
-    
-    println!("总执行时间: {:?}", total_time);
-    println!("结果1: {:?}", result1);
-    println!("结果2: {:?}", result2);
-    println!("结果3: {:?}", result3);
-    
-    Ok(())
+/// 按命令行选项运行示例
+struct Runner {
+    /// 性能测试结果写入的基准测试数据库
+    benchdb: Option<PathBuf>,
+    /// 命令行传入的配置覆盖项
+    config_overrides: Vec<String>,
 }
 
-/// 异步流处理示例
-async fn stream_processing() -> Result<()> {
-    println!("\n=== 异步流处理示例 ===");
-    
-    use tokio_stream::{self as stream, StreamExt};
-    
-    let numbers = stream::iter(1..=5);
-    let results: Vec<i32> = numbers
-        .then(|n| async move {
-            // 模拟异步处理
-            tokio::time::sleep(Duration::from_millis(100)).await;
-            n * n
-        })
-        .collect()
-        .await;
-    
-    println!("流处理结果: {:?}", results);
-    Ok(())
-}
+impl Runner {
+    /// 运行一个示例并记入运行报告
+    async fn run<F, T>(&self, name: &str, example: F) -> Result<()>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        report::track(name, example).await.map(|_| ())
+    }
+
+    async fn basics(&self) -> Result<()> {
+        println!("\n=== 基础异步示例 ===");
+        self.run("基础异步示例", simple_async_examples()).await?;
+        self.run("任务池示例", examples::basic::task_pool_example()).await?;
 
-/// 异步错误处理示例
-async fn error_handling_example() -> Result<()> {
-    println!("\n=== 异步错误处理示例 ===");
-    
-    let client = Client::new();
-    
-    // 处理超时错误
-    let result = tokio::time::timeout(
-        Duration::from_secs(2),
-        client.get("https://httpbin.org/delay/3").send()
-    ).await;
-    
-    match result {
-        Ok(Ok(response)) => println!("请求成功: {}", response.status()),
-        Ok(Err(e)) => println!("请求失败: {}", e),
-        Err(_) => println!("请求超时"),
+        println!("\n=== 流处理示例 ===");
+        self.run("简单流示例", simple_stream_example()).await?;
+        self.run("流转换示例", stream_transform_example()).await?;
+        self.run("流过滤示例", stream_filter_example()).await?;
+        self.run("流聚合示例", stream_aggregate_example()).await?;
+        self.run("流合并示例", stream_merge_example()).await?;
+        self.run("流错误处理示例", stream_error_handling_example()).await?;
+
+        println!("\n=== 定时器、互斥锁、select! 和通道示例 ===");
+        self.run("定时器示例", timer_example()).await?;
+        self.run("互斥锁示例", mutex_example()).await?;
+        self.run("资源管理示例", resource_management_example()).await?;
+        self.run("select示例", select_example()).await?;
+        self.run("通道示例", channels_example()).await
     }
-    
-    // 处理网络错误
-    let invalid_url = "https://invalid-url-that-does-not-exist.com";
-    match fetch_url(&client, invalid_url).await {
-        Ok(response) => println!("意外成功: {:?}", response),
-        Err(e) => println!("预期的错误: {}", e),
+
+    async fn batch(&self) -> Result<()> {
+        println!("\n=== 批处理示例 ===");
+        self.run("简单批处理示例", simple_batch_example()).await?;
+        self.run("动态批处理示例", dynamic_batch_example()).await?;
+        self.run("优化批处理示例", optimized_batch_example()).await?;
+        self.run("监控批处理示例", monitored_batch_example()).await?;
+        self.run("批处理管道示例", batch_pipeline_example()).await
     }
-    
-    Ok(())
-}
 
-/// 异步文件操作示例
-async fn async_file_operations() -> Result<()> {
-    println!("\n=== 异步文件操作示例 ===");
-    
-    use tokio::fs;
-    
-    // 异步写入文件
-    let content = "Hello, Async World!\n这是异步文件操作示例。";
-    fs::write("async_output.txt", content).await?;
-    println!("文件写入完成");
-    
-    // 异步读取文件
-    let read_content = fs::read_to_string("async_output.txt").await?;
-    println!("读取内容: {}", read_content);
-    
-    // 异步文件信息
-    let metadata = fs::metadata("async_output.txt").await?;
-    println!("文件大小: {} 字节", metadata.len());
-    
-    Ok(())
-}
+    async fn utils(&self) -> Result<()> {
+        println!("\n=== 工具模块示例 ===");
+        self.run("时间工具示例", time_utils_example()).await?;
+        self.run("错误处理测试", error_handling_test_example()).await?;
+        self.run("配置工具示例", config_utils_example(&self.config_overrides)).await?;
+        self.run("错误处理示例", error_handling_example()).await?;
+        self.run("日志工具示例", logging_utils_example()).await?;
+        self.run("隔舱隔离示例", bulkhead_example()).await
+    }
+
+    async fn http(&self) -> Result<()> {
+        println!("\n=== HTTP 示例 ===");
+
+        // HTTP客户端示例
+        let server = TestServer::start().await?;
+        println!("本地测试服务器已启动: {}", server.base_url());
+
+        self.run("HTTP客户端示例", async {
+            let http_client = AsyncHttpClient::new();
+            let urls = vec![
+                server.url("/get"),
+                server.url("/status/200"),
+                server.url("/user-agent"),
+            ];
+            let url_count = urls.len() as u64;
+
+            let start = Instant::now();
+            let results = http_client.fetch_multiple_urls(urls).await?;
+            let total_time = start.elapsed();
+
+            println!("HTTP客户端并发请求完成，总耗时: {:?}", total_time);
+            report::count("HTTP客户端示例", "成功请求数", results.len() as u64);
+            report::count("HTTP客户端示例", "失败请求数", url_count - results.len() as u64);
+            for result in results {
+                println!("URL: {}, 状态: {}, 响应时间: {}ms, 内容长度: {:?}",
+                        result.url, result.status, result.response_time_ms, result.content_length);
+            }
+
+            // gzip 响应透明解压后直接反序列化
+            let body: serde_json::Value = http_client.fetch_json(&server.url("/gzip")).await?;
+            println!("fetch_json 解压 gzip 响应: gzipped = {}", body["gzipped"]);
+
+            // HTML 页面不是 JSON，解码失败不属于网络错误，重试无济于事
+            if let Err(e) = http_client.fetch_json::<serde_json::Value>(&server.url("/site/index.html")).await {
+                println!("fetch_json 失败: {}（解码错误: {}，网络错误: {}）", e, e.is_decode(), e.is_network());
+            }
+
+            let status_urls = [server.url("/status/200"), server.url("/status/201")];
+            let responses = http_client.concurrent_requests(status_urls.iter().map(String::as_str).collect()).await?;
+            println!("并发请求状态码: {:?}", responses.iter().map(|r| r.status).collect::<Vec<_>>());
+
+            let response = http_client.fetch_with_retry(&server.url("/get"), 3).await?;
+            println!("带重试的请求完成: 状态 {}，{}ms", response.status, response.response_time_ms);
+            Ok::<(), anyhow::Error>(())
+        }).await?;
+
+        // Web服务器示例
+        self.run("Web服务器示例", async {
+            let web_server = AsyncWebServer::new();
+            let test_urls = [server.url("/site/index.html"), server.url("/site/about.html"), server.url("/site/blog.html")];
+
+            let start = Instant::now();
+            let results = web_server.process_multiple_requests(test_urls.iter().map(String::as_str).collect()).await?;
+            let server_time = start.elapsed();
+
+            println!("Web服务器处理完成，耗时: {:?}", server_time);
+            report::count("Web服务器示例", "处理请求数", results.len() as u64);
+            for (url, page) in test_urls.iter().zip(&results) {
+                let base = reqwest::Url::parse(url)?;
+                println!("{}：{}，{} 个链接（导航 {:?}），摘要: {:?}",
+                    url,
+                    html::extract_title(page).as_deref().unwrap_or("无标题"),
+                    html::extract_links(page, &base).len(),
+                    html::select_attr(page, "a.nav", "href")?,
+                    html::select_text(page, "p.summary")?);
+            }
+
+            // 查询参数顺序不同的 URL 命中同一个缓存条目，不同 URL 的相同响应体只保存一份
+            web_server.fetch_with_cache(&server.url("/anything/page?a=1&b=2")).await?;
+            web_server.fetch_with_cache(&server.url("/anything/page?b=2&a=1")).await?;
+            web_server.fetch_with_cache(&server.url("/bytes/512?copy=1")).await?;
+            web_server.fetch_with_cache(&server.url("/bytes/512?copy=2")).await?;
+            let stats = web_server.cache_stats().await;
+            println!("缓存: {} 个条目，{} 个不同响应体，去重节省 {} 字节",
+                stats.total_entries, stats.unique_bodies, stats.dedup_saved_bytes);
+            report::count("Web服务器示例", "去重节省字节", stats.dedup_saved_bytes as u64);
+
+            // 内存预算 4KB，超出部分溢出到磁盘，再次访问时读回
+            let dir = std::env::temp_dir().join(format!("august-code-web-cache-{}", std::process::id()));
+            let tiered = AsyncWebServer::with_disk_tier(DiskTierConfig { memory_budget_bytes: 4096, dir: dir.clone() })?;
+            for round in 0..2 {
+                for n in [2048, 3072, 1024] {
+                    tiered.fetch_with_cache(&server.url(&format!("/bytes/{}", n))).await?;
+                }
+                let stats = tiered.cache_stats().await;
+                println!("第 {} 轮: 内存 {} 字节，磁盘 {} 字节，内存命中 {}，磁盘命中 {}，未命中 {}",
+                    round + 1, stats.memory_bytes, stats.disk_bytes, stats.memory_hits, stats.disk_hits, stats.misses);
+            }
+            let _ = std::fs::remove_dir_all(&dir);
+
+            // 模拟时钟拨过 TTL 后，清理会释放过期条目
+            let clock = MockClock::new();
+            let timed = AsyncWebServer::new().with_clock(std::sync::Arc::new(clock.clone()));
+            timed.fetch_with_cache(&server.url("/get")).await?;
+            clock.advance(Duration::from_secs(301));
+            timed.cleanup_cache().await;
+            println!("缓存过期清理后剩余 {} 个条目", timed.cache_stats().await.total_entries);
+            Ok::<(), anyhow::Error>(())
+        }).await?;
+
+        // 限流器示例
+        self.run("限流器示例", async {
+            println!("\n=== 限流器示例 ===");
+            let rate_limiter = RateLimiter::new(3, Duration::from_secs(1));
+
+            for i in 1..=5 {
+                if rate_limiter.allow_request().await {
+                    println!("请求 {} 被允许", i);
+                    report::count("限流器示例", "允许", 1);
+                } else {
+                    println!("请求 {} 被限制", i);
+                    report::count("限流器示例", "限制", 1);
+                }
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
+
+            // 不直接拒绝，而是等到有令牌为止
+            let start = Instant::now();
+            rate_limiter.wait_for_permission().await;
+            println!("等待 {:?} 后获得许可", start.elapsed());
+            Ok::<(), anyhow::Error>(())
+        }).await?;
+
+        server.shutdown().await;
 
-/// 异步任务池示例
-async fn task_pool_example() -> Result<()> {
-    println!("\n=== 异步任务池示例 ===");
-    
-    let mut handles = Vec::new();
-    
-    // 创建多个异步任务
-    for i in 1..=5 {
-        let handle = tokio::spawn(async move {
-            let duration = Duration::from_millis(i * 100);
-            tokio::time::sleep(duration).await;
-            println!("任务 {} 完成", i);
-            i * i
-        });
-        handles.push(handle);
+        self.run("类型化API客户端示例", api_client_example()).await?;
+        self.run("HTTP录制回放示例", recording_example()).await?;
+        self.run("出站请求队列示例", outbound_example()).await?;
+        self.run("礼貌爬取示例", crawl_example()).await?;
+        self.run("WebSocket客户端示例", ws_client_example()).await?;
+        self.run("DoH解析器示例", resolver_example()).await
     }
-    
-    // 等待所有任务完成并收集结果
-    let mut results = Vec::new();
-    for handle in handles {
-        if let Ok(result) = handle.await {
-            results.push(result);
+
+    async fn db(&self) -> Result<()> {
+        self.run("数据库操作示例", database_operations_example()).await?;
+        self.run("数据库迁移示例", migrations_example()).await?;
+        self.run("数据库备份示例", backup_example()).await?;
+        self.run("读副本一致性示例", replication_example()).await?;
+        self.run("用户查询缓存示例", user_cache_example()).await?;
+        self.run("数据源抽象示例", data_source_example()).await
+    }
+
+    async fn scheduler(&self) -> Result<()> {
+        self.run("任务调度器示例", scheduler_example()).await?;
+        self.run("截止时间传播示例", context_example()).await
+    }
+
+    async fn bench(&self) -> Result<()> {
+        println!("\n=== 性能和并发测试 ===");
+        self.run("性能测试", performance_test_example(self.benchdb.as_deref())).await?;
+        self.run("并发测试", concurrency_test_example()).await
+    }
+
+    async fn offline(&self) -> Result<()> {
+        println!("\n=== 离线示例 ===");
+        self.run("离线示例", offline_async_examples()).await?;
+        self.run("集成测试", integration_test_example()).await
+    }
+
+    async fn all(&self) -> Result<()> {
+        self.basics().await?;
+        self.batch().await?;
+        self.utils().await?;
+        self.http().await?;
+        self.db().await?;
+        self.scheduler().await?;
+        self.bench().await?;
+        self.offline().await
+    }
+
+    async fn execute(&self, command: Commands) -> Result<()> {
+        match command {
+            Commands::Basics => self.basics().await,
+            Commands::Http => self.http().await,
+            Commands::Db => self.db().await,
+            Commands::Scheduler => self.scheduler().await,
+            Commands::Batch => self.batch().await,
+            Commands::Utils => self.utils().await,
+            Commands::Offline => self.offline().await,
+            Commands::Bench => self.bench().await,
+            Commands::All => self.all().await,
+            Commands::Backup { wal, output } => self.backup(&wal, &output).await,
+            Commands::Restore { input, wal } => self.restore(&input, &wal).await,
         }
     }
-    
-    println!("所有任务结果: {:?}", results);
-    Ok(())
-}
 
-/// 异步 HTTP 客户端高级用法
-async fn advanced_http_client() -> Result<()> {
-    println!("\n=== 高级 HTTP 客户端用法 ===");
-    
-    let client = Client::builder()
-        .timeout(Duration::from_secs(30))
-        .user_agent("Rust Async Client/1.0")
-        .build()?;
-    
-    // 发送 JSON 数据
-    let json_data = serde_json::json!({
-        "name": "Rust Async",
-        "version": "1.0"
-    });
-    
-    let response = client
-        .post("https://httpbin.org/post")
-        .json(&json_data)
-        .send()
-        .await?;
-    
-    let status = response.status();
-    println!("POST 请求状态: {}", status);
-    
-    // 解析 JSON 响应
-    if let Ok(json_response) = response.json::<HttpBinResponse>().await {
-        println!("响应 URL: {}", json_response.url);
-        println!("来源 IP: {}", json_response.origin);
+    async fn backup(&self, wal: &Path, output: &Path) -> Result<()> {
+        let db = AsyncDatabase::recover(wal).await?;
+        let info = db.export_snapshot(output).await?;
+        print!("已导出到 {}: ", output.display());
+        info.print();
+        Ok(())
+    }
+
+    async fn restore(&self, input: &Path, wal: &Path) -> Result<()> {
+        let db = AsyncDatabase::recover(wal).await?;
+        let info = db.import_snapshot(input).await?;
+        print!("已从 {} 恢复: ", input.display());
+        info.print();
+        Ok(())
     }
-    
-    Ok(())
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    // --report json 或 AUGUST_REPORT=json 时把结果写入 JSON 报告
+    let report_path = cli.report_path();
+    if report_path.is_some() {
+        report::enable();
+    }
+
     println!("Rust 异步编程示例程序（模块化版本）");
     println!("=====================================");
-    
-    // 1. 基础异步示例
-    println!("\n=== 基础异步示例 ===");
-    simple_async_examples().await?;
-    
-    // 2. 流处理示例
-    println!("\n=== 流处理示例 ===");
-    simple_stream_example().await?;
-    stream_transform_example().await?;
-    
-    // 3. 批处理示例
-    println!("\n=== 批处理示例 ===");
-    simple_batch_example().await?;
-    dynamic_batch_example().await?;
-    
-    // 4. 定时器和互斥锁示例
-    println!("\n=== 定时器和互斥锁示例 ===");
-    timer_example().await?;
-    mutex_example().await?;
-    
-    // 5. 工具模块示例
-    println!("\n=== 工具模块示例 ===");
-    time_utils_example().await?;
-    error_handling_test_example().await?;
-    config_utils_example().await?;
-    logging_utils_example().await?;
-    
-    // 6. 核心模块示例
-    println!("\n=== 核心模块示例 ===");
-    
-    // HTTP客户端示例
-    let http_client = AsyncHttpClient::new();
-    let urls = vec![
-        "https://httpbin.org/get".to_string(),
-        "https://httpbin.org/status/200".to_string(),
-        "https://httpbin.org/user-agent".to_string(),
-    ];
-    
-    let start = Instant::now();
-    let results = http_client.fetch_multiple_urls(urls).await?;
-    let total_time = start.elapsed();
-    
-    println!("HTTP客户端并发请求完成，总耗时: {:?}", total_time);
-    for result in results {
-        println!("URL: {}, 状态: {}, 响应时间: {}ms, 内容长度: {:?}",
-                result.url, result.status, result.response_time_ms, result.content_length);
+
+    // 网络不可达时自动切换到模拟客户端，避免访问外部地址时卡在请求超时上
+    if cli.skip_network {
+        connectivity::set_offline(true);
+    } else {
+        connectivity::detect_offline_mode().await;
     }
-    
-    // Web服务器示例
-    let web_server = AsyncWebServer::new();
-    let test_urls = vec![
-        "https://httpbin.org/get",
-        "https://httpbin.org/user-agent",
-        "https://httpbin.org/headers",
-    ];
-    
-    let start = Instant::now();
-    let results = web_server.process_multiple_requests(test_urls).await?;
-    let server_time = start.elapsed();
-    
-    println!("Web服务器处理完成，耗时: {:?}", server_time);
-    for (i, result) in results.iter().enumerate() {
-        println!("结果 {}: {} 字符", i + 1, result.len());
+
+    // 覆盖项写错时在运行示例之前就报错
+    utils::config::ConfigManager::new().apply_cli_overrides(&cli.set).await?;
+
+    let runner = Runner {
+        benchdb: cli.benchdb.clone(),
+        config_overrides: cli.set.clone(),
+    };
+    let result = runner.execute(cli.command.unwrap_or(Commands::All)).await;
+    if result.is_ok() {
+        println!("\n所有异步操作完成！");
     }
-    
-    // 数据库操作示例
-    database_operations_example().await?;
-    
-    // 限流器示例
-    println!("\n=== 限流器示例 ===");
-    let rate_limiter = RateLimiter::new(3, Duration::from_secs(1));
-    
-    for i in 1..=5 {
-        if rate_limiter.allow_request().await {
-            println!("请求 {} 被允许", i);
-        } else {
-            println!("请求 {} 被限制", i);
-        }
-        tokio::time::sleep(Duration::from_millis(200)).await;
+
+    if let Some(path) = report_path {
+        report::write_json(&path, result.as_ref().err())?;
+        println!("运行报告已写入 {}", path.display());
+    }
+    result
+}
+
+#[cfg(test)]
+mod main_tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_cli_parsing() {
+        let cli = Cli::try_parse_from(["august-code"]).unwrap();
+        assert_eq!(cli.command, None);
+        assert!(!cli.skip_network);
+
+        let cli = Cli::try_parse_from(["august-code", "db", "--skip-network", "--report", "json"]).unwrap();
+        assert_eq!(cli.command, Some(Commands::Db));
+        assert!(cli.skip_network);
+        assert_eq!(cli.report_path(), Some(PathBuf::from(report::DEFAULT_REPORT_FILE)));
+
+        let cli = Cli::try_parse_from(["august-code", "--report-file", "out.json", "bench"]).unwrap();
+        assert_eq!(cli.command, Some(Commands::Bench));
+        assert_eq!(cli.report_path(), Some(PathBuf::from("out.json")));
+
+        let cli = Cli::try_parse_from(["august-code", "backup", "--wal", "users.wal", "-o", "users.json.gz"]).unwrap();
+        assert_eq!(cli.command, Some(Commands::Backup {
+            wal: PathBuf::from("users.wal"),
+            output: PathBuf::from("users.json.gz"),
+        }));
+        assert!(Cli::try_parse_from(["august-code", "restore", "--wal", "users.wal"]).is_err());
+
+        let cli = Cli::try_parse_from(["august-code", "utils", "--set", "server.port=9999", "--set", "features.debug_mode=on"]).unwrap();
+        assert_eq!(cli.set, vec!["server.port=9999", "features.debug_mode=on"]);
+
+        assert!(Cli::try_parse_from(["august-code", "--report", "xml"]).is_err());
+        assert!(Cli::try_parse_from(["august-code", "unknown"]).is_err());
     }
-    
-    // 任务调度器示例
-    println!("\n=== 任务调度器示例 ===");
-    let scheduler = TaskScheduler::new();
-    
-    scheduler.add_periodic_task(
-        "清理任务",
-        Duration::from_secs(1),
-        || {
-            println!("执行清理任务...");
-        },
-    ).await;
-    
-    // 运行调度器 3 秒
-    tokio::time::sleep(Duration::from_secs(3)).await;
-    
-    // 7. 测试模块示例
-    println!("\n=== 测试模块示例 ===");
-    performance_test_example().await?;
-    error_handling_test_example().await?;
-    concurrency_test_example().await?;
-    integration_test_example().await?;
-    
-    // 8. 离线示例
-    println!("\n=== 离线示例 ===");
-    offline_async_examples().await?;
-    
-    println!("\n所有异步操作完成！");
-    Ok(())
 }
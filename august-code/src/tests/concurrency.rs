@@ -307,8 +307,6 @@ impl ConcurrencyTester {
 pub async fn concurrency_test_example() -> Result<()> {
     println!("\n=== 并发测试示例 ===");
     
-    let tester = ConcurrencyTester;
-    
     // 运行各种并发测试
     ConcurrencyTester::test_mutex_safety().await?;
     ConcurrencyTester::test_rwlock_safety().await?;
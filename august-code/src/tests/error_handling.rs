@@ -309,8 +309,6 @@ fn classify_error(error: &anyhow::Error) -> &'static str {
 pub async fn error_handling_test_example() -> Result<()> {
     println!("\n=== 错误处理测试示例 ===");
     
-    let tester = ErrorHandlingTester;
-    
     // 运行各种错误处理测试
     ErrorHandlingTester::test_timeout_handling().await?;
     ErrorHandlingTester::test_network_error_handling().await?;
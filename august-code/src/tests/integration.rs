@@ -5,11 +5,18 @@
 //! - 系统集成测试
 //! - 性能集成测试
 //! - 错误处理集成测试
+//! - 基于本地测试服务器的 HTTP 集成测试
+//! - 基于回显服务器的 WebSocket 集成测试
 
 use anyhow::Result;
 use std::time::Duration;
 use tokio::time::Instant;
 
+use crate::core::http_client::AsyncHttpClient;
+use crate::core::web_server::AsyncWebServer;
+use crate::core::ws_client::{WsClient, WsConfig, WsEvent};
+use crate::utils::test_server::{TestServer, WsEchoServer};
+
 /// 集成测试器
 pub struct IntegrationTester;
 
@@ -53,7 +60,6 @@ impl IntegrationTester {
         // 模拟多个系统组件
         let http_client = MockHttpClient::new();
         let database = MockDatabase::new();
-        let cache = MockCache::new();
         
         // 测试HTTP客户端集成
         println!("测试HTTP客户端集成");
@@ -169,6 +175,84 @@ impl IntegrationTester {
         Ok(())
     }
     
+    /// 测试 HTTP 客户端和 Web 服务器与本地测试服务器的集成
+    pub async fn test_http_integration() -> Result<()> {
+        println!("\n--- 测试 HTTP 集成 ---");
+        
+        let server = TestServer::start().await?;
+        let client = AsyncHttpClient::with_timeout(Duration::from_secs(5));
+        
+        // 状态码和超时
+        let ok = client.fetch_url(&server.url("/get")).await?;
+        let not_found = client.fetch_url(&server.url("/status/404")).await?;
+        if ok.status != 200 || not_found.status != 404 {
+            return Err(anyhow::anyhow!("状态码不符合预期: {} / {}", ok.status, not_found.status));
+        }
+        let slow = AsyncHttpClient::with_timeout(Duration::from_millis(200));
+        if slow.fetch_url(&server.url("/delay/1")).await.is_ok() {
+            return Err(anyhow::anyhow!("慢请求应该超时"));
+        }
+        println!("HTTP客户端集成通过");
+        
+        // Web服务器缓存：第二次请求命中缓存
+        let web_server = AsyncWebServer::new();
+        let url = server.url("/get");
+        let first = web_server.fetch_with_cache(&url).await?;
+        let second = web_server.fetch_with_cache(&url).await?;
+        if first != second {
+            return Err(anyhow::anyhow!("缓存内容不一致"));
+        }
+        println!("Web服务器缓存集成通过");
+        
+        server.shutdown().await;
+        Ok(())
+    }
+    
+    /// 测试 WebSocket 客户端与回显服务器的集成，包括断线重连
+    pub async fn test_websocket_integration() -> Result<()> {
+        println!("\n--- 测试 WebSocket 集成 ---");
+        
+        let server = WsEchoServer::start().await?;
+        let config = WsConfig {
+            heartbeat_interval: Duration::from_millis(100),
+            initial_backoff: Duration::from_millis(20),
+            ..Default::default()
+        };
+        let mut client = WsClient::connect(&server.url(), config);
+        let timeout = Duration::from_secs(5);
+        client.wait_for(&WsEvent::Connected, timeout).await?;
+        
+        client.send_text("ping-1").await?;
+        if client.recv_text(timeout).await? != "ping-1" {
+            return Err(anyhow::anyhow!("回显内容不一致"));
+        }
+        
+        // 等待几个心跳周期，连接应保持可用
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        client.send_text("ping-2").await?;
+        if client.recv_text(timeout).await? != "ping-2" {
+            return Err(anyhow::anyhow!("心跳后回显内容不一致"));
+        }
+        println!("WebSocket 收发和心跳通过");
+        
+        // 服务端断开后自动重连，断线期间发送的消息在重连后送达
+        server.drop_connections();
+        tokio::time::timeout(timeout, async {
+            while !matches!(client.recv().await, Some(WsEvent::Disconnected(_)) | None) {}
+        })
+        .await?;
+        client.send_text("after-reconnect").await?;
+        client.wait_for(&WsEvent::Connected, timeout).await?;
+        if client.recv_text(timeout).await? != "after-reconnect" {
+            return Err(anyhow::anyhow!("重连后回显内容不一致"));
+        }
+        println!("WebSocket 断线重连通过");
+        
+        client.close().await;
+        server.shutdown().await;
+        Ok(())
+    }
+    
     /// 测试资源管理集成
     pub async fn test_resource_management_integration() -> Result<()> {
         println!("\n--- 测试资源管理集成 ---");
@@ -275,6 +359,10 @@ async fn validate_results(aggregate: &DataAggregate) -> Result<()> {
         return Err(anyhow::anyhow!("最大值不能小于最小值"));
     }
     
+    if (aggregate.average - aggregate.sum as f64 / aggregate.count as f64).abs() > 1e-9 {
+        return Err(anyhow::anyhow!("平均值与总和、数量不一致"));
+    }
+    
     Ok(())
 }
 
@@ -348,8 +436,7 @@ impl MockCache {
     
     async fn get(&self, key: &str) -> Result<String> {
         tokio::time::sleep(Duration::from_millis(5)).await;
-        self.data.get(key)
-            .map(|v| v.clone())
+        self.data.get(key).cloned()
             .ok_or_else(|| anyhow::anyhow!("键不存在"))
     }
 }
@@ -369,7 +456,7 @@ async fn integrate_components(
     // 从缓存获取数据
     let cached_data = cache.get("key1").await?;
     
-    Ok(format!("集成结果: {} + {} + {}", http_data, user.name, cached_data))
+    Ok(format!("集成结果: {} + {}（{}，{}）+ {}", http_data, user.name, user.id, user.email, cached_data))
 }
 
 /// 执行复杂操作
@@ -377,7 +464,7 @@ async fn perform_complex_operation(id: usize) -> Result<String> {
     tokio::time::sleep(Duration::from_millis(100)).await;
     
     // 模拟可能失败的操作
-    if id % 7 == 0 {
+    if id.is_multiple_of(7) {
         Err(anyhow::anyhow!("操作 {} 失败", id))
     } else {
         Ok(format!("操作 {} 成功", id))
@@ -443,14 +530,14 @@ struct Resource {
 pub async fn integration_test_example() -> Result<()> {
     println!("\n=== 集成测试示例 ===");
     
-    let tester = IntegrationTester;
-    
     // 运行各种集成测试
     IntegrationTester::test_complete_async_workflow().await?;
     IntegrationTester::test_system_integration().await?;
     IntegrationTester::test_performance_integration().await?;
     IntegrationTester::test_error_handling_integration().await?;
     IntegrationTester::test_resource_management_integration().await?;
+    IntegrationTester::test_http_integration().await?;
+    IntegrationTester::test_websocket_integration().await?;
     
     Ok(())
 }
@@ -482,4 +569,16 @@ mod tests {
         let result = IntegrationTester::test_error_handling_integration().await;
         assert!(result.is_ok());
     }
+    
+    #[tokio::test]
+    async fn test_http_integration() {
+        let result = IntegrationTester::test_http_integration().await;
+        assert!(result.is_ok(), "{:?}", result);
+    }
+    
+    #[tokio::test]
+    async fn test_websocket_integration() {
+        let result = IntegrationTester::test_websocket_integration().await;
+        assert!(result.is_ok(), "{:?}", result);
+    }
 }
@@ -5,13 +5,23 @@
 //! - 内存使用测试
 //! - 延迟测试
 //! - 吞吐量测试
+//! - HTML 报告导出
+//! - 数据库批量插入与逐条插入对比
+//! - 数据库连接池压力测试
+//! - 结果记录到基准测试数据库（benchdb）
 
 use anyhow::Result;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
 use tokio::time::Instant;
 
+use crate::core::database::{AsyncDatabase, BulkMode, PoolStats, User};
+use crate::utils::memory::MemoryStats;
+use crate::utils::report;
+
 /// 性能测试结果
 #[derive(Debug, Clone)]
 pub struct PerformanceResult {
@@ -22,7 +32,81 @@ pub struct PerformanceResult {
     pub average_latency: Duration,
     pub min_latency: Duration,
     pub max_latency: Duration,
+    /// 测试结束时的内存使用量（字节）
     pub memory_usage: u64,
+    /// 测试结束时的峰值内存使用量（字节）
+    pub peak_memory_usage: u64,
+}
+
+/// 吞吐量测试配置
+#[derive(Debug, Clone)]
+pub struct ThroughputConfig {
+    /// 测试总时长（包含预热）
+    pub duration: Duration,
+    /// 至少丢弃的预热时长
+    pub warmup: Duration,
+    /// 统计吞吐量的窗口大小
+    pub window: Duration,
+    /// 判定稳态所需的连续窗口数
+    pub stable_windows: usize,
+    /// 判定稳态的变异系数阈值
+    pub cv_threshold: f64,
+}
+
+impl ThroughputConfig {
+    /// 指定总时长，预热取总时长的 1/5，窗口取总时长的 1/20
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            warmup: duration / 5,
+            window: (duration / 20).max(Duration::from_millis(10)),
+            stable_windows: 3,
+            cv_threshold: 0.1,
+        }
+    }
+}
+
+/// 吞吐量测试报告：预热阶段和稳态阶段分开统计
+#[derive(Debug, Clone)]
+pub struct ThroughputReport {
+    pub warmup: PerformanceResult,
+    pub steady_state: PerformanceResult,
+    /// 进入稳态的时刻（相对测试开始），未检测到稳态时为 `None`
+    pub steady_state_at: Option<Duration>,
+    /// 每个窗口的吞吐量（次/秒）
+    pub window_throughput: Vec<f64>,
+}
+
+/// 变异系数：标准差 / 平均值，平均值为 0 时返回无穷大
+pub fn coefficient_of_variation(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return f64::INFINITY;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    if mean == 0.0 {
+        return f64::INFINITY;
+    }
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    variance.sqrt() / mean
+}
+
+/// 在滚动窗口上检测稳态，返回稳态开始的窗口下标
+///
+/// 从 `min_start` 开始，找到第一段连续 `stable_windows` 个窗口，
+/// 其吞吐量的变异系数不超过 `cv_threshold`。
+pub fn detect_steady_state(
+    window_throughput: &[f64],
+    min_start: usize,
+    stable_windows: usize,
+    cv_threshold: f64,
+) -> Option<usize> {
+    let stable_windows = stable_windows.max(1);
+    if window_throughput.len() < min_start + stable_windows {
+        return None;
+    }
+    (min_start..=window_throughput.len() - stable_windows).find(|&start| {
+        coefficient_of_variation(&window_throughput[start..start + stable_windows]) <= cv_threshold
+    })
 }
 
 /// 性能测试器
@@ -52,11 +136,11 @@ impl PerformanceTester {
         let mut handles = Vec::new();
         
         // 创建并发任务
-        for i in 0..concurrency {
+        for _ in 0..concurrency {
             let handle = tokio::spawn(async move {
                 let mut latencies = Vec::new();
                 
-                for j in 0..operations_per_task {
+                for _ in 0..operations_per_task {
                     let op_start = Instant::now();
                     
                     // 模拟异步操作
@@ -107,6 +191,7 @@ impl PerformanceTester {
             min_latency,
             max_latency,
             memory_usage: self.get_memory_usage(),
+            peak_memory_usage: self.get_peak_memory_usage(),
         };
         
         // 保存结果
@@ -167,6 +252,7 @@ impl PerformanceTester {
             min_latency,
             max_latency,
             memory_usage: self.get_memory_usage(),
+            peak_memory_usage: self.get_peak_memory_usage(),
         };
         
         // 保存结果
@@ -179,32 +265,89 @@ impl PerformanceTester {
         Ok(result)
     }
     
-    /// 运行吞吐量测试
-    pub async fn run_throughput_test(
+    /// 运行带预热和稳态检测的吞吐量测试
+    ///
+    /// 操作按时间窗口分桶统计吞吐量，丢弃预热期后，从第一段连续
+    /// `stable_windows` 个窗口的变异系数不超过阈值处开始视为稳态。
+    /// 未检测到稳态时，预热期之后的全部操作都计入稳态结果。
+    pub async fn run_throughput_test_with(
         &self,
         operation: &str,
-        duration: Duration,
-    ) -> Result<PerformanceResult> {
-        println!("运行吞吐量测试: {} (持续时间: {:?})", operation, duration);
+        config: ThroughputConfig,
+    ) -> Result<ThroughputReport> {
+        println!("运行吞吐量测试: {} (持续时间: {:?}, 预热: {:?})", operation, config.duration, config.warmup);
         
         let start = Instant::now();
-        let mut operations_count = 0u64;
-        let mut latencies = Vec::new();
+        // (操作完成时刻相对开始的偏移, 延迟)
+        let mut samples = Vec::new();
         
-        while start.elapsed() < duration {
+        while start.elapsed() < config.duration {
             let op_start = Instant::now();
             
             // 模拟异步操作
             tokio::time::sleep(Duration::from_millis(1)).await;
             
             let latency = op_start.elapsed();
-            latencies.push(latency);
-            operations_count += 1;
+            samples.push((start.elapsed(), latency));
         }
         
         let total_time = start.elapsed();
         
-        // 计算统计信息
+        // 按窗口统计吞吐量
+        let window_secs = config.window.as_secs_f64();
+        // 最后一次操作可能略微超出测试时长，计入最后一个窗口
+        let window_count = (config.duration.as_secs_f64() / window_secs).ceil().max(1.0) as usize;
+        let mut window_ops = vec![0u64; window_count];
+        for (offset, _) in &samples {
+            let index = ((offset.as_secs_f64() / window_secs) as usize).min(window_count - 1);
+            window_ops[index] += 1;
+        }
+        let window_throughput: Vec<f64> = window_ops.iter().map(|&ops| ops as f64 / window_secs).collect();
+        
+        let warmup_windows = (config.warmup.as_secs_f64() / window_secs).ceil() as usize;
+        let steady_window = detect_steady_state(
+            &window_throughput,
+            warmup_windows,
+            config.stable_windows,
+            config.cv_threshold,
+        );
+        let boundary = config
+            .window
+            .mul_f64(steady_window.unwrap_or(warmup_windows) as f64)
+            .min(total_time);
+        
+        let (warmup_samples, steady_samples): (Vec<_>, Vec<_>) =
+            samples.iter().partition(|(offset, _)| *offset <= boundary);
+        let warmup_latencies: Vec<Duration> = warmup_samples.iter().map(|(_, latency)| *latency).collect();
+        let steady_latencies: Vec<Duration> = steady_samples.iter().map(|(_, latency)| *latency).collect();
+        
+        let warmup = self.build_result(&format!("{} (预热)", operation), boundary, &warmup_latencies);
+        let steady_state = self.build_result(operation, total_time - boundary, &steady_latencies);
+        
+        let report = ThroughputReport {
+            warmup,
+            steady_state,
+            steady_state_at: steady_window.map(|_| boundary),
+            window_throughput,
+        };
+        
+        // 只保存稳态结果
+        {
+            let mut results = self.results.lock().await;
+            results.push(report.steady_state.clone());
+        }
+        
+        match report.steady_state_at {
+            Some(at) => println!("在 {:?} 进入稳态", at),
+            None => println!("未检测到稳态，预热期之后的结果均计入稳态"),
+        }
+        println!("预热阶段每秒操作数: {:.2}", report.warmup.operations_per_second);
+        self.print_result(&report.steady_state);
+        Ok(report)
+    }
+    
+    /// 根据一组延迟构造测试结果
+    fn build_result(&self, operation: &str, total_time: Duration, latencies: &[Duration]) -> PerformanceResult {
         let min_latency = latencies.iter().min().copied().unwrap_or_default();
         let max_latency = latencies.iter().max().copied().unwrap_or_default();
         let total_latency: Duration = latencies.iter().sum();
@@ -214,13 +357,14 @@ impl PerformanceTester {
             Duration::from_secs(0)
         };
         
+        let operations_count = latencies.len() as u64;
         let operations_per_second = if total_time.as_secs_f64() > 0.0 {
             operations_count as f64 / total_time.as_secs_f64()
         } else {
             0.0
         };
         
-        let result = PerformanceResult {
+        PerformanceResult {
             operation: operation.to_string(),
             total_time,
             operations_count,
@@ -229,16 +373,118 @@ impl PerformanceTester {
             min_latency,
             max_latency,
             memory_usage: self.get_memory_usage(),
-        };
+            peak_memory_usage: self.get_peak_memory_usage(),
+        }
+    }
+    
+    /// 对比逐条 `create_user` 和 `create_users_bulk` 插入 `count` 个用户的耗时
+    ///
+    /// 批量插入只有一次调用，延迟按用户数平均
+    pub async fn run_bulk_insert_comparison(&self, count: usize) -> Result<(PerformanceResult, PerformanceResult)> {
+        println!("运行批量插入对比测试 (用户数: {})", count);
+        let users: Vec<User> = (0..count)
+            .map(|i| User {
+                id: format!("user_{:06}", i),
+                name: format!("用户{}", i),
+                email: format!("user{}@example.com", i),
+                created_at: i as u64,
+                version: 0,
+            })
+            .collect();
+        
+        let database = AsyncDatabase::new();
+        let start = Instant::now();
+        let mut latencies = Vec::with_capacity(count);
+        for user in users.clone() {
+            let op_start = Instant::now();
+            database.create_user(user).await?;
+            latencies.push(op_start.elapsed());
+        }
+        let looped = self.build_result("逐条插入", start.elapsed(), &latencies);
+        
+        let database = AsyncDatabase::new();
+        let start = Instant::now();
+        let report = database.create_users_bulk(users, BulkMode::AllOrNothing).await?;
+        let total_time = start.elapsed();
+        if report.inserted != count {
+            return Err(anyhow::anyhow!("批量插入失败: {:?}", report.failures.first()));
+        }
+        let per_user = total_time / count.max(1) as u32;
+        let bulk = self.build_result("批量插入", total_time, &vec![per_user; count]);
         
-        // 保存结果
         {
             let mut results = self.results.lock().await;
-            results.push(result.clone());
+            results.push(looped.clone());
+            results.push(bulk.clone());
+        }
+        self.print_result(&looped);
+        self.print_result(&bulk);
+        if bulk.total_time > Duration::ZERO {
+            println!(
+                "批量插入加速比: {:.1}x",
+                looped.total_time.as_secs_f64() / bulk.total_time.as_secs_f64()
+            );
+        }
+        Ok((looped, bulk))
+    }
+    
+    /// 运行连接池压力测试：`tasks` 个任务争用 `max_connections` 个连接
+    ///
+    /// 每个任务持有连接一段时间，统计同时持有连接的任务数，超过上限时返回错误。
+    /// 延迟统计为获取连接的等待时间。
+    pub async fn run_pool_load_test(&self, tasks: usize, max_connections: usize) -> Result<(PerformanceResult, PoolStats)> {
+        println!("运行连接池压力测试 (任务数: {}, 连接上限: {})", tasks, max_connections);
+        let database = AsyncDatabase::new().with_pool(max_connections, Duration::from_secs(30));
+        let in_use = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        
+        let start = Instant::now();
+        let handles: Vec<_> = (0..tasks)
+            .map(|_| {
+                let database = database.clone();
+                let in_use = in_use.clone();
+                let peak = peak.clone();
+                tokio::spawn(async move {
+                    let wait_start = Instant::now();
+                    let connection = database.get_connection().await?;
+                    let waited = wait_start.elapsed();
+                    let current = in_use.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(current, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(2)).await;
+                    in_use.fetch_sub(1, Ordering::SeqCst);
+                    drop(connection);
+                    Ok::<_, anyhow::Error>(waited)
+                })
+            })
+            .collect();
+        
+        let mut latencies = Vec::with_capacity(tasks);
+        for handle in handles {
+            latencies.push(handle.await??);
+        }
+        let result = self.build_result("连接池获取", start.elapsed(), &latencies);
+        let stats = database.pool_stats();
+        
+        let peak = peak.load(Ordering::SeqCst);
+        if peak > max_connections || stats.open > max_connections {
+            return Err(anyhow::anyhow!(
+                "连接数超过上限 {}: 同时持有 {}，已创建 {}",
+                max_connections,
+                peak,
+                stats.open
+            ));
         }
         
+        self.results.lock().await.push(result.clone());
         self.print_result(&result);
-        Ok(result)
+        println!(
+            "  同时持有峰值: {}，已创建连接: {}，平均等待: {:?}，最长等待: {:?}",
+            peak,
+            stats.open,
+            stats.average_wait(),
+            stats.max_wait
+        );
+        Ok((result, stats))
     }
     
     /// 运行内存使用测试
@@ -250,6 +496,7 @@ impl PerformanceTester {
         println!("运行内存使用测试: {} (迭代次数: {})", operation, iterations);
         
         let start = Instant::now();
+        let before = self.get_memory_usage();
         let mut data = Vec::new();
         
         for i in 0..iterations {
@@ -267,6 +514,7 @@ impl PerformanceTester {
         
         let total_time = start.elapsed();
         let memory_usage = self.get_memory_usage();
+        println!("测试期间内存增长: {} KB", memory_usage.saturating_sub(before) / 1024);
         
         let result = PerformanceResult {
             operation: operation.to_string(),
@@ -277,6 +525,7 @@ impl PerformanceTester {
             min_latency: Duration::from_millis(1),
             max_latency: Duration::from_millis(1),
             memory_usage,
+            peak_memory_usage: self.get_peak_memory_usage(),
         };
         
         // 清理内存
@@ -292,11 +541,17 @@ impl PerformanceTester {
         Ok(result)
     }
     
-    /// 获取内存使用量（简化版本）
+    /// 获取当前内存使用量（字节）
+    ///
+    /// 启用 `alloc-stats` 特性时为堆上已分配字节数，否则为 Linux 上的 RSS；
+    /// 两者都不可用时返回 0。
     fn get_memory_usage(&self) -> u64 {
-        // 这里应该使用更精确的内存测量方法
-        // 为了示例，我们返回一个模拟值
-        std::process::id() as u64 * 1024
+        MemoryStats::current().current_bytes()
+    }
+    
+    /// 获取峰值内存使用量（字节），取值来源与 `get_memory_usage` 一致
+    fn get_peak_memory_usage(&self) -> u64 {
+        MemoryStats::current().peak_bytes()
     }
     
     /// 打印测试结果
@@ -309,6 +564,7 @@ impl PerformanceTester {
         println!("  最小延迟: {:?}", result.min_latency);
         println!("  最大延迟: {:?}", result.max_latency);
         println!("  内存使用: {} KB", result.memory_usage / 1024);
+        println!("  峰值内存: {} KB", result.peak_memory_usage / 1024);
         println!();
     }
     
@@ -343,10 +599,142 @@ impl PerformanceTester {
         println!("  总时间: {:?}", total_time);
         println!("  平均每秒操作数: {:.2}", avg_ops_per_second);
     }
+    
+    /// 把性能报告导出为独立的 HTML 页面
+    pub async fn print_performance_report_html(&self, path: &str) -> Result<()> {
+        let results = self.get_all_results().await;
+        tokio::fs::write(path, render_performance_report_html(&results)).await?;
+        println!("性能报告已导出到 {}", path);
+        Ok(())
+    }
+}
+
+/// 转义 HTML 特殊字符
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 渲染延迟柱状图：每个测试一组最小/平均/最大延迟柱
+fn render_latency_svg(results: &[PerformanceResult]) -> String {
+    const BAR_WIDTH: usize = 18;
+    const GROUP_GAP: usize = 24;
+    const CHART_HEIGHT: f64 = 200.0;
+    const LABEL_HEIGHT: usize = 40;
+
+    let max_latency = results
+        .iter()
+        .map(|r| r.max_latency.as_secs_f64())
+        .fold(0.0, f64::max);
+    let scale = if max_latency > 0.0 { CHART_HEIGHT / max_latency } else { 0.0 };
+    let group_width = BAR_WIDTH * 3 + GROUP_GAP;
+    let width = group_width * results.len().max(1) + GROUP_GAP;
+    let height = CHART_HEIGHT as usize + LABEL_HEIGHT;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" class=\"chart\">\n",
+        width, height
+    );
+    for (i, result) in results.iter().enumerate() {
+        let x = GROUP_GAP + i * group_width;
+        let bars = [
+            ("min", result.min_latency, "#8fbc8f"),
+            ("avg", result.average_latency, "#4682b4"),
+            ("max", result.max_latency, "#cd5c5c"),
+        ];
+        for (j, (label, latency, color)) in bars.iter().enumerate() {
+            let bar_height = latency.as_secs_f64() * scale;
+            svg.push_str(&format!(
+                "  <rect x=\"{}\" y=\"{:.1}\" width=\"{}\" height=\"{:.1}\" fill=\"{}\"><title>{} {}: {:?}</title></rect>\n",
+                x + j * BAR_WIDTH,
+                CHART_HEIGHT - bar_height,
+                BAR_WIDTH - 2,
+                bar_height,
+                color,
+                escape_html(&result.operation),
+                label,
+                latency
+            ));
+        }
+        svg.push_str(&format!(
+            "  <text x=\"{}\" y=\"{}\" font-size=\"12\">{}</text>\n",
+            x,
+            CHART_HEIGHT as usize + 20,
+            escape_html(&result.operation)
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// 把性能测试结果渲染为独立的 HTML 页面
+pub fn render_performance_report_html(results: &[PerformanceResult]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>性能测试报告</title>\n<style>\n\
+         body { font-family: sans-serif; margin: 2em; }\n\
+         table { border-collapse: collapse; margin-bottom: 2em; }\n\
+         th, td { border: 1px solid #ccc; padding: 4px 10px; text-align: right; }\n\
+         th:first-child, td:first-child { text-align: left; }\n\
+         .chart { border: 1px solid #eee; }\n\
+         </style>\n</head>\n<body>\n<h1>性能测试报告</h1>\n",
+    );
+
+    if results.is_empty() {
+        html.push_str("<p>没有性能测试结果</p>\n</body>\n</html>\n");
+        return html;
+    }
+
+    html.push_str(
+        "<table>\n<tr><th>测试</th><th>总时间</th><th>操作次数</th><th>每秒操作数</th>\
+         <th>平均延迟</th><th>最小延迟</th><th>最大延迟</th><th>内存使用 (KB)</th><th>峰值内存 (KB)</th></tr>\n",
+    );
+    for result in results {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{:?}</td><td>{}</td><td>{:.2}</td><td>{:?}</td><td>{:?}</td><td>{:?}</td><td>{}</td><td>{}</td></tr>\n",
+            escape_html(&result.operation),
+            result.total_time,
+            result.operations_count,
+            result.operations_per_second,
+            result.average_latency,
+            result.min_latency,
+            result.max_latency,
+            result.memory_usage / 1024,
+            result.peak_memory_usage / 1024
+        ));
+    }
+    html.push_str("</table>\n");
+
+    let total_operations: u64 = results.iter().map(|r| r.operations_count).sum();
+    let total_time: Duration = results.iter().map(|r| r.total_time).sum();
+    let avg_ops_per_second = results.iter().map(|r| r.operations_per_second).sum::<f64>() / results.len() as f64;
+    html.push_str(&format!(
+        "<h2>总体统计</h2>\n<ul>\n<li>总操作数: {}</li>\n<li>总时间: {:?}</li>\n<li>平均每秒操作数: {:.2}</li>\n</ul>\n",
+        total_operations, total_time, avg_ops_per_second
+    ));
+
+    html.push_str("<h2>延迟分布（最小 / 平均 / 最大）</h2>\n");
+    html.push_str(&render_latency_svg(results));
+    html.push_str("</body>\n</html>\n");
+    html
 }
 
 /// 性能测试示例
-pub async fn performance_test_example() -> Result<()> {
+/// 把性能测试结果记录到基准测试数据库，测试名为操作名，耗时取平均延迟
+pub async fn record_to_benchdb(path: &Path, results: &[PerformanceResult]) -> Result<i64> {
+    let mut run = benchdb::RunRecord::new("august-code");
+    for result in results {
+        run.push(benchdb::Measurement::new(&result.operation, result.average_latency, result.operations_count));
+    }
+    let path = path.to_path_buf();
+    // rusqlite 是同步接口，放到阻塞线程池里执行
+    let run_id = tokio::task::spawn_blocking(move || benchdb::BenchDb::open(&path)?.record(&run)).await??;
+    Ok(run_id)
+}
+
+pub async fn performance_test_example(benchdb: Option<&Path>) -> Result<()> {
     println!("\n=== 性能测试示例 ===");
     
     let tester = PerformanceTester::new();
@@ -358,13 +746,45 @@ pub async fn performance_test_example() -> Result<()> {
     tester.run_latency_test("延迟测试", 1000).await?;
     
     // 吞吐量测试
-    tester.run_throughput_test("吞吐量测试", Duration::from_secs(2)).await?;
+    let throughput = tester
+        .run_throughput_test_with("吞吐量测试", ThroughputConfig::new(Duration::from_secs(2)))
+        .await?;
+    let windows = &throughput.window_throughput;
+    let peak = windows.iter().cloned().fold(0.0, f64::max);
+    println!("吞吐量测试: {} 个窗口，峰值 {:.0} 次/秒，稳态开始于 {:?}",
+             windows.len(), peak, throughput.steady_state_at);
     
     // 内存使用测试
     tester.run_memory_test("内存测试", 1000).await?;
     
+    // 批量插入对比
+    tester.run_bulk_insert_comparison(10_000).await?;
+    let (_, pool_stats) = tester.run_pool_load_test(500, 10).await?;
+    report::metric("性能测试", "连接池/平均等待(ms)", pool_stats.average_wait().as_secs_f64() * 1000.0);
+    report::metric("性能测试", "连接池/最长等待(ms)", pool_stats.max_wait.as_secs_f64() * 1000.0);
+    report::metric("性能测试", "连接池/已创建连接", pool_stats.open as f64);
+    
+    // 记入运行报告，未开启报告时不必复制结果
+    if report::is_enabled() {
+        for result in tester.get_all_results().await {
+            let prefix = result.operation.as_str();
+            report::metric("性能测试", &format!("{}/操作次数", prefix), result.operations_count as f64);
+            report::metric("性能测试", &format!("{}/每秒操作数", prefix), result.operations_per_second);
+            report::metric("性能测试", &format!("{}/平均延迟(ms)", prefix), result.average_latency.as_secs_f64() * 1000.0);
+            report::metric("性能测试", &format!("{}/峰值内存(KB)", prefix), (result.peak_memory_usage / 1024) as f64);
+        }
+    }
+    
     // 打印性能报告
     tester.print_performance_report().await;
+    tester.print_performance_report_html("performance_report.html").await?;
+    
+    if let Some(path) = benchdb {
+        let run_id = record_to_benchdb(path, &tester.get_all_results().await).await?;
+        println!("结果已记录到 {}（运行 #{}）", path.display(), run_id);
+    } else {
+        report::skip("基准测试记录", "未指定 --benchdb");
+    }
     
     Ok(())
 }
@@ -401,4 +821,117 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert!(results[0].operations_count > 0);
     }
+    
+    #[tokio::test]
+    async fn test_performance_report_html() {
+        let tester = PerformanceTester::new();
+        tester.run_latency_test("<延迟>", 5).await.unwrap();
+        tester.run_concurrency_test("并发", 2, 5).await.unwrap();
+        
+        let path = std::env::temp_dir().join(format!("august-code-report-{}.html", std::process::id()));
+        let path = path.to_str().unwrap();
+        tester.print_performance_report_html(path).await.unwrap();
+        let html = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert_eq!(html.matches("<tr><td>").count(), 2);
+        assert_eq!(html.matches("<rect ").count(), 6);
+        assert!(html.contains("&lt;延迟&gt;"));
+        assert!(!html.contains("<延迟>"));
+    }
+    
+    #[test]
+    fn test_empty_performance_report_html() {
+        let html = render_performance_report_html(&[]);
+        assert!(html.contains("没有性能测试结果"));
+        assert!(!html.contains("<svg"));
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_memory_test_reports_real_usage() {
+        let tester = PerformanceTester::new();
+        let result = tester.run_memory_test("内存", 10).await.unwrap();
+        assert!(result.memory_usage > 0);
+        assert!(result.peak_memory_usage >= result.memory_usage);
+        assert_ne!(result.memory_usage, std::process::id() as u64 * 1024);
+    }
+    
+    #[test]
+    fn test_coefficient_of_variation() {
+        assert_eq!(coefficient_of_variation(&[5.0, 5.0, 5.0]), 0.0);
+        assert!((coefficient_of_variation(&[1.0, 3.0]) - 0.5).abs() < 1e-9);
+        assert!(coefficient_of_variation(&[]).is_infinite());
+        assert!(coefficient_of_variation(&[0.0, 0.0]).is_infinite());
+    }
+    
+    #[test]
+    fn test_detect_steady_state() {
+        let windows = [10.0, 40.0, 80.0, 100.0, 101.0, 99.0, 100.0];
+        assert_eq!(detect_steady_state(&windows, 0, 3, 0.05), Some(3));
+        assert_eq!(detect_steady_state(&windows, 4, 3, 0.05), Some(4));
+        assert_eq!(detect_steady_state(&windows, 5, 3, 0.05), None);
+        assert_eq!(detect_steady_state(&windows[..4], 0, 3, 0.05), None);
+    }
+    
+    #[tokio::test]
+    async fn test_throughput_test_splits_warmup() {
+        let tester = PerformanceTester::new();
+        let config = ThroughputConfig {
+            duration: Duration::from_millis(300),
+            warmup: Duration::from_millis(100),
+            window: Duration::from_millis(50),
+            stable_windows: 2,
+            cv_threshold: 1.0,
+        };
+        let report = tester.run_throughput_test_with("吞吐量", config).await.unwrap();
+        
+        assert_eq!(report.window_throughput.len(), 6);
+        assert!(report.steady_state_at.unwrap() >= Duration::from_millis(100));
+        assert!(report.warmup.operations_count > 0);
+        assert!(report.steady_state.operations_count > 0);
+        assert!(report.steady_state.total_time + report.warmup.total_time >= Duration::from_millis(300));
+        
+        let results = tester.get_all_results().await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].operation, "吞吐量");
+    }
+    
+    #[tokio::test]
+    async fn test_bulk_insert_comparison() {
+        let tester = PerformanceTester::new();
+        let (looped, bulk) = tester.run_bulk_insert_comparison(200).await.unwrap();
+        assert_eq!(looped.operations_count, 200);
+        assert_eq!(bulk.operations_count, 200);
+        assert_eq!(tester.get_all_results().await.len(), 2);
+    }
+    
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_pool_load_test_respects_cap() {
+        let tester = PerformanceTester::new();
+        let (result, stats) = tester.run_pool_load_test(200, 5).await.unwrap();
+        assert_eq!(result.operations_count, 200);
+        assert_eq!(stats.acquired, 200);
+        assert!(stats.open <= 5);
+        assert_eq!((stats.in_use, stats.waiting, stats.timeouts), (0, 0, 0));
+    }
+    
+    #[tokio::test]
+    async fn test_record_to_benchdb() {
+        let tester = PerformanceTester::new();
+        tester.run_concurrency_test("并发操作", 2, 10).await.unwrap();
+        let results = tester.get_all_results().await;
+        
+        let dir = std::env::temp_dir().join(format!("august-code-benchdb-{}", std::process::id()));
+        let path = dir.join("bench.sqlite");
+        record_to_benchdb(&path, &results).await.unwrap();
+        record_to_benchdb(&path, &results).await.unwrap();
+        
+        let db = benchdb::BenchDb::open(&path).unwrap();
+        let history = db.history("august-code", "并发操作", None, 10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].iterations, results[0].operations_count);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,372 @@
+//! 隔舱（Bulkhead）隔离工具模块
+//!
+//! 为每个下游依赖单独限制并发，避免一个慢依赖耗尽整个运行时：
+//! - 基于信号量的并发上限
+//! - 有界等待队列，队列满时立即拒绝
+//! - 可选的排队超时
+//! - 接受、拒绝、超时等统计指标
+
+use anyhow::Result;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+use crate::core::database::{AsyncDatabase, User};
+use crate::core::http_client::AsyncHttpClient;
+use super::test_server::TestServer;
+
+/// 隔舱拒绝执行的原因
+#[derive(Debug, thiserror::Error)]
+pub enum BulkheadError {
+    #[error("隔舱 {0} 已满：并发和等待队列都已占满")]
+    Full(String),
+
+    #[error("隔舱 {0} 排队超时")]
+    QueueTimeout(String),
+}
+
+/// 隔舱配置
+#[derive(Debug, Clone)]
+pub struct BulkheadConfig {
+    /// 同时执行的最大操作数
+    pub max_concurrent: usize,
+    /// 等待执行的最大操作数
+    pub max_queue: usize,
+    /// 排队等待的最长时间，`None` 表示一直等待
+    pub max_wait: Option<Duration>,
+}
+
+impl Default for BulkheadConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 10,
+            max_queue: 20,
+            max_wait: Some(Duration::from_secs(1)),
+        }
+    }
+}
+
+/// 隔舱统计快照
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BulkheadStats {
+    pub accepted: u64,
+    pub rejected: u64,
+    pub timed_out: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    pub active: usize,
+    pub queued: usize,
+    pub peak_active: usize,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    accepted: AtomicU64,
+    rejected: AtomicU64,
+    timed_out: AtomicU64,
+    succeeded: AtomicU64,
+    failed: AtomicU64,
+    queued: AtomicUsize,
+    peak_active: AtomicUsize,
+}
+
+/// 排队名额，离开作用域（包括被取消）时归还
+struct QueueSlot<'a>(&'a AtomicUsize);
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// 单个依赖的隔舱，克隆后共享同一组许可和统计
+#[derive(Debug, Clone)]
+pub struct Bulkhead {
+    name: String,
+    config: BulkheadConfig,
+    permits: Arc<Semaphore>,
+    counters: Arc<Counters>,
+}
+
+impl Bulkhead {
+    /// 创建新的隔舱
+    pub fn new(name: &str, config: BulkheadConfig) -> Self {
+        Self {
+            name: name.to_string(),
+            permits: Arc::new(Semaphore::new(config.max_concurrent)),
+            config,
+            counters: Arc::new(Counters::default()),
+        }
+    }
+
+    /// 隔舱名称
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 在隔舱内执行操作
+    ///
+    /// 有空闲许可时立即执行；否则进入等待队列，队列已满或排队超时时
+    /// 返回 `BulkheadError`，操作本身不会被调用。
+    pub async fn execute<F, Fut, T>(&self, operation: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let _permit = match self.permits.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                let _slot = self.enter_queue()?;
+                let acquire = self.permits.clone().acquire_owned();
+                let acquired = match self.config.max_wait {
+                    Some(max_wait) => match tokio::time::timeout(max_wait, acquire).await {
+                        Ok(acquired) => acquired,
+                        Err(_) => {
+                            self.counters.timed_out.fetch_add(1, Ordering::SeqCst);
+                            return Err(BulkheadError::QueueTimeout(self.name.clone()).into());
+                        }
+                    },
+                    None => acquire.await,
+                };
+                acquired.expect("隔舱信号量不会被关闭")
+            }
+        };
+
+        self.counters.accepted.fetch_add(1, Ordering::SeqCst);
+        self.counters.peak_active.fetch_max(self.active(), Ordering::SeqCst);
+
+        let result = operation().await;
+        let counter = if result.is_ok() { &self.counters.succeeded } else { &self.counters.failed };
+        counter.fetch_add(1, Ordering::SeqCst);
+        result
+    }
+
+    /// 占用一个排队名额，队列已满时记录拒绝
+    fn enter_queue(&self) -> Result<QueueSlot<'_>, BulkheadError> {
+        let max_queue = self.config.max_queue;
+        let entered = self.counters.queued.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |queued| {
+            (queued < max_queue).then_some(queued + 1)
+        });
+        match entered {
+            Ok(_) => Ok(QueueSlot(&self.counters.queued)),
+            Err(_) => {
+                self.counters.rejected.fetch_add(1, Ordering::SeqCst);
+                Err(BulkheadError::Full(self.name.clone()))
+            }
+        }
+    }
+
+    /// 正在执行的操作数
+    pub fn active(&self) -> usize {
+        self.config.max_concurrent - self.permits.available_permits()
+    }
+
+    /// 获取统计快照
+    pub fn stats(&self) -> BulkheadStats {
+        BulkheadStats {
+            accepted: self.counters.accepted.load(Ordering::SeqCst),
+            rejected: self.counters.rejected.load(Ordering::SeqCst),
+            timed_out: self.counters.timed_out.load(Ordering::SeqCst),
+            succeeded: self.counters.succeeded.load(Ordering::SeqCst),
+            failed: self.counters.failed.load(Ordering::SeqCst),
+            active: self.active(),
+            queued: self.counters.queued.load(Ordering::SeqCst),
+            peak_active: self.counters.peak_active.load(Ordering::SeqCst),
+        }
+    }
+
+    /// 打印统计信息
+    pub fn print_stats(&self) {
+        let stats = self.stats();
+        println!(
+            "隔舱 {}: 接受 {}, 拒绝 {}, 排队超时 {}, 成功 {}, 失败 {}, 峰值并发 {}",
+            self.name,
+            stats.accepted,
+            stats.rejected,
+            stats.timed_out,
+            stats.succeeded,
+            stats.failed,
+            stats.peak_active
+        );
+    }
+}
+
+/// 隔舱隔离示例：慢 HTTP 依赖被限制在自己的隔舱里，不影响数据库操作
+pub async fn bulkhead_example() -> Result<()> {
+    println!("\n=== 隔舱隔离示例 ===");
+
+    let http_bulkhead = Bulkhead::new("HTTP", BulkheadConfig {
+        max_concurrent: 2,
+        max_queue: 2,
+        max_wait: Some(Duration::from_millis(500)),
+    });
+    let db_bulkhead = Bulkhead::new("数据库", BulkheadConfig {
+        max_concurrent: 4,
+        max_queue: 8,
+        max_wait: None,
+    });
+
+    let server = TestServer::start().await?;
+    let slow_url = server.url("/delay/2");
+    let http_client = Arc::new(AsyncHttpClient::with_timeout(Duration::from_secs(3)));
+    let database = AsyncDatabase::new();
+
+    // 大量慢请求涌入 HTTP 隔舱，超出部分被拒绝或排队超时
+    let mut http_handles = Vec::new();
+    for i in 1..=8 {
+        let bulkhead = http_bulkhead.clone();
+        let client = http_client.clone();
+        let url = slow_url.clone();
+        http_handles.push(tokio::spawn(async move {
+            let result = bulkhead
+                .execute(|| async move { client.fetch_url(&url).await })
+                .await;
+            match result {
+                Ok(response) => println!("HTTP 请求 {} 完成: {}", i, response.status),
+                Err(e) => println!("HTTP 请求 {} 失败: {}", i, e),
+            }
+        }));
+    }
+
+    // 同时进行的数据库操作只受数据库隔舱限制
+    let mut db_handles = Vec::new();
+    for i in 1..=10 {
+        let bulkhead = db_bulkhead.clone();
+        let database = database.clone();
+        db_handles.push(tokio::spawn(async move {
+            bulkhead
+                .execute(|| async move {
+                    database.create_user(User {
+                        id: i.to_string(),
+                        name: format!("用户{}", i),
+                        email: format!("user{}@example.com", i),
+                        created_at: 1234567890 + i,
+                        version: 0,
+                    }).await?;
+                    let connection = database.get_connection().await?;
+                    connection.query("SELECT * FROM users").await
+                })
+                .await
+        }));
+    }
+
+    for handle in db_handles {
+        handle.await??;
+    }
+    println!("数据库操作全部完成，{} 隔舱当前并发: {}", http_bulkhead.name(), http_bulkhead.active());
+
+    for handle in http_handles {
+        handle.await?;
+    }
+
+    http_bulkhead.print_stats();
+    db_bulkhead.print_stats();
+    server.shutdown().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::Notify;
+
+    fn bulkhead(max_concurrent: usize, max_queue: usize, max_wait: Option<Duration>) -> Bulkhead {
+        Bulkhead::new("test", BulkheadConfig {
+            max_concurrent,
+            max_queue,
+            max_wait,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_bulkhead_rejects_when_queue_full() {
+        let bulkhead = bulkhead(1, 1, None);
+        let release = Arc::new(Notify::new());
+
+        let running = {
+            let bulkhead = bulkhead.clone();
+            let release = release.clone();
+            tokio::spawn(async move {
+                bulkhead.execute(|| async move {
+                    release.notified().await;
+                    Ok(1)
+                }).await
+            })
+        };
+        while bulkhead.active() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let queued = {
+            let bulkhead = bulkhead.clone();
+            tokio::spawn(async move { bulkhead.execute(|| async { Ok(2) }).await })
+        };
+        while bulkhead.stats().queued == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let rejected = bulkhead.execute(|| async { Ok(3) }).await;
+        assert!(matches!(
+            rejected.unwrap_err().downcast_ref::<BulkheadError>(),
+            Some(BulkheadError::Full(_))
+        ));
+
+        release.notify_one();
+        assert_eq!(running.await.unwrap().unwrap(), 1);
+        assert_eq!(queued.await.unwrap().unwrap(), 2);
+
+        let stats = bulkhead.stats();
+        assert_eq!(stats.accepted, 2);
+        assert_eq!(stats.rejected, 1);
+        assert_eq!(stats.succeeded, 2);
+        assert_eq!(stats.peak_active, 1);
+        assert_eq!(stats.active, 0);
+        assert_eq!(stats.queued, 0);
+    }
+
+    #[tokio::test]
+    async fn test_bulkhead_queue_timeout() {
+        let bulkhead = bulkhead(1, 4, Some(Duration::from_millis(20)));
+        let release = Arc::new(Notify::new());
+
+        let running = {
+            let bulkhead = bulkhead.clone();
+            let release = release.clone();
+            tokio::spawn(async move {
+                bulkhead.execute(|| async move {
+                    release.notified().await;
+                    Ok(())
+                }).await
+            })
+        };
+        while bulkhead.active() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let result = bulkhead.execute(|| async { Ok(()) }).await;
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<BulkheadError>(),
+            Some(BulkheadError::QueueTimeout(_))
+        ));
+        assert_eq!(bulkhead.stats().timed_out, 1);
+        assert_eq!(bulkhead.stats().queued, 0);
+
+        release.notify_one();
+        running.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_bulkhead_counts_failures() {
+        let bulkhead = bulkhead(2, 0, None);
+        let result: Result<()> = bulkhead.execute(|| async { Err(anyhow::anyhow!("失败")) }).await;
+        assert!(result.is_err());
+        bulkhead.execute(|| async { Ok(()) }).await.unwrap();
+
+        let stats = bulkhead.stats();
+        assert_eq!(stats.accepted, 2);
+        assert_eq!(stats.failed, 1);
+        assert_eq!(stats.succeeded, 1);
+    }
+}
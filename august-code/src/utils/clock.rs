@@ -0,0 +1,202 @@
+//! 可注入的时钟
+//!
+//! 需要读取当前时间或等待的组件持有 `Arc<dyn Clock>`：
+//! - [`SystemClock`]：真实时间，基于 tokio 的定时器
+//! - [`MockClock`]：虚拟时间，测试中调用 `advance` 推进，不必真的等待
+
+use futures::future::BoxFuture;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// 时钟：当前时间、等待和固定周期的定时
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// 当前时间
+    fn now(&self) -> Instant;
+
+    /// 等待到指定时间，时间已过时立即完成
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'static, ()>;
+
+    /// 等待一段时间
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        self.sleep_until(self.now() + duration)
+    }
+
+    /// 固定周期的定时器，第一次 `tick` 立即完成
+    fn interval(&self, period: Duration) -> ClockInterval;
+}
+
+/// 真实时钟
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep_until(deadline.into()))
+    }
+
+    fn interval(&self, period: Duration) -> ClockInterval {
+        ClockInterval::new(Arc::new(*self), period)
+    }
+}
+
+/// 默认使用的真实时钟
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// 虚拟时钟，只有调用 [`MockClock::advance`] 时时间才会前进
+///
+/// 克隆出的时钟共享同一个时间。推进时间后，到期的等待被唤醒，
+/// 等待方在调用方让出执行权（例如 `yield_now`）后继续运行。
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    start: Instant,
+    elapsed: Arc<watch::Sender<Duration>>,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Arc::new(watch::Sender::new(Duration::ZERO)),
+        }
+    }
+
+    /// 时间前进 `duration`，唤醒所有到期的等待
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed.send_modify(|elapsed| *elapsed += duration);
+    }
+
+    /// 从创建到现在经过的虚拟时间
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.borrow()
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'static, ()> {
+        let start = self.start;
+        let mut elapsed = self.elapsed.subscribe();
+        Box::pin(async move {
+            while start + *elapsed.borrow_and_update() < deadline {
+                // 时钟已经被丢弃，时间不会再前进
+                if elapsed.changed().await.is_err() {
+                    std::future::pending::<()>().await;
+                }
+            }
+        })
+    }
+
+    fn interval(&self, period: Duration) -> ClockInterval {
+        ClockInterval::new(Arc::new(self.clone()), period)
+    }
+}
+
+/// 按时钟计时的固定周期定时器，错过的触发会连续补上
+pub struct ClockInterval {
+    clock: Arc<dyn Clock>,
+    next: Instant,
+    period: Duration,
+}
+
+impl ClockInterval {
+    pub fn new(clock: Arc<dyn Clock>, period: Duration) -> Self {
+        assert!(!period.is_zero(), "定时器周期不能为0");
+        let next = clock.now();
+        Self { clock, next, period }
+    }
+
+    /// 等待下一次触发，返回这次触发的计划时间
+    pub async fn tick(&mut self) -> Instant {
+        let scheduled = self.next;
+        self.clock.sleep_until(scheduled).await;
+        self.next = scheduled + self.period;
+        scheduled
+    }
+}
+
+impl fmt::Debug for ClockInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClockInterval")
+            .field("clock", &self.clock)
+            .field("next", &self.next)
+            .field("period", &self.period)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// 让出执行权，使被唤醒的任务运行到下一次等待
+    async fn settle() {
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_sleep_completes_only_after_advance() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let sleeper = tokio::spawn(clock.sleep(Duration::from_secs(60)));
+
+        clock.advance(Duration::from_secs(59));
+        settle().await;
+        assert!(!sleeper.is_finished());
+
+        clock.advance(Duration::from_secs(1));
+        settle().await;
+        assert!(sleeper.is_finished());
+        assert_eq!(clock.now() - start, Duration::from_secs(60));
+
+        // 已经到期的等待立即完成
+        clock.sleep(Duration::ZERO).await;
+        clock.sleep_until(start).await;
+    }
+
+    #[tokio::test]
+    async fn test_mock_interval_catches_up() {
+        let clock = MockClock::new();
+        let ticks = Arc::new(AtomicU32::new(0));
+        let mut interval = clock.interval(Duration::from_secs(10));
+        let counter = Arc::clone(&ticks);
+        let task = tokio::spawn(async move {
+            loop {
+                interval.tick().await;
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        settle().await;
+        assert_eq!(ticks.load(Ordering::SeqCst), 1);
+
+        clock.advance(Duration::from_secs(10));
+        settle().await;
+        assert_eq!(ticks.load(Ordering::SeqCst), 2);
+
+        // 一次前进多个周期时，错过的触发连续补上
+        clock.advance(Duration::from_secs(30));
+        settle().await;
+        assert_eq!(ticks.load(Ordering::SeqCst), 5);
+        task.abort();
+    }
+}
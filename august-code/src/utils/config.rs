@@ -4,13 +4,149 @@
 //! - 配置加载
 //! - 配置验证
 //! - 环境变量支持
-//! - 配置热重载
+//! - 配置热重载，观察者收到新旧配置的逐项差异
+//! - 按配置节订阅变化的 watch 通道
+//! - 时长和字节数支持 `"30s"`、`"10MB"` 这样的写法
+//! - 配置 profile：`[profile.dev]`、`[profile.prod]` 等覆盖基础配置，由 `APP_PROFILE` 选择
+//! - 命令行 `--set key.path=value` 覆盖，优先级最高
 
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use common::config::{env_parse, env_var, parse_bool, parse_byte_size, parse_duration};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{watch, RwLock};
+
+/// 可读的时长，配置中写作 `"500ms"`、`"30s"`、`"5m"`、`"2h"`，整数按秒计算
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct HumanDuration(pub Duration);
+
+impl HumanDuration {
+    pub fn from_secs(secs: u64) -> Self {
+        Self(Duration::from_secs(secs))
+    }
+    
+    pub fn as_duration(&self) -> Duration {
+        self.0
+    }
+    
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+}
+
+impl From<Duration> for HumanDuration {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl FromStr for HumanDuration {
+    type Err = common::Error;
+    
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        parse_duration(s).map(Self)
+    }
+}
+
+/// 输出能被重新解析的最大整数单位，例如 `90s` 输出为 `90s`，`120s` 输出为 `2m`
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let millis = self.0.as_millis();
+        if !millis.is_multiple_of(1000) {
+            return write!(f, "{}ms", millis);
+        }
+        let secs = self.0.as_secs();
+        match secs {
+            0 => write!(f, "0s"),
+            s if s.is_multiple_of(86400) => write!(f, "{}d", s / 86400),
+            s if s.is_multiple_of(3600) => write!(f, "{}h", s / 3600),
+            s if s.is_multiple_of(60) => write!(f, "{}m", s / 60),
+            s => write!(f, "{}s", s),
+        }
+    }
+}
+
+/// 字节数，配置中写作 `"512"`、`"64KB"`、`"10MB"`（按 1024 进位），整数按字节计算
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct ByteSize(pub u64);
+
+impl ByteSize {
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for ByteSize {
+    fn from(bytes: u64) -> Self {
+        Self(bytes)
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = common::Error;
+    
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        parse_byte_size(s).map(Self)
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [(u64, &str); 4] = [(1 << 40, "TB"), (1 << 30, "GB"), (1 << 20, "MB"), (1 << 10, "KB")];
+        match UNITS.iter().find(|(size, _)| self.0 != 0 && self.0.is_multiple_of(*size)) {
+            Some((size, unit)) => write!(f, "{}{}", self.0 / size, unit),
+            None => write!(f, "{}B", self.0),
+        }
+    }
+}
+
+/// 两种类型的序列化方式相同：写成可读字符串，读取时接受字符串或非负整数
+macro_rules! human_serde {
+    ($ty:ty, $expecting:literal, $from_int:expr) => {
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+        
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+                struct HumanVisitor;
+                
+                impl Visitor<'_> for HumanVisitor {
+                    type Value = $ty;
+                    
+                    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        f.write_str($expecting)
+                    }
+                    
+                    fn visit_str<E: de::Error>(self, value: &str) -> std::result::Result<$ty, E> {
+                        value.parse().map_err(E::custom)
+                    }
+                    
+                    fn visit_u64<E: de::Error>(self, value: u64) -> std::result::Result<$ty, E> {
+                        Ok($from_int(value))
+                    }
+                    
+                    fn visit_i64<E: de::Error>(self, value: i64) -> std::result::Result<$ty, E> {
+                        u64::try_from(value)
+                            .map($from_int)
+                            .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+                    }
+                }
+                
+                deserializer.deserialize_any(HumanVisitor)
+            }
+        }
+    };
+}
+
+human_serde!(HumanDuration, "时长，例如 \"500ms\"、\"30s\"、\"5m\" 或秒数", HumanDuration::from_secs);
+human_serde!(ByteSize, "字节数，例如 \"64KB\"、\"10MB\" 或字节数", ByteSize);
 
 /// 应用配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,34 +158,34 @@ pub struct AppConfig {
 }
 
 /// 服务器配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
-    pub timeout: u64,
+    pub timeout: HumanDuration,
     pub max_connections: u32,
 }
 
 /// 数据库配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DatabaseConfig {
     pub url: String,
     pub max_connections: u32,
-    pub timeout: u64,
+    pub timeout: HumanDuration,
     pub retry_attempts: u32,
 }
 
 /// 日志配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LoggingConfig {
     pub level: String,
     pub file: Option<String>,
-    pub max_size: u64,
+    pub max_size: ByteSize,
     pub max_files: u32,
 }
 
 /// 功能配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FeatureConfig {
     pub enable_cache: bool,
     pub enable_metrics: bool,
@@ -63,19 +199,19 @@ impl Default for AppConfig {
             server: ServerConfig {
                 host: "localhost".to_string(),
                 port: 8080,
-                timeout: 30,
+                timeout: HumanDuration::from_secs(30),
                 max_connections: 100,
             },
             database: DatabaseConfig {
                 url: "sqlite://:memory:".to_string(),
                 max_connections: 10,
-                timeout: 5,
+                timeout: HumanDuration::from_secs(5),
                 retry_attempts: 3,
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
                 file: None,
-                max_size: 10 * 1024 * 1024, // 10MB
+                max_size: ByteSize(10 * 1024 * 1024),
                 max_files: 5,
             },
             features: FeatureConfig {
@@ -88,98 +224,390 @@ impl Default for AppConfig {
     }
 }
 
+/// 选择配置 profile 的环境变量
+pub const PROFILE_ENV: &str = "APP_PROFILE";
+
+/// 使用 `prod` profile 时配置文件中必须写明的键
+pub const PROD_REQUIRED_KEYS: &[&str] = &["database.url", "logging.file"];
+
+/// 解析 TOML 配置，出错时的信息包含出错的行和键
+pub fn parse_config(content: &str) -> Result<AppConfig> {
+    toml::from_str(content).map_err(|e| {
+        match e.span().and_then(|span| key_at(content, span.start)) {
+            Some((line, key)) => anyhow::anyhow!("第 {} 行的 {} 无效: {}", line, key, e.message()),
+            None => anyhow::anyhow!("{}", e),
+        }
+    })
+}
+
+/// 解析带 profile 的 TOML 配置：`[profile.<名称>]` 中的值逐项覆盖基础配置，
+/// 没有选择 profile 时只使用基础配置
+pub fn parse_config_with_profile(content: &str, profile: Option<&str>) -> Result<AppConfig> {
+    let Some(name) = profile else {
+        return parse_config(content);
+    };
+    
+    let mut root: toml::Table = toml::from_str(content)?;
+    let profiles = match root.remove("profile") {
+        Some(toml::Value::Table(profiles)) => profiles,
+        Some(_) => return Err(anyhow::anyhow!("profile 必须是表，例如 [profile.{}]", name)),
+        None => toml::Table::new(),
+    };
+    let Some(overlay) = profiles.get(name) else {
+        let available: Vec<&str> = profiles.keys().map(String::as_str).collect();
+        return Err(anyhow::anyhow!("未定义的 profile: {}（可用: {}）", name, available.join(", ")));
+    };
+    merge_toml(&mut root, overlay);
+    
+    if name == "prod" {
+        let missing: Vec<&str> = PROD_REQUIRED_KEYS.iter().copied().filter(|key| !has_key(&root, key)).collect();
+        if !missing.is_empty() {
+            return Err(anyhow::anyhow!("prod profile 缺少必需的配置项: {}", missing.join(", ")));
+        }
+    }
+    
+    // 合并后的文本没有对应的原始行号，错误信息中只给出键
+    let merged = toml::to_string(&root)?;
+    toml::from_str(&merged).map_err(|e| {
+        match e.span().and_then(|span| key_at(&merged, span.start)) {
+            Some((_, key)) => anyhow::anyhow!("应用 profile {} 后 {} 无效: {}", name, key, e.message()),
+            None => anyhow::anyhow!("应用 profile {} 后配置无效: {}", name, e),
+        }
+    })
+}
+
+/// 解析 `key.path=value` 形式的覆盖项
+pub fn parse_override(item: &str) -> Result<(String, String)> {
+    let (key, value) = item
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("覆盖项 {:?} 必须是 key.path=value 形式", item))?;
+    let key = key.trim();
+    if key.is_empty() || key.split('.').any(str::is_empty) {
+        return Err(anyhow::anyhow!("覆盖项 {:?} 的键无效", item));
+    }
+    Ok((key.to_string(), value.trim().to_string()))
+}
+
+/// 把覆盖项依次应用到配置上，任何一项无效时返回错误
+pub fn apply_overrides(config: &AppConfig, overrides: &[(String, String)]) -> Result<AppConfig> {
+    let mut root = toml::Table::try_from(config)?;
+    for (path, raw) in overrides {
+        set_override(&mut root, path, raw).map_err(|e| anyhow::anyhow!("覆盖项 {}={} 无效: {}", path, raw, e))?;
+    }
+    Ok(root.try_into()?)
+}
+
+/// 设置单个覆盖项，值按该键原来的类型转换，并检查结果仍是合法的配置
+fn set_override(root: &mut toml::Table, path: &str, raw: &str) -> Result<()> {
+    let (parents, leaf) = match path.rsplit_once('.') {
+        Some((parents, leaf)) => (Some(parents), leaf),
+        None => (None, path),
+    };
+    let mut table = &mut *root;
+    for part in parents.into_iter().flat_map(|parents| parents.split('.')) {
+        table = match table.get_mut(part) {
+            Some(toml::Value::Table(next)) => next,
+            _ => return Err(anyhow::anyhow!("未知的配置项")),
+        };
+    }
+    let value = coerce_value(table.get(leaf), raw)?;
+    table.insert(leaf.to_string(), value);
+    
+    // 未知的键在反序列化时会被忽略，重新序列化后还在才说明键存在
+    let config: AppConfig = root.clone().try_into().map_err(|e: toml::de::Error| anyhow::anyhow!("{}", e.message()))?;
+    if !has_key(&toml::Table::try_from(&config)?, path) {
+        return Err(anyhow::anyhow!("未知的配置项"));
+    }
+    Ok(())
+}
+
+/// 按原值的类型把字符串转换为 TOML 值，可选项原来没有值时按字符串处理
+fn coerce_value(existing: Option<&toml::Value>, raw: &str) -> Result<toml::Value> {
+    Ok(match existing {
+        Some(toml::Value::Integer(_)) => {
+            toml::Value::Integer(raw.parse().map_err(|_| anyhow::anyhow!("{:?} 不是整数", raw))?)
+        }
+        Some(toml::Value::Float(_)) => {
+            toml::Value::Float(raw.parse().map_err(|_| anyhow::anyhow!("{:?} 不是数字", raw))?)
+        }
+        Some(toml::Value::Boolean(_)) => toml::Value::Boolean(parse_bool(raw)?),
+        Some(toml::Value::Table(_)) => return Err(anyhow::anyhow!("这是配置表，只能覆盖其中的单个键")),
+        _ => toml::Value::String(raw.to_string()),
+    })
+}
+
+/// 把 `overlay` 合并到 `base`：两边都是表时逐项合并，否则用 `overlay` 的值替换
+fn merge_toml(base: &mut toml::Table, overlay: &toml::Value) {
+    let Some(overlay) = overlay.as_table() else {
+        return;
+    };
+    for (key, value) in overlay {
+        match (base.get_mut(key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(_)) => merge_toml(base_table, value),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// 按 `a.b.c` 形式的路径判断键是否存在
+fn has_key(table: &toml::Table, path: &str) -> bool {
+    let mut current = table;
+    let mut parts = path.split('.').peekable();
+    while let Some(part) = parts.next() {
+        match (current.get(part), parts.peek()) {
+            (Some(_), None) => return true,
+            (Some(toml::Value::Table(next)), Some(_)) => current = next,
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// 找出 TOML 文本中某个位置所在的行号和键，键带上所在的表名，例如 `server.timeout`
+fn key_at(content: &str, offset: usize) -> Option<(usize, String)> {
+    let mut table = String::new();
+    let mut line_start = 0;
+    for (number, line) in content.split_inclusive('\n').enumerate() {
+        let line_end = line_start + line.len();
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && !trimmed.starts_with("[[") {
+            table = trimmed.trim_matches(|c| c == '[' || c == ']').trim().to_string();
+        }
+        if (line_start..line_end).contains(&offset) {
+            let (key, _) = trimmed.split_once('=')?;
+            let key = key.trim().trim_matches('"');
+            let key = if table.is_empty() { key.to_string() } else { format!("{}.{}", table, key) };
+            return Some((number + 1, key));
+        }
+        line_start = line_end;
+    }
+    None
+}
+
 /// 配置管理器
 pub struct ConfigManager {
     config: Arc<RwLock<AppConfig>>,
     watchers: Arc<RwLock<Vec<Box<dyn ConfigWatcher + Send + Sync>>>>,
+    active_profile: Arc<RwLock<Option<String>>>,
+    cli_overrides: Arc<RwLock<Vec<(String, String)>>>,
+    server_tx: watch::Sender<ServerConfig>,
+    database_tx: watch::Sender<DatabaseConfig>,
+    logging_tx: watch::Sender<LoggingConfig>,
+    features_tx: watch::Sender<FeatureConfig>,
 }
 
-/// 配置观察者trait
+/// 配置中一个键的变化，值为 `None` 表示该键不存在（可选项没有值）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigChange {
+    pub path: String,
+    pub old: Option<toml::Value>,
+    pub new: Option<toml::Value>,
+}
+
+/// 新旧配置之间的结构化差异，按键路径（如 `server.port`）列出变化的叶子值
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigDiff {
+    pub changes: Vec<ConfigChange>,
+}
+
+impl ConfigDiff {
+    /// 比较两份配置
+    pub fn between(old: &AppConfig, new: &AppConfig) -> Self {
+        let old = toml::Table::try_from(old).expect("AppConfig 总能序列化为 TOML");
+        let new = toml::Table::try_from(new).expect("AppConfig 总能序列化为 TOML");
+        let mut changes = Vec::new();
+        diff_tables("", &old, &new, &mut changes);
+        Self { changes }
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+    
+    /// 某个键或某一节（如 `logging`）下的任意键是否有变化
+    pub fn changed(&self, path: &str) -> bool {
+        self.changes.iter().any(|change| {
+            change.path == path
+                || change.path.strip_prefix(path).is_some_and(|rest| rest.starts_with('.'))
+        })
+    }
+    
+    /// 某个键的变化
+    pub fn get(&self, path: &str) -> Option<&ConfigChange> {
+        self.changes.iter().find(|change| change.path == path)
+    }
+}
+
+/// 每行一项变化，例如 `server.port: 8080 -> 9090`
+impl fmt::Display for ConfigDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |value: &Option<toml::Value>| value.as_ref().map_or("(无)".to_string(), toml::Value::to_string);
+        for (i, change) in self.changes.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}: {} -> {}", change.path, show(&change.old), show(&change.new))?;
+        }
+        Ok(())
+    }
+}
+
+/// 递归比较两个表，两边都是表的键逐项比较，其他值不同时记为一项变化
+fn diff_tables(prefix: &str, old: &toml::Table, new: &toml::Table, changes: &mut Vec<ConfigChange>) {
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    
+    for key in keys {
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match (old.get(key), new.get(key)) {
+            (Some(toml::Value::Table(old)), Some(toml::Value::Table(new))) => diff_tables(&path, old, new, changes),
+            (old, new) if old != new => changes.push(ConfigChange { path, old: old.cloned(), new: new.cloned() }),
+            _ => {}
+        }
+    }
+}
+
+/// 配置观察者trait，配置有变化时收到差异和新配置，可以只处理关心的键
 pub trait ConfigWatcher {
-    fn on_config_changed(&self, config: &AppConfig);
+    fn on_config_changed(&self, diff: &ConfigDiff, config: &AppConfig);
 }
 
 impl ConfigManager {
     /// 创建新的配置管理器
     pub fn new() -> Self {
+        let config = AppConfig::default();
         Self {
-            config: Arc::new(RwLock::new(AppConfig::default())),
+            server_tx: watch::Sender::new(config.server.clone()),
+            database_tx: watch::Sender::new(config.database.clone()),
+            logging_tx: watch::Sender::new(config.logging.clone()),
+            features_tx: watch::Sender::new(config.features.clone()),
+            config: Arc::new(RwLock::new(config)),
             watchers: Arc::new(RwLock::new(Vec::new())),
+            active_profile: Arc::new(RwLock::new(None)),
+            cli_overrides: Arc::new(RwLock::new(Vec::new())),
         }
     }
     
-    /// 从环境变量加载配置
+    /// 从环境变量加载配置，时长和字节数支持带单位的写法，值不合法时错误信息包含变量名
     pub async fn load_from_env(&self) -> Result<()> {
         let mut config = self.config.write().await;
+        let old = config.clone();
         
         // 服务器配置
-        if let Ok(host) = std::env::var("SERVER_HOST") {
+        if let Some(host) = env_var("SERVER_HOST")? {
             config.server.host = host;
         }
-        if let Ok(port) = std::env::var("SERVER_PORT") {
-            config.server.port = port.parse()?;
+        if let Some(port) = env_var("SERVER_PORT")? {
+            config.server.port = port;
         }
-        if let Ok(timeout) = std::env::var("SERVER_TIMEOUT") {
-            config.server.timeout = timeout.parse()?;
+        if let Some(timeout) = env_var("SERVER_TIMEOUT")? {
+            config.server.timeout = timeout;
         }
-        if let Ok(max_conn) = std::env::var("SERVER_MAX_CONNECTIONS") {
-            config.server.max_connections = max_conn.parse()?;
+        if let Some(max_conn) = env_var("SERVER_MAX_CONNECTIONS")? {
+            config.server.max_connections = max_conn;
         }
         
         // 数据库配置
-        if let Ok(url) = std::env::var("DATABASE_URL") {
+        if let Some(url) = env_var("DATABASE_URL")? {
             config.database.url = url;
         }
-        if let Ok(max_conn) = std::env::var("DATABASE_MAX_CONNECTIONS") {
-            config.database.max_connections = max_conn.parse()?;
+        if let Some(max_conn) = env_var("DATABASE_MAX_CONNECTIONS")? {
+            config.database.max_connections = max_conn;
         }
-        if let Ok(timeout) = std::env::var("DATABASE_TIMEOUT") {
-            config.database.timeout = timeout.parse()?;
+        if let Some(timeout) = env_var("DATABASE_TIMEOUT")? {
+            config.database.timeout = timeout;
         }
-        if let Ok(retries) = std::env::var("DATABASE_RETRY_ATTEMPTS") {
-            config.database.retry_attempts = retries.parse()?;
+        if let Some(retries) = env_var("DATABASE_RETRY_ATTEMPTS")? {
+            config.database.retry_attempts = retries;
         }
         
         // 日志配置
-        if let Ok(level) = std::env::var("LOG_LEVEL") {
+        if let Some(level) = env_var("LOG_LEVEL")? {
             config.logging.level = level;
         }
-        if let Ok(file) = std::env::var("LOG_FILE") {
+        if let Some(file) = env_var("LOG_FILE")? {
             config.logging.file = Some(file);
         }
-        if let Ok(max_size) = std::env::var("LOG_MAX_SIZE") {
-            config.logging.max_size = max_size.parse()?;
+        if let Some(max_size) = env_var("LOG_MAX_SIZE")? {
+            config.logging.max_size = max_size;
         }
-        if let Ok(max_files) = std::env::var("LOG_MAX_FILES") {
-            config.logging.max_files = max_files.parse()?;
+        if let Some(max_files) = env_var("LOG_MAX_FILES")? {
+            config.logging.max_files = max_files;
         }
         
         // 功能配置
-        if let Ok(enable_cache) = std::env::var("ENABLE_CACHE") {
-            config.features.enable_cache = enable_cache.parse()?;
+        if let Some(enable_cache) = env_parse("ENABLE_CACHE", parse_bool)? {
+            config.features.enable_cache = enable_cache;
         }
-        if let Ok(enable_metrics) = std::env::var("ENABLE_METRICS") {
-            config.features.enable_metrics = enable_metrics.parse()?;
+        if let Some(enable_metrics) = env_parse("ENABLE_METRICS", parse_bool)? {
+            config.features.enable_metrics = enable_metrics;
         }
-        if let Ok(enable_tracing) = std::env::var("ENABLE_TRACING") {
-            config.features.enable_tracing = enable_tracing.parse()?;
+        if let Some(enable_tracing) = env_parse("ENABLE_TRACING", parse_bool)? {
+            config.features.enable_tracing = enable_tracing;
         }
-        if let Ok(debug_mode) = std::env::var("DEBUG_MODE") {
-            config.features.debug_mode = debug_mode.parse()?;
+        if let Some(debug_mode) = env_parse("DEBUG_MODE", parse_bool)? {
+            config.features.debug_mode = debug_mode;
         }
         
+        // 命令行覆盖项优先于环境变量
+        *config = apply_overrides(&config, &self.cli_overrides.read().await)?;
+        drop(config);
+        
+        self.notify_watchers(&old).await;
         Ok(())
     }
     
-    /// 从文件加载配置
+    /// 从文件加载配置，使用 `APP_PROFILE` 环境变量选择的 profile
     pub async fn load_from_file(&self, path: &str) -> Result<()> {
+        let profile = env_var::<String>(PROFILE_ENV)?.filter(|name| !name.is_empty());
+        self.load_from_file_with_profile(path, profile.as_deref()).await
+    }
+    
+    /// 从文件加载配置并应用指定的 profile，`None` 表示只使用基础配置
+    pub async fn load_from_file_with_profile(&self, path: &str, profile: Option<&str>) -> Result<()> {
         let content = tokio::fs::read_to_string(path).await?;
-        let config: AppConfig = toml::from_str(&content)?;
+        let config = parse_config_with_profile(&content, profile)
+            .map_err(|e| anyhow::anyhow!("配置文件 {} 无效: {}", path, e))?;
+        let config = apply_overrides(&config, &self.cli_overrides.read().await)?;
+        
+        let old = std::mem::replace(&mut *self.config.write().await, config);
+        *self.active_profile.write().await = profile.map(str::to_string);
+        
+        self.notify_watchers(&old).await;
+        Ok(())
+    }
+    
+    /// 当前生效的 profile，只使用基础配置时为 `None`
+    pub async fn get_active_profile(&self) -> Option<String> {
+        self.active_profile.read().await.clone()
+    }
+    
+    /// 应用命令行的 `key.path=value` 覆盖项，值按配置项的类型转换。
+    /// 覆盖项优先级最高，之后从文件或环境变量重新加载时会再次应用
+    pub async fn apply_cli_overrides<I, S>(&self, overrides: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let overrides = overrides
+            .into_iter()
+            .map(|item| parse_override(item.as_ref()))
+            .collect::<Result<Vec<_>>>()?;
+        if overrides.is_empty() {
+            return Ok(());
+        }
         
-        let mut current_config = self.config.write().await;
-        *current_config = config;
+        let old = {
+            let mut config = self.config.write().await;
+            let updated = apply_overrides(&config, &overrides)?;
+            self.cli_overrides.write().await.extend(overrides);
+            std::mem::replace(&mut *config, updated)
+        };
         
-        self.notify_watchers().await;
+        self.notify_watchers(&old).await;
         Ok(())
     }
     
@@ -201,9 +629,14 @@ impl ConfigManager {
     where
         F: FnOnce(&mut AppConfig),
     {
-        let mut config = self.config.write().await;
-        updater(&mut config);
-        self.notify_watchers().await;
+        let old = {
+            let mut config = self.config.write().await;
+            let old = config.clone();
+            updater(&mut config);
+            old
+        };
+        // 写锁必须先释放，通知观察者时还要读取配置
+        self.notify_watchers(&old).await;
         Ok(())
     }
     
@@ -213,13 +646,42 @@ impl ConfigManager {
         watchers.push(watcher);
     }
     
-    /// 通知所有观察者
-    async fn notify_watchers(&self) {
+    /// 订阅服务器配置，只有这一节变化时接收方的 `changed()` 才会返回
+    pub fn watch_server(&self) -> watch::Receiver<ServerConfig> {
+        self.server_tx.subscribe()
+    }
+    
+    /// 订阅数据库配置
+    pub fn watch_database(&self) -> watch::Receiver<DatabaseConfig> {
+        self.database_tx.subscribe()
+    }
+    
+    /// 订阅日志配置
+    pub fn watch_logging(&self) -> watch::Receiver<LoggingConfig> {
+        self.logging_tx.subscribe()
+    }
+    
+    /// 订阅功能开关
+    pub fn watch_features(&self) -> watch::Receiver<FeatureConfig> {
+        self.features_tx.subscribe()
+    }
+    
+    /// 把相对 `old` 的差异通知所有观察者并更新各配置节的 watch 通道，没有变化时不通知
+    async fn notify_watchers(&self, old: &AppConfig) {
         let config = self.config.read().await;
-        let watchers = self.watchers.read().await;
+        let diff = ConfigDiff::between(old, &config);
+        if diff.is_empty() {
+            return;
+        }
         
+        publish_section(&self.server_tx, &config.server);
+        publish_section(&self.database_tx, &config.database);
+        publish_section(&self.logging_tx, &config.logging);
+        publish_section(&self.features_tx, &config.features);
+        
+        let watchers = self.watchers.read().await;
         for watcher in watchers.iter() {
-            watcher.on_config_changed(&config);
+            watcher.on_config_changed(&diff, &config);
         }
     }
     
@@ -232,7 +694,7 @@ impl ConfigManager {
         if config.server.port == 0 {
             errors.push("服务器端口不能为0".to_string());
         }
-        if config.server.timeout == 0 {
+        if config.server.timeout.is_zero() {
             errors.push("服务器超时时间不能为0".to_string());
         }
         if config.server.max_connections == 0 {
@@ -246,7 +708,7 @@ impl ConfigManager {
         if config.database.max_connections == 0 {
             errors.push("数据库最大连接数不能为0".to_string());
         }
-        if config.database.timeout == 0 {
+        if config.database.timeout.is_zero() {
             errors.push("数据库超时时间不能为0".to_string());
         }
         
@@ -255,7 +717,7 @@ impl ConfigManager {
         if !valid_levels.contains(&config.logging.level.as_str()) {
             errors.push(format!("无效的日志级别: {}", config.logging.level));
         }
-        if config.logging.max_size == 0 {
+        if config.logging.max_size.as_u64() == 0 {
             errors.push("日志文件最大大小不能为0".to_string());
         }
         if config.logging.max_files == 0 {
@@ -266,6 +728,17 @@ impl ConfigManager {
     }
 }
 
+/// 配置节的值变化时才发送，没有订阅者时也会更新保存的值
+fn publish_section<T: Clone + PartialEq>(tx: &watch::Sender<T>, value: &T) {
+    tx.send_if_modified(|current| {
+        if current == value {
+            return false;
+        }
+        *current = value.clone();
+        true
+    });
+}
+
 /// 配置热重载器
 pub struct ConfigReloader {
     config_manager: Arc<ConfigManager>,
@@ -281,12 +754,12 @@ impl ConfigReloader {
         }
     }
     
-    /// 开始监控配置文件变化
-    pub async fn start_watching(&self) -> Result<()> {
+    /// 开始监控配置文件变化，返回后台监控任务的句柄，中止句柄即停止监控
+    pub async fn start_watching(&self) -> Result<tokio::task::JoinHandle<()>> {
         let path = self.watch_path.clone();
         let manager = Arc::clone(&self.config_manager);
         
-        tokio::spawn(async move {
+        let handle = tokio::spawn(async move {
             let mut last_modified = std::time::SystemTime::UNIX_EPOCH;
             
             loop {
@@ -295,6 +768,7 @@ impl ConfigReloader {
                         if modified > last_modified {
                             last_modified = modified;
                             
+                            // 观察者会收到和上一份配置之间的差异
                             if let Err(e) = manager.load_from_file(&path).await {
                                 eprintln!("重新加载配置失败: {}", e);
                             } else {
@@ -308,26 +782,30 @@ impl ConfigReloader {
             }
         });
         
-        Ok(())
+        Ok(handle)
     }
 }
 
-/// 配置工具示例
-pub async fn config_utils_example() -> Result<()> {
+/// 配置工具示例，`overrides` 是命令行 `--set` 传入的覆盖项
+pub async fn config_utils_example(overrides: &[String]) -> Result<()> {
     println!("\n=== 配置工具示例 ===");
     
     // 创建配置管理器
     let config_manager = Arc::new(ConfigManager::new());
     
-    // 从环境变量加载配置
+    // 从环境变量加载配置，再应用命令行覆盖项
     config_manager.load_from_env().await?;
+    config_manager.apply_cli_overrides(overrides).await?;
+    if !overrides.is_empty() {
+        println!("命令行覆盖项: {}", overrides.join(", "));
+    }
     
     // 获取配置
     let config = config_manager.get_config().await;
     println!("当前配置:");
-    println!("  服务器: {}:{}", config.server.host, config.server.port);
-    println!("  数据库: {}", config.database.url);
-    println!("  日志级别: {}", config.logging.level);
+    println!("  服务器: {}:{}（超时 {}）", config.server.host, config.server.port, config.server.timeout);
+    println!("  数据库: {}（超时 {:?}）", config.database.url, config.database.timeout.as_duration());
+    println!("  日志级别: {}（单个文件最大 {}）", config.logging.level, config.logging.max_size);
     println!("  缓存启用: {}", config.features.enable_cache);
     
     // 验证配置
@@ -341,11 +819,39 @@ pub async fn config_utils_example() -> Result<()> {
         }
     }
     
+    // 观察者只处理关心的那一节配置
+    struct ServerWatcher;
+    
+    impl ConfigWatcher for ServerWatcher {
+        fn on_config_changed(&self, diff: &ConfigDiff, config: &AppConfig) {
+            if diff.changed("server") {
+                println!("服务器配置变化，按 {}:{} 重新绑定:\n{}", config.server.host, config.server.port, diff);
+            }
+            if let Some(change) = diff.get("server.port") {
+                println!("端口从 {:?} 变为 {:?}", change.old, change.new);
+            }
+        }
+    }
+    config_manager.add_watcher(Box::new(ServerWatcher)).await;
+    
+    // 异步组件也可以只订阅一节配置，等待它变化
+    let mut server_rx = config_manager.watch_server();
+    let logging_rx = config_manager.watch_logging();
+    let listener = tokio::spawn(async move {
+        server_rx.changed().await.ok()?;
+        let server = server_rx.borrow_and_update().clone();
+        Some(server.port)
+    });
+    
     // 更新配置
     config_manager.update_config(|config| {
         config.server.port = 9090;
         config.features.debug_mode = true;
     }).await?;
+    if let Ok(Ok(Some(port))) = tokio::time::timeout(Duration::from_secs(1), listener).await {
+        println!("watch_server 收到新端口: {}", port);
+    }
+    println!("日志配置是否变化: {}", logging_rx.has_changed()?);
     
     let updated_config = config_manager.get_config().await;
     println!("更新后的端口: {}", updated_config.server.port);
@@ -355,6 +861,52 @@ pub async fn config_utils_example() -> Result<()> {
     config_manager.save_to_file("config.toml").await?;
     println!("配置已保存到 config.toml");
     
+    // profile 覆盖基础配置
+    let path = std::env::temp_dir().join(format!("august-config-profiles-{}.toml", std::process::id()));
+    let mut content = toml::to_string(&AppConfig::default())?;
+    content.push_str(concat!(
+        "\n[profile.dev.features]\ndebug_mode = true\n",
+        "\n[profile.prod.server]\nport = 443\ntimeout = \"1m\"\n",
+        "\n[profile.prod.database]\nurl = \"postgres://db.internal/app\"\n",
+    ));
+    tokio::fs::write(&path, &content).await?;
+    let path_str = path.to_string_lossy();
+    
+    let profiles = ConfigManager::new();
+    profiles.load_from_file_with_profile(&path_str, Some("dev")).await?;
+    println!("profile {:?}: 调试模式 {}", profiles.get_active_profile().await, profiles.get_config().await.features.debug_mode);
+    if let Err(e) = profiles.load_from_file_with_profile(&path_str, Some("prod")).await {
+        println!("加载 prod 失败: {}", e);
+    }
+    content.push_str("\n[profile.prod.logging]\nfile = \"/var/log/app.log\"\n");
+    tokio::fs::write(&path, &content).await?;
+    profiles.load_from_file_with_profile(&path_str, Some("prod")).await?;
+    let config = profiles.get_config().await;
+    println!("profile {:?}: 端口 {}，超时 {}，数据库 {}",
+        profiles.get_active_profile().await, config.server.port, config.server.timeout, config.database.url);
+    let _ = tokio::fs::remove_file(&path).await;
+    
+    // 热重载：配置文件修改后自动重新加载，profile 由 APP_PROFILE 环境变量选择
+    let path = std::env::temp_dir().join(format!("august-config-reload-{}.toml", std::process::id()));
+    let path_str = path.to_string_lossy().into_owned();
+    config_manager.save_to_file(&path_str).await?;
+    let mut database_rx = config_manager.watch_database();
+    let features_rx = config_manager.watch_features();
+    let reloader = ConfigReloader::new(Arc::clone(&config_manager), path_str.clone());
+    let watching = reloader.start_watching().await?;
+    println!("监控配置文件 {}（{}={:?}）", path_str, PROFILE_ENV, std::env::var(PROFILE_ENV).ok());
+    
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let mut edited = config_manager.get_config().await;
+    edited.database.max_connections = 50;
+    tokio::fs::write(&path, toml::to_string_pretty(&edited)?).await?;
+    if let Ok(Ok(())) = tokio::time::timeout(Duration::from_secs(3), database_rx.changed()).await {
+        println!("热重载后数据库最大连接数: {}", database_rx.borrow_and_update().max_connections);
+    }
+    println!("功能开关是否变化: {}", features_rx.has_changed()?);
+    watching.abort();
+    let _ = tokio::fs::remove_file(&path).await;
+    
     Ok(())
 }
 
@@ -377,6 +929,225 @@ mod tests {
         assert_eq!(config.server.port, 8080);
     }
     
+    #[test]
+    fn test_human_duration_and_byte_size() {
+        assert_eq!("500ms".parse::<HumanDuration>().unwrap(), HumanDuration(Duration::from_millis(500)));
+        assert_eq!("10MB".parse::<ByteSize>().unwrap(), ByteSize(10 * 1024 * 1024));
+        for text in ["500ms", "1500ms", "45s", "2m", "3h", "1d", "0s"] {
+            assert_eq!(text.parse::<HumanDuration>().unwrap().to_string(), text);
+        }
+        for text in ["512B", "64KB", "10MB", "3GB"] {
+            assert_eq!(text.parse::<ByteSize>().unwrap().to_string(), text);
+        }
+        assert!("30 parsecs".parse::<HumanDuration>().is_err());
+    }
+    
+    #[test]
+    fn test_parse_config_human_values() {
+        let mut text = toml::to_string(&AppConfig::default()).unwrap();
+        assert!(text.contains("timeout = \"30s\""));
+        assert!(text.contains("max_size = \"10MB\""));
+        
+        text = text.replace("timeout = \"30s\"", "timeout = \"1m\"").replace("max_size = \"10MB\"", "max_size = 4096");
+        let config = parse_config(&text).unwrap();
+        assert_eq!(config.server.timeout.as_duration(), Duration::from_secs(60));
+        assert_eq!(config.database.timeout.as_duration(), Duration::from_secs(5));
+        assert_eq!(config.logging.max_size.as_u64(), 4096);
+        
+        // 旧格式的整数秒依然可以读取
+        let old = text.replace("timeout = \"5s\"", "timeout = 7");
+        assert_eq!(parse_config(&old).unwrap().database.timeout.as_duration(), Duration::from_secs(7));
+    }
+    
+    #[test]
+    fn test_parse_error_names_key() {
+        let text = toml::to_string(&AppConfig::default()).unwrap().replace("timeout = \"5s\"", "timeout = \"5 fortnights\"");
+        let line = text.lines().position(|line| line.contains("fortnights")).unwrap() + 1;
+        let message = parse_config(&text).unwrap_err().to_string();
+        assert!(message.starts_with(&format!("第 {} 行的 database.timeout 无效", line)), "{}", message);
+        assert!(message.contains("5 fortnights"), "{}", message);
+        
+        std::env::set_var("AUGUST_TEST_CONFIG_MAX_SIZE", "lots");
+        let message = env_var::<ByteSize>("AUGUST_TEST_CONFIG_MAX_SIZE").unwrap_err().to_string();
+        assert!(message.contains("AUGUST_TEST_CONFIG_MAX_SIZE"), "{}", message);
+    }
+    
+    #[test]
+    fn test_profile_overrides_base() {
+        let mut text = toml::to_string(&AppConfig::default()).unwrap();
+        text.push_str("\n[profile.dev.features]\ndebug_mode = true\n\n[profile.staging.server]\nport = 9000\n");
+
+        let base = parse_config_with_profile(&text, None).unwrap();
+        assert!(!base.features.debug_mode);
+
+        let dev = parse_config_with_profile(&text, Some("dev")).unwrap();
+        assert!(dev.features.debug_mode);
+        assert!(dev.features.enable_cache);
+        assert_eq!(dev.server.port, 8080);
+
+        let staging = parse_config_with_profile(&text, Some("staging")).unwrap();
+        assert_eq!(staging.server.port, 9000);
+        assert_eq!(staging.server.host, "localhost");
+
+        let message = parse_config_with_profile(&text, Some("qa")).unwrap_err().to_string();
+        assert!(message.contains("未定义的 profile: qa") && message.contains("dev, staging"), "{}", message);
+    }
+
+    #[test]
+    fn test_prod_profile_requires_keys() {
+        // 默认配置没有 logging.file，prod 必须自己写明
+        let mut text = toml::to_string(&AppConfig::default()).unwrap();
+        text.push_str("\n[profile.prod.server]\nport = 443\n");
+        let message = parse_config_with_profile(&text, Some("prod")).unwrap_err().to_string();
+        assert!(message.contains("logging.file") && !message.contains("database.url"), "{}", message);
+
+        text.push_str("\n[profile.prod.logging]\nfile = \"/var/log/app.log\"\nmax_size = \"lots\"\n");
+        let message = parse_config_with_profile(&text, Some("prod")).unwrap_err().to_string();
+        assert!(message.contains("应用 profile prod 后 logging.max_size 无效"), "{}", message);
+
+        let text = text.replace("max_size = \"lots\"", "max_size = \"50MB\"");
+        let config = parse_config_with_profile(&text, Some("prod")).unwrap();
+        assert_eq!(config.server.port, 443);
+        assert_eq!(config.logging.file.as_deref(), Some("/var/log/app.log"));
+        assert_eq!(config.logging.max_size.as_u64(), 50 * 1024 * 1024);
+    }
+
+    #[tokio::test]
+    async fn test_active_profile() {
+        let mut text = toml::to_string(&AppConfig::default()).unwrap();
+        text.push_str("\n[profile.dev.server]\nport = 3000\n");
+        let path = std::env::temp_dir().join(format!("august-config-test-{}.toml", std::process::id()));
+        tokio::fs::write(&path, &text).await.unwrap();
+
+        let manager = ConfigManager::new();
+        assert_eq!(manager.get_active_profile().await, None);
+        manager.load_from_file_with_profile(&path.to_string_lossy(), Some("dev")).await.unwrap();
+        assert_eq!(manager.get_active_profile().await.as_deref(), Some("dev"));
+        assert_eq!(manager.get_config().await.server.port, 3000);
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[test]
+    fn test_apply_overrides_coerces_types() {
+        let overrides = ["server.port=9999", "features.debug_mode=yes", "server.timeout=2m", "logging.file=/tmp/app.log"]
+            .iter()
+            .map(|item| parse_override(item).unwrap())
+            .collect::<Vec<_>>();
+        let config = apply_overrides(&AppConfig::default(), &overrides).unwrap();
+        assert_eq!(config.server.port, 9999);
+        assert!(config.features.debug_mode);
+        assert_eq!(config.server.timeout.as_duration(), Duration::from_secs(120));
+        assert_eq!(config.logging.file.as_deref(), Some("/tmp/app.log"));
+        assert_eq!(config.server.host, "localhost");
+    }
+    
+    #[test]
+    fn test_invalid_overrides() {
+        assert!(parse_override("server.port").is_err());
+        assert!(parse_override("server..port=1").is_err());
+        assert_eq!(parse_override(" server.host = a=b ").unwrap(), ("server.host".to_string(), "a=b".to_string()));
+        
+        let config = AppConfig::default();
+        for (item, expected) in [
+            ("server.port=abc", "不是整数"),
+            ("server.port=70000", "server.port=70000 无效"),
+            ("server.prot=1", "未知的配置项"),
+            ("nothing.here=1", "未知的配置项"),
+            ("server=1", "配置表"),
+            ("features.debug_mode=maybe", "features.debug_mode=maybe 无效"),
+        ] {
+            let message = apply_overrides(&config, &[parse_override(item).unwrap()]).unwrap_err().to_string();
+            assert!(message.contains(expected), "{}: {}", item, message);
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_cli_overrides_take_precedence() {
+        let mut text = toml::to_string(&AppConfig::default()).unwrap();
+        text.push_str("\n[profile.dev.server]\nport = 3000\nhost = \"dev.local\"\n");
+        let path = std::env::temp_dir().join(format!("august-config-overrides-{}.toml", std::process::id()));
+        tokio::fs::write(&path, &text).await.unwrap();
+        
+        let manager = ConfigManager::new();
+        manager.apply_cli_overrides(["server.port=9999"]).await.unwrap();
+        assert!(manager.apply_cli_overrides(["server.port=oops"]).await.is_err());
+        manager.load_from_file_with_profile(&path.to_string_lossy(), Some("dev")).await.unwrap();
+        let config = manager.get_config().await;
+        assert_eq!(config.server.port, 9999);
+        assert_eq!(config.server.host, "dev.local");
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_update_config_notifies_watchers() {
+        struct PortWatcher(Arc<std::sync::Mutex<Vec<u16>>>);
+        
+        impl ConfigWatcher for PortWatcher {
+            fn on_config_changed(&self, diff: &ConfigDiff, config: &AppConfig) {
+                if diff.changed("server.port") {
+                    self.0.lock().unwrap().push(config.server.port);
+                }
+            }
+        }
+        
+        let manager = ConfigManager::new();
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        manager.add_watcher(Box::new(PortWatcher(Arc::clone(&seen)))).await;
+        manager.update_config(|config| config.server.port = 9090).await.unwrap();
+        manager.update_config(|config| config.features.debug_mode = true).await.unwrap();
+        manager.update_config(|config| config.server.port = 9090).await.unwrap();
+        manager.apply_cli_overrides(["server.port=7070"]).await.unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec![9090, 7070]);
+    }
+    
+    #[tokio::test]
+    async fn test_section_watch_channels() {
+        let manager = ConfigManager::new();
+        let mut server_rx = manager.watch_server();
+        let mut logging_rx = manager.watch_logging();
+        assert_eq!(server_rx.borrow().port, 8080);
+        
+        let waiter = tokio::spawn(async move {
+            logging_rx.changed().await.unwrap();
+            logging_rx.borrow_and_update().level.clone()
+        });
+        
+        manager.update_config(|config| config.server.port = 9090).await.unwrap();
+        assert!(server_rx.has_changed().unwrap());
+        assert_eq!(server_rx.borrow_and_update().port, 9090);
+        
+        // 只改日志配置时服务器通道没有变化
+        manager.update_config(|config| config.logging.level = "debug".to_string()).await.unwrap();
+        assert!(!server_rx.has_changed().unwrap());
+        assert_eq!(waiter.await.unwrap(), "debug");
+        
+        // 之后订阅的接收方拿到的是当前值
+        assert_eq!(manager.watch_logging().borrow().level, "debug");
+    }
+    
+    #[test]
+    fn test_config_diff() {
+        let old = AppConfig::default();
+        let mut new = old.clone();
+        assert!(ConfigDiff::between(&old, &new).is_empty());
+        
+        new.server.port = 9090;
+        new.logging.file = Some("app.log".to_string());
+        new.logging.max_size = ByteSize(1024);
+        let diff = ConfigDiff::between(&old, &new);
+        let paths: Vec<&str> = diff.changes.iter().map(|change| change.path.as_str()).collect();
+        assert_eq!(paths, vec!["logging.file", "logging.max_size", "server.port"]);
+        
+        assert!(diff.changed("logging") && diff.changed("server.port"));
+        assert!(!diff.changed("server.host") && !diff.changed("database") && !diff.changed("log"));
+        assert_eq!(diff.get("logging.file").unwrap().old, None);
+        assert_eq!(diff.get("server.port").unwrap().new, Some(toml::Value::Integer(9090)));
+        assert_eq!(
+            diff.to_string(),
+            "logging.file: (无) -> \"app.log\"\nlogging.max_size: \"10MB\" -> \"1KB\"\nserver.port: 8080 -> 9090"
+        );
+    }
+    
     #[tokio::test]
     async fn test_config_validation() {
         let manager = ConfigManager::new();
@@ -1,18 +1,31 @@
 //! 错误处理工具模块
 //! 
 //! 提供统一的错误处理功能：
-//! - 自定义错误类型
-//! - 错误转换
+//! - 自定义错误类型，支持附加上下文和保留原始错误
+//! - 错误转换（按类型识别底层错误）
 //! - 错误恢复
+//! - 多个操作共享的重试预算
+//! - 把异步任务中的 panic 转换为错误
 //! - 错误日志
 
 use anyhow::Result;
+use common::TokenBucket;
+use futures::FutureExt;
+use std::any::Any;
+use std::error::Error as StdError;
 use std::fmt;
-use std::time::Duration;
+use std::future::Future;
+use std::io;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::time::timeout;
 
+use crate::core::http_client::FetchError;
+
 /// 应用错误类型
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum AppError {
     #[error("网络错误: {0}")]
     Network(String),
@@ -34,17 +47,135 @@ pub enum AppError {
     
     #[error("未知错误: {0}")]
     Unknown(String),
+    
+    #[error("任务 panic: {0}")]
+    Panic(String),
+    
+    /// 附加了上下文说明的错误，`source` 保留原始错误
+    #[error("{context}")]
+    Context {
+        context: String,
+        #[source]
+        source: Arc<dyn StdError + Send + Sync>,
+    },
 }
 
-/// 错误恢复策略
-#[derive(Debug, Clone)]
-pub enum RetryStrategy {
-    /// 固定间隔重试
-    Fixed(Duration),
-    /// 指数退避重试
-    Exponential(Duration, f64),
-    /// 线性退避重试
-    Linear(Duration, Duration),
+impl AppError {
+    /// 在错误外面加一层上下文说明，原错误作为 `source` 保留
+    pub fn with_context(self, context: impl Into<String>) -> Self {
+        AppError::Context { context: context.into(), source: Arc::new(self) }
+    }
+    
+    /// 给任意错误加上下文
+    pub fn wrap<E: StdError + Send + Sync + 'static>(source: E, context: impl Into<String>) -> Self {
+        AppError::Context { context: context.into(), source: Arc::new(source) }
+    }
+    
+    /// 去掉上下文后的错误：沿 `source` 找到第一个不是 `Context` 的 `AppError`，
+    /// 上下文包着的不是 `AppError` 时返回最内层的 `Context`
+    pub fn kind(&self) -> &AppError {
+        let mut current = self;
+        while let AppError::Context { source, .. } = current {
+            match source.downcast_ref::<AppError>() {
+                Some(inner) => current = inner,
+                None => break,
+            }
+        }
+        current
+    }
+    
+    /// 错误链最底层的错误
+    pub fn root_cause(&self) -> &(dyn StdError + 'static) {
+        let mut current: &(dyn StdError + 'static) = self;
+        while let Some(source) = current.source() {
+            current = source;
+        }
+        current
+    }
+    
+    /// 用于打印整条错误链
+    pub fn chain(&self) -> ErrorChain<'_> {
+        ErrorChain(self)
+    }
+}
+
+/// 打印整条错误链：默认每个原因一行，`{:#}` 时用冒号连成一行
+pub struct ErrorChain<'a>(pub &'a (dyn StdError + 'static));
+
+impl fmt::Display for ErrorChain<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)?;
+        let mut source = self.0.source();
+        while let Some(cause) = source {
+            if f.alternate() {
+                write!(f, ": {}", cause)?;
+            } else {
+                write!(f, "\n  原因: {}", cause)?;
+            }
+            source = cause.source();
+        }
+        Ok(())
+    }
+}
+
+/// 错误恢复策略，即 common 中的退避策略
+pub use common::Backoff as RetryStrategy;
+
+/// 重试预算：多个操作共享的令牌桶，每次重试（不含第一次尝试）消耗一个令牌
+///
+/// 下游大面积故障时，每个失败的请求都重试会把负载放大好几倍；
+/// 预算耗尽后失败直接返回，不再重试，等令牌按时间窗口补充后恢复。
+#[derive(Debug)]
+pub struct RetryBudget {
+    bucket: Mutex<TokenBucket>,
+    granted: AtomicU64,
+    rejected: AtomicU64,
+}
+
+/// 重试预算的使用情况
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryBudgetStats {
+    pub remaining: u32,
+    pub granted: u64,
+    pub rejected: u64,
+}
+
+impl RetryBudget {
+    /// 每个 `window` 内最多允许 `max_retries` 次重试
+    pub fn new(max_retries: u32, window: Duration) -> Self {
+        let refill_per_sec = f64::from(max_retries) / window.as_secs_f64().max(f64::EPSILON);
+        Self {
+            bucket: Mutex::new(TokenBucket::new(max_retries, refill_per_sec)),
+            granted: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+    
+    /// 申请一次重试，预算不足时返回 false 并计入被拒绝的次数
+    pub fn try_acquire(&self) -> bool {
+        let granted = self.bucket.lock().unwrap().try_acquire().is_ok();
+        let counter = if granted { &self.granted } else { &self.rejected };
+        counter.fetch_add(1, Ordering::Relaxed);
+        granted
+    }
+    
+    /// 当前剩余的重试次数
+    pub fn remaining(&self) -> u32 {
+        self.bucket.lock().unwrap().available_at(Instant::now())
+    }
+    
+    /// 被拒绝的重试次数
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+    
+    pub fn stats(&self) -> RetryBudgetStats {
+        RetryBudgetStats {
+            remaining: self.remaining(),
+            granted: self.granted.load(Ordering::Relaxed),
+            rejected: self.rejected(),
+        }
+    }
 }
 
 /// 重试配置
@@ -53,6 +184,8 @@ pub struct RetryConfig {
     pub max_attempts: u32,
     pub strategy: RetryStrategy,
     pub timeout: Option<Duration>,
+    /// 共享的重试预算，`None` 表示不限制
+    pub budget: Option<Arc<RetryBudget>>,
 }
 
 impl Default for RetryConfig {
@@ -61,6 +194,7 @@ impl Default for RetryConfig {
             max_attempts: 3,
             strategy: RetryStrategy::Fixed(Duration::from_millis(100)),
             timeout: Some(Duration::from_secs(30)),
+            budget: None,
         }
     }
 }
@@ -77,6 +211,21 @@ impl ErrorHandler {
     where
         F: Fn() -> Fut,
         Fut: std::future::Future<Output = Result<T>>,
+    {
+        Self::with_retry_if(operation, config, |_| true).await
+    }
+    
+    /// 带重试的异步操作，只有 `should_retry` 返回 true 的错误才会重试；
+    /// 配置了重试预算时，每次重试前先申请预算，预算耗尽则直接返回错误
+    pub async fn with_retry_if<F, Fut, T, P>(
+        operation: F,
+        config: RetryConfig,
+        should_retry: P,
+    ) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+        P: Fn(&anyhow::Error) -> bool,
     {
         let mut last_error = None;
         
@@ -90,6 +239,14 @@ impl ErrorHandler {
             match result {
                 Ok(value) => return Ok(value),
                 Err(e) => {
+                    if !should_retry(&e) {
+                        return Err(e);
+                    }
+                    if attempt < config.max_attempts
+                        && config.budget.as_ref().is_some_and(|budget| !budget.try_acquire())
+                    {
+                        return Err(e);
+                    }
                     last_error = Some(e);
                     
                     if attempt < config.max_attempts {
@@ -105,20 +262,21 @@ impl ErrorHandler {
     
     /// 计算重试延迟
     fn calculate_delay(strategy: &RetryStrategy, attempt: u32) -> Duration {
-        match strategy {
-            RetryStrategy::Fixed(delay) => *delay,
-            RetryStrategy::Exponential(base_delay, multiplier) => {
-                let delay_ms = base_delay.as_millis() as f64 * multiplier.powi(attempt as i32 - 1);
-                Duration::from_millis(delay_ms as u64)
-            }
-            RetryStrategy::Linear(base_delay, increment) => {
-                *base_delay + *increment * (attempt - 1)
-            }
-        }
+        strategy.delay(attempt)
     }
     
-    /// 错误分类
+    /// 错误分类：沿错误链按类型识别（`AppError`、超时、reqwest、IO、配置解析等），
+    /// 都识别不了时再按错误信息中的关键字判断
     pub fn categorize_error(error: &anyhow::Error) -> AppError {
+        for cause in error.chain() {
+            if let Some(app_error) = cause.downcast_ref::<AppError>() {
+                return app_error.clone();
+            }
+            if let Some(categorize) = Self::categorize_typed(cause) {
+                return categorize(format!("{:#}", error));
+            }
+        }
+        
         let error_str = error.to_string().to_lowercase();
         
         if error_str.contains("network") || error_str.contains("connection") {
@@ -136,6 +294,39 @@ impl ErrorHandler {
         }
     }
     
+    /// 按具体类型判断错误类别，返回对应的 `AppError` 构造函数
+    fn categorize_typed(cause: &(dyn StdError + 'static)) -> Option<fn(String) -> AppError> {
+        if cause.is::<tokio::time::error::Elapsed>() {
+            return Some(AppError::Timeout);
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return Some(if e.is_timeout() { AppError::Timeout } else { AppError::Network });
+        }
+        if let Some(e) = cause.downcast_ref::<FetchError>() {
+            return match e {
+                FetchError::Timeout { .. } => Some(AppError::Timeout),
+                FetchError::Network { .. } | FetchError::Status { .. } => Some(AppError::Network),
+                _ => None,
+            };
+        }
+        if let Some(e) = cause.downcast_ref::<io::Error>() {
+            return Some(match e.kind() {
+                io::ErrorKind::TimedOut => AppError::Timeout,
+                io::ErrorKind::ConnectionRefused
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::NotConnected
+                | io::ErrorKind::AddrNotAvailable
+                | io::ErrorKind::BrokenPipe => AppError::Network,
+                _ => AppError::FileSystem,
+            });
+        }
+        if cause.is::<toml::de::Error>() || cause.is::<common::Error>() {
+            return Some(AppError::Config);
+        }
+        None
+    }
+    
     /// 错误恢复
     pub async fn recover_from_error<F, Fut, T>(
         error: &AppError,
@@ -145,7 +336,8 @@ impl ErrorHandler {
         F: FnOnce() -> Fut,
         Fut: std::future::Future<Output = Result<T>>,
     {
-        match error {
+        // 按包装在上下文里的底层错误判断，加了上下文的网络错误同样可以恢复
+        match error.kind() {
             AppError::Network(_) | AppError::Timeout(_) => {
                 // 网络错误可以尝试恢复
                 recovery_fn().await
@@ -162,13 +354,40 @@ impl ErrorHandler {
     }
 }
 
+/// 取出 panic 携带的信息，`panic!` 的参数是 `&str` 或 `String`，其他类型无法打印
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "未知的 panic 信息".to_string()
+    }
+}
+
+/// 运行 future，其中的 panic 转换为 `AppError::Panic` 并记录错误日志
+///
+/// 在 `tokio::spawn` 的任务里使用，panic 就会作为普通错误返回，
+/// 可以计入错误统计，而不是只在 `JoinHandle` 上得到一个 `JoinError`。
+pub async fn catch_panic_async<F: Future>(future: F) -> std::result::Result<F::Output, AppError> {
+    match AssertUnwindSafe(future).catch_unwind().await {
+        Ok(output) => Ok(output),
+        Err(payload) => {
+            let error = AppError::Panic(panic_message(payload.as_ref()));
+            ErrorLogger::log_error(&anyhow::Error::new(error.clone()), "catch_panic_async");
+            Err(error)
+        }
+    }
+}
+
 /// 错误日志记录器
 pub struct ErrorLogger;
 
 impl ErrorLogger {
     /// 记录错误
     pub fn log_error(error: &anyhow::Error, context: &str) {
-        eprintln!("[ERROR] {} - {}", context, error);
+        let error: &(dyn StdError + 'static) = error.as_ref();
+        eprintln!("[ERROR] {} - {}", context, ErrorChain(error));
         
         // 这里可以添加更复杂的日志记录逻辑
         // 比如写入文件、发送到日志服务等
@@ -195,22 +414,24 @@ pub struct ErrorStats {
     pub timeout_errors: u64,
     pub config_errors: u64,
     pub business_errors: u64,
+    pub panic_errors: u64,
     pub unknown_errors: u64,
 }
 
 impl ErrorStats {
-    /// 记录错误
+    /// 记录错误，带上下文的错误按内层错误的类别统计
     pub fn record_error(&mut self, error: &AppError) {
         self.total_errors += 1;
         
-        match error {
+        match error.kind() {
             AppError::Network(_) => self.network_errors += 1,
             AppError::Database(_) => self.database_errors += 1,
             AppError::FileSystem(_) => self.file_system_errors += 1,
             AppError::Timeout(_) => self.timeout_errors += 1,
             AppError::Config(_) => self.config_errors += 1,
             AppError::Business(_) => self.business_errors += 1,
-            AppError::Unknown(_) => self.unknown_errors += 1,
+            AppError::Panic(_) => self.panic_errors += 1,
+            AppError::Unknown(_) | AppError::Context { .. } => self.unknown_errors += 1,
         }
     }
     
@@ -233,6 +454,7 @@ impl ErrorStats {
         println!("  超时错误: {}", self.timeout_errors);
         println!("  配置错误: {}", self.config_errors);
         println!("  业务错误: {}", self.business_errors);
+        println!("  panic: {}", self.panic_errors);
         println!("  未知错误: {}", self.unknown_errors);
     }
 }
@@ -258,8 +480,24 @@ pub async fn error_handling_example() -> Result<()> {
         ErrorLogger::log_error(&anyhow::anyhow!(error), "错误处理示例");
     }
     
+    // 附加上下文的错误保留原始错误，统计时按内层类别计数
+    let io_error = io::Error::new(io::ErrorKind::NotFound, "config.toml 不存在");
+    let error = AppError::wrap(io_error, "读取配置文件失败").with_context("启动服务失败");
+    println!("错误链:\n{}", error.chain());
+    println!("根本原因: {}", error.root_cause());
+    stats.record_error(&ErrorHandler::categorize_error(&anyhow::Error::new(error)));
+    
     // 打印统计信息
     stats.print_stats();
+    println!("错误率（共 10 次操作）: {:.0}%", stats.get_error_rate(10) * 100.0);
+    
+    // 网络和超时错误可以走恢复路径，业务错误直接返回
+    for error in [AppError::Timeout("主节点无响应".to_string()), AppError::Business("余额不足".to_string())] {
+        match ErrorHandler::recover_from_error(&error, || async { Ok("已切换到备用节点") }).await {
+            Ok(value) => ErrorLogger::log_info(value, "错误恢复"),
+            Err(e) => ErrorLogger::log_warning(&e.to_string(), "错误恢复"),
+        }
+    }
     
     // 测试重试机制
     println!("\n测试重试机制:");
@@ -267,6 +505,7 @@ pub async fn error_handling_example() -> Result<()> {
         max_attempts: 3,
         strategy: RetryStrategy::Exponential(Duration::from_millis(100), 2.0),
         timeout: Some(Duration::from_secs(1)),
+        budget: None,
     };
     
     let result = ErrorHandler::with_retry(
@@ -287,6 +526,36 @@ pub async fn error_handling_example() -> Result<()> {
         Err(e) => println!("重试失败: {}", e),
     }
     
+    // 下游全部失败时，共享的重试预算限制了额外请求的数量
+    println!("\n测试重试预算:");
+    let budget = Arc::new(RetryBudget::new(5, Duration::from_secs(10)));
+    let calls = Arc::new(AtomicU64::new(0));
+    let config = RetryConfig {
+        max_attempts: 4,
+        strategy: RetryStrategy::Fixed(Duration::from_millis(1)),
+        timeout: None,
+        budget: Some(Arc::clone(&budget)),
+    };
+    let operations = (0..10).map(|_| {
+        let calls = Arc::clone(&calls);
+        ErrorHandler::with_retry(
+            move || {
+                let calls = Arc::clone(&calls);
+                async move {
+                    calls.fetch_add(1, Ordering::Relaxed);
+                    Err::<(), _>(anyhow::anyhow!(AppError::Network("下游不可用".to_string())))
+                }
+            },
+            config.clone(),
+        )
+    });
+    futures::future::join_all(operations).await;
+    let budget_stats = budget.stats();
+    println!(
+        "10 个操作共发出 {} 次请求（无预算时为 40 次），允许重试 {} 次，拒绝 {} 次，剩余预算 {}",
+        calls.load(Ordering::Relaxed), budget_stats.granted, budget_stats.rejected, budget_stats.remaining
+    );
+    
     Ok(())
 }
 
@@ -299,10 +568,67 @@ mod tests {
         let error = anyhow::anyhow!("Network connection failed");
         let app_error = ErrorHandler::categorize_error(&error);
         
-        match app_error {
-            AppError::Network(_) => assert!(true),
-            _ => assert!(false, "应该被分类为网络错误"),
-        }
+        assert!(matches!(app_error, AppError::Network(_)), "应该被分类为网络错误");
+    }
+    
+    #[test]
+    fn test_context_chain_and_root_cause() {
+        let error = AppError::Database("连接池耗尽".to_string())
+            .with_context("查询用户 42 失败")
+            .with_context("处理请求失败");
+        
+        assert!(matches!(error.kind(), AppError::Database(_)));
+        assert_eq!(error.root_cause().to_string(), "数据库错误: 连接池耗尽");
+        assert_eq!(
+            error.chain().to_string(),
+            "处理请求失败\n  原因: 查询用户 42 失败\n  原因: 数据库错误: 连接池耗尽"
+        );
+        assert_eq!(format!("{:#}", error.chain()), "处理请求失败: 查询用户 42 失败: 数据库错误: 连接池耗尽");
+        
+        let mut stats = ErrorStats::default();
+        stats.record_error(&error);
+        assert_eq!(stats.database_errors, 1);
+        
+        let wrapped = AppError::wrap(io::Error::other("磁盘已满"), "写入失败");
+        assert!(matches!(wrapped.kind(), AppError::Context { .. }));
+        assert_eq!(wrapped.root_cause().to_string(), "磁盘已满");
+    }
+    
+    #[tokio::test]
+    async fn test_recover_from_wrapped_error() {
+        let error = AppError::Timeout("主节点无响应".to_string()).with_context("查询用户失败");
+        let recovered = ErrorHandler::recover_from_error(&error, || async { Ok("备用节点") }).await;
+        assert_eq!(recovered.unwrap(), "备用节点");
+        
+        let error = AppError::Business("余额不足".to_string()).with_context("扣款失败");
+        assert!(ErrorHandler::recover_from_error(&error, || async { Ok(()) }).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_categorize_by_type() {
+        let timeout_error = tokio::time::timeout(Duration::from_millis(1), std::future::pending::<()>())
+            .await
+            .unwrap_err();
+        let error = anyhow::Error::new(timeout_error).context("等待响应");
+        assert!(matches!(ErrorHandler::categorize_error(&error), AppError::Timeout(_)));
+        
+        // 信息里的 "file" 关键字不影响按类型判断
+        let refused = anyhow::Error::new(io::Error::new(io::ErrorKind::ConnectionRefused, "file server"));
+        assert!(matches!(ErrorHandler::categorize_error(&refused), AppError::Network(_)));
+        
+        let toml_error = toml::from_str::<toml::Value>("= 1").unwrap_err();
+        let error = anyhow::Error::new(toml_error).context("加载设置");
+        assert!(matches!(ErrorHandler::categorize_error(&error), AppError::Config(_)));
+        
+        let fetch = FetchError::Status { url: "http://127.0.0.1/".to_string(), status: 503 };
+        assert!(matches!(ErrorHandler::categorize_error(&anyhow::Error::new(fetch)), AppError::Network(_)));
+        
+        // 错误链中已有的 AppError 原样保留
+        let inner = AppError::Business("余额不足".to_string()).with_context("扣款失败");
+        let error = anyhow::Error::new(inner).context("下单失败");
+        let categorized = ErrorHandler::categorize_error(&error);
+        assert!(matches!(categorized.kind(), AppError::Business(_)));
+        assert_eq!(categorized.to_string(), "扣款失败");
     }
     
     #[tokio::test]
@@ -312,6 +638,90 @@ mod tests {
         assert!(config.timeout.is_some());
     }
     
+    #[tokio::test]
+    async fn test_with_retry_if_stops_on_permanent_error() {
+        let attempts = std::sync::atomic::AtomicU32::new(0);
+        let counter = &attempts;
+        let config = RetryConfig {
+            max_attempts: 5,
+            strategy: RetryStrategy::Fixed(Duration::from_millis(1)),
+            timeout: None,
+            budget: None,
+        };
+        
+        let result: Result<()> = ErrorHandler::with_retry_if(
+            move || async move {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Err(anyhow::anyhow!(AppError::Business("参数错误".to_string())))
+            },
+            config,
+            |e| !matches!(e.downcast_ref::<AppError>(), Some(AppError::Business(_))),
+        ).await;
+        
+        assert!(result.is_err());
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_retry_budget_shared_across_operations() {
+        let budget = Arc::new(RetryBudget::new(3, Duration::from_secs(60)));
+        let attempts = AtomicU64::new(0);
+        let counter = &attempts;
+        let config = RetryConfig {
+            max_attempts: 3,
+            strategy: RetryStrategy::Fixed(Duration::from_millis(1)),
+            timeout: None,
+            budget: Some(Arc::clone(&budget)),
+        };
+        
+        for _ in 0..3 {
+            let result: Result<()> = ErrorHandler::with_retry(
+                move || async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Err(anyhow::anyhow!("失败"))
+                },
+                config.clone(),
+            ).await;
+            assert!(result.is_err());
+        }
+        
+        // 第一个操作用掉 2 次重试，第二个用掉 1 次后被拒绝，第三个直接被拒绝
+        assert_eq!(attempts.load(Ordering::SeqCst), 3 + 2 + 1);
+        assert_eq!(budget.stats(), RetryBudgetStats { remaining: 0, granted: 3, rejected: 2 });
+    }
+    
+    #[tokio::test]
+    async fn test_retry_budget_refills_over_window() {
+        let budget = RetryBudget::new(2, Duration::from_millis(100));
+        assert!(budget.try_acquire());
+        assert!(budget.try_acquire());
+        assert!(!budget.try_acquire());
+        assert_eq!(budget.rejected(), 1);
+        
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(budget.remaining(), 1);
+        assert!(budget.try_acquire());
+    }
+    
+    #[tokio::test]
+    async fn test_catch_panic_async() {
+        assert_eq!(catch_panic_async(async { 42 }).await.unwrap(), 42);
+        
+        let handle = tokio::spawn(catch_panic_async(async {
+            tokio::task::yield_now().await;
+            panic!("第 {} 个任务出错", 3);
+        }));
+        let error = handle.await.expect("panic 不应让任务异常结束").unwrap_err();
+        assert!(matches!(&error, AppError::Panic(message) if message == "第 3 个任务出错"));
+        
+        let error = catch_panic_async(async { std::panic::panic_any(7_u32) }).await.unwrap_err();
+        assert_eq!(error.to_string(), "任务 panic: 未知的 panic 信息");
+        
+        let mut stats = ErrorStats::default();
+        stats.record_error(&error.with_context("处理批次失败"));
+        assert_eq!(stats.panic_errors, 1);
+    }
+    
     #[tokio::test]
     async fn test_error_stats() {
         let mut stats = ErrorStats::default();
@@ -0,0 +1,126 @@
+//! HTML 解析和提取工具模块
+//!
+//! 基于 scraper 的 CSS 选择器，从抓取到的页面中提取内容：
+//! - 链接（`<a>`/`<area>` 的 href，按页面 URL 解析为绝对地址）
+//! - 页面标题
+//! - 按 CSS 选择器提取元素文本
+
+use anyhow::Result;
+use reqwest::Url;
+use scraper::{Html, Selector};
+
+/// 解析 CSS 选择器，语法错误时返回错误
+pub fn parse_selector(selector: &str) -> Result<Selector> {
+    Selector::parse(selector).map_err(|e| anyhow::anyhow!("无效的 CSS 选择器 {:?}: {}", selector, e))
+}
+
+/// 把连续空白折叠为单个空格并去掉首尾空白
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 提取页面中的链接：相对链接按 `base` 解析，只保留 http/https 链接并去掉片段，按出现顺序去重
+pub fn extract_links(html: &str, base: &Url) -> Vec<String> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("a[href], area[href]").expect("固定的选择器");
+
+    let mut links: Vec<String> = Vec::new();
+    for element in document.select(&selector) {
+        let Some(href) = element.value().attr("href") else {
+            continue;
+        };
+        let Ok(mut url) = base.join(href.trim()) else {
+            continue;
+        };
+        if !matches!(url.scheme(), "http" | "https") {
+            continue;
+        }
+        url.set_fragment(None);
+        let url = url.to_string();
+        if !links.contains(&url) {
+            links.push(url);
+        }
+    }
+    links
+}
+
+/// 提取 `<title>` 的文本，没有标题或标题为空时返回 `None`
+pub fn extract_title(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("title").expect("固定的选择器");
+    document
+        .select(&selector)
+        .next()
+        .map(|title| collapse_whitespace(&title.text().collect::<String>()))
+        .filter(|title| !title.is_empty())
+}
+
+/// 按 CSS 选择器提取所有匹配元素的文本（空白已折叠），跳过没有文本的元素
+pub fn select_text(html: &str, selector: &str) -> Result<Vec<String>> {
+    let selector = parse_selector(selector)?;
+    let document = Html::parse_document(html);
+    Ok(document
+        .select(&selector)
+        .map(|element| collapse_whitespace(&element.text().collect::<String>()))
+        .filter(|text| !text.is_empty())
+        .collect())
+}
+
+/// 按 CSS 选择器提取所有匹配元素的某个属性
+pub fn select_attr(html: &str, selector: &str, attr: &str) -> Result<Vec<String>> {
+    let selector = parse_selector(selector)?;
+    let document = Html::parse_document(html);
+    Ok(document
+        .select(&selector)
+        .filter_map(|element| element.value().attr(attr).map(str::to_string))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<!DOCTYPE html>
+        <html><head><title>
+            示例   页面
+        </title></head>
+        <body>
+            <h1 id="main">标题</h1>
+            <p class="summary">第一段
+                摘要</p>
+            <p class="summary"></p>
+            <a href="a.html">a</a>
+            <a href='/root#section'>root</a>
+            <a href="a.html#again">a again</a>
+            <a href="mailto:x@example.test">mail</a>
+            <a name="anchor">no href</a>
+            <map><area href="https://other.test/"></map>
+            <link rel="stylesheet" href="style.css">
+            <img src="logo.png" alt="logo">
+        </body></html>"#;
+
+    #[test]
+    fn test_extract_links() {
+        let base = Url::parse("http://example.test/dir/page.html").unwrap();
+        assert_eq!(
+            extract_links(PAGE, &base),
+            vec!["http://example.test/dir/a.html", "http://example.test/root", "https://other.test/"]
+        );
+    }
+
+    #[test]
+    fn test_extract_title() {
+        assert_eq!(extract_title(PAGE).as_deref(), Some("示例 页面"));
+        assert_eq!(extract_title("<html><body>no title</body></html>"), None);
+        assert_eq!(extract_title("<title>  </title>"), None);
+    }
+
+    #[test]
+    fn test_select_text_and_attr() {
+        assert_eq!(select_text(PAGE, "p.summary").unwrap(), vec!["第一段 摘要"]);
+        assert_eq!(select_text(PAGE, "#main").unwrap(), vec!["标题"]);
+        assert!(select_text(PAGE, "table td").unwrap().is_empty());
+        assert!(select_text(PAGE, "p[").is_err());
+        assert_eq!(select_attr(PAGE, "img", "src").unwrap(), vec!["logo.png"]);
+    }
+}
@@ -4,35 +4,20 @@
 //! - 结构化日志
 //! - 日志级别控制
 //! - 日志轮转
-//! - 异步日志记录
+//! - 异步日志记录：有上限的队列加后台写入任务，满时丢弃最旧或最新的条目
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use tokio::time::{Duration, Instant};
-
-/// 日志级别
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-pub enum LogLevel {
-    Trace = 0,
-    Debug = 1,
-    Info = 2,
-    Warn = 3,
-    Error = 4,
-}
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{oneshot, Notify, RwLock};
+use tokio::time::Instant;
 
-impl std::fmt::Display for LogLevel {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            LogLevel::Trace => write!(f, "TRACE"),
-            LogLevel::Debug => write!(f, "DEBUG"),
-            LogLevel::Info => write!(f, "INFO"),
-            LogLevel::Warn => write!(f, "WARN"),
-            LogLevel::Error => write!(f, "ERROR"),
-        }
-    }
-}
+use super::tasks::TaskSet;
+
+/// 日志级别，定义在 common 中，与其他月份的项目共用
+pub use common::LogLevel;
 
 /// 日志条目
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,7 +37,11 @@ pub struct LogConfig {
     pub output: LogOutput,
     pub max_file_size: u64,
     pub max_files: u32,
+    /// 写入任务一次最多输出的条数
     pub buffer_size: usize,
+    /// 等待输出的条目上限
+    pub queue_capacity: usize,
+    pub overflow_policy: OverflowPolicy,
 }
 
 /// 日志格式
@@ -80,62 +69,130 @@ impl Default for LogConfig {
             max_file_size: 10 * 1024 * 1024, // 10MB
             max_files: 5,
             buffer_size: 1000,
+            queue_capacity: 10_000,
+            overflow_policy: OverflowPolicy::DropNewest,
         }
     }
 }
 
+/// 队列已满时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OverflowPolicy {
+    /// 丢弃新写入的条目
+    #[default]
+    DropNewest,
+    /// 丢弃队列中最旧的条目，保留最新的日志
+    DropOldest,
+}
+
+/// 发给写入任务的消息
+enum LogMessage {
+    Entry(LogEntry),
+    /// 之前的条目全部写出后回复
+    Flush(oneshot::Sender<()>),
+}
+
+#[derive(Default)]
+struct QueueItems {
+    messages: VecDeque<LogMessage>,
+    /// `messages` 中日志条目的数量，不含刷新请求
+    entries: usize,
+    /// 记录器已被丢弃，写入任务取完剩余消息后退出
+    closed: bool,
+}
+
+/// 记录器和写入任务共享的队列，条目数不会超过 `capacity`
+struct LogQueue {
+    capacity: usize,
+    policy: OverflowPolicy,
+    items: Mutex<QueueItems>,
+    notify: Notify,
+    dropped: AtomicU64,
+}
+
+impl LogQueue {
+    fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            policy,
+            items: Mutex::new(QueueItems::default()),
+            notify: Notify::new(),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// 放入一条日志，队列已满时按策略在入队时就丢弃
+    fn push_entry(&self, entry: LogEntry) {
+        {
+            let mut items = self.items.lock().unwrap();
+            if items.entries >= self.capacity {
+                self.dropped.fetch_add(1, Ordering::SeqCst);
+                match self.policy {
+                    OverflowPolicy::DropNewest => return,
+                    OverflowPolicy::DropOldest => {
+                        // 刷新请求留在原位，只移除最旧的日志条目
+                        if let Some(oldest) = items.messages.iter().position(|m| matches!(m, LogMessage::Entry(_))) {
+                            items.messages.remove(oldest);
+                            items.entries -= 1;
+                        }
+                    }
+                }
+            }
+            items.messages.push_back(LogMessage::Entry(entry));
+            items.entries += 1;
+        }
+        self.notify.notify_one();
+    }
+
+    fn push_flush(&self, ack: oneshot::Sender<()>) {
+        self.items.lock().unwrap().messages.push_back(LogMessage::Flush(ack));
+        self.notify.notify_one();
+    }
+
+    /// 取出最多 `max` 条消息；队列为空时返回空列表
+    fn take(&self, max: usize) -> Vec<LogMessage> {
+        let mut items = self.items.lock().unwrap();
+        let count = items.messages.len().min(max);
+        let batch: Vec<LogMessage> = items.messages.drain(..count).collect();
+        items.entries -= batch.iter().filter(|m| matches!(m, LogMessage::Entry(_))).count();
+        batch
+    }
+
+    fn is_closed(&self) -> bool {
+        self.items.lock().unwrap().closed
+    }
+
+    fn close(&self) {
+        self.items.lock().unwrap().closed = true;
+        self.notify.notify_one();
+    }
+}
+
 /// 异步日志记录器
+///
+/// 记录日志只把条目放进队列，格式化和输出由后台的写入任务完成；
+/// 需要在 tokio 运行时内创建。
+/// 队列中的条目数最多为 `queue_capacity`，满时按 `overflow_policy` 丢弃日志。
 pub struct AsyncLogger {
     config: LogConfig,
-    buffer: Arc<RwLock<Vec<LogEntry>>>,
-    last_flush: Arc<RwLock<Instant>>,
+    queue: Arc<LogQueue>,
 }
 
 impl AsyncLogger {
-    /// 创建新的日志记录器
+    /// 创建新的日志记录器，并启动写入任务
     pub fn new(config: LogConfig) -> Self {
-        Self {
-            config,
-            buffer: Arc::new(RwLock::new(Vec::new())),
-            last_flush: Arc::new(RwLock::new(Instant::now())),
-        }
+        let queue = Arc::new(LogQueue::new(config.queue_capacity, config.overflow_policy));
+        let writer = LogWriter {
+            format: config.format.clone(),
+            output: config.output.clone(),
+        };
+        tokio::spawn(writer.run(Arc::clone(&queue), config.buffer_size.max(1)));
+        Self { config, queue }
     }
     
     /// 记录日志
     pub async fn log(&self, level: LogLevel, target: &str, message: &str) {
-        if level < self.config.level {
-            return;
-        }
-        
-        let entry = LogEntry {
-            timestamp: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
-            level,
-            target: target.to_string(),
-            message: message.to_string(),
-            fields: std::collections::HashMap::new(),
-        };
-        
-        // 添加到缓冲区
-        {
-            let mut buffer = self.buffer.write().await;
-            buffer.push(entry);
-            
-            // 检查是否需要刷新
-            if buffer.len() >= self.config.buffer_size {
-                drop(buffer);
-                self.flush().await;
-            }
-        }
-        
-        // 检查是否需要定期刷新
-        let should_flush = {
-            let last_flush = self.last_flush.read().await;
-            last_flush.elapsed() > Duration::from_secs(1)
-        };
-        
-        if should_flush {
-            self.flush().await;
-        }
+        self.log_with_fields(level, target, message, std::collections::HashMap::new()).await;
     }
     
     /// 记录带字段的日志
@@ -157,63 +214,103 @@ impl AsyncLogger {
             message: message.to_string(),
             fields,
         };
-        
-        {
-            let mut buffer = self.buffer.write().await;
-            buffer.push(entry);
-            
-            if buffer.len() >= self.config.buffer_size {
-                drop(buffer);
-                self.flush().await;
-            }
-        }
+        self.enqueue(entry);
     }
     
-    /// 刷新日志缓冲区
+    /// 放入队列，队列已满时按策略丢弃
+    fn enqueue(&self, entry: LogEntry) {
+        self.queue.push_entry(entry);
+    }
+    
+    /// 等待之前记录的日志全部输出
     pub async fn flush(&self) {
-        let entries = {
-            let mut buffer = self.buffer.write().await;
-            let entries = buffer.clone();
-            buffer.clear();
-            entries
-        };
-        
-        if entries.is_empty() {
-            return;
-        }
-        
-        // 更新最后刷新时间
-        {
-            let mut last_flush = self.last_flush.write().await;
-            *last_flush = Instant::now();
-        }
-        
-        // 输出日志
-        for entry in entries {
-            self.output_log(&entry).await;
+        let (ack, done) = oneshot::channel();
+        self.queue.push_flush(ack);
+        // 写入任务只会在运行时关闭时退出，此时无法再等待
+        let _ = done.await;
+    }
+    
+    /// 因为队列已满被丢弃的日志条数
+    pub fn dropped_entries(&self) -> u64 {
+        self.queue.dropped.load(Ordering::SeqCst)
+    }
+    
+    /// 队列中等待输出的日志条数
+    pub fn queued_entries(&self) -> usize {
+        self.queue.items.lock().unwrap().entries
+    }
+}
+
+impl Drop for AsyncLogger {
+    fn drop(&mut self) {
+        self.queue.close();
+    }
+}
+
+/// 后台写入任务，持有格式和输出配置
+struct LogWriter {
+    format: LogFormat,
+    output: LogOutput,
+}
+
+impl LogWriter {
+    /// 每次最多取出 `batch_size` 条消息，格式化后一次输出；记录器被丢弃且队列取空后退出
+    async fn run(self, queue: Arc<LogQueue>, batch_size: usize) {
+        loop {
+            let batch = queue.take(batch_size);
+            if batch.is_empty() {
+                if queue.is_closed() {
+                    break;
+                }
+                // notify_one 会保留一个许可，取空之后才入队的消息也不会错过
+                queue.notify.notified().await;
+                continue;
+            }
+            let mut lines = Vec::new();
+            let mut acks = Vec::new();
+            for message in batch {
+                match message {
+                    LogMessage::Entry(entry) => lines.push(self.format_entry(&entry)),
+                    LogMessage::Flush(ack) => acks.push(ack),
+                }
+            }
+            self.output_lines(&lines).await;
+            for ack in acks {
+                let _ = ack.send(());
+            }
         }
     }
     
-    /// 输出日志
-    async fn output_log(&self, entry: &LogEntry) {
-        let formatted = match self.config.format {
+    fn format_entry(&self, entry: &LogEntry) -> String {
+        match self.format {
             LogFormat::Json => serde_json::to_string(entry).unwrap_or_else(|_| "{}".to_string()),
             LogFormat::Text => self.format_text(entry),
             LogFormat::Compact => self.format_compact(entry),
-        };
+        }
+    }
+    
+    /// 输出日志
+    async fn output_lines(&self, lines: &[String]) {
+        if lines.is_empty() {
+            return;
+        }
         
-        match &self.config.output {
+        match &self.output {
             LogOutput::Console => {
-                println!("{}", formatted);
+                for line in lines {
+                    println!("{}", line);
+                }
             }
             LogOutput::File(path) => {
-                if let Err(e) = self.write_to_file(path, &formatted).await {
+                if let Err(e) = self.write_to_file(path, lines).await {
                     eprintln!("写入日志文件失败: {}", e);
                 }
             }
             LogOutput::Both(path) => {
-                println!("{}", formatted);
-                if let Err(e) = self.write_to_file(path, &formatted).await {
+                for line in lines {
+                    println!("{}", line);
+                }
+                if let Err(e) = self.write_to_file(path, lines).await {
                     eprintln!("写入日志文件失败: {}", e);
                 }
             }
@@ -247,8 +344,8 @@ impl AsyncLogger {
         )
     }
     
-    /// 写入文件
-    async fn write_to_file(&self, path: &str, content: &str) -> Result<()> {
+    /// 写入文件，一批日志只打开一次文件
+    async fn write_to_file(&self, path: &str, lines: &[String]) -> Result<()> {
         use tokio::fs::OpenOptions;
         use tokio::io::AsyncWriteExt;
         
@@ -258,7 +355,9 @@ impl AsyncLogger {
             .open(path)
             .await?;
         
-        file.write_all(format!("{}\n", content).as_bytes()).await?;
+        let mut content = lines.join("\n");
+        content.push('\n');
+        file.write_all(content.as_bytes()).await?;
         file.flush().await?;
         
         Ok(())
@@ -411,6 +510,35 @@ pub async fn logging_utils_example() -> Result<()> {
     // 刷新日志
     logger.flush().await;
     
+    // 高吞吐：多个任务同时写日志，队列满时丢弃最旧的条目
+    let path = std::env::temp_dir().join(format!("august_logging_{}.log", std::process::id()));
+    let burst_logger = Arc::new(AsyncLogger::new(LogConfig {
+        format: LogFormat::Compact,
+        output: LogOutput::File(path.display().to_string()),
+        queue_capacity: 500,
+        overflow_policy: OverflowPolicy::DropOldest,
+        ..Default::default()
+    }));
+    let mut tasks = TaskSet::new();
+    for worker in 0..4 {
+        let logger = Arc::clone(&burst_logger);
+        tasks.spawn_tracked(format!("写入者{}", worker), async move {
+            for i in 0..1000 {
+                logger.log(LogLevel::Info, "burst", &format!("写入者 {} 第 {} 条", worker, i)).await;
+            }
+        });
+    }
+    tasks.join_all().await.into_result()?;
+    println!("写入结束时队列中还有 {} 条等待输出", burst_logger.queued_entries());
+    burst_logger.flush().await;
+    let written = tokio::fs::read_to_string(&path).await?.lines().count();
+    println!(
+        "高吞吐日志: 记录 4000 条，写出 {} 条，丢弃 {} 条",
+        written,
+        burst_logger.dropped_entries()
+    );
+    let _ = tokio::fs::remove_file(&path).await;
+    
     Ok(())
 }
 
@@ -451,4 +579,80 @@ mod tests {
         tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
         perf_logger.end_timer("test_operation").await;
     }
+    
+    /// 在队列里放入 10 条日志，写入任务还没机会运行，队列上限为 3
+    async fn log_burst(policy: OverflowPolicy) -> (Vec<String>, u64) {
+        let path = std::env::temp_dir().join(format!("august_logging_{:?}_{}.log", policy, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let logger = AsyncLogger::new(LogConfig {
+            format: LogFormat::Compact,
+            output: LogOutput::File(path.display().to_string()),
+            queue_capacity: 3,
+            overflow_policy: policy,
+            ..Default::default()
+        });
+        
+        for i in 0..10 {
+            logger.log(LogLevel::Info, "test", &format!("第{}条", i)).await;
+        }
+        assert_eq!(logger.queued_entries(), 3);
+        logger.flush().await;
+        assert_eq!(logger.queued_entries(), 0);
+        
+        let messages = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| line.rsplit(": ").next().unwrap().to_string())
+            .collect();
+        std::fs::remove_file(&path).unwrap();
+        (messages, logger.dropped_entries())
+    }
+    
+    #[tokio::test]
+    async fn test_drop_newest_keeps_earliest_entries() {
+        let (messages, dropped) = log_burst(OverflowPolicy::DropNewest).await;
+        assert_eq!(messages, vec!["第0条", "第1条", "第2条"]);
+        assert_eq!(dropped, 7);
+    }
+    
+    #[tokio::test]
+    async fn test_drop_oldest_keeps_latest_entries() {
+        let (messages, dropped) = log_burst(OverflowPolicy::DropOldest).await;
+        assert_eq!(messages, vec!["第7条", "第8条", "第9条"]);
+        assert_eq!(dropped, 7);
+    }
+    
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_queue_never_exceeds_capacity() {
+        for policy in [OverflowPolicy::DropOldest, OverflowPolicy::DropNewest] {
+            let path = std::env::temp_dir().join(format!("august_logging_bound_{:?}_{}.log", policy, std::process::id()));
+            let _ = std::fs::remove_file(&path);
+            let logger = Arc::new(AsyncLogger::new(LogConfig {
+                format: LogFormat::Compact,
+                output: LogOutput::File(path.display().to_string()),
+                queue_capacity: 8,
+                overflow_policy: policy,
+                ..Default::default()
+            }));
+            
+            // 多个任务同时写入，写入任务并发地取出条目
+            let mut tasks = crate::utils::tasks::TaskSet::new();
+            for writer in 0..4 {
+                let logger = Arc::clone(&logger);
+                tasks.spawn_tracked(format!("writer-{}", writer), async move {
+                    for i in 0..500 {
+                        logger.log(LogLevel::Info, "burst", &format!("{}-{}", writer, i)).await;
+                        assert!(logger.queued_entries() <= 8);
+                    }
+                });
+            }
+            tasks.join_all().await.into_result().unwrap();
+            assert!(logger.queued_entries() <= 8);
+            
+            logger.flush().await;
+            let written = std::fs::read_to_string(&path).unwrap().lines().count() as u64;
+            assert_eq!(written + logger.dropped_entries(), 2000);
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
 }
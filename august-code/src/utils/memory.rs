@@ -0,0 +1,172 @@
+//! 内存统计工具模块
+//!
+//! 提供真实的内存使用数据，而不是估算值：
+//! - Linux 上解析 `/proc/self/status` 获取常驻内存（RSS）和峰值 RSS
+//! - 启用 `alloc-stats` 特性时，通过计数分配器统计堆上已分配字节数和峰值
+
+/// 内存使用快照，不可用的指标为 `None`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MemoryStats {
+    /// 当前堆上已分配字节数（需要 `alloc-stats` 特性）
+    pub allocated_bytes: Option<u64>,
+    /// 堆上已分配字节数的峰值（需要 `alloc-stats` 特性）
+    pub peak_allocated_bytes: Option<u64>,
+    /// 当前常驻内存字节数（仅 Linux）
+    pub rss_bytes: Option<u64>,
+    /// 常驻内存峰值字节数（仅 Linux）
+    pub peak_rss_bytes: Option<u64>,
+}
+
+impl MemoryStats {
+    /// 采集当前进程的内存使用情况
+    pub fn current() -> Self {
+        let (allocated_bytes, peak_allocated_bytes) = allocator_stats();
+        let (rss_bytes, peak_rss_bytes) = proc_status_stats();
+        Self {
+            allocated_bytes,
+            peak_allocated_bytes,
+            rss_bytes,
+            peak_rss_bytes,
+        }
+    }
+
+    /// 当前内存使用量：优先使用分配器统计，其次是 RSS，都不可用时为 0
+    pub fn current_bytes(&self) -> u64 {
+        self.allocated_bytes.or(self.rss_bytes).unwrap_or(0)
+    }
+
+    /// 峰值内存使用量，取值来源与 `current_bytes` 一致
+    pub fn peak_bytes(&self) -> u64 {
+        if self.allocated_bytes.is_some() {
+            self.peak_allocated_bytes.unwrap_or(0)
+        } else {
+            self.peak_rss_bytes.unwrap_or(0)
+        }
+    }
+}
+
+/// 从 `/proc/self/status` 的内容中读取某一项（单位 kB），返回字节数
+pub fn parse_status_kb(status: &str, key: &str) -> Option<u64> {
+    status.lines().find_map(|line| {
+        let value = line.strip_prefix(key)?.strip_prefix(':')?;
+        let kb = value.trim().strip_suffix("kB")?.trim().parse::<u64>().ok()?;
+        Some(kb * 1024)
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn proc_status_stats() -> (Option<u64>, Option<u64>) {
+    match std::fs::read_to_string("/proc/self/status") {
+        Ok(status) => (parse_status_kb(&status, "VmRSS"), parse_status_kb(&status, "VmHWM")),
+        Err(_) => (None, None),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn proc_status_stats() -> (Option<u64>, Option<u64>) {
+    (None, None)
+}
+
+#[cfg(feature = "alloc-stats")]
+fn allocator_stats() -> (Option<u64>, Option<u64>) {
+    use std::sync::atomic::Ordering;
+    (
+        Some(counting::ALLOCATED.load(Ordering::Relaxed)),
+        Some(counting::PEAK.load(Ordering::Relaxed)),
+    )
+}
+
+#[cfg(not(feature = "alloc-stats"))]
+fn allocator_stats() -> (Option<u64>, Option<u64>) {
+    (None, None)
+}
+
+/// 计数全局分配器，在系统分配器之上统计已分配字节数
+#[cfg(feature = "alloc-stats")]
+mod counting {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    pub static ALLOCATED: AtomicU64 = AtomicU64::new(0);
+    pub static PEAK: AtomicU64 = AtomicU64::new(0);
+
+    pub struct CountingAllocator;
+
+    fn record_alloc(size: usize) {
+        let now = ALLOCATED.fetch_add(size as u64, Ordering::Relaxed) + size as u64;
+        PEAK.fetch_max(now, Ordering::Relaxed);
+    }
+
+    fn record_dealloc(size: usize) {
+        ALLOCATED.fetch_sub(size as u64, Ordering::Relaxed);
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                record_alloc(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                record_alloc(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            record_dealloc(layout.size());
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                record_dealloc(layout.size());
+                record_alloc(new_size);
+            }
+            new_ptr
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status_kb() {
+        let status = "Name:\tavg\nVmHWM:\t    2048 kB\nVmRSS:\t    1024 kB\nThreads:\t4\n";
+        assert_eq!(parse_status_kb(status, "VmRSS"), Some(1024 * 1024));
+        assert_eq!(parse_status_kb(status, "VmHWM"), Some(2048 * 1024));
+        assert_eq!(parse_status_kb(status, "VmSwap"), None);
+        assert_eq!(parse_status_kb(status, "Threads"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_current_reads_proc_status() {
+        let stats = MemoryStats::current();
+        let rss = stats.rss_bytes.unwrap();
+        assert!(rss > 0);
+        assert!(stats.peak_rss_bytes.unwrap() >= rss);
+        assert!(stats.current_bytes() > 0);
+    }
+
+    #[cfg(feature = "alloc-stats")]
+    #[test]
+    fn test_counting_allocator_tracks_allocations() {
+        let before = MemoryStats::current();
+        let data = vec![0u8; 4 * 1024 * 1024];
+        let during = MemoryStats::current();
+        assert!(during.peak_allocated_bytes.unwrap() >= before.allocated_bytes.unwrap() + data.len() as u64);
+        drop(data);
+    }
+}
@@ -3,10 +3,24 @@
 //! 这个模块包含了项目中使用的工具函数和通用功能：
 //! - 错误处理工具
 //! - 时间工具
+//! - 可注入的时钟（真实时间和测试用的虚拟时间）
+//! - 按名称追踪的任务集合，统一汇总错误和取消
 //! - 配置工具
 //! - 日志工具
+//! - 隔舱隔离工具
+//! - 内存统计工具
+//! - 运行报告
+//! - 内置 HTTP 测试服务器
+//! - HTML 解析和提取
 
 pub mod error;
 pub mod time;
+pub mod clock;
+pub mod tasks;
 pub mod config;
 pub mod logging;
+pub mod bulkhead;
+pub mod memory;
+pub mod report;
+pub mod test_server;
+pub mod html;
@@ -0,0 +1,185 @@
+//! 运行报告模块
+//!
+//! 把各个示例和测试器的结果汇总成一份机器可读的 JSON 报告：
+//! - 由 main 在 `--report json` 或 `AUGUST_REPORT=json` 时开启
+//! - 每个示例记录耗时、状态和错误信息
+//! - 示例内部可以额外记录计数和指标
+//! - 未开启时所有记录调用都是空操作
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use super::time::TimeUtils;
+
+/// 默认报告文件
+pub const DEFAULT_REPORT_FILE: &str = "august_report.json";
+
+/// 单个示例的结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SectionReport {
+    pub name: String,
+    pub duration_ms: u64,
+    /// "ok"、"error"、"skipped" 或 "pending"（只记录了指标，没有经过 `track`）
+    pub status: String,
+    pub error: Option<String>,
+    pub metrics: BTreeMap<String, f64>,
+}
+
+/// 整次运行的报告
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    pub started_at: u64,
+    pub total_ms: u64,
+    pub sections: Vec<SectionReport>,
+    pub error: Option<String>,
+}
+
+struct Collector {
+    started: Instant,
+    started_at: u64,
+    sections: Vec<SectionReport>,
+}
+
+impl Collector {
+    fn section(&mut self, name: &str) -> &mut SectionReport {
+        let index = match self.sections.iter().position(|s| s.name == name) {
+            Some(index) => index,
+            None => {
+                self.sections.push(SectionReport {
+                    name: name.to_string(),
+                    status: "pending".to_string(),
+                    ..Default::default()
+                });
+                self.sections.len() - 1
+            }
+        };
+        &mut self.sections[index]
+    }
+}
+
+static COLLECTOR: OnceLock<Mutex<Option<Collector>>> = OnceLock::new();
+
+fn with_collector<R>(f: impl FnOnce(&mut Collector) -> R) -> Option<R> {
+    let mut guard = COLLECTOR.get()?.lock().unwrap_or_else(|e| e.into_inner());
+    guard.as_mut().map(f)
+}
+
+/// 开启报告收集，重复调用会清空已有记录
+pub fn enable() {
+    let collector = Collector {
+        started: Instant::now(),
+        started_at: TimeUtils::current_timestamp(),
+        sections: Vec::new(),
+    };
+    let slot = COLLECTOR.get_or_init(|| Mutex::new(None));
+    *slot.lock().unwrap_or_else(|e| e.into_inner()) = Some(collector);
+}
+
+/// 是否已开启报告收集
+pub fn is_enabled() -> bool {
+    with_collector(|_| ()).is_some()
+}
+
+/// 执行一个示例并记录耗时和结果，结果原样返回
+pub async fn track<F, T>(name: &str, example: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    let start = Instant::now();
+    let result = example.await;
+    let duration_ms = start.elapsed().as_millis() as u64;
+    with_collector(|collector| {
+        let section = collector.section(name);
+        section.duration_ms += duration_ms;
+        match &result {
+            Ok(_) if section.status != "error" => section.status = "ok".to_string(),
+            Ok(_) => {}
+            Err(e) => {
+                section.status = "error".to_string();
+                section.error = Some(format!("{:#}", e));
+            }
+        }
+    });
+    result
+}
+
+/// 记录某个示例被跳过及原因
+pub fn skip(name: &str, reason: &str) {
+    with_collector(|collector| {
+        let section = collector.section(name);
+        section.status = "skipped".to_string();
+        section.error = Some(reason.to_string());
+    });
+}
+
+/// 记录某个示例的一项指标，同名指标会被覆盖
+pub fn metric(section: &str, key: &str, value: f64) {
+    with_collector(|collector| {
+        collector.section(section).metrics.insert(key.to_string(), value);
+    });
+}
+
+/// 为某个示例的计数累加 `n`
+pub fn count(section: &str, key: &str, n: u64) {
+    with_collector(|collector| {
+        *collector.section(section).metrics.entry(key.to_string()).or_insert(0.0) += n as f64;
+    });
+}
+
+/// 生成当前的报告快照，未开启时返回 `None`
+pub fn snapshot(error: Option<&anyhow::Error>) -> Option<RunReport> {
+    with_collector(|collector| RunReport {
+        started_at: collector.started_at,
+        total_ms: collector.started.elapsed().as_millis() as u64,
+        sections: collector.sections.clone(),
+        error: error.map(|e| format!("{:#}", e)),
+    })
+}
+
+/// 把报告写入 JSON 文件
+pub fn write_json(path: &Path, error: Option<&anyhow::Error>) -> Result<()> {
+    let report = snapshot(error).ok_or_else(|| anyhow::anyhow!("报告收集未开启"))?;
+    std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 收集器是进程级全局状态，相关断言放在同一个测试里避免并发干扰
+    #[tokio::test]
+    async fn test_track_and_metrics() {
+        enable();
+        assert!(is_enabled());
+
+        track("成功", async { Ok(()) }).await.unwrap();
+        let failed: Result<()> = track("失败", async { Err(anyhow::anyhow!("出错了")) }).await;
+        assert!(failed.is_err());
+        count("成功", "请求数", 2);
+        count("成功", "请求数", 3);
+        metric("指标", "每秒操作数", 12.5);
+        skip("跳过", "--skip-network");
+
+        let report = snapshot(None).unwrap();
+        let names: Vec<&str> = report.sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["成功", "失败", "指标", "跳过"]);
+        assert_eq!(report.sections[0].status, "ok");
+        assert_eq!(report.sections[0].metrics["请求数"], 5.0);
+        assert_eq!(report.sections[1].status, "error");
+        assert_eq!(report.sections[1].error.as_deref(), Some("出错了"));
+        assert_eq!(report.sections[2].status, "pending");
+        assert_eq!(report.sections[3].status, "skipped");
+
+        let path = std::env::temp_dir().join(format!("august-code-report-{}.json", std::process::id()));
+        write_json(&path, None).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(json["sections"][2]["metrics"]["每秒操作数"], 12.5);
+    }
+}
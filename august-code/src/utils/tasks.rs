@@ -0,0 +1,334 @@
+//! 异步任务集合
+//!
+//! 基于 `tokio::task::JoinSet` 统一管理一组并发任务，代替手写的 `Vec<JoinHandle>`：
+//! - `spawn_tracked` 按名称启动任务，可以按名称取消
+//! - `join_all` / `join_all_with_timeout` 按启动顺序收集结果，
+//!   panic、取消和超时汇总为 [`TaskSetError`]
+//! - 集合被丢弃时，未结束的任务全部取消
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+use tokio::task::{AbortHandle, Id, JoinError, JoinSet};
+
+use super::error::panic_message;
+
+/// 任务失败的原因
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskFailure {
+    /// 任务 panic，附带 panic 信息
+    Panicked(String),
+    /// 任务被取消
+    Cancelled,
+    /// 超过 `join_all_with_timeout` 的等待时间，任务已被取消
+    TimedOut,
+}
+
+impl fmt::Display for TaskFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskFailure::Panicked(message) => write!(f, "panic: {}", message),
+            TaskFailure::Cancelled => write!(f, "已取消"),
+            TaskFailure::TimedOut => write!(f, "超时"),
+        }
+    }
+}
+
+/// 失败的任务
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailedTask {
+    pub name: String,
+    pub failure: TaskFailure,
+}
+
+/// 一组任务中有任务失败
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("{} 个任务失败: {}", failures.len(), describe(failures))]
+pub struct TaskSetError {
+    pub failures: Vec<FailedTask>,
+}
+
+fn describe(failures: &[FailedTask]) -> String {
+    failures
+        .iter()
+        .map(|task| format!("{}（{}）", task.name, task.failure))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 所有任务的结果，按启动顺序排列
+#[derive(Debug)]
+pub struct TaskResults<T> {
+    pub completed: Vec<(String, T)>,
+    pub failed: Vec<FailedTask>,
+}
+
+impl<T> TaskResults<T> {
+    /// 所有任务都正常结束
+    pub fn is_ok(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// 所有任务的返回值，有任务失败时返回汇总了全部失败的错误
+    pub fn into_result(self) -> Result<Vec<T>, TaskSetError> {
+        if self.failed.is_empty() {
+            Ok(self.into_values())
+        } else {
+            Err(TaskSetError { failures: self.failed })
+        }
+    }
+
+    /// 正常结束的任务的返回值，忽略失败的任务
+    pub fn into_values(self) -> Vec<T> {
+        self.completed.into_iter().map(|(_, value)| value).collect()
+    }
+}
+
+struct TrackedTask {
+    index: usize,
+    name: String,
+    abort: AbortHandle,
+    aborted: bool,
+}
+
+/// 按名称追踪的任务集合
+pub struct TaskSet<T> {
+    set: JoinSet<T>,
+    tasks: HashMap<Id, TrackedTask>,
+    next_index: usize,
+}
+
+impl<T: Send + 'static> TaskSet<T> {
+    pub fn new() -> Self {
+        Self {
+            set: JoinSet::new(),
+            tasks: HashMap::new(),
+            next_index: 0,
+        }
+    }
+
+    /// 启动一个带名称的任务，名称用于取消和错误汇总，可以重复
+    pub fn spawn_tracked<F>(&mut self, name: impl Into<String>, future: F) -> AbortHandle
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        let abort = self.set.spawn(future);
+        self.tasks.insert(abort.id(), TrackedTask {
+            index: self.next_index,
+            name: name.into(),
+            abort: abort.clone(),
+            aborted: false,
+        });
+        self.next_index += 1;
+        abort
+    }
+
+    /// 还没有被收集结果的任务数，包括已经结束的任务
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// 取消指定名称的所有未结束任务，没有可取消的任务时返回 false
+    pub fn abort(&mut self, name: &str) -> bool {
+        let mut aborted = false;
+        for task in self.tasks.values_mut() {
+            if task.name == name && !task.aborted && !task.abort.is_finished() {
+                task.abort.abort();
+                task.aborted = true;
+                aborted = true;
+            }
+        }
+        aborted
+    }
+
+    /// 取消所有未结束的任务并等待它们退出，返回这次被取消的任务名称
+    ///
+    /// 已经通过 `abort` 取消的任务不会重复计入
+    pub async fn shutdown(&mut self) -> Vec<String> {
+        self.set.abort_all();
+        let mut cancelled = Vec::new();
+        while let Some(result) = self.set.join_next_with_id().await {
+            let id = match &result {
+                Ok((id, _)) => *id,
+                Err(error) => error.id(),
+            };
+            let task = self.take(id);
+            if matches!(&result, Err(error) if error.is_cancelled()) && !task.aborted {
+                cancelled.push((task.index, task.name));
+            }
+        }
+        cancelled.sort_by_key(|(index, _)| *index);
+        cancelled.into_iter().map(|(_, name)| name).collect()
+    }
+
+    /// 等待所有任务结束
+    pub async fn join_all(mut self) -> TaskResults<T> {
+        self.collect(None).await
+    }
+
+    /// 最多等待 `timeout`，到时仍未结束的任务被取消并记为超时
+    pub async fn join_all_with_timeout(mut self, timeout: Duration) -> TaskResults<T> {
+        self.collect(Some(timeout)).await
+    }
+
+    async fn collect(&mut self, timeout: Option<Duration>) -> TaskResults<T> {
+        let deadline = timeout.map(|timeout| tokio::time::Instant::now() + timeout);
+        let mut completed = Vec::new();
+        let mut failed = Vec::new();
+
+        loop {
+            let next = match deadline {
+                Some(deadline) => match tokio::time::timeout_at(deadline, self.set.join_next_with_id()).await {
+                    Ok(next) => next,
+                    Err(_) => break,
+                },
+                None => self.set.join_next_with_id().await,
+            };
+            match next {
+                Some(Ok((id, value))) => {
+                    let task = self.take(id);
+                    completed.push((task.index, task.name, value));
+                }
+                Some(Err(error)) => {
+                    let task = self.take(error.id());
+                    failed.push((task.index, task.name, failure_of(error)));
+                }
+                None => break,
+            }
+        }
+
+        // 超时：取消剩下的任务，等它们退出后再返回
+        if !self.set.is_empty() {
+            self.set.abort_all();
+            while let Some(result) = self.set.join_next_with_id().await {
+                match result {
+                    Ok((id, value)) => {
+                        let task = self.take(id);
+                        completed.push((task.index, task.name, value));
+                    }
+                    Err(error) => {
+                        let task = self.take(error.id());
+                        let failure = match failure_of(error) {
+                            TaskFailure::Cancelled if !task.aborted => TaskFailure::TimedOut,
+                            failure => failure,
+                        };
+                        failed.push((task.index, task.name, failure));
+                    }
+                }
+            }
+        }
+
+        completed.sort_by_key(|(index, _, _)| *index);
+        failed.sort_by_key(|(index, _, _)| *index);
+        TaskResults {
+            completed: completed.into_iter().map(|(_, name, value)| (name, value)).collect(),
+            failed: failed
+                .into_iter()
+                .map(|(_, name, failure)| FailedTask { name, failure })
+                .collect(),
+        }
+    }
+
+    fn take(&mut self, id: Id) -> TrackedTask {
+        self.tasks.remove(&id).expect("集合中的任务都有追踪记录")
+    }
+}
+
+impl<T: Send + 'static> Default for TaskSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for TaskSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskSet")
+            .field("len", &self.set.len())
+            .field("next_index", &self.next_index)
+            .finish()
+    }
+}
+
+fn failure_of(error: JoinError) -> TaskFailure {
+    if error.is_panic() {
+        TaskFailure::Panicked(panic_message(error.into_panic().as_ref()))
+    } else {
+        TaskFailure::Cancelled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_results_keep_spawn_order() {
+        let mut tasks = TaskSet::new();
+        for (i, delay) in [30_u64, 10, 20].into_iter().enumerate() {
+            tasks.spawn_tracked(format!("任务{}", i), async move {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                i
+            });
+        }
+        assert_eq!(tasks.len(), 3);
+
+        let results = tasks.join_all().await;
+        assert!(results.is_ok());
+        assert_eq!(results.completed[0].0, "任务0");
+        assert_eq!(results.into_result().unwrap(), vec![0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_aggregated() {
+        let mut tasks = TaskSet::new();
+        tasks.spawn_tracked("正常", async { 1 });
+        tasks.spawn_tracked("崩溃", async { panic!("第 {} 步出错", 2) });
+        tasks.spawn_tracked("等待", std::future::pending::<i32>());
+        assert!(tasks.abort("等待"));
+        assert!(!tasks.abort("等待"));
+        assert!(!tasks.abort("不存在"));
+
+        let results = tasks.join_all().await;
+        assert_eq!(results.failed, vec![
+            FailedTask { name: "崩溃".to_string(), failure: TaskFailure::Panicked("第 2 步出错".to_string()) },
+            FailedTask { name: "等待".to_string(), failure: TaskFailure::Cancelled },
+        ]);
+        let error = results.into_result().unwrap_err();
+        assert_eq!(error.to_string(), "2 个任务失败: 崩溃（panic: 第 2 步出错）, 等待（已取消）");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_join_all_with_timeout_cancels_stragglers() {
+        let mut tasks = TaskSet::new();
+        tasks.spawn_tracked("快", async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            "快"
+        });
+        tasks.spawn_tracked("慢", async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            "慢"
+        });
+
+        let results = tasks.join_all_with_timeout(Duration::from_secs(1)).await;
+        assert_eq!(results.completed, vec![("快".to_string(), "快")]);
+        assert_eq!(results.failed, vec![FailedTask { name: "慢".to_string(), failure: TaskFailure::TimedOut }]);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_reports_cancelled_tasks() {
+        let mut tasks = TaskSet::new();
+        tasks.spawn_tracked("已结束", async {});
+        tasks.spawn_tracked("周期任务", std::future::pending::<()>());
+        tasks.spawn_tracked("已取消", std::future::pending::<()>());
+        tasks.abort("已取消");
+        tokio::task::yield_now().await;
+
+        assert_eq!(tasks.shutdown().await, vec!["周期任务".to_string()]);
+        assert!(tasks.is_empty());
+    }
+}
@@ -0,0 +1,547 @@
+//! 内置 HTTP 测试服务器模块
+//!
+//! 在随机端口上启动一个本地 axum 服务器，模拟 httpbin 的常用接口，
+//! 让示例和集成测试不再依赖外部的 httpbin.org：
+//! - `GET /get`：返回请求 URL、请求头和来源
+//! - `GET /delay/{n}`：等待 n 秒（最多 10 秒）后返回
+//! - `/status/{code}`：返回指定状态码
+//! - `POST /post`：回显请求体
+//! - `GET /headers`、`GET /user-agent`、`/anything/*`：其他常用接口
+//! - `GET /gzip`：总是以 gzip 压缩返回 JSON（与 httpbin 一致，不看 Accept-Encoding）
+//! - `GET /bytes/{n}`：返回 n 字节的数据
+//! - `/api/users`：内存中的用户 REST 接口，支持分页、查询和创建
+//! - `GET /dns-query`：DoH JSON 接口，所有 `.test` 域名的 A 记录都解析到 127.0.0.1
+//! - `GET /site/*`：几个互相链接的 HTML 页面，配合 `GET /robots.txt`（禁止 `/site/private`）演示爬取
+//!
+//! 另外提供 `WsEchoServer`，一个回显文本和二进制消息的 WebSocket 服务器。
+
+use anyhow::Result;
+use axum::extract::{OriginalUri, Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{any, get, post};
+use axum::{Json, Router};
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, oneshot};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+/// 最长延迟秒数，与 httpbin 保持一致
+const MAX_DELAY_SECS: u64 = 10;
+
+/// 用户接口的内存状态
+#[derive(Clone, Default)]
+struct ApiState {
+    users: Arc<Mutex<Vec<Value>>>,
+    /// 接下来这么多次用户接口请求返回 503
+    fail_next: Arc<AtomicUsize>,
+}
+
+impl ApiState {
+    /// 需要模拟故障时消耗一次故障次数并返回 true
+    fn take_failure(&self) -> bool {
+        self.fail_next
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+    }
+}
+
+/// 运行中的测试服务器，被丢弃时自动停止
+pub struct TestServer {
+    addr: SocketAddr,
+    state: ApiState,
+    shutdown: Option<oneshot::Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl TestServer {
+    /// 在 127.0.0.1 的随机端口上启动服务器
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let state = ApiState::default();
+        let app = router(state.clone());
+
+        let handle = tokio::spawn(async move {
+            let server = axum::serve(listener, app).with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            });
+            if let Err(e) = server.await {
+                eprintln!("测试服务器异常退出: {}", e);
+            }
+        });
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown: Some(shutdown_tx),
+            handle: Some(handle),
+        })
+    }
+
+    /// 服务器监听地址
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 服务器根地址，例如 `http://127.0.0.1:54321`
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// 拼接完整 URL，`path` 以 `/` 开头
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url(), path)
+    }
+
+    /// 让接下来 `n` 次用户接口请求返回 503，用于演示重试
+    pub fn fail_next(&self, n: usize) {
+        self.state.fail_next.store(n, Ordering::SeqCst);
+    }
+
+    /// 停止服务器并等待其退出
+    pub async fn shutdown(mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.await;
+        }
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+fn router(state: ApiState) -> Router {
+    let api = Router::new()
+        .route("/api/users", get(list_users_handler).post(create_user_handler))
+        .route("/api/users/:id", get(get_user_handler))
+        .with_state(state);
+
+    Router::new()
+        .route("/get", get(get_handler))
+        .route("/delay/:seconds", get(delay_handler))
+        .route("/status/:code", any(status_handler))
+        .route("/post", post(post_handler))
+        .route("/headers", get(headers_handler))
+        .route("/user-agent", get(user_agent_handler))
+        .route("/anything", any(get_handler))
+        .route("/anything/*rest", any(get_handler))
+        .route("/gzip", get(gzip_handler))
+        .route("/bytes/:n", get(bytes_handler))
+        .route("/dns-query", get(dns_query_handler))
+        .route("/robots.txt", get(robots_handler))
+        .route("/site/*page", get(site_handler))
+        .merge(api)
+}
+
+fn headers_json(headers: &HeaderMap) -> Value {
+    let map: serde_json::Map<String, Value> = headers
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                Value::String(value.to_str().unwrap_or_default().to_string()),
+            )
+        })
+        .collect();
+    Value::Object(map)
+}
+
+async fn get_handler(OriginalUri(uri): OriginalUri, headers: HeaderMap) -> Json<Value> {
+    Json(json!({
+        "url": uri.to_string(),
+        "headers": headers_json(&headers),
+        "origin": "127.0.0.1",
+    }))
+}
+
+async fn delay_handler(
+    Path(seconds): Path<u64>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Json<Value> {
+    tokio::time::sleep(Duration::from_secs(seconds.min(MAX_DELAY_SECS))).await;
+    get_handler(OriginalUri(uri), headers).await
+}
+
+async fn status_handler(Path(code): Path<u16>) -> StatusCode {
+    StatusCode::from_u16(code).unwrap_or(StatusCode::BAD_REQUEST)
+}
+
+async fn post_handler(OriginalUri(uri): OriginalUri, headers: HeaderMap, body: String) -> Json<Value> {
+    let parsed = serde_json::from_str::<Value>(&body).unwrap_or(Value::Null);
+    Json(json!({
+        "url": uri.to_string(),
+        "headers": headers_json(&headers),
+        "origin": "127.0.0.1",
+        "data": body,
+        "json": parsed,
+    }))
+}
+
+async fn headers_handler(headers: HeaderMap) -> Json<Value> {
+    Json(json!({ "headers": headers_json(&headers) }))
+}
+
+async fn user_agent_handler(headers: HeaderMap) -> Json<Value> {
+    let user_agent = headers
+        .get(axum::http::header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    Json(json!({ "user-agent": user_agent }))
+}
+
+async fn gzip_handler(headers: HeaderMap) -> Response {
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    let body = json!({ "gzipped": true, "headers": headers_json(&headers) }).to_string();
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let compressed = encoder.write_all(body.as_bytes()).and_then(|_| encoder.finish());
+    match compressed {
+        Ok(bytes) => (
+            [
+                (axum::http::header::CONTENT_TYPE, "application/json"),
+                (axum::http::header::CONTENT_ENCODING, "gzip"),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// 最多返回 1MB，避免测试误用时占满内存
+async fn bytes_handler(Path(n): Path<usize>) -> Vec<u8> {
+    vec![b'x'; n.min(1024 * 1024)]
+}
+
+/// 演示站点的页面：路径 -> (标题, 链接)
+const SITE_PAGES: &[(&str, &str, &[&str])] = &[
+    (
+        "index.html",
+        "首页",
+        &["about.html", "/site/blog.html", "private/secret.html", "#top", "mailto:admin@example.test", "http://other.test/"],
+    ),
+    ("about.html", "关于", &["index.html", "contact.html"]),
+    ("blog.html", "博客", &["post-1.html", "post-2.html"]),
+    ("post-1.html", "第一篇文章", &["blog.html"]),
+    ("post-2.html", "第二篇文章", &["deep.html"]),
+    ("contact.html", "联系方式", &[]),
+    ("deep.html", "深层页面", &[]),
+    ("private/secret.html", "私密页面", &[]),
+];
+
+async fn robots_handler() -> &'static str {
+    "# 演示站点\nUser-agent: *\nDisallow: /site/private\n"
+}
+
+async fn site_handler(Path(page): Path<String>) -> Response {
+    let Some((_, title, links)) = SITE_PAGES.iter().find(|(path, _, _)| *path == page) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let items: String = links
+        .iter()
+        .map(|link| format!("<li><a class=\"nav\" href=\"{}\">{}</a></li>", link, link))
+        .collect();
+    let html = format!(
+        "<!DOCTYPE html><html><head><title>{title}</title></head>\
+         <body><h1>{title}</h1><p class=\"summary\">这是{title}。</p><ul>{items}</ul></body></html>"
+    );
+    axum::response::Html(html).into_response()
+}
+
+/// DoH 查询参数
+#[derive(Debug, Deserialize)]
+struct DnsQuery {
+    name: String,
+    #[serde(rename = "type", default)]
+    record_type: Option<String>,
+}
+
+/// 按 DoH JSON 格式应答：`.test` 域名的 A 记录为 127.0.0.1，其他域名返回 NXDOMAIN
+async fn dns_query_handler(Query(query): Query<DnsQuery>) -> Json<Value> {
+    let name = query.name.trim_end_matches('.').to_ascii_lowercase();
+    if !name.ends_with(".test") {
+        return Json(json!({ "Status": 3, "Question": [{ "name": name }] }));
+    }
+    let answer = match query.record_type.as_deref().unwrap_or("A") {
+        "A" | "1" => vec![json!({ "name": name, "type": 1, "TTL": 60, "data": "127.0.0.1" })],
+        _ => Vec::new(),
+    };
+    Json(json!({ "Status": 0, "Question": [{ "name": name }], "Answer": answer }))
+}
+
+/// 分页参数
+#[derive(Debug, Deserialize)]
+struct PageQuery {
+    page: Option<usize>,
+    per_page: Option<usize>,
+}
+
+fn service_unavailable() -> Response {
+    (StatusCode::SERVICE_UNAVAILABLE, Json(json!({ "error": "服务暂时不可用" }))).into_response()
+}
+
+async fn list_users_handler(State(state): State<ApiState>, Query(query): Query<PageQuery>) -> Response {
+    if state.take_failure() {
+        return service_unavailable();
+    }
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(10).clamp(1, 100);
+    let users = state.users.lock().unwrap();
+    let items: Vec<Value> = users.iter().skip((page - 1) * per_page).take(per_page).cloned().collect();
+    Json(json!({
+        "items": items,
+        "page": page,
+        "per_page": per_page,
+        "total": users.len(),
+    }))
+    .into_response()
+}
+
+async fn get_user_handler(State(state): State<ApiState>, Path(id): Path<u64>) -> Response {
+    if state.take_failure() {
+        return service_unavailable();
+    }
+    let users = state.users.lock().unwrap();
+    match users.iter().find(|user| user["id"] == id) {
+        Some(user) => Json(user.clone()).into_response(),
+        None => (StatusCode::NOT_FOUND, Json(json!({ "error": format!("用户 {} 不存在", id) }))).into_response(),
+    }
+}
+
+async fn create_user_handler(State(state): State<ApiState>, Json(body): Json<Value>) -> Response {
+    if state.take_failure() {
+        return service_unavailable();
+    }
+    let name = body["name"].as_str().unwrap_or_default().trim();
+    let email = body["email"].as_str().unwrap_or_default().trim();
+    if name.is_empty() || !email.contains('@') {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({ "error": "name 不能为空且 email 必须合法" })),
+        )
+            .into_response();
+    }
+
+    let mut users = state.users.lock().unwrap();
+    let user = json!({ "id": users.len() as u64 + 1, "name": name, "email": email });
+    users.push(user.clone());
+    (StatusCode::CREATED, Json(user)).into_response()
+}
+
+/// 运行中的 WebSocket 回显服务器，被丢弃时自动停止
+pub struct WsEchoServer {
+    addr: SocketAddr,
+    /// 通知所有连接立即断开
+    disconnect: broadcast::Sender<()>,
+    shutdown: Option<oneshot::Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl WsEchoServer {
+    /// 在 127.0.0.1 的随机端口上启动服务器
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+        let (disconnect, _) = broadcast::channel(4);
+
+        let connections = disconnect.clone();
+        let handle = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = &mut shutdown_rx => break,
+                    accepted = listener.accept() => match accepted {
+                        Ok((stream, _)) => {
+                            let dropped = connections.subscribe();
+                            tokio::spawn(async move {
+                                if let Err(e) = echo_connection(stream, dropped).await {
+                                    eprintln!("回显连接异常结束: {}", e);
+                                }
+                            });
+                        }
+                        Err(e) => eprintln!("接受连接失败: {}", e),
+                    },
+                }
+            }
+            // 停止时断开所有已建立的连接
+            let _ = connections.send(());
+        });
+
+        Ok(Self {
+            addr,
+            disconnect,
+            shutdown: Some(shutdown_tx),
+            handle: Some(handle),
+        })
+    }
+
+    /// WebSocket 地址，例如 `ws://127.0.0.1:54321`
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    /// 不经过关闭握手直接断开所有连接，模拟网络中断
+    pub fn drop_connections(&self) {
+        let _ = self.disconnect.send(());
+    }
+
+    /// 停止服务器并等待其退出
+    pub async fn shutdown(mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.await;
+        }
+    }
+}
+
+impl Drop for WsEchoServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+/// 回显单个连接上的文本和二进制消息，收到断开通知时直接丢弃连接
+async fn echo_connection(stream: TcpStream, mut dropped: broadcast::Receiver<()>) -> Result<()> {
+    let mut socket = tokio_tungstenite::accept_async(stream).await?;
+    loop {
+        tokio::select! {
+            _ = dropped.recv() => return Ok(()),
+            message = socket.next() => match message {
+                Some(Ok(message @ (Message::Text(_) | Message::Binary(_)))) => socket.send(message).await?,
+                Some(Ok(Message::Close(_))) | None => return Ok(()),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_server_endpoints() {
+        let server = TestServer::start().await.unwrap();
+        let client = reqwest::Client::new();
+
+        let body: Value = client.get(server.url("/get?a=1")).send().await.unwrap().json().await.unwrap();
+        assert_eq!(body["url"], "/get?a=1");
+        assert_eq!(body["origin"], "127.0.0.1");
+
+        let response = client.get(server.url("/status/418")).send().await.unwrap();
+        assert_eq!(response.status().as_u16(), 418);
+
+        let body: Value = client
+            .post(server.url("/post"))
+            .json(&json!({ "name": "august" }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(body["json"]["name"], "august");
+
+        let body: Value = client
+            .get(server.url("/user-agent"))
+            .header("user-agent", "test-agent")
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(body["user-agent"], "test-agent");
+
+        let response = client.get(server.url("/anything/1")).send().await.unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+
+        server.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_server_users_api() {
+        let server = TestServer::start().await.unwrap();
+        let client = reqwest::Client::new();
+
+        for name in ["a", "b", "c"] {
+            let response = client
+                .post(server.url("/api/users"))
+                .json(&json!({ "name": name, "email": format!("{}@example.com", name) }))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status().as_u16(), 201);
+        }
+        let response = client
+            .post(server.url("/api/users"))
+            .json(&json!({ "name": "", "email": "bad" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 422);
+
+        let page: Value = client
+            .get(server.url("/api/users?page=2&per_page=2"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(page["total"], 3);
+        assert_eq!(page["items"][0]["name"], "c");
+
+        let user: Value = client.get(server.url("/api/users/2")).send().await.unwrap().json().await.unwrap();
+        assert_eq!(user["name"], "b");
+        let response = client.get(server.url("/api/users/9")).send().await.unwrap();
+        assert_eq!(response.status().as_u16(), 404);
+
+        server.fail_next(1);
+        let response = client.get(server.url("/api/users/1")).send().await.unwrap();
+        assert_eq!(response.status().as_u16(), 503);
+        let response = client.get(server.url("/api/users/1")).send().await.unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_server_delay() {
+        let server = TestServer::start().await.unwrap();
+        let client = reqwest::Client::new();
+
+        let start = std::time::Instant::now();
+        let response = client.get(server.url("/delay/1")).send().await.unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        assert!(start.elapsed() >= Duration::from_secs(1));
+
+        let timed_out = client
+            .get(server.url("/delay/2"))
+            .timeout(Duration::from_millis(100))
+            .send()
+            .await;
+        assert!(timed_out.is_err());
+    }
+}
@@ -1,14 +1,23 @@
 //! 时间工具模块
 //! 
 //! 提供时间相关的工具函数：
-//! - 时间格式化
+//! - 时间格式化：`1h 30m` 形式的时长、"2 hours ago" 形式的相对时间、ISO 8601 时间戳
+//! - 时长和 ISO 8601 时间戳解析
+//! - 按 IANA 时区名（如 `Asia/Shanghai`）转换时间
 //! - 时间计算
-//! - 定时器工具
-//! - 性能测量
+//! - 定时器工具，按绝对时间触发、不累积漂移的周期定时器
+//! - 性能测量，分段计时的秒表（计圈、暂停/恢复、统计）
 
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::time::{sleep, timeout, Interval};
 
+use super::clock::{system_clock, Clock, MockClock};
+use super::logging::PerformanceLogger;
+
 /// 时间工具
 pub struct TimeUtils;
 
@@ -29,29 +38,120 @@ impl TimeUtils {
             .as_millis() as u64
     }
     
-    /// 格式化时间戳
+    /// 秒级时间戳转为 UTC 时间，超出范围时为 Unix 纪元
+    pub fn to_datetime(timestamp: u64) -> DateTime<Utc> {
+        i64::try_from(timestamp)
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .unwrap_or_default()
+    }
+    
+    /// 格式化时间戳（UTC），例如 `2024-01-02 03:04:05`
     pub fn format_timestamp(timestamp: u64) -> String {
-        let datetime = chrono::DateTime::from_timestamp(timestamp as i64, 0)
-            .unwrap_or_default();
-        datetime.format("%Y-%m-%d %H:%M:%S").to_string()
+        Self::to_datetime(timestamp).format("%Y-%m-%d %H:%M:%S").to_string()
+    }
+    
+    /// 格式化为 ISO 8601（RFC 3339），例如 `2024-01-02T03:04:05Z`
+    pub fn format_iso8601(datetime: &DateTime<Utc>) -> String {
+        datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+    
+    /// 解析 ISO 8601 时间戳并转为 UTC，带偏移（`+08:00`、`Z`）或不带偏移（按 UTC）都可以
+    pub fn parse_iso8601(input: &str) -> Result<DateTime<Utc>> {
+        let input = input.trim();
+        if let Ok(datetime) = DateTime::parse_from_rfc3339(input) {
+            return Ok(datetime.with_timezone(&Utc));
+        }
+        NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M:%S%.f")
+            .map(|naive| naive.and_utc())
+            .map_err(|_| anyhow::anyhow!("无效的 ISO 8601 时间: {:?}", input))
+    }
+    
+    /// 按 IANA 名称查找时区，例如 `Asia/Shanghai`、`America/New_York`
+    pub fn parse_timezone(name: &str) -> Result<Tz> {
+        name.trim().parse().map_err(|_| anyhow::anyhow!("未知的时区: {:?}", name))
+    }
+    
+    /// 把 UTC 时间转换到指定时区
+    pub fn to_timezone(datetime: &DateTime<Utc>, timezone: &str) -> Result<DateTime<Tz>> {
+        Ok(datetime.with_timezone(&Self::parse_timezone(timezone)?))
+    }
+    
+    /// 把某时区的本地时间（`2024-03-10 02:30:00`）转为 UTC，夏令时切换造成的不存在或有歧义的时间返回错误
+    pub fn local_to_utc(local: &str, timezone: &str) -> Result<DateTime<Utc>> {
+        let tz = Self::parse_timezone(timezone)?;
+        let naive = NaiveDateTime::parse_from_str(local.trim(), "%Y-%m-%d %H:%M:%S")
+            .map_err(|_| anyhow::anyhow!("无效的本地时间: {:?}", local))?;
+        tz.from_local_datetime(&naive)
+            .single()
+            .map(|datetime| datetime.with_timezone(&Utc))
+            .ok_or_else(|| anyhow::anyhow!("{} 在时区 {} 中不存在或有歧义", local, timezone))
+    }
+    
+    /// 按指定时区格式化时间戳，例如 `2024-01-02 11:04:05 CST`
+    pub fn format_in_timezone(timestamp: u64, timezone: &str) -> Result<String> {
+        let local = Self::to_timezone(&Self::to_datetime(timestamp), timezone)?;
+        Ok(local.format("%Y-%m-%d %H:%M:%S %Z").to_string())
+    }
+    
+    /// 解析时长，可以组合多个单位：`1h30m`、`2m 30s`、`1d2h`、`1.5h`、`250ms`，单独的数字按秒计算
+    pub fn parse_duration(input: &str) -> Result<Duration> {
+        let compact: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+        if compact.is_empty() {
+            return Err(anyhow::anyhow!("时长不能为空"));
+        }
+        let segments = split_duration_segments(&compact);
+        if segments.len() > 1 && segments.iter().any(|segment| segment.ends_with(|c: char| c.is_ascii_digit())) {
+            return Err(anyhow::anyhow!("无效的时长 {:?}: 组合多个单位时每一段都要带单位", input));
+        }
+        
+        let mut total = Duration::ZERO;
+        for segment in segments {
+            let part = common::config::parse_duration(segment).map_err(|e| anyhow::anyhow!("无效的时长 {:?}: {}", input, e))?;
+            total += part;
+        }
+        Ok(total)
     }
     
-    /// 格式化持续时间
+    /// 格式化持续时间，例如 `1h 30m`、`1s 234ms`，结果可以被 [`TimeUtils::parse_duration`] 解析
     pub fn format_duration(duration: Duration) -> String {
         let total_secs = duration.as_secs();
-        let hours = total_secs / 3600;
-        let minutes = (total_secs % 3600) / 60;
-        let seconds = total_secs % 60;
-        let millis = duration.subsec_millis();
-        
-        if hours > 0 {
-            format!("{}h {}m {}s {}ms", hours, minutes, seconds, millis)
-        } else if minutes > 0 {
-            format!("{}m {}s {}ms", minutes, seconds, millis)
-        } else if seconds > 0 {
-            format!("{}s {}ms", seconds, millis)
+        let parts = [
+            (total_secs / 86400, "d"),
+            (total_secs % 86400 / 3600, "h"),
+            (total_secs % 3600 / 60, "m"),
+            (total_secs % 60, "s"),
+            (u64::from(duration.subsec_millis()), "ms"),
+        ];
+        let formatted: Vec<String> = parts
+            .iter()
+            .filter(|(value, _)| *value > 0)
+            .map(|(value, unit)| format!("{}{}", value, unit))
+            .collect();
+        if formatted.is_empty() {
+            "0ms".to_string()
         } else {
-            format!("{}ms", millis)
+            formatted.join(" ")
+        }
+    }
+    
+    /// 把已经过去的时长表示为相对时间，例如 `just now`、`5 minutes ago`、`2 hours ago`
+    pub fn humanize(elapsed: Duration) -> String {
+        match relative_unit(elapsed) {
+            Some((value, unit)) => format!("{} {}{} ago", value, unit, if value == 1 { "" } else { "s" }),
+            None => "just now".to_string(),
+        }
+    }
+    
+    /// 相对 `now` 描述某个时间，过去的时间为 `2 hours ago`，将来的时间为 `in 2 hours`
+    pub fn humanize_between(datetime: &DateTime<Utc>, now: &DateTime<Utc>) -> String {
+        let delta = *now - *datetime;
+        if delta >= chrono::TimeDelta::zero() {
+            return Self::humanize(delta.to_std().unwrap_or_default());
+        }
+        match relative_unit((-delta).to_std().unwrap_or_default()) {
+            Some((value, unit)) => format!("in {} {}{}", value, unit, if value == 1 { "" } else { "s" }),
+            None => "just now".to_string(),
         }
     }
     
@@ -59,11 +159,44 @@ impl TimeUtils {
     pub async fn delay(ms: u64) {
         sleep(Duration::from_millis(ms)).await;
     }
-    
-    /// 创建延迟（秒）
-    pub async fn delay_seconds(seconds: u64) {
-        sleep(Duration::from_secs(seconds)).await;
+}
+
+/// 按数字和单位的边界拆分时长，例如 `1h30m` 拆为 `1h`、`30m`
+fn split_duration_segments(input: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut prev_is_unit = false;
+    for (i, c) in input.char_indices() {
+        let is_number = c.is_ascii_digit() || c == '.';
+        if is_number && prev_is_unit {
+            segments.push(&input[start..i]);
+            start = i;
+        }
+        prev_is_unit = !is_number;
+    }
+    if start < input.len() {
+        segments.push(&input[start..]);
     }
+    segments
+}
+
+/// 相对时间使用的最大整数单位，不足 5 秒时为 `None`（“刚刚”）
+fn relative_unit(elapsed: Duration) -> Option<(u64, &'static str)> {
+    const UNITS: [(u64, &str); 6] = [
+        (365 * 86400, "year"),
+        (30 * 86400, "month"),
+        (86400, "day"),
+        (3600, "hour"),
+        (60, "minute"),
+        (1, "second"),
+    ];
+    let secs = elapsed.as_secs();
+    if secs < 5 {
+        return None;
+    }
+    UNITS.iter()
+        .find(|(size, _)| secs >= *size)
+        .map(|(size, unit)| (secs / size, *unit))
 }
 
 /// 性能测量器
@@ -104,6 +237,156 @@ impl Drop for PerformanceTimer {
     }
 }
 
+/// 秒表的一段计时
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lap {
+    pub name: String,
+    pub duration: Duration,
+}
+
+/// 秒表统计：总运行时间和各段的最短/平均/最长耗时，没有分段时均为 0
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StopwatchSummary {
+    pub total: Duration,
+    pub laps: usize,
+    pub min: Duration,
+    pub avg: Duration,
+    pub max: Duration,
+}
+
+impl std::fmt::Display for StopwatchSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "总计 {}，{} 段，最短 {}，平均 {}，最长 {}",
+            TimeUtils::format_duration(self.total),
+            self.laps,
+            TimeUtils::format_duration(self.min),
+            TimeUtils::format_duration(self.avg),
+            TimeUtils::format_duration(self.max),
+        )
+    }
+}
+
+/// 分段计时的秒表
+///
+/// 在 [`PerformanceTimer`] 的基础上支持计圈（`lap`）和暂停/恢复，暂停期间不计时。
+/// 设置了 [`PerformanceLogger`] 时，`finish` 会把每一段和总耗时记录为性能指标。
+pub struct Stopwatch {
+    name: String,
+    clock: Arc<dyn Clock>,
+    /// 运行中时为本次恢复计时的时间，暂停时为 `None`
+    running_since: Option<Instant>,
+    /// 之前各次运行累计的时间
+    accumulated: Duration,
+    /// 上一次计圈时的总运行时间
+    last_lap_at: Duration,
+    laps: Vec<Lap>,
+    logger: Option<Arc<PerformanceLogger>>,
+}
+
+impl Stopwatch {
+    /// 创建并立即开始计时
+    pub fn start(name: &str) -> Self {
+        Self::start_with_clock(name, system_clock())
+    }
+    
+    /// 按指定时钟创建并开始计时
+    pub fn start_with_clock(name: &str, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            name: name.to_string(),
+            running_since: Some(clock.now()),
+            clock,
+            accumulated: Duration::ZERO,
+            last_lap_at: Duration::ZERO,
+            laps: Vec::new(),
+            logger: None,
+        }
+    }
+    
+    /// 结束时通过性能日志记录各段耗时
+    pub fn with_logger(mut self, logger: Arc<PerformanceLogger>) -> Self {
+        self.logger = Some(logger);
+        self
+    }
+    
+    /// 总运行时间，不含暂停的时间
+    pub fn elapsed(&self) -> Duration {
+        let current = self.running_since
+            .map(|since| self.clock.now().saturating_duration_since(since))
+            .unwrap_or_default();
+        self.accumulated + current
+    }
+    
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+    
+    /// 结束当前一段并开始下一段，返回这一段的运行时间
+    pub fn lap(&mut self, name: &str) -> Duration {
+        let elapsed = self.elapsed();
+        let duration = elapsed - self.last_lap_at;
+        self.last_lap_at = elapsed;
+        self.laps.push(Lap { name: name.to_string(), duration });
+        duration
+    }
+    
+    /// 暂停计时，已经暂停时不做任何事
+    pub fn pause(&mut self) {
+        if let Some(since) = self.running_since.take() {
+            self.accumulated += self.clock.now().saturating_duration_since(since);
+        }
+    }
+    
+    /// 恢复计时，正在运行时不做任何事
+    pub fn resume(&mut self) {
+        if self.running_since.is_none() {
+            self.running_since = Some(self.clock.now());
+        }
+    }
+    
+    pub fn laps(&self) -> &[Lap] {
+        &self.laps
+    }
+    
+    /// 当前的统计
+    pub fn summary(&self) -> StopwatchSummary {
+        let durations = self.laps.iter().map(|lap| lap.duration);
+        let count = self.laps.len();
+        StopwatchSummary {
+            total: self.elapsed(),
+            laps: count,
+            min: durations.clone().min().unwrap_or_default(),
+            avg: if count == 0 { Duration::ZERO } else { durations.clone().sum::<Duration>() / count as u32 },
+            max: durations.max().unwrap_or_default(),
+        }
+    }
+    
+    /// 停止计时并返回统计，设置了性能日志时记录 `<名称>.<分段>` 和 `<名称>.total` 指标（毫秒）
+    pub async fn finish(mut self) -> StopwatchSummary {
+        self.pause();
+        let summary = self.summary();
+        if let Some(logger) = &self.logger {
+            for lap in &self.laps {
+                let metric = format!("{}.{}", self.name, lap.name);
+                logger.record_metric(&metric, lap.duration.as_secs_f64() * 1000.0, "ms").await;
+            }
+            let metric = format!("{}.total", self.name);
+            logger.record_metric(&metric, summary.total.as_secs_f64() * 1000.0, "ms").await;
+        }
+        summary
+    }
+    
+    /// 打印每一段的耗时和统计
+    pub fn print_summary(&self) {
+        println!("{} 分段耗时:", self.name);
+        for lap in &self.laps {
+            println!("  {}: {}", lap.name, TimeUtils::format_duration(lap.duration));
+        }
+        println!("{} {}", self.name, self.summary());
+    }
+}
+
 /// 异步定时器
 pub struct AsyncTimer {
     interval: Interval,
@@ -134,6 +417,121 @@ impl AsyncTimer {
     }
 }
 
+/// 错过触发时间时的处理方式，对应 tokio 的 `MissedTickBehavior`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissedTickPolicy {
+    /// 连续触发补上错过的每一次，之后回到原来的时间表
+    #[default]
+    Burst,
+    /// 放弃错过的触发，下一次对齐到原时间表中当前时间之后的第一个时刻
+    Skip,
+    /// 从当前时间重新开始计算周期
+    Delay,
+}
+
+/// 一次触发：计划时间和实际触发时比计划晚了多少
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tick {
+    pub scheduled: Instant,
+    pub lateness: Duration,
+}
+
+/// 周期定时器的延迟统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TickStats {
+    pub ticks: u64,
+    /// 晚了至少一个周期的触发次数
+    pub missed: u64,
+    /// `Skip` 策略放弃的触发次数
+    pub skipped: u64,
+    pub total_lateness: Duration,
+    pub max_lateness: Duration,
+}
+
+impl TickStats {
+    /// 平均延迟
+    pub fn avg_lateness(&self) -> Duration {
+        if self.ticks == 0 {
+            Duration::ZERO
+        } else {
+            self.total_lateness / self.ticks as u32
+        }
+    }
+}
+
+/// 按绝对时间触发的周期定时器
+///
+/// 第 n 次触发的计划时间是 `start + n * period`，任务本身的耗时不会让后续触发逐渐推迟；
+/// 只有晚了一整个周期以上时才按 [`MissedTickPolicy`] 处理。第一次 `tick` 立即完成。
+pub struct DriftFreeInterval {
+    clock: Arc<dyn Clock>,
+    period: Duration,
+    next: Instant,
+    policy: MissedTickPolicy,
+    stats: TickStats,
+}
+
+impl DriftFreeInterval {
+    pub fn new(period: Duration) -> Self {
+        Self::with_clock(period, system_clock())
+    }
+    
+    /// 按指定时钟计时
+    pub fn with_clock(period: Duration, clock: Arc<dyn Clock>) -> Self {
+        assert!(!period.is_zero(), "定时器周期不能为0");
+        Self {
+            next: clock.now(),
+            clock,
+            period,
+            policy: MissedTickPolicy::default(),
+            stats: TickStats::default(),
+        }
+    }
+    
+    /// 设置错过触发时间时的处理方式
+    pub fn with_policy(mut self, policy: MissedTickPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+    
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+    
+    pub fn stats(&self) -> TickStats {
+        self.stats
+    }
+    
+    /// 等待下一次触发，取消等待不会影响时间表
+    pub async fn tick(&mut self) -> Tick {
+        let scheduled = self.next;
+        self.clock.sleep_until(scheduled).await;
+        let now = self.clock.now();
+        let lateness = now.saturating_duration_since(scheduled);
+        
+        self.stats.ticks += 1;
+        self.stats.total_lateness += lateness;
+        self.stats.max_lateness = self.stats.max_lateness.max(lateness);
+        
+        self.next = if lateness < self.period {
+            scheduled + self.period
+        } else {
+            self.stats.missed += 1;
+            match self.policy {
+                MissedTickPolicy::Burst => scheduled + self.period,
+                MissedTickPolicy::Skip => {
+                    let behind = (lateness.as_nanos() / self.period.as_nanos()) as u32;
+                    self.stats.skipped += u64::from(behind);
+                    scheduled + self.period * (behind + 1)
+                }
+                MissedTickPolicy::Delay => now + self.period,
+            }
+        };
+        
+        Tick { scheduled, lateness }
+    }
+}
+
 /// 超时包装器
 pub struct TimeoutWrapper;
 
@@ -173,35 +571,41 @@ impl std::error::Error for TimeoutError {}
 pub struct TimeWindow {
     start: Instant,
     duration: Duration,
+    clock: Arc<dyn Clock>,
 }
 
 impl TimeWindow {
     /// 创建新的时间窗口
     pub fn new(duration: Duration) -> Self {
+        Self::with_clock(duration, system_clock())
+    }
+    
+    /// 创建按指定时钟计时的时间窗口
+    pub fn with_clock(duration: Duration, clock: Arc<dyn Clock>) -> Self {
         Self {
-            start: Instant::now(),
+            start: clock.now(),
             duration,
+            clock,
         }
     }
     
     /// 检查是否在窗口内
     pub fn is_within_window(&self) -> bool {
-        self.start.elapsed() < self.duration
+        self.elapsed() < self.duration
     }
     
     /// 获取剩余时间
     pub fn remaining_time(&self) -> Duration {
-        let elapsed = self.start.elapsed();
-        if elapsed < self.duration {
-            self.duration - elapsed
-        } else {
-            Duration::from_secs(0)
-        }
+        self.duration.saturating_sub(self.elapsed())
     }
     
     /// 重置时间窗口
     pub fn reset(&mut self) {
-        self.start = Instant::now();
+        self.start = self.clock.now();
+    }
+    
+    fn elapsed(&self) -> Duration {
+        self.clock.now().saturating_duration_since(self.start)
     }
 }
 
@@ -211,8 +615,25 @@ pub async fn time_utils_example() -> Result<(), anyhow::Error> {
     
     // 基本时间操作
     let timestamp = TimeUtils::current_timestamp();
-    println!("当前时间戳: {}", timestamp);
+    println!("当前时间戳: {}（毫秒 {}）", timestamp, TimeUtils::current_timestamp_ms());
     println!("格式化时间: {}", TimeUtils::format_timestamp(timestamp));
+    println!("ISO 8601: {}", TimeUtils::format_iso8601(&TimeUtils::to_datetime(timestamp)));
+    for timezone in ["Asia/Shanghai", "America/New_York", "Europe/London"] {
+        println!("{}: {}", timezone, TimeUtils::format_in_timezone(timestamp, timezone)?);
+    }
+    // 夏令时开始的那天凌晨 2 点到 3 点在纽约不存在
+    for local in ["2024-03-10 01:30:00", "2024-03-10 02:30:00"] {
+        match TimeUtils::local_to_utc(local, "America/New_York") {
+            Ok(utc) => println!("纽约 {} = {}", local, TimeUtils::format_iso8601(&utc)),
+            Err(e) => println!("纽约 {}: {}", local, e),
+        }
+    }
+    
+    // 时长解析和相对时间
+    let duration = TimeUtils::parse_duration("1h30m")?;
+    println!("1h30m = {} 秒，格式化为 {}", duration.as_secs(), TimeUtils::format_duration(duration));
+    let deployed = TimeUtils::parse_iso8601("2024-06-01T08:00:00+08:00")?;
+    println!("部署于 {}（{}）", TimeUtils::format_iso8601(&deployed), TimeUtils::humanize_between(&deployed, &Utc::now()));
     
     // 性能测量
     {
@@ -223,7 +644,7 @@ pub async fn time_utils_example() -> Result<(), anyhow::Error> {
     // 手动性能测量
     let timer = PerformanceTimer::new("手动测量");
     TimeUtils::delay(200).await;
-    timer.print_elapsed();
+    println!("手动测量已经过 {}ms", timer.elapsed_ms());
     
     // 异步定时器
     let mut async_timer = AsyncTimer::new("示例定时器", Duration::from_millis(500));
@@ -231,6 +652,7 @@ pub async fn time_utils_example() -> Result<(), anyhow::Error> {
         async_timer.wait().await;
         println!("定时器触发第 {} 次", i);
     }
+    println!("定时器共触发 {} 次", async_timer.count());
     
     // 超时包装器
     let result = TimeoutWrapper::with_timeout(
@@ -247,13 +669,53 @@ pub async fn time_utils_example() -> Result<(), anyhow::Error> {
     }
     
     // 时间窗口
-    let mut window = TimeWindow::new(Duration::from_millis(1000));
+    let window = TimeWindow::new(Duration::from_millis(1000));
     println!("时间窗口剩余时间: {:?}", window.remaining_time());
     
     TimeUtils::delay(500).await;
     println!("时间窗口剩余时间: {:?}", window.remaining_time());
     println!("是否在窗口内: {}", window.is_within_window());
     
+    // 任务耗时 20ms、周期 50ms：每次睡眠一个周期会逐渐漂移，按绝对时间触发则不会
+    let period = Duration::from_millis(50);
+    let start = Instant::now();
+    for _ in 0..5 {
+        TimeUtils::delay(20).await;
+        sleep(period).await;
+    }
+    println!("sleep 循环 5 次耗时: {}", TimeUtils::format_duration(start.elapsed()));
+    let start = Instant::now();
+    let mut interval = DriftFreeInterval::new(period);
+    for _ in 0..6 {
+        interval.tick().await;
+        TimeUtils::delay(20).await;
+    }
+    let stats = interval.stats();
+    println!("DriftFreeInterval 5 个周期耗时: {}，平均延迟 {:?}，最大延迟 {:?}",
+        TimeUtils::format_duration(start.elapsed()), stats.avg_lateness(), stats.max_lateness);
+    
+    // 任务卡住 3.5 个周期后，不同策略处理错过的触发
+    for policy in [MissedTickPolicy::Burst, MissedTickPolicy::Skip, MissedTickPolicy::Delay] {
+        let mut interval = DriftFreeInterval::new(Duration::from_millis(20)).with_policy(policy);
+        interval.tick().await;
+        sleep(interval.period() * 7 / 2).await;
+        for _ in 0..3 {
+            interval.tick().await;
+        }
+        let stats = interval.stats();
+        println!("{:?}: 错过 {} 次，放弃 {} 次，最大延迟 {:?}", policy, stats.missed, stats.skipped, stats.max_lateness);
+    }
+    
+    // 虚拟时钟：不用真的等待一小时
+    let clock = MockClock::new();
+    let mut window = TimeWindow::with_clock(Duration::from_secs(3600), Arc::new(clock.clone()));
+    clock.advance(Duration::from_secs(3599));
+    println!("虚拟时间过去 {:?} 后剩余: {:?}", clock.elapsed(), window.remaining_time());
+    clock.advance(Duration::from_secs(1));
+    println!("虚拟时间过去 {:?} 后是否在窗口内: {}", clock.elapsed(), window.is_within_window());
+    window.reset();
+    println!("重置后剩余: {:?}", window.remaining_time());
+    
     Ok(())
 }
 
@@ -273,6 +735,73 @@ mod tests {
         let formatted = TimeUtils::format_duration(duration);
         assert!(formatted.contains("1s"));
         assert!(formatted.contains("234ms"));
+        assert_eq!(TimeUtils::format_duration(Duration::from_secs(5400)), "1h 30m");
+        assert_eq!(TimeUtils::format_duration(Duration::from_secs(90061)), "1d 1h 1m 1s");
+        assert_eq!(TimeUtils::format_duration(Duration::ZERO), "0ms");
+    }
+    
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(TimeUtils::parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+        assert_eq!(TimeUtils::parse_duration("2m 30s").unwrap(), Duration::from_secs(150));
+        assert_eq!(TimeUtils::parse_duration("1d2h").unwrap(), Duration::from_secs(93600));
+        assert_eq!(TimeUtils::parse_duration("1.5h").unwrap(), Duration::from_secs(5400));
+        assert_eq!(TimeUtils::parse_duration("1s250ms").unwrap(), Duration::from_millis(1250));
+        assert_eq!(TimeUtils::parse_duration("45").unwrap(), Duration::from_secs(45));
+        for input in ["", "1h30", "abc", "1x", "h30m"] {
+            assert!(TimeUtils::parse_duration(input).is_err(), "{:?}", input);
+        }
+        
+        // 格式化结果可以原样解析回来
+        for secs in [1, 59, 61, 3600, 5400, 90061] {
+            let duration = Duration::from_secs(secs);
+            assert_eq!(TimeUtils::parse_duration(&TimeUtils::format_duration(duration)).unwrap(), duration);
+        }
+    }
+    
+    #[test]
+    fn test_humanize() {
+        assert_eq!(TimeUtils::humanize(Duration::from_secs(3)), "just now");
+        assert_eq!(TimeUtils::humanize(Duration::from_secs(45)), "45 seconds ago");
+        assert_eq!(TimeUtils::humanize(Duration::from_secs(60)), "1 minute ago");
+        assert_eq!(TimeUtils::humanize(Duration::from_secs(2 * 3600 + 59 * 60)), "2 hours ago");
+        assert_eq!(TimeUtils::humanize(Duration::from_secs(3 * 86400)), "3 days ago");
+        assert_eq!(TimeUtils::humanize(Duration::from_secs(400 * 86400)), "1 year ago");
+        
+        let now = TimeUtils::parse_iso8601("2024-06-01T12:00:00Z").unwrap();
+        let later = TimeUtils::parse_iso8601("2024-06-01T14:00:00Z").unwrap();
+        assert_eq!(TimeUtils::humanize_between(&now, &later), "2 hours ago");
+        assert_eq!(TimeUtils::humanize_between(&later, &now), "in 2 hours");
+    }
+    
+    #[test]
+    fn test_iso8601_round_trip() {
+        let datetime = TimeUtils::parse_iso8601("2024-01-02T11:04:05+08:00").unwrap();
+        assert_eq!(TimeUtils::format_iso8601(&datetime), "2024-01-02T03:04:05Z");
+        assert_eq!(datetime, TimeUtils::to_datetime(1704164645));
+        assert_eq!(TimeUtils::parse_iso8601("2024-01-02T03:04:05").unwrap(), datetime);
+        
+        let precise = TimeUtils::parse_iso8601("2024-01-02T03:04:05.250Z").unwrap();
+        assert_eq!(TimeUtils::format_iso8601(&precise), "2024-01-02T03:04:05.250Z");
+        assert!(TimeUtils::parse_iso8601("2024-13-02T03:04:05Z").is_err());
+        assert!(TimeUtils::parse_iso8601("yesterday").is_err());
+    }
+    
+    #[test]
+    fn test_timezone_conversions() {
+        let timestamp = 1704164645; // 2024-01-02T03:04:05Z
+        assert_eq!(TimeUtils::format_timestamp(timestamp), "2024-01-02 03:04:05");
+        assert_eq!(TimeUtils::format_in_timezone(timestamp, "Asia/Shanghai").unwrap(), "2024-01-02 11:04:05 CST");
+        assert_eq!(TimeUtils::format_in_timezone(timestamp, "America/New_York").unwrap(), "2024-01-01 22:04:05 EST");
+        assert!(TimeUtils::format_in_timezone(timestamp, "Mars/Olympus").is_err());
+        
+        // 夏令时：纽约 7 月是 EDT（UTC-4）
+        let summer = TimeUtils::local_to_utc("2024-07-04 12:00:00", "America/New_York").unwrap();
+        assert_eq!(TimeUtils::format_iso8601(&summer), "2024-07-04T16:00:00Z");
+        assert_eq!(TimeUtils::to_timezone(&summer, "Asia/Tokyo").unwrap().to_rfc3339(), "2024-07-05T01:00:00+09:00");
+        
+        // 2024-03-10 02:30 在纽约因为夏令时跳过而不存在
+        assert!(TimeUtils::local_to_utc("2024-03-10 02:30:00", "America/New_York").is_err());
     }
     
     #[tokio::test]
@@ -296,12 +825,153 @@ mod tests {
         assert_eq!(result.unwrap(), "成功");
     }
     
+    #[test]
+    fn test_stopwatch_laps_and_pause() {
+        let clock = MockClock::new();
+        let mut stopwatch = Stopwatch::start_with_clock("管道", Arc::new(clock.clone()));
+        
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(stopwatch.lap("读取"), Duration::from_millis(100));
+        
+        clock.advance(Duration::from_millis(50));
+        stopwatch.pause();
+        assert!(!stopwatch.is_running());
+        // 暂停期间不计时
+        clock.advance(Duration::from_secs(10));
+        stopwatch.resume();
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(stopwatch.lap("转换"), Duration::from_millis(300));
+        
+        clock.advance(Duration::from_millis(200));
+        stopwatch.lap("写入");
+        
+        let summary = stopwatch.summary();
+        assert_eq!(summary, StopwatchSummary {
+            total: Duration::from_millis(600),
+            laps: 3,
+            min: Duration::from_millis(100),
+            avg: Duration::from_millis(200),
+            max: Duration::from_millis(300),
+        });
+        assert_eq!(summary.to_string(), "总计 600ms，3 段，最短 100ms，平均 200ms，最长 300ms");
+        let names: Vec<&str> = stopwatch.laps().iter().map(|lap| lap.name.as_str()).collect();
+        assert_eq!(names, vec!["读取", "转换", "写入"]);
+        assert_eq!(Stopwatch::start_with_clock("空", Arc::new(clock)).summary().avg, Duration::ZERO);
+    }
+    
     #[tokio::test]
-    async fn test_time_window() {
-        let window = TimeWindow::new(Duration::from_millis(100));
+    async fn test_stopwatch_finish_logs_metrics() {
+        use super::super::logging::{AsyncLogger, LogConfig, LogEntry, LogFormat, LogOutput};
+        
+        let path = std::env::temp_dir().join(format!("august-stopwatch-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let logger = Arc::new(AsyncLogger::new(LogConfig {
+            format: LogFormat::Json,
+            output: LogOutput::File(path.to_string_lossy().into_owned()),
+            ..LogConfig::default()
+        }));
+        
+        let clock = MockClock::new();
+        let mut stopwatch = Stopwatch::start_with_clock("导入", Arc::new(clock.clone()))
+            .with_logger(Arc::new(PerformanceLogger::new(Arc::clone(&logger))));
+        clock.advance(Duration::from_millis(40));
+        stopwatch.lap("解析");
+        clock.advance(Duration::from_millis(60));
+        stopwatch.lap("保存");
+        
+        let summary = stopwatch.finish().await;
+        assert_eq!(summary.total, Duration::from_millis(100));
+        logger.flush().await;
+        
+        let metrics: Vec<(String, String)> = std::fs::read_to_string(&path).unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<LogEntry>(line).unwrap())
+            .map(|entry| (entry.fields["metric"].clone(), entry.fields["value"].clone()))
+            .collect();
+        assert_eq!(metrics, vec![
+            ("导入.解析".to_string(), "40".to_string()),
+            ("导入.保存".to_string(), "60".to_string()),
+            ("导入.total".to_string(), "100".to_string()),
+        ]);
+        std::fs::remove_file(&path).unwrap();
+    }
+    
+    /// 虚拟时间下依次取 `count` 次已经到期的触发，未到期时返回已取到的
+    fn ready_ticks(interval: &mut DriftFreeInterval, count: usize) -> Vec<Tick> {
+        use futures::FutureExt;
+        (0..count).map_while(|_| interval.tick().now_or_never()).collect()
+    }
+    
+    #[test]
+    fn test_drift_free_interval_keeps_schedule() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let mut interval = DriftFreeInterval::with_clock(Duration::from_secs(10), Arc::new(clock.clone()));
+        
+        // 每次任务耗时 3 秒，触发时间仍然是 0、10、20、30 秒
+        for n in 0..4u32 {
+            let tick = ready_ticks(&mut interval, 1)[0];
+            assert_eq!(tick.scheduled, start + Duration::from_secs(10) * n);
+            clock.advance(Duration::from_secs(3));
+            assert!(ready_ticks(&mut interval, 1).is_empty());
+            clock.advance(Duration::from_secs(7));
+        }
+        let stats = interval.stats();
+        assert_eq!((stats.ticks, stats.missed, stats.max_lateness), (4, 0, Duration::ZERO));
+        
+        // 晚了不到一个周期时下一次仍按原时间表
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(ready_ticks(&mut interval, 1)[0].lateness, Duration::from_secs(4));
+        clock.advance(Duration::from_secs(6));
+        assert_eq!(ready_ticks(&mut interval, 1)[0].scheduled, start + Duration::from_secs(50));
+    }
+    
+    #[test]
+    fn test_missed_tick_policies() {
+        let run = |policy| {
+            let clock = MockClock::new();
+            let start = clock.now();
+            let mut interval = DriftFreeInterval::with_clock(Duration::from_secs(10), Arc::new(clock.clone()))
+                .with_policy(policy);
+            ready_ticks(&mut interval, 1);
+            // 任务卡住 35 秒，错过了 10、20、30 秒三次触发
+            clock.advance(Duration::from_secs(35));
+            let ready: Vec<u64> = ready_ticks(&mut interval, 10).iter()
+                .map(|tick| (tick.scheduled - start).as_secs())
+                .collect();
+            clock.advance(Duration::from_secs(10));
+            let next = (ready_ticks(&mut interval, 1)[0].scheduled - start).as_secs();
+            (ready, next, interval.stats())
+        };
+        
+        let (ready, next, stats) = run(MissedTickPolicy::Burst);
+        assert_eq!((ready, next), (vec![10, 20, 30], 40));
+        assert_eq!((stats.missed, stats.skipped, stats.max_lateness), (2, 0, Duration::from_secs(25)));
+        
+        let (ready, next, stats) = run(MissedTickPolicy::Skip);
+        assert_eq!((ready, next), (vec![10], 40));
+        assert_eq!((stats.missed, stats.skipped), (1, 2));
+        
+        let (ready, next, stats) = run(MissedTickPolicy::Delay);
+        assert_eq!((ready, next), (vec![10], 45));
+        assert_eq!((stats.missed, stats.skipped), (1, 0));
+        assert_eq!(stats.avg_lateness(), Duration::from_secs(25) / 3);
+    }
+    
+    #[test]
+    fn test_time_window() {
+        let clock = MockClock::new();
+        let mut window = TimeWindow::with_clock(Duration::from_millis(100), Arc::new(clock.clone()));
         assert!(window.is_within_window());
         
-        TimeUtils::delay(150).await;
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(window.remaining_time(), Duration::from_millis(40));
+        
+        clock.advance(Duration::from_millis(90));
         assert!(!window.is_within_window());
+        assert_eq!(window.remaining_time(), Duration::ZERO);
+        
+        window.reset();
+        assert!(window.is_within_window());
     }
 }
@@ -0,0 +1,48 @@
+//! 各月份项目共用的基准测试结果数据库
+//!
+//! - [`RunRecord`]：一次基准测试运行，包含所属项目、git 提交、机器信息和若干条测量结果
+//! - [`BenchDb`]：把运行记录保存到本地 SQLite 文件，并按项目和测试名查询历史
+//! - [`trend`]：根据历史数据计算趋势（最新值、与上次相比的变化、最佳值）
+//!
+//! 数据库文件默认位于仓库根目录的 `bench_results.sqlite`，可以用 `BENCHDB` 环境变量覆盖。
+
+pub mod machine;
+pub mod store;
+pub mod trend;
+
+pub use machine::{git_commit, Machine};
+pub use store::{BenchDb, Filter, HistoryPoint, Measurement, RunRecord};
+pub use trend::Trend;
+
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// 指定数据库路径的环境变量
+pub const ENV_VAR: &str = "BENCHDB";
+
+/// 数据库错误
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("SQLite 错误: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("无法创建目录 {path}: {source}")]
+    CreateDir {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// 默认数据库路径：`BENCHDB` 环境变量，未设置时为仓库根目录下的 `bench_results.sqlite`
+pub fn default_path() -> PathBuf {
+    match std::env::var_os(ENV_VAR) {
+        Some(path) if !path.is_empty() => PathBuf::from(path),
+        _ => {
+            let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+            manifest_dir.parent().unwrap_or(manifest_dir).join("bench_results.sqlite")
+        }
+    }
+}
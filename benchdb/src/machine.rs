@@ -0,0 +1,58 @@
+//! 运行环境信息
+
+use std::path::Path;
+use std::process::Command;
+
+/// 运行基准测试的机器，不同机器上的结果不能直接比较
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    pub hostname: String,
+    pub os: String,
+    pub arch: String,
+    pub cpus: u32,
+}
+
+impl Machine {
+    /// 当前机器的信息，主机名取不到时为 `unknown`
+    pub fn current() -> Self {
+        Self {
+            hostname: hostname().unwrap_or_else(|| "unknown".to_string()),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            cpus: std::thread::available_parallelism().map_or(1, |n| n.get() as u32),
+        }
+    }
+}
+
+fn hostname() -> Option<String> {
+    let from_env = std::env::var("HOSTNAME").or_else(|_| std::env::var("COMPUTERNAME")).ok();
+    let from_file = || std::fs::read_to_string("/etc/hostname").ok();
+    let from_command = || {
+        let output = Command::new("hostname").output().ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    from_env
+        .or_else(from_file)
+        .or_else(from_command)
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// `dir` 所在仓库当前的短提交哈希，工作区有未提交的修改时追加 `-dirty`
+///
+/// 不在 git 仓库中或没有安装 git 时返回 `None`
+pub fn git_commit(dir: &Path) -> Option<String> {
+    let git = |args: &[&str]| Command::new("git").args(args).current_dir(dir).output().ok();
+
+    let output = git(&["rev-parse", "--short", "HEAD"])?;
+    if !output.status.success() {
+        return None;
+    }
+    let mut commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let dirty = git(&["status", "--porcelain", "--untracked-files=no"])
+        .is_some_and(|status| status.status.success() && !status.stdout.is_empty());
+    if dirty {
+        commit.push_str("-dirty");
+    }
+    Some(commit)
+}
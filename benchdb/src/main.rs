@@ -0,0 +1,148 @@
+//! 查看基准测试结果数据库
+//!
+//! ```text
+//! benchdb report --crate october-code --last 10
+//! benchdb history --crate october-code --test avg/optimized
+//! ```
+
+use benchdb::trend::format_nanos;
+use benchdb::{BenchDb, Filter};
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::process;
+
+/// 基准测试结果数据库
+#[derive(Parser)]
+#[command(name = "benchdb")]
+#[command(about = "查看各月份项目记录的基准测试结果和性能趋势")]
+struct Cli {
+    /// 数据库文件，默认为仓库根目录下的 bench_results.sqlite
+    #[arg(long, env = benchdb::ENV_VAR, global = true)]
+    db: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// 按测试汇总最近几次运行的趋势
+    Report {
+        /// 只看某个项目，如 october-code
+        #[arg(long = "crate")]
+        crate_name: Option<String>,
+        /// 只看测试名包含该字符串的测试
+        #[arg(long)]
+        test: Option<String>,
+        /// 只看某台机器上的结果
+        #[arg(long)]
+        host: Option<String>,
+        /// 每个测试取最近几次运行
+        #[arg(long, default_value_t = 10)]
+        last: usize,
+        /// 比上次变慢超过该百分比时标记为回归
+        #[arg(long, default_value_t = 10.0)]
+        threshold: f64,
+    },
+    /// 列出某个测试的历史记录
+    History {
+        #[arg(long = "crate")]
+        crate_name: String,
+        #[arg(long)]
+        test: String,
+        #[arg(long)]
+        host: Option<String>,
+        #[arg(long, default_value_t = 20)]
+        last: usize,
+    },
+}
+
+fn report(db: &BenchDb, filter: &Filter<'_>, last: usize, threshold: f64) -> benchdb::Result<()> {
+    let trends = db.trends(filter, last.max(1))?;
+    if trends.is_empty() {
+        println!("没有符合条件的记录");
+        return Ok(());
+    }
+    if let Some(time) = db.last_recorded_at()? {
+        println!("共 {} 次运行，最近一次: {}\n", db.run_count()?, time.format("%Y-%m-%d %H:%M:%S UTC"));
+    }
+
+    println!(
+        "{:<14} {:<22} {:>4} {:>10} {:>8} {:>10} {:>10}  趋势",
+        "crate", "test", "runs", "latest", "change", "best", "mean"
+    );
+    let mut regressions = 0;
+    for trend in &trends {
+        let change = trend.change();
+        let regressed = change.is_some_and(|c| c * 100.0 > threshold);
+        if regressed {
+            regressions += 1;
+        }
+        println!(
+            "{:<14} {:<22} {:>4} {:>10} {:>8} {:>10} {:>10}  {}{}",
+            trend.crate_name,
+            trend.test,
+            trend.points.len(),
+            trend.latest().map_or("-".to_string(), format_nanos),
+            change.map_or("-".to_string(), |c| format!("{:+.1}%", c * 100.0)),
+            trend.best().map_or("-".to_string(), format_nanos),
+            trend.mean().map_or("-".to_string(), format_nanos),
+            trend.sparkline(),
+            if regressed { "  REGRESSION" } else { "" },
+        );
+    }
+    if regressions > 0 {
+        println!("\n{} 个测试比上次变慢超过 {}%", regressions, threshold);
+    }
+    Ok(())
+}
+
+fn history(db: &BenchDb, crate_name: &str, test: &str, host: Option<&str>, last: usize) -> benchdb::Result<()> {
+    let points = db.history(crate_name, test, host, last.max(1))?;
+    if points.is_empty() {
+        println!("没有 {} / {} 的记录", crate_name, test);
+        return Ok(());
+    }
+    println!(
+        "{:>5}  {:<19}  {:<14} {:<12} {:>10} {:>10}  params",
+        "run", "time (UTC)", "commit", "host", "per iter", "iters"
+    );
+    for p in points {
+        println!(
+            "{:>5}  {:<19}  {:<14} {:<12} {:>10} {:>10}  {}",
+            p.run_id,
+            p.recorded_at.format("%Y-%m-%d %H:%M:%S"),
+            p.git_commit.as_deref().unwrap_or("-"),
+            p.hostname,
+            format_nanos(p.nanos),
+            p.iterations,
+            p.params,
+        );
+    }
+    Ok(())
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let path = cli.db.unwrap_or_else(benchdb::default_path);
+    if !path.exists() {
+        eprintln!("数据库不存在: {}（运行带 --benchdb 的基准测试后生成）", path.display());
+        process::exit(1);
+    }
+
+    let result = BenchDb::open(&path).and_then(|db| match &cli.command {
+        Commands::Report { crate_name, test, host, last, threshold } => {
+            let filter = Filter {
+                crate_name: crate_name.as_deref(),
+                test: test.as_deref(),
+                hostname: host.as_deref(),
+            };
+            report(&db, &filter, *last, *threshold)
+        }
+        Commands::History { crate_name, test, host, last } => history(&db, crate_name, test, host.as_deref(), *last),
+    });
+    if let Err(e) = result {
+        eprintln!("错误: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,307 @@
+//! SQLite 存储
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::machine::{git_commit, Machine};
+use crate::trend::Trend;
+use crate::{Error, Result};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id          INTEGER PRIMARY KEY,
+    crate       TEXT NOT NULL,
+    recorded_at TEXT NOT NULL,
+    git_commit  TEXT,
+    hostname    TEXT NOT NULL,
+    os          TEXT NOT NULL,
+    arch        TEXT NOT NULL,
+    cpus        INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS measurements (
+    run_id     INTEGER NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+    test       TEXT NOT NULL,
+    nanos      REAL NOT NULL,
+    iterations INTEGER NOT NULL,
+    params     TEXT NOT NULL DEFAULT ''
+);
+CREATE INDEX IF NOT EXISTS measurements_by_test ON measurements(test, run_id);
+";
+
+/// 一条测量结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    /// 测试名，同一项目内唯一，如 `avg/optimized`
+    pub test: String,
+    /// 每次迭代的平均耗时（纳秒）
+    pub nanos: f64,
+    pub iterations: u64,
+    /// 测试参数，如 `size=100000`，参数不同的结果不宜直接比较
+    pub params: String,
+}
+
+impl Measurement {
+    pub fn new(test: impl Into<String>, per_iteration: Duration, iterations: u64) -> Self {
+        Self {
+            test: test.into(),
+            nanos: per_iteration.as_secs_f64() * 1e9,
+            iterations,
+            params: String::new(),
+        }
+    }
+
+    pub fn with_params(mut self, params: impl Into<String>) -> Self {
+        self.params = params.into();
+        self
+    }
+}
+
+/// 一次基准测试运行
+#[derive(Debug, Clone)]
+pub struct RunRecord {
+    /// 所属项目，如 `october-code`
+    pub crate_name: String,
+    pub recorded_at: DateTime<Utc>,
+    pub git_commit: Option<String>,
+    pub machine: Machine,
+    pub measurements: Vec<Measurement>,
+}
+
+impl RunRecord {
+    /// 以当前时间、当前机器和当前目录所在的 git 提交创建运行记录
+    pub fn new(crate_name: impl Into<String>) -> Self {
+        let commit = std::env::current_dir().ok().and_then(|dir| git_commit(&dir));
+        Self {
+            crate_name: crate_name.into(),
+            recorded_at: Utc::now(),
+            git_commit: commit,
+            machine: Machine::current(),
+            measurements: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, measurement: Measurement) -> &mut Self {
+        self.measurements.push(measurement);
+        self
+    }
+}
+
+/// 历史数据中的一个点
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryPoint {
+    pub run_id: i64,
+    pub recorded_at: DateTime<Utc>,
+    pub git_commit: Option<String>,
+    pub hostname: String,
+    pub nanos: f64,
+    pub iterations: u64,
+    pub params: String,
+}
+
+/// 查询条件，字段为 `None` 时不过滤
+#[derive(Debug, Clone, Default)]
+pub struct Filter<'a> {
+    pub crate_name: Option<&'a str>,
+    /// 测试名包含该字符串即匹配
+    pub test: Option<&'a str>,
+    pub hostname: Option<&'a str>,
+}
+
+/// 基准测试结果数据库
+pub struct BenchDb {
+    conn: Connection,
+}
+
+impl BenchDb {
+    /// 打开数据库文件，不存在时创建
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|source| Error::CreateDir {
+                path: parent.to_path_buf(),
+                source,
+            })?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    /// 内存数据库，用于测试
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// 在一个事务中保存运行记录及其全部测量结果，返回运行 ID
+    pub fn record(&mut self, run: &RunRecord) -> Result<i64> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO runs (crate, recorded_at, git_commit, hostname, os, arch, cpus)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                run.crate_name,
+                run.recorded_at.to_rfc3339(),
+                run.git_commit,
+                run.machine.hostname,
+                run.machine.os,
+                run.machine.arch,
+                run.machine.cpus,
+            ],
+        )?;
+        let run_id = tx.last_insert_rowid();
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO measurements (run_id, test, nanos, iterations, params) VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for m in &run.measurements {
+                insert.execute(params![run_id, m.test, m.nanos, m.iterations as i64, m.params])?;
+            }
+        }
+        tx.commit()?;
+        Ok(run_id)
+    }
+
+    /// 运行记录总数
+    pub fn run_count(&self) -> Result<u64> {
+        let count: i64 = self.conn.query_row("SELECT COUNT(*) FROM runs", [], |row| row.get(0))?;
+        Ok(count as u64)
+    }
+
+    /// 最近一次运行的时间
+    pub fn last_recorded_at(&self) -> Result<Option<DateTime<Utc>>> {
+        let value: Option<String> = self
+            .conn
+            .query_row("SELECT recorded_at FROM runs ORDER BY id DESC LIMIT 1", [], |row| row.get(0))
+            .optional()?;
+        Ok(value.as_deref().and_then(parse_time))
+    }
+
+    /// 符合条件的 (项目, 测试名)，按项目和测试名排序
+    pub fn tests(&self, filter: &Filter<'_>) -> Result<Vec<(String, String)>> {
+        let mut statement = self.conn.prepare(
+            "SELECT DISTINCT runs.crate, measurements.test
+             FROM measurements JOIN runs ON runs.id = measurements.run_id
+             WHERE (?1 IS NULL OR runs.crate = ?1)
+               AND (?2 IS NULL OR instr(measurements.test, ?2) > 0)
+               AND (?3 IS NULL OR runs.hostname = ?3)
+             ORDER BY runs.crate, measurements.test",
+        )?;
+        let rows = statement.query_map(params![filter.crate_name, filter.test, filter.hostname], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// 某个测试最近 `limit` 次的结果，按时间从早到晚排列
+    pub fn history(&self, crate_name: &str, test: &str, hostname: Option<&str>, limit: usize) -> Result<Vec<HistoryPoint>> {
+        let mut statement = self.conn.prepare(
+            "SELECT runs.id, runs.recorded_at, runs.git_commit, runs.hostname,
+                    measurements.nanos, measurements.iterations, measurements.params
+             FROM measurements JOIN runs ON runs.id = measurements.run_id
+             WHERE runs.crate = ?1 AND measurements.test = ?2 AND (?3 IS NULL OR runs.hostname = ?3)
+             ORDER BY runs.id DESC
+             LIMIT ?4",
+        )?;
+        let rows = statement.query_map(params![crate_name, test, hostname, limit as i64], |row| {
+            let recorded_at: String = row.get(1)?;
+            Ok(HistoryPoint {
+                run_id: row.get(0)?,
+                recorded_at: parse_time(&recorded_at).unwrap_or_default(),
+                git_commit: row.get(2)?,
+                hostname: row.get(3)?,
+                nanos: row.get(4)?,
+                iterations: row.get::<_, i64>(5)? as u64,
+                params: row.get(6)?,
+            })
+        })?;
+        let mut points = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+        points.reverse();
+        Ok(points)
+    }
+
+    /// 符合条件的每个测试最近 `last` 次结果的趋势
+    pub fn trends(&self, filter: &Filter<'_>, last: usize) -> Result<Vec<Trend>> {
+        self.tests(filter)?
+            .into_iter()
+            .map(|(crate_name, test)| {
+                let points = self.history(&crate_name, &test, filter.hostname, last)?;
+                Ok(Trend::new(crate_name, test, points))
+            })
+            .collect()
+    }
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value).ok().map(|time| time.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(crate_name: &str, host: &str, measurements: &[(&str, u64)]) -> RunRecord {
+        let mut run = RunRecord::new(crate_name);
+        run.git_commit = Some("abc1234".to_string());
+        run.machine.hostname = host.to_string();
+        for &(test, nanos) in measurements {
+            run.push(Measurement::new(test, Duration::from_nanos(nanos), 10).with_params("size=100"));
+        }
+        run
+    }
+
+    #[test]
+    fn test_record_and_query_history() {
+        let mut db = BenchDb::open_in_memory().unwrap();
+        db.record(&run("october-code", "a", &[("avg/optimized", 100), ("avg/unoptimized", 300)])).unwrap();
+        db.record(&run("october-code", "a", &[("avg/optimized", 90)])).unwrap();
+        db.record(&run("august-code", "a", &[("延迟测试", 5000)])).unwrap();
+        assert_eq!(db.run_count().unwrap(), 3);
+        assert!(db.last_recorded_at().unwrap().is_some());
+
+        let history = db.history("october-code", "avg/optimized", None, 10).unwrap();
+        assert_eq!(history.iter().map(|p| p.nanos).collect::<Vec<_>>(), vec![100.0, 90.0]);
+        assert_eq!(history[0].git_commit.as_deref(), Some("abc1234"));
+        assert_eq!(history[0].params, "size=100");
+
+        // limit 取最近的几次
+        let latest = db.history("october-code", "avg/optimized", None, 1).unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].nanos, 90.0);
+    }
+
+    #[test]
+    fn test_filters() {
+        let mut db = BenchDb::open_in_memory().unwrap();
+        db.record(&run("october-code", "a", &[("avg/optimized", 100), ("freq/optimized", 300)])).unwrap();
+        db.record(&run("october-code", "b", &[("avg/optimized", 50)])).unwrap();
+        db.record(&run("august-code", "a", &[("延迟测试", 5000)])).unwrap();
+
+        assert_eq!(db.tests(&Filter::default()).unwrap().len(), 3);
+        let october = Filter { crate_name: Some("october-code"), test: Some("avg"), ..Default::default() };
+        assert_eq!(db.tests(&october).unwrap(), vec![("october-code".to_string(), "avg/optimized".to_string())]);
+
+        let host_b = Filter { hostname: Some("b"), ..Default::default() };
+        let trends = db.trends(&host_b, 10).unwrap();
+        assert_eq!(trends.len(), 1);
+        assert_eq!(trends[0].points.len(), 1);
+        assert_eq!(trends[0].latest(), Some(50.0));
+    }
+
+    #[test]
+    fn test_open_creates_file_and_persists() {
+        let dir = std::env::temp_dir().join(format!("benchdb-test-{}", std::process::id()));
+        let path = dir.join("nested").join("bench.sqlite");
+        {
+            let mut db = BenchDb::open(&path).unwrap();
+            db.record(&run("october-code", "a", &[("avg/optimized", 100)])).unwrap();
+        }
+        let db = BenchDb::open(&path).unwrap();
+        assert_eq!(db.run_count().unwrap(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,138 @@
+//! 性能趋势
+
+use crate::store::HistoryPoint;
+
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// 一个测试最近若干次运行的趋势，耗时越低越好
+#[derive(Debug, Clone)]
+pub struct Trend {
+    pub crate_name: String,
+    pub test: String,
+    /// 按时间从早到晚排列
+    pub points: Vec<HistoryPoint>,
+}
+
+impl Trend {
+    pub fn new(crate_name: String, test: String, points: Vec<HistoryPoint>) -> Self {
+        Self { crate_name, test, points }
+    }
+
+    /// 最近一次的耗时（纳秒）
+    pub fn latest(&self) -> Option<f64> {
+        self.points.last().map(|p| p.nanos)
+    }
+
+    /// 上一次的耗时（纳秒）
+    pub fn previous(&self) -> Option<f64> {
+        self.points.len().checked_sub(2).map(|index| self.points[index].nanos)
+    }
+
+    /// 最近一次相对上一次的变化比例，正数表示变慢
+    ///
+    /// 两次的参数不同时不可比较，返回 `None`
+    pub fn change(&self) -> Option<f64> {
+        let [.., previous, latest] = self.points.as_slice() else {
+            return None;
+        };
+        if previous.params != latest.params || previous.nanos <= 0.0 {
+            return None;
+        }
+        Some(latest.nanos / previous.nanos - 1.0)
+    }
+
+    /// 最短耗时
+    pub fn best(&self) -> Option<f64> {
+        self.points.iter().map(|p| p.nanos).reduce(f64::min)
+    }
+
+    /// 平均耗时
+    pub fn mean(&self) -> Option<f64> {
+        if self.points.is_empty() {
+            return None;
+        }
+        Some(self.points.iter().map(|p| p.nanos).sum::<f64>() / self.points.len() as f64)
+    }
+
+    /// 用方块字符画出耗时变化，越高越慢
+    pub fn sparkline(&self) -> String {
+        let (Some(min), Some(max)) = (
+            self.points.iter().map(|p| p.nanos).reduce(f64::min),
+            self.points.iter().map(|p| p.nanos).reduce(f64::max),
+        ) else {
+            return String::new();
+        };
+        self.points
+            .iter()
+            .map(|p| {
+                let level = if max > min {
+                    ((p.nanos - min) / (max - min) * (SPARK_CHARS.len() - 1) as f64).round() as usize
+                } else {
+                    0
+                };
+                SPARK_CHARS[level]
+            })
+            .collect()
+    }
+}
+
+/// 把纳秒格式化成合适的单位
+pub fn format_nanos(nanos: f64) -> String {
+    if nanos >= 1e9 {
+        format!("{:.2}s", nanos / 1e9)
+    } else if nanos >= 1e6 {
+        format!("{:.2}ms", nanos / 1e6)
+    } else if nanos >= 1e3 {
+        format!("{:.2}µs", nanos / 1e3)
+    } else {
+        format!("{:.0}ns", nanos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trend(values: &[(f64, &str)]) -> Trend {
+        let points = values
+            .iter()
+            .enumerate()
+            .map(|(index, &(nanos, params))| HistoryPoint {
+                run_id: index as i64 + 1,
+                recorded_at: Default::default(),
+                git_commit: None,
+                hostname: "host".to_string(),
+                nanos,
+                iterations: 1,
+                params: params.to_string(),
+            })
+            .collect();
+        Trend::new("october-code".to_string(), "avg/optimized".to_string(), points)
+    }
+
+    #[test]
+    fn test_statistics() {
+        let t = trend(&[(200.0, ""), (100.0, ""), (150.0, "")]);
+        assert_eq!(t.latest(), Some(150.0));
+        assert_eq!(t.previous(), Some(100.0));
+        assert_eq!(t.best(), Some(100.0));
+        assert_eq!(t.mean(), Some(150.0));
+        assert!((t.change().unwrap() - 0.5).abs() < 1e-9);
+        assert_eq!(t.sparkline(), "█▁▅");
+    }
+
+    #[test]
+    fn test_change_requires_same_params() {
+        assert_eq!(trend(&[(100.0, "size=1"), (200.0, "size=2")]).change(), None);
+        assert_eq!(trend(&[(100.0, "")]).change(), None);
+        assert_eq!(trend(&[]).sparkline(), "");
+    }
+
+    #[test]
+    fn test_format_nanos() {
+        assert_eq!(format_nanos(512.0), "512ns");
+        assert_eq!(format_nanos(1_500.0), "1.50µs");
+        assert_eq!(format_nanos(2_000_000.0), "2.00ms");
+        assert_eq!(format_nanos(3e9), "3.00s");
+    }
+}
@@ -0,0 +1,135 @@
+//! 配置值解析：环境变量和配置文件里常见的布尔值、时长、字节数
+
+use std::env;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+
+/// 解析布尔值：`1/true/yes/on` 和 `0/false/no/off`，不区分大小写
+pub fn parse_bool(input: &str) -> Result<bool> {
+    match input.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err(Error::parse(input, "布尔值 (true/false, yes/no, on/off, 1/0)")),
+    }
+}
+
+/// 拆出数字和单位，例如 `"1.5GB"` -> `(1.5, "gb")`
+fn split_number(input: &str) -> Option<(f64, String)> {
+    let input = input.trim();
+    let unit_start = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(input.len());
+    let value: f64 = input[..unit_start].parse().ok()?;
+    Some((value, input[unit_start..].trim().to_ascii_lowercase()))
+}
+
+/// 解析时长：`500ms`、`30s`、`5m`、`2h`、`1d`，不带单位时按秒计算
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let invalid = || Error::parse(input, "时长 (例如 500ms, 30s, 5m, 2h)");
+    let (value, unit) = split_number(input).ok_or_else(invalid)?;
+    let seconds = match unit.as_str() {
+        "ms" => value / 1000.0,
+        "" | "s" => value,
+        "m" | "min" => value * 60.0,
+        "h" => value * 3600.0,
+        "d" => value * 86400.0,
+        _ => return Err(invalid()),
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|_| invalid())
+}
+
+/// 解析字节数：`512`、`64KB`、`10MB`、`1.5GB`，按 1024 进位，`KiB` 等写法同样接受
+pub fn parse_byte_size(input: &str) -> Result<u64> {
+    let invalid = || Error::parse(input, "字节数 (例如 512, 64KB, 10MB)");
+    let (value, unit) = split_number(input).ok_or_else(invalid)?;
+    let multiplier: u64 = match unit.trim_end_matches("ib").trim_end_matches('b') {
+        "" => 1,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        "t" => 1 << 40,
+        _ => return Err(invalid()),
+    };
+    let bytes = value * multiplier as f64;
+    if bytes.is_finite() && bytes <= u64::MAX as f64 {
+        Ok(bytes as u64)
+    } else {
+        Err(invalid())
+    }
+}
+
+/// 读取并解析环境变量，未设置时返回 `None`，值不合法时返回带变量名的错误
+pub fn env_parse<T, F>(name: &str, parse: F) -> Result<Option<T>>
+where
+    F: FnOnce(&str) -> Result<T>,
+{
+    match env::var(name) {
+        Ok(value) => parse(&value).map(Some).map_err(|e| Error::Env {
+            name: name.to_string(),
+            source: Box::new(e),
+        }),
+        Err(_) => Ok(None),
+    }
+}
+
+/// 用 `FromStr` 解析环境变量
+pub fn env_var<T: FromStr>(name: &str) -> Result<Option<T>> {
+    env_parse(name, |value| {
+        value
+            .trim()
+            .parse()
+            .map_err(|_| Error::parse(value, std::any::type_name::<T>()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bool() {
+        for input in ["1", "true", "YES", " on "] {
+            assert_eq!(parse_bool(input), Ok(true));
+        }
+        for input in ["0", "False", "no", "OFF"] {
+            assert_eq!(parse_bool(input), Ok(false));
+        }
+        assert!(parse_bool("maybe").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("30"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("1d"), Ok(Duration::from_secs(86400)));
+        assert!(parse_duration("5 weeks").is_err());
+        assert!(parse_duration("ms").is_err());
+    }
+
+    #[test]
+    fn test_parse_byte_size() {
+        assert_eq!(parse_byte_size("512"), Ok(512));
+        assert_eq!(parse_byte_size("64KB"), Ok(64 * 1024));
+        assert_eq!(parse_byte_size("10mb"), Ok(10 * 1024 * 1024));
+        assert_eq!(parse_byte_size("1.5GiB"), Ok(3 * 512 * 1024 * 1024));
+        assert!(parse_byte_size("10 parsecs").is_err());
+    }
+
+    #[test]
+    fn test_env_parse() {
+        env::set_var("COMMON_TEST_ENV_PARSE", "250ms");
+        assert_eq!(
+            env_parse("COMMON_TEST_ENV_PARSE", parse_duration),
+            Ok(Some(Duration::from_millis(250)))
+        );
+        env::set_var("COMMON_TEST_ENV_PARSE_BAD", "abc");
+        let err = env_var::<u32>("COMMON_TEST_ENV_PARSE_BAD").unwrap_err();
+        assert!(err.to_string().contains("COMMON_TEST_ENV_PARSE_BAD"));
+        assert_eq!(env_var::<u32>("COMMON_TEST_ENV_UNSET"), Ok(None));
+    }
+}
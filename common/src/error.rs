@@ -0,0 +1,31 @@
+//! 公共错误类型
+
+use thiserror::Error;
+
+/// 公共组件的错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum Error {
+    /// 字符串无法解析为期望的值
+    #[error("无法把 '{input}' 解析为{expected}")]
+    Parse { input: String, expected: &'static str },
+
+    /// 环境变量的值不合法
+    #[error("环境变量 {name} 的值不合法: {source}")]
+    Env {
+        name: String,
+        #[source]
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    pub(crate) fn parse(input: &str, expected: &'static str) -> Self {
+        Error::Parse {
+            input: input.to_string(),
+            expected,
+        }
+    }
+}
+
+/// 公共组件的结果类型
+pub type Result<T> = std::result::Result<T, Error>;
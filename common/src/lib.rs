@@ -0,0 +1,20 @@
+//! 各月份项目共用的基础组件
+//!
+//! - [`error`]：公共错误类型
+//! - [`retry`]：重试策略与退避算法
+//! - [`rate_limit`]：令牌桶限流器
+//! - [`log_level`]：日志级别
+//! - [`config`]：配置值解析（布尔值、时长、字节数、环境变量）
+//!
+//! 这里只放与运行时无关的同步实现，异步项目在外面包一层即可。
+
+pub mod config;
+pub mod error;
+pub mod log_level;
+pub mod rate_limit;
+pub mod retry;
+
+pub use error::{Error, Result};
+pub use log_level::LogLevel;
+pub use rate_limit::{RateLimiter, TokenBucket};
+pub use retry::{Backoff, RetryPolicy};
@@ -0,0 +1,84 @@
+//! 日志级别
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::Error;
+
+/// 日志级别，从低到高排列，`level >= threshold` 表示应当输出
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum LogLevel {
+    Trace = 0,
+    Debug = 1,
+    #[default]
+    Info = 2,
+    Warn = 3,
+    Error = 4,
+}
+
+impl LogLevel {
+    /// 全部级别，从低到高
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Trace,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = Error;
+
+    /// 不区分大小写，`warning` 等同于 `warn`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "trace" => Ok(LogLevel::Trace),
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            _ => Err(Error::parse(s, "日志级别 (trace, debug, info, warn, error)")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordering_and_display() {
+        assert!(LogLevel::Trace < LogLevel::Debug);
+        assert!(LogLevel::Warn < LogLevel::Error);
+        let names: Vec<String> = LogLevel::ALL.iter().map(ToString::to_string).collect();
+        assert_eq!(names, ["TRACE", "DEBUG", "INFO", "WARN", "ERROR"]);
+    }
+
+    #[test]
+    fn test_parse() {
+        for level in LogLevel::ALL {
+            assert_eq!(level.as_str().parse::<LogLevel>(), Ok(level));
+            assert_eq!(level.as_str().to_lowercase().parse::<LogLevel>(), Ok(level));
+        }
+        assert_eq!(" Warning ".parse::<LogLevel>(), Ok(LogLevel::Warn));
+        assert!("verbose".parse::<LogLevel>().is_err());
+    }
+}
@@ -0,0 +1,204 @@
+//! 令牌桶限流
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 客户端数量超过这个值时清理已经回满的桶
+const PRUNE_THRESHOLD: usize = 10_000;
+/// 两次清理之间至少间隔这么久，清理的 O(n) 开销由这段时间内的请求分摊
+const PRUNE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 令牌桶：最多攒 `capacity` 个令牌，每秒补充 `refill_per_sec` 个
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_sec: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// 创建装满令牌的桶
+    pub fn new(capacity: u32, refill_per_sec: f64) -> Self {
+        Self::full_at(f64::from(capacity), refill_per_sec, Instant::now())
+    }
+
+    fn full_at(capacity: f64, refill_per_sec: f64, now: Instant) -> Self {
+        TokenBucket {
+            capacity,
+            refill_per_sec,
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// 当前可用的令牌数（向下取整）
+    pub fn available_at(&mut self, now: Instant) -> u32 {
+        self.refill(now);
+        self.tokens as u32
+    }
+
+    fn is_full(&self) -> bool {
+        self.tokens >= self.capacity
+    }
+
+    /// 取一个令牌，不够时返回还需要等待多久
+    pub fn try_acquire(&mut self) -> Result<(), Duration> {
+        self.try_acquire_at(Instant::now())
+    }
+
+    pub fn try_acquire_at(&mut self, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else if self.refill_per_sec > 0.0 {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.refill_per_sec))
+        } else {
+            Err(Duration::MAX)
+        }
+    }
+}
+
+#[derive(Default)]
+struct Buckets {
+    by_key: HashMap<String, TokenBucket>,
+    last_prune: Option<Instant>,
+}
+
+/// 按键（客户端、API key 等）分别限流，每个键一个令牌桶
+pub struct RateLimiter {
+    burst: u32,
+    refill_per_sec: f64,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    /// 每个键最多连续 `burst` 次请求，之后每秒恢复 `refill_per_sec` 次
+    pub fn new(burst: u32, refill_per_sec: f64) -> Self {
+        RateLimiter {
+            burst,
+            refill_per_sec,
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    /// 每个键在 `window` 内最多 `max_requests` 次请求
+    pub fn per_window(max_requests: u32, window: Duration) -> Self {
+        let refill_per_sec = if window.is_zero() {
+            f64::INFINITY
+        } else {
+            f64::from(max_requests) / window.as_secs_f64()
+        };
+        Self::new(max_requests, refill_per_sec)
+    }
+
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    pub fn refill_per_sec(&self) -> f64 {
+        self.refill_per_sec
+    }
+
+    /// 允许请求时返回 `Ok`，否则返回需要等待的时间
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        self.check_at(key, Instant::now())
+    }
+
+    pub fn check_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let capacity = f64::from(self.burst);
+        let mut buckets = self.buckets.lock().unwrap();
+
+        let prune_due = buckets
+            .last_prune
+            .is_none_or(|last| now.saturating_duration_since(last) >= PRUNE_INTERVAL);
+        if buckets.by_key.len() > PRUNE_THRESHOLD && prune_due {
+            // 已经回满的桶和新建的桶没有区别，不必保留
+            buckets.by_key.retain(|_, bucket| {
+                bucket.refill(now);
+                !bucket.is_full()
+            });
+            buckets.last_prune = Some(now);
+        }
+
+        buckets
+            .by_key
+            .entry(key.to_string())
+            .or_insert_with(|| TokenBucket::full_at(capacity, self.refill_per_sec, now))
+            .try_acquire_at(now)
+    }
+
+    /// 当前正在跟踪的键数量
+    pub fn tracked_keys(&self) -> usize {
+        self.buckets.lock().unwrap().by_key.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_burst_then_refill() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::full_at(2.0, 1.0, start);
+        assert!(bucket.try_acquire_at(start).is_ok());
+        assert!(bucket.try_acquire_at(start).is_ok());
+        let wait = bucket.try_acquire_at(start).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(1));
+
+        let later = start + Duration::from_millis(1500);
+        assert_eq!(bucket.available_at(later), 1);
+        assert!(bucket.try_acquire_at(later).is_ok());
+        assert!(bucket.try_acquire_at(later).is_err());
+    }
+
+    #[test]
+    fn test_zero_refill_never_recovers() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::full_at(1.0, 0.0, start);
+        assert!(bucket.try_acquire_at(start).is_ok());
+        assert_eq!(bucket.try_acquire_at(start + Duration::from_secs(3600)), Err(Duration::MAX));
+    }
+
+    #[test]
+    fn test_limiter_keys_are_independent() {
+        let limiter = RateLimiter::per_window(2, Duration::from_secs(1));
+        let now = Instant::now();
+        assert!(limiter.check_at("a", now).is_ok());
+        assert!(limiter.check_at("a", now).is_ok());
+        assert!(limiter.check_at("a", now).is_err());
+        assert!(limiter.check_at("b", now).is_ok());
+        assert!(limiter.check_at("a", now + Duration::from_millis(500)).is_ok());
+        assert_eq!(limiter.tracked_keys(), 2);
+    }
+
+    #[test]
+    fn test_prune_runs_at_most_once_per_interval() {
+        let limiter = RateLimiter::per_window(2, Duration::from_secs(1));
+        let start = Instant::now();
+        for i in 0..=PRUNE_THRESHOLD {
+            assert!(limiter.check_at(&format!("client-{}", i), start).is_ok());
+        }
+        // 超过阈值后的第一次请求触发清理，但此时没有回满的桶
+        assert!(limiter.check_at("first", start).is_ok());
+        assert_eq!(limiter.tracked_keys(), PRUNE_THRESHOLD + 2);
+
+        // 所有桶都已回满，但距上次清理不足一个间隔，不会再全量扫描
+        let refilled = start + Duration::from_millis(600);
+        assert!(limiter.check_at("second", refilled).is_ok());
+        assert_eq!(limiter.tracked_keys(), PRUNE_THRESHOLD + 3);
+
+        // 间隔过后清理掉回满的桶，只剩还没回满的 second 和新来的 third
+        assert!(limiter.check_at("third", start + PRUNE_INTERVAL).is_ok());
+        assert_eq!(limiter.tracked_keys(), 2);
+    }
+}
@@ -0,0 +1,161 @@
+//! 重试策略
+
+use std::thread;
+use std::time::Duration;
+
+/// 退避算法：第 `attempt` 次失败后（从 1 开始）等待多久再重试
+#[derive(Debug, Clone, PartialEq)]
+pub enum Backoff {
+    /// 固定间隔
+    Fixed(Duration),
+    /// 指数退避：`base * factor^(attempt-1)`
+    Exponential(Duration, f64),
+    /// 线性退避：`base + step * (attempt-1)`
+    Linear(Duration, Duration),
+}
+
+impl Backoff {
+    /// 第 `attempt` 次失败后的等待时间，`attempt` 从 1 开始
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let n = attempt.saturating_sub(1);
+        match self {
+            Backoff::Fixed(delay) => *delay,
+            Backoff::Exponential(base, factor) => {
+                let secs = base.as_secs_f64() * factor.powi(n.min(i32::MAX as u32) as i32);
+                // 溢出或非有限值时取最大值，由 RetryPolicy::max_delay 兜底
+                Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX)
+            }
+            Backoff::Linear(base, step) => step
+                .checked_mul(n)
+                .and_then(|extra| base.checked_add(extra))
+                .unwrap_or(Duration::MAX),
+        }
+    }
+}
+
+/// 重试策略：最多尝试几次、每次之间等待多久
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// 总尝试次数（包括第一次），至少为 1
+    pub max_attempts: u32,
+    pub backoff: Backoff,
+    /// 单次等待的上限
+    pub max_delay: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            backoff: Backoff::Fixed(Duration::from_millis(100)),
+            max_delay: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, backoff: Backoff) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            backoff,
+            max_delay: None,
+        }
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = Some(max_delay);
+        self
+    }
+
+    /// 第 `attempt` 次失败后的等待时间，已经用完尝试次数时返回 `None`
+    pub fn delay_for(&self, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let delay = self.backoff.delay(attempt);
+        Some(self.max_delay.map_or(delay, |max| delay.min(max)))
+    }
+
+    /// 依次返回每次重试前的等待时间，共 `max_attempts - 1` 个
+    pub fn delays(&self) -> impl Iterator<Item = Duration> + '_ {
+        (1..self.max_attempts).filter_map(|attempt| self.delay_for(attempt))
+    }
+
+    /// 阻塞地执行 `operation`，失败且 `should_retry` 返回 true 时按策略等待后重试
+    ///
+    /// `operation` 的参数是当前尝试次数（从 1 开始）；异步代码可以用 [`RetryPolicy::delay_for`] 自己等待
+    pub fn retry_blocking<T, E, F, P>(&self, mut operation: F, should_retry: P) -> Result<T, E>
+    where
+        F: FnMut(u32) -> Result<T, E>,
+        P: Fn(&E) -> bool,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation(attempt) {
+                Ok(value) => return Ok(value),
+                Err(e) => match self.delay_for(attempt) {
+                    Some(delay) if should_retry(&e) => {
+                        thread::sleep(delay);
+                        attempt += 1;
+                    }
+                    _ => return Err(e),
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delays() {
+        let ms = Duration::from_millis;
+        assert_eq!(Backoff::Fixed(ms(100)).delay(5), ms(100));
+        assert_eq!(Backoff::Exponential(ms(100), 2.0).delay(1), ms(100));
+        assert_eq!(Backoff::Exponential(ms(100), 2.0).delay(4), ms(800));
+        assert_eq!(Backoff::Linear(ms(100), ms(50)).delay(3), ms(200));
+        assert_eq!(Backoff::Exponential(ms(100), 10.0).delay(1000), Duration::MAX);
+    }
+
+    #[test]
+    fn test_policy_caps_and_counts() {
+        let policy = RetryPolicy::new(4, Backoff::Exponential(Duration::from_secs(1), 3.0))
+            .with_max_delay(Duration::from_secs(5));
+        let delays: Vec<u64> = policy.delays().map(|d| d.as_secs()).collect();
+        assert_eq!(delays, vec![1, 3, 5]);
+        assert_eq!(policy.delay_for(4), None);
+        assert_eq!(RetryPolicy::new(0, Backoff::Fixed(Duration::ZERO)).max_attempts, 1);
+    }
+
+    #[test]
+    fn test_retry_blocking() {
+        let policy = RetryPolicy::new(3, Backoff::Fixed(Duration::ZERO));
+
+        let result: Result<u32, &str> = policy.retry_blocking(|attempt| if attempt < 3 { Err("busy") } else { Ok(attempt) }, |_| true);
+        assert_eq!(result, Ok(3));
+
+        let mut calls = 0;
+        let result: Result<(), &str> = policy.retry_blocking(
+            |_| {
+                calls += 1;
+                Err("fatal")
+            },
+            |e| *e != "fatal",
+        );
+        assert_eq!(result, Err("fatal"));
+        assert_eq!(calls, 1);
+
+        let mut calls = 0;
+        let result: Result<(), &str> = policy.retry_blocking(
+            |_| {
+                calls += 1;
+                Err("busy")
+            },
+            |_| true,
+        );
+        assert_eq!(result, Err("busy"));
+        assert_eq!(calls, 3);
+    }
+}
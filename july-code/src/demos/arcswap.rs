@@ -0,0 +1,121 @@
+use std::hint::black_box;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use arc_swap::ArcSwap;
+
+// 读多写少的配置热更新：rwlock_map 示例中读者每次都要拿读锁，
+// 这里对比三种方式在一个周期性更新配置的写者存在时的读吞吐量：
+// 1. RwLock<Config>：读时持有读锁
+// 2. RwLock<Arc<Config>>：只在克隆 Arc 时短暂持锁，之后读快照
+// 3. ArcSwap<Config>：无锁读取当前快照
+
+#[derive(Debug, Clone)]
+struct Config {
+    version: u64,
+    max_connections: usize,
+    endpoint: String,
+}
+
+impl Config {
+    fn new(version: u64) -> Self {
+        Config {
+            version,
+            max_connections: 100 + version as usize,
+            endpoint: format!("https://api.example.com/v{version}"),
+        }
+    }
+
+    // 模拟读者使用配置
+    fn checksum(&self) -> u64 {
+        self.version + self.max_connections as u64 + self.endpoint.len() as u64
+    }
+}
+
+const READERS: usize = 4;
+const DURATION: Duration = Duration::from_millis(200);
+const WRITE_INTERVAL: Duration = Duration::from_millis(1);
+
+// 运行 READERS 个读线程和一个写线程，返回 (总读取次数, 写入次数)
+fn measure<R, W>(read: R, write: W) -> (u64, u64)
+where
+    R: Fn() -> u64 + Sync,
+    W: Fn(u64) + Sync,
+{
+    let stop = AtomicBool::new(false);
+    thread::scope(|s| {
+        let readers: Vec<_> = (0..READERS)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut reads = 0u64;
+                    while !stop.load(Ordering::Relaxed) {
+                        black_box(read());
+                        reads += 1;
+                    }
+                    reads
+                })
+            })
+            .collect();
+        let writer = s.spawn(|| {
+            let mut version = 1;
+            while !stop.load(Ordering::Relaxed) {
+                write(version);
+                version += 1;
+                thread::sleep(WRITE_INTERVAL);
+            }
+            version - 1
+        });
+
+        let start = Instant::now();
+        while start.elapsed() < DURATION {
+            thread::sleep(Duration::from_millis(10));
+        }
+        stop.store(true, Ordering::Relaxed);
+
+        let reads = readers.into_iter().map(|h| h.join().unwrap()).sum();
+        (reads, writer.join().unwrap())
+    })
+}
+
+pub fn run() {
+    let locked = RwLock::new(Config::new(0));
+    let rwlock = measure(
+        || locked.read().unwrap().checksum(),
+        |v| *locked.write().unwrap() = Config::new(v),
+    );
+
+    let snapshot_lock = RwLock::new(Arc::new(Config::new(0)));
+    let snapshot = measure(
+        || {
+            // 持锁时间只有一次引用计数加一
+            let config = Arc::clone(&snapshot_lock.read().unwrap());
+            config.checksum()
+        },
+        |v| *snapshot_lock.write().unwrap() = Arc::new(Config::new(v)),
+    );
+
+    let swap = ArcSwap::from_pointee(Config::new(0));
+    let arcswap = measure(
+        || swap.load().checksum(),
+        |v| swap.store(Arc::new(Config::new(v))),
+    );
+    println!("[ArcSwap] 最终配置: {:?}", swap.load());
+
+    let per_sec = |reads: u64| reads as f64 / DURATION.as_secs_f64();
+    for (name, (reads, writes)) in [
+        ("RwLock<Config>", rwlock),
+        ("RwLock<Arc<Config>>", snapshot),
+        ("ArcSwap<Config>", arcswap),
+    ] {
+        println!(
+            "[ArcSwap] {name:<20} 读取 {:>12.0} 次/秒, 期间写入 {writes} 次",
+            per_sec(reads)
+        );
+    }
+    println!(
+        "[ArcSwap] ArcSwap 相对 RwLock<Config> 的读吞吐量: {:.2}x",
+        arcswap.0 as f64 / rwlock.0 as f64
+    );
+}
@@ -0,0 +1,72 @@
+use std::sync::mpsc as std_mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc as tokio_mpsc;
+
+// 在普通线程（std::sync::mpsc）与 tokio 运行时（tokio::sync::mpsc）之间搬运数据：
+// 工作线程 --std 有界通道--> spawn_blocking 桥接 --tokio 有界通道--> 异步消费者
+// 异步消费者 --tokio 通道--> 普通线程（blocking_recv）汇总结果
+// 两段通道都是有界的，消费者慢时阻塞会一路传回工作线程，形成背压
+pub fn run() {
+    let items = 8u32;
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .expect("failed to build tokio runtime");
+
+    let (std_tx, std_rx) = std_mpsc::sync_channel::<u32>(2);
+    let (async_tx, mut async_rx) = tokio_mpsc::channel::<u32>(2);
+    let (result_tx, mut result_rx) = tokio_mpsc::channel::<(u32, u32)>(4);
+
+    // 普通工作线程：缓冲满时 send 阻塞，记录被阻塞的总时长
+    let producer = thread::spawn(move || {
+        let mut blocked = Duration::ZERO;
+        for i in 0..items {
+            let start = Instant::now();
+            std_tx.send(i).expect("bridge closed");
+            blocked += start.elapsed();
+        }
+        println!("[Bridge] 工作线程发送完毕，因背压累计阻塞 {:?}", blocked);
+    });
+
+    // 桥接：std 的 recv 会阻塞线程，所以放在 spawn_blocking 的线程池里，
+    // 用 blocking_send 写入 tokio 通道（tokio 通道满时同样阻塞）
+    let bridge = runtime.spawn_blocking(move || {
+        let mut forwarded = 0;
+        for v in std_rx {
+            if async_tx.blocking_send(v).is_err() {
+                break;
+            }
+            forwarded += 1;
+        }
+        forwarded
+    });
+
+    // 异步消费者：模拟较慢的异步处理，结果经 tokio 通道发回普通线程
+    let consumer = runtime.spawn(async move {
+        while let Some(v) = async_rx.recv().await {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            if result_tx.send((v, v * v)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    // 普通线程从 tokio 通道接收：blocking_recv 不能在运行时线程里调用
+    let collector = thread::spawn(move || {
+        let mut sum = 0;
+        while let Some((v, squared)) = result_rx.blocking_recv() {
+            println!("[Bridge] 收到异步结果 {v}^2 = {squared}");
+            sum += squared;
+        }
+        sum
+    });
+
+    producer.join().unwrap();
+    let forwarded = runtime.block_on(bridge).expect("bridge task panicked");
+    runtime.block_on(consumer).expect("consumer task panicked");
+    let sum = collector.join().unwrap();
+    println!("[Bridge] 桥接转发 {forwarded} 条，平方和 = {sum}");
+}
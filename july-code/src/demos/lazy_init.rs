@@ -0,0 +1,125 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Barrier, LazyLock, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+// 共享的昂贵资源在并发访问下只初始化一次：
+// OnceLock（运行时决定初始化逻辑）、LazyLock（初始化逻辑写在定义处）、
+// 以及手写双重检查锁定的正确与错误写法
+
+const THREADS: usize = 8;
+
+struct Resource {
+    squares: Vec<u64>,
+}
+
+impl Resource {
+    // 模拟昂贵的初始化，并记录被初始化的次数
+    fn load(counter: &AtomicUsize) -> Resource {
+        counter.fetch_add(1, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(20));
+        Resource {
+            squares: (0..1000).map(|i| i * i).collect(),
+        }
+    }
+
+    fn lookup(&self, i: usize) -> u64 {
+        self.squares[i]
+    }
+}
+
+// 所有线程在屏障处同时出发，尽量制造初始化竞争
+fn race<F>(access: F) -> u64
+where
+    F: Fn() -> u64 + Sync,
+{
+    let barrier = Barrier::new(THREADS);
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..THREADS)
+            .map(|_| {
+                s.spawn(|| {
+                    barrier.wait();
+                    access()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    })
+}
+
+static LAZY_INITS: AtomicUsize = AtomicUsize::new(0);
+static LAZY_RESOURCE: LazyLock<Resource> = LazyLock::new(|| Resource::load(&LAZY_INITS));
+
+// 错误写法：检查与初始化之间没有互斥，多个线程可能同时看到“未初始化”
+struct NaiveLazy {
+    initialized: AtomicBool,
+    value: Mutex<Option<Resource>>,
+}
+
+impl NaiveLazy {
+    fn get(&self, counter: &AtomicUsize, i: usize) -> u64 {
+        if !self.initialized.load(Ordering::Acquire) {
+            let resource = Resource::load(counter);
+            *self.value.lock().unwrap() = Some(resource);
+            self.initialized.store(true, Ordering::Release);
+        }
+        self.value.lock().unwrap().as_ref().unwrap().lookup(i)
+    }
+}
+
+// 正确的双重检查锁定：快路径无锁检查，慢路径加锁后再检查一次
+// 标志位必须在数据写好之后用 Release 发布，读者用 Acquire 读取
+struct DoubleChecked {
+    initialized: AtomicBool,
+    value: Mutex<Option<Resource>>,
+}
+
+impl DoubleChecked {
+    fn get(&self, counter: &AtomicUsize, i: usize) -> u64 {
+        if !self.initialized.load(Ordering::Acquire) {
+            let mut slot = self.value.lock().unwrap();
+            // 第二次检查：等锁期间可能已被其他线程初始化
+            if slot.is_none() {
+                *slot = Some(Resource::load(counter));
+                self.initialized.store(true, Ordering::Release);
+            }
+        }
+        // 这里仍需加锁读取；想要真正无锁的读取就该直接用 OnceLock
+        self.value.lock().unwrap().as_ref().unwrap().lookup(i)
+    }
+}
+
+pub fn run() {
+    // OnceLock：初始化闭包在调用处给出，可以捕获局部变量
+    let once_inits = AtomicUsize::new(0);
+    let once = OnceLock::new();
+    let sum = race(|| once.get_or_init(|| Resource::load(&once_inits)).lookup(10));
+    assert_eq!(once_inits.load(Ordering::SeqCst), 1);
+    println!("[LazyInit] OnceLock: 初始化 1 次, 结果之和 {sum}");
+
+    // LazyLock：第一次解引用时初始化
+    let sum = race(|| LAZY_RESOURCE.lookup(20));
+    assert_eq!(LAZY_INITS.load(Ordering::SeqCst), 1);
+    println!("[LazyInit] LazyLock: 初始化 1 次, 结果之和 {sum}");
+
+    let checked_inits = AtomicUsize::new(0);
+    let checked = DoubleChecked {
+        initialized: AtomicBool::new(false),
+        value: Mutex::new(None),
+    };
+    let sum = race(|| checked.get(&checked_inits, 30));
+    assert_eq!(checked_inits.load(Ordering::SeqCst), 1);
+    println!("[LazyInit] 双重检查锁定: 初始化 1 次, 结果之和 {sum}");
+
+    // 竞争条件的结果不确定，所以这里不做断言
+    let naive_inits = AtomicUsize::new(0);
+    let naive = NaiveLazy {
+        initialized: AtomicBool::new(false),
+        value: Mutex::new(None),
+    };
+    let sum = race(|| naive.get(&naive_inits, 30));
+    println!(
+        "[LazyInit] 只检查不加锁(错误写法): 初始化 {} 次, 结果之和 {sum}",
+        naive_inits.load(Ordering::SeqCst)
+    );
+}
@@ -5,5 +5,9 @@ pub mod atomic_counter;
 pub mod condvar;
 pub mod sync_channel;
 pub mod scoped_threads;
-
+pub mod bridge;
+pub mod work_stealing;
+pub mod arcswap;
+pub mod lazy_init;
+pub mod thread_local;
 
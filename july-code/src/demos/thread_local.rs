@@ -0,0 +1,125 @@
+use std::cell::Cell;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 高竞争计数：所有线程共享一个原子变量（与 atomic 示例相同的写法）
+// 对比每个线程在 thread_local! 里累加、结束时只合并一次
+// 最后用相邻与按缓存行填充的计数器演示伪共享
+
+const THREADS: usize = 4;
+const ADDS_PER_THREAD: usize = 2_000_000;
+
+thread_local! {
+    static LOCAL_COUNT: Cell<usize> = const { Cell::new(0) };
+}
+
+fn timed<F: FnOnce() -> usize>(f: F) -> (usize, Duration) {
+    let start = Instant::now();
+    let total = f();
+    (total, start.elapsed())
+}
+
+// 每次加一都要争抢同一条缓存行
+fn shared_atomic() -> usize {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let mut hs = Vec::new();
+    for _ in 0..THREADS {
+        let c = Arc::clone(&counter);
+        hs.push(thread::spawn(move || {
+            for _ in 0..ADDS_PER_THREAD {
+                c.fetch_add(black_box(1), Ordering::Relaxed);
+            }
+        }));
+    }
+    for h in hs { h.join().unwrap(); }
+    counter.load(Ordering::Relaxed)
+}
+
+// 线程内累加不需要同步，结束前把本线程的结果合并到共享总数
+fn thread_local_merge() -> usize {
+    let total = Arc::new(AtomicUsize::new(0));
+    let mut hs = Vec::new();
+    for _ in 0..THREADS {
+        let t = Arc::clone(&total);
+        hs.push(thread::spawn(move || {
+            for _ in 0..ADDS_PER_THREAD {
+                LOCAL_COUNT.with(|c| c.set(c.get() + black_box(1)));
+            }
+            let local = LOCAL_COUNT.with(|c| c.replace(0));
+            t.fetch_add(local, Ordering::Relaxed);
+        }));
+    }
+    for h in hs { h.join().unwrap(); }
+    total.load(Ordering::Relaxed)
+}
+
+// 每个计数器独占一条 64 字节缓存行
+#[repr(align(64))]
+#[derive(Default)]
+struct Padded(AtomicUsize);
+
+// 每个线程只写自己的槽位，但相邻槽位落在同一缓存行时仍会互相使对方缓存失效
+fn per_thread_slots<S, F>(slots: &[S], get: F) -> usize
+where
+    S: Sync,
+    F: Fn(&S) -> &AtomicUsize + Sync,
+{
+    thread::scope(|s| {
+        for slot in slots {
+            let get = &get;
+            s.spawn(move || {
+                for _ in 0..ADDS_PER_THREAD {
+                    get(slot).fetch_add(black_box(1), Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    slots.iter().map(|slot| get(slot).load(Ordering::Relaxed)).sum()
+}
+
+fn report(name: &str, total: usize, elapsed: Duration, baseline: Duration) {
+    println!(
+        "[ThreadLocal] {name:<12} 计数 {total}, 用时 {:>8.2?}, 相对共享原子 {:.2}x",
+        elapsed,
+        baseline.as_secs_f64() / elapsed.as_secs_f64()
+    );
+}
+
+pub fn run() {
+    let expected = THREADS * ADDS_PER_THREAD;
+
+    let (total, atomic_time) = timed(shared_atomic);
+    assert_eq!(total, expected);
+    report("共享原子", total, atomic_time, atomic_time);
+
+    let (total, tls_time) = timed(thread_local_merge);
+    assert_eq!(total, expected);
+    report("thread_local", total, tls_time, atomic_time);
+
+    let adjacent: Vec<AtomicUsize> = (0..THREADS).map(|_| AtomicUsize::new(0)).collect();
+    let (total, adjacent_time) = timed(|| per_thread_slots(&adjacent, |c| c));
+    assert_eq!(total, expected);
+    report("相邻槽位", total, adjacent_time, atomic_time);
+
+    let padded: Vec<Padded> = (0..THREADS).map(|_| Padded::default()).collect();
+    let (total, padded_time) = timed(|| per_thread_slots(&padded, |p| &p.0));
+    assert_eq!(total, expected);
+    report("填充槽位", total, padded_time, atomic_time);
+
+    println!(
+        "[ThreadLocal] 相邻的 {} 个计数器共占 {} 字节，位于同一缓存行：各写各的也会伪共享",
+        THREADS,
+        THREADS * std::mem::size_of::<AtomicUsize>()
+    );
+    println!(
+        "[ThreadLocal] 填充后每个计数器占 {} 字节，互不干扰；thread_local 完全不共享，最快",
+        std::mem::size_of::<Padded>()
+    );
+    println!(
+        "[ThreadLocal] 可用核心数: {}（单核时线程不会真正并行，差距会变小）",
+        thread::available_parallelism().map_or(1, |n| n.get())
+    );
+}
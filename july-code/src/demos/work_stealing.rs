@@ -0,0 +1,161 @@
+use std::collections::VecDeque;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_deque::{Injector, Steal, Stealer, Worker};
+
+// 每个工作线程一个双端队列：自己从队头取任务，空闲时从全局注入队列或
+// 其他线程的队列“偷”任务。对比所有线程争用同一把锁的全局队列。
+
+struct Metrics {
+    elapsed: Duration,
+    per_worker: Vec<usize>,
+    steals: usize,
+}
+
+// 模拟耗时不均的任务：每 8 个任务中有 1 个是重任务
+fn task_cost(i: usize) -> u64 {
+    if i.is_multiple_of(8) {
+        200_000
+    } else {
+        5_000
+    }
+}
+
+fn do_work(cost: u64) -> u64 {
+    let mut x = 0u64;
+    for i in 0..cost {
+        x = black_box(x.wrapping_mul(31).wrapping_add(i));
+    }
+    x
+}
+
+fn find_task(
+    id: usize,
+    local: &Worker<u64>,
+    injector: &Injector<u64>,
+    stealers: &[Stealer<u64>],
+    steals: &AtomicUsize,
+) -> Option<u64> {
+    // 先取自己的队列，再从全局队列批量拿，最后从其他线程的队列批量偷
+    if let Some(task) = local.pop() {
+        return Some(task);
+    }
+    loop {
+        let from_injector = injector.steal_batch_and_pop(local);
+        if let Steal::Success(task) = from_injector {
+            return Some(task);
+        }
+        let from_peers: Steal<u64> = stealers
+            .iter()
+            .enumerate()
+            .filter(|&(peer, _)| peer != id)
+            .map(|(_, s)| s.steal_batch_and_pop(local))
+            .collect();
+        if let Steal::Success(task) = from_peers {
+            steals.fetch_add(1, Ordering::Relaxed);
+            return Some(task);
+        }
+        // Retry 表示与其他线程竞争失败，需要重试；两边都为空才算真的没有任务
+        if !from_injector.is_retry() && !from_peers.is_retry() {
+            return None;
+        }
+    }
+}
+
+fn run_work_stealing(costs: &[u64], workers: usize) -> Metrics {
+    let injector = Injector::new();
+    let locals: Vec<Worker<u64>> = (0..workers).map(|_| Worker::new_fifo()).collect();
+    let stealers: Vec<Stealer<u64>> = locals.iter().map(|w| w.stealer()).collect();
+    // 不均衡的初始分配：所有任务都在 0 号线程的本地队列里
+    for &cost in costs {
+        locals[0].push(cost);
+    }
+    let remaining = AtomicUsize::new(costs.len());
+    let steals = AtomicUsize::new(0);
+
+    let start = Instant::now();
+    let per_worker = thread::scope(|s| {
+        let handles: Vec<_> = locals
+            .into_iter()
+            .enumerate()
+            .map(|(id, local)| {
+                let (injector, stealers) = (&injector, &stealers);
+                let (remaining, steals) = (&remaining, &steals);
+                s.spawn(move || {
+                    let mut done = 0;
+                    while remaining.load(Ordering::Acquire) > 0 {
+                        match find_task(id, &local, injector, stealers, steals) {
+                            Some(cost) => {
+                                do_work(cost);
+                                done += 1;
+                                remaining.fetch_sub(1, Ordering::AcqRel);
+                            }
+                            None => thread::yield_now(),
+                        }
+                    }
+                    done
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    Metrics {
+        elapsed: start.elapsed(),
+        per_worker,
+        steals: steals.load(Ordering::Relaxed),
+    }
+}
+
+fn run_global_queue(costs: &[u64], workers: usize) -> Metrics {
+    let queue = Mutex::new(costs.iter().copied().collect::<VecDeque<u64>>());
+
+    let start = Instant::now();
+    let per_worker = thread::scope(|s| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                let queue = &queue;
+                s.spawn(move || {
+                    let mut done = 0;
+                    // 每取一个任务都要拿一次全局锁
+                    while let Some(cost) = { queue.lock().unwrap().pop_front() } {
+                        do_work(cost);
+                        done += 1;
+                    }
+                    done
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    Metrics {
+        elapsed: start.elapsed(),
+        per_worker,
+        steals: 0,
+    }
+}
+
+pub fn run() {
+    let workers = 4usize;
+    let costs: Vec<u64> = (0..2_000).map(task_cost).collect();
+
+    let stealing = run_work_stealing(&costs, workers);
+    let global = run_global_queue(&costs, workers);
+    for m in [&stealing, &global] {
+        assert_eq!(m.per_worker.iter().sum::<usize>(), costs.len());
+    }
+
+    println!(
+        "[WorkStealing] 工作窃取: 耗时 {:?}, 每线程任务数 {:?}, 窃取次数 {}",
+        stealing.elapsed, stealing.per_worker, stealing.steals
+    );
+    println!(
+        "[WorkStealing] 全局队列: 耗时 {:?}, 每线程任务数 {:?}",
+        global.elapsed, global.per_worker
+    );
+}
@@ -13,6 +13,11 @@ fn main() {
             demos::condvar::run();
             demos::sync_channel::run();
             demos::scoped_threads::run();
+            demos::bridge::run();
+            demos::work_stealing::run();
+            demos::arcswap::run();
+            demos::lazy_init::run();
+            demos::thread_local::run();
         }
         "mutex" => demos::mutex_counter::run(),
         "channels" => demos::channels::run(),
@@ -21,9 +26,14 @@ fn main() {
         "condvar" => demos::condvar::run(),
         "sync" => demos::sync_channel::run(),
         "scoped" => demos::scoped_threads::run(),
+        "bridge" => demos::bridge::run(),
+        "stealing" => demos::work_stealing::run(),
+        "arcswap" => demos::arcswap::run(),
+        "lazy" => demos::lazy_init::run(),
+        "tls" => demos::thread_local::run(),
         other => {
             eprintln!(
-                "未知示例: {}\n用法: cargo run -- <all|mutex|channels|rwlock|atomic|condvar|sync|scoped>",
+                "未知示例: {}\n用法: cargo run -- <all|mutex|channels|rwlock|atomic|condvar|sync|scoped|bridge|stealing|arcswap|lazy|tls>",
                 other
             );
         }
@@ -0,0 +1,398 @@
+//! 购物车：用闭包描述折扣规则
+//!
+//! - `Fn`：折扣规则会被反复调用，只读取购物车
+//! - `FnMut`：`remove_where` 的判断闭包可以在调用之间修改自己捕获的状态
+//! - `FnOnce`：`checkout` 消耗购物车，结账回调只会被调用一次
+//!
+//! 金额统一以“分”为单位保存，避免浮点误差
+
+use std::fmt;
+
+use crate::product::Product;
+
+/// 将以“分”为单位的金额格式化为 `¥12.34`
+pub fn format_cents(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}¥{}.{:02}", sign, cents.abs() / 100, cents.abs() % 100)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CartItem {
+    pub product: Product,
+    pub quantity: u32,
+}
+
+impl CartItem {
+    /// 小计（分）
+    pub fn line_total(&self) -> i64 {
+        self.product.price as i64 * 100 * self.quantity as i64
+    }
+}
+
+/// 一条折扣：说明与减免金额（分），金额为 0 表示不适用
+#[derive(Debug, Clone, PartialEq)]
+pub struct Discount {
+    pub label: String,
+    pub amount: i64,
+}
+
+impl Discount {
+    pub fn new(label: impl Into<String>, amount: i64) -> Self {
+        Discount {
+            label: label.into(),
+            amount,
+        }
+    }
+
+    pub fn none() -> Self {
+        Discount::new("", 0)
+    }
+}
+
+/// 折扣规则：根据购物车内容计算折扣
+pub type DiscountRule = Box<dyn Fn(&Cart) -> Discount>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CartError {
+    OutOfStock(String),
+    NotInCart(String),
+    InvalidQuantity,
+}
+
+impl fmt::Display for CartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CartError::OutOfStock(name) => write!(f, "商品无货: {}", name),
+            CartError::NotInCart(name) => write!(f, "购物车中没有: {}", name),
+            CartError::InvalidQuantity => write!(f, "数量必须大于0"),
+        }
+    }
+}
+
+impl std::error::Error for CartError {}
+
+/// 金额汇总（分）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Totals {
+    pub subtotal: i64,
+    pub discount: i64,
+    pub tax: i64,
+    pub total: i64,
+}
+
+pub struct Cart {
+    items: Vec<CartItem>,
+    rules: Vec<DiscountRule>,
+    /// 税率，0.13 表示 13%
+    tax_rate: f64,
+}
+
+impl Cart {
+    pub fn new(tax_rate: f64) -> Self {
+        Cart {
+            items: Vec::new(),
+            rules: Vec::new(),
+            tax_rate,
+        }
+    }
+
+    pub fn items(&self) -> &[CartItem] {
+        &self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// 商品总件数
+    pub fn quantity(&self) -> u32 {
+        self.items.iter().map(|item| item.quantity).sum()
+    }
+
+    /// 某个商品在购物车中的件数
+    pub fn quantity_of(&self, name: &str) -> u32 {
+        self.items
+            .iter()
+            .find(|item| item.product.name == name)
+            .map_or(0, |item| item.quantity)
+    }
+
+    /// 加入商品，同名商品合并数量
+    pub fn add(&mut self, product: &Product, quantity: u32) -> Result<(), CartError> {
+        if quantity == 0 {
+            return Err(CartError::InvalidQuantity);
+        }
+        if !product.in_stock {
+            return Err(CartError::OutOfStock(product.name.clone()));
+        }
+        match self
+            .items
+            .iter_mut()
+            .find(|item| item.product.name == product.name)
+        {
+            Some(item) => item.quantity += quantity,
+            None => self.items.push(CartItem {
+                product: product.clone(),
+                quantity,
+            }),
+        }
+        Ok(())
+    }
+
+    /// 减少商品数量，减到 0 时移出购物车
+    pub fn remove(&mut self, name: &str, quantity: u32) -> Result<(), CartError> {
+        let index = self
+            .items
+            .iter()
+            .position(|item| item.product.name == name)
+            .ok_or_else(|| CartError::NotInCart(name.to_string()))?;
+        let item = &mut self.items[index];
+        if quantity >= item.quantity {
+            self.items.remove(index);
+        } else {
+            item.quantity -= quantity;
+        }
+        Ok(())
+    }
+
+    /// 移除所有满足条件的商品并返回它们
+    pub fn remove_where<F>(&mut self, mut predicate: F) -> Vec<CartItem>
+    where
+        F: FnMut(&CartItem) -> bool,
+    {
+        let (removed, kept) = self.items.drain(..).partition(|item| predicate(item));
+        self.items = kept;
+        removed
+    }
+
+    /// 添加折扣规则，结账时按添加顺序逐条计算
+    pub fn add_rule<F>(&mut self, rule: F)
+    where
+        F: Fn(&Cart) -> Discount + 'static,
+    {
+        self.rules.push(Box::new(rule));
+    }
+
+    /// 商品原价合计（分）
+    pub fn subtotal(&self) -> i64 {
+        self.items.iter().map(CartItem::line_total).sum()
+    }
+
+    /// 所有适用的折扣；总折扣不会超过商品原价合计
+    pub fn discounts(&self) -> Vec<Discount> {
+        let mut remaining = self.subtotal();
+        let mut applied = Vec::new();
+        for rule in &self.rules {
+            let mut discount = rule(self);
+            discount.amount = discount.amount.clamp(0, remaining);
+            if discount.amount > 0 {
+                remaining -= discount.amount;
+                applied.push(discount);
+            }
+        }
+        applied
+    }
+
+    /// 先减折扣，再按税率对折后金额计税（四舍五入到分）
+    pub fn totals(&self) -> Totals {
+        let subtotal = self.subtotal();
+        let discount: i64 = self.discounts().iter().map(|d| d.amount).sum();
+        let taxable = subtotal - discount;
+        let tax = (taxable as f64 * self.tax_rate).round() as i64;
+        Totals {
+            subtotal,
+            discount,
+            tax,
+            total: taxable + tax,
+        }
+    }
+
+    pub fn receipt(&self) -> Receipt {
+        Receipt {
+            items: self.items.clone(),
+            discounts: self.discounts(),
+            tax_rate: self.tax_rate,
+            totals: self.totals(),
+        }
+    }
+
+    /// 结账：消耗购物车，把小票交给回调处理（如支付），返回回调的结果
+    pub fn checkout<F, R>(self, on_checkout: F) -> R
+    where
+        F: FnOnce(Receipt) -> R,
+    {
+        on_checkout(self.receipt())
+    }
+}
+
+/// 逐项列出的小票
+#[derive(Debug, Clone, PartialEq)]
+pub struct Receipt {
+    pub items: Vec<CartItem>,
+    pub discounts: Vec<Discount>,
+    pub tax_rate: f64,
+    pub totals: Totals,
+}
+
+impl fmt::Display for Receipt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for item in &self.items {
+            writeln!(
+                f,
+                "  {} x{} @ {} = {}",
+                item.product.name,
+                item.quantity,
+                format_cents(item.product.price as i64 * 100),
+                format_cents(item.line_total())
+            )?;
+        }
+        writeln!(f, "  小计: {}", format_cents(self.totals.subtotal))?;
+        for discount in &self.discounts {
+            writeln!(f, "  {}: {}", discount.label, format_cents(-discount.amount))?;
+        }
+        writeln!(
+            f,
+            "  税费 ({:.0}%): {}",
+            self.tax_rate * 100.0,
+            format_cents(self.totals.tax)
+        )?;
+        write!(f, "  合计: {}", format_cents(self.totals.total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phone() -> Product {
+        Product::new("手机", 2999, true)
+    }
+
+    fn earphones() -> Product {
+        Product::new("耳机", 199, true)
+    }
+
+    #[test]
+    fn add_and_remove() {
+        let mut cart = Cart::new(0.0);
+        cart.add(&phone(), 1).unwrap();
+        cart.add(&earphones(), 2).unwrap();
+        cart.add(&earphones(), 1).unwrap();
+        assert_eq!(cart.items().len(), 2);
+        assert_eq!(cart.quantity_of("耳机"), 3);
+        assert_eq!(cart.subtotal(), 299_900 + 3 * 19_900);
+
+        cart.remove("耳机", 2).unwrap();
+        assert_eq!(cart.quantity_of("耳机"), 1);
+        cart.remove("耳机", 5).unwrap();
+        assert_eq!(cart.quantity_of("耳机"), 0);
+
+        assert_eq!(cart.remove("耳机", 1), Err(CartError::NotInCart("耳机".into())));
+        assert_eq!(
+            cart.add(&Product::new("平板", 3999, false), 1),
+            Err(CartError::OutOfStock("平板".into()))
+        );
+        assert_eq!(cart.add(&phone(), 0), Err(CartError::InvalidQuantity));
+    }
+
+    #[test]
+    fn discount_rules_and_tax() {
+        let mut cart = Cart::new(0.13);
+        cart.add(&phone(), 1).unwrap();
+        cart.add(&earphones(), 2).unwrap();
+
+        // 满 3000 减 200
+        cart.add_rule(|cart| {
+            if cart.subtotal() >= 300_000 {
+                Discount::new("满3000减200", 20_000)
+            } else {
+                Discount::none()
+            }
+        });
+        // 捕获环境变量：指定商品 9 折
+        let target = "耳机".to_string();
+        cart.add_rule(move |cart| {
+            let amount: i64 = cart
+                .items()
+                .iter()
+                .filter(|item| item.product.name == target)
+                .map(|item| item.line_total() / 10)
+                .sum();
+            Discount::new(format!("{}九折", target), amount)
+        });
+        // 不适用的规则不会出现在小票上
+        cart.add_rule(|cart| {
+            if cart.quantity() >= 10 {
+                Discount::new("批量优惠", 10_000)
+            } else {
+                Discount::none()
+            }
+        });
+
+        let discounts = cart.discounts();
+        assert_eq!(discounts.len(), 2);
+        assert_eq!(discounts[1].amount, 3_980);
+
+        let totals = cart.totals();
+        assert_eq!(totals.subtotal, 339_700);
+        assert_eq!(totals.discount, 23_980);
+        // (339700 - 23980) * 0.13 = 41043.6 -> 41044
+        assert_eq!(totals.tax, 41_044);
+        assert_eq!(totals.total, 315_720 + 41_044);
+    }
+
+    #[test]
+    fn discounts_never_exceed_subtotal() {
+        let mut cart = Cart::new(0.1);
+        cart.add(&earphones(), 1).unwrap();
+        cart.add_rule(|_| Discount::new("大额券", 1_000_000));
+        cart.add_rule(|_| Discount::new("再减", 500));
+        let totals = cart.totals();
+        assert_eq!(totals.discount, totals.subtotal);
+        assert_eq!(totals.total, 0);
+        assert_eq!(cart.discounts().len(), 1);
+    }
+
+    #[test]
+    fn remove_where_with_stateful_closure() {
+        let mut cart = Cart::new(0.0);
+        cart.add(&phone(), 1).unwrap();
+        cart.add(&earphones(), 1).unwrap();
+        cart.add(&Product::new("充电器", 99, true), 1).unwrap();
+
+        // FnMut：闭包记录检查过多少件商品
+        let mut checked = 0;
+        let removed = cart.remove_where(|item| {
+            checked += 1;
+            item.product.price < 1000
+        });
+        assert_eq!(checked, 3);
+        assert_eq!(removed.len(), 2);
+        assert_eq!(cart.items().len(), 1);
+    }
+
+    #[test]
+    fn checkout_and_receipt() {
+        let mut cart = Cart::new(0.1);
+        cart.add(&earphones(), 2).unwrap();
+        cart.add_rule(|_| Discount::new("新人券", 1_000));
+
+        let receipt_text = cart.receipt().to_string();
+        assert!(receipt_text.contains("耳机 x2 @ ¥199.00 = ¥398.00"));
+        assert!(receipt_text.contains("新人券: -¥10.00"));
+        assert!(receipt_text.contains("税费 (10%): ¥38.80"));
+        assert!(receipt_text.ends_with("合计: ¥426.80"));
+
+        // FnOnce：回调可以移走捕获的值
+        let payer = String::from("alice");
+        let paid = cart.checkout(move |receipt| (payer, receipt.totals.total));
+        assert_eq!(paid, ("alice".to_string(), 42_680));
+    }
+
+    #[test]
+    fn money_formatting() {
+        assert_eq!(format_cents(0), "¥0.00");
+        assert_eq!(format_cents(123_456), "¥1234.56");
+        assert_eq!(format_cents(-5), "-¥0.05");
+    }
+}
@@ -0,0 +1,7 @@
+//! Rust闭包与迭代器示例库
+
+pub mod cart;
+pub mod pipeline;
+pub mod product;
+pub mod ranking;
+pub mod sequences;
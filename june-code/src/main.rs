@@ -1,3 +1,9 @@
+use closure_iterator_demo::cart::{format_cents, Cart, Discount};
+use closure_iterator_demo::pipeline;
+use closure_iterator_demo::product::Product;
+use closure_iterator_demo::ranking::{self, RankKey};
+use closure_iterator_demo::sequences::{self, SequenceExt};
+
 fn main() {
     println!("Rust闭包与迭代器示例程序");
     
@@ -87,7 +93,7 @@ fn main() {
     
     // 自定义排序 - 按价格从高到低
     let mut sorted_products = products.clone();
-    sorted_products.sort_by(|a, b| b.price.cmp(&a.price));
+    sorted_products.sort_by_key(|p| std::cmp::Reverse(p.price));
     
     println!("商品按价格排序:");
     for product in sorted_products {
@@ -97,6 +103,99 @@ fn main() {
             if product.in_stock { "有货" } else { "无货" }
         );
     }
+
+    // 多条件加权排名：有货优先（权重3）、价格低优先（权重2）、名称（权重0.5）
+    let keys = [
+        RankKey::by_key_desc(3.0, |p: &Product| p.in_stock),
+        RankKey::by_key(2.0, |p: &Product| p.price),
+        RankKey::by_key(0.5, |p: &Product| p.name.clone()),
+    ];
+    println!("商品综合排名:");
+    for (position, ranked) in ranking::rank_by(&products, &keys).iter().enumerate() {
+        println!("  {}. {} - 得分 {:.3}", position + 1, ranked.item.name, ranked.score);
+    }
+
+    // 6. 通道流水线：迭代器链 → 多线程阶段
+    println!("\n6. 通道流水线");
+    let evens = pipeline::run_pipeline(1..=10, |x| x * x, |x| x % 2 == 0);
+    println!("流水线 (平方后取偶数): {:?}", evens);
+
+    // 每个元素开销很小时，通道通信的成本占主导
+    let cheap = pipeline::compare_throughput(200_000, |x| x + 1, |x| x % 3 == 0);
+    // 每个元素开销较大时，map 与 filter 阶段可以在不同 CPU 核上重叠执行（单核机器上没有收益）
+    let heavy = pipeline::compare_throughput(
+        5_000,
+        pipeline::heavy_work,
+        |total| pipeline::heavy_work(*total).is_multiple_of(2),
+    );
+    for (label, result) in [("轻量计算", cheap), ("较重计算", heavy)] {
+        match result {
+            Ok(result) => println!(
+                "{}: 迭代器链 {:?} ({:.0} 个/秒), 流水线 {:?} ({:.0} 个/秒)",
+                label,
+                result.iterator_time,
+                result.iterator_items_per_sec(),
+                result.pipeline_time,
+                result.pipeline_items_per_sec()
+            ),
+            Err(mismatch) => println!(
+                "{}: 流水线结果与迭代器链不一致 ({} 个 vs {} 个)",
+                label,
+                mismatch.actual.len(),
+                mismatch.expected.len()
+            ),
+        }
+    }
+
+    // 7. 惰性无限序列
+    println!("\n7. 惰性无限序列");
+    let first_naturals: Vec<u64> = sequences::naturals().take(10).collect();
+    println!("前10个自然数: {:?}", first_naturals);
+
+    let small_primes: Vec<u64> = sequences::primes().take_until(|&p| p >= 50).collect();
+    println!("直到第一个不小于50的素数: {:?}", small_primes);
+
+    let twin_primes: Vec<(u64, u64)> = sequences::primes()
+        .pairwise()
+        .filter(|(a, b)| b - a == 2)
+        .take(5)
+        .collect();
+    println!("前5对孪生素数: {:?}", twin_primes);
+
+    let series: Vec<f64> = sequences::geometric(3.0, 2.0).take(6).collect();
+    println!("等比数列 (首项3, 公比2): {:?}", series);
+
+    let mixed: Vec<u64> = sequences::naturals()
+        .map(|n| n * 100)
+        .interleave(sequences::primes())
+        .take(8)
+        .collect();
+    println!("交替产出 100的倍数 与 素数: {:?}", mixed);
+
+    // 8. 购物车：用闭包描述折扣规则
+    println!("\n8. 购物车");
+    let mut cart = Cart::new(0.13);
+    for (product, quantity) in products.iter().zip([1, 1, 2, 1]) {
+        if let Err(e) = cart.add(product, quantity) {
+            println!("无法加入购物车: {}", e);
+        }
+    }
+    cart.add_rule(|cart| {
+        if cart.subtotal() >= 1_000_000 {
+            Discount::new("满10000减500", 50_000)
+        } else {
+            Discount::none()
+        }
+    });
+    let member_rate = 5;
+    cart.add_rule(move |cart| {
+        Discount::new(format!("会员{}%折扣", member_rate), cart.subtotal() * member_rate / 100)
+    });
+    let total = cart.checkout(|receipt| {
+        println!("小票:\n{}", receipt);
+        receipt.totals.total
+    });
+    println!("应付金额: {}", format_cents(total));
 }
 
 // 用于闭包示例的函数
@@ -119,11 +218,3 @@ where
     }
     result
 }
-
-// 定义一个商品结构体用于实际应用示例
-#[derive(Debug, Clone)]
-struct Product {
-    name: String,
-    price: i32,
-    in_stock: bool,
-}
\ No newline at end of file
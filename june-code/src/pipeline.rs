@@ -0,0 +1,200 @@
+//! 通道流水线：把迭代器链拆成由通道连接的多个线程
+//!
+//! `iter.map(f).filter(p).collect()` 对应为：
+//! 数据源线程 → map 阶段线程 → filter 阶段线程 → 收集者（当前线程）
+
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// 每个通道最多缓存的元素数，下游处理慢时上游会阻塞（背压）
+const CHANNEL_CAPACITY: usize = 256;
+
+/// 启动一个阶段线程：从 `input` 接收元素，用闭包处理后把 `Some` 的结果发往下游
+///
+/// 闭包只在这一个线程里被调用，所以可以是 `FnMut`，能保存阶段内部的状态
+pub fn spawn_stage<T, U, F>(input: Receiver<T>, mut stage: F) -> (Receiver<U>, JoinHandle<()>)
+where
+    T: Send + 'static,
+    U: Send + 'static,
+    F: FnMut(T) -> Option<U> + Send + 'static,
+{
+    let (tx, rx) = mpsc::sync_channel(CHANNEL_CAPACITY);
+    let handle = thread::spawn(move || {
+        for item in input {
+            if let Some(output) = stage(item) {
+                // 下游已关闭时提前结束
+                if tx.send(output).is_err() {
+                    break;
+                }
+            }
+        }
+        // 离开作用域时 tx 被丢弃，下游的 for 循环随之结束
+    });
+    (rx, handle)
+}
+
+/// 与 `source.into_iter().map(map_fn).filter(filter_fn).collect()` 结果相同，
+/// 但每一步在各自的线程中执行，元素按原顺序到达收集者
+pub fn run_pipeline<I, T, U, M, P>(source: I, map_fn: M, filter_fn: P) -> Vec<U>
+where
+    I: IntoIterator<Item = T> + Send + 'static,
+    T: Send + 'static,
+    U: Send + 'static,
+    M: Fn(T) -> U + Send + 'static,
+    P: Fn(&U) -> bool + Send + 'static,
+{
+    let (tx, source_rx) = mpsc::sync_channel(CHANNEL_CAPACITY);
+    let source_handle = thread::spawn(move || {
+        for item in source {
+            if tx.send(item).is_err() {
+                break;
+            }
+        }
+    });
+
+    let (mapped, map_handle) = spawn_stage(source_rx, move |item| Some(map_fn(item)));
+    let (filtered, filter_handle) =
+        spawn_stage(mapped, move |item| if filter_fn(&item) { Some(item) } else { None });
+
+    // 收集者：通道关闭（所有上游结束）后迭代自然结束
+    let result: Vec<U> = filtered.into_iter().collect();
+
+    for handle in [source_handle, map_handle, filter_handle] {
+        handle.join().expect("流水线阶段线程 panic");
+    }
+    result
+}
+
+/// 吞吐量对比结果
+#[derive(Debug)]
+pub struct Throughput {
+    pub items: usize,
+    pub iterator_time: Duration,
+    pub pipeline_time: Duration,
+}
+
+impl Throughput {
+    pub fn iterator_items_per_sec(&self) -> f64 {
+        self.items as f64 / self.iterator_time.as_secs_f64()
+    }
+
+    pub fn pipeline_items_per_sec(&self) -> f64 {
+        self.items as f64 / self.pipeline_time.as_secs_f64()
+    }
+}
+
+/// 流水线结果与迭代器链不一致
+#[derive(Debug, PartialEq)]
+pub struct Mismatch {
+    pub expected: Vec<u64>,
+    pub actual: Vec<u64>,
+}
+
+/// 用同一组闭包分别跑普通迭代器链和通道流水线，返回两者耗时
+///
+/// 两者结果不一致时返回 `Mismatch`
+pub fn compare_throughput<M, P>(items: usize, map_fn: M, filter_fn: P) -> Result<Throughput, Mismatch>
+where
+    M: Fn(u64) -> u64 + Clone + Send + 'static,
+    P: Fn(&u64) -> bool + Clone + Send + 'static,
+{
+    let start = Instant::now();
+    let expected: Vec<u64> = (0..items as u64)
+        .map(map_fn.clone())
+        .filter(filter_fn.clone())
+        .collect();
+    let iterator_time = start.elapsed();
+
+    let start = Instant::now();
+    let actual = run_pipeline(0..items as u64, map_fn, filter_fn);
+    let pipeline_time = start.elapsed();
+
+    if expected != actual {
+        return Err(Mismatch { expected, actual });
+    }
+    Ok(Throughput {
+        items,
+        iterator_time,
+        pipeline_time,
+    })
+}
+
+/// Collatz 序列的步数
+pub fn collatz_steps(mut n: u64) -> u64 {
+    let mut steps = 0;
+    while n > 1 {
+        n = if n.is_multiple_of(2) { n / 2 } else { 3 * n + 1 };
+        steps += 1;
+    }
+    steps
+}
+
+/// 模拟开销较大的计算：`n` 起连续 64 个数的 Collatz 步数之和
+pub fn heavy_work(n: u64) -> u64 {
+    (n * 64..n * 64 + 64).map(collatz_steps).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_matches_iterator_chain() {
+        let expected: Vec<u64> = (0..1_000u64).map(|x| x * 3).filter(|x| x % 2 == 0).collect();
+        let actual = run_pipeline(0..1_000u64, |x| x * 3, |x| x % 2 == 0);
+        assert_eq!(actual, expected);
+
+        // 超过通道容量时背压不影响顺序
+        let words: Vec<String> = (0..1_000).map(|i| format!("w{}", i)).collect();
+        let lengths = run_pipeline(words.clone(), |w: String| w.len(), |_| true);
+        assert_eq!(lengths, words.iter().map(String::len).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_empty_source() {
+        let result = run_pipeline(Vec::<u64>::new(), |x| x + 1, |_| true);
+        assert!(result.is_empty());
+
+        let throughput = compare_throughput(0, |x| x + 1, |_| true).unwrap();
+        assert_eq!(throughput.items, 0);
+    }
+
+    #[test]
+    fn test_stage_stops_when_downstream_closed() {
+        let (tx, rx) = mpsc::sync_channel(1);
+        let (output, handle) = spawn_stage(rx, |x: u32| Some(x));
+        drop(output);
+
+        // 阶段线程发送失败后退出，并关闭自己的输入
+        tx.send(1).unwrap();
+        handle.join().unwrap();
+        assert!(tx.send(2).is_err());
+    }
+
+    #[test]
+    fn test_stage_keeps_state() {
+        let (tx, rx) = mpsc::sync_channel(CHANNEL_CAPACITY);
+        let mut total = 0;
+        let (sums, handle) = spawn_stage(rx, move |x: u32| {
+            total += x;
+            Some(total)
+        });
+        for x in 1..=4 {
+            tx.send(x).unwrap();
+        }
+        drop(tx);
+        assert_eq!(sums.into_iter().collect::<Vec<_>>(), vec![1, 3, 6, 10]);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_compare_throughput_reports_mismatch() {
+        // 有状态的过滤闭包在两次运行中看到的序列不同，结果会不一致
+        let seen = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let filter = move |_: &u64| seen.fetch_add(1, std::sync::atomic::Ordering::SeqCst) < 3;
+        let mismatch = compare_throughput(5, |x| x, filter).unwrap_err();
+        assert_eq!(mismatch.expected, vec![0, 1, 2]);
+        assert!(mismatch.actual.is_empty());
+    }
+}
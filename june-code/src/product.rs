@@ -0,0 +1,19 @@
+//! 商品类型，供实际应用示例使用
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Product {
+    pub name: String,
+    /// 价格（元）
+    pub price: i32,
+    pub in_stock: bool,
+}
+
+impl Product {
+    pub fn new(name: impl Into<String>, price: i32, in_stock: bool) -> Self {
+        Product {
+            name: name.into(),
+            price,
+            in_stock,
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! 多条件加权排名
+//!
+//! 每个条件是一个比较闭包加一个权重。先按每个条件单独排出名次，
+//! 把名次换算成 0~1 的得分（第一名为 1），再按权重求加权平均作为综合得分
+
+use std::cmp::Ordering;
+
+/// 比较闭包，`Less` 表示第一个参数排在前面
+pub type Comparator<'a, T> = Box<dyn Fn(&T, &T) -> Ordering + 'a>;
+
+/// 一个排名条件：`compare(a, b)` 返回 `Less` 表示 a 应排在 b 前面
+pub struct RankKey<'a, T> {
+    compare: Comparator<'a, T>,
+    weight: f64,
+}
+
+impl<'a, T> RankKey<'a, T> {
+    pub fn new<F>(weight: f64, compare: F) -> Self
+    where
+        F: Fn(&T, &T) -> Ordering + 'a,
+    {
+        RankKey {
+            compare: Box::new(compare),
+            weight,
+        }
+    }
+
+    /// 按提取出的键升序排名（键越小越靠前）
+    pub fn by_key<K, F>(weight: f64, key: F) -> Self
+    where
+        K: Ord,
+        F: Fn(&T) -> K + 'a,
+    {
+        Self::new(weight, move |a, b| key(a).cmp(&key(b)))
+    }
+
+    /// 按提取出的键降序排名（键越大越靠前）
+    pub fn by_key_desc<K, F>(weight: f64, key: F) -> Self
+    where
+        K: Ord,
+        F: Fn(&T) -> K + 'a,
+    {
+        Self::new(weight, move |a, b| key(b).cmp(&key(a)))
+    }
+}
+
+/// 排名结果：元素引用及其综合得分（0~1，越大越靠前）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ranked<'a, T> {
+    pub item: &'a T,
+    pub score: f64,
+}
+
+/// 单个条件下每个元素的得分，相等的元素名次相同
+fn key_scores<T>(items: &[T], key: &RankKey<'_, T>) -> Vec<f64> {
+    let n = items.len();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| (key.compare)(&items[a], &items[b]));
+
+    let mut scores = vec![0.0; n];
+    let mut rank = 0;
+    for (position, &index) in order.iter().enumerate() {
+        if position > 0 && (key.compare)(&items[order[position - 1]], &items[index]) != Ordering::Equal {
+            rank = position;
+        }
+        scores[index] = if n > 1 {
+            1.0 - rank as f64 / (n - 1) as f64
+        } else {
+            1.0
+        };
+    }
+    scores
+}
+
+/// 按多个加权条件对 `items` 排名，返回从高到低的结果
+///
+/// - 综合得分相同的元素保持输入顺序（稳定排名）
+/// - 只有一个条件时结果与 `sort_by` 相同
+/// - 没有条件或权重之和为 0 时，所有得分为 0，保持输入顺序
+pub fn rank_by<'a, T>(items: &'a [T], keys: &[RankKey<'_, T>]) -> Vec<Ranked<'a, T>> {
+    let total_weight: f64 = keys.iter().map(|key| key.weight).sum();
+    let mut scores = vec![0.0; items.len()];
+    if total_weight > 0.0 {
+        for key in keys {
+            for (score, key_score) in scores.iter_mut().zip(key_scores(items, key)) {
+                *score += key.weight * key_score / total_weight;
+            }
+        }
+    }
+
+    let mut ranked: Vec<Ranked<'a, T>> = items
+        .iter()
+        .zip(scores)
+        .map(|(item, score)| Ranked { item, score })
+        .collect();
+    // sort_by 是稳定排序，得分相同的元素保持原顺序
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::product::Product;
+
+    fn products() -> Vec<Product> {
+        vec![
+            Product::new("手机", 2999, true),
+            Product::new("笔记本", 5999, true),
+            Product::new("耳机", 999, false),
+            Product::new("平板", 3999, true),
+        ]
+    }
+
+    fn names<T>(ranked: &[Ranked<'_, T>], name: impl Fn(&T) -> &str) -> Vec<String> {
+        ranked.iter().map(|r| name(r.item).to_string()).collect()
+    }
+
+    #[test]
+    fn single_key_matches_sort_by() {
+        let products = products();
+        let ranked = rank_by(&products, &[RankKey::by_key(1.0, |p: &Product| p.price)]);
+
+        let mut sorted = products.clone();
+        sorted.sort_by_key(|p| p.price);
+        let sorted_names: Vec<String> = sorted.into_iter().map(|p| p.name).collect();
+        assert_eq!(names(&ranked, |p| &p.name), sorted_names);
+        assert_eq!(ranked[0].score, 1.0);
+        assert_eq!(ranked[3].score, 0.0);
+    }
+
+    #[test]
+    fn weights_decide_between_keys() {
+        let products = products();
+        let cheap_first = |weight| RankKey::by_key(weight, |p: &Product| p.price);
+        let in_stock_first = |weight| RankKey::by_key_desc(weight, |p: &Product| p.in_stock);
+
+        // 价格权重高：最便宜的耳机排第一，尽管无货
+        let ranked = rank_by(&products, &[cheap_first(4.0), in_stock_first(1.0)]);
+        assert_eq!(ranked[0].item.name, "耳机");
+
+        // 库存权重高：有货且便宜的手机排第一，耳机排最后
+        let ranked = rank_by(&products, &[cheap_first(1.0), in_stock_first(3.0)]);
+        assert_eq!(ranked[0].item.name, "手机");
+        assert_eq!(ranked[3].item.name, "耳机");
+    }
+
+    #[test]
+    fn ties_are_stable() {
+        let items = vec![("a", 1), ("b", 2), ("c", 1), ("d", 2)];
+        let ranked = rank_by(&items, &[RankKey::by_key(1.0, |x: &(&str, i32)| x.1)]);
+        assert_eq!(names(&ranked, |x| x.0), vec!["a", "c", "b", "d"]);
+        assert_eq!(ranked[0].score, ranked[1].score);
+
+        // 没有条件时保持输入顺序
+        let ranked = rank_by(&items, &[]);
+        assert_eq!(names(&ranked, |x| x.0), vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn custom_comparator_and_name_key() {
+        let products = products();
+        let by_name_len = RankKey::new(1.0, |a: &Product, b: &Product| {
+            a.name.chars().count().cmp(&b.name.chars().count())
+        });
+        let by_name = RankKey::by_key(0.1, |p: &Product| p.name.clone());
+        let ranked = rank_by(&products, &[by_name_len, by_name]);
+        assert_eq!(ranked[3].item.name, "笔记本");
+        assert!(rank_by::<Product>(&[], &[]).is_empty());
+    }
+}
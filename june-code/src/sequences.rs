@@ -0,0 +1,227 @@
+//! 惰性无限序列及组合器
+//!
+//! 这些迭代器本身永不结束，只有在被 `take`、`take_until` 等适配器截断
+//! 或被 `find` 之类的消费者提前停止时才会终止，元素按需逐个计算
+
+use std::collections::HashMap;
+use std::iter;
+
+/// 自然数 1, 2, 3, ...
+pub fn naturals() -> impl Iterator<Item = u64> {
+    1u64..
+}
+
+/// 素数 2, 3, 5, 7, ...（增量埃拉托斯特尼筛法）
+///
+/// 不需要预先确定上限：每找到一个素数 p，就在表中登记它的下一个倍数 p²，
+/// 走到某个合数时再把登记在它上面的素数挪到各自的下一个倍数
+pub fn primes() -> Primes {
+    Primes {
+        composites: HashMap::new(),
+        candidate: 2,
+    }
+}
+
+pub struct Primes {
+    /// 合数 -> 以它为下一个倍数的素数列表
+    composites: HashMap<u64, Vec<u64>>,
+    candidate: u64,
+}
+
+impl Iterator for Primes {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        loop {
+            let n = self.candidate;
+            self.candidate += 1;
+            match self.composites.remove(&n) {
+                Some(factors) => {
+                    for p in factors {
+                        self.composites.entry(n + p).or_default().push(p);
+                    }
+                }
+                None => {
+                    self.composites.entry(n * n).or_default().push(n);
+                    return Some(n);
+                }
+            }
+        }
+    }
+}
+
+/// 等比数列 first, first·ratio, first·ratio², ...
+pub fn geometric(first: f64, ratio: f64) -> impl Iterator<Item = f64> {
+    iter::successors(Some(first), move |&x| Some(x * ratio))
+}
+
+/// 为所有迭代器提供额外的组合器
+pub trait SequenceExt: Iterator + Sized {
+    /// 产出元素直到第一个满足 `predicate` 的元素（包含该元素），然后结束
+    ///
+    /// 与 `take_while` 不同，触发停止的元素本身也会被产出
+    fn take_until<P>(self, predicate: P) -> TakeUntil<Self, P>
+    where
+        P: FnMut(&Self::Item) -> bool,
+    {
+        TakeUntil {
+            iter: self,
+            predicate,
+            done: false,
+        }
+    }
+
+    /// 交替产出两个迭代器的元素，一方耗尽后继续产出另一方剩余的元素
+    fn interleave<J>(self, other: J) -> Interleave<Self, J::IntoIter>
+    where
+        J: IntoIterator<Item = Self::Item>,
+    {
+        Interleave {
+            a: self,
+            b: other.into_iter(),
+            take_a: true,
+        }
+    }
+
+    /// 产出相邻元素对 (x0, x1), (x1, x2), ...
+    fn pairwise(self) -> Pairwise<Self>
+    where
+        Self::Item: Clone,
+    {
+        Pairwise {
+            iter: self,
+            previous: None,
+        }
+    }
+}
+
+impl<I: Iterator> SequenceExt for I {}
+
+pub struct TakeUntil<I, P> {
+    iter: I,
+    predicate: P,
+    done: bool,
+}
+
+impl<I, P> Iterator for TakeUntil<I, P>
+where
+    I: Iterator,
+    P: FnMut(&I::Item) -> bool,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        if self.done {
+            return None;
+        }
+        let item = self.iter.next()?;
+        if (self.predicate)(&item) {
+            self.done = true;
+        }
+        Some(item)
+    }
+}
+
+pub struct Interleave<A, B> {
+    a: A,
+    b: B,
+    take_a: bool,
+}
+
+impl<A, B> Iterator for Interleave<A, B>
+where
+    A: Iterator,
+    B: Iterator<Item = A::Item>,
+{
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<A::Item> {
+        let take_a = self.take_a;
+        self.take_a = !take_a;
+        if take_a {
+            self.a.next().or_else(|| self.b.next())
+        } else {
+            self.b.next().or_else(|| self.a.next())
+        }
+    }
+}
+
+pub struct Pairwise<I: Iterator> {
+    iter: I,
+    previous: Option<I::Item>,
+}
+
+impl<I> Iterator for Pairwise<I>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+    type Item = (I::Item, I::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let previous = match self.previous.take() {
+            Some(item) => item,
+            None => self.iter.next()?,
+        };
+        let current = self.iter.next()?;
+        self.previous = Some(current.clone());
+        Some((previous, current))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_naturals() {
+        let first: Vec<u64> = naturals().take(5).collect();
+        assert_eq!(first, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_primes() {
+        let first: Vec<u64> = primes().take(10).collect();
+        assert_eq!(first, vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert_eq!(primes().nth(999), Some(7919));
+    }
+
+    #[test]
+    fn test_geometric() {
+        let halves: Vec<f64> = geometric(1.0, 0.5).take(4).collect();
+        assert_eq!(halves, vec![1.0, 0.5, 0.25, 0.125]);
+        // 部分和收敛到 2
+        let sum: f64 = geometric(1.0, 0.5).take(60).sum();
+        assert!((sum - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_take_until() {
+        // 包含第一个大于 20 的素数
+        let until: Vec<u64> = primes().take_until(|&p| p > 20).collect();
+        assert_eq!(until, vec![2, 3, 5, 7, 11, 13, 17, 19, 23]);
+
+        let empty: Vec<i32> = Vec::<i32>::new().into_iter().take_until(|_| true).collect();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_interleave() {
+        let mixed: Vec<u64> = naturals().interleave(primes()).take(6).collect();
+        assert_eq!(mixed, vec![1, 2, 2, 3, 3, 5]);
+
+        // 短的一方耗尽后继续产出另一方
+        let uneven: Vec<i32> = vec![1, 2].into_iter().interleave(vec![10, 20, 30, 40]).collect();
+        assert_eq!(uneven, vec![1, 10, 2, 20, 30, 40]);
+    }
+
+    #[test]
+    fn test_pairwise() {
+        let gaps: Vec<u64> = primes().pairwise().map(|(a, b)| b - a).take(5).collect();
+        assert_eq!(gaps, vec![1, 2, 2, 4, 2]);
+
+        assert_eq!(vec![1].into_iter().pairwise().count(), 0);
+        let pairs: Vec<(char, char)> = "abc".chars().pairwise().collect();
+        assert_eq!(pairs, vec![('a', 'b'), ('b', 'c')]);
+    }
+}
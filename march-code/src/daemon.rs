@@ -0,0 +1,93 @@
+use chrono::{Duration, Local, Utc};
+use march_code::{JsonFileStore, Reminder, ReminderState, TaskManager, TaskResult};
+use std::path::PathBuf;
+use std::thread;
+
+/// 提醒守护进程的配置
+pub struct DaemonConfig {
+    /// 截止时间在多久之内的任务需要提醒
+    pub window: Duration,
+    /// 两次检查之间的间隔
+    pub interval: std::time::Duration,
+    /// 只检查一次就退出，适合交给 cron 等定时调度
+    pub once: bool,
+    /// 是否尝试发送桌面通知，失败或未启用 `notify` 特性时打印到终端
+    pub desktop: bool,
+    pub state_file: PathBuf,
+}
+
+/// 周期性检查即将到期的任务并发送提醒
+///
+/// 每次检查都重新读取任务文件，其他命令对任务的修改会在下一轮生效
+pub fn run(store: &JsonFileStore, config: &DaemonConfig) -> TaskResult<()> {
+    println!(
+        "提醒守护进程已启动：提前 {} 分钟提醒，每 {} 秒检查一次，状态文件 {}",
+        config.window.num_minutes(),
+        config.interval.as_secs(),
+        config.state_file.display()
+    );
+
+    loop {
+        match check_once(store, config) {
+            Ok(_) => {},
+            // 常驻运行时单次失败（例如文件暂时不可读）不退出，下一轮再试
+            Err(e) if !config.once => eprintln!("错误: {}", e),
+            Err(e) => return Err(e),
+        }
+        if config.once {
+            return Ok(());
+        }
+        thread::sleep(config.interval);
+    }
+}
+
+/// 检查一轮，返回发送的提醒数量
+fn check_once(store: &JsonFileStore, config: &DaemonConfig) -> TaskResult<usize> {
+    let task_manager = TaskManager::load(store)?;
+    let tasks = task_manager.tasks();
+    let mut state = ReminderState::load(&config.state_file)?;
+    let mut changed = state.prune(&tasks);
+
+    let now = Utc::now();
+    let reminders = state.pending(&tasks, now, config.window);
+    for reminder in &reminders {
+        notify(reminder, now, config.desktop);
+        state.mark_notified(reminder);
+        changed = true;
+    }
+
+    if changed {
+        state.save(&config.state_file)?;
+    }
+    Ok(reminders.len())
+}
+
+fn notify(reminder: &Reminder, now: chrono::DateTime<Utc>, desktop: bool) {
+    let due = reminder.due.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+    let summary = if reminder.is_overdue(now) { "任务已逾期" } else { "任务即将到期" };
+    let body = format!("#{} {}（截止 {}）", reminder.id, reminder.title, due);
+
+    if desktop {
+        match desktop_notify(summary, &body) {
+            Ok(()) => return,
+            Err(e) => eprintln!("桌面通知发送失败，改为打印: {}", e),
+        }
+    }
+    println!("[{}] {}: {}", Local::now().format("%H:%M:%S"), summary, body);
+}
+
+#[cfg(feature = "notify")]
+fn desktop_notify(summary: &str, body: &str) -> Result<(), String> {
+    notify_rust::Notification::new()
+        .appname("march-code")
+        .summary(summary)
+        .body(body)
+        .show()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "notify"))]
+fn desktop_notify(_summary: &str, _body: &str) -> Result<(), String> {
+    Err("编译时未启用 notify 特性".to_string())
+}
@@ -0,0 +1,24 @@
+use thiserror::Error;
+
+/// 任务管理相关的错误
+#[derive(Debug, Error, PartialEq)]
+pub enum TaskError {
+    /// 指定ID的任务不存在
+    #[error("找不到ID为{0}的任务")]
+    NotFound(usize),
+
+    /// 无法识别的任务状态
+    #[error("无效的状态 '{0}'，可选值：todo, progress, done")]
+    InvalidStatus(String),
+
+    /// 读写任务数据失败
+    #[error("存储错误: {0}")]
+    StorageError(String),
+
+    /// 输入无法解析（例如ID不是数字）
+    #[error("解析错误: {0}")]
+    ParseError(String),
+}
+
+/// 任务操作的结果类型
+pub type TaskResult<T> = Result<T, TaskError>;
@@ -0,0 +1,36 @@
+//! 任务管理引擎
+//!
+//! 命令行程序 `march-code` 基于这个库实现，其他程序也可以直接嵌入：
+//! - [`Task`] / [`TaskStatus`]：任务及其状态
+//! - [`TaskManager`]：增删改查任务，失败时返回 [`TaskError`]
+//! - [`TaskStore`]：任务的加载和保存，内置 [`JsonFileStore`] 和 [`MemoryStore`]
+//! - [`Report`]：完成率、平均完成耗时、每周新建/完成数量和连续完成天数
+//! - [`ReminderState`]：截止时间提醒，记录已提醒过的任务避免重复
+//!
+//! ```
+//! use march_code::{MemoryStore, Task, TaskManager, TaskStatus, TaskError};
+//!
+//! let store = MemoryStore::new();
+//! let mut manager = TaskManager::load(&store).unwrap();
+//! let id = manager.add_task(Task::new("写周报".to_string(), "周五前提交".to_string()));
+//! manager.update_task_status(id, TaskStatus::Done).unwrap();
+//! manager.save(&store).unwrap();
+//!
+//! let manager = TaskManager::load(&store).unwrap();
+//! assert_eq!(manager.get_task(id).unwrap().status, TaskStatus::Done);
+//! assert_eq!(manager.get_task(42).unwrap_err(), TaskError::NotFound(42));
+//! ```
+
+pub mod error;
+pub mod models;
+pub mod reminders;
+pub mod stats;
+pub mod storage;
+pub mod tasks;
+
+pub use error::{TaskError, TaskResult};
+pub use models::task::{parse_due, StatusChange, Task, TaskStatus};
+pub use reminders::state::{state_path_for, Reminder, ReminderState};
+pub use stats::report::{Report, WeekStats};
+pub use storage::store::{JsonFileStore, MemoryStore, TaskSnapshot, TaskStore};
+pub use tasks::task_manager::TaskManager;
@@ -1,186 +1,352 @@
-use std::io;
 use std::env;
+use std::path::PathBuf;
+use std::process;
 
-mod models;
-mod tasks;
+mod daemon;
 mod ui;
 mod utils;
 
-use models::task::{Task, TaskStatus};
-use tasks::task_manager::TaskManager;
+use daemon::DaemonConfig;
+use march_code::{parse_due, state_path_for, JsonFileStore, Report, Task, TaskError, TaskManager, TaskResult, TaskStatus, TaskStore};
 use ui::cli::CliInterface;
+use ui::picker;
+use ui::render::{self, OutputFormat, RenderOptions};
+
+/// 默认的任务数据文件，可以用环境变量 `TASK_MANAGER_FILE` 指定其他路径
+const DEFAULT_DATA_FILE: &str = "tasks.json";
+
+/// 统计报告默认统计最近几周
+const DEFAULT_REPORT_WEEKS: usize = 8;
+
+/// 提醒守护进程默认提前多少分钟提醒
+const DEFAULT_REMINDER_WINDOW_MINUTES: u64 = 60;
+
+/// 提醒守护进程默认的检查间隔（秒）
+const DEFAULT_CHECK_INTERVAL_SECS: u64 = 60;
+
+/// 可以出现在任意位置的命令行选项
+struct CliOptions {
+    format: OutputFormat,
+    render: RenderOptions,
+    due: Option<String>,
+}
+
+impl CliOptions {
+    /// 从参数中取出选项，剩下的是命令和位置参数
+    fn parse(args: &mut Vec<String>) -> TaskResult<Self> {
+        // 遵循 NO_COLOR 约定：设置了该环境变量时默认不输出颜色
+        let no_color = take_flag(args, "--no-color") || env::var_os("NO_COLOR").is_some();
+        let format = match take_option(args, "--format")? {
+            Some(format) => format.parse()?,
+            None => OutputFormat::Table,
+        };
+        Ok(CliOptions {
+            format,
+            render: RenderOptions::new(!no_color),
+            due: take_option(args, "--due")?,
+        })
+    }
+}
+
+fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+    let before = args.len();
+    args.retain(|arg| arg != name);
+    args.len() != before
+}
+
+/// 取出 `--name value` 或 `--name=value` 形式的选项
+fn take_option(args: &mut Vec<String>, name: &str) -> TaskResult<Option<String>> {
+    let prefix = format!("{}=", name);
+    if let Some(index) = args.iter().position(|arg| arg.starts_with(&prefix)) {
+        return Ok(Some(args.remove(index)[prefix.len()..].to_string()));
+    }
+    match args.iter().position(|arg| arg == name) {
+        Some(index) if index + 1 < args.len() => {
+            args.remove(index);
+            Ok(Some(args.remove(index)))
+        }
+        Some(_) => Err(TaskError::ParseError(format!("选项 {} 缺少参数", name))),
+        None => Ok(None),
+    }
+}
+
+/// 解析 daemon 命令的选项
+fn parse_daemon_config(args: &[String], store: &JsonFileStore) -> TaskResult<DaemonConfig> {
+    let mut args = args.to_vec();
+    let parse_number = |name: &str, value: Option<String>, default: u64| -> TaskResult<u64> {
+        match value {
+            Some(value) => value
+                .parse::<u64>()
+                .ok()
+                .filter(|&n| n > 0)
+                .ok_or_else(|| TaskError::ParseError(format!("选项 {} 需要正整数，实际为 '{}'", name, value))),
+            None => Ok(default),
+        }
+    };
+    let window = parse_number("--window", take_option(&mut args, "--window")?, DEFAULT_REMINDER_WINDOW_MINUTES)?;
+    let interval = parse_number("--interval", take_option(&mut args, "--interval")?, DEFAULT_CHECK_INTERVAL_SECS)?;
+    let state_file = match take_option(&mut args, "--state")? {
+        Some(path) => PathBuf::from(path),
+        None => state_path_for(store.path()),
+    };
+
+    Ok(DaemonConfig {
+        window: chrono::Duration::minutes(window as i64),
+        interval: std::time::Duration::from_secs(interval),
+        once: take_flag(&mut args, "--once"),
+        desktop: !take_flag(&mut args, "--print"),
+        state_file,
+    })
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let mut task_manager = TaskManager::new();
+    let mut args: Vec<String> = env::args().collect();
+    let store = JsonFileStore::new(env::var("TASK_MANAGER_FILE").unwrap_or_else(|_| DEFAULT_DATA_FILE.to_string()));
     let cli = CliInterface::new();
 
-    if args.len() > 1 {
-        // 命令行参数模式
-        handle_command_args(&args, &mut task_manager);
-    } else {
-        // 交互式模式
-        run_interactive_mode(&mut task_manager, &cli);
+    let result = CliOptions::parse(&mut args).and_then(|options| {
+        let mut task_manager = TaskManager::load(&store)?;
+        if args.len() > 1 {
+            // 命令行参数模式
+            handle_command_args(&args, &cli, &options, &mut task_manager, &store)
+        } else {
+            // 交互式模式
+            run_interactive_mode(&mut task_manager, &cli, &options, &store);
+            Ok(())
+        }
+    });
+
+    if let Err(e) = result {
+        eprintln!("错误: {}", e);
+        process::exit(1);
     }
 }
 
-fn run_interactive_mode(task_manager: &mut TaskManager, cli: &CliInterface) {
+/// 解析任务ID
+fn parse_id(input: &str) -> TaskResult<usize> {
+    input
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| TaskError::ParseError(format!("无效的ID '{}'，请输入数字", input.trim())))
+}
+
+/// 解析交互式菜单中的状态编号
+fn parse_status_choice(input: &str) -> TaskResult<TaskStatus> {
+    match input.trim() {
+        "1" => Ok(TaskStatus::Todo),
+        "2" => Ok(TaskStatus::InProgress),
+        "3" => Ok(TaskStatus::Done),
+        other => Err(TaskError::InvalidStatus(other.to_string())),
+    }
+}
+
+/// 按输出格式打印任务列表
+fn print_list(task_manager: &TaskManager, options: &CliOptions) -> TaskResult<()> {
+    let tasks = task_manager.tasks();
+    let output = match options.format {
+        OutputFormat::Table => render::render_table(&tasks, &options.render),
+        OutputFormat::Json => render::render_json_list(&tasks, &options.render)?,
+    };
+    print!("{}", output);
+    Ok(())
+}
+
+/// 按输出格式打印任务详情
+fn print_task(task_manager: &TaskManager, id: usize, options: &CliOptions) -> TaskResult<()> {
+    let task = task_manager.get_task(id)?;
+    let output = match options.format {
+        OutputFormat::Table => render::render_details(id, task, &options.render),
+        OutputFormat::Json => render::render_json_task(id, task, &options.render)?,
+    };
+    print!("{}", output);
+    Ok(())
+}
+
+/// 模糊搜索选择任务，再对选中的任务执行操作
+fn pick(task_manager: &mut TaskManager, cli: &CliInterface, options: &CliOptions, store: &dyn TaskStore) -> TaskResult<()> {
+    if task_manager.count() == 0 {
+        println!("没有任务记录");
+        return Ok(());
+    }
+    let Some(id) = picker::pick_task(&task_manager.tasks(), options.render.color)? else {
+        println!("已取消");
+        return Ok(());
+    };
+
+    print!("{}", render::render_details(id, task_manager.get_task(id)?, &options.render));
+    cli.display_pick_actions();
+    match cli.get_user_input("请选择操作: ").trim() {
+        "v" | "V" => {},
+        "u" | "U" => {
+            cli.display_status_options();
+            let status = parse_status_choice(&cli.get_user_input("请选择新的状态 (1-3): "))?;
+            task_manager.update_task_status(id, status)?;
+            task_manager.save(store)?;
+            println!("任务状态已更新！");
+        },
+        "d" | "D" => {
+            task_manager.delete_task(id)?;
+            task_manager.save(store)?;
+            println!("任务已删除！");
+        },
+        _ => {},
+    }
+    Ok(())
+}
+
+/// 生成统计报告，指定 `--csv <文件>` 时同时导出 CSV
+fn report(args: &[String], task_manager: &TaskManager, options: &CliOptions) -> TaskResult<()> {
+    let mut args = args.to_vec();
+    let weeks = match take_option(&mut args, "--weeks")? {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| TaskError::ParseError(format!("选项 --weeks 需要整数，实际为 '{}'", value)))?,
+        None => DEFAULT_REPORT_WEEKS,
+    };
+    let csv_file = take_option(&mut args, "--csv")?;
+
+    let report = Report::from_tasks(&task_manager.tasks(), chrono::Local::now().date_naive(), weeks);
+    print!("{}", render::render_report(&report, &options.render));
+    if let Some(path) = csv_file {
+        std::fs::write(&path, report.to_csv())
+            .map_err(|e| TaskError::StorageError(format!("{}: {}", path, e)))?;
+        println!("报告已导出到 {}", path);
+    }
+    Ok(())
+}
+
+fn run_interactive_mode(task_manager: &mut TaskManager, cli: &CliInterface, options: &CliOptions, store: &dyn TaskStore) {
     println!("欢迎使用任务管理系统");
-    
+
     loop {
         cli.display_menu();
         let choice = cli.get_user_input("请输入你的选择: ");
-        
-        match choice.trim() {
+
+        let result = match choice.trim() {
             "1" => {
                 let title = cli.get_user_input("任务标题: ");
                 let description = cli.get_user_input("任务描述: ");
-                let task = Task::new(title, description);
+                let task = Task::new(title.trim().to_string(), description.trim().to_string());
                 task_manager.add_task(task);
-                println!("任务已添加！");
-            },
-            "2" => {
-                task_manager.list_tasks();
-            },
-            "3" => {
-                let id_str = cli.get_user_input("请输入要更新的任务ID: ");
-                match id_str.trim().parse::<usize>() {
-                    Ok(id) => {
-                        cli.display_status_options();
-                        let status_input = cli.get_user_input("请选择新的状态 (1-3): ");
-                        
-                        let new_status = match status_input.trim() {
-                            "1" => TaskStatus::Todo,
-                            "2" => TaskStatus::InProgress,
-                            "3" => TaskStatus::Done,
-                            _ => {
-                                println!("无效的状态选择");
-                                continue;
-                            }
-                        };
-                        
-                        if task_manager.update_task_status(id, new_status) {
-                            println!("任务状态已更新！");
-                        } else {
-                            println!("找不到指定ID的任务");
-                        }
-                    },
-                    Err(_) => println!("无效的ID，请输入数字"),
-                }
-            },
-            "4" => {
-                let id_str = cli.get_user_input("请输入要删除的任务ID: ");
-                match id_str.trim().parse::<usize>() {
-                    Ok(id) => {
-                        if task_manager.delete_task(id) {
-                            println!("任务已删除！");
-                        } else {
-                            println!("找不到指定ID的任务");
-                        }
-                    },
-                    Err(_) => println!("无效的ID，请输入数字"),
-                }
-            },
-            "5" => {
-                let id_str = cli.get_user_input("请输入要查看的任务ID: ");
-                match id_str.trim().parse::<usize>() {
-                    Ok(id) => {
-                        task_manager.view_task(id);
-                    },
-                    Err(_) => println!("无效的ID，请输入数字"),
-                }
+                task_manager.save(store).map(|_| println!("任务已添加！"))
             },
+            "2" => print_list(task_manager, options),
+            "3" => parse_id(&cli.get_user_input("请输入要更新的任务ID: ")).and_then(|id| {
+                cli.display_status_options();
+                let status = parse_status_choice(&cli.get_user_input("请选择新的状态 (1-3): "))?;
+                task_manager.update_task_status(id, status)?;
+                task_manager.save(store)?;
+                println!("任务状态已更新！");
+                Ok(())
+            }),
+            "4" => parse_id(&cli.get_user_input("请输入要删除的任务ID: ")).and_then(|id| {
+                task_manager.delete_task(id)?;
+                task_manager.save(store)?;
+                println!("任务已删除！");
+                Ok(())
+            }),
+            "5" => parse_id(&cli.get_user_input("请输入要查看的任务ID: "))
+                .and_then(|id| print_task(task_manager, id, options)),
+            "6" => pick(task_manager, cli, options, store),
             "q" | "Q" => {
                 println!("感谢使用，再见！");
                 break;
             },
-            _ => println!("无效的选择，请重试"),
+            _ => {
+                println!("无效的选择，请重试");
+                Ok(())
+            },
+        };
+
+        if let Err(e) = result {
+            println!("错误: {}", e);
         }
     }
 }
 
-fn handle_command_args(args: &[String], task_manager: &mut TaskManager) {
+fn handle_command_args(
+    args: &[String],
+    cli: &CliInterface,
+    options: &CliOptions,
+    task_manager: &mut TaskManager,
+    store: &JsonFileStore,
+) -> TaskResult<()> {
     match args[1].as_str() {
         "add" => {
             if args.len() < 4 {
-                println!("使用方式: {} add <标题> <描述>", args[0]);
-                return;
+                println!("使用方式: {} add <标题> <描述> [--due YYYY-MM-DD]", args[0]);
+                return Ok(());
             }
-            let task = Task::new(args[2].clone(), args[3].clone());
-            task_manager.add_task(task);
-            println!("任务已添加！");
+            let mut task = Task::new(args[2].clone(), args[3].clone());
+            if let Some(due) = &options.due {
+                task = task.with_due(parse_due(due)?);
+            }
+            let id = task_manager.add_task(task);
+            task_manager.save(store)?;
+            println!("任务已添加！ID: {}", id);
         },
         "list" => {
-            task_manager.list_tasks();
+            print_list(task_manager, options)?;
         },
         "update" => {
             if args.len() < 4 {
                 println!("使用方式: {} update <ID> <状态>", args[0]);
-                return;
-            }
-            
-            match args[2].parse::<usize>() {
-                Ok(id) => {
-                    let new_status = match args[3].as_str() {
-                        "todo" => TaskStatus::Todo,
-                        "progress" => TaskStatus::InProgress,
-                        "done" => TaskStatus::Done,
-                        _ => {
-                            println!("无效的状态，可选值：todo, progress, done");
-                            return;
-                        }
-                    };
-                    
-                    if task_manager.update_task_status(id, new_status) {
-                        println!("任务状态已更新！");
-                    } else {
-                        println!("找不到指定ID的任务");
-                    }
-                },
-                Err(_) => println!("无效的ID，请输入数字"),
+                return Ok(());
             }
+
+            let id = parse_id(&args[2])?;
+            let new_status: TaskStatus = args[3].parse()?;
+            task_manager.update_task_status(id, new_status)?;
+            task_manager.save(store)?;
+            println!("任务状态已更新！");
         },
         "delete" => {
             if args.len() < 3 {
                 println!("使用方式: {} delete <ID>", args[0]);
-                return;
-            }
-            
-            match args[2].parse::<usize>() {
-                Ok(id) => {
-                    if task_manager.delete_task(id) {
-                        println!("任务已删除！");
-                    } else {
-                        println!("找不到指定ID的任务");
-                    }
-                },
-                Err(_) => println!("无效的ID，请输入数字"),
+                return Ok(());
             }
+
+            task_manager.delete_task(parse_id(&args[2])?)?;
+            task_manager.save(store)?;
+            println!("任务已删除！");
         },
         "view" => {
             if args.len() < 3 {
                 println!("使用方式: {} view <ID>", args[0]);
-                return;
-            }
-            
-            match args[2].parse::<usize>() {
-                Ok(id) => {
-                    task_manager.view_task(id);
-                },
-                Err(_) => println!("无效的ID，请输入数字"),
+                return Ok(());
             }
+
+            print_task(task_manager, parse_id(&args[2])?, options)?;
+        },
+        "pick" => {
+            pick(task_manager, cli, options, store)?;
+        },
+        "report" => {
+            report(&args[2..], task_manager, options)?;
+        },
+        "daemon" => {
+            daemon::run(store, &parse_daemon_config(&args[2..], store)?)?;
         },
         "help" => {
             println!("任务管理器 - 命令列表：");
-            println!("  {} add <标题> <描述> - 添加新任务", args[0]);
-            println!("  {} list - 列出所有任务", args[0]);
+            println!("  {} add <标题> <描述> [--due YYYY-MM-DD] - 添加新任务", args[0]);
+            println!("  {} list [--format table|json] - 列出所有任务", args[0]);
             println!("  {} update <ID> <状态> - 更新任务状态 (状态: todo, progress, done)", args[0]);
             println!("  {} delete <ID> - 删除任务", args[0]);
-            println!("  {} view <ID> - 查看任务详情", args[0]);
+            println!("  {} view <ID> [--format table|json] - 查看任务详情", args[0]);
+            println!("  {} pick - 模糊搜索任务标题，选中后查看、更新或删除", args[0]);
+            println!("  {} report [--weeks <周数>] [--csv <文件>] - 统计完成率、每周新建/完成数量和连续完成天数", args[0]);
+            println!(
+                "  {} daemon [--window <分钟>] [--interval <秒>] [--once] [--print] [--state <文件>] - 提醒即将到期的任务",
+                args[0]
+            );
             println!("  {} help - 显示此帮助", args[0]);
+            println!("选项: --no-color 关闭颜色输出（也可设置环境变量 NO_COLOR）");
+            println!("任务保存在 {}（可用环境变量 TASK_MANAGER_FILE 修改）", DEFAULT_DATA_FILE);
         },
         _ => {
             println!("未知命令。使用 '{} help' 查看可用命令", args[0]);
         }
     }
-} 
\ No newline at end of file
+    Ok(())
+}
@@ -1 +1 @@
-pub mod task; 
\ No newline at end of file
+pub mod task;
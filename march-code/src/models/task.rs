@@ -1,8 +1,13 @@
 use std::fmt;
-use chrono::{DateTime, Utc};
+use std::str::FromStr;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::TaskError;
 
 /// 任务状态枚举
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TaskStatus {
     Todo,       // 待办
     InProgress, // 进行中
@@ -19,14 +24,57 @@ impl fmt::Display for TaskStatus {
     }
 }
 
+impl FromStr for TaskStatus {
+    type Err = TaskError;
+
+    /// 解析命令行中的状态名（todo, progress, done）
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "todo" => Ok(TaskStatus::Todo),
+            "progress" => Ok(TaskStatus::InProgress),
+            "done" => Ok(TaskStatus::Done),
+            other => Err(TaskError::InvalidStatus(other.to_string())),
+        }
+    }
+}
+
 /// 任务结构体
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
     pub title: String,
     pub description: String,
     pub status: TaskStatus,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// 截止时间，没有截止时间的任务永远不会逾期
+    #[serde(default)]
+    pub due: Option<DateTime<Utc>>,
+    /// 状态变更记录，按时间顺序追加，用于统计报告
+    #[serde(default)]
+    pub history: Vec<StatusChange>,
+}
+
+/// 一次状态变更
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusChange {
+    pub status: TaskStatus,
+    pub at: DateTime<Utc>,
+}
+
+/// 解析本地时间的截止时间：`YYYY-MM-DD`（当天 23:59:59）或 `YYYY-MM-DD HH:MM`
+pub fn parse_due(input: &str) -> Result<DateTime<Utc>, TaskError> {
+    let input = input.trim();
+    let naive = NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M")
+        .or_else(|_| {
+            NaiveDate::parse_from_str(input, "%Y-%m-%d")
+                .map(|date| date.and_time(NaiveTime::from_hms_opt(23, 59, 59).unwrap()))
+        })
+        .map_err(|_| TaskError::ParseError(format!("无效的截止时间 '{}'，格式为 YYYY-MM-DD 或 'YYYY-MM-DD HH:MM'", input)))?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|local| local.with_timezone(&Utc))
+        .ok_or_else(|| TaskError::ParseError(format!("本地时间 '{}' 不存在", input)))
 }
 
 impl Task {
@@ -39,13 +87,42 @@ impl Task {
             status: TaskStatus::Todo, // 默认为待办状态
             created_at: now,
             updated_at: now,
+            due: None,
+            history: Vec::new(),
         }
     }
 
+    /// 设置截止时间
+    pub fn with_due(mut self, due: DateTime<Utc>) -> Self {
+        self.due = Some(due);
+        self
+    }
+
+    /// 在 `now` 时是否已逾期（已完成的任务不算逾期）
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        self.status != TaskStatus::Done && self.due.is_some_and(|due| due < now)
+    }
+
     /// 更新任务状态
     pub fn update_status(&mut self, status: TaskStatus) {
+        let now = Utc::now();
+        self.history.push(StatusChange {
+            status: status.clone(),
+            at: now,
+        });
         self.status = status;
-        self.updated_at = Utc::now();
+        self.updated_at = now;
+    }
+
+    /// 完成时间，未完成时返回 `None`
+    ///
+    /// 取最后一次变为已完成的时间；没有变更记录的旧数据用更新时间代替
+    pub fn completed_at(&self) -> Option<DateTime<Utc>> {
+        if self.status != TaskStatus::Done {
+            return None;
+        }
+        let completed = self.history.iter().rev().find(|change| change.status == TaskStatus::Done);
+        Some(completed.map_or(self.updated_at, |change| change.at))
     }
 
     /// 任务详情显示
@@ -56,5 +133,8 @@ impl Task {
         println!("状态: {}", self.status);
         println!("创建时间: {}", self.created_at.format("%Y-%m-%d %H:%M:%S"));
         println!("更新时间: {}", self.updated_at.format("%Y-%m-%d %H:%M:%S"));
+        if let Some(due) = self.due {
+            println!("截止时间: {}", due.with_timezone(&Local).format("%Y-%m-%d %H:%M"));
+        }
     }
 } 
\ No newline at end of file
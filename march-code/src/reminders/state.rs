@@ -0,0 +1,183 @@
+use crate::error::TaskResult;
+use crate::models::task::{Task, TaskStatus};
+use crate::storage::store::{read_json, write_json};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// 需要提醒的任务
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reminder {
+    pub id: usize,
+    pub title: String,
+    pub due: DateTime<Utc>,
+}
+
+impl Reminder {
+    /// 在 `now` 时是否已经过了截止时间
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        self.due < now
+    }
+}
+
+/// 已发送过的提醒，保存在状态文件中，重启守护进程也不会重复提醒
+///
+/// 记录的是提醒时任务的截止时间，截止时间被修改后会再次提醒
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReminderState {
+    notified: BTreeMap<usize, DateTime<Utc>>,
+}
+
+impl ReminderState {
+    /// 从状态文件读取，文件不存在时返回空状态
+    pub fn load(path: &Path) -> TaskResult<Self> {
+        Ok(read_json(path)?.unwrap_or_default())
+    }
+
+    /// 保存到状态文件
+    pub fn save(&self, path: &Path) -> TaskResult<()> {
+        write_json(path, self)
+    }
+
+    /// 找出截止时间在 `now + window` 之前、尚未完成且没有提醒过的任务，按截止时间排序
+    ///
+    /// 已经逾期但还没提醒过的任务也会返回
+    pub fn pending(&self, tasks: &[(usize, &Task)], now: DateTime<Utc>, window: Duration) -> Vec<Reminder> {
+        let deadline = now + window;
+        let mut reminders: Vec<Reminder> = tasks
+            .iter()
+            .filter(|(_, task)| task.status != TaskStatus::Done)
+            .filter_map(|&(id, task)| task.due.map(|due| (id, task, due)))
+            .filter(|&(id, _, due)| due <= deadline && self.notified.get(&id) != Some(&due))
+            .map(|(id, task, due)| Reminder {
+                id,
+                title: task.title.clone(),
+                due,
+            })
+            .collect();
+        reminders.sort_by_key(|reminder| (reminder.due, reminder.id));
+        reminders
+    }
+
+    /// 记录已提醒
+    pub fn mark_notified(&mut self, reminder: &Reminder) {
+        self.notified.insert(reminder.id, reminder.due);
+    }
+
+    /// 删除已不存在或已完成任务的记录，返回是否有变化
+    pub fn prune(&mut self, tasks: &[(usize, &Task)]) -> bool {
+        let before = self.notified.len();
+        self.notified.retain(|id, _| {
+            tasks
+                .iter()
+                .any(|(task_id, task)| task_id == id && task.status != TaskStatus::Done)
+        });
+        self.notified.len() != before
+    }
+}
+
+/// 数据文件对应的提醒状态文件：`tasks.json` -> `tasks.reminders.json`
+pub fn state_path_for(data_file: &Path) -> PathBuf {
+    data_file.with_extension("reminders.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap()
+    }
+
+    fn task_due(title: &str, due: DateTime<Utc>) -> Task {
+        Task::new(title.to_string(), String::new()).with_due(due)
+    }
+
+    fn ids(reminders: &[Reminder]) -> Vec<usize> {
+        reminders.iter().map(|reminder| reminder.id).collect()
+    }
+
+    #[test]
+    fn pending_selects_due_within_window() {
+        let soon = task_due("soon", now() + Duration::minutes(30));
+        let overdue = task_due("overdue", now() - Duration::hours(2));
+        let later = task_due("later", now() + Duration::hours(3));
+        let no_due = Task::new("no due".to_string(), String::new());
+        let mut done = task_due("done", now());
+        done.update_status(TaskStatus::Done);
+        let tasks = [(1, &soon), (2, &overdue), (3, &later), (4, &no_due), (5, &done)];
+
+        let reminders = ReminderState::default().pending(&tasks, now(), Duration::hours(1));
+        // 按截止时间排序，逾期的排在前面
+        assert_eq!(ids(&reminders), vec![2, 1]);
+        assert!(reminders[0].is_overdue(now()));
+        assert!(!reminders[1].is_overdue(now()));
+    }
+
+    #[test]
+    fn reminder_fires_once() {
+        let task = task_due("soon", now() + Duration::minutes(10));
+        let tasks = [(1, &task)];
+        let mut state = ReminderState::default();
+
+        let reminders = state.pending(&tasks, now(), Duration::hours(1));
+        assert_eq!(ids(&reminders), vec![1]);
+        state.mark_notified(&reminders[0]);
+
+        assert!(state.pending(&tasks, now(), Duration::hours(1)).is_empty());
+        assert!(state.pending(&tasks, now() + Duration::hours(1), Duration::hours(1)).is_empty());
+    }
+
+    #[test]
+    fn changed_due_fires_again() {
+        let mut task = task_due("soon", now() + Duration::minutes(10));
+        let mut state = ReminderState::default();
+        let reminders = state.pending(&[(1, &task)], now(), Duration::hours(1));
+        state.mark_notified(&reminders[0]);
+
+        task.due = Some(now() + Duration::minutes(40));
+        let reminders = state.pending(&[(1, &task)], now(), Duration::hours(1));
+        assert_eq!(ids(&reminders), vec![1]);
+    }
+
+    #[test]
+    fn prune_drops_deleted_and_done_tasks() {
+        let kept = task_due("kept", now());
+        let mut finished = task_due("finished", now());
+        let deleted = task_due("deleted", now());
+        let mut state = ReminderState::default();
+        let tasks = [(1, &kept), (2, &finished), (3, &deleted)];
+        for reminder in state.pending(&tasks, now(), Duration::hours(1)) {
+            state.mark_notified(&reminder);
+        }
+
+        finished.update_status(TaskStatus::Done);
+        let remaining = [(1, &kept), (2, &finished)];
+        assert!(state.prune(&remaining));
+        assert_eq!(state.notified.keys().copied().collect::<Vec<_>>(), vec![1]);
+        // 没有可清理的记录时返回 false
+        assert!(!state.prune(&remaining));
+        // 仍保留的记录照样去重
+        assert!(state.pending(&remaining, now(), Duration::hours(1)).is_empty());
+    }
+
+    #[test]
+    fn state_survives_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("march-code-reminders-{}", std::process::id()));
+        let path = state_path_for(&dir.join("tasks.json"));
+        assert_eq!(path.file_name().unwrap(), "tasks.reminders.json");
+        assert!(ReminderState::load(&path).unwrap().notified.is_empty());
+
+        let task = task_due("soon", now());
+        let mut state = ReminderState::default();
+        let reminders = state.pending(&[(1, &task)], now(), Duration::hours(1));
+        state.mark_notified(&reminders[0]);
+        state.save(&path).unwrap();
+
+        let loaded = ReminderState::load(&path).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(loaded.pending(&[(1, &task)], now(), Duration::hours(1)).is_empty());
+    }
+}
@@ -0,0 +1,274 @@
+use crate::models::task::{Task, TaskStatus};
+use chrono::{DateTime, Datelike, Days, Duration, Local, NaiveDate, Utc};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// 一周的统计，周一为一周的第一天
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeekStats {
+    pub week_start: NaiveDate,
+    pub created: usize,
+    pub completed: usize,
+}
+
+/// 任务统计报告
+///
+/// 按本地日期统计，已删除的任务不在统计范围内
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub total: usize,
+    pub done: usize,
+    pub in_progress: usize,
+    pub todo: usize,
+    /// 从创建到完成的平均耗时，没有已完成任务时为 `None`
+    pub average_time_to_done: Option<Duration>,
+    /// 最近若干周，从早到晚排列，包含本周
+    pub weeks: Vec<WeekStats>,
+    /// 截至今天连续有任务完成的天数（今天还没完成任务时从昨天算起）
+    pub current_streak: usize,
+    pub longest_streak: usize,
+}
+
+fn local_date(time: DateTime<Utc>) -> NaiveDate {
+    time.with_timezone(&Local).date_naive()
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Days::new(date.weekday().num_days_from_monday() as u64)
+}
+
+impl Report {
+    /// 根据任务生成报告，`today` 为本地日期，统计最近 `weeks` 周
+    pub fn from_tasks(tasks: &[(usize, &Task)], today: NaiveDate, weeks: usize) -> Self {
+        let count = |status: TaskStatus| tasks.iter().filter(|(_, task)| task.status == status).count();
+        let completions: Vec<(&Task, DateTime<Utc>)> = tasks
+            .iter()
+            .filter_map(|&(_, task)| task.completed_at().map(|at| (task, at)))
+            .collect();
+
+        let average_time_to_done = if completions.is_empty() {
+            None
+        } else {
+            let total: Duration = completions
+                .iter()
+                .map(|(task, at)| (*at - task.created_at).max(Duration::zero()))
+                .sum();
+            Some(total / completions.len() as i32)
+        };
+
+        let current_week = week_start(today);
+        let weeks: Vec<WeekStats> = (0..weeks as u64)
+            .rev()
+            .map(|offset| {
+                let start = current_week - Days::new(offset * 7);
+                let in_week = |time: DateTime<Utc>| week_start(local_date(time)) == start;
+                WeekStats {
+                    week_start: start,
+                    created: tasks.iter().filter(|(_, task)| in_week(task.created_at)).count(),
+                    completed: completions.iter().filter(|(_, at)| in_week(*at)).count(),
+                }
+            })
+            .collect();
+
+        let days: BTreeSet<NaiveDate> = completions.iter().map(|&(_, at)| local_date(at)).collect();
+        let (current_streak, longest_streak) = streaks(&days, today);
+
+        Report {
+            total: tasks.len(),
+            done: count(TaskStatus::Done),
+            in_progress: count(TaskStatus::InProgress),
+            todo: count(TaskStatus::Todo),
+            average_time_to_done,
+            weeks,
+            current_streak,
+            longest_streak,
+        }
+    }
+
+    /// 完成率，没有任务时为 0
+    pub fn completion_rate(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.done as f64 / self.total as f64
+        }
+    }
+
+    /// 导出为 CSV：先是 `metric,value` 汇总，空一行后是每周统计
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("metric,value\n");
+        let average_hours = self
+            .average_time_to_done
+            .map(|d| format!("{:.2}", d.num_seconds() as f64 / 3600.0))
+            .unwrap_or_default();
+        let metrics = [
+            ("total", self.total.to_string()),
+            ("done", self.done.to_string()),
+            ("in_progress", self.in_progress.to_string()),
+            ("todo", self.todo.to_string()),
+            ("completion_rate", format!("{:.4}", self.completion_rate())),
+            ("average_hours_to_done", average_hours),
+            ("current_streak_days", self.current_streak.to_string()),
+            ("longest_streak_days", self.longest_streak.to_string()),
+        ];
+        for (name, value) in metrics {
+            let _ = writeln!(csv, "{},{}", name, value);
+        }
+
+        csv.push_str("\nweek_start,created,completed\n");
+        for week in &self.weeks {
+            let _ = writeln!(csv, "{},{},{}", week.week_start, week.created, week.completed);
+        }
+        csv
+    }
+}
+
+/// 计算当前和最长的连续完成天数
+fn streaks(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> (usize, usize) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for &day in days {
+        run = match previous {
+            Some(prev) if prev.succ_opt() == Some(day) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    // 今天还没完成任务不算中断
+    let mut day = if days.contains(&today) { today } else { today - Days::new(1) };
+    let mut current = 0;
+    while days.contains(&day) {
+        current += 1;
+        match day.pred_opt() {
+            Some(prev) => day = prev,
+            None => break,
+        }
+    }
+    (current, longest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task::StatusChange;
+    use chrono::{NaiveTime, TimeZone};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// 本地日期 `day` 的 `hour` 点
+    fn at(day: NaiveDate, hour: u32) -> DateTime<Utc> {
+        let time = NaiveTime::from_hms_opt(hour, 0, 0).unwrap();
+        Local.from_local_datetime(&day.and_time(time)).earliest().unwrap().with_timezone(&Utc)
+    }
+
+    fn task(created: DateTime<Utc>, done: Option<DateTime<Utc>>) -> Task {
+        let mut task = Task::new("任务".to_string(), String::new());
+        task.created_at = created;
+        task.updated_at = created;
+        if let Some(done) = done {
+            task.status = TaskStatus::Done;
+            task.updated_at = done;
+            task.history.push(StatusChange { status: TaskStatus::Done, at: done });
+        }
+        task
+    }
+
+    fn done_on(days: &[NaiveDate]) -> Vec<Task> {
+        days.iter().map(|&day| task(at(day, 9), Some(at(day, 12)))).collect()
+    }
+
+    fn build(tasks: &[Task], today: NaiveDate, weeks: usize) -> Report {
+        let tasks: Vec<(usize, &Task)> = tasks.iter().enumerate().map(|(i, t)| (i + 1, t)).collect();
+        Report::from_tasks(&tasks, today, weeks)
+    }
+
+    #[test]
+    fn empty_task_list() {
+        let report = build(&[], date(2024, 3, 13), 2);
+        assert_eq!(report.total, 0);
+        assert_eq!(report.completion_rate(), 0.0);
+        assert_eq!(report.average_time_to_done, None);
+        assert_eq!((report.current_streak, report.longest_streak), (0, 0));
+        assert_eq!(report.weeks.iter().map(|w| w.week_start).collect::<Vec<_>>(), vec![date(2024, 3, 4), date(2024, 3, 11)]);
+        assert!(report.weeks.iter().all(|w| w.created == 0 && w.completed == 0));
+
+        let csv = report.to_csv();
+        assert!(csv.contains("completion_rate,0.0000\n"));
+        assert!(csv.contains("average_hours_to_done,\n"));
+        assert!(csv.ends_with("week_start,created,completed\n2024-03-04,0,0\n2024-03-11,0,0\n"));
+    }
+
+    #[test]
+    fn broken_streak() {
+        // 3 月 4-6 日连续三天，7 日中断，8-9 日再连续两天
+        let days = [date(2024, 3, 4), date(2024, 3, 5), date(2024, 3, 6), date(2024, 3, 8), date(2024, 3, 9)];
+        let tasks = done_on(&days);
+
+        let report = build(&tasks, date(2024, 3, 9), 1);
+        assert_eq!((report.current_streak, report.longest_streak), (2, 3));
+        // 今天还没完成任务，从昨天算起
+        assert_eq!(build(&tasks, date(2024, 3, 10), 1).current_streak, 2);
+        // 昨天也没有完成任务，当前连续天数归零
+        assert_eq!(build(&tasks, date(2024, 3, 11), 1).current_streak, 0);
+    }
+
+    #[test]
+    fn same_day_completions_count_once() {
+        let day = date(2024, 3, 5);
+        let tasks = done_on(&[day, day, day]);
+        let report = build(&tasks, day, 1);
+        assert_eq!((report.current_streak, report.longest_streak), (1, 1));
+    }
+
+    #[test]
+    fn weekly_buckets_split_on_monday() {
+        let sunday = date(2024, 3, 10);
+        let monday = date(2024, 3, 11);
+        let tasks = vec![
+            // 周日深夜创建、周一完成，分别计入两周
+            task(at(sunday, 23), Some(at(monday, 1))),
+            task(at(sunday, 10), None),
+            task(at(monday, 8), Some(at(monday, 9))),
+            // 统计范围之外的旧任务
+            task(at(date(2024, 2, 1), 9), Some(at(date(2024, 2, 1), 10))),
+        ];
+
+        let report = build(&tasks, date(2024, 3, 13), 2);
+        assert_eq!(
+            report.weeks,
+            vec![
+                WeekStats { week_start: date(2024, 3, 4), created: 2, completed: 0 },
+                WeekStats { week_start: monday, created: 1, completed: 2 },
+            ]
+        );
+        assert!(report.to_csv().ends_with("2024-03-04,2,0\n2024-03-11,1,2\n"));
+    }
+
+    #[test]
+    fn average_time_to_done_and_counts() {
+        let day = date(2024, 3, 5);
+        let mut in_progress = task(at(day, 8), None);
+        in_progress.status = TaskStatus::InProgress;
+        let tasks = vec![
+            task(at(day, 8), Some(at(day, 9))),
+            task(at(day, 8), Some(at(day, 11))),
+            in_progress,
+            task(at(day, 8), None),
+        ];
+
+        let report = build(&tasks, day, 1);
+        assert_eq!((report.total, report.done, report.in_progress, report.todo), (4, 2, 1, 1));
+        assert_eq!(report.average_time_to_done, Some(Duration::hours(2)));
+        assert_eq!(report.completion_rate(), 0.5);
+
+        let csv = report.to_csv();
+        assert!(csv.starts_with("metric,value\ntotal,4\ndone,2\nin_progress,1\ntodo,1\n"));
+        assert!(csv.contains("completion_rate,0.5000\naverage_hours_to_done,2.00\n"));
+        assert!(csv.contains("current_streak_days,1\nlongest_streak_days,1\n"));
+    }
+}
@@ -0,0 +1,228 @@
+use crate::error::{TaskError, TaskResult};
+use crate::models::task::Task;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 持久化的任务数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskSnapshot {
+    /// 下一个新任务的ID，删除任务后ID也不会被复用
+    pub next_id: usize,
+    pub tasks: BTreeMap<usize, Task>,
+}
+
+impl Default for TaskSnapshot {
+    fn default() -> Self {
+        TaskSnapshot {
+            next_id: 1,
+            tasks: BTreeMap::new(),
+        }
+    }
+}
+
+/// 任务存储
+///
+/// `TaskManager` 通过它加载和保存任务，嵌入到其他程序时可以换成自己的实现
+pub trait TaskStore {
+    /// 读取全部任务，尚无数据时返回空快照
+    fn load(&self) -> TaskResult<TaskSnapshot>;
+
+    /// 用快照覆盖已保存的任务
+    fn save(&self, snapshot: &TaskSnapshot) -> TaskResult<()>;
+}
+
+/// 保存为 JSON 文件的任务存储
+pub struct JsonFileStore {
+    path: PathBuf,
+}
+
+impl JsonFileStore {
+    /// 创建文件存储，文件在第一次保存时创建
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        JsonFileStore { path: path.into() }
+    }
+
+    /// 数据文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+fn storage_error(path: &Path, e: impl std::fmt::Display) -> TaskError {
+    TaskError::StorageError(format!("{}: {}", path.display(), e))
+}
+
+/// 读取 JSON 文件，文件不存在时返回 `None`
+pub(crate) fn read_json<T: DeserializeOwned>(path: &Path) -> TaskResult<Option<T>> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| TaskError::ParseError(format!("{}: {}", path.display(), e))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(storage_error(path, e)),
+    }
+}
+
+/// 写入 JSON 文件，必要时创建上级目录
+pub(crate) fn write_json<T: Serialize>(path: &Path, value: &T) -> TaskResult<()> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| storage_error(path, e))?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| storage_error(parent, e))?;
+    }
+    // 先写临时文件再重命名，写入中途失败不会损坏原有数据
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+    fs::write(&temp_path, json).map_err(|e| storage_error(&temp_path, e))?;
+    fs::rename(&temp_path, path).map_err(|e| storage_error(path, e))
+}
+
+impl TaskStore for JsonFileStore {
+    fn load(&self) -> TaskResult<TaskSnapshot> {
+        Ok(read_json(&self.path)?.unwrap_or_default())
+    }
+
+    fn save(&self, snapshot: &TaskSnapshot) -> TaskResult<()> {
+        write_json(&self.path, snapshot)
+    }
+}
+
+/// 内存中的任务存储，适合测试或不需要持久化的场景
+#[derive(Default)]
+pub struct MemoryStore {
+    snapshot: Mutex<TaskSnapshot>,
+}
+
+impl MemoryStore {
+    /// 创建空的内存存储
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+}
+
+impl TaskStore for MemoryStore {
+    fn load(&self) -> TaskResult<TaskSnapshot> {
+        Ok(self.snapshot.lock().unwrap().clone())
+    }
+
+    fn save(&self, snapshot: &TaskSnapshot) -> TaskResult<()> {
+        *self.snapshot.lock().unwrap() = snapshot.clone();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task::TaskStatus;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 每个测试独占的临时目录，测试结束时删除
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+            let dir = std::env::temp_dir().join(format!(
+                "march-code-store-{}-{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn sample_snapshot() -> TaskSnapshot {
+        let mut done = Task::new("写报告".to_string(), "周报".to_string());
+        done.update_status(TaskStatus::Done);
+        let mut tasks = BTreeMap::new();
+        tasks.insert(1, Task::new("买菜".to_string(), String::new()));
+        tasks.insert(3, done);
+        TaskSnapshot { next_id: 4, tasks }
+    }
+
+    fn assert_same(loaded: &TaskSnapshot, expected: &TaskSnapshot) {
+        assert_eq!(loaded.next_id, expected.next_id);
+        assert_eq!(loaded.tasks.keys().collect::<Vec<_>>(), expected.tasks.keys().collect::<Vec<_>>());
+        for (id, task) in &expected.tasks {
+            let got = &loaded.tasks[id];
+            assert_eq!(got.title, task.title);
+            assert_eq!(got.description, task.description);
+            assert_eq!(got.status, task.status);
+            assert_eq!(got.created_at, task.created_at);
+            assert_eq!(got.history, task.history);
+        }
+    }
+
+    #[test]
+    fn json_file_store_round_trip() {
+        let dir = TempDir::new();
+        // 上级目录不存在时保存会自动创建
+        let store = JsonFileStore::new(dir.0.join("data").join("tasks.json"));
+        let snapshot = sample_snapshot();
+
+        store.save(&snapshot).unwrap();
+        assert_same(&store.load().unwrap(), &snapshot);
+        // 重命名后不留下临时文件
+        assert!(!dir.0.join("data").join("tasks.json.tmp").exists());
+
+        // 新的存储实例读取同一个文件
+        let reopened = JsonFileStore::new(store.path());
+        assert_same(&reopened.load().unwrap(), &snapshot);
+    }
+
+    #[test]
+    fn json_file_store_missing_file_is_empty() {
+        let dir = TempDir::new();
+        let store = JsonFileStore::new(dir.0.join("tasks.json"));
+        let snapshot = store.load().unwrap();
+        assert_eq!(snapshot.next_id, 1);
+        assert!(snapshot.tasks.is_empty());
+    }
+
+    #[test]
+    fn json_file_store_corrupt_file_is_parse_error() {
+        let dir = TempDir::new();
+        let path = dir.0.join("tasks.json");
+        fs::write(&path, "{ not json").unwrap();
+        let store = JsonFileStore::new(&path);
+        assert!(matches!(store.load(), Err(TaskError::ParseError(_))));
+    }
+
+    #[test]
+    fn json_file_store_unreadable_path_is_storage_error() {
+        let dir = TempDir::new();
+        // 路径是目录，既不能读也不能写
+        let store = JsonFileStore::new(&dir.0);
+        assert!(matches!(store.load(), Err(TaskError::StorageError(_))));
+        assert!(matches!(store.save(&sample_snapshot()), Err(TaskError::StorageError(_))));
+    }
+
+    #[test]
+    fn memory_store_round_trip() {
+        let store = MemoryStore::new();
+        let empty = store.load().unwrap();
+        assert_eq!(empty.next_id, 1);
+        assert!(empty.tasks.is_empty());
+
+        let snapshot = sample_snapshot();
+        store.save(&snapshot).unwrap();
+        assert_same(&store.load().unwrap(), &snapshot);
+
+        // 再次保存会整体覆盖
+        store.save(&TaskSnapshot::default()).unwrap();
+        assert!(store.load().unwrap().tasks.is_empty());
+    }
+}
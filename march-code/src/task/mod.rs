@@ -1,2 +0,0 @@
-pub mod task; 
-pub mod task_manager; 
\ No newline at end of file
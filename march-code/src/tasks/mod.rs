@@ -1 +1 @@
-pub mod task_manager; 
\ No newline at end of file
+pub mod task_manager;
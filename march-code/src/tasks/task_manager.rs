@@ -1,4 +1,6 @@
+use crate::error::{TaskError, TaskResult};
 use crate::models::task::{Task, TaskStatus};
+use crate::storage::store::{TaskSnapshot, TaskStore};
 use std::collections::HashMap;
 
 /// 任务管理器
@@ -16,6 +18,34 @@ impl TaskManager {
         }
     }
 
+    /// 从快照恢复任务管理器
+    pub fn from_snapshot(snapshot: TaskSnapshot) -> Self {
+        // 旧数据中的 next_id 可能落后于已有ID，取两者中较大的一个
+        let max_id = snapshot.tasks.keys().next_back().copied().unwrap_or(0);
+        TaskManager {
+            tasks: snapshot.tasks.into_iter().collect(),
+            next_id: snapshot.next_id.max(max_id + 1),
+        }
+    }
+
+    /// 导出当前全部任务
+    pub fn snapshot(&self) -> TaskSnapshot {
+        TaskSnapshot {
+            next_id: self.next_id,
+            tasks: self.tasks.iter().map(|(&id, task)| (id, task.clone())).collect(),
+        }
+    }
+
+    /// 从存储加载任务
+    pub fn load(store: &dyn TaskStore) -> TaskResult<Self> {
+        Ok(TaskManager::from_snapshot(store.load()?))
+    }
+
+    /// 把全部任务保存到存储
+    pub fn save(&self, store: &dyn TaskStore) -> TaskResult<()> {
+        store.save(&self.snapshot())
+    }
+
     /// 添加任务
     pub fn add_task(&mut self, task: Task) -> usize {
         let id = self.next_id;
@@ -24,6 +54,13 @@ impl TaskManager {
         id
     }
 
+    /// 按ID排序的全部任务
+    pub fn tasks(&self) -> Vec<(usize, &Task)> {
+        let mut sorted_tasks: Vec<(usize, &Task)> = self.tasks.iter().map(|(&id, task)| (id, task)).collect();
+        sorted_tasks.sort_by_key(|&(id, _)| id);
+        sorted_tasks
+    }
+
     /// 列出所有任务
     pub fn list_tasks(&self) {
         if self.tasks.is_empty() {
@@ -35,44 +72,44 @@ impl TaskManager {
         println!("{:<5} {:<20} {:<10}", "ID", "标题", "状态");
         println!("{:-<5} {:-<20} {:-<10}", "", "", "");
 
-        // 将任务按ID排序
-        let mut sorted_tasks: Vec<(&usize, &Task)> = self.tasks.iter().collect();
-        sorted_tasks.sort_by_key(|&(id, _)| id);
-
-        for (id, task) in sorted_tasks {
+        for (id, task) in self.tasks() {
             println!("{:<5} {:<20} {:<10}", id, task.title, task.status);
         }
     }
 
+    /// 获取任务
+    pub fn get_task(&self, id: usize) -> TaskResult<&Task> {
+        self.tasks.get(&id).ok_or(TaskError::NotFound(id))
+    }
+
     /// 更新任务状态
-    pub fn update_task_status(&mut self, id: usize, status: TaskStatus) -> bool {
-        match self.tasks.get_mut(&id) {
-            Some(task) => {
-                task.update_status(status);
-                true
-            }
-            None => false,
-        }
+    pub fn update_task_status(&mut self, id: usize, status: TaskStatus) -> TaskResult<()> {
+        let task = self.tasks.get_mut(&id).ok_or(TaskError::NotFound(id))?;
+        task.update_status(status);
+        Ok(())
     }
 
-    /// 删除任务
-    pub fn delete_task(&mut self, id: usize) -> bool {
-        self.tasks.remove(&id).is_some()
+    /// 删除任务，返回被删除的任务
+    pub fn delete_task(&mut self, id: usize) -> TaskResult<Task> {
+        self.tasks.remove(&id).ok_or(TaskError::NotFound(id))
     }
 
     /// 查看任务详情
-    pub fn view_task(&self, id: usize) {
-        match self.tasks.get(&id) {
-            Some(task) => {
-                println!("ID: {}", id);
-                task.display_details();
-            }
-            None => println!("找不到ID为{}的任务", id),
-        }
+    pub fn view_task(&self, id: usize) -> TaskResult<()> {
+        let task = self.get_task(id)?;
+        println!("ID: {}", id);
+        task.display_details();
+        Ok(())
     }
 
     /// 获取任务总数
     pub fn count(&self) -> usize {
         self.tasks.len()
     }
-} 
\ No newline at end of file
+}
+
+impl Default for TaskManager {
+    fn default() -> Self {
+        TaskManager::new()
+    }
+}
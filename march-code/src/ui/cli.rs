@@ -27,9 +27,18 @@ impl CliInterface {
         println!("3. 更新任务状态");
         println!("4. 删除任务");
         println!("5. 查看任务详情");
+        println!("6. 搜索并选择任务");
         println!("q. 退出程序");
     }
 
+    /// 显示选中任务后可执行的操作
+    pub fn display_pick_actions(&self) {
+        println!("v. 查看详情");
+        println!("u. 更新状态");
+        println!("d. 删除任务");
+        println!("q. 返回");
+    }
+
     /// 显示状态选项
     pub fn display_status_options(&self) {
         println!("可用的状态选项：");
@@ -1 +1,3 @@
-pub mod cli; 
\ No newline at end of file
+pub mod cli;
+pub mod picker;
+pub mod render;
@@ -0,0 +1,238 @@
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use march_code::{Task, TaskError, TaskResult};
+use std::io::{self, Write};
+
+/// 列表中最多显示的候选数量
+const MAX_VISIBLE: usize = 15;
+
+/// 模糊匹配的结果
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// 命中字符在标题中的下标（按字符计）
+    pub positions: Vec<usize>,
+}
+
+/// 按子序列做模糊匹配，查询中的字符需按顺序出现在文本中（忽略大小写）
+///
+/// 连续命中和单词开头命中加分，首个命中位置越靠后扣分越多
+pub fn fuzzy_match(query: &str, text: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch { score: 0, positions: Vec::new() });
+    }
+
+    let mut positions = Vec::with_capacity(query.len());
+    let mut score = 0i64;
+    let mut previous: Option<char> = None;
+    let mut query_index = 0;
+    for (index, c) in text.chars().enumerate() {
+        if query_index == query.len() {
+            break;
+        }
+        if c.to_lowercase().eq(std::iter::once(query[query_index])) {
+            score += 1;
+            if positions.last().is_some_and(|&last| last + 1 == index) {
+                score += 5;
+            }
+            if !previous.is_some_and(char::is_alphanumeric) {
+                score += 3;
+            }
+            positions.push(index);
+            query_index += 1;
+        }
+        previous = Some(c);
+    }
+
+    if query_index < query.len() {
+        return None;
+    }
+    score -= positions[0] as i64 / 2;
+    Some(FuzzyMatch { score, positions })
+}
+
+/// 按查询过滤任务，得分高的排在前面，同分按ID排序
+pub fn filter_tasks<'a>(query: &str, tasks: &[(usize, &'a Task)]) -> Vec<(usize, &'a Task, FuzzyMatch)> {
+    let mut matches: Vec<_> = tasks
+        .iter()
+        .filter_map(|&(id, task)| fuzzy_match(query, &task.title).map(|m| (id, task, m)))
+        .collect();
+    matches.sort_by(|a, b| b.2.score.cmp(&a.2.score).then(a.0.cmp(&b.0)));
+    matches
+}
+
+/// 进入备用屏幕和原始模式，离开时（包括出错返回）自动恢复终端
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        if let Err(e) = execute!(io::stdout(), EnterAlternateScreen, cursor::Hide) {
+            let _ = terminal::disable_raw_mode();
+            return Err(e);
+        }
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn terminal_error(e: io::Error) -> TaskError {
+    TaskError::StorageError(format!("终端操作失败: {}", e))
+}
+
+/// 交互式选择任务，边输入边过滤
+///
+/// 上下方向键移动，回车确认，Esc 或 Ctrl-C 取消（返回 `None`）
+pub fn pick_task(tasks: &[(usize, &Task)], color: bool) -> TaskResult<Option<usize>> {
+    let _guard = TerminalGuard::enter().map_err(terminal_error)?;
+    let mut query = String::new();
+    let mut selected = 0;
+
+    loop {
+        let matches = filter_tasks(&query, tasks);
+        selected = selected.min(matches.len().saturating_sub(1));
+        draw(&query, &matches, tasks.len(), selected, color).map_err(terminal_error)?;
+
+        let key = match event::read().map_err(terminal_error)? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => key,
+            _ => continue,
+        };
+        match key {
+            KeyEvent { code: KeyCode::Esc, .. } => return Ok(None),
+            KeyEvent { code: KeyCode::Char('c'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(None)
+            }
+            KeyEvent { code: KeyCode::Enter, .. } => {
+                if let Some((id, _, _)) = matches.get(selected) {
+                    return Ok(Some(*id));
+                }
+            }
+            KeyEvent { code: KeyCode::Up, .. } => selected = selected.saturating_sub(1),
+            KeyEvent { code: KeyCode::Down, .. } => selected += 1,
+            KeyEvent { code: KeyCode::Backspace, .. } => {
+                query.pop();
+                selected = 0;
+            }
+            KeyEvent { code: KeyCode::Char(c), modifiers, .. } if !modifiers.contains(KeyModifiers::CONTROL) => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn draw(
+    query: &str,
+    matches: &[(usize, &Task, FuzzyMatch)],
+    total: usize,
+    selected: usize,
+    color: bool,
+) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    queue!(
+        stdout,
+        terminal::Clear(ClearType::All),
+        cursor::MoveTo(0, 0),
+        Print(format!("搜索任务: {}", query)),
+        cursor::MoveTo(0, 1),
+        Print(format!("{}/{} 个任务  (↑↓ 选择, 回车 确认, Esc 取消)", matches.len(), total)),
+    )?;
+
+    // 选中项始终在可见范围内
+    let start = selected.saturating_sub(MAX_VISIBLE - 1);
+    for (row, (index, (id, task, m))) in matches.iter().enumerate().skip(start).take(MAX_VISIBLE).enumerate() {
+        let marker = if index == selected { "> " } else { "  " };
+        queue!(stdout, cursor::MoveTo(0, row as u16 + 2), Print(format!("{}{:>4}  ", marker, id)))?;
+        for (position, c) in task.title.chars().enumerate() {
+            if color && m.positions.contains(&position) {
+                queue!(stdout, SetAttribute(Attribute::Bold), Print(c), SetAttribute(Attribute::Reset))?;
+            } else {
+                queue!(stdout, Print(c))?;
+            }
+        }
+        queue!(stdout, Print(format!("  [{}]", task.status)))?;
+    }
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(matches: &[(usize, &Task, FuzzyMatch)]) -> Vec<usize> {
+        matches.iter().map(|(id, _, _)| *id).collect()
+    }
+
+    #[test]
+    fn fuzzy_match_subsequence() {
+        let m = fuzzy_match("wrt", "write report").unwrap();
+        assert_eq!(m.positions, vec![0, 1, 3]);
+        // 字符必须按顺序出现
+        assert!(fuzzy_match("tw", "write").is_none());
+        assert!(fuzzy_match("wx", "write").is_none());
+        // 查询中的空白被忽略
+        assert_eq!(fuzzy_match("w r", "write report").unwrap().positions, vec![0, 1]);
+    }
+
+    #[test]
+    fn fuzzy_match_ignores_case() {
+        let m = fuzzy_match("REP", "weekly report").unwrap();
+        assert_eq!(m.positions, vec![7, 8, 9]);
+        assert_eq!(fuzzy_match("rep", "Weekly REPORT").unwrap().positions, vec![7, 8, 9]);
+    }
+
+    #[test]
+    fn fuzzy_match_scores_contiguous_and_word_start_higher() {
+        let contiguous = fuzzy_match("rep", "report").unwrap();
+        let scattered = fuzzy_match("rep", "rxexp").unwrap();
+        assert!(contiguous.score > scattered.score);
+
+        let word_start = fuzzy_match("p", "buy paint").unwrap();
+        let mid_word = fuzzy_match("p", "buy apple").unwrap();
+        assert!(word_start.score > mid_word.score);
+
+        let later_start = fuzzy_match("re", "write report").unwrap();
+        let early_start = fuzzy_match("re", "report").unwrap();
+        assert!(early_start.score > later_start.score);
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        let m = fuzzy_match("", "anything").unwrap();
+        assert_eq!(m.score, 0);
+        assert!(m.positions.is_empty());
+        assert!(fuzzy_match("  ", "").is_some());
+
+        let a = Task::new("买菜".to_string(), String::new());
+        let b = Task::new("写周报".to_string(), String::new());
+        let tasks = [(2, &b), (1, &a)];
+        // 全部同分，按ID排序
+        assert_eq!(ids(&filter_tasks("", &tasks)), vec![1, 2]);
+    }
+
+    #[test]
+    fn filter_tasks_ranks_by_score_then_id() {
+        let report = Task::new("report".to_string(), String::new());
+        let scattered = Task::new("review expenses plan".to_string(), String::new());
+        let unrelated = Task::new("buy milk".to_string(), String::new());
+        let report_copy = Task::new("report".to_string(), String::new());
+        let tasks = [(4, &report_copy), (1, &scattered), (2, &unrelated), (3, &report)];
+
+        let matches = filter_tasks("rep", &tasks);
+        assert_eq!(ids(&matches), vec![3, 4, 1]);
+        assert!(matches[0].2.score > matches[2].2.score);
+        assert!(filter_tasks("zzz", &tasks).is_empty());
+    }
+}
@@ -0,0 +1,311 @@
+use chrono::{DateTime, Duration, Local, Utc};
+use march_code::{Report, Task, TaskError, TaskResult, TaskStatus};
+use serde::Serialize;
+use std::str::FromStr;
+use unicode_width::UnicodeWidthStr;
+
+/// 标题列的最大显示宽度，超出部分用省略号截断
+const MAX_TITLE_WIDTH: usize = 40;
+
+/// list/view 命令的输出格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            other => Err(TaskError::ParseError(format!("无效的输出格式 '{}'，可选值：table, json", other))),
+        }
+    }
+}
+
+/// 渲染选项
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions {
+    /// 是否输出 ANSI 颜色
+    pub color: bool,
+    /// 判断逾期的当前时间
+    pub now: DateTime<Utc>,
+}
+
+impl RenderOptions {
+    pub fn new(color: bool) -> Self {
+        RenderOptions { color, now: Utc::now() }
+    }
+}
+
+/// ANSI 颜色
+#[derive(Debug, Clone, Copy)]
+enum Style {
+    Yellow,
+    Blue,
+    Green,
+    RedBold,
+    Dim,
+}
+
+impl Style {
+    fn code(self) -> &'static str {
+        match self {
+            Style::Yellow => "33",
+            Style::Blue => "34",
+            Style::Green => "32",
+            Style::RedBold => "1;31",
+            Style::Dim => "2",
+        }
+    }
+}
+
+fn paint(text: &str, style: Option<Style>, options: &RenderOptions) -> String {
+    match style {
+        Some(style) if options.color => format!("\x1b[{}m{}\x1b[0m", style.code(), text),
+        _ => text.to_string(),
+    }
+}
+
+fn status_style(status: &TaskStatus) -> Style {
+    match status {
+        TaskStatus::Todo => Style::Yellow,
+        TaskStatus::InProgress => Style::Blue,
+        TaskStatus::Done => Style::Green,
+    }
+}
+
+/// 终端中的显示宽度，中文等全角字符占两列
+fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// 按显示宽度截断，超出时以 `…` 结尾
+fn truncate(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+    let mut result = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        let char_width = unicode_width::UnicodeWidthChar::width(c).unwrap_or(0);
+        if width + char_width + 1 > max_width {
+            break;
+        }
+        result.push(c);
+        width += char_width;
+    }
+    result.push('…');
+    result
+}
+
+/// 先按纯文本宽度补齐再着色，颜色转义序列不影响对齐
+fn cell(text: &str, width: usize, style: Option<Style>, options: &RenderOptions) -> String {
+    let padding = width.saturating_sub(display_width(text));
+    format!("{}{}", paint(text, style, options), " ".repeat(padding))
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()
+}
+
+fn due_text(task: &Task, options: &RenderOptions) -> String {
+    match task.due {
+        Some(due) if task.is_overdue(options.now) => format!("{} 已逾期", format_time(due)),
+        Some(due) => format_time(due),
+        None => "-".to_string(),
+    }
+}
+
+/// 渲染任务列表表格
+pub fn render_table(tasks: &[(usize, &Task)], options: &RenderOptions) -> String {
+    if tasks.is_empty() {
+        return "没有任务记录\n".to_string();
+    }
+
+    let headers = ["ID", "标题", "状态", "截止时间", "更新时间"];
+    let rows: Vec<[String; 5]> = tasks
+        .iter()
+        .map(|(id, task)| {
+            [
+                id.to_string(),
+                truncate(&task.title, MAX_TITLE_WIDTH),
+                task.status.to_string(),
+                due_text(task, options),
+                format_time(task.updated_at),
+            ]
+        })
+        .collect();
+
+    let mut widths = headers.map(display_width);
+    for row in &rows {
+        for (width, text) in widths.iter_mut().zip(row) {
+            *width = (*width).max(display_width(text));
+        }
+    }
+
+    let mut output = String::new();
+    let header_line: Vec<String> = headers
+        .iter()
+        .zip(widths)
+        .map(|(header, width)| cell(header, width, None, options))
+        .collect();
+    output.push_str(header_line.join("  ").trim_end());
+    output.push('\n');
+    let separator: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+    output.push_str(&separator.join("  "));
+    output.push('\n');
+
+    for ((_, task), row) in tasks.iter().zip(&rows) {
+        let overdue = task.is_overdue(options.now);
+        let styles = [
+            None,
+            None,
+            Some(status_style(&task.status)),
+            if overdue { Some(Style::RedBold) } else { None },
+            Some(Style::Dim),
+        ];
+        let line: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .zip(styles)
+            .map(|((text, width), style)| cell(text, width, style, options))
+            .collect();
+        output.push_str(line.join("  ").trim_end());
+        output.push('\n');
+    }
+    output
+}
+
+/// 渲染单个任务的详情
+pub fn render_details(id: usize, task: &Task, options: &RenderOptions) -> String {
+    let overdue = task.is_overdue(options.now);
+    let mut fields = vec![
+        ("ID", id.to_string(), None),
+        ("标题", task.title.clone(), None),
+        ("描述", task.description.clone(), None),
+        ("状态", task.status.to_string(), Some(status_style(&task.status))),
+        ("创建时间", format_time(task.created_at), None),
+        ("更新时间", format_time(task.updated_at), None),
+    ];
+    if task.due.is_some() {
+        fields.push(("截止时间", due_text(task, options), overdue.then_some(Style::RedBold)));
+    }
+
+    let label_width = fields.iter().map(|(label, _, _)| display_width(label)).max().unwrap_or(0);
+    let mut output = String::new();
+    for (label, value, style) in fields {
+        output.push_str(&cell(label, label_width, None, options));
+        output.push_str("  ");
+        output.push_str(&paint(&value, style, options));
+        output.push('\n');
+    }
+    output
+}
+
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    match (minutes / (24 * 60), minutes / 60 % 24, minutes % 60) {
+        (0, 0, m) => format!("{}分钟", m),
+        (0, h, m) => format!("{}小时{}分钟", h, m),
+        (d, h, _) => format!("{}天{}小时", d, h),
+    }
+}
+
+/// 渲染统计报告：汇总指标和每周新建/完成数量
+pub fn render_report(report: &Report, options: &RenderOptions) -> String {
+    let average = report.average_time_to_done.map_or("-".to_string(), format_duration);
+    let fields = [
+        ("任务总数", report.total.to_string(), None),
+        ("待办", report.todo.to_string(), Some(Style::Yellow)),
+        ("进行中", report.in_progress.to_string(), Some(Style::Blue)),
+        ("已完成", report.done.to_string(), Some(Style::Green)),
+        ("完成率", format!("{:.1}%", report.completion_rate() * 100.0), None),
+        ("平均完成耗时", average, None),
+        ("当前连续完成", format!("{}天", report.current_streak), None),
+        ("最长连续完成", format!("{}天", report.longest_streak), None),
+    ];
+
+    let label_width = fields.iter().map(|(label, _, _)| display_width(label)).max().unwrap_or(0);
+    let mut output = String::new();
+    for (label, value, style) in fields {
+        output.push_str(&cell(label, label_width, None, options));
+        output.push_str("  ");
+        output.push_str(&paint(&value, style, options));
+        output.push('\n');
+    }
+
+    if report.weeks.is_empty() {
+        return output;
+    }
+    let headers = ["周", "新建", "完成"];
+    let mut widths = headers.map(display_width);
+    widths[0] = widths[0].max("YYYY-MM-DD".len());
+    let max_count = report.weeks.iter().map(|w| w.created.max(w.completed)).max().unwrap_or(0);
+    widths[1] = widths[1].max(max_count.to_string().len());
+    widths[2] = widths[1];
+
+    output.push('\n');
+    let header_line: Vec<String> = headers
+        .iter()
+        .zip(widths)
+        .map(|(header, width)| cell(header, width, None, options))
+        .collect();
+    output.push_str(header_line.join("  ").trim_end());
+    output.push('\n');
+    let separator: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+    output.push_str(&separator.join("  "));
+    output.push('\n');
+    for week in &report.weeks {
+        // 完成数大于 0 的周用绿色标出
+        let completed_style = (week.completed > 0).then_some(Style::Green);
+        output.push_str(&format!(
+            "{}  {:>width$}  {}\n",
+            week.week_start,
+            week.created,
+            paint(&format!("{:>width$}", week.completed, width = widths[2]), completed_style, options),
+            width = widths[1]
+        ));
+    }
+    output
+}
+
+/// JSON 输出中的任务，附带ID和逾期标记
+#[derive(Serialize)]
+struct TaskView<'a> {
+    id: usize,
+    #[serde(flatten)]
+    task: &'a Task,
+    overdue: bool,
+}
+
+fn to_json<T: Serialize>(value: &T) -> TaskResult<String> {
+    serde_json::to_string_pretty(value)
+        .map(|json| json + "\n")
+        .map_err(|e| TaskError::StorageError(format!("序列化 JSON 失败: {}", e)))
+}
+
+/// 以 JSON 数组输出任务列表
+pub fn render_json_list(tasks: &[(usize, &Task)], options: &RenderOptions) -> TaskResult<String> {
+    let views: Vec<TaskView> = tasks
+        .iter()
+        .map(|&(id, task)| TaskView {
+            id,
+            task,
+            overdue: task.is_overdue(options.now),
+        })
+        .collect();
+    to_json(&views)
+}
+
+/// 以 JSON 对象输出单个任务
+pub fn render_json_task(id: usize, task: &Task, options: &RenderOptions) -> TaskResult<String> {
+    to_json(&TaskView {
+        id,
+        task,
+        overdue: task.is_overdue(options.now),
+    })
+}
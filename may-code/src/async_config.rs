@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use crate::config::ConfigParser;
+use crate::error::{ConfigError, ConfigResult};
+
+/// 异步配置管理器
+/// 与 ConfigManager 共用 ConfigParser trait，只是把磁盘 IO 换成 tokio::fs
+#[derive(Debug, Clone)]
+pub struct AsyncConfigManager<T, P>
+where
+    T: Serialize + for<'de> Deserialize<'de> + Debug + Clone,
+    P: ConfigParser<T>,
+{
+    parser: P,
+    config: Option<T>,
+    file_path: Option<String>,
+}
+
+impl<T, P> AsyncConfigManager<T, P>
+where
+    T: Serialize + for<'de> Deserialize<'de> + Debug + Clone,
+    P: ConfigParser<T>,
+{
+    /// 创建新的异步配置管理器实例
+    pub fn new(parser: P) -> Self {
+        Self {
+            parser,
+            config: None,
+            file_path: None,
+        }
+    }
+
+    /// 异步从文件加载配置
+    pub async fn load_from_file(&mut self, path: &str) -> ConfigResult<&T> {
+        let config = read_config(&self.parser, path).await?;
+
+        self.config = Some(config);
+        self.file_path = Some(path.to_string());
+
+        self.config
+            .as_ref()
+            .ok_or_else(|| ConfigError::ConversionError("配置加载失败".to_string()))
+    }
+
+    /// 异步保存配置到文件，未指定路径时写回加载时的文件
+    /// 先写入同目录下的临时文件再重命名，写入中途失败不会损坏原文件
+    pub async fn save_to_file(&self, path: Option<&str>) -> ConfigResult<()> {
+        let config = self.config.as_ref().ok_or_else(|| {
+            ConfigError::ValidationError {
+                message: "没有可保存的配置".to_string(),
+            }
+        })?;
+
+        let target_path = path
+            .or(self.file_path.as_deref())
+            .ok_or_else(|| ConfigError::ValidationError {
+                message: "未指定保存路径".to_string(),
+            })?;
+
+        let content = self.parser.serialize_to_string(config)?;
+        let temp_path = format!("{}.tmp", target_path);
+        tokio::fs::write(&temp_path, content).await?;
+        if let Err(e) = tokio::fs::rename(&temp_path, target_path).await {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(e.into());
+        }
+
+        println!("配置已异步保存到: {}", target_path);
+        Ok(())
+    }
+
+    /// 获取配置引用
+    pub fn get_config(&self) -> Option<&T> {
+        self.config.as_ref()
+    }
+
+    /// 更新配置，新配置需通过验证
+    pub fn update_config(&mut self, config: T) -> ConfigResult<()> {
+        self.parser.validate(&config)?;
+        self.config = Some(config);
+        Ok(())
+    }
+}
+
+impl<T, P> AsyncConfigManager<T, P>
+where
+    T: Serialize + for<'de> Deserialize<'de> + Debug + Clone + Send + 'static,
+    P: ConfigParser<T> + Clone + Send + Sync + 'static,
+{
+    /// 监听已加载的配置文件，文件变化并稳定 `debounce` 时长后重新加载
+    /// 每次重新加载的结果通过通道发送，接收端被丢弃时后台任务自动退出
+    pub async fn watch(&self, debounce: Duration) -> ConfigResult<mpsc::Receiver<ConfigResult<T>>> {
+        let path = self.file_path.clone().ok_or_else(|| ConfigError::ValidationError {
+            message: "请先加载配置文件再开启监听".to_string(),
+        })?;
+        let parser = self.parser.clone();
+        let (tx, rx) = mpsc::channel(8);
+        // 在返回前记录基准修改时间，避免遗漏调用方紧接着做的修改
+        let mut last_modified = modified_time(&path).await;
+
+        tokio::spawn(async move {
+            // 轮询间隔取防抖时长的一半，保证变化能被及时发现
+            let poll_interval = (debounce / 2).max(Duration::from_millis(10));
+            let mut pending_since: Option<Instant> = None;
+
+            loop {
+                tokio::time::sleep(poll_interval).await;
+                if tx.is_closed() {
+                    break;
+                }
+
+                let modified = modified_time(&path).await;
+                if modified != last_modified {
+                    // 文件仍在变化，重新开始计时
+                    last_modified = modified;
+                    pending_since = Some(Instant::now());
+                    continue;
+                }
+
+                if let Some(since) = pending_since {
+                    if since.elapsed() >= debounce {
+                        pending_since = None;
+                        let result = read_config(&parser, &path).await;
+                        if tx.send(result).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+}
+
+/// 读取、解析并验证配置文件
+async fn read_config<T, P>(parser: &P, path: &str) -> ConfigResult<T>
+where
+    T: Serialize + for<'de> Deserialize<'de> + Debug + Clone,
+    P: ConfigParser<T>,
+{
+    if !tokio::fs::try_exists(path).await.unwrap_or(false) {
+        return Err(ConfigError::FileNotFound {
+            path: path.to_string(),
+        });
+    }
+
+    let content = tokio::fs::read_to_string(path).await?;
+    let config = parser.parse_from_str(&content)?;
+    parser.validate(&config)?;
+    Ok(config)
+}
+
+/// 获取文件修改时间，文件不存在时返回 None
+async fn modified_time(path: &str) -> Option<SystemTime> {
+    tokio::fs::metadata(path)
+        .await
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// 演示泛型函数的使用
+pub fn create_async_config_manager<T, P>(parser: P) -> AsyncConfigManager<T, P>
+where
+    T: Serialize + for<'de> Deserialize<'de> + Debug + Clone,
+    P: ConfigParser<T>,
+{
+    AsyncConfigManager::new(parser)
+}
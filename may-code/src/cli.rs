@@ -1,7 +1,10 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::sync::Arc;
+use std::time::Duration;
+use crate::async_config::create_async_config_manager;
 use crate::config::{AppConfig, create_config_manager, ConfigParser};
 use crate::error::{ConfigError, ConfigResult, check_file_extension};
-use crate::parser::{JsonParser, YamlParser, TomlParser, ParserFactory};
+use crate::parser::{JsonParser, ParserFactory};
 
 /// 配置文件管理器 - 展示 Rust 错误处理和泛型的强大功能
 #[derive(Parser)]
@@ -9,10 +12,23 @@ use crate::parser::{JsonParser, YamlParser, TomlParser, ParserFactory};
 #[command(about = "一个演示 Rust 泛型和错误处理的配置文件管理工具")]
 #[command(version = "1.0.0")]
 pub struct Cli {
+    /// 输出格式，json 模式下错误以 {code, message, path, hint} 对象输出
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+/// 程序输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// 面向人类阅读的文本
+    Text,
+    /// 机器可读的 JSON
+    Json,
+}
+
 #[derive(Subcommand)]
 pub enum Commands {
     /// 加载并显示配置文件
@@ -22,15 +38,15 @@ pub enum Commands {
         file: String,
         
         /// 指定文件格式 (json, yaml, toml)
-        #[arg(short, long)]
+        #[arg(long)]
         format: Option<String>,
     },
     
     /// 创建默认配置文件
     Create {
-        /// 输出文件路径
-        #[arg(short, long)]
-        output: String,
+        /// 输出文件路径（`--output` 是全局的输出格式参数）
+        #[arg(short = 'o', long = "output-file")]
+        output_file: String,
         
         /// 文件格式 (json, yaml, toml)
         #[arg(short, long, default_value = "json")]
@@ -43,9 +59,9 @@ pub enum Commands {
         #[arg(short, long)]
         input: String,
         
-        /// 输出文件路径
-        #[arg(short, long)]
-        output: String,
+        /// 输出文件路径（`--output` 是全局的输出格式参数）
+        #[arg(short = 'o', long = "output-file")]
+        output_file: String,
         
         /// 目标格式 (json, yaml, toml)
         #[arg(short, long)]
@@ -59,6 +75,17 @@ pub enum Commands {
         file: String,
     },
     
+    /// 监听配置文件变化并自动重新加载
+    Watch {
+        /// 配置文件路径
+        #[arg(short, long)]
+        file: String,
+
+        /// 防抖时长（毫秒），文件稳定这么久之后才重新加载
+        #[arg(short, long, default_value_t = 500)]
+        debounce_ms: u64,
+    },
+
     /// 显示支持的格式
     Formats,
     
@@ -70,6 +97,19 @@ pub enum Commands {
     },
 }
 
+impl Commands {
+    /// 命令读取的配置文件路径，错误报告在错误本身不带路径时使用
+    /// 转换命令报告输入文件，创建命令报告输出文件
+    pub fn config_path(&self) -> Option<&str> {
+        match self {
+            Commands::Load { file, .. } | Commands::Validate { file } | Commands::Watch { file, .. } => Some(file),
+            Commands::Convert { input, .. } => Some(input),
+            Commands::Create { output_file, .. } => Some(output_file),
+            Commands::Formats | Commands::Demo { .. } => None,
+        }
+    }
+}
+
 /// CLI 处理器
 pub struct CliHandler;
 
@@ -78,11 +118,12 @@ impl CliHandler {
     pub fn run(cli: Cli) -> ConfigResult<()> {
         match cli.command {
             Commands::Load { file, format } => Self::handle_load(file, format),
-            Commands::Create { output, format } => Self::handle_create(output, format),
-            Commands::Convert { input, output, target_format } => {
-                Self::handle_convert(input, output, target_format)
+            Commands::Create { output_file, format } => Self::handle_create(output_file, format),
+            Commands::Convert { input, output_file, target_format } => {
+                Self::handle_convert(input, output_file, target_format)
             }
             Commands::Validate { file } => Self::handle_validate(file),
+            Commands::Watch { file, debounce_ms } => Self::handle_watch(file, debounce_ms),
             Commands::Formats => Self::handle_formats(),
             Commands::Demo { demo_type } => Self::handle_demo(demo_type),
         }
@@ -96,34 +137,17 @@ impl CliHandler {
         let detected_format = if let Some(fmt) = format {
             fmt
         } else {
-            check_file_extension(&file)?
+            check_file_extension::<AppConfig>(&file)?
         };
 
         println!("📄 检测到格式: {}", detected_format);
 
-        // 根据格式创建相应的解析器和配置管理器
-        match detected_format.to_lowercase().as_str() {
-            "json" => {
-                let mut manager = create_config_manager::<AppConfig, _>(JsonParser);
-                let config = manager.load_from_file(&file)?;
-                Self::display_config(config);
-            }
-            "yaml" | "yml" => {
-                let mut manager = create_config_manager::<AppConfig, _>(YamlParser);
-                let config = manager.load_from_file(&file)?;
-                Self::display_config(config);
-            }
-            "toml" => {
-                let mut manager = create_config_manager::<AppConfig, _>(TomlParser);
-                let config = manager.load_from_file(&file)?;
-                Self::display_config(config);
-            }
-            _ => {
-                return Err(ConfigError::UnsupportedFormat {
-                    format: detected_format,
-                });
-            }
-        }
+        // 通过解析器注册表创建解析器，自定义格式同样可用
+        let parser = ParserFactory::create_parser::<AppConfig>(&detected_format)?;
+        let mut manager = create_config_manager(parser);
+        println!("🔧 使用解析器: {}", manager.parser_info());
+        let config = manager.load_from_file(&file)?;
+        Self::display_config(config);
 
         Ok(())
     }
@@ -132,16 +156,19 @@ impl CliHandler {
     fn handle_create(output: String, format: String) -> ConfigResult<()> {
         println!("🆕 创建默认配置文件: {} (格式: {})", output, format);
 
-        let default_config = AppConfig::default();
-        
-        // 使用泛型解析器创建文件
+        // 使用泛型解析器和异步配置管理器创建文件
         let parser = ParserFactory::create_parser::<AppConfig>(&format)?;
-        let content = parser.serialize_to_string(&default_config)?;
-        
-        std::fs::write(&output, content)?;
-        println!("✅ 配置文件已创建: {}", output);
-
-        Ok(())
+        let runtime = tokio::runtime::Runtime::new()?;
+        runtime.block_on(async {
+            let mut manager = create_async_config_manager(parser);
+            manager.update_config(AppConfig::default())?;
+            manager.save_to_file(Some(&output)).await?;
+            println!("✅ 配置文件已创建: {}", output);
+            if let Some(config) = manager.get_config() {
+                Self::display_config(config);
+            }
+            Ok(())
+        })
     }
 
     /// 处理转换命令（演示错误处理和泛型组合使用）
@@ -149,20 +176,19 @@ impl CliHandler {
         println!("🔄 转换配置文件: {} -> {} (目标格式: {})", input, output, target_format);
 
         // 检测输入文件格式
-        let input_format = check_file_extension(&input)?;
+        let input_format = check_file_extension::<AppConfig>(&input)?;
         println!("📥 输入格式: {}", input_format);
 
         // 读取并解析输入文件
-        let content = std::fs::read_to_string(&input)?;
+        let content = read_file(&input)?;
         let input_parser = ParserFactory::create_parser::<AppConfig>(&input_format)?;
         let config = input_parser.parse_from_str(&content)?;
 
-        // 使用目标格式序列化
+        // 使用目标格式序列化并写入输出文件
         let output_parser = ParserFactory::create_parser::<AppConfig>(&target_format)?;
-        let output_content = output_parser.serialize_to_string(&config)?;
-
-        // 写入输出文件
-        std::fs::write(&output, output_content)?;
+        let mut output_manager = create_config_manager(output_parser);
+        output_manager.update_config(config)?;
+        output_manager.save_to_file(Some(&output))?;
         println!("✅ 转换完成: {} -> {}", input, output);
 
         Ok(())
@@ -172,8 +198,8 @@ impl CliHandler {
     fn handle_validate(file: String) -> ConfigResult<()> {
         println!("🔍 验证配置文件: {}", file);
 
-        let format = check_file_extension(&file)?;
-        let content = std::fs::read_to_string(&file)?;
+        let format = check_file_extension::<AppConfig>(&file)?;
+        let content = read_file(&file)?;
         let parser = ParserFactory::create_parser::<AppConfig>(&format)?;
         
         match parser.parse_from_str(&content) {
@@ -191,10 +217,38 @@ impl CliHandler {
         Ok(())
     }
 
+    /// 处理监听命令（演示异步配置管理器）
+    fn handle_watch(file: String, debounce_ms: u64) -> ConfigResult<()> {
+        println!("👀 监听配置文件: {} (防抖 {} ms，按 Ctrl+C 退出)", file, debounce_ms);
+
+        let format = check_file_extension::<AppConfig>(&file)?;
+        let parser: Arc<dyn ConfigParser<AppConfig> + Send + Sync> =
+            Arc::from(ParserFactory::create_parser::<AppConfig>(&format)?);
+
+        let runtime = tokio::runtime::Runtime::new()?;
+        runtime.block_on(async move {
+            let mut manager = create_async_config_manager(parser);
+            let config = manager.load_from_file(&file).await?;
+            Self::display_config(config);
+
+            let mut changes = manager.watch(Duration::from_millis(debounce_ms)).await?;
+            while let Some(result) = changes.recv().await {
+                match result {
+                    Ok(config) => {
+                        println!("🔄 检测到配置变化，已重新加载");
+                        Self::display_config(&config);
+                    }
+                    Err(e) => println!("❌ 重新加载失败: {}", e),
+                }
+            }
+            Ok(())
+        })
+    }
+
     /// 显示支持的格式
     fn handle_formats() -> ConfigResult<()> {
         println!("📋 支持的配置文件格式:");
-        for format in ParserFactory::supported_formats() {
+        for format in ParserFactory::supported_formats::<AppConfig>() {
             println!("  • {}", format);
         }
         Ok(())
@@ -226,8 +280,6 @@ impl CliHandler {
 
         // 演示不同格式的序列化
         let json_parser = JsonParser;
-        let yaml_parser = YamlParser;
-        let toml_parser = TomlParser;
 
         println!("\n🔄 不同格式序列化演示:");
         if let Ok(json) = json_parser.serialize_to_string(&config) {
@@ -245,8 +297,10 @@ impl CliHandler {
         // 演示泛型函数
         println!("🔧 泛型配置管理器演示:");
         let mut manager = create_config_manager(JsonParser);
-        let mut config = AppConfig::default();
-        config.name = "高级配置示例".to_string();
+        let mut config = AppConfig {
+            name: "高级配置示例".to_string(),
+            ..Default::default()
+        };
         config.settings.insert("advanced".to_string(), "true".to_string());
 
         manager.update_config(config)?;
@@ -255,6 +309,16 @@ impl CliHandler {
             Self::display_config(cfg);
         }
 
+        // 演示运行时注册自定义格式
+        println!("\n🧩 自定义格式注册演示:");
+        ParserFactory::register::<AppConfig>("cfg", || Box::new(JsonParser));
+        let cfg_parser = ParserFactory::create_parser::<AppConfig>("cfg")?;
+        println!("注册 cfg 格式 (基于 {} 解析器)", cfg_parser.supported_format());
+        println!(
+            "AppConfig 支持的格式: {}",
+            ParserFactory::supported_formats::<AppConfig>().join(", ")
+        );
+
         // 演示 Option 和 Result 组合使用
         println!("\n🔍 配置查找演示:");
         if let Some(cfg) = manager.get_config() {
@@ -281,7 +345,7 @@ impl CliHandler {
         }
 
         println!("\n2. 不支持的格式错误:");
-        match check_file_extension("test.unknown") {
+        match check_file_extension::<AppConfig>("test.unknown") {
             Err(e) => println!("   捕获错误: {}", e),
             Ok(_) => println!("   意外成功"),
         }
@@ -303,6 +367,7 @@ impl CliHandler {
         println!("  名称: {}", config.name);
         println!("  版本: {}", config.version);
         println!("  调试模式: {}", if config.debug { "开启" } else { "关闭" });
+        println!("  日志级别: {}", config.log_level);
         
         println!("  功能特性:");
         for feature in &config.features {
@@ -315,4 +380,14 @@ impl CliHandler {
         }
         println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     }
-} 
\ No newline at end of file
+} 
+
+/// 读取文件内容，文件不存在时返回带路径的 FileNotFound 错误
+fn read_file(path: &str) -> ConfigResult<String> {
+    std::fs::read_to_string(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => ConfigError::FileNotFound {
+            path: path.to_string(),
+        },
+        _ => ConfigError::IoError(e),
+    })
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::sync::Arc;
+use common::LogLevel;
 use crate::error::{ConfigError, ConfigResult};
 
 /// 泛型 trait 定义 - 配置解析器的统一接口
@@ -25,6 +27,37 @@ where
     }
 }
 
+/// 让智能指针包装的解析器（包括 trait object）也能作为解析器使用
+/// 这样 ConfigManager 可以直接接收 ParserFactory 创建的解析器
+macro_rules! impl_parser_for_pointer {
+    ($pointer:ident) => {
+        impl<T, P> ConfigParser<T> for $pointer<P>
+        where
+            T: Serialize + for<'de> Deserialize<'de> + Debug + Clone,
+            P: ConfigParser<T> + ?Sized,
+        {
+            fn parse_from_str(&self, content: &str) -> ConfigResult<T> {
+                (**self).parse_from_str(content)
+            }
+
+            fn serialize_to_string(&self, config: &T) -> ConfigResult<String> {
+                (**self).serialize_to_string(config)
+            }
+
+            fn supported_format(&self) -> &'static str {
+                (**self).supported_format()
+            }
+
+            fn validate(&self, config: &T) -> ConfigResult<()> {
+                (**self).validate(config)
+            }
+        }
+    };
+}
+
+impl_parser_for_pointer!(Box);
+impl_parser_for_pointer!(Arc);
+
 /// 泛型配置管理器结构体
 /// 演示了泛型结构体的定义和使用
 #[derive(Debug, Clone)]
@@ -127,6 +160,9 @@ pub struct AppConfig {
     pub settings: HashMap<String, String>,
     pub features: Vec<String>,
     pub debug: bool,
+    /// 日志级别，旧配置文件中没有该字段时使用默认的 Info
+    #[serde(default)]
+    pub log_level: LogLevel,
 }
 
 impl Default for AppConfig {
@@ -141,6 +177,7 @@ impl Default for AppConfig {
             settings,
             features: vec!["logging".to_string(), "caching".to_string()],
             debug: false,
+            log_level: LogLevel::default(),
         }
     }
 }
@@ -1,4 +1,6 @@
+use serde::Serialize;
 use thiserror::Error;
+use crate::parser::ParserFactory;
 
 /// 配置管理器的自定义错误类型
 /// 演示了 thiserror 库的使用和错误传播
@@ -32,6 +34,73 @@ pub enum ConfigError {
     ConversionError(String),
 }
 
+impl ConfigError {
+    /// 稳定的错误码，供脚本和 CI 流水线匹配，新增变体时不要修改已有的值
+    pub fn code(&self) -> &'static str {
+        match self {
+            ConfigError::FileNotFound { .. } => "FILE_NOT_FOUND",
+            ConfigError::UnsupportedFormat { .. } => "UNSUPPORTED_FORMAT",
+            ConfigError::JsonError(_) => "JSON_PARSE_ERROR",
+            ConfigError::YamlError(_) => "YAML_PARSE_ERROR",
+            ConfigError::TomlDeError(_) => "TOML_PARSE_ERROR",
+            ConfigError::TomlSerError(_) => "TOML_SERIALIZE_ERROR",
+            ConfigError::IoError(_) => "IO_ERROR",
+            ConfigError::ValidationError { .. } => "VALIDATION_ERROR",
+            ConfigError::ConversionError(_) => "CONVERSION_ERROR",
+        }
+    }
+
+    /// 与错误相关的文件路径（如果有）
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            ConfigError::FileNotFound { path } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// 给用户的修复建议
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            ConfigError::FileNotFound { .. } => Some("检查文件路径是否正确，或使用 create 命令生成默认配置"),
+            ConfigError::UnsupportedFormat { .. } => Some("使用 formats 命令查看支持的格式"),
+            ConfigError::JsonError(_) | ConfigError::YamlError(_) | ConfigError::TomlDeError(_) => {
+                Some("检查配置文件语法以及字段是否与 AppConfig 匹配")
+            }
+            ConfigError::ValidationError { .. } => Some("根据错误信息修正配置内容后重试"),
+            _ => None,
+        }
+    }
+
+    /// 生成机器可读的错误报告
+    pub fn to_report(&self) -> ErrorReport {
+        ErrorReport {
+            code: self.code(),
+            message: self.to_string(),
+            path: self.path().map(str::to_string),
+            hint: self.hint(),
+        }
+    }
+}
+
+/// 机器可读的错误报告，用于 `--output json`
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    pub code: &'static str,
+    pub message: String,
+    pub path: Option<String>,
+    pub hint: Option<&'static str>,
+}
+
+impl ErrorReport {
+    /// 错误本身不带路径时，使用调用方已知的文件路径
+    pub fn with_fallback_path(mut self, path: Option<&str>) -> Self {
+        if self.path.is_none() {
+            self.path = path.map(str::to_string);
+        }
+        self
+    }
+}
+
 /// Result 类型别名，简化错误处理
 pub type ConfigResult<T> = Result<T, ConfigError>;
 
@@ -44,7 +113,8 @@ pub fn validate_config_path(path: Option<&str>) -> ConfigResult<String> {
 }
 
 /// 演示错误链式传播的示例函数
-pub fn check_file_extension(path: &str) -> ConfigResult<String> {
+/// 扩展名必须是配置类型 T 可用的格式
+pub fn check_file_extension<T: 'static>(path: &str) -> ConfigResult<String> {
     let extension = std::path::Path::new(path)
         .extension()
         .and_then(|ext| ext.to_str())
@@ -52,10 +122,12 @@ pub fn check_file_extension(path: &str) -> ConfigResult<String> {
             message: format!("无法获取文件扩展名: {}", path),
         })?;
 
-    match extension.to_lowercase().as_str() {
-        "json" | "yaml" | "yml" | "toml" => Ok(extension.to_string()),
-        _ => Err(ConfigError::UnsupportedFormat {
+    // 内置格式和为 T 注册的自定义格式都视为支持
+    if ParserFactory::is_registered::<T>(extension) {
+        Ok(extension.to_string())
+    } else {
+        Err(ConfigError::UnsupportedFormat {
             format: extension.to_string(),
-        }),
+        })
     }
 } 
\ No newline at end of file
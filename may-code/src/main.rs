@@ -1,10 +1,11 @@
 mod error;
 mod config;
+mod async_config;
 mod parser;
 mod cli;
 
 use clap::Parser;
-use cli::{Cli, CliHandler};
+use cli::{Cli, CliHandler, OutputFormat};
 use error::ConfigResult;
 
 /// 主函数 - 程序入口点
@@ -12,17 +13,29 @@ use error::ConfigResult;
 fn main() {
     // 解析命令行参数
     let cli = Cli::parse();
+    let output = cli.output;
+    let config_path = cli.command.config_path().map(str::to_string);
 
-    // 显示欢迎信息
-    print_welcome();
+    // 显示欢迎信息（JSON 模式下保持输出干净）
+    if output == OutputFormat::Text {
+        print_welcome();
+    }
 
     // 执行命令并处理错误
     if let Err(e) = CliHandler::run(cli) {
-        eprintln!("❌ 程序执行出错: {}", e);
+        match output {
+            OutputFormat::Text => eprintln!("❌ 程序执行出错: {}", e),
+            OutputFormat::Json => match serde_json::to_string(&e.to_report().with_fallback_path(config_path.as_deref())) {
+                Ok(json) => eprintln!("{}", json),
+                Err(_) => eprintln!("{{\"code\":\"{}\"}}", e.code()),
+            },
+        }
         std::process::exit(1);
     }
 
-    println!("\n🎉 程序执行完成！");
+    if output == OutputFormat::Text {
+        println!("\n🎉 程序执行完成！");
+    }
 }
 
 /// 显示欢迎信息和学习要点
@@ -38,6 +51,7 @@ fn print_welcome() {
     println!("  🔹 trait objects (Box<dyn Trait>)");
     println!("  🔹 序列化和反序列化 (serde)");
     println!("  🔹 命令行参数解析 (clap)");
+    println!("  🔹 异步文件 IO 与防抖监听 (tokio)");
     println!("═══════════════════════════════════════════════════════════");
     println!();
 }
@@ -46,8 +60,8 @@ fn print_welcome() {
 /// 这里展示了如何在代码中使用我们创建的各种组件
 #[allow(dead_code)]
 fn demonstrate_usage() -> ConfigResult<()> {
-    use config::{AppConfig, create_config_manager};
-    use parser::{JsonParser, ParserFactory};
+    use config::{AppConfig, ConfigParser, create_config_manager};
+    use parser::{batch_process_configs, demonstrate_polymorphism, GenericParser, JsonParser, ParserFactory, TomlParser};
     use error::{ConfigError, validate_config_path};
 
     // 1. 错误处理演示
@@ -85,6 +99,28 @@ fn demonstrate_usage() -> ConfigResult<()> {
     let yaml_parser = ParserFactory::create_parser::<AppConfig>("yaml")?;
     println!("  创建 YAML 解析器: {}", yaml_parser.supported_format());
 
+    // 4. 泛型解析器包装和多态演示
+    println!("\n🎭 多态解析演示:");
+    let generic = GenericParser::<AppConfig>::new("yaml")?;
+    println!("  泛型解析器包装的格式: {}", generic.get_format());
+    let json = JsonParser.serialize_to_string(&AppConfig::default())?;
+    // YAML 是 JSON 的超集，两种解析器都能解析同一段 JSON
+    let parsers: Vec<Box<dyn ConfigParser<AppConfig>>> = vec![Box::new(JsonParser), Box::new(generic)];
+    for result in demonstrate_polymorphism(parsers, &json) {
+        match result {
+            Ok(cfg) => println!("  解析成功: {}", cfg.name),
+            Err(e) => println!("  解析失败: {}", e),
+        }
+    }
+
+    // 5. 按扩展名批量处理演示
+    println!("\n📚 批量处理演示:");
+    let toml = TomlParser.serialize_to_string(&AppConfig::default())?;
+    println!("  TOML 格式:\n{}", toml);
+    let files = vec![("app.json".to_string(), json), ("app.toml".to_string(), toml)];
+    let configs: Vec<AppConfig> = batch_process_configs(files)?;
+    println!("  共处理 {} 个配置文件", configs.len());
+
     Ok(())
 }
 
@@ -94,7 +130,7 @@ fn demonstrate_usage() -> ConfigResult<()> {
 mod tests {
     use super::*;
     use config::{AppConfig, ConfigParser};
-    use parser::{JsonParser, YamlParser, TomlParser};
+    use parser::JsonParser;
     use error::ConfigError;
 
     #[test]
@@ -118,12 +154,12 @@ mod tests {
         use error::check_file_extension;
         
         // 测试成功情况
-        let result = check_file_extension("test.json");
+        let result = check_file_extension::<AppConfig>("test.json");
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "json");
         
         // 测试错误情况
-        let result = check_file_extension("test.unknown");
+        let result = check_file_extension::<AppConfig>("test.unknown");
         assert!(result.is_err());
         
         if let Err(ConfigError::UnsupportedFormat { format }) = result {
@@ -133,6 +169,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_definition() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_output_flag_does_not_clash_with_output_file() {
+        let cli = Cli::try_parse_from(["config-manager", "--output", "json", "create", "-o", "app.yaml", "-f", "yaml"]).unwrap();
+        assert_eq!(cli.output, cli::OutputFormat::Json);
+        assert!(matches!(cli.command, cli::Commands::Create { output_file, .. } if output_file == "app.yaml"));
+    }
+
+    #[test]
+    fn test_error_report() {
+        let error = ConfigError::FileNotFound {
+            path: "missing.json".to_string(),
+        };
+        assert_eq!(error.code(), "FILE_NOT_FOUND");
+
+        let report = serde_json::to_value(error.to_report()).unwrap();
+        assert_eq!(report["code"], "FILE_NOT_FOUND");
+        assert_eq!(report["path"], "missing.json");
+        assert!(report["message"].as_str().unwrap().contains("missing.json"));
+        assert!(report["hint"].is_string());
+
+        // 没有路径的错误输出 null
+        let error = ConfigError::ConversionError("bad".to_string());
+        let report = serde_json::to_value(error.to_report()).unwrap();
+        assert_eq!(report["code"], "CONVERSION_ERROR");
+        assert!(report["path"].is_null());
+
+        // 解析错误使用命令读取的文件路径，错误自带的路径优先
+        let cli = Cli::try_parse_from(["config-manager", "--output", "json", "validate", "-f", "broken.json"]).unwrap();
+        let error = ConfigError::from(serde_json::from_str::<AppConfig>("{").unwrap_err());
+        let report = serde_json::to_value(error.to_report().with_fallback_path(cli.command.config_path())).unwrap();
+        assert_eq!(report["code"], "JSON_PARSE_ERROR");
+        assert_eq!(report["path"], "broken.json");
+        let error = ConfigError::FileNotFound {
+            path: "missing.json".to_string(),
+        };
+        let report = serde_json::to_value(error.to_report().with_fallback_path(Some("other.json"))).unwrap();
+        assert_eq!(report["path"], "missing.json");
+    }
+
     #[test]
     fn test_generic_parser_factory() {
         use parser::ParserFactory;
@@ -152,6 +233,117 @@ mod tests {
         assert!(unknown_parser.is_err());
     }
 
+    #[test]
+    fn test_register_closure_constructor() {
+        use parser::{ParserFactory, YamlParser};
+
+        // 闭包直接返回 Box，配置类型通过 turbofish 指定
+        ParserFactory::register::<AppConfig>("ron", || Box::new(JsonParser));
+        assert!(ParserFactory::create_parser::<AppConfig>("ron").is_ok());
+
+        // 构造函数内部再注册其他格式不会死锁
+        ParserFactory::register::<AppConfig>("nested-test", || {
+            ParserFactory::register::<AppConfig>("inner-test", || Box::new(YamlParser));
+            Box::new(JsonParser)
+        });
+        assert!(ParserFactory::create_parser::<AppConfig>("nested-test").is_ok());
+        assert!(ParserFactory::is_registered::<AppConfig>("inner-test"));
+    }
+
+    #[test]
+    fn test_register_custom_parser() {
+        use parser::ParserFactory;
+
+        // 未注册前不支持该格式
+        assert!(ParserFactory::create_parser::<AppConfig>("jsonc").is_err());
+
+        // 配置类型由构造函数的返回类型推断
+        fn jsonc_parser() -> parser::DynParser<AppConfig> {
+            Box::new(JsonParser)
+        }
+        ParserFactory::register("JSONC", jsonc_parser);
+
+        // 格式名不区分大小写，且出现在支持列表中
+        let parser = ParserFactory::create_parser::<AppConfig>("jsonc");
+        assert!(parser.is_ok());
+        assert!(ParserFactory::is_registered::<AppConfig>("jsonc"));
+        assert!(ParserFactory::supported_formats::<AppConfig>().contains(&"jsonc".to_string()));
+        assert_eq!(error::check_file_extension::<AppConfig>("app.jsonc").unwrap(), "jsonc");
+
+        // 自定义解析器只对注册时的配置类型生效，扩展名检查也一样
+        type OtherConfig = std::collections::HashMap<String, String>;
+        assert!(ParserFactory::create_parser::<OtherConfig>("jsonc").is_err());
+        assert!(error::check_file_extension::<OtherConfig>("app.jsonc").is_err());
+        assert!(!ParserFactory::is_registered::<OtherConfig>("jsonc"));
+        assert!(!ParserFactory::supported_formats::<OtherConfig>().contains(&"jsonc".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_async_manager_watch_reloads_after_debounce() {
+        use async_config::create_async_config_manager;
+        use std::time::Duration;
+
+        let path = std::env::temp_dir().join(format!("async-config-{}.json", std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+        let parser = JsonParser;
+        let mut config = AppConfig::default();
+        tokio::fs::write(&path, parser.serialize_to_string(&config).unwrap())
+            .await
+            .unwrap();
+
+        let mut manager = create_async_config_manager::<AppConfig, _>(parser.clone());
+        let loaded = manager.load_from_file(&path).await.unwrap();
+        assert_eq!(loaded.name, config.name);
+
+        let mut changes = manager.watch(Duration::from_millis(50)).await.unwrap();
+        config.name = "已修改".to_string();
+        tokio::fs::write(&path, parser.serialize_to_string(&config).unwrap())
+            .await
+            .unwrap();
+
+        let reloaded = tokio::time::timeout(Duration::from_secs(5), changes.recv())
+            .await
+            .expect("等待重新加载超时")
+            .expect("监听通道已关闭")
+            .unwrap();
+        assert_eq!(reloaded.name, "已修改");
+
+        let _ = tokio::fs::remove_file(&path).await;
+    }
+
+    #[tokio::test]
+    async fn test_async_manager_save_round_trip() {
+        use async_config::create_async_config_manager;
+
+        let path = std::env::temp_dir().join(format!("async-config-save-{}.yaml", std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+
+        // 没有配置或没有路径时不能保存
+        let mut manager = create_async_config_manager::<AppConfig, _>(parser::YamlParser);
+        assert!(manager.save_to_file(Some(&path)).await.is_err());
+        let mut config = AppConfig {
+            name: "异步保存".to_string(),
+            ..AppConfig::default()
+        };
+        manager.update_config(config.clone()).unwrap();
+        assert!(manager.save_to_file(None).await.is_err());
+
+        manager.save_to_file(Some(&path)).await.unwrap();
+        assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
+
+        let mut reloaded = create_async_config_manager::<AppConfig, _>(parser::YamlParser);
+        assert_eq!(reloaded.load_from_file(&path).await.unwrap().name, "异步保存");
+
+        // 未指定路径时写回加载时的文件
+        config.name = "再次保存".to_string();
+        reloaded.update_config(config).unwrap();
+        reloaded.save_to_file(None).await.unwrap();
+        assert_eq!(manager.load_from_file(&path).await.unwrap().name, "再次保存");
+        assert_eq!(manager.get_config().unwrap().name, "再次保存");
+
+        let _ = tokio::fs::remove_file(&path).await;
+    }
+
     #[test]
     fn test_option_and_result_combinations() {
         use config::find_config_value;
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
 use std::fmt::Debug;
+use std::sync::{Arc, OnceLock, RwLock};
 use crate::config::ConfigParser;
 use crate::error::{ConfigError, ConfigResult};
 
@@ -97,30 +100,146 @@ where
     }
 }
 
+/// 工厂创建的解析器 trait object，可以跨线程共享
+pub type DynParser<T> = Box<dyn ConfigParser<T> + Send + Sync>;
+
+/// 解析器构造函数类型
+/// 注册表中按配置类型 T 区分存储，取出时再向下转型
+pub type ParserConstructor<T> = Box<dyn Fn() -> DynParser<T> + Send + Sync>;
+
+/// 内置格式，对任意配置类型 T 都可用
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BuiltinFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl BuiltinFormat {
+    fn create<T>(self) -> DynParser<T>
+    where
+        T: Serialize + for<'de> Deserialize<'de> + Debug + Clone + 'static,
+    {
+        match self {
+            BuiltinFormat::Json => Box::new(JsonParser),
+            BuiltinFormat::Yaml => Box::new(YamlParser),
+            BuiltinFormat::Toml => Box::new(TomlParser),
+        }
+    }
+}
+
+/// 注册表中的一个格式条目
+/// 自定义构造函数以 TypeId 区分，优先于内置实现
+#[derive(Default)]
+struct Registration {
+    builtin: Option<BuiltinFormat>,
+    custom: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl Registration {
+    /// 该格式对配置类型 T 是否可用
+    fn available_for<T: 'static>(&self) -> bool {
+        self.builtin.is_some() || self.custom.contains_key(&TypeId::of::<T>())
+    }
+}
+
+type Registry = RwLock<HashMap<String, Registration>>;
+
+/// 全局解析器注册表，首次访问时预注册内置格式
+fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let builtins = [
+            ("json", BuiltinFormat::Json),
+            ("yaml", BuiltinFormat::Yaml),
+            ("yml", BuiltinFormat::Yaml),
+            ("toml", BuiltinFormat::Toml),
+        ];
+        let map = builtins
+            .into_iter()
+            .map(|(name, builtin)| {
+                let registration = Registration {
+                    builtin: Some(builtin),
+                    ..Default::default()
+                };
+                (name.to_string(), registration)
+            })
+            .collect();
+        RwLock::new(map)
+    })
+}
+
 /// 动态解析器工厂
 /// 演示了泛型和动态分发的结合使用
 pub struct ParserFactory;
 
 impl ParserFactory {
+    /// 注册自定义格式的解析器
+    /// 同名格式会被覆盖，格式名不区分大小写
+    /// 构造函数返回 `DynParser<T>` 时 T 可以推断；闭包需要显式指定 T，
+    /// 返回的 Box 会自动转换为 `DynParser<T>`：
+    /// `ParserFactory::register::<AppConfig>("ron", || Box::new(RonParser))`
+    pub fn register<T>(format: &str, constructor: impl Fn() -> DynParser<T> + Send + Sync + 'static)
+    where
+        T: Serialize + for<'de> Deserialize<'de> + Debug + Clone + 'static,
+    {
+        let constructor: ParserConstructor<T> = Box::new(constructor);
+        let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+        registry
+            .entry(format.to_lowercase())
+            .or_default()
+            .custom
+            .insert(TypeId::of::<T>(), Arc::new(constructor));
+    }
+
+    /// 检查格式对配置类型 T 是否可用（内置格式，或为 T 注册的自定义格式）
+    /// 与 `create_parser::<T>` 的判断保持一致
+    pub fn is_registered<T: 'static>(format: &str) -> bool {
+        let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+        registry
+            .get(&format.to_lowercase())
+            .is_some_and(Registration::available_for::<T>)
+    }
+
     /// 根据文件扩展名创建相应的解析器
     /// 返回 Box<dyn ConfigParser<T>> 展示 trait object 的使用
-    pub fn create_parser<T>(format: &str) -> ConfigResult<Box<dyn ConfigParser<T>>>
+    pub fn create_parser<T>(format: &str) -> ConfigResult<DynParser<T>>
     where
         T: Serialize + for<'de> Deserialize<'de> + Debug + Clone + 'static,
     {
-        match format.to_lowercase().as_str() {
-            "json" => Ok(Box::new(JsonParser)),
-            "yaml" | "yml" => Ok(Box::new(YamlParser)),
-            "toml" => Ok(Box::new(TomlParser)),
-            _ => Err(ConfigError::UnsupportedFormat {
-                format: format.to_string(),
-            }),
+        // 只在查找期间持有读锁，构造函数里再调用 register 也不会死锁
+        let (custom, builtin) = {
+            let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+            let registration = registry.get(&format.to_lowercase());
+            (
+                registration.and_then(|r| r.custom.get(&TypeId::of::<T>()).cloned()),
+                registration.and_then(|r| r.builtin),
+            )
+        };
+
+        // 优先使用为该配置类型注册的自定义解析器
+        let custom = custom.as_ref().and_then(|ctor| ctor.downcast_ref::<ParserConstructor<T>>());
+        if let Some(constructor) = custom {
+            return Ok(constructor());
         }
+
+        builtin
+            .map(BuiltinFormat::create::<T>)
+            .ok_or_else(|| ConfigError::UnsupportedFormat {
+                format: format.to_string(),
+            })
     }
 
-    /// 获取支持的格式列表
-    pub fn supported_formats() -> Vec<&'static str> {
-        vec!["json", "yaml", "yml", "toml"]
+    /// 获取配置类型 T 支持的格式列表（内置格式和为 T 注册的自定义格式）
+    pub fn supported_formats<T: 'static>() -> Vec<String> {
+        let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+        let mut formats: Vec<String> = registry
+            .iter()
+            .filter(|(_, r)| r.available_for::<T>())
+            .map(|(name, _)| name.clone())
+            .collect();
+        formats.sort();
+        formats
     }
 }
 
@@ -227,4 +346,4 @@ where
     }
 
     Ok(results)
-} 
\ No newline at end of file
+}
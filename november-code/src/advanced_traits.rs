@@ -1,5 +1,7 @@
 //! 高级特性（Traits）和关联类型示例
 
+use std::marker::PhantomData;
+
 /// 关联类型示例：自定义迭代器特性
 pub trait MyIterator {
     type Item;  // 关联类型
@@ -80,6 +82,11 @@ pub trait Collection {
 
     fn get(&self, index: usize) -> Option<Self::Item<'_>>;
     fn len(&self) -> usize;
+
+    // 默认实现
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 // 注意：GAT 需要 Rust 1.65+ 版本
@@ -101,6 +108,12 @@ impl StringCollection {
     }
 }
 
+impl Default for StringCollection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Collection for StringCollection {
     type Item<'a> = &'a str;
 
@@ -141,6 +154,193 @@ pub fn draw_all(items: &[Box<dyn Drawable>]) {
     }
 }
 
+/// 访问者模式：表达式树
+///
+/// 双重分派：`Expr::accept` 按节点类型选择 `Visitor` 的方法，具体执行什么由访问者的类型决定
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Add(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Neg(Box<Expr>),
+}
+
+impl Expr {
+    pub fn number(value: f64) -> Self {
+        Expr::Number(value)
+    }
+
+    pub fn sum(left: Expr, right: Expr) -> Self {
+        Expr::Add(Box::new(left), Box::new(right))
+    }
+
+    pub fn product(left: Expr, right: Expr) -> Self {
+        Expr::Mul(Box::new(left), Box::new(right))
+    }
+
+    pub fn negate(operand: Expr) -> Self {
+        Expr::Neg(Box::new(operand))
+    }
+
+    /// 把自己交给访问者，`V` 可以是具体类型，也可以是 `dyn Visitor`
+    pub fn accept<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        match self {
+            Expr::Number(value) => visitor.visit_number(*value),
+            Expr::Add(left, right) => visitor.visit_add(left, right),
+            Expr::Mul(left, right) => visitor.visit_mul(left, right),
+            Expr::Neg(operand) => visitor.visit_neg(operand),
+        }
+    }
+
+    /// 运算符优先级，数字最高
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Add(..) => 1,
+            Expr::Mul(..) => 2,
+            Expr::Neg(..) => 3,
+            Expr::Number(_) => 4,
+        }
+    }
+}
+
+/// 表达式访问者
+///
+/// 默认实现只遍历子节点，访问者只需重写关心的节点
+pub trait Visitor {
+    fn visit_number(&mut self, _value: f64) {}
+
+    fn visit_add(&mut self, left: &Expr, right: &Expr) {
+        left.accept(self);
+        right.accept(self);
+    }
+
+    fn visit_mul(&mut self, left: &Expr, right: &Expr) {
+        left.accept(self);
+        right.accept(self);
+    }
+
+    fn visit_neg(&mut self, operand: &Expr) {
+        operand.accept(self);
+    }
+}
+
+/// 求值访问者：用栈保存子表达式的结果
+#[derive(Default)]
+pub struct Evaluator {
+    stack: Vec<f64>,
+}
+
+impl Evaluator {
+    pub fn evaluate(expr: &Expr) -> f64 {
+        let mut evaluator = Evaluator::default();
+        expr.accept(&mut evaluator);
+        evaluator.stack.pop().expect("表达式求值后栈中应有结果")
+    }
+
+    fn binary(&mut self, left: &Expr, right: &Expr, op: fn(f64, f64) -> f64) {
+        left.accept(self);
+        right.accept(self);
+        let rhs = self.stack.pop().unwrap();
+        let lhs = self.stack.pop().unwrap();
+        self.stack.push(op(lhs, rhs));
+    }
+}
+
+impl Visitor for Evaluator {
+    fn visit_number(&mut self, value: f64) {
+        self.stack.push(value);
+    }
+
+    fn visit_add(&mut self, left: &Expr, right: &Expr) {
+        self.binary(left, right, |a, b| a + b);
+    }
+
+    fn visit_mul(&mut self, left: &Expr, right: &Expr) {
+        self.binary(left, right, |a, b| a * b);
+    }
+
+    fn visit_neg(&mut self, operand: &Expr) {
+        operand.accept(self);
+        let value = self.stack.pop().unwrap();
+        self.stack.push(-value);
+    }
+}
+
+/// 打印访问者：按优先级只输出必要的括号
+#[derive(Default)]
+pub struct PrettyPrinter {
+    output: String,
+}
+
+impl PrettyPrinter {
+    pub fn print(expr: &Expr) -> String {
+        let mut printer = PrettyPrinter::default();
+        expr.accept(&mut printer);
+        printer.output
+    }
+
+    /// 子表达式优先级低于 `min_precedence` 时加括号
+    fn child(&mut self, expr: &Expr, min_precedence: u8) {
+        let needs_parens = expr.precedence() < min_precedence;
+        if needs_parens {
+            self.output.push('(');
+        }
+        expr.accept(self);
+        if needs_parens {
+            self.output.push(')');
+        }
+    }
+
+    fn binary(&mut self, left: &Expr, right: &Expr, op: &str, precedence: u8) {
+        self.child(left, precedence);
+        self.output.push_str(op);
+        self.child(right, precedence);
+    }
+}
+
+impl Visitor for PrettyPrinter {
+    fn visit_number(&mut self, value: f64) {
+        self.output.push_str(&value.to_string());
+    }
+
+    fn visit_add(&mut self, left: &Expr, right: &Expr) {
+        self.binary(left, right, " + ", 1);
+    }
+
+    fn visit_mul(&mut self, left: &Expr, right: &Expr) {
+        self.binary(left, right, " * ", 2);
+    }
+
+    fn visit_neg(&mut self, operand: &Expr) {
+        self.output.push('-');
+        // 负数和嵌套的取负也加括号，避免打印出 `--1`
+        match operand {
+            Expr::Neg(_) => self.child(operand, u8::MAX),
+            Expr::Number(value) if *value < 0.0 => self.child(operand, u8::MAX),
+            _ => self.child(operand, 3),
+        }
+    }
+}
+
+/// 只重写 `visit_number` 的访问者，其余节点沿用默认的遍历
+#[derive(Default)]
+pub struct NumberCounter {
+    pub count: usize,
+}
+
+impl Visitor for NumberCounter {
+    fn visit_number(&mut self, _value: f64) {
+        self.count += 1;
+    }
+}
+
+/// 特性对象示例：同一棵表达式树依次交给多个访问者
+pub fn visit_all(expr: &Expr, visitors: &mut [&mut dyn Visitor]) {
+    for visitor in visitors.iter_mut() {
+        expr.accept(*visitor);
+    }
+}
+
 /// 高级特性：特性继承
 pub trait Readable: Drawable {
     fn read(&self) -> String;
@@ -180,15 +380,88 @@ impl Constants for Validator {
 }
 
 /// 高级特性：特性方法中的泛型
+///
+/// 通过全局实现（blanket impl）让所有类型都能 `value.convert::<T>()`，
+/// 目标类型只要实现了 `From<Self>` 即可，转换本身完全由 `From` 决定，没有额外开销
 pub trait Converter {
     fn convert<T: From<Self>>(self) -> T
     where
-        Self: Sized;
+        Self: Sized,
+    {
+        T::from(self)
+    }
 }
 
-impl Converter for i32 {
-    fn convert<T: From<Self>>(self) -> T {
-        T::from(self)
+impl<S> Converter for S {}
+
+/// 可能失败的转换，基于 `TryFrom`
+pub trait TryConverter {
+    fn try_convert<T: TryFrom<Self>>(self) -> Result<T, T::Error>
+    where
+        Self: Sized,
+    {
+        T::try_from(self)
+    }
+}
+
+impl<S> TryConverter for S {}
+
+/// 逐个转换元素的迭代器适配器，见 [`convert_all`]
+pub struct ConvertAll<I, T> {
+    iter: I,
+    // fn() -> T 不拥有 T，ConvertAll 的 Send/Sync 只取决于 I
+    _target: PhantomData<fn() -> T>,
+}
+
+impl<I, T> Iterator for ConvertAll<I, T>
+where
+    I: Iterator,
+    I::Item: Into<T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.iter.next().map(Into::into)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I, T> DoubleEndedIterator for ConvertAll<I, T>
+where
+    I: DoubleEndedIterator,
+    I::Item: Into<T>,
+{
+    fn next_back(&mut self) -> Option<T> {
+        self.iter.next_back().map(Into::into)
+    }
+}
+
+impl<I, T> ExactSizeIterator for ConvertAll<I, T>
+where
+    I: ExactSizeIterator,
+    I::Item: Into<T>,
+{
+}
+
+/// 把可迭代对象中的每个元素转换为 `T`
+///
+/// ```
+/// use macro_examples::advanced_traits::convert_all;
+///
+/// let wide: Vec<i64> = convert_all([1i32, 2, 3]).collect();
+/// assert_eq!(wide, vec![1i64, 2, 3]);
+/// ```
+pub fn convert_all<I, T>(iter: I) -> ConvertAll<I::IntoIter, T>
+where
+    I: IntoIterator,
+    I::Item: Into<T>,
+{
+    ConvertAll {
+        iter: iter.into_iter(),
+        _target: PhantomData,
     }
 }
 
@@ -1,19 +1,83 @@
 //! 声明式宏（macro_rules!）示例
 
-/// 创建一个简单的日志宏，可以记录不同级别的日志
+/// 日志宏，支持级别、目标和结构化键值对，输出交给 [`crate::logging`] 中的后端
+///
+/// ```
+/// use macro_examples::log;
+///
+/// log!("默认为 INFO 级别");
+/// log!(DEBUG, "调试信息");
+/// log!(ERROR, "错误代码: {}, 消息: {}", 404, "未找到");
+/// log!(INFO, target: "auth", user_id = 42, "login ok");
+/// ```
+///
+/// 级别为 `ERROR`、`WARN`、`INFO`、`DEBUG`、`TRACE`，未指定目标时为调用处的模块路径。
+/// 键值对写在消息之前，值需要实现 `Display`。
 #[macro_export]
 macro_rules! log {
+    // 没有消息
+    () => {
+        compile_error!("log! 需要日志消息，例如 log!(\"消息\") 或 log!(ERROR, \"消息\")")
+    };
     // 无级别，默认为 INFO
     ($msg:expr) => {
-        println!("[INFO] {}", $msg);
+        $crate::log!(INFO, $msg)
+    };
+    // 带目标
+    ($level:ident, target: $target:expr, $($rest:tt)+) => {
+        $crate::__log_munch!(@level $level, @target $target, @fields [], $($rest)+)
     };
     // 带级别
-    ($level:ident, $msg:expr) => {
-        println!("[{}] {}", stringify!($level), $msg);
+    ($level:ident, $($rest:tt)+) => {
+        $crate::__log_munch!(@level $level, @target ::std::module_path!(), @fields [], $($rest)+)
+    };
+}
+
+/// `log!` 的内部实现：逐个取出消息之前的 `key = value`，最后生成对后端的调用
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_munch {
+    // 取出一个键值对
+    (@level $level:ident, @target $target:expr, @fields [$($fields:tt)*], $key:ident = $value:expr, $($rest:tt)+) => {
+        $crate::__log_munch!(@level $level, @target $target, @fields [$($fields)* ($key, $value)], $($rest)+)
+    };
+    // 格式化字符串
+    (@level $level:ident, @target $target:expr, @fields [$(($key:ident, $value:expr))*], $fmt:literal $(, $arg:expr)* $(,)?) => {
+        $crate::__log_emit!($level, $target, [$(($key, $value))*], ::std::format_args!($fmt $(, $arg)*))
     };
-    // 带格式化的消息
-    ($level:ident, $($arg:tt)*) => {
-        println!("[{}] {}", stringify!($level), format!($($arg)*));
+    // 任意实现了 Display 的表达式
+    (@level $level:ident, @target $target:expr, @fields [$(($key:ident, $value:expr))*], $msg:expr) => {
+        $crate::__log_emit!($level, $target, [$(($key, $value))*], ::std::format_args!("{}", $msg))
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_emit {
+    ($level:ident, $target:expr, [$(($key:ident, $value:expr))*], $message:expr) => {{
+        let level = $crate::__log_level!($level);
+        // STATIC_MAX_LEVEL 是常量，被特性过滤掉的级别整个分支会被编译器移除
+        if level <= $crate::logging::STATIC_MAX_LEVEL && $crate::logging::enabled(level) {
+            $crate::logging::__dispatch(
+                level,
+                $target,
+                $message,
+                &[$((::std::stringify!($key), &$value as &dyn ::std::fmt::Display)),*],
+            );
+        }
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_level {
+    (ERROR) => { $crate::logging::Level::Error };
+    (WARN) => { $crate::logging::Level::Warn };
+    (INFO) => { $crate::logging::Level::Info };
+    (DEBUG) => { $crate::logging::Level::Debug };
+    (TRACE) => { $crate::logging::Level::Trace };
+    ($other:ident) => {
+        compile_error!(concat!("log! 不支持的级别 `", stringify!($other), "`，可用的级别：ERROR, WARN, INFO, DEBUG, TRACE"))
     };
 }
 
@@ -75,11 +139,31 @@ macro_rules! calculate {
             max_val
         }
     };
+    // 以下规则只用于给出可读的错误信息
+    (add $($rest:tt)*) => {
+        compile_error!("calculate!(add ...) 需要至少一个表达式，多个表达式用逗号分隔")
+    };
+    (mul $($rest:tt)*) => {
+        compile_error!("calculate!(mul ...) 需要至少一个表达式，多个表达式用逗号分隔")
+    };
+    (max $($rest:tt)*) => {
+        compile_error!("calculate!(max ...) 需要至少两个表达式，用逗号分隔")
+    };
+    ($op:ident $($rest:tt)*) => {
+        compile_error!(concat!("calculate! 不支持的运算 `", stringify!($op), "`，可用的运算：add, mul, max"))
+    };
+    ($($rest:tt)*) => {
+        compile_error!("calculate! 需要以运算开头，例如 calculate!(add 1, 2, 3)")
+    };
 }
 
 /// 创建一个用于创建结构体实例的宏，自动处理默认值
 #[macro_export]
 macro_rules! create_user {
+    // 缺少必需字段
+    () => {
+        compile_error!("create_user! 至少需要用户名：create_user!(name), create_user!(name, age) 或 create_user!(name, age, email)")
+    };
     // 只有必需字段
     ($name:expr) => {
         User {
@@ -104,6 +188,9 @@ macro_rules! create_user {
             email: $email.to_string(),
         }
     };
+    ($($rest:tt)*) => {
+        compile_error!("create_user! 最多接受三个参数：create_user!(name, age, email)")
+    };
 }
 
 /// 用户结构体（用于宏示例）
@@ -167,11 +254,25 @@ macro_rules! repeat {
             }
         }
     };
+    ($($rest:tt)*) => {
+        compile_error!("repeat! 的用法：repeat!(次数, { 代码块 })")
+    };
 }
 
-/// 创建一个用于定义枚举变体的宏，自动实现 Display trait
+/// 创建一个用于定义状态码枚举的宏，自动实现 Display trait
+///
+/// 每个变体的值就是它的状态码（`u16`），宏会同时生成：
+/// - `ALL`：按定义顺序排列的全部变体
+/// - `code()` / `from_code()`：变体与状态码互相转换
+/// - `is_success()` / `is_error()`：按 HTTP 约定判断，2xx 为成功，4xx 和 5xx 为错误
 #[macro_export]
 macro_rules! define_status {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {}
+    ) => {
+        compile_error!(concat!("define_status! 定义的枚举 `", stringify!($name), "` 至少需要一个变体，例如 Ok = 200"));
+    };
     (
         $(#[$meta:meta])*
         $vis:vis enum $name:ident {
@@ -183,15 +284,196 @@ macro_rules! define_status {
             $($variant = $val),+,
         }
 
-        impl std::fmt::Display for $name {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        impl $name {
+            /// 全部变体，按定义顺序排列
+            pub const ALL: &'static [$name] = &[$($name::$variant),+];
+
+            /// 状态码
+            pub fn code(&self) -> u16 {
                 match self {
                     $(
-                        $name::$variant => write!(f, "{}", $val),
+                        $name::$variant => $val,
                     )+
                 }
             }
+
+            /// 根据状态码查找变体，未定义的状态码返回 None
+            pub fn from_code(code: u16) -> Option<Self> {
+                $(
+                    if code == $val {
+                        return Some($name::$variant);
+                    }
+                )+
+                None
+            }
+
+            /// 是否为成功状态（2xx）
+            pub fn is_success(&self) -> bool {
+                (200..300).contains(&self.code())
+            }
+
+            /// 是否为错误状态（4xx 或 5xx）
+            pub fn is_error(&self) -> bool {
+                (400..600).contains(&self.code())
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.code())
+            }
+        }
+    };
+}
+
+/// 为结构体生成 [`Validate`](crate::validation::Validate) 实现，按字段规则逐一检查并收集所有错误
+///
+/// 支持的规则：
+/// - `non_empty`：字段不能为空（`String`、`Vec` 等有 `is_empty` 方法的类型）
+/// - `range(范围)`：字段需在范围内，例如 `range(1..=150)`
+/// - `regex("模式")`：字段需匹配正则表达式，正则只在第一次使用时编译
+///
+/// 同一字段需要多条规则时重复写该字段即可：
+///
+/// ```
+/// use macro_examples::validate_struct;
+/// use macro_examples::validation::Validate;
+///
+/// struct Account {
+///     login: String,
+///     level: u8,
+/// }
+///
+/// validate_struct! {
+///     Account {
+///         login: non_empty,
+///         login: regex("^[a-z]+$"),
+///         level: range(1..=10),
+///     }
+/// }
+///
+/// let account = Account { login: String::new(), level: 0 };
+/// let errors = account.validate().unwrap_err();
+/// assert_eq!(errors.len(), 3);
+/// ```
+#[macro_export]
+macro_rules! validate_struct {
+    (
+        $ty:ty {
+            $($field:ident : $rule:ident $(($($args:tt)*))?),+ $(,)?
+        }
+    ) => {
+        impl $crate::validation::Validate for $ty {
+            fn validate(&self) -> ::std::result::Result<(), ::std::vec::Vec<$crate::validation::ValidationError>> {
+                let mut errors = ::std::vec::Vec::new();
+                $(
+                    if let ::std::option::Option::Some(message) =
+                        $crate::__validate_rule!(&self.$field, $rule $(($($args)*))?)
+                    {
+                        errors.push($crate::validation::ValidationError {
+                            field: ::std::stringify!($field),
+                            message,
+                        });
+                    }
+                )+
+                if errors.is_empty() {
+                    ::std::result::Result::Ok(())
+                } else {
+                    ::std::result::Result::Err(errors)
+                }
+            }
+        }
+    };
+}
+
+/// `validate_struct!` 的单条规则，校验失败时返回错误信息
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __validate_rule {
+    ($value:expr, non_empty) => {
+        if $value.is_empty() {
+            ::std::option::Option::Some(::std::string::String::from("不能为空"))
+        } else {
+            ::std::option::Option::None
+        }
+    };
+    ($value:expr, range($range:expr)) => {{
+        let range = $range;
+        let value = $value;
+        if range.contains(value) {
+            ::std::option::Option::None
+        } else {
+            ::std::option::Option::Some(::std::format!("必须在 {:?} 范围内，实际为 {}", range, value))
+        }
+    }};
+    ($value:expr, regex($pattern:literal)) => {{
+        static PATTERN: ::std::sync::OnceLock<$crate::validation::regex::Regex> = ::std::sync::OnceLock::new();
+        let pattern = PATTERN.get_or_init(|| $crate::validation::regex::Regex::new($pattern).expect("无效的正则表达式"));
+        if pattern.is_match($value) {
+            ::std::option::Option::None
+        } else {
+            ::std::option::Option::Some(::std::format!("格式不正确，需匹配 {}", $pattern))
+        }
+    }};
+    ($value:expr, $rule:ident $($args:tt)*) => {
+        compile_error!(concat!("validate_struct! 不支持的规则 `", stringify!($rule), "`，可用的规则：non_empty, range(..), regex(\"..\")"))
+    };
+}
+
+/// 定义包装 `f64` 的单位新类型：`#[repr(transparent)]`，运行时与 `f64` 完全相同
+///
+/// 生成 `new()` / `value()` 和带单位后缀的 `Display`
+#[macro_export]
+macro_rules! newtype {
+    ($(#[$meta:meta])* $vis:vis $name:ident($inner:ty), $unit:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+        #[repr(transparent)]
+        $vis struct $name(pub $inner);
+
+        impl $name {
+            pub const fn new(value: $inner) -> Self {
+                $name(value)
+            }
+
+            pub const fn value(self) -> $inner {
+                self.0
+            }
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                write!(f, "{}{}", self.0, $unit)
+            }
         }
     };
 }
 
+/// 为两个新类型生成双向的 `From` 实现，生成后即可使用 `Converter` 和 `convert_all`
+///
+/// ```
+/// use macro_examples::{newtype, newtype_conversion};
+/// use macro_examples::advanced_traits::Converter;
+///
+/// newtype!(pub Grams(f64), "g");
+/// newtype!(pub Kilograms(f64), "kg");
+/// newtype_conversion!(Grams <=> Kilograms, |g| g / 1000.0, |kg| kg * 1000.0);
+///
+/// let kg: Kilograms = Grams(2500.0).convert();
+/// assert_eq!(kg, Kilograms(2.5));
+/// ```
+#[macro_export]
+macro_rules! newtype_conversion {
+    ($from:ident <=> $to:ident, |$a:ident| $forward:expr, |$b:ident| $backward:expr $(,)?) => {
+        $crate::newtype_conversion!($from => $to, |$a| $forward);
+        $crate::newtype_conversion!($to => $from, |$b| $backward);
+    };
+    ($from:ident => $to:ident, |$a:ident| $forward:expr $(,)?) => {
+        impl ::std::convert::From<$from> for $to {
+            fn from(value: $from) -> Self {
+                let $a = value.0;
+                $to($forward)
+            }
+        }
+    };
+}
@@ -0,0 +1,144 @@
+//! 手写的迷你执行器：不依赖 tokio，演示 Future、Waker 和执行器之间如何配合
+//!
+//! - [`block_on`]：在当前线程上反复 poll 一个 Future，返回 Pending 时挂起线程等待唤醒
+//! - [`TimerFuture`]：在后台线程计时，到期后通过 Waker 唤醒执行器
+//! - [`YieldNow`]：第一次 poll 返回 Pending 并立即唤醒自己，把控制权交回执行器
+
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+// Waker 的数据指针是 Arc<Thread>::into_raw 得到的指针，下面四个函数手动维护引用计数
+
+unsafe fn clone_waker(data: *const ()) -> RawWaker {
+    Arc::increment_strong_count(data as *const Thread);
+    RawWaker::new(data, &VTABLE)
+}
+
+unsafe fn wake(data: *const ()) {
+    // 取回所有权，唤醒后随之释放
+    let thread = Arc::from_raw(data as *const Thread);
+    thread.unpark();
+}
+
+unsafe fn wake_by_ref(data: *const ()) {
+    (*(data as *const Thread)).unpark();
+}
+
+unsafe fn drop_waker(data: *const ()) {
+    drop(Arc::from_raw(data as *const Thread));
+}
+
+static VTABLE: RawWakerVTable = RawWakerVTable::new(clone_waker, wake, wake_by_ref, drop_waker);
+
+/// 创建唤醒指定线程的 Waker
+fn thread_waker(thread: Thread) -> Waker {
+    let data = Arc::into_raw(Arc::new(thread)) as *const ();
+    // 安全性：VTABLE 中的函数按 Arc<Thread> 的约定使用 data
+    unsafe { Waker::from_raw(RawWaker::new(data, &VTABLE)) }
+}
+
+/// 在当前线程上运行 Future 直到完成
+///
+/// 每次 poll 返回 Pending 就 park 当前线程，直到某个 Waker 调用 unpark 再重新 poll。
+/// park 可能被虚假唤醒，此时多 poll 一次也不影响正确性。
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = thread_waker(thread::current());
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// 计时器的共享状态
+struct TimerState {
+    completed: bool,
+    /// 最近一次 poll 时传入的 Waker，计时结束后用它唤醒执行器
+    waker: Option<Waker>,
+}
+
+/// 经过指定时间后完成的 Future
+///
+/// 第一次 poll 时启动后台线程计时，之后的 poll 只更新 Waker
+pub struct TimerFuture {
+    duration: Duration,
+    state: Arc<Mutex<TimerState>>,
+    started: bool,
+}
+
+impl TimerFuture {
+    pub fn new(duration: Duration) -> Self {
+        TimerFuture {
+            duration,
+            state: Arc::new(Mutex::new(TimerState {
+                completed: false,
+                waker: None,
+            })),
+            started: false,
+        }
+    }
+}
+
+impl Future for TimerFuture {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.completed {
+                return Poll::Ready(());
+            }
+            // Future 可能在不同的任务间移动，每次都保存最新的 Waker
+            state.waker = Some(cx.waker().clone());
+        }
+
+        if !self.started {
+            self.started = true;
+            let state = Arc::clone(&self.state);
+            let duration = self.duration;
+            thread::spawn(move || {
+                thread::sleep(duration);
+                let mut state = state.lock().unwrap();
+                state.completed = true;
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+            });
+        }
+        Poll::Pending
+    }
+}
+
+/// 让出一次执行权的 Future：第一次 poll 返回 Pending，第二次返回 Ready
+#[derive(Default)]
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl YieldNow {
+    pub fn new() -> Self {
+        YieldNow::default()
+    }
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        // 返回 Pending 之前必须安排唤醒，否则执行器会一直等下去
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
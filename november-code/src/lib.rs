@@ -5,6 +5,10 @@
 //! 2. 过程宏的使用
 //! 3. 高级特性（Traits）和关联类型
 //! 4. 使用宏简化代码的实用示例
+//! 5. 可替换后端的结构化日志宏
+//! 6. 由宏生成 trait 实现的字段校验
+//! 7. 手写的迷你执行器，演示 Future 在运行时之下如何工作
+//! 8. 基于 From/TryFrom 的转换框架和单位新类型
 
 // 使用 #[macro_use] 导入宏模块（宏会通过 #[macro_export] 自动导出到 crate 根）
 #[macro_use]
@@ -14,9 +18,14 @@ pub mod declarative_macros;
 pub mod utils;
 
 pub mod advanced_traits;
+pub mod executors;
+pub mod logging;
+pub mod units;
+pub mod validation;
 
 // 重新导出非宏项
 pub use declarative_macros::User;
 pub use advanced_traits::*;
 pub use utils::Person;
+pub use validation::{Validate, ValidationError};
 
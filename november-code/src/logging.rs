@@ -0,0 +1,149 @@
+//! `log!` 宏的日志后端
+//!
+//! 宏只负责收集级别、目标、键值对和消息，真正的输出交给实现了 [`LogBackend`] 的后端，
+//! 默认后端 [`StdoutBackend`] 打印到标准输出，可以用 [`set_backend`] 替换。
+//!
+//! 级别过滤分两层：
+//! - 编译期：启用 `max_level_error` / `max_level_warn` / `max_level_info` / `max_level_debug`
+//!   特性后，高于该级别的 `log!` 调用会被编译器整体移除，参数也不会求值
+//! - 运行期：[`set_max_level`]
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// 日志级别，越靠前越严重
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    fn from_usize(value: usize) -> Option<Level> {
+        match value {
+            1 => Some(Level::Error),
+            2 => Some(Level::Warn),
+            3 => Some(Level::Info),
+            4 => Some(Level::Debug),
+            5 => Some(Level::Trace),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 编译期允许的最高级别，由 `max_level_*` 特性决定，同时启用多个时取最严格的
+pub const STATIC_MAX_LEVEL: Level = if cfg!(feature = "max_level_error") {
+    Level::Error
+} else if cfg!(feature = "max_level_warn") {
+    Level::Warn
+} else if cfg!(feature = "max_level_info") {
+    Level::Info
+} else if cfg!(feature = "max_level_debug") {
+    Level::Debug
+} else {
+    Level::Trace
+};
+
+static MAX_LEVEL: AtomicUsize = AtomicUsize::new(Level::Trace as usize);
+
+/// 设置运行期允许的最高级别
+pub fn set_max_level(level: Level) {
+    MAX_LEVEL.store(level as usize, Ordering::Relaxed);
+}
+
+/// 运行期允许的最高级别
+pub fn max_level() -> Level {
+    Level::from_usize(MAX_LEVEL.load(Ordering::Relaxed)).unwrap_or(Level::Trace)
+}
+
+/// 一条日志记录
+pub struct Record<'a> {
+    pub level: Level,
+    /// 日志目标，未指定时为调用处的模块路径
+    pub target: &'a str,
+    pub message: fmt::Arguments<'a>,
+    /// 结构化字段，按书写顺序排列
+    pub fields: &'a [(&'static str, &'a dyn fmt::Display)],
+}
+
+/// 日志后端
+pub trait LogBackend: Send + Sync {
+    fn log(&self, record: &Record<'_>);
+}
+
+/// 打印到标准输出：`[INFO auth] login ok user_id=42`
+pub struct StdoutBackend;
+
+impl LogBackend for StdoutBackend {
+    fn log(&self, record: &Record<'_>) {
+        println!("{}", format_record(record));
+    }
+}
+
+/// 按 [`StdoutBackend`] 的格式把记录格式化成一行
+pub fn format_record(record: &Record<'_>) -> String {
+    let mut line = format!("[{} {}] {}", record.level, record.target, record.message);
+    for (key, value) in record.fields {
+        line.push_str(&format!(" {}={}", key, value));
+    }
+    line
+}
+
+static BACKEND: RwLock<Option<Arc<dyn LogBackend>>> = RwLock::new(None);
+
+/// 替换日志后端
+pub fn set_backend(backend: impl LogBackend + 'static) {
+    *BACKEND.write().unwrap() = Some(Arc::new(backend));
+}
+
+/// 恢复默认的 [`StdoutBackend`]
+pub fn reset_backend() {
+    *BACKEND.write().unwrap() = None;
+}
+
+/// 运行期级别是否允许输出，宏展开时调用
+pub fn enabled(level: Level) -> bool {
+    level <= max_level()
+}
+
+/// 把记录交给当前后端，宏展开时调用
+#[doc(hidden)]
+pub fn __dispatch(
+    level: Level,
+    target: &str,
+    message: fmt::Arguments<'_>,
+    fields: &[(&'static str, &dyn fmt::Display)],
+) {
+    let record = Record {
+        level,
+        target,
+        message,
+        fields,
+    };
+    // 先克隆出后端再调用，后端内部再打日志也不会死锁
+    let backend = BACKEND.read().unwrap().clone();
+    match backend {
+        Some(backend) => backend.log(&record),
+        None => StdoutBackend.log(&record),
+    }
+}
@@ -4,10 +4,12 @@
 use macro_examples::{
     advanced_traits::*,
     User,
+    Validate,
 };
 
 // 在模块级别定义枚举（宏需要在顶层展开）
 macro_examples::define_status! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum HttpStatus {
         Ok = 200,
         NotFound = 404,
@@ -38,6 +40,12 @@ fn demonstrate_declarative_macros() {
     macro_examples::log!("这是一条信息日志");
     macro_examples::log!(DEBUG, "这是一条调试日志");
     macro_examples::log!(ERROR, "错误代码: {}, 消息: {}", 404, "未找到");
+    macro_examples::log!(INFO, target: "auth", user_id = 42, "login ok");
+    macro_examples::log!(WARN, retries = 3, timeout_ms = 500, "连接超时，正在重试");
+    // 运行期调高过滤级别后 DEBUG 日志不再输出
+    macro_examples::logging::set_max_level(macro_examples::logging::Level::Info);
+    macro_examples::log!(DEBUG, "这条日志不会输出");
+    macro_examples::logging::set_max_level(macro_examples::logging::Level::Trace);
 
     // 自定义 vec! 宏
     let v1: Vec<i32> = macro_examples::my_vec!();
@@ -63,6 +71,17 @@ fn demonstrate_declarative_macros() {
     println!("用户2: {:?}", user2);
     println!("用户3: {:?}", user3);
 
+    // 校验宏生成的 validate()，一次返回所有不合法的字段
+    for user in [&user1, &user3] {
+        match user.validate() {
+            Ok(()) => println!("{} 校验通过", user.name),
+            Err(errors) => {
+                let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                println!("{} 校验失败: {}", user.name, messages.join("; "));
+            }
+        }
+    }
+
     // 重复宏
     print!("重复打印: ");
     macro_examples::repeat!(3, {
@@ -73,6 +92,17 @@ fn demonstrate_declarative_macros() {
     // 使用在模块级别定义的枚举
     println!("HTTP 状态: {}", HttpStatus::Ok);
     println!("HTTP 状态: {}", HttpStatus::NotFound);
+    for status in HttpStatus::ALL {
+        println!(
+            "{:?}: 状态码 {}, 成功: {}, 错误: {}",
+            status,
+            status.code(),
+            status.is_success(),
+            status.is_error()
+        );
+    }
+    println!("状态码 404 对应: {:?}", HttpStatus::from_code(404));
+    println!("状态码 302 对应: {:?}", HttpStatus::from_code(302));
 }
 
 /// 演示高级特性
@@ -121,6 +151,19 @@ fn demonstrate_advanced_traits() {
     println!("\n绘制所有对象:");
     draw_all(&drawables);
 
+    // 访问者模式：同一棵表达式树交给不同的访问者
+    // (1 + 2) * -(3 + 4)
+    let expr = Expr::product(
+        Expr::sum(Expr::number(1.0), Expr::number(2.0)),
+        Expr::negate(Expr::sum(Expr::number(3.0), Expr::number(4.0))),
+    );
+    println!("表达式: {}", PrettyPrinter::print(&expr));
+    println!("求值结果: {}", Evaluator::evaluate(&expr));
+    let mut counter = NumberCounter::default();
+    let mut printer = PrettyPrinter::default();
+    visit_all(&expr, &mut [&mut counter, &mut printer]);
+    println!("数字个数: {}", counter.count);
+
     // 特性继承
     let readable_text = Text {
         content: "可读文本".to_string(),
@@ -137,10 +180,32 @@ fn demonstrate_advanced_traits() {
     let converted: i64 = num.convert();
     println!("转换 {} -> {}", num, converted);
 
+    // 单位新类型之间的转换
+    use macro_examples::units::{Celsius, Fahrenheit, Feet, Kelvin, Meters};
+    let height: Feet = Meters(1.8).convert();
+    println!("{} = {:.2}ft", Meters(1.8), height.value());
+    let temperatures: Vec<Fahrenheit> = convert_all([Celsius(0.0), Celsius(37.0), Celsius(100.0)]).collect();
+    println!("摄氏度转华氏度: {:?}", temperatures);
+    match Celsius(-300.0).try_convert::<Kelvin>() {
+        Ok(kelvin) => println!("转换为开尔文: {}", kelvin),
+        Err(e) => println!("转换失败: {}", e),
+    }
+
     // 处理器
     let processor = IntProcessor;
     let result = processor.process(21);
     println!("处理 21 -> {}", result);
+
+    // 手写执行器运行 async 代码
+    use macro_examples::executors::{block_on, TimerFuture, YieldNow};
+    use std::time::{Duration, Instant};
+    let start = Instant::now();
+    let answer = block_on(async {
+        TimerFuture::new(Duration::from_millis(20)).await;
+        YieldNow::new().await;
+        21 * 2
+    });
+    println!("block_on 结果: {}，耗时约 {:?}", answer, start.elapsed());
 }
 
 /// 演示实用宏
@@ -165,8 +230,10 @@ fn demonstrate_utility_macros() {
     let user = macro_examples::test_data!(user "李四", 35);
     println!("测试用户: {:?}", user);
 
-    // 近似相等断言（在测试中使用）
+    // 近似相等断言（在测试中使用），这里故意用 π 的近似值演示
+    #[allow(clippy::approx_constant)]
     let pi = 3.14159;
+    #[allow(clippy::approx_constant)]
     let approx_pi = 3.1416;
     macro_examples::assert_approx_eq!(pi, approx_pi, 0.0001);
     println!("近似相等断言通过: {} ≈ {}", pi, approx_pi);
@@ -198,6 +265,84 @@ mod tests {
         assert!(!rect.contains(&Point2D { x: 15.0, y: 2.5 }));
     }
 
+    #[test]
+    fn test_expr_visitors() {
+        // 2 * (3 + 4) + -5
+        let expr = Expr::sum(
+            Expr::product(Expr::number(2.0), Expr::sum(Expr::number(3.0), Expr::number(4.0))),
+            Expr::negate(Expr::number(5.0)),
+        );
+        assert_eq!(Evaluator::evaluate(&expr), 9.0);
+        assert_eq!(PrettyPrinter::print(&expr), "2 * (3 + 4) + -5");
+
+        let nested = Expr::negate(Expr::negate(Expr::product(Expr::number(-1.5), Expr::number(2.0))));
+        assert_eq!(Evaluator::evaluate(&nested), -3.0);
+        assert_eq!(PrettyPrinter::print(&nested), "-(-(-1.5 * 2))");
+    }
+
+    #[test]
+    fn test_visitor_defaults_and_trait_objects() {
+        let expr = Expr::sum(
+            Expr::number(1.0),
+            Expr::product(Expr::negate(Expr::number(2.0)), Expr::number(3.0)),
+        );
+        let mut counter = NumberCounter::default();
+        let mut evaluator = Evaluator::default();
+        visit_all(&expr, &mut [&mut counter, &mut evaluator]);
+        // NumberCounter 只重写了 visit_number，默认实现负责遍历
+        assert_eq!(counter.count, 3);
+
+        let visitor: &mut dyn Visitor = &mut counter;
+        Expr::number(0.0).accept(visitor);
+        assert_eq!(counter.count, 4);
+    }
+
+    #[test]
+    fn test_unit_conversions() {
+        use macro_examples::units::{Celsius, Fahrenheit, Feet, Meters};
+
+        let feet: Feet = Meters(1.0).convert();
+        macro_examples::assert_approx_eq!(feet.value(), 3.28084, 1e-5);
+        let meters: Meters = Feet(10.0).convert();
+        macro_examples::assert_approx_eq!(meters.value(), 3.048, 1e-9);
+
+        assert_eq!(Fahrenheit::from(Celsius(100.0)), Fahrenheit(212.0));
+        assert_eq!(Celsius::from(Fahrenheit(-40.0)), Celsius(-40.0));
+        // 往返转换结果不变（在浮点误差范围内）
+        for value in [-40.0, 0.0, 36.6, 1000.0] {
+            let back: Celsius = Celsius(value).convert::<Fahrenheit>().convert();
+            macro_examples::assert_approx_eq!(back.value(), value, 1e-9);
+        }
+        assert_eq!(Meters(2.5).to_string(), "2.5m");
+    }
+
+    #[test]
+    fn test_try_convert() {
+        use macro_examples::units::{BelowAbsoluteZero, Celsius, Kelvin};
+
+        assert_eq!(Celsius(0.0).try_convert::<Kelvin>(), Ok(Kelvin(273.15)));
+        assert_eq!(Celsius(-300.0).try_convert::<Kelvin>(), Err(BelowAbsoluteZero(Celsius(-300.0))));
+        assert_eq!(Celsius::from(Kelvin(0.0)), Celsius(-273.15));
+        assert!(300i32.try_convert::<u8>().is_err());
+        assert_eq!(200i32.try_convert::<u8>(), Ok(200u8));
+    }
+
+    #[test]
+    fn test_convert_all() {
+        use macro_examples::units::{Celsius, Fahrenheit};
+
+        let wide: Vec<i64> = convert_all(vec![1i32, -2, 3]).collect();
+        assert_eq!(wide, vec![1i64, -2, 3]);
+
+        let iter = convert_all::<_, Fahrenheit>([Celsius(0.0), Celsius(100.0)]);
+        assert_eq!(iter.len(), 2);
+        let reversed: Vec<Fahrenheit> = iter.rev().collect();
+        assert_eq!(reversed, vec![Fahrenheit(212.0), Fahrenheit(32.0)]);
+
+        // 新类型与 f64 内存布局相同
+        assert_eq!(std::mem::size_of::<Celsius>(), std::mem::size_of::<f64>());
+    }
+
     #[test]
     fn test_my_vec() {
         let v1: Vec<i32> = macro_examples::my_vec!();
@@ -216,5 +361,174 @@ mod tests {
         assert_eq!(macro_examples::calculate!(mul 2, 3, 4), 24);
         assert_eq!(macro_examples::calculate!(max 10, 25, 5), 25);
     }
+
+    /// 把日志记录保存下来的后端
+    struct CaptureBackend(std::sync::Arc<std::sync::Mutex<Vec<String>>>);
+
+    impl macro_examples::logging::LogBackend for CaptureBackend {
+        fn log(&self, record: &macro_examples::logging::Record<'_>) {
+            self.0.lock().unwrap().push(macro_examples::logging::format_record(record));
+        }
+    }
+
+    #[test]
+    fn test_log_backend_and_fields() {
+        use macro_examples::logging::{self, Level};
+
+        let lines = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        logging::set_backend(CaptureBackend(lines.clone()));
+
+        let user_id = 42;
+        macro_examples::log!(INFO, target: "auth", user_id = user_id, ip = "127.0.0.1", "login ok");
+        macro_examples::log!(ERROR, target: "db", "错误代码: {}", 500);
+        macro_examples::log!(WARN, "默认目标");
+        let message = String::from("非字面量消息");
+        macro_examples::log!(TRACE, target: "misc", message);
+
+        logging::set_max_level(Level::Warn);
+        macro_examples::log!(INFO, target: "auth", "被过滤");
+        macro_examples::log!(WARN, target: "auth", "未被过滤");
+        logging::set_max_level(Level::Trace);
+        logging::reset_backend();
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(
+            *lines,
+            vec![
+                "[INFO auth] login ok user_id=42 ip=127.0.0.1".to_string(),
+                "[ERROR db] 错误代码: 500".to_string(),
+                format!("[WARN {}] 默认目标", module_path!()),
+                "[TRACE misc] 非字面量消息".to_string(),
+                "[WARN auth] 未被过滤".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_user() {
+        let valid = macro_examples::create_user!("Charlie", 30, "charlie@example.com");
+        assert_eq!(valid.validate(), Ok(()));
+
+        let invalid = macro_examples::create_user!("", 200, "not-an-email");
+        let errors = invalid.validate().unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field).collect();
+        assert_eq!(fields, vec!["name", "age", "email"]);
+        assert_eq!(errors[1].message, "必须在 1..=150 范围内，实际为 200");
+    }
+
+    #[test]
+    fn test_validate_person() {
+        let person = macro_examples::Person::new("张三", 28, "zhangsan@example.com");
+        assert!(person.validate().is_ok());
+
+        // 同一字段的多条规则分别报告
+        let person = macro_examples::Person::new("张三", 28, "");
+        let errors = person.validate().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(|e| e.field == "email"));
+        assert_eq!(errors[0].to_string(), "email: 不能为空");
+    }
+
+    #[test]
+    fn test_block_on_chain_of_futures() {
+        use macro_examples::executors::{block_on, TimerFuture, YieldNow};
+        use std::time::{Duration, Instant};
+
+        async fn double_after(value: u32, delay: Duration) -> u32 {
+            TimerFuture::new(delay).await;
+            value * 2
+        }
+
+        let start = Instant::now();
+        let result = block_on(async {
+            let a = double_after(1, Duration::from_millis(10)).await;
+            YieldNow::new().await;
+            let b = double_after(a, Duration::from_millis(10)).await;
+            YieldNow::new().await;
+            double_after(b, Duration::from_millis(10)).await
+        });
+        assert_eq!(result, 8);
+        assert!(start.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[test]
+    fn test_yield_now_is_polled_twice() {
+        use macro_examples::executors::{block_on, YieldNow};
+        use std::future::Future;
+        use std::pin::Pin;
+        use std::task::{Context, Poll};
+
+        /// 记录被 poll 的次数
+        struct CountPolls<F> {
+            inner: F,
+            polls: u32,
+        }
+
+        impl<F: Future + Unpin> Future for CountPolls<F> {
+            type Output = u32;
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<u32> {
+                self.polls += 1;
+                match Pin::new(&mut self.inner).poll(cx) {
+                    Poll::Ready(_) => Poll::Ready(self.polls),
+                    Poll::Pending => Poll::Pending,
+                }
+            }
+        }
+
+        assert_eq!(block_on(CountPolls { inner: YieldNow::new(), polls: 0 }), 2);
+        assert_eq!(block_on(async { "不需要等待的 Future" }), "不需要等待的 Future");
+    }
+
+    macro_examples::define_status! {
+        /// 覆盖各个区间的状态码
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        enum TestStatus {
+            Continue = 100,
+            Created = 201,
+            NoContent = 204,
+            Found = 302,
+            BadRequest = 400,
+            Teapot = 418,
+            BadGateway = 502,
+        }
+    }
+
+    #[test]
+    fn test_status_code_round_trip() {
+        assert_eq!(HttpStatus::ALL, &[HttpStatus::Ok, HttpStatus::NotFound, HttpStatus::ServerError]);
+        for &status in HttpStatus::ALL {
+            assert_eq!(HttpStatus::from_code(status.code()), Some(status));
+            assert_eq!(status.to_string(), status.code().to_string());
+        }
+        for &status in TestStatus::ALL {
+            assert_eq!(TestStatus::from_code(status.code()), Some(status));
+        }
+    }
+
+    #[test]
+    fn test_status_from_unknown_code() {
+        let defined: Vec<u16> = HttpStatus::ALL.iter().map(HttpStatus::code).collect();
+        for code in (0..=u16::MAX).filter(|code| !defined.contains(code)) {
+            assert_eq!(HttpStatus::from_code(code), None);
+        }
+    }
+
+    #[test]
+    fn test_status_predicates() {
+        assert_eq!(HttpStatus::Ok.code(), 200);
+        assert!(HttpStatus::Ok.is_success() && !HttpStatus::Ok.is_error());
+        assert!(HttpStatus::NotFound.is_error() && !HttpStatus::NotFound.is_success());
+        assert!(HttpStatus::ServerError.is_error());
+
+        for &status in TestStatus::ALL {
+            let code = status.code();
+            assert_eq!(status.is_success(), (200..300).contains(&code), "{:?}", status);
+            assert_eq!(status.is_error(), code >= 400, "{:?}", status);
+        }
+        // 1xx 和 3xx 既不是成功也不是错误
+        assert!(!TestStatus::Continue.is_success() && !TestStatus::Continue.is_error());
+        assert!(!TestStatus::Found.is_success() && !TestStatus::Found.is_error());
+    }
 }
 
@@ -0,0 +1,58 @@
+//! 用 `newtype!` 和 `newtype_conversion!` 定义的单位类型
+
+use std::fmt;
+
+const FEET_PER_METER: f64 = 3.280_839_895;
+
+/// 绝对零度（摄氏度）
+const ABSOLUTE_ZERO_CELSIUS: f64 = -273.15;
+
+newtype!(
+    /// 米
+    pub Meters(f64), "m"
+);
+newtype!(
+    /// 英尺
+    pub Feet(f64), "ft"
+);
+newtype!(
+    /// 摄氏度
+    pub Celsius(f64), "°C"
+);
+newtype!(
+    /// 华氏度
+    pub Fahrenheit(f64), "°F"
+);
+newtype!(
+    /// 开尔文，不能低于 0
+    pub Kelvin(f64), "K"
+);
+
+newtype_conversion!(Meters <=> Feet, |m| m * FEET_PER_METER, |ft| ft / FEET_PER_METER);
+newtype_conversion!(Celsius <=> Fahrenheit, |c| c * 9.0 / 5.0 + 32.0, |f| (f - 32.0) * 5.0 / 9.0);
+newtype_conversion!(Kelvin => Celsius, |k| k + ABSOLUTE_ZERO_CELSIUS);
+
+/// 温度低于绝对零度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BelowAbsoluteZero(pub Celsius);
+
+impl fmt::Display for BelowAbsoluteZero {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} 低于绝对零度", self.0)
+    }
+}
+
+impl std::error::Error for BelowAbsoluteZero {}
+
+/// 摄氏度转开尔文可能失败，用 `TryConverter::try_convert` 调用
+impl TryFrom<Celsius> for Kelvin {
+    type Error = BelowAbsoluteZero;
+
+    fn try_from(celsius: Celsius) -> Result<Self, Self::Error> {
+        if celsius.0 < ABSOLUTE_ZERO_CELSIUS {
+            Err(BelowAbsoluteZero(celsius))
+        } else {
+            Ok(Kelvin(celsius.0 - ABSOLUTE_ZERO_CELSIUS))
+        }
+    }
+}
@@ -0,0 +1,47 @@
+//! 字段校验：`validate_struct!` 宏为结构体生成 [`Validate`] 实现
+
+use std::fmt;
+
+use crate::{Person, User};
+
+// 宏展开后通过 `$crate::validation::regex` 使用，调用方不需要自己依赖 regex
+#[doc(hidden)]
+pub use regex;
+
+/// 单个字段的校验错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// 可校验的类型
+pub trait Validate {
+    /// 检查所有规则，返回全部失败的字段，而不是遇到第一个错误就停止
+    fn validate(&self) -> Result<(), Vec<ValidationError>>;
+}
+
+validate_struct! {
+    User {
+        name: non_empty,
+        age: range(1..=150),
+        email: regex(r"^[^@\s]+@[^@\s]+\.[^@\s]+$"),
+    }
+}
+
+validate_struct! {
+    Person {
+        name: non_empty,
+        age: range(0..=150),
+        email: non_empty,
+        email: regex(r"^[^@\s]+@[^@\s]+\.[^@\s]+$"),
+    }
+}
@@ -0,0 +1,9 @@
+//! 宏的编译失败测试：确保错误用法会被拒绝，并给出可读的错误信息
+//!
+//! 修改宏的错误信息后，用 `TRYBUILD=overwrite cargo test --test ui` 更新 `tests/ui/*.stderr`
+
+#[test]
+fn macro_misuse_is_rejected() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
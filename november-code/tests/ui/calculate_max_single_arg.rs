@@ -0,0 +1,3 @@
+fn main() {
+    let _ = macro_examples::calculate!(max 1);
+}
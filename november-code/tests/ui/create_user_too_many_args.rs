@@ -0,0 +1,5 @@
+use macro_examples::User;
+
+fn main() {
+    let _user: User = macro_examples::create_user!("Alice", 30, "alice@example.com", "extra");
+}
@@ -0,0 +1,5 @@
+macro_examples::define_status! {
+    pub enum EmptyStatus {}
+}
+
+fn main() {}
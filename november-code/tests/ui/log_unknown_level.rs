@@ -0,0 +1,3 @@
+fn main() {
+    macro_examples::log!(VERBOSE, "unknown level");
+}
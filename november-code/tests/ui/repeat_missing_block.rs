@@ -0,0 +1,3 @@
+fn main() {
+    macro_examples::repeat!(3);
+}
@@ -0,0 +1,11 @@
+struct Config {
+    port: u16,
+}
+
+macro_examples::validate_struct! {
+    Config {
+        port: positive,
+    }
+}
+
+fn main() {}
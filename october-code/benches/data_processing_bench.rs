@@ -1,5 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use performance_optimization_demo::{optimized, unoptimized};
+use performance_optimization_demo::optimized::ReserveStrategy;
+use performance_optimization_demo::{hashers, mapreduce, optimized, unoptimized};
 use rand::Rng;
 
 fn generate_test_data(size: usize) -> Vec<i32> {
@@ -41,6 +42,79 @@ fn bench_find_most_frequent(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_hash_algorithms(c: &mut Criterion) {
+    // 不同数字较少（约 2001 个）和几乎全部不同两种分布
+    let bounded = generate_test_data(100_000);
+    let mut rng = rand::thread_rng();
+    let distinct: Vec<i32> = (0..100_000).map(|_| rng.gen()).collect();
+
+    let mut group = c.benchmark_group("hash_algorithms");
+
+    for (name, data) in [("bounded", &bounded), ("distinct", &distinct)] {
+        for (reserve_name, reserve) in [
+            ("no_reserve", ReserveStrategy::None),
+            ("half_length", ReserveStrategy::HalfLength),
+            ("sampled", ReserveStrategy::Sampled),
+        ] {
+            group.bench_function(format!("siphash_{}/{}", reserve_name, name), |b| {
+                b.iter(|| optimized::find_most_frequent_with(black_box(data), hashers::SipHash::new(), reserve))
+            });
+        }
+
+        #[cfg(feature = "fxhash")]
+        group.bench_function(format!("fxhash_sampled/{}", name), |b| {
+            b.iter(|| optimized::find_most_frequent_with(black_box(data), hashers::FxHash, ReserveStrategy::Sampled))
+        });
+
+        #[cfg(feature = "ahash")]
+        group.bench_function(format!("ahash_sampled/{}", name), |b| {
+            b.iter(|| {
+                optimized::find_most_frequent_with(black_box(data), hashers::AHash::new(), ReserveStrategy::Sampled)
+            })
+        });
+    }
+
+    // 单独测量抽样估算本身的开销
+    group.bench_function("estimate_cardinality/bounded", |b| {
+        b.iter(|| optimized::estimate_cardinality(black_box(&bounded)))
+    });
+
+    group.finish();
+}
+
+fn bench_counting_sort(c: &mut Criterion) {
+    let bounded = generate_test_data(100_000);
+    let (min, max) = optimized::probe_range(&bounded).unwrap();
+    let mut rng = rand::thread_rng();
+    let wide: Vec<i32> = (0..100_000).map(|_| rng.gen()).collect();
+
+    let mut group = c.benchmark_group("counting_sort");
+
+    group.bench_function("hashmap/bounded", |b| {
+        b.iter(|| optimized::find_most_frequent_hashed(black_box(&bounded)))
+    });
+
+    group.bench_function("dense_array/bounded", |b| {
+        b.iter(|| optimized::find_most_frequent_bounded(black_box(&bounded), min, max))
+    });
+
+    // 包含范围探测的开销
+    group.bench_function("auto/bounded", |b| {
+        b.iter(|| optimized::find_most_frequent_auto(black_box(&bounded)))
+    });
+
+    // 范围过大时自动退回 HashMap，这里只多了一次范围探测
+    group.bench_function("hashmap/wide", |b| {
+        b.iter(|| optimized::find_most_frequent_hashed(black_box(&wide)))
+    });
+
+    group.bench_function("auto/wide", |b| {
+        b.iter(|| optimized::find_most_frequent_auto(black_box(&wide)))
+    });
+
+    group.finish();
+}
+
 fn bench_filter_and_transform(c: &mut Criterion) {
     let data = generate_test_data(10000);
     
@@ -73,12 +147,92 @@ fn bench_process_strings(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_iterator_variants(c: &mut Criterion) {
+    let data = generate_test_data(10000);
+    let strings_data: Vec<i32> = (0..1000).collect();
+
+    let mut group = c.benchmark_group("iterator_variants");
+
+    // 调用方只需要求和时，收集版本多了一次 Vec 分配和一次遍历
+    group.bench_function("filter_collect_then_sum", |b| {
+        b.iter(|| optimized::filter_and_transform(black_box(&data)).iter().sum::<i32>())
+    });
+
+    group.bench_function("filter_iter_sum", |b| {
+        b.iter(|| optimized::filter_and_transform_iter(black_box(&data)).sum::<i32>())
+    });
+
+    // 写入调用方复用的缓冲区
+    let mut buffer = Vec::with_capacity(data.len());
+    group.bench_function("filter_iter_into_buffer", |b| {
+        b.iter(|| {
+            buffer.clear();
+            buffer.extend(optimized::filter_and_transform_iter(black_box(&data)));
+            buffer.len()
+        })
+    });
+
+    group.bench_function("strings_collect_then_write", |b| {
+        let mut sink = String::with_capacity(16 * strings_data.len());
+        b.iter(|| {
+            sink.clear();
+            for s in optimized::process_strings(black_box(&strings_data)) {
+                sink.push_str(&s);
+            }
+            sink.len()
+        })
+    });
+
+    group.bench_function("strings_iter_write", |b| {
+        let mut sink = String::with_capacity(16 * strings_data.len());
+        b.iter(|| {
+            sink.clear();
+            for s in optimized::process_strings_iter(black_box(&strings_data)) {
+                sink.push_str(&s);
+            }
+            sink.len()
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_map_reduce(c: &mut Criterion) {
+    // 多线程只有在数据量较大时才有收益
+    let data = generate_test_data(1_000_000);
+    let chunk_size = mapreduce::default_chunk_size(data.len());
+
+    let mut group = c.benchmark_group("map_reduce");
+
+    group.bench_function("average_single_thread", |b| {
+        b.iter(|| optimized::calculate_average(black_box(&data)))
+    });
+
+    group.bench_function("average_map_reduce", |b| {
+        b.iter(|| mapreduce::calculate_average(black_box(&data), chunk_size))
+    });
+
+    group.bench_function("most_frequent_single_thread", |b| {
+        b.iter(|| optimized::find_most_frequent(black_box(&data)))
+    });
+
+    group.bench_function("most_frequent_map_reduce", |b| {
+        b.iter(|| mapreduce::find_most_frequent(black_box(&data), chunk_size))
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_calculate_average,
     bench_find_most_frequent,
+    bench_hash_algorithms,
+    bench_counting_sort,
     bench_filter_and_transform,
-    bench_process_strings
+    bench_process_strings,
+    bench_iterator_variants,
+    bench_map_reduce
 );
 criterion_main!(benches);
 
@@ -0,0 +1,20 @@
+//! 模糊测试：`filter_and_transform` 的两个版本输出必须一致
+//!
+//! 运行：cargo +nightly fuzz run filter_and_transform
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use performance_optimization_demo::{optimized, unoptimized};
+
+fuzz_target!(|numbers: Vec<i32>| {
+    // 两个版本在 x * 2 溢出时都会 panic（debug 断言），这类输入不参与比较
+    if numbers.iter().any(|&x| x > i32::MAX / 2) {
+        return;
+    }
+
+    let unopt = unoptimized::filter_and_transform(&numbers);
+    let opt = optimized::filter_and_transform(&numbers);
+    assert_eq!(unopt, opt);
+    assert!(opt.iter().all(|&x| x > 0 && x % 2 == 0));
+});
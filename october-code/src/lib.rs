@@ -4,8 +4,15 @@
 //! - 内存分配优化
 //! - 算法优化
 //! - CPU使用优化
+//! - 多线程 map-reduce
+//! - 分析结果缓存
+//! - 基准结果回归检测
+//! - 可替换的哈希算法和频率表预分配策略
+//! - 取值范围较小时用计数数组代替 HashMap
+//! - 返回迭代器的转换接口，避免中间 Vec
 
 /// 优化前的版本：处理数据并计算统计信息
+#[allow(clippy::ptr_arg)] // 故意保留 &Vec 参数作为反面示例
 pub mod unoptimized {
     use std::collections::HashMap;
 
@@ -14,6 +21,8 @@ pub mod unoptimized {
     /// 问题：
     /// - 多次遍历数据
     /// - 不必要的类型转换
+    /// - 使用 i32 累加，累加过程超出 i32 范围时溢出（debug 模式 panic，release 模式回绕）
+    /// - 空输入返回 NaN
     pub fn calculate_average(numbers: &Vec<i32>) -> f64 {
         let mut sum = 0;
         for num in numbers {
@@ -22,6 +31,30 @@ pub mod unoptimized {
         sum as f64 / numbers.len() as f64
     }
 
+    /// 求和（检查溢出版本）
+    ///
+    /// 累加过程中任意一步溢出 i32 时返回 `None`
+    pub fn sum_checked(numbers: &[i32]) -> Option<i32> {
+        numbers.iter().try_fold(0i32, |acc, &x| acc.checked_add(x))
+    }
+
+    /// 求和（饱和版本）
+    ///
+    /// 溢出时停留在 `i32::MAX` / `i32::MIN`，之后的加法从饱和值继续
+    pub fn sum_saturating(numbers: &[i32]) -> i32 {
+        numbers.iter().fold(0i32, |acc, &x| acc.saturating_add(x))
+    }
+
+    /// 计算平均值（检查溢出版本）
+    ///
+    /// 与 `calculate_average` 一样用 i32 累加，但在溢出或输入为空时返回 `None`
+    pub fn calculate_average_checked(numbers: &[i32]) -> Option<f64> {
+        if numbers.is_empty() {
+            return None;
+        }
+        sum_checked(numbers).map(|sum| sum as f64 / numbers.len() as f64)
+    }
+
     /// 查找出现频率最高的数字（未优化版本）
     /// 
     /// 问题：
@@ -84,13 +117,20 @@ pub mod unoptimized {
 
 /// 优化后的版本：性能优化实践
 pub mod optimized {
+    use std::collections::hash_map::RandomState;
     use std::collections::HashMap;
+    use std::hash::BuildHasher;
 
     /// 计算数据集的平均值（优化版本）
     /// 
     /// 优化点：
     /// - 单次遍历
     /// - 使用更高效的类型
+    ///
+    /// 溢出语义：
+    /// - 用 i64 累加，每项绝对值不超过 2^31，长度小于 2^32 时累加不会溢出
+    /// - 和的绝对值不超过 2^53 时结果精确，超过后按 f64 舍入
+    /// - 空输入返回 0.0
     pub fn calculate_average(numbers: &[i32]) -> f64 {
         if numbers.is_empty() {
             return 0.0;
@@ -104,13 +144,82 @@ pub mod optimized {
     /// 
     /// 优化点：
     /// - 单次遍历完成统计和查找
-    /// - 使用更高效的数据结构访问
+    /// - 取值范围较小时用计数数组代替 HashMap，见 `find_most_frequent_auto`
     pub fn find_most_frequent(numbers: &[i32]) -> i32 {
+        find_most_frequent_auto(numbers)
+    }
+
+    /// 查找出现频率最高的数字（HashMap 版本），按输入长度的一半预分配频率表
+    pub fn find_most_frequent_hashed(numbers: &[i32]) -> i32 {
+        find_most_frequent_with(numbers, RandomState::new(), ReserveStrategy::HalfLength)
+    }
+
+    /// 频率表的预分配策略
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ReserveStrategy {
+        /// 不预分配，随插入扩容
+        None,
+        /// 预分配输入长度的一半，不同数字很少时浪费内存
+        HalfLength,
+        /// 抽样估算不同数字的个数，按估算值预分配
+        Sampled,
+    }
+
+    impl ReserveStrategy {
+        /// 按策略计算频率表的初始容量
+        pub fn capacity(self, numbers: &[i32]) -> usize {
+            match self {
+                ReserveStrategy::None => 0,
+                ReserveStrategy::HalfLength => numbers.len() / 2,
+                ReserveStrategy::Sampled => estimate_cardinality(numbers),
+            }
+        }
+    }
+
+    /// 估算不同数字个数时的样本大小
+    pub const CARDINALITY_SAMPLE_SIZE: usize = 1024;
+
+    /// 抽样估算不同数字的个数
+    ///
+    /// - 把输入等分为 `CARDINALITY_SAMPLE_SIZE` 段，每段用乘法哈希选一个位置，
+    ///   样本位置互不重复，也不容易与数据的周期重合
+    /// - 样本中出现一次、两次的数字分别有 f1、f2 个时，估算值为 d + f1(f1-1) / (2(f2+1))（Chao1 估计量）
+    /// - 输入不超过样本大小时直接返回输入长度，结果不超过输入长度
+    pub fn estimate_cardinality(numbers: &[i32]) -> usize {
+        let len = numbers.len();
+        if len <= CARDINALITY_SAMPLE_SIZE {
+            return len;
+        }
+
+        let stride = len / CARDINALITY_SAMPLE_SIZE;
+        let mut sample: HashMap<i32, usize> = HashMap::with_capacity(CARDINALITY_SAMPLE_SIZE);
+        for i in 0..CARDINALITY_SAMPLE_SIZE {
+            let jitter = ((i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) as usize % stride;
+            *sample.entry(numbers[i * stride + jitter]).or_insert(0) += 1;
+        }
+
+        let distinct = sample.len();
+        let singletons = sample.values().filter(|&&c| c == 1).count();
+        let doubletons = sample.values().filter(|&&c| c == 2).count();
+        let unseen = singletons * singletons.saturating_sub(1) / (2 * (doubletons + 1));
+        (distinct + unseen).min(len)
+    }
+
+    /// 查找出现频率最高的数字，可指定哈希算法和频率表的预分配策略
+    ///
+    /// 结果与 `find_most_frequent` 相同：频率并列时返回最先达到最高频率的数字，空输入返回 0。
+    /// 可选的哈希算法见 `hashers` 模块。
+    pub fn find_most_frequent_with<S: BuildHasher>(
+        numbers: &[i32],
+        hash_builder: S,
+        reserve: ReserveStrategy,
+    ) -> i32 {
         if numbers.is_empty() {
             return 0;
         }
         
-        let mut frequency: HashMap<i32, usize> = HashMap::with_capacity(numbers.len() / 2);
+        let mut frequency: HashMap<i32, usize, S> =
+            HashMap::with_capacity_and_hasher(reserve.capacity(numbers), hash_builder);
         let mut max_count = 0;
         let mut most_frequent = numbers[0];
         
@@ -126,12 +235,78 @@ pub mod optimized {
         most_frequent
     }
 
+    /// `find_most_frequent_bounded` 允许的最大取值范围（计数数组长度）
+    pub const MAX_DENSE_RANGE: usize = 1 << 20;
+
+    /// 扫描一遍数据，返回 `(最小值, 最大值)`，空输入返回 `None`
+    pub fn probe_range(numbers: &[i32]) -> Option<(i32, i32)> {
+        let first = *numbers.first()?;
+        Some(
+            numbers
+                .iter()
+                .fold((first, first), |(min, max), &x| (min.min(x), max.max(x))),
+        )
+    }
+
+    /// 查找出现频率最高的数字（计数数组版本），所有值必须落在 `min..=max` 内
+    ///
+    /// 优化点：
+    /// - 用长度为 `max - min + 1` 的数组计数，下标运算代替哈希和探测
+    /// - 结果与 `find_most_frequent` 相同：频率并列时返回最先达到最高频率的数字，空输入返回 0
+    ///
+    /// 有值超出范围、`min > max` 或范围超过 `MAX_DENSE_RANGE` 时返回 `None`
+    pub fn find_most_frequent_bounded(numbers: &[i32], min: i32, max: i32) -> Option<i32> {
+        let range = max as i64 - min as i64 + 1;
+        if range <= 0 || range as usize > MAX_DENSE_RANGE {
+            return None;
+        }
+        if numbers.is_empty() {
+            return Some(0);
+        }
+
+        let mut counts = vec![0usize; range as usize];
+        let mut max_count = 0;
+        let mut most_frequent = numbers[0];
+        for &num in numbers {
+            if num < min || num > max {
+                return None;
+            }
+            let count = &mut counts[(num as i64 - min as i64) as usize];
+            *count += 1;
+            if *count > max_count {
+                max_count = *count;
+                most_frequent = num;
+            }
+        }
+        Some(most_frequent)
+    }
+
+    /// 查找出现频率最高的数字，先探测取值范围再选择实现
+    ///
+    /// 范围不超过 `MAX_DENSE_RANGE` 且不超过数据量的 4 倍时使用计数数组，
+    /// 否则计数数组太稀疏，退回 HashMap 版本。两种实现的结果相同。
+    pub fn find_most_frequent_auto(numbers: &[i32]) -> i32 {
+        let Some((min, max)) = probe_range(numbers) else {
+            return 0;
+        };
+        let range = (max as i64 - min as i64 + 1) as usize;
+        if range <= MAX_DENSE_RANGE && range <= numbers.len().saturating_mul(4) {
+            if let Some(most_frequent) = find_most_frequent_bounded(numbers, min, max) {
+                return most_frequent;
+            }
+        }
+        find_most_frequent_hashed(numbers)
+    }
+
     /// 过滤并转换数据（优化版本）
     /// 
     /// 优化点：
     /// - 单次遍历完成过滤和转换
     /// - 预分配Vec容量
     /// - 避免不必要的克隆
+    ///
+    /// 溢出语义：与未优化版本相同，`x > i32::MAX / 2` 时 `x * 2` 溢出
+    /// （debug 模式 panic，release 模式回绕）
     pub fn filter_and_transform(numbers: &[i32]) -> Vec<i32> {
         let capacity = numbers.len() / 2; // 预估容量
         let mut result = Vec::with_capacity(capacity);
@@ -146,6 +321,16 @@ pub mod optimized {
         result
     }
 
+    /// 过滤并转换数据（迭代器版本）
+    ///
+    /// 与 `filter_and_transform` 结果相同，但不分配 Vec：调用方可以直接求和、
+    /// 写入自己的缓冲区，或用 `take` 只取前几个结果。迭代器是惰性的，没有消费时不做任何计算。
+    ///
+    /// 溢出语义与 `filter_and_transform` 相同
+    pub fn filter_and_transform_iter(numbers: &[i32]) -> impl Iterator<Item = i32> + '_ {
+        numbers.iter().filter(|&&x| x > 0).map(|&x| x * 2)
+    }
+
     /// 处理大量数据（优化版本）
     /// 
     /// 优化点：
@@ -155,18 +340,31 @@ pub mod optimized {
     pub fn process_strings(data: &[i32]) -> Vec<String> {
         let mut result = Vec::with_capacity(data.len());
         for &value in data {
-            let mut s = String::with_capacity(15); // 预估容量 "Value: 1234567"
-            s.push_str("Value: ");
-            s.push_str(&value.to_string());
-            result.push(s);
+            result.push(format_value(value));
         }
         result
     }
 
-    /// 并行处理数据（使用rayon，需要添加依赖）
-    /// 
-    /// 注意：此函数需要添加 rayon = "1.8" 到 Cargo.toml
-    /// 这里仅作为示例，实际使用时取消注释并添加依赖
+    /// 处理大量数据（迭代器版本）
+    ///
+    /// 逐个产生与 `process_strings` 相同的字符串，省去存放全部结果的 Vec；
+    /// 适合边生成边写入文件或网络的场景，每个字符串在写出后即可释放。
+    /// 长度已知，调用方需要收集时可以一次分配好容量。
+    pub fn process_strings_iter(data: &[i32]) -> impl ExactSizeIterator<Item = String> + '_ {
+        data.iter().map(|&value| format_value(value))
+    }
+
+    fn format_value(value: i32) -> String {
+        let mut s = String::with_capacity(15); // 预估容量 "Value: 1234567"
+        s.push_str("Value: ");
+        s.push_str(&value.to_string());
+        s
+    }
+
+    // 并行处理数据（使用rayon，需要添加依赖）
+    // 
+    // 注意：此函数需要添加 rayon = "1.8" 到 Cargo.toml
+    // 这里仅作为示例，实际使用时取消注释并添加依赖
     /*
     use rayon::prelude::*;
     
@@ -179,6 +377,415 @@ pub mod optimized {
     */
 }
 
+/// 可用于 `optimized::find_most_frequent_with` 的哈希算法
+///
+/// std 默认的 SipHash 能抵御哈希洪水攻击，但对 i32 这样的短键较慢；
+/// 启用 `fxhash` / `ahash` feature 后可以换用更快的非加密哈希。
+pub mod hashers {
+    /// std 默认的 SipHash-1-3，每个频率表使用随机种子
+    pub use std::collections::hash_map::RandomState as SipHash;
+
+    /// rustc 使用的 FxHash，对整数键最快，但没有随机种子
+    #[cfg(feature = "fxhash")]
+    pub use rustc_hash::FxBuildHasher as FxHash;
+
+    /// AHash，利用 AES 指令加速，带随机种子
+    #[cfg(feature = "ahash")]
+    pub use ahash::RandomState as AHash;
+}
+
+/// 基于 std::thread::scope 的简易 map-reduce 框架（不依赖 rayon）
+pub mod mapreduce {
+    use std::collections::HashMap;
+    use std::thread;
+
+    /// 将 `data` 按 `chunk_size` 切块，每块在独立的作用域线程中执行 `map_fn`，
+    /// 再按块的原始顺序用 `reduce_fn` 合并结果
+    ///
+    /// - 每个块一个线程，`chunk_size` 应让块数接近 CPU 核数
+    /// - `chunk_size` 为 0 时按 1 处理
+    /// - 输入为空时返回 `None`
+    pub fn map_reduce<T, R, M, F>(data: &[T], chunk_size: usize, map_fn: M, reduce_fn: F) -> Option<R>
+    where
+        T: Sync,
+        R: Send,
+        M: Fn(&[T]) -> R + Sync,
+        F: Fn(R, R) -> R,
+    {
+        let map_fn = &map_fn;
+        thread::scope(|scope| {
+            let handles: Vec<_> = data
+                .chunks(chunk_size.max(1))
+                .map(|chunk| scope.spawn(move || map_fn(chunk)))
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("map 线程 panic"))
+                .reduce(&reduce_fn)
+        })
+    }
+
+    /// 按可用 CPU 核数估算块大小
+    pub fn default_chunk_size(len: usize) -> usize {
+        let workers = thread::available_parallelism().map_or(1, |n| n.get());
+        len.div_ceil(workers).max(1)
+    }
+
+    /// 并行计算平均值，语义与 `optimized::calculate_average` 相同（空输入返回 0.0）
+    pub fn calculate_average(numbers: &[i32], chunk_size: usize) -> f64 {
+        let total = map_reduce(
+            numbers,
+            chunk_size,
+            |chunk| chunk.iter().map(|&x| x as i64).sum::<i64>(),
+            |a, b| a + b,
+        );
+        match total {
+            Some(sum) => sum as f64 / numbers.len() as f64,
+            None => 0.0,
+        }
+    }
+
+    /// 并行统计每个数字出现的次数
+    pub fn count_frequencies(numbers: &[i32], chunk_size: usize) -> HashMap<i32, usize> {
+        map_reduce(
+            numbers,
+            chunk_size,
+            |chunk| {
+                let mut frequency = HashMap::new();
+                for &num in chunk {
+                    *frequency.entry(num).or_insert(0) += 1;
+                }
+                frequency
+            },
+            |mut a, mut b| {
+                // 把较小的表合并进较大的表
+                if a.len() < b.len() {
+                    std::mem::swap(&mut a, &mut b);
+                }
+                for (num, count) in b {
+                    *a.entry(num).or_insert(0) += count;
+                }
+                a
+            },
+        )
+        .unwrap_or_default()
+    }
+
+    /// 并行查找出现频率最高的数字；频率相同时返回较小的数字，空输入返回 0
+    pub fn find_most_frequent(numbers: &[i32], chunk_size: usize) -> i32 {
+        count_frequencies(numbers, chunk_size)
+            .into_iter()
+            .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))
+            .map_or(0, |(num, _)| num)
+    }
+}
+
+/// 分析结果缓存：相同内容的数据集重复分析时直接返回缓存结果
+pub mod cache {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashMap;
+    use std::hash::{Hash, Hasher};
+
+    use crate::optimized;
+
+    /// 默认最多缓存的数据集个数
+    pub const DEFAULT_CAPACITY: usize = 16;
+
+    /// 数据集的内容哈希（包含长度），用于定位缓存条目
+    ///
+    /// 哈希只用来分桶，命中时还会比较完整的数据，碰撞不会返回另一数据集的结果
+    pub fn content_hash(numbers: &[i32]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        numbers.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// 缓存命中统计
+    #[derive(Debug, Clone, Copy, Default, PartialEq)]
+    pub struct CacheStats {
+        pub hits: u64,
+        pub misses: u64,
+        /// 缓存的结果个数（平均值和频率表分别计数）
+        pub entries: usize,
+        /// 因超出容量被淘汰的数据集个数
+        pub evictions: u64,
+    }
+
+    impl CacheStats {
+        /// 命中率，尚无查询时为 0.0
+        pub fn hit_rate(&self) -> f64 {
+            let total = self.hits + self.misses;
+            if total == 0 {
+                0.0
+            } else {
+                self.hits as f64 / total as f64
+            }
+        }
+    }
+
+    /// 一个数据集的缓存结果，保存完整数据用于命中时校验
+    struct Entry {
+        data: Vec<i32>,
+        average: Option<f64>,
+        frequencies: Option<HashMap<i32, usize>>,
+        last_used: u64,
+    }
+
+    /// 分析结果缓存，最多保存 `capacity` 个数据集，超出时淘汰最久未使用的
+    pub struct AnalyticsCache {
+        entries: HashMap<u64, Entry>,
+        capacity: usize,
+        tick: u64,
+        hits: u64,
+        misses: u64,
+        evictions: u64,
+    }
+
+    impl Default for AnalyticsCache {
+        fn default() -> Self {
+            Self::with_capacity(DEFAULT_CAPACITY)
+        }
+    }
+
+    impl AnalyticsCache {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// 最多缓存 `capacity` 个数据集（至少 1 个）
+        pub fn with_capacity(capacity: usize) -> Self {
+            Self {
+                entries: HashMap::new(),
+                capacity: capacity.max(1),
+                tick: 0,
+                hits: 0,
+                misses: 0,
+                evictions: 0,
+            }
+        }
+
+        /// 找到（或新建）数据集的条目并标记为最近使用，返回条目的键；
+        /// 哈希相同但数据不同时替换旧条目
+        fn touch(&mut self, numbers: &[i32]) -> u64 {
+            let key = content_hash(numbers);
+            self.tick += 1;
+            let stale = self
+                .entries
+                .get(&key)
+                .is_some_and(|entry| entry.data != numbers);
+            if stale {
+                self.entries.remove(&key);
+            }
+            if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+                self.evict_least_recently_used();
+            }
+            let entry = self.entries.entry(key).or_insert_with(|| Entry {
+                data: numbers.to_vec(),
+                average: None,
+                frequencies: None,
+                last_used: 0,
+            });
+            entry.last_used = self.tick;
+            key
+        }
+
+        fn evict_least_recently_used(&mut self) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(&key, _)| key);
+            if let Some(key) = oldest {
+                self.entries.remove(&key);
+                self.evictions += 1;
+            }
+        }
+
+        fn record(&mut self, hit: bool) {
+            if hit {
+                self.hits += 1;
+            } else {
+                self.misses += 1;
+            }
+        }
+
+        /// 平均值，语义与 `optimized::calculate_average` 相同
+        pub fn average(&mut self, numbers: &[i32]) -> f64 {
+            let key = self.touch(numbers);
+            let entry = self.entries.get_mut(&key).expect("touch 之后条目一定存在");
+            let hit = entry.average.is_some();
+            let average = *entry
+                .average
+                .get_or_insert_with(|| optimized::calculate_average(numbers));
+            self.record(hit);
+            average
+        }
+
+        /// 频率表：数字 -> 出现次数
+        pub fn frequencies(&mut self, numbers: &[i32]) -> &HashMap<i32, usize> {
+            let key = self.touch(numbers);
+            let hit = self.entries[&key].frequencies.is_some();
+            self.record(hit);
+            let entry = self.entries.get_mut(&key).expect("touch 之后条目一定存在");
+            entry.frequencies.get_or_insert_with(|| {
+                let mut frequency = HashMap::with_capacity(numbers.len() / 2);
+                for &num in numbers {
+                    *frequency.entry(num).or_insert(0) += 1;
+                }
+                frequency
+            })
+        }
+
+        /// 出现频率最高的数字（基于缓存的频率表）；频率相同时返回较小的数字，空输入返回 0
+        pub fn most_frequent(&mut self, numbers: &[i32]) -> i32 {
+            self.frequencies(numbers)
+                .iter()
+                .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))
+                .map_or(0, |(&num, _)| num)
+        }
+
+        /// 移除某个数据集的全部缓存结果，返回是否存在缓存
+        pub fn invalidate(&mut self, numbers: &[i32]) -> bool {
+            let key = content_hash(numbers);
+            let matches = self
+                .entries
+                .get(&key)
+                .is_some_and(|entry| entry.data == numbers);
+            matches && self.entries.remove(&key).is_some()
+        }
+
+        /// 清空所有缓存结果（命中统计保留）
+        pub fn clear(&mut self) {
+            self.entries.clear();
+        }
+
+        pub fn stats(&self) -> CacheStats {
+            CacheStats {
+                hits: self.hits,
+                misses: self.misses,
+                entries: self
+                    .entries
+                    .values()
+                    .map(|entry| {
+                        entry.average.is_some() as usize + entry.frequencies.is_some() as usize
+                    })
+                    .sum(),
+                evictions: self.evictions,
+            }
+        }
+    }
+}
+
+/// 基准结果回归检测：与保存的基线比较每次迭代的耗时
+pub mod regression {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    use serde::{Deserialize, Serialize};
+
+    /// 单个测试每次迭代的平均耗时（纳秒）
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    pub struct Timing {
+        pub size: usize,
+        pub unoptimized_ns: f64,
+        pub optimized_ns: f64,
+    }
+
+    /// 基线文件内容：测试名 -> 耗时
+    #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+    pub struct Baseline {
+        pub timings: BTreeMap<String, Timing>,
+    }
+
+    impl Baseline {
+        /// 读取基线文件，文件不存在时返回 `Ok(None)`
+        pub fn load(path: &Path) -> io::Result<Option<Self>> {
+            match fs::read_to_string(path) {
+                Ok(content) => serde_json::from_str(&content)
+                    .map(Some)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e),
+            }
+        }
+
+        pub fn save(&self, path: &Path) -> io::Result<()> {
+            let json = serde_json::to_string_pretty(self)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            fs::write(path, json + "\n")
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Status {
+        /// 变化在阈值以内（或更快）
+        Ok,
+        /// 比基线慢超过阈值
+        Regressed,
+        /// 基线中没有该测试
+        Missing,
+        /// 数据量与基线不同，无法比较
+        SizeMismatch,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Comparison {
+        pub test: String,
+        pub baseline: Option<Timing>,
+        pub current: Timing,
+        /// 优化版本相对基线的变化比例，0.1 表示慢了 10%
+        pub optimized_change: f64,
+        /// 未优化版本相对基线的变化比例
+        pub unoptimized_change: f64,
+        pub status: Status,
+    }
+
+    fn change(baseline: f64, current: f64) -> f64 {
+        if baseline > 0.0 {
+            current / baseline - 1.0
+        } else {
+            0.0
+        }
+    }
+
+    /// 逐个测试比较当前结果与基线；任一版本慢于基线超过 `threshold`
+    /// （比例，0.1 即 10%）时判定为回归
+    pub fn compare(baseline: &Baseline, current: &Baseline, threshold: f64) -> Vec<Comparison> {
+        current
+            .timings
+            .iter()
+            .map(|(test, &current)| {
+                let base = baseline.timings.get(test).copied();
+                let (optimized_change, unoptimized_change, status) = match base {
+                    None => (0.0, 0.0, Status::Missing),
+                    Some(base) if base.size != current.size => (0.0, 0.0, Status::SizeMismatch),
+                    Some(base) => {
+                        let opt = change(base.optimized_ns, current.optimized_ns);
+                        let unopt = change(base.unoptimized_ns, current.unoptimized_ns);
+                        let status = if opt > threshold || unopt > threshold {
+                            Status::Regressed
+                        } else {
+                            Status::Ok
+                        };
+                        (opt, unopt, status)
+                    }
+                };
+                Comparison {
+                    test: test.clone(),
+                    baseline: base,
+                    current,
+                    optimized_change,
+                    unoptimized_change,
+                    status,
+                }
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -190,6 +797,33 @@ mod tests {
         assert_eq!(optimized::calculate_average(&data), 3.0);
     }
 
+    #[test]
+    fn test_average_extremes() {
+        let max = vec![i32::MAX; 4];
+        let min = vec![i32::MIN; 4];
+        assert_eq!(optimized::calculate_average(&max), i32::MAX as f64);
+        assert_eq!(optimized::calculate_average(&min), i32::MIN as f64);
+        assert_eq!(optimized::calculate_average(&[i32::MAX, i32::MIN]), -0.5);
+        assert_eq!(optimized::calculate_average(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_checked_and_saturating() {
+        assert_eq!(unoptimized::sum_checked(&[1, 2, 3]), Some(6));
+        assert_eq!(unoptimized::sum_checked(&[i32::MAX, 1]), None);
+        assert_eq!(unoptimized::sum_checked(&[i32::MIN, -1]), None);
+        // 中间结果溢出，即使最终和在范围内也返回 None
+        assert_eq!(unoptimized::sum_checked(&[i32::MAX, 1, -1]), None);
+
+        assert_eq!(unoptimized::sum_saturating(&[i32::MAX, 1]), i32::MAX);
+        assert_eq!(unoptimized::sum_saturating(&[i32::MIN, -1]), i32::MIN);
+        assert_eq!(unoptimized::sum_saturating(&[i32::MAX, 1, -1]), i32::MAX - 1);
+
+        assert_eq!(unoptimized::calculate_average_checked(&[1, 2, 3, 4, 5]), Some(3.0));
+        assert_eq!(unoptimized::calculate_average_checked(&[]), None);
+        assert_eq!(unoptimized::calculate_average_checked(&[i32::MAX, i32::MAX]), None);
+    }
+
     #[test]
     fn test_most_frequent() {
         let data = vec![1, 2, 2, 3, 3, 3, 4];
@@ -197,6 +831,173 @@ mod tests {
         assert_eq!(optimized::find_most_frequent(&data), 3);
     }
 
+    #[test]
+    fn test_most_frequent_with_hashers_and_reserve() {
+        use optimized::ReserveStrategy;
+
+        let data = vec![1, 2, 2, 3, 3, 3, 4];
+        for reserve in [ReserveStrategy::None, ReserveStrategy::HalfLength, ReserveStrategy::Sampled] {
+            assert_eq!(optimized::find_most_frequent_with(&data, hashers::SipHash::new(), reserve), 3);
+            #[cfg(feature = "fxhash")]
+            assert_eq!(optimized::find_most_frequent_with(&data, hashers::FxHash, reserve), 3);
+            #[cfg(feature = "ahash")]
+            assert_eq!(optimized::find_most_frequent_with(&data, hashers::AHash::new(), reserve), 3);
+        }
+        assert_eq!(optimized::find_most_frequent_with(&[], hashers::SipHash::new(), ReserveStrategy::Sampled), 0);
+    }
+
+    #[test]
+    fn test_most_frequent_bounded() {
+        let data = vec![1, 2, 2, 3, 3, 3, 4];
+        assert_eq!(optimized::find_most_frequent_bounded(&data, 1, 4), Some(3));
+        assert_eq!(optimized::find_most_frequent_bounded(&data, -1000, 1000), Some(3));
+        assert_eq!(optimized::find_most_frequent_bounded(&data, 2, 4), None);
+        assert_eq!(optimized::find_most_frequent_bounded(&data, 4, 1), None);
+        assert_eq!(optimized::find_most_frequent_bounded(&data, i32::MIN, i32::MAX), None);
+        assert_eq!(optimized::find_most_frequent_bounded(&[], 0, 10), Some(0));
+        assert_eq!(
+            optimized::find_most_frequent_bounded(&[i32::MAX, i32::MAX - 1, i32::MAX], i32::MAX - 1, i32::MAX),
+            Some(i32::MAX)
+        );
+
+        assert_eq!(optimized::probe_range(&data), Some((1, 4)));
+        assert_eq!(optimized::probe_range(&[]), None);
+
+        // 频率并列时与 HashMap 版本一样返回最先达到最高频率的数字
+        let tied = vec![5, 1, 1, 5];
+        assert_eq!(optimized::find_most_frequent_auto(&tied), optimized::find_most_frequent_hashed(&tied));
+        assert_eq!(optimized::find_most_frequent(&tied), 1);
+        // 范围过大时退回 HashMap 版本
+        assert_eq!(optimized::find_most_frequent_auto(&[i32::MIN, 0, 0, i32::MAX]), 0);
+        assert_eq!(optimized::find_most_frequent_auto(&[]), 0);
+    }
+
+    #[test]
+    fn test_estimate_cardinality() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let bounded: Vec<i32> = (0..100_000).map(|_| rng.gen_range(-1000..=1000)).collect();
+        let estimate = optimized::estimate_cardinality(&bounded);
+        assert!((1600..=2400).contains(&estimate), "估算值 {} 偏离 2001 过多", estimate);
+
+        // 全部不同时估算值不超过输入长度
+        let distinct: Vec<i32> = (0..100_000).collect();
+        assert_eq!(optimized::estimate_cardinality(&distinct), 100_000);
+        assert_eq!(optimized::estimate_cardinality(&[7; 5000]), 1);
+        // 小输入不抽样
+        assert_eq!(optimized::estimate_cardinality(&[1, 1, 1]), 3);
+    }
+
+    #[test]
+    fn test_map_reduce() {
+        let data: Vec<i32> = (1..=10).collect();
+        let sum = mapreduce::map_reduce(&data, 3, |c| c.iter().sum::<i32>(), |a, b| a + b);
+        assert_eq!(sum, Some(55));
+        // 按块顺序合并，非交换的 reduce 也能得到正确结果
+        let joined = mapreduce::map_reduce(&data, 4, |c| format!("{:?}", c), |a, b| a + &b);
+        assert_eq!(joined.as_deref(), Some("[1, 2, 3, 4][5, 6, 7, 8][9, 10]"));
+        assert_eq!(mapreduce::map_reduce(&[] as &[i32], 4, |c| c.len(), |a, b| a + b), None);
+        assert_eq!(mapreduce::map_reduce(&data, 0, |c| c.len(), |a, b| a + b), Some(10));
+    }
+
+    #[test]
+    fn test_mapreduce_analytics() {
+        let data = vec![1, 2, 2, 3, 3, 3, 4];
+        assert_eq!(mapreduce::calculate_average(&data, 2), optimized::calculate_average(&data));
+        assert_eq!(mapreduce::find_most_frequent(&data, 2), 3);
+        assert_eq!(mapreduce::count_frequencies(&data, 3)[&3], 3);
+        assert_eq!(mapreduce::calculate_average(&[], 2), 0.0);
+        assert_eq!(mapreduce::find_most_frequent(&[1, 2, 2, 1], 1), 1);
+    }
+
+    #[test]
+    fn test_analytics_cache() {
+        let data = vec![1, 2, 2, 3, 3, 3, 4];
+        let mut cache = cache::AnalyticsCache::new();
+
+        assert_eq!(cache.average(&data), optimized::calculate_average(&data));
+        assert_eq!(cache.average(&data), optimized::calculate_average(&data));
+        assert_eq!(cache.most_frequent(&data), 3);
+        assert_eq!(cache.frequencies(&data)[&2], 2);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 2, 2));
+        assert_eq!(stats.hit_rate(), 0.5);
+
+        // 内容不同的数据集不会命中
+        cache.average(&[1, 2, 3]);
+        assert_eq!(cache.stats().misses, 3);
+
+        assert!(cache.invalidate(&data));
+        assert!(!cache.invalidate(&data));
+        cache.average(&data);
+        assert_eq!(cache.stats().misses, 4);
+
+        cache.clear();
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn test_analytics_cache_evicts_least_recently_used() {
+        let (a, b, c) = (vec![1, 1, 2], vec![3, 4, 4], vec![5, 6, 6, 6]);
+        let mut cache = cache::AnalyticsCache::with_capacity(2);
+
+        cache.average(&a);
+        cache.average(&b);
+        // 访问 a 后，b 成为最久未使用的数据集
+        cache.average(&a);
+        cache.average(&c);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions), (1, 3, 1));
+
+        cache.average(&a);
+        assert_eq!(cache.stats().hits, 2);
+        assert!(!cache.invalidate(&b));
+        assert_eq!(cache.most_frequent(&c), 6);
+        assert_eq!(cache.stats().entries, 3);
+    }
+
+    #[test]
+    fn test_regression_compare() {
+        use regression::{Baseline, Status, Timing};
+
+        let timing = |size, unoptimized_ns, optimized_ns| Timing {
+            size,
+            unoptimized_ns,
+            optimized_ns,
+        };
+        let mut baseline = Baseline::default();
+        baseline.timings.insert("avg".into(), timing(100, 100.0, 50.0));
+        baseline.timings.insert("freq".into(), timing(100, 100.0, 50.0));
+        baseline.timings.insert("filter".into(), timing(100, 100.0, 50.0));
+
+        let mut current = Baseline::default();
+        current.timings.insert("avg".into(), timing(100, 105.0, 40.0));
+        current.timings.insert("freq".into(), timing(100, 100.0, 60.0));
+        current.timings.insert("filter".into(), timing(200, 100.0, 50.0));
+        current.timings.insert("strings".into(), timing(100, 100.0, 50.0));
+
+        let statuses: Vec<(String, Status)> = regression::compare(&baseline, &current, 0.1)
+            .into_iter()
+            .map(|c| (c.test, c.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("avg".to_string(), Status::Ok),
+                ("filter".to_string(), Status::SizeMismatch),
+                ("freq".to_string(), Status::Regressed),
+                ("strings".to_string(), Status::Missing),
+            ]
+        );
+
+        let freq = &regression::compare(&baseline, &current, 0.1)[2];
+        assert!((freq.optimized_change - 0.2).abs() < 1e-9);
+        // 阈值放宽后不再判定为回归
+        assert_eq!(regression::compare(&baseline, &current, 0.25)[2].status, Status::Ok);
+    }
+
     #[test]
     fn test_filter_and_transform() {
         let data = vec![-1, 2, -3, 4, 5];
@@ -205,5 +1006,19 @@ mod tests {
         assert_eq!(unopt, opt);
         assert_eq!(opt, vec![4, 8, 10]);
     }
+
+    #[test]
+    fn test_iterator_variants() {
+        let data = vec![-1, 2, -3, 4, 5];
+        let streamed: Vec<i32> = optimized::filter_and_transform_iter(&data).collect();
+        assert_eq!(streamed, optimized::filter_and_transform(&data));
+        assert_eq!(optimized::filter_and_transform_iter(&data).sum::<i32>(), 22);
+        assert_eq!(optimized::filter_and_transform_iter(&data).take(1).collect::<Vec<_>>(), vec![4]);
+
+        let strings: Vec<String> = optimized::process_strings_iter(&data).collect();
+        assert_eq!(strings, optimized::process_strings(&data));
+        assert_eq!(optimized::process_strings_iter(&data).len(), data.len());
+        assert_eq!(optimized::process_strings_iter(&[]).next(), None);
+    }
 }
 
@@ -1,10 +1,126 @@
 //! 性能优化示例项目 - 主程序
-//! 
+//!
 //! 运行此程序可以查看优化前后的性能对比
 
+use clap::{Parser, ValueEnum};
+use performance_optimization_demo::cache::AnalyticsCache;
+use performance_optimization_demo::regression::{self, Baseline, Status, Timing};
 use performance_optimization_demo::{optimized, unoptimized};
 use rand::Rng;
-use std::time::Instant;
+use std::fs::OpenOptions;
+use std::hint::black_box;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 性能对比基准测试
+#[derive(Parser)]
+#[command(name = "performance-optimization-demo")]
+#[command(about = "对比优化前后版本的性能")]
+struct Cli {
+    /// 测试数据量（字符串测试使用其十分之一），可带 K/M 后缀，如 `1M`
+    #[arg(long, default_value = "100000", value_parser = parse_size)]
+    size: usize,
+
+    /// 每个版本的迭代次数
+    #[arg(long, default_value_t = 100)]
+    iterations: u32,
+
+    /// 要运行的测试，逗号分隔
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "avg,freq,filter,strings,cache"
+    )]
+    tests: Vec<BenchTest>,
+
+    /// 将结果追加到 CSV 文件，便于跟踪性能变化
+    #[arg(long)]
+    csv: Option<PathBuf>,
+
+    /// 把结果记录到基准测试数据库（SQLite），用 `benchdb report` 查看趋势
+    #[arg(long, env = "BENCHDB")]
+    benchdb: Option<PathBuf>,
+
+    /// 与基线 JSON 文件比较，出现回归时以非零状态码退出
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+
+    /// 判定为回归的变慢比例（百分比）
+    #[arg(long, default_value_t = 10.0)]
+    threshold: f64,
+
+    /// 比较后用本次结果覆盖基线（回归不再导致失败）
+    #[arg(long, requires = "baseline")]
+    update_baseline: bool,
+}
+
+/// 解析数据量，支持 `K`（千）和 `M`（百万）后缀
+fn parse_size(value: &str) -> Result<usize, String> {
+    let value = value.trim();
+    let (digits, multiplier) = match value.char_indices().last() {
+        Some((index, 'k' | 'K')) => (&value[..index], 1_000),
+        Some((index, 'm' | 'M')) => (&value[..index], 1_000_000),
+        _ => (value, 1),
+    };
+    digits
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| format!("无效的数据量: {}（示例: 50000、100K、1M）", value))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BenchTest {
+    /// 计算平均值
+    Avg,
+    /// 查找最频繁数字
+    Freq,
+    /// 过滤和转换
+    Filter,
+    /// 处理字符串
+    Strings,
+    /// 分析结果缓存（对比无缓存与有缓存）
+    Cache,
+}
+
+impl BenchTest {
+    fn name(self) -> &'static str {
+        match self {
+            BenchTest::Avg => "avg",
+            BenchTest::Freq => "freq",
+            BenchTest::Filter => "filter",
+            BenchTest::Strings => "strings",
+            BenchTest::Cache => "cache",
+        }
+    }
+}
+
+/// 单个测试的结果：`unoptimized` 为对照版本，`optimized` 为优化版本
+struct BenchResult {
+    test: BenchTest,
+    data_size: usize,
+    iterations: u32,
+    unoptimized: Duration,
+    optimized: Duration,
+}
+
+impl BenchResult {
+    fn speedup(&self) -> f64 {
+        self.unoptimized.as_secs_f64() / self.optimized.as_secs_f64()
+    }
+
+    /// 每次迭代的平均耗时，不同迭代次数的运行之间可以直接比较
+    fn timing(&self) -> Timing {
+        let per_iter = |d: Duration| d.as_secs_f64() * 1e9 / self.iterations.max(1) as f64;
+        Timing {
+            size: self.data_size,
+            unoptimized_ns: per_iter(self.unoptimized),
+            optimized_ns: per_iter(self.optimized),
+        }
+    }
+}
 
 fn generate_test_data(size: usize) -> Vec<i32> {
     let mut rng = rand::thread_rng();
@@ -13,90 +129,268 @@ fn generate_test_data(size: usize) -> Vec<i32> {
         .collect()
 }
 
-fn main() {
-    println!("性能优化示例项目");
-    println!("==================\n");
-
-    let data = generate_test_data(100000);
-    let iterations = 100;
-
-    // 测试1: 计算平均值
-    println!("测试1: 计算平均值 (数据量: {}, 迭代次数: {})", data.len(), iterations);
-    
+/// 结果经过 `black_box`，避免 release 模式下计算被整体优化掉
+fn time_iterations<R, F: FnMut() -> R>(iterations: u32, mut f: F) -> Duration {
     let start = Instant::now();
     for _ in 0..iterations {
-        let _ = unoptimized::calculate_average(&data);
+        black_box(f());
     }
-    let unopt_time = start.elapsed();
-    
-    let start = Instant::now();
-    for _ in 0..iterations {
-        let _ = optimized::calculate_average(&data);
+    start.elapsed()
+}
+
+fn run_test(test: BenchTest, data: &[i32], iterations: u32) -> BenchResult {
+    let vec_data = data.to_vec();
+    let (data_size, unopt_time, opt_time) = match test {
+        BenchTest::Avg => (
+            data.len(),
+            time_iterations(iterations, || unoptimized::calculate_average(black_box(&vec_data))),
+            time_iterations(iterations, || optimized::calculate_average(black_box(data))),
+        ),
+        BenchTest::Freq => (
+            data.len(),
+            time_iterations(iterations, || unoptimized::find_most_frequent(black_box(&vec_data))),
+            time_iterations(iterations, || optimized::find_most_frequent(black_box(data))),
+        ),
+        BenchTest::Filter => (
+            data.len(),
+            time_iterations(iterations, || unoptimized::filter_and_transform(black_box(&vec_data))),
+            time_iterations(iterations, || optimized::filter_and_transform(black_box(data))),
+        ),
+        BenchTest::Strings => {
+            let small_data: Vec<i32> = (0..(data.len() / 10) as i32).collect();
+            (
+                small_data.len(),
+                time_iterations(iterations, || unoptimized::process_strings(black_box(&small_data))),
+                time_iterations(iterations, || optimized::process_strings(black_box(&small_data))),
+            )
+        }
+        BenchTest::Cache => {
+            // 两个数据集交替分析
+            let other_data = generate_test_data(data.len());
+            let datasets = [data, other_data.as_slice()];
+            let mut round = 0;
+            let uncached = time_iterations(iterations, || {
+                let dataset = datasets[round % 2];
+                round += 1;
+                (
+                    optimized::calculate_average(dataset),
+                    optimized::find_most_frequent(dataset),
+                )
+            });
+
+            let mut cache = AnalyticsCache::new();
+            let mut round = 0;
+            let cached = time_iterations(iterations, || {
+                let dataset = datasets[round % 2];
+                round += 1;
+                (cache.average(dataset), cache.most_frequent(dataset))
+            });
+
+            let stats = cache.stats();
+            println!(
+                "缓存统计: 命中 {} 次, 未命中 {} 次, 命中率 {:.1}%, 缓存条目 {}, 淘汰 {} 个数据集",
+                stats.hits,
+                stats.misses,
+                stats.hit_rate() * 100.0,
+                stats.entries,
+                stats.evictions
+            );
+
+            // 数据被修改后按内容查找不会命中旧结果；原数据集失效后重新计算
+            let mut updated = data.to_vec();
+            if let Some(first) = updated.first_mut() {
+                *first = first.wrapping_add(1);
+            }
+            let misses = cache.stats().misses;
+            let updated_average = cache.average(&updated);
+            println!(
+                "修改数据后平均值 {:.3}（未命中 +{}），失效原数据集: {}",
+                updated_average,
+                cache.stats().misses - misses,
+                cache.invalidate(data)
+            );
+            let misses = cache.stats().misses;
+            cache.average(data);
+            println!("失效后再次计算原数据集（未命中 +{}）", cache.stats().misses - misses);
+            (data.len(), uncached, cached)
+        }
+    };
+
+    BenchResult {
+        test,
+        data_size,
+        iterations,
+        unoptimized: unopt_time,
+        optimized: opt_time,
     }
-    let opt_time = start.elapsed();
-    
-    println!("  未优化版本: {:?}", unopt_time);
-    println!("  优化版本:   {:?}", opt_time);
-    println!("  性能提升:   {:.2}x\n", unopt_time.as_secs_f64() / opt_time.as_secs_f64());
-
-    // 测试2: 查找最频繁数字
-    println!("测试2: 查找最频繁数字 (数据量: {}, 迭代次数: {})", data.len(), iterations);
-    
-    let start = Instant::now();
-    for _ in 0..iterations {
-        let _ = unoptimized::find_most_frequent(&data);
+}
+
+fn print_table(results: &[BenchResult]) {
+    println!(
+        "{:<8} {:>10} {:>8} {:>14} {:>14} {:>8}",
+        "test", "size", "iters", "unoptimized", "optimized", "speedup"
+    );
+    for r in results {
+        println!(
+            "{:<8} {:>10} {:>8} {:>12.3}ms {:>12.3}ms {:>7.2}x",
+            r.test.name(),
+            r.data_size,
+            r.iterations,
+            r.unoptimized.as_secs_f64() * 1000.0,
+            r.optimized.as_secs_f64() * 1000.0,
+            r.speedup()
+        );
     }
-    let unopt_time = start.elapsed();
-    
-    let start = Instant::now();
-    for _ in 0..iterations {
-        let _ = optimized::find_most_frequent(&data);
+}
+
+/// 追加写入 CSV，文件不存在时先写表头
+fn write_csv(path: &Path, results: &[BenchResult]) -> io::Result<()> {
+    let new_file = !path.exists();
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if new_file {
+        writeln!(file, "timestamp,test,size,iterations,unoptimized_ms,optimized_ms,speedup")?;
     }
-    let opt_time = start.elapsed();
-    
-    println!("  未优化版本: {:?}", unopt_time);
-    println!("  优化版本:   {:?}", opt_time);
-    println!("  性能提升:   {:.2}x\n", unopt_time.as_secs_f64() / opt_time.as_secs_f64());
-
-    // 测试3: 过滤和转换
-    println!("测试3: 过滤和转换 (数据量: {}, 迭代次数: {})", data.len(), iterations);
-    
-    let start = Instant::now();
-    for _ in 0..iterations {
-        let _ = unoptimized::filter_and_transform(&data);
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    for r in results {
+        writeln!(
+            file,
+            "{},{},{},{},{:.3},{:.3},{:.3}",
+            timestamp,
+            r.test.name(),
+            r.data_size,
+            r.iterations,
+            r.unoptimized.as_secs_f64() * 1000.0,
+            r.optimized.as_secs_f64() * 1000.0,
+            r.speedup()
+        )?;
     }
-    let unopt_time = start.elapsed();
-    
-    let start = Instant::now();
-    for _ in 0..iterations {
-        let _ = optimized::filter_and_transform(&data);
+    Ok(())
+}
+
+/// 把每个测试的两个版本分别记为 `<test>/unoptimized` 和 `<test>/optimized`
+fn record_to_benchdb(path: &Path, results: &[BenchResult]) -> benchdb::Result<i64> {
+    let mut run = benchdb::RunRecord::new("october-code");
+    for r in results {
+        let iterations = r.iterations.max(1);
+        let params = format!("size={}", r.data_size);
+        for (version, total) in [("unoptimized", r.unoptimized), ("optimized", r.optimized)] {
+            run.push(
+                benchdb::Measurement::new(format!("{}/{}", r.test.name(), version), total / iterations, iterations as u64)
+                    .with_params(params.clone()),
+            );
+        }
     }
-    let opt_time = start.elapsed();
-    
-    println!("  未优化版本: {:?}", unopt_time);
-    println!("  优化版本:   {:?}", opt_time);
-    println!("  性能提升:   {:.2}x\n", unopt_time.as_secs_f64() / opt_time.as_secs_f64());
-
-    // 测试4: 处理字符串
-    let small_data: Vec<i32> = (0..10000).collect();
-    println!("测试4: 处理字符串 (数据量: {}, 迭代次数: {})", small_data.len(), iterations);
-    
-    let start = Instant::now();
-    for _ in 0..iterations {
-        let _ = unoptimized::process_strings(&small_data);
+    benchdb::BenchDb::open(path)?.record(&run)
+}
+
+fn format_change(change: f64) -> String {
+    format!("{:+.1}%", change * 100.0)
+}
+
+/// 与基线比较并打印结果，返回是否存在回归
+fn check_baseline(
+    path: &Path,
+    results: &[BenchResult],
+    threshold_percent: f64,
+    update: bool,
+) -> io::Result<bool> {
+    let current = Baseline {
+        timings: results
+            .iter()
+            .map(|r| (r.test.name().to_string(), r.timing()))
+            .collect(),
+    };
+
+    let regressed = match Baseline::load(path)? {
+        Some(baseline) => {
+            println!("\n与基线比较: {} (阈值 {}%)", path.display(), threshold_percent);
+            println!("{:<8} {:>12} {:>12}  status", "test", "unoptimized", "optimized");
+            let comparisons = regression::compare(&baseline, &current, threshold_percent / 100.0);
+            for c in &comparisons {
+                let (unopt, opt) = match c.status {
+                    Status::Ok | Status::Regressed => {
+                        (format_change(c.unoptimized_change), format_change(c.optimized_change))
+                    }
+                    _ => ("-".to_string(), "-".to_string()),
+                };
+                let status = match c.status {
+                    Status::Ok => "ok",
+                    Status::Regressed => "REGRESSION",
+                    Status::Missing => "基线中不存在",
+                    Status::SizeMismatch => "数据量不同，跳过",
+                };
+                println!("{:<8} {:>12} {:>12}  {}", c.test, unopt, opt, status);
+            }
+            comparisons.iter().any(|c| c.status == Status::Regressed)
+        }
+        None => {
+            println!("\n基线文件不存在: {}", path.display());
+            false
+        }
+    };
+
+    if update {
+        // 保留本次未运行的测试的基线
+        let mut merged = Baseline::load(path)?.unwrap_or_default();
+        merged.timings.extend(current.timings);
+        merged.save(path)?;
+        println!("基线已更新: {}", path.display());
     }
-    let unopt_time = start.elapsed();
-    
-    let start = Instant::now();
-    for _ in 0..iterations {
-        let _ = optimized::process_strings(&small_data);
+    Ok(regressed)
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    println!("性能优化示例项目");
+    println!("==================\n");
+
+    let data = generate_test_data(cli.size);
+    let results: Vec<BenchResult> = cli
+        .tests
+        .iter()
+        .map(|&test| run_test(test, &data, cli.iterations))
+        .collect();
+
+    println!();
+    print_table(&results);
+
+    if let Some(path) = &cli.csv {
+        match write_csv(path, &results) {
+            Ok(()) => println!("\n结果已追加到: {}", path.display()),
+            Err(e) => {
+                eprintln!("写入 CSV 失败: {}", e);
+                std::process::exit(1);
+            }
+        }
     }
-    let opt_time = start.elapsed();
-    
-    println!("  未优化版本: {:?}", unopt_time);
-    println!("  优化版本:   {:?}", opt_time);
-    println!("  性能提升:   {:.2}x\n", unopt_time.as_secs_f64() / opt_time.as_secs_f64());
 
-    println!("提示: 运行 'cargo bench' 进行更详细的基准测试");
-}
+    if let Some(path) = &cli.benchdb {
+        match record_to_benchdb(path, &results) {
+            Ok(run_id) => println!("\n结果已记录到 {}（运行 #{}）", path.display(), run_id),
+            Err(e) => {
+                eprintln!("写入基准测试数据库失败: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(path) = &cli.baseline {
+        match check_baseline(path, &results, cli.threshold, cli.update_baseline) {
+            Ok(true) if !cli.update_baseline => {
+                eprintln!("\n检测到性能回归（超过 {}%）", cli.threshold);
+                std::process::exit(1);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("处理基线文件失败: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 
+    println!("\n提示: 运行 'cargo bench' 进行更详细的基准测试");
+}
@@ -0,0 +1,198 @@
+//! 属性测试：验证优化版本与未优化版本在任意输入下行为一致
+
+use std::collections::HashMap;
+
+use performance_optimization_demo::{optimized, unoptimized};
+use proptest::prelude::*;
+
+/// 混入空值、边界值的 i32 生成策略
+fn edge_i32() -> impl Strategy<Value = i32> {
+    prop_oneof![
+        Just(i32::MIN),
+        Just(i32::MAX),
+        Just(-1),
+        Just(0),
+        Just(1),
+        any::<i32>(),
+    ]
+}
+
+/// 乘 2 不会溢出的取值（`filter_and_transform` 的两个版本都会在溢出时 panic）
+fn doublable_i32() -> impl Strategy<Value = i32> {
+    prop_oneof![
+        Just(i32::MIN),
+        Just(i32::MAX / 2),
+        Just(-1),
+        Just(0),
+        Just(1),
+        i32::MIN..=i32::MAX / 2,
+    ]
+}
+
+/// 逐项累加不会溢出 i32 的数据：大量普通值，或少量边界值
+fn summable_vec() -> impl Strategy<Value = Vec<i32>> {
+    prop_oneof![
+        prop::collection::vec(-10_000_000..=10_000_000, 1..200),
+        prop::collection::vec(edge_i32(), 1..4).prop_filter("i32 prefix sum overflows", |v| {
+            v.iter().try_fold(0i32, |acc, &x| acc.checked_add(x)).is_some()
+        }),
+    ]
+}
+
+fn count_of(numbers: &[i32], value: i32) -> usize {
+    numbers.iter().filter(|&&x| x == value).count()
+}
+
+fn max_count(numbers: &[i32]) -> usize {
+    let mut frequency: HashMap<i32, usize> = HashMap::new();
+    for &num in numbers {
+        *frequency.entry(num).or_insert(0) += 1;
+    }
+    frequency.into_values().max().unwrap_or(0)
+}
+
+proptest! {
+    #[test]
+    fn average_matches(numbers in summable_vec()) {
+        // 未优化版本用 i32 累加，只比较累加过程不溢出的输入
+        let unopt = unoptimized::calculate_average(&numbers);
+        let opt = optimized::calculate_average(&numbers);
+        prop_assert_eq!(unopt, opt);
+    }
+
+    #[test]
+    fn optimized_average_is_exact_for_extremes(numbers in prop::collection::vec(edge_i32(), 0..200)) {
+        // 优化版本用 i64 累加，任意 i32 输入都不会溢出
+        let avg = optimized::calculate_average(&numbers);
+        if numbers.is_empty() {
+            prop_assert_eq!(avg, 0.0);
+        } else {
+            let sum: i128 = numbers.iter().map(|&x| x as i128).sum();
+            prop_assert_eq!(avg, sum as f64 / numbers.len() as f64);
+            prop_assert!(avg >= i32::MIN as f64 && avg <= i32::MAX as f64);
+        }
+    }
+
+    #[test]
+    fn most_frequent_has_max_count(numbers in prop::collection::vec(edge_i32(), 1..200)) {
+        // 频率并列时两个版本可能返回不同的数字，但出现次数必须都是最大值
+        let expected = max_count(&numbers);
+        let unopt = unoptimized::find_most_frequent(&numbers);
+        let opt = optimized::find_most_frequent(&numbers);
+        prop_assert_eq!(count_of(&numbers, unopt), expected);
+        prop_assert_eq!(count_of(&numbers, opt), expected);
+    }
+
+    #[test]
+    fn filter_and_transform_matches(numbers in prop::collection::vec(doublable_i32(), 0..200)) {
+        let unopt = unoptimized::filter_and_transform(&numbers);
+        let opt = optimized::filter_and_transform(&numbers);
+        prop_assert_eq!(&unopt, &opt);
+        prop_assert_eq!(opt.len(), numbers.iter().filter(|&&x| x > 0).count());
+    }
+
+    #[test]
+    fn process_strings_matches(numbers in prop::collection::vec(edge_i32(), 0..200)) {
+        prop_assert_eq!(
+            unoptimized::process_strings(&numbers),
+            optimized::process_strings(&numbers)
+        );
+    }
+}
+
+#[test]
+fn empty_inputs() {
+    let empty: Vec<i32> = Vec::new();
+    // 未优化版本对空输入返回 NaN，优化版本返回 0.0
+    assert!(unoptimized::calculate_average(&empty).is_nan());
+    assert_eq!(optimized::calculate_average(&empty), 0.0);
+    assert_eq!(unoptimized::find_most_frequent(&empty), 0);
+    assert_eq!(optimized::find_most_frequent(&empty), 0);
+    assert!(unoptimized::filter_and_transform(&empty).is_empty());
+    assert!(optimized::filter_and_transform(&empty).is_empty());
+    assert!(unoptimized::process_strings(&empty).is_empty());
+    assert!(optimized::process_strings(&empty).is_empty());
+}
+
+proptest! {
+    #[test]
+    fn checked_average_matches_optimized(numbers in prop::collection::vec(edge_i32(), 0..200)) {
+        // 不溢出时检查版本与优化版本一致，溢出时返回 None
+        let exact: i64 = numbers.iter().map(|&x| x as i64).sum();
+        let prefix_fits = unoptimized::sum_checked(&numbers).is_some();
+        match unoptimized::calculate_average_checked(&numbers) {
+            Some(avg) => prop_assert_eq!(avg, optimized::calculate_average(&numbers)),
+            None => prop_assert!(numbers.is_empty() || !prefix_fits),
+        }
+        if prefix_fits {
+            prop_assert_eq!(unoptimized::sum_saturating(&numbers) as i64, exact);
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn mapreduce_matches_single_threaded(
+        numbers in prop::collection::vec(edge_i32(), 0..300),
+        chunk_size in 0usize..64,
+    ) {
+        use performance_optimization_demo::mapreduce;
+
+        prop_assert_eq!(
+            mapreduce::calculate_average(&numbers, chunk_size),
+            optimized::calculate_average(&numbers)
+        );
+        let most = mapreduce::find_most_frequent(&numbers, chunk_size);
+        if numbers.is_empty() {
+            prop_assert_eq!(most, 0);
+        } else {
+            prop_assert_eq!(count_of(&numbers, most), max_count(&numbers));
+        }
+        let frequency = mapreduce::count_frequencies(&numbers, chunk_size);
+        prop_assert_eq!(frequency.values().sum::<usize>(), numbers.len());
+    }
+}
+
+proptest! {
+    #[test]
+    fn most_frequent_with_matches_default(numbers in prop::collection::vec(-50i32..50, 0..2000)) {
+        use performance_optimization_demo::hashers;
+        use performance_optimization_demo::optimized::ReserveStrategy;
+
+        // 只换哈希算法和预分配策略，遍历顺序不变，结果必须完全相同
+        let expected = optimized::find_most_frequent_hashed(&numbers);
+        for reserve in [ReserveStrategy::None, ReserveStrategy::HalfLength, ReserveStrategy::Sampled] {
+            prop_assert_eq!(
+                optimized::find_most_frequent_with(&numbers, hashers::SipHash::new(), reserve),
+                expected
+            );
+        }
+        let estimate = optimized::estimate_cardinality(&numbers);
+        prop_assert!(estimate <= numbers.len());
+    }
+}
+
+proptest! {
+    #[test]
+    fn bounded_most_frequent_matches_hashmap(numbers in prop::collection::vec(-1000i32..=1000, 0..500)) {
+        let expected = optimized::find_most_frequent_hashed(&numbers);
+        prop_assert_eq!(optimized::find_most_frequent_bounded(&numbers, -1000, 1000), Some(expected));
+        prop_assert_eq!(optimized::find_most_frequent_auto(&numbers), expected);
+        prop_assert_eq!(optimized::find_most_frequent(&numbers), expected);
+    }
+
+    #[test]
+    fn auto_most_frequent_handles_any_range(numbers in prop::collection::vec(edge_i32(), 0..200)) {
+        prop_assert_eq!(optimized::find_most_frequent_auto(&numbers), optimized::find_most_frequent_hashed(&numbers));
+    }
+}
+
+proptest! {
+    #[test]
+    fn iterator_variants_match_collecting(numbers in prop::collection::vec(doublable_i32(), 0..200)) {
+        let streamed: Vec<i32> = optimized::filter_and_transform_iter(&numbers).collect();
+        prop_assert_eq!(streamed, optimized::filter_and_transform(&numbers));
+        let strings: Vec<String> = optimized::process_strings_iter(&numbers).collect();
+        prop_assert_eq!(strings, optimized::process_strings(&numbers));
+    }
+}
@@ -1,25 +1,149 @@
+use std::env;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use actix_web::dev::Service;
 use actix_web::{web, App, HttpServer};
-use http::{configure, AppState};
+use common::config::env_var;
+use http::audit::{AuditConfig, AuditLog};
+use http::auth::{AuthConfig, Role};
+use http::security::SecurityConfig;
+use http::{configure_with, AppState};
 use model::MyObject;
-use std::sync::{Arc, Mutex};
+use tokio::signal::unix::{signal, SignalKind};
+use tracing_subscriber::EnvFilter;
+
+const TOKEN_TTL_SECS: u64 = 24 * 60 * 60;
+const DEFAULT_GRACE_SECS: u64 = 30;
+const DEFAULT_DATA_FILE: &str = "objects.json";
+const DEFAULT_AUDIT_FILE: &str = "audit.jsonl";
+
+/// `rust-rest-api issue-token <role> [subject]` prints a bearer token signed
+/// with `AUTH_SECRET` instead of starting the server.
+fn issue_token(args: &[String]) -> std::io::Result<()> {
+    let role: Role = args
+        .first()
+        .ok_or("usage: rust-rest-api issue-token <viewer|editor|admin> [subject]")
+        .and_then(|r| {
+            r.parse()
+                .map_err(|_| "role must be viewer, editor or admin")
+        })
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    let subject = args.get(1).map(String::as_str).unwrap_or("cli");
+    println!(
+        "{}",
+        auth_config()?.issue_token(subject, role, TOKEN_TTL_SECS)
+    );
+    Ok(())
+}
+
+fn auth_config() -> std::io::Result<AuthConfig> {
+    AuthConfig::from_env().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))
+}
+
+/// Resolves on SIGTERM (container stop) or SIGINT (Ctrl+C).
+async fn shutdown_signal() -> &'static str {
+    let mut terminate = signal(SignalKind::terminate()).expect("failed to install SIGTERM handler");
+    tokio::select! {
+        _ = terminate.recv() => "SIGTERM",
+        _ = tokio::signal::ctrl_c() => "SIGINT",
+    }
+}
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    let app_state = web::Data::new(AppState {
-        objects: Arc::new(Mutex::new(vec![
-            MyObject { id: 1, name: "Initial Object 1".to_string() },
-            MyObject { id: 2, name: "Initial Object 2".to_string() },
-        ])),
-    });
+    // JSON lines on stderr; `RUST_LOG` adjusts the level (default `info`).
+    tracing_subscriber::fmt()
+        .json()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .with_writer(std::io::stderr)
+        .init();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("issue-token") {
+        return issue_token(&args[1..]);
+    }
+    let auth = auth_config()?;
+
+    let grace_secs = match env_var("SHUTDOWN_GRACE_SECS") {
+        Ok(grace_secs) => grace_secs.unwrap_or(DEFAULT_GRACE_SECS),
+        Err(err) => {
+            tracing::warn!(%err, "ignoring SHUTDOWN_GRACE_SECS");
+            DEFAULT_GRACE_SECS
+        }
+    };
+    let data_file =
+        PathBuf::from(env::var("DATA_FILE").unwrap_or_else(|_| DEFAULT_DATA_FILE.into()));
+    let audit_file =
+        PathBuf::from(env::var("AUDIT_LOG_FILE").unwrap_or_else(|_| DEFAULT_AUDIT_FILE.into()));
+
+    let objects = match http::repository::ObjectRepository::load_snapshot(&data_file)? {
+        Some(objects) => {
+            tracing::info!(count = objects.len(), file = %data_file.display(), "loaded objects");
+            objects
+        }
+        None => vec![
+            MyObject::new(1, "Initial Object 1"),
+            MyObject::new(2, "Initial Object 2"),
+        ],
+    };
+    let audit = AuditLog::open(AuditConfig::from_env(), &audit_file)?;
+    let app_state = web::Data::new(AppState::new(objects, auth).with_audit_log(audit));
+    let requests = Arc::new(AtomicU64::new(0));
+    let started = Instant::now();
 
-    HttpServer::new(move || {
+    let security = SecurityConfig::load();
+    let counter = requests.clone();
+    let state = app_state.clone();
+    let server = HttpServer::new(move || {
+        let counter = counter.clone();
         App::new()
-            .app_data(app_state.clone())
-            .configure(configure)
+            .app_data(state.clone())
+            .wrap_fn(move |req, srv| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                srv.call(req)
+            })
+            .configure(|cfg| configure_with(cfg, &security))
     })
     .bind(("127.0.0.1", 8080))?
-    .run()
-    .await
-}
+    .shutdown_timeout(grace_secs)
+    .disable_signals()
+    .run();
+
+    // Stop accepting connections on the first signal and give in-flight
+    // requests up to `grace_secs` to finish. Open SSE streams are closed
+    // right away since they would otherwise hold the whole grace period.
+    let handle = server.handle();
+    let events_state = app_state.clone();
+    tokio::spawn(async move {
+        let signal = shutdown_signal().await;
+        tracing::info!(
+            signal,
+            grace_secs,
+            "shutdown requested, draining connections"
+        );
+        events_state.events.close();
+        handle.stop(true).await;
+    });
 
+    server.await?;
 
+    // Flush the audit log first so a failing snapshot cannot lose entries.
+    app_state.audit.flush()?;
+    let flushed = app_state
+        .repository
+        .lock()
+        .unwrap()
+        .save_snapshot(&data_file)?;
+    tracing::info!(
+        uptime_secs = started.elapsed().as_secs_f64(),
+        requests_served = requests.load(Ordering::Relaxed),
+        objects_flushed = flushed,
+        file = %data_file.display(),
+        audit_file = %audit_file.display(),
+        "shutdown complete"
+    );
+    Ok(())
+}
@@ -0,0 +1,950 @@
+mod common;
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::http::{Method, StatusCode};
+use actix_web::{test, web};
+use http::attachments::AttachmentConfig;
+use http::audit::{AuditConfig, AuditLog};
+use http::auth::Role;
+use http::events::ChangeKind;
+use http::rate_limit::RateLimitConfig;
+use http::security::SecurityConfig;
+use model::MyObject;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use common::{
+    app_state, bearer, multipart_body, seed_objects, spawn_app_with_security, spawn_app_with_state,
+    spawn_test_app, temp_attachment_dir, test_state,
+};
+
+#[actix_web::test]
+async fn crud_lifecycle() {
+    let app = spawn_test_app().await;
+
+    let req = test::TestRequest::post()
+        .uri("/objects")
+        .insert_header(bearer(Role::Editor))
+        .set_json(json!({"id": 10, "name": "Lifecycle object"}))
+        .to_request();
+    let created: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(created["id"], 10);
+    assert_eq!(created["version"], 1);
+
+    let req = test::TestRequest::get()
+        .uri("/objects/10")
+        .insert_header(bearer(Role::Viewer))
+        .to_request();
+    let fetched: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(fetched["name"], "Lifecycle object");
+
+    let req = test::TestRequest::put()
+        .uri("/objects/10")
+        .insert_header(bearer(Role::Editor))
+        .set_json(json!({"id": 10, "name": "Renamed", "version": 1}))
+        .to_request();
+    let updated: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(updated["name"], "Renamed");
+    assert_eq!(updated["version"], 2);
+    assert_eq!(updated["created_at"], created["created_at"]);
+    assert!(updated["updated_at"].as_str().unwrap() >= created["updated_at"].as_str().unwrap());
+
+    let req = test::TestRequest::delete()
+        .uri("/objects/10")
+        .insert_header(bearer(Role::Admin))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri("/objects/10")
+        .insert_header(bearer(Role::Viewer))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::post()
+        .uri("/objects/10/restore")
+        .insert_header(bearer(Role::Admin))
+        .to_request();
+    let restored: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(restored["deleted_at"].is_null());
+}
+
+#[actix_web::test]
+async fn create_rejects_existing_id() {
+    let app = spawn_test_app().await;
+    let create = |name: &str| {
+        test::TestRequest::post()
+            .uri("/objects")
+            .insert_header(bearer(Role::Editor))
+            .set_json(json!({"id": 1, "name": name}))
+            .to_request()
+    };
+
+    let req = test::TestRequest::patch()
+        .uri("/objects/1")
+        .insert_header(bearer(Role::Editor))
+        .set_json(json!({"name": "Edited", "version": 1}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let resp = test::call_service(&app, create("Overwrite")).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    // Soft-deleted objects keep their id as well.
+    let req = test::TestRequest::delete()
+        .uri("/objects/1")
+        .insert_header(bearer(Role::Admin))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    let resp = test::call_service(&app, create("Overwrite")).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let req = test::TestRequest::post()
+        .uri("/objects/1/restore")
+        .insert_header(bearer(Role::Admin))
+        .to_request();
+    let restored: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(restored["name"], "Edited");
+    assert_eq!(restored["version"], 4);
+
+    let req = test::TestRequest::get()
+        .uri("/audit?object_id=1")
+        .insert_header(bearer(Role::Admin))
+        .to_request();
+    let entries: Value = test::call_and_read_body_json(&app, req).await;
+    let actions: Vec<&str> = entries
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(actions, vec!["update", "soft_delete", "restore"]);
+}
+
+#[actix_web::test]
+async fn stale_version_is_rejected() {
+    let app = spawn_test_app().await;
+
+    let req = test::TestRequest::patch()
+        .uri("/objects/1")
+        .insert_header(bearer(Role::Editor))
+        .set_json(json!({"name": "first", "version": 1}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::patch()
+        .uri("/objects/1")
+        .insert_header(bearer(Role::Editor))
+        .set_json(json!({"name": "second", "version": 1}))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::CONFLICT
+    );
+}
+
+#[actix_web::test]
+async fn auth_failures() {
+    let app = spawn_test_app().await;
+
+    let req = test::TestRequest::get().uri("/objects").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::get()
+        .uri("/objects")
+        .insert_header(("Authorization", "Bearer not-a-token"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::delete()
+        .uri("/objects/1")
+        .insert_header(bearer(Role::Editor))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "forbidden");
+    assert_eq!(body["required_role"], "admin");
+}
+
+#[actix_web::test]
+async fn validation_errors() {
+    let app = spawn_test_app().await;
+
+    let req = test::TestRequest::post()
+        .uri("/objects")
+        .insert_header(bearer(Role::Editor))
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload("{\"id\": \"not a number\"}")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get()
+        .uri("/objects/search?q=%20")
+        .insert_header(bearer(Role::Viewer))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn pagination() {
+    let app = spawn_app_with_state(test_state(seed_objects(25))).await;
+
+    let req = test::TestRequest::get()
+        .uri("/objects?offset=20&limit=10")
+        .insert_header(bearer(Role::Viewer))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "25");
+    let page: Vec<Value> = test::read_body_json(resp).await;
+    let ids: Vec<u64> = page.iter().map(|o| o["id"].as_u64().unwrap()).collect();
+    assert_eq!(ids, vec![21, 22, 23, 24, 25]);
+}
+
+#[actix_web::test]
+async fn responses_carry_request_id() {
+    let app = spawn_test_app().await;
+
+    let req = test::TestRequest::get()
+        .uri("/hello")
+        .insert_header(("X-Request-Id", "trace-me"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get("X-Request-Id").unwrap(), "trace-me");
+}
+
+#[actix_web::test]
+async fn rate_limit_per_client() {
+    let limits = RateLimitConfig {
+        burst: 2,
+        refill_per_sec: 0.5,
+        ..RateLimitConfig::default()
+    };
+    let state = web::Data::new(app_state(seed_objects(1)).with_rate_limit_config(limits));
+    let app = spawn_app_with_state(state).await;
+    let from = |addr: &str| {
+        test::TestRequest::get()
+            .uri("/hello")
+            .peer_addr(addr.parse().unwrap())
+            .to_request()
+    };
+
+    for _ in 0..2 {
+        let resp = test::call_service(&app, from("10.0.0.1:4000")).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+    let resp = test::call_service(&app, from("10.0.0.1:4001")).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(resp.headers().get("Retry-After").unwrap(), "2");
+    assert!(resp.headers().contains_key("X-Request-Id"));
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "rate_limited");
+
+    // Other clients and authenticated subjects have their own buckets.
+    let resp = test::call_service(&app, from("10.0.0.2:4000")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let req = test::TestRequest::get()
+        .uri("/hello")
+        .peer_addr("10.0.0.1:4000".parse().unwrap())
+        .insert_header(bearer(Role::Viewer))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Unverified keys do not buy a fresh bucket.
+    for key in ["client-a", "client-b"] {
+        let req = test::TestRequest::get()
+            .uri("/hello")
+            .peer_addr("10.0.0.1:4000".parse().unwrap())
+            .insert_header(("X-Api-Key", key))
+            .insert_header(("Authorization", "Bearer forged"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+}
+
+#[actix_web::test]
+async fn export_formats() {
+    let mut objects = seed_objects(3);
+    objects[1].name = "Quoted, \"name\"".to_string();
+    let app = spawn_app_with_state(test_state(objects)).await;
+
+    let req = test::TestRequest::get()
+        .uri("/objects/export?format=csv")
+        .insert_header(bearer(Role::Viewer))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers().get("Content-Type").unwrap(),
+        "text/csv; charset=utf-8"
+    );
+    assert_eq!(
+        resp.headers().get("Content-Disposition").unwrap(),
+        "attachment; filename=\"objects.csv\""
+    );
+    let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("id,name,version"));
+    assert!(lines[2].starts_with("2,\"Quoted, \"\"name\"\"\",1,"));
+
+    let req = test::TestRequest::get()
+        .uri("/objects/export?format=ndjson")
+        .insert_header(bearer(Role::Viewer))
+        .to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    let ids: Vec<u64> = String::from_utf8(body.to_vec())
+        .unwrap()
+        .lines()
+        .map(|line| {
+            serde_json::from_str::<Value>(line).unwrap()["id"]
+                .as_u64()
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(ids, vec![1, 2, 3]);
+
+    let req = test::TestRequest::get()
+        .uri("/objects/export?format=xml")
+        .insert_header(bearer(Role::Viewer))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn audit_log_records_writes() {
+    let app = spawn_test_app().await;
+
+    let req = test::TestRequest::patch()
+        .uri("/objects/1")
+        .insert_header(bearer(Role::Editor))
+        .set_json(json!({"name": "Audited", "version": 1}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::delete()
+        .uri("/objects/1?hard=true")
+        .insert_header(bearer(Role::Admin))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri("/objects")
+        .insert_header(bearer(Role::Editor))
+        .set_json(json!({"id": 5, "name": "Other"}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri("/audit?object_id=1")
+        .insert_header(bearer(Role::Admin))
+        .to_request();
+    let entries: Value = test::call_and_read_body_json(&app, req).await;
+    let entries = entries.as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["action"], "update");
+    assert_eq!(entries[0]["actor"], "tester");
+    assert_eq!(entries[0]["role"], "editor");
+    assert_eq!(entries[0]["old"]["name"], "Object 1");
+    assert_eq!(entries[0]["new"]["name"], "Audited");
+    assert_eq!(entries[1]["action"], "hard_delete");
+    assert_eq!(entries[1]["old"]["name"], "Audited");
+    assert!(entries[1]["new"].is_null());
+
+    let req = test::TestRequest::get()
+        .uri("/audit")
+        .insert_header(bearer(Role::Admin))
+        .to_request();
+    let all: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(all.as_array().unwrap().len(), 3);
+    assert_eq!(all[2]["action"], "create");
+    assert!(all[2]["old"].is_null());
+
+    let req = test::TestRequest::get()
+        .uri("/audit")
+        .insert_header(bearer(Role::Editor))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::FORBIDDEN
+    );
+}
+
+#[actix_web::test]
+async fn audit_log_persists_across_restarts() {
+    let dir = temp_attachment_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("audit.jsonl");
+    let rename = |name: &str| {
+        test::TestRequest::patch()
+            .uri("/objects/1")
+            .insert_header(bearer(Role::Editor))
+            .set_json(json!({"name": name, "version": 1}))
+            .to_request()
+    };
+
+    let log = AuditLog::open(AuditConfig::default(), &path).unwrap();
+    let state = web::Data::new(app_state(seed_objects(1)).with_audit_log(log));
+    let app = spawn_app_with_state(state.clone()).await;
+    assert_eq!(
+        test::call_service(&app, rename("Before")).await.status(),
+        StatusCode::OK
+    );
+    let req = test::TestRequest::delete()
+        .uri("/objects/1")
+        .insert_header(bearer(Role::Admin))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    // Every entry is on disk as soon as the write returns.
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(contents.lines().count(), 2);
+
+    // A line torn by a crash is skipped on the next start.
+    std::fs::write(&path, format!("{}{{\"id\": 3, \"obj", contents)).unwrap();
+    let log = AuditLog::open(AuditConfig::default(), &path).unwrap();
+    let state = web::Data::new(app_state(seed_objects(1)).with_audit_log(log));
+    let app = spawn_app_with_state(state.clone()).await;
+    assert_eq!(
+        test::call_service(&app, rename("After")).await.status(),
+        StatusCode::OK
+    );
+    state.audit.flush().unwrap();
+
+    let req = test::TestRequest::get()
+        .uri("/audit?object_id=1")
+        .insert_header(bearer(Role::Admin))
+        .to_request();
+    let entries: Value = test::call_and_read_body_json(&app, req).await;
+    let ids: Vec<u64> = entries
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![1, 2, 3]);
+    assert_eq!(entries[0]["new"]["name"], "Before");
+    assert_eq!(entries[1]["action"], "soft_delete");
+    assert_eq!(entries[2]["new"]["name"], "After");
+
+    let reloaded = AuditLog::open(AuditConfig::default(), &path).unwrap();
+    assert_eq!(reloaded.entries(None).len(), 3);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[actix_web::test]
+async fn audit_log_file_is_compacted_on_open() {
+    let dir = temp_attachment_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("audit.jsonl");
+    let config = AuditConfig {
+        retention: None,
+        max_entries: Some(2),
+    };
+
+    let log = AuditLog::open(config.clone(), &path).unwrap();
+    let state = web::Data::new(app_state(seed_objects(1)).with_audit_log(log));
+    let app = spawn_app_with_state(state.clone()).await;
+    for (version, name) in ["One", "Two", "Three"].iter().enumerate() {
+        let req = test::TestRequest::patch()
+            .uri("/objects/1")
+            .insert_header(bearer(Role::Editor))
+            .set_json(json!({"name": name, "version": version + 1}))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    }
+    assert_eq!(state.audit.entries(None).len(), 2);
+    assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 3);
+
+    // Reopening drops the entries outside the limits from the file too.
+    let reloaded = AuditLog::open(config, &path).unwrap();
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let ids: Vec<u64> = contents
+        .lines()
+        .map(|line| {
+            serde_json::from_str::<Value>(line).unwrap()["id"]
+                .as_u64()
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(ids, vec![2, 3]);
+    assert_eq!(reloaded.entries(None).len(), 2);
+    assert!(!path.with_extension("tmp").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[actix_web::test]
+async fn audit_log_file_is_compacted_while_running() {
+    let dir = temp_attachment_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("audit.jsonl");
+    let config = AuditConfig {
+        retention: None,
+        max_entries: Some(2),
+    };
+
+    let log = AuditLog::open(config, &path).unwrap();
+    let state = web::Data::new(app_state(seed_objects(1)).with_audit_log(log));
+    let app = spawn_app_with_state(state.clone()).await;
+    let mut line_counts = Vec::new();
+    for version in 1..=5 {
+        let req = test::TestRequest::patch()
+            .uri("/objects/1")
+            .insert_header(bearer(Role::Editor))
+            .set_json(json!({"name": format!("Name {}", version), "version": version}))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+        line_counts.push(std::fs::read_to_string(&path).unwrap().lines().count());
+    }
+
+    // The file is rewritten once the pruned entries outnumber the retained ones.
+    assert_eq!(line_counts, vec![1, 2, 3, 4, 2]);
+    let ids: Vec<u64> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            serde_json::from_str::<Value>(line).unwrap()["id"]
+                .as_u64()
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(ids, vec![4, 5]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[actix_web::test]
+async fn audit_retention_keeps_newest_entries() {
+    let state = app_state(seed_objects(1)).with_audit_config(AuditConfig {
+        retention: None,
+        max_entries: Some(2),
+    });
+    let app = spawn_app_with_state(web::Data::new(state)).await;
+
+    for version in 1..=3 {
+        let req = test::TestRequest::patch()
+            .uri("/objects/1")
+            .insert_header(bearer(Role::Editor))
+            .set_json(json!({"name": format!("Name {}", version), "version": version}))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/audit?object_id=1")
+        .insert_header(bearer(Role::Admin))
+        .to_request();
+    let entries: Value = test::call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = entries
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["new"]["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["Name 2", "Name 3"]);
+    assert_eq!(entries[1]["id"], 3);
+}
+
+#[actix_web::test]
+async fn versioned_routes() {
+    let app = spawn_test_app().await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/objects")
+        .insert_header(bearer(Role::Editor))
+        .set_json(json!({"id": 10, "name": "Versioned object"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(resp.headers().get("Deprecation").is_none());
+    let created: Value = test::read_body_json(resp).await;
+    assert_eq!(created["version"], 1);
+
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/objects/10")
+        .insert_header(bearer(Role::Editor))
+        .set_json(json!({"name": "Patched", "version": 1}))
+        .to_request();
+    let patched: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(patched["name"], "Patched");
+
+    // The legacy path serves the same data but points clients at its successor.
+    let req = test::TestRequest::get()
+        .uri("/objects/10")
+        .insert_header(bearer(Role::Viewer))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get("Deprecation").unwrap(), "true");
+    assert_eq!(
+        resp.headers().get("Link").unwrap(),
+        "</api/v1/objects/10>; rel=\"successor-version\""
+    );
+    let legacy: Value = test::read_body_json(resp).await;
+    assert_eq!(legacy, patched);
+
+    let req = test::TestRequest::get().uri("/hello").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.headers().get("Deprecation").is_none());
+}
+
+#[actix_web::test]
+async fn unknown_api_version() {
+    let app = spawn_test_app().await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/v9/objects")
+        .insert_header(bearer(Role::Viewer))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "unsupported_version");
+    assert_eq!(body["supported"], json!(["v1"]));
+}
+
+async fn search_ids<S, B>(app: &S, query: &str) -> Vec<u64>
+where
+    S: Service<actix_http::Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let req = test::TestRequest::get()
+        .uri(&format!("/objects/search?q={}", query))
+        .insert_header(bearer(Role::Viewer))
+        .to_request();
+    let results: Vec<Value> = test::call_and_read_body_json(app, req).await;
+    results
+        .iter()
+        .map(|result| result["object"]["id"].as_u64().unwrap())
+        .collect()
+}
+
+#[actix_web::test]
+async fn search_ranking() {
+    let names = ["Red apple", "Green apple", "Red red apple pie", "Banana"];
+    let objects = names
+        .iter()
+        .zip(1..)
+        .map(|(name, id)| MyObject::new(id, *name))
+        .collect();
+    let app = spawn_app_with_state(test_state(objects)).await;
+
+    // Scores count every query token occurrence: 3 > 2 > 1.
+    let req = test::TestRequest::get()
+        .uri("/objects/search?q=red%20apple")
+        .insert_header(bearer(Role::Viewer))
+        .to_request();
+    let results: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    let ranked: Vec<(u64, u64)> = results
+        .iter()
+        .map(|r| {
+            (
+                r["object"]["id"].as_u64().unwrap(),
+                r["score"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(ranked, vec![(3, 3), (1, 2), (2, 1)]);
+
+    // Tokens split on punctuation and match case-insensitively; equal scores
+    // are ordered by id.
+    assert_eq!(search_ids(&app, "APPLE").await, vec![1, 2, 3]);
+    assert_eq!(search_ids(&app, "Red-PIE").await, vec![3, 1]);
+    assert!(search_ids(&app, "cherry").await.is_empty());
+}
+
+#[actix_web::test]
+async fn search_index_follows_writes() {
+    let app = spawn_test_app().await;
+    assert_eq!(search_ids(&app, "object").await, vec![1, 2]);
+
+    let req = test::TestRequest::put()
+        .uri("/objects/1")
+        .insert_header(bearer(Role::Editor))
+        .set_json(json!({"id": 1, "name": "Renamed thing", "version": 1}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    assert_eq!(search_ids(&app, "object").await, vec![2]);
+    assert_eq!(search_ids(&app, "renamed").await, vec![1]);
+
+    let req = test::TestRequest::delete()
+        .uri("/objects/2")
+        .insert_header(bearer(Role::Admin))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    assert!(search_ids(&app, "object").await.is_empty());
+
+    let req = test::TestRequest::post()
+        .uri("/objects/2/restore")
+        .insert_header(bearer(Role::Admin))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    assert_eq!(search_ids(&app, "object").await, vec![2]);
+
+    let req = test::TestRequest::delete()
+        .uri("/objects/2?hard=true")
+        .insert_header(bearer(Role::Admin))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    assert!(search_ids(&app, "object").await.is_empty());
+}
+
+/// Reads SSE frames from `body` until the event with id `until` arrives and
+/// returns the ids of all events seen, in order.
+async fn read_event_ids<B: MessageBody>(body: B, until: u64) -> Vec<u64> {
+    let mut body = Box::pin(body);
+    let mut ids = Vec::new();
+    while ids.last() != Some(&until) {
+        let next = std::future::poll_fn(|cx| body.as_mut().poll_next(cx));
+        let chunk = tokio::time::timeout(Duration::from_secs(5), next)
+            .await
+            .expect("timed out waiting for an event")
+            .expect("event stream ended")
+            .ok()
+            .expect("event stream failed");
+        let frame = String::from_utf8(chunk.to_vec()).unwrap();
+        ids.extend(
+            frame
+                .lines()
+                .filter_map(|line| line.strip_prefix("id: "))
+                .map(|id| id.parse::<u64>().unwrap()),
+        );
+    }
+    ids
+}
+
+#[actix_web::test]
+async fn event_stream_resumes_after_last_event_id() {
+    let app = spawn_test_app().await;
+
+    for id in 10..14 {
+        let req = test::TestRequest::post()
+            .uri("/objects")
+            .insert_header(bearer(Role::Editor))
+            .set_json(json!({"id": id, "name": format!("Object {}", id)}))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/objects/stream")
+        .insert_header(bearer(Role::Viewer))
+        .insert_header(("Last-Event-ID", "2"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers().get("Content-Type").unwrap(),
+        "text/event-stream"
+    );
+    assert_eq!(read_event_ids(resp.into_body(), 4).await, vec![3, 4]);
+}
+
+#[actix_web::test]
+async fn event_stream_replays_retained_history_for_expired_id() {
+    let state = test_state(seed_objects(1));
+    let app = spawn_app_with_state(state.clone()).await;
+    let object = MyObject::new(1, "Object 1");
+    for _ in 0..1100 {
+        state.events.publish(ChangeKind::Updated, &object);
+    }
+
+    // Event 5 has aged out of the 1024-entry history, so the client gets
+    // everything that is still retained: events 77 through 1100.
+    let req = test::TestRequest::get()
+        .uri("/objects/stream")
+        .insert_header(bearer(Role::Viewer))
+        .insert_header(("Last-Event-ID", "5"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let ids = read_event_ids(resp.into_body(), 1100).await;
+    assert_eq!(ids, (77..=1100).collect::<Vec<_>>());
+}
+
+#[actix_web::test]
+async fn attachment_upload_and_download() {
+    let app = spawn_test_app().await;
+
+    let (content_type, body) = multipart_body(&[
+        ("notes.txt", "text/plain", b"hello attachments".as_slice()),
+        ("data.json", "application/json", b"{\"a\":1}".as_slice()),
+    ]);
+    let req = test::TestRequest::post()
+        .uri("/objects/1/attachments")
+        .insert_header(bearer(Role::Editor))
+        .insert_header(content_type)
+        .set_payload(body)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let obj: Value = test::read_body_json(resp).await;
+    assert_eq!(obj["attachments"][0]["name"], "notes.txt");
+    assert_eq!(obj["attachments"][0]["size"], 17);
+    assert_eq!(obj["attachments"][1]["content_type"], "application/json");
+
+    let req = test::TestRequest::get()
+        .uri("/objects/1/attachments/notes.txt")
+        .insert_header(bearer(Role::Viewer))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers().get("Content-Type").unwrap(), "text/plain");
+    assert_eq!(
+        resp.headers().get("Content-Disposition").unwrap(),
+        "attachment; filename=\"notes.txt\""
+    );
+    assert_eq!(test::read_body(resp).await, "hello attachments");
+
+    let req = test::TestRequest::get()
+        .uri("/objects/1/attachments/missing.txt")
+        .insert_header(bearer(Role::Viewer))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn attachment_limits_reject_whole_upload() {
+    let dir = temp_attachment_dir();
+    let attachments = AttachmentConfig {
+        dir: dir.clone(),
+        max_bytes: 8,
+        max_request_bytes: 12,
+        ..AttachmentConfig::default()
+    };
+    let state = web::Data::new(app_state(seed_objects(1)).with_attachment_config(attachments));
+    let app = spawn_app_with_state(state).await;
+
+    let uploads: [&[(&str, &str, &[u8])]; 3] = [
+        // One file over the per-file limit.
+        &[("big.txt", "text/plain", b"123456789")],
+        // Each file fits, together they exceed the request limit.
+        &[
+            ("a.txt", "text/plain", b"1234567"),
+            ("b.txt", "text/plain", b"1234567"),
+        ],
+        // A later field fails after an earlier one was accepted.
+        &[
+            ("ok.txt", "text/plain", b"fine"),
+            ("bad.svg", "image/svg+xml", b"<svg/>"),
+        ],
+    ];
+    let expected = [
+        StatusCode::PAYLOAD_TOO_LARGE,
+        StatusCode::PAYLOAD_TOO_LARGE,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+    ];
+    for (files, status) in uploads.iter().zip(expected) {
+        let (content_type, body) = multipart_body(files);
+        let req = test::TestRequest::post()
+            .uri("/objects/1/attachments")
+            .insert_header(bearer(Role::Editor))
+            .insert_header(content_type)
+            .set_payload(body)
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), status);
+    }
+
+    // Nothing was recorded or left on disk, not even staged files.
+    let req = test::TestRequest::get()
+        .uri("/objects/1")
+        .insert_header(bearer(Role::Viewer))
+        .to_request();
+    let obj: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(obj["attachments"], json!([]));
+    let leftovers = std::fs::read_dir(dir.join("1"))
+        .map(|entries| entries.count())
+        .unwrap_or(0);
+    assert_eq!(leftovers, 0);
+}
+
+#[actix_web::test]
+async fn cors_preflight() {
+    let mut security = SecurityConfig::default();
+    security.cors.allowed_origins = vec!["https://app.example.com".to_string()];
+    let app = spawn_app_with_security(test_state(seed_objects(1)), security).await;
+
+    let req = test::TestRequest::default()
+        .method(Method::OPTIONS)
+        .uri("/objects")
+        .insert_header(("Origin", "https://app.example.com"))
+        .insert_header(("Access-Control-Request-Method", "PATCH"))
+        .insert_header(("Access-Control-Request-Headers", "authorization"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let headers = resp.headers();
+    assert_eq!(
+        headers.get("Access-Control-Allow-Origin").unwrap(),
+        "https://app.example.com"
+    );
+    let methods = headers
+        .get("Access-Control-Allow-Methods")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert!(methods.contains("PATCH"));
+    assert_eq!(headers.get("Access-Control-Max-Age").unwrap(), "3600");
+
+    let req = test::TestRequest::default()
+        .method(Method::OPTIONS)
+        .uri("/objects")
+        .insert_header(("Origin", "https://evil.example.com"))
+        .insert_header(("Access-Control-Request-Method", "GET"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert!(resp.headers().get("Access-Control-Allow-Origin").is_none());
+}
+
+#[actix_web::test]
+async fn cors_skips_invalid_origins() {
+    let mut security = SecurityConfig::default();
+    security.cors.allowed_origins = vec![
+        "https://bad origin.example.com".to_string(),
+        "https://app.example.com".to_string(),
+    ];
+    let app = spawn_app_with_security(test_state(seed_objects(1)), security).await;
+
+    let req = test::TestRequest::default()
+        .method(Method::OPTIONS)
+        .uri("/objects")
+        .insert_header(("Origin", "https://app.example.com"))
+        .insert_header(("Access-Control-Request-Method", "GET"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers().get("Access-Control-Allow-Origin").unwrap(),
+        "https://app.example.com"
+    );
+}
+
+#[actix_web::test]
+async fn security_headers() {
+    let app = spawn_test_app().await;
+
+    for (uri, status) in [
+        ("/hello", StatusCode::OK),
+        ("/objects", StatusCode::UNAUTHORIZED),
+    ] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), status);
+        let headers = resp.headers();
+        assert_eq!(headers.get("X-Content-Type-Options").unwrap(), "nosniff");
+        assert_eq!(headers.get("X-Frame-Options").unwrap(), "DENY");
+        assert_eq!(
+            headers.get("Content-Security-Policy").unwrap(),
+            "default-src 'none'; frame-ancestors 'none'"
+        );
+        assert_eq!(headers.get("Referrer-Policy").unwrap(), "no-referrer");
+    }
+}
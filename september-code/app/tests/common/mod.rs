@@ -0,0 +1,122 @@
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::{test, web, App};
+use http::attachments::AttachmentConfig;
+use http::audit::AuditConfig;
+use http::auth::{AuthConfig, Role};
+use http::rate_limit::RateLimitConfig;
+use http::security::SecurityConfig;
+use http::{configure_with, AppState};
+use model::MyObject;
+
+pub const SECRET: &str = "integration-test-secret";
+
+/// Objects named "Object 1" ... "Object n" with ids 1..=n.
+pub fn seed_objects(n: u32) -> Vec<MyObject> {
+    (1..=n)
+        .map(|id| MyObject::new(id, format!("Object {}", id)))
+        .collect()
+}
+
+/// A scratch attachments directory unique to this test process and call.
+pub fn temp_attachment_dir() -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    std::env::temp_dir().join(format!(
+        "september-attachments-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Application state wired for tests: fixed auth secret, scratch attachment
+/// directory and default rate limits and audit retention regardless of the
+/// environment.
+pub fn app_state(objects: Vec<MyObject>) -> AppState {
+    let attachments = AttachmentConfig {
+        dir: temp_attachment_dir(),
+        ..AttachmentConfig::default()
+    };
+    AppState::new(objects, AuthConfig::new(SECRET))
+        .with_attachment_config(attachments)
+        .with_rate_limit_config(RateLimitConfig::default())
+        .with_audit_config(AuditConfig::default())
+}
+
+pub fn test_state(objects: Vec<MyObject>) -> web::Data<AppState> {
+    web::Data::new(app_state(objects))
+}
+
+/// Boots the full application in-process around `state`.
+pub async fn spawn_app_with_state(
+    state: web::Data<AppState>,
+) -> impl Service<
+    actix_http::Request,
+    Response = ServiceResponse<impl MessageBody>,
+    Error = actix_web::Error,
+> {
+    spawn_app_with_security(state, SecurityConfig::default()).await
+}
+
+/// Boots the full application with explicit CORS and security header settings.
+pub async fn spawn_app_with_security(
+    state: web::Data<AppState>,
+    security: SecurityConfig,
+) -> impl Service<
+    actix_http::Request,
+    Response = ServiceResponse<impl MessageBody>,
+    Error = actix_web::Error,
+> {
+    test::init_service(
+        App::new()
+            .app_data(state)
+            .configure(|cfg| configure_with(cfg, &security)),
+    )
+    .await
+}
+
+/// Boots the application seeded with two objects.
+pub async fn spawn_test_app() -> impl Service<
+    actix_http::Request,
+    Response = ServiceResponse<impl MessageBody>,
+    Error = actix_web::Error,
+> {
+    spawn_app_with_state(test_state(seed_objects(2))).await
+}
+
+pub fn token(role: Role) -> String {
+    AuthConfig::new(SECRET).issue_token("tester", role, 3600)
+}
+
+pub fn bearer(role: Role) -> (&'static str, String) {
+    ("Authorization", format!("Bearer {}", token(role)))
+}
+
+pub const MULTIPART_BOUNDARY: &str = "test-boundary";
+
+/// A `multipart/form-data` body with one file field per `(name, content
+/// type, bytes)`, plus the matching `Content-Type` header.
+pub fn multipart_body(files: &[(&str, &str, &[u8])]) -> ((&'static str, String), Vec<u8>) {
+    let mut body = Vec::new();
+    for (name, content_type, bytes) in files {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+                MULTIPART_BOUNDARY, name, content_type
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(bytes);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", MULTIPART_BOUNDARY).as_bytes());
+    let header = (
+        "Content-Type",
+        format!("multipart/form-data; boundary={}", MULTIPART_BOUNDARY),
+    );
+    (header, body)
+}
@@ -0,0 +1,345 @@
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use actix_multipart::{Field, Multipart};
+use actix_web::HttpResponse;
+use futures_util::TryStreamExt;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+use chrono::Utc;
+use common::config::env_var;
+use model::{Attachment, ObjectId};
+use uuid::Uuid;
+
+const DEFAULT_DIR: &str = "attachments";
+const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+const DEFAULT_MAX_REQUEST_BYTES: u64 = 50 * 1024 * 1024;
+const DEFAULT_CONTENT_TYPES: &[&str] = &[
+    "application/json",
+    "application/pdf",
+    "image/gif",
+    "image/jpeg",
+    "image/png",
+    "text/csv",
+    "text/plain",
+];
+
+/// Where and what may be uploaded. Read from `ATTACHMENTS_DIR`,
+/// `ATTACHMENTS_MAX_BYTES`, `ATTACHMENTS_MAX_REQUEST_BYTES` and
+/// `ATTACHMENTS_CONTENT_TYPES` (comma separated).
+#[derive(Clone)]
+pub struct AttachmentConfig {
+    pub dir: PathBuf,
+    /// Limit per file.
+    pub max_bytes: u64,
+    /// Limit on all files of one upload request together.
+    pub max_request_bytes: u64,
+    pub allowed_content_types: Vec<String>,
+}
+
+impl Default for AttachmentConfig {
+    fn default() -> Self {
+        AttachmentConfig {
+            dir: PathBuf::from(DEFAULT_DIR),
+            max_bytes: DEFAULT_MAX_BYTES,
+            max_request_bytes: DEFAULT_MAX_REQUEST_BYTES,
+            allowed_content_types: DEFAULT_CONTENT_TYPES
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl AttachmentConfig {
+    pub fn from_env() -> Self {
+        let mut config = AttachmentConfig::default();
+        if let Ok(dir) = env::var("ATTACHMENTS_DIR") {
+            config.dir = PathBuf::from(dir);
+        }
+        match env_var("ATTACHMENTS_MAX_BYTES") {
+            Ok(Some(max_bytes)) => config.max_bytes = max_bytes,
+            Ok(None) => {}
+            Err(err) => tracing::warn!(%err, "ignoring ATTACHMENTS_MAX_BYTES"),
+        }
+        match env_var("ATTACHMENTS_MAX_REQUEST_BYTES") {
+            Ok(Some(max_request_bytes)) => config.max_request_bytes = max_request_bytes,
+            Ok(None) => {}
+            Err(err) => tracing::warn!(%err, "ignoring ATTACHMENTS_MAX_REQUEST_BYTES"),
+        }
+        if let Ok(types) = env::var("ATTACHMENTS_CONTENT_TYPES") {
+            config.allowed_content_types = types
+                .split(',')
+                .map(|t| t.trim().to_lowercase())
+                .filter(|t| !t.is_empty())
+                .collect();
+        }
+        config
+    }
+}
+
+#[derive(Debug)]
+pub enum AttachmentError {
+    InvalidName(String),
+    UnsupportedType(String),
+    TooLarge { limit: u64 },
+    RequestTooLarge { limit: u64 },
+    MissingFile,
+    Multipart(String),
+    Io(std::io::Error),
+}
+
+impl From<std::io::Error> for AttachmentError {
+    fn from(err: std::io::Error) -> Self {
+        AttachmentError::Io(err)
+    }
+}
+
+impl AttachmentError {
+    pub fn to_response(&self) -> HttpResponse {
+        match self {
+            AttachmentError::InvalidName(name) => {
+                HttpResponse::BadRequest().body(format!("Invalid attachment name: {}", name))
+            }
+            AttachmentError::UnsupportedType(content_type) => HttpResponse::UnsupportedMediaType()
+                .body(format!("Content type not allowed: {}", content_type)),
+            AttachmentError::TooLarge { limit } => HttpResponse::PayloadTooLarge()
+                .body(format!("Attachment exceeds the limit of {} bytes", limit)),
+            AttachmentError::RequestTooLarge { limit } => HttpResponse::PayloadTooLarge().body(
+                format!("Upload exceeds the request limit of {} bytes", limit),
+            ),
+            AttachmentError::MissingFile => {
+                HttpResponse::BadRequest().body("Multipart body contains no file field")
+            }
+            AttachmentError::Multipart(message) => {
+                HttpResponse::BadRequest().body(format!("Malformed multipart body: {}", message))
+            }
+            AttachmentError::Io(err) => HttpResponse::InternalServerError()
+                .body(format!("Attachment storage error: {}", err)),
+        }
+    }
+}
+
+/// Only plain file names are accepted so uploads cannot escape the object's directory.
+fn validate_name(name: &str) -> Result<(), AttachmentError> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    if valid {
+        Ok(())
+    } else {
+        Err(AttachmentError::InvalidName(name.to_string()))
+    }
+}
+
+/// Stores attachment files on disk under `<dir>/<object id>/<name>`.
+pub struct AttachmentStore {
+    config: AttachmentConfig,
+}
+
+impl AttachmentStore {
+    pub fn new(config: AttachmentConfig) -> Self {
+        AttachmentStore { config }
+    }
+
+    pub fn config(&self) -> &AttachmentConfig {
+        &self.config
+    }
+
+    fn object_dir(&self, id: ObjectId) -> PathBuf {
+        self.config.dir.join(id.to_string())
+    }
+
+    pub fn path_for(&self, id: ObjectId, name: &str) -> Result<PathBuf, AttachmentError> {
+        validate_name(name)?;
+        Ok(self.object_dir(id).join(name))
+    }
+
+    /// Saves every file field of the multipart body and returns their metadata.
+    /// All fields are staged first and only moved into place once the whole
+    /// body has been accepted. Stored files with the same names are moved
+    /// aside while the staged ones are moved in and put back if a move fails,
+    /// so a failed upload leaves the stored files as they were.
+    pub async fn save_multipart(
+        &self,
+        id: ObjectId,
+        payload: Multipart,
+    ) -> Result<Vec<Attachment>, AttachmentError> {
+        let mut staged = Vec::new();
+        let result = self.stage_multipart(id, payload, &mut staged).await;
+        if let Err(err) = result {
+            discard(&staged).await;
+            return Err(err);
+        }
+        if staged.is_empty() {
+            return Err(AttachmentError::MissingFile);
+        }
+
+        let mut placed = Vec::with_capacity(staged.len());
+        for (i, (partial, attachment)) in staged.iter().enumerate() {
+            let path = self.object_dir(id).join(&attachment.name);
+            if let Err(err) = place(partial, &path, &mut placed).await {
+                restore(&placed).await;
+                discard(&staged[i..]).await;
+                return Err(err.into());
+            }
+        }
+        for (_, backup) in placed {
+            if let Some(backup) = backup {
+                let _ = fs::remove_file(backup).await;
+            }
+        }
+        Ok(staged
+            .into_iter()
+            .map(|(_, attachment)| attachment)
+            .collect())
+    }
+
+    async fn stage_multipart(
+        &self,
+        id: ObjectId,
+        mut payload: Multipart,
+        staged: &mut Vec<(PathBuf, Attachment)>,
+    ) -> Result<(), AttachmentError> {
+        let mut total = 0u64;
+        while let Some(field) = payload
+            .try_next()
+            .await
+            .map_err(|e| AttachmentError::Multipart(e.to_string()))?
+        {
+            let filename = field
+                .content_disposition()
+                .and_then(|cd| cd.get_filename())
+                .map(str::to_string);
+            if let Some(filename) = filename {
+                self.stage_field(id, &filename, field, &mut total, staged)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Streams one field into a temporary file, recorded in `staged` as soon
+    /// as it exists so the caller can clean it up on any later error.
+    async fn stage_field(
+        &self,
+        id: ObjectId,
+        name: &str,
+        mut field: Field,
+        total: &mut u64,
+        staged: &mut Vec<(PathBuf, Attachment)>,
+    ) -> Result<(), AttachmentError> {
+        validate_name(name)?;
+        let content_type = field
+            .content_type()
+            .map(|mime| mime.essence_str().to_lowercase())
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+        if !self.config.allowed_content_types.contains(&content_type) {
+            return Err(AttachmentError::UnsupportedType(content_type));
+        }
+
+        fs::create_dir_all(self.object_dir(id)).await?;
+        // Valid names never start with a dot, so staged files cannot collide
+        // with stored ones, and the uuid keeps concurrent uploads apart.
+        let partial = self
+            .object_dir(id)
+            .join(format!(".{}.{}.part", name, Uuid::new_v4()));
+        let mut file = fs::File::create(&partial).await?;
+        staged.push((
+            partial,
+            Attachment {
+                name: name.to_string(),
+                content_type,
+                size: 0,
+                uploaded_at: Utc::now(),
+            },
+        ));
+
+        let mut size = 0u64;
+        while let Some(chunk) = field
+            .try_next()
+            .await
+            .map_err(|e| AttachmentError::Multipart(e.to_string()))?
+        {
+            size += chunk.len() as u64;
+            *total += chunk.len() as u64;
+            if size > self.config.max_bytes {
+                return Err(AttachmentError::TooLarge {
+                    limit: self.config.max_bytes,
+                });
+            }
+            if *total > self.config.max_request_bytes {
+                return Err(AttachmentError::RequestTooLarge {
+                    limit: self.config.max_request_bytes,
+                });
+            }
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+
+        if let Some((_, attachment)) = staged.last_mut() {
+            attachment.size = size;
+            attachment.uploaded_at = Utc::now();
+        }
+        Ok(())
+    }
+
+    pub async fn read(&self, id: ObjectId, name: &str) -> Result<Vec<u8>, AttachmentError> {
+        Ok(fs::read(self.path_for(id, name)?).await?)
+    }
+
+    pub async fn remove_all(&self, id: ObjectId) -> Result<(), AttachmentError> {
+        match fs::remove_dir_all(self.object_dir(id)).await {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Moves a staged file to `path`, first moving a stored file at `path` aside.
+/// On success the path and the backup, if any, are added to `placed`.
+async fn place(
+    partial: &Path,
+    path: &Path,
+    placed: &mut Vec<(PathBuf, Option<PathBuf>)>,
+) -> io::Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let backup = path.with_file_name(format!(".{}.{}.old", name, Uuid::new_v4()));
+    let backup = match fs::rename(path, &backup).await {
+        Ok(()) => Some(backup),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err),
+    };
+    if let Err(err) = fs::rename(partial, path).await {
+        if let Some(backup) = &backup {
+            let _ = fs::rename(backup, path).await;
+        }
+        return Err(err);
+    }
+    placed.push((path.to_path_buf(), backup));
+    Ok(())
+}
+
+/// Undoes `place` in reverse order, so a name uploaded twice ends up with
+/// the file stored before the upload.
+async fn restore(placed: &[(PathBuf, Option<PathBuf>)]) {
+    for (path, backup) in placed.iter().rev() {
+        let result = match backup {
+            Some(backup) => fs::rename(backup, path).await,
+            None => fs::remove_file(path).await,
+        };
+        if let Err(err) = result {
+            tracing::warn!(path = %path.display(), error = %err, "failed to roll back attachment");
+        }
+    }
+}
+
+async fn discard(staged: &[(PathBuf, Attachment)]) {
+    for (partial, _) in staged {
+        let _ = fs::remove_file(partial).await;
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use common::config::{env_parse, env_var, parse_duration};
+use serde::{Deserialize, Serialize};
+
+use model::{MyObject, ObjectId};
+
+use crate::auth::{Claims, Role};
+
+const DEFAULT_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+const DEFAULT_MAX_ENTRIES: usize = 100_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Update,
+    SoftDelete,
+    HardDelete,
+    Restore,
+}
+
+/// One write as seen by the API: who did it, when, and the object before
+/// (`None` for creates) and after (`None` for hard deletes).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: u64,
+    pub object_id: ObjectId,
+    pub action: AuditAction,
+    pub actor: String,
+    pub role: Role,
+    pub at: DateTime<Utc>,
+    pub old: Option<MyObject>,
+    pub new: Option<MyObject>,
+}
+
+/// How long entries are kept. Read from `AUDIT_RETENTION` (e.g. `90d`) and
+/// `AUDIT_MAX_ENTRIES`; `0` disables the respective limit.
+#[derive(Clone)]
+pub struct AuditConfig {
+    pub retention: Option<Duration>,
+    pub max_entries: Option<usize>,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        AuditConfig {
+            retention: Some(DEFAULT_RETENTION),
+            max_entries: Some(DEFAULT_MAX_ENTRIES),
+        }
+    }
+}
+
+impl AuditConfig {
+    pub fn from_env() -> Self {
+        let mut config = AuditConfig::default();
+        match env_parse("AUDIT_RETENTION", parse_duration) {
+            Ok(Some(retention)) => config.retention = Some(retention).filter(|d| !d.is_zero()),
+            Ok(None) => {}
+            Err(err) => tracing::warn!(%err, "ignoring AUDIT_RETENTION"),
+        }
+        match env_var::<usize>("AUDIT_MAX_ENTRIES") {
+            Ok(Some(max)) => config.max_entries = Some(max).filter(|&max| max > 0),
+            Ok(None) => {}
+            Err(err) => tracing::warn!(%err, "ignoring AUDIT_MAX_ENTRIES"),
+        }
+        config
+    }
+}
+
+struct AuditEntries {
+    next_id: u64,
+    entries: VecDeque<AuditEntry>,
+    /// Recorded entries not yet written to the file.
+    pending: Vec<AuditEntry>,
+    /// Entries pruned since the file was last compacted.
+    dropped: usize,
+    persistent: bool,
+}
+
+struct AuditFile {
+    path: PathBuf,
+    file: File,
+}
+
+/// Append-only record of object writes. Entries are never modified; the
+/// oldest ones are dropped once they fall outside the retention limits.
+///
+/// A log opened with [`AuditLog::open`] also keeps every entry as one JSON
+/// line in its file. [`AuditLog::record`] only queues the entry, and
+/// [`AuditLog::flush`] writes and syncs the queue, so the disk is not hit
+/// while the caller holds other locks. The file is compacted to the
+/// retained entries when it is opened and whenever the pruned entries
+/// outnumber the retained ones, which keeps it at most about twice as
+/// large as the retained log.
+pub struct AuditLog {
+    config: AuditConfig,
+    log: Mutex<AuditEntries>,
+    /// Held while writing, so entries reach the file in id order.
+    file: Mutex<Option<AuditFile>>,
+}
+
+impl AuditLog {
+    pub fn new(config: AuditConfig) -> Self {
+        AuditLog {
+            config,
+            log: Mutex::new(AuditEntries {
+                next_id: 1,
+                entries: VecDeque::new(),
+                pending: Vec::new(),
+                dropped: 0,
+                persistent: false,
+            }),
+            file: Mutex::new(None),
+        }
+    }
+
+    /// Loads the entries of the JSON lines file at `path`, creating it if
+    /// needed, and appends new entries to it. A line that cannot be parsed,
+    /// such as one torn by a crash, is skipped. If the retention limits drop
+    /// any entries, the file is rewritten to hold only the retained ones.
+    pub fn open(config: AuditConfig, path: &Path) -> io::Result<Self> {
+        let mut entries = VecDeque::new();
+        let mut torn_tail = false;
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                torn_tail = !contents.is_empty() && !contents.ends_with('\n');
+                for (number, line) in contents.lines().enumerate() {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<AuditEntry>(line) {
+                        Ok(entry) => entries.push_back(entry),
+                        Err(err) => tracing::warn!(
+                            file = %path.display(),
+                            line = number + 1,
+                            error = %err,
+                            "skipping unreadable audit entry"
+                        ),
+                    }
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+
+        // Ids keep counting from the newest entry, even if it is pruned below.
+        let next_id = entries.iter().map(|e| e.id).max().unwrap_or(0) + 1;
+        let log = AuditLog::new(config);
+        if log.prune(&mut entries, Utc::now()) > 0 {
+            compact(path, &entries)?;
+            torn_tail = false;
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if torn_tail {
+            // Finish the torn line so the next entry starts on its own.
+            file.write_all(b"\n")?;
+        }
+        *log.log.lock().unwrap() = AuditEntries {
+            next_id,
+            entries,
+            pending: Vec::new(),
+            dropped: 0,
+            persistent: true,
+        };
+        *log.file.lock().unwrap() = Some(AuditFile {
+            path: path.to_path_buf(),
+            file,
+        });
+        Ok(log)
+    }
+
+    /// Writes the queued entries to the file in a single write and syncs
+    /// it, compacting the file instead if enough entries have been pruned.
+    /// Does nothing for a log without a file or with nothing queued.
+    pub fn flush(&self) -> io::Result<()> {
+        let mut guard = self.file.lock().unwrap();
+        let Some(audit_file) = guard.as_mut() else {
+            return Ok(());
+        };
+        let (pending, retained) = {
+            let mut log = self.log.lock().unwrap();
+            let pending = std::mem::take(&mut log.pending);
+            let retained = (log.dropped > log.entries.len()).then(|| {
+                log.dropped = 0;
+                log.entries.clone()
+            });
+            (pending, retained)
+        };
+
+        if let Some(retained) = retained {
+            // The retained entries include the queued ones.
+            match compact(&audit_file.path, &retained) {
+                Ok(()) => {
+                    audit_file.file = OpenOptions::new().append(true).open(&audit_file.path)?;
+                    return Ok(());
+                }
+                Err(err) => tracing::warn!(
+                    file = %audit_file.path.display(),
+                    error = %err,
+                    "failed to compact audit log, appending instead"
+                ),
+            }
+        }
+        if pending.is_empty() {
+            return Ok(());
+        }
+        append(&mut audit_file.file, &pending)
+    }
+
+    /// Records an entry attributed to the token subject and returns its id.
+    /// At least one of `old` and `new` must be given. For a log with a file
+    /// the entry is queued until the next [`AuditLog::flush`].
+    pub fn record(
+        &self,
+        claims: &Claims,
+        action: AuditAction,
+        old: Option<&MyObject>,
+        new: Option<&MyObject>,
+    ) -> u64 {
+        let object_id = new
+            .or(old)
+            .map(|obj| obj.id)
+            .expect("an audit entry needs the old or the new object");
+        let now = Utc::now();
+        let mut guard = self.log.lock().unwrap();
+        let log = &mut *guard;
+        let id = log.next_id;
+        log.next_id += 1;
+        let entry = AuditEntry {
+            id,
+            object_id,
+            action,
+            actor: claims.sub.clone(),
+            role: claims.role,
+            at: now,
+            old: old.cloned(),
+            new: new.cloned(),
+        };
+        if log.persistent {
+            log.pending.push(entry.clone());
+        }
+        log.entries.push_back(entry);
+        log.dropped += self.prune(&mut log.entries, now);
+        tracing::info!(
+            audit_id = id,
+            object_id = %object_id,
+            ?action,
+            actor = %claims.sub,
+            "audit entry recorded"
+        );
+        id
+    }
+
+    /// Retained entries, oldest first, optionally only those for one object.
+    pub fn entries(&self, object_id: Option<ObjectId>) -> Vec<AuditEntry> {
+        let mut log = self.log.lock().unwrap();
+        let log = &mut *log;
+        log.dropped += self.prune(&mut log.entries, Utc::now());
+        log.entries
+            .iter()
+            .filter(|e| object_id.is_none_or(|id| e.object_id == id))
+            .cloned()
+            .collect()
+    }
+
+    /// Drops the entries outside the limits and returns how many it dropped.
+    fn prune(&self, entries: &mut VecDeque<AuditEntry>, now: DateTime<Utc>) -> usize {
+        let before = entries.len();
+        if let Some(retention) = self.config.retention {
+            let cutoff = chrono::Duration::from_std(retention)
+                .ok()
+                .and_then(|retention| now.checked_sub_signed(retention));
+            if let Some(cutoff) = cutoff {
+                while entries.front().is_some_and(|e| e.at < cutoff) {
+                    entries.pop_front();
+                }
+            }
+        }
+        if let Some(max) = self.config.max_entries {
+            let excess = entries.len().saturating_sub(max);
+            entries.drain(..excess);
+        }
+        before - entries.len()
+    }
+}
+
+/// Writes the entries as lines in a single write and syncs them, so a crash
+/// loses at most the lines being written.
+fn append(file: &mut File, entries: &[AuditEntry]) -> io::Result<()> {
+    let mut lines = Vec::new();
+    for entry in entries {
+        serde_json::to_writer(&mut lines, entry)?;
+        lines.push(b'\n');
+    }
+    file.write_all(&lines)?;
+    file.sync_data()
+}
+
+/// Replaces the file at `path` with `entries`, going through a temporary
+/// file so a crash leaves either the old or the new contents.
+fn compact(path: &Path, entries: &VecDeque<AuditEntry>) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let mut tmp = File::create(&tmp_path)?;
+    for entry in entries {
+        serde_json::to_writer(&mut tmp, entry)?;
+        tmp.write_all(b"\n")?;
+    }
+    tmp.sync_all()?;
+    fs::rename(&tmp_path, path)
+}
@@ -0,0 +1,231 @@
+use std::env;
+use std::fmt;
+use std::future::{ready, Ready};
+use std::marker::PhantomData;
+
+use actix_web::dev::Payload;
+use actix_web::http::header::HeaderMap;
+use actix_web::http::StatusCode;
+use actix_web::{web, FromRequest, HttpRequest, HttpResponse, ResponseError};
+use chrono::Utc;
+use common::config::{env_parse, parse_bool};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::AppState;
+
+const DEV_SECRET: &str = "dev-secret-change-me";
+
+/// Roles are ordered: every role includes the permissions of the ones below it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Viewer,
+    Editor,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "viewer" => Ok(Role::Viewer),
+            "editor" => Ok(Role::Editor),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!("unknown role: {}", other)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub role: Role,
+    pub exp: u64,
+}
+
+/// HS256 signing secret, read from `AUTH_SECRET`.
+#[derive(Clone)]
+pub struct AuthConfig {
+    secret: String,
+}
+
+impl AuthConfig {
+    pub fn new(secret: impl Into<String>) -> Self {
+        AuthConfig {
+            secret: secret.into(),
+        }
+    }
+
+    /// Fails when `AUTH_SECRET` is unset or empty, since tokens signed with
+    /// a publicly known secret could be minted by anyone. `AUTH_DEV_MODE=1`
+    /// opts into the insecure development secret for local use.
+    pub fn from_env() -> Result<Self, String> {
+        match env::var("AUTH_SECRET") {
+            Ok(secret) if !secret.is_empty() => Ok(AuthConfig::new(secret)),
+            _ if matches!(env_parse("AUTH_DEV_MODE", parse_bool), Ok(Some(true))) => {
+                tracing::warn!("AUTH_SECRET is not set, using the insecure development secret");
+                Ok(AuthConfig::new(DEV_SECRET))
+            }
+            _ => Err("AUTH_SECRET is not set; set AUTH_DEV_MODE=1 to use the insecure development secret".to_string()),
+        }
+    }
+
+    pub fn issue_token(&self, subject: &str, role: Role, ttl_secs: u64) -> String {
+        let claims = Claims {
+            sub: subject.to_string(),
+            role,
+            exp: Utc::now().timestamp().max(0) as u64 + ttl_secs,
+        };
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(self.secret.as_bytes()),
+        )
+        .expect("HS256 encoding with a byte secret cannot fail")
+    }
+
+    pub fn verify(&self, token: &str) -> Result<Claims, AuthError> {
+        decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(self.secret.as_bytes()),
+            &Validation::default(),
+        )
+        .map(|data| data.claims)
+        .map_err(|e| AuthError::InvalidToken(e.to_string()))
+    }
+}
+
+#[derive(Debug)]
+pub enum AuthError {
+    MissingToken,
+    InvalidToken(String),
+    Forbidden { required: Role, actual: Role },
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::MissingToken => write!(f, "Missing bearer token"),
+            AuthError::InvalidToken(reason) => write!(f, "Invalid token: {}", reason),
+            AuthError::Forbidden { required, actual } => write!(
+                f,
+                "Role '{}' is not allowed, '{}' or higher is required",
+                actual.as_str(),
+                required.as_str()
+            ),
+        }
+    }
+}
+
+impl ResponseError for AuthError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AuthError::MissingToken | AuthError::InvalidToken(_) => StatusCode::UNAUTHORIZED,
+            AuthError::Forbidden { .. } => StatusCode::FORBIDDEN,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let body = match self {
+            AuthError::Forbidden { required, actual } => json!({
+                "error": "forbidden",
+                "message": self.to_string(),
+                "required_role": required,
+                "role": actual,
+            }),
+            _ => json!({
+                "error": "unauthorized",
+                "message": self.to_string(),
+            }),
+        };
+        HttpResponse::build(self.status_code()).json(body)
+    }
+}
+
+/// Marker types naming the minimum role an endpoint needs.
+pub mod require {
+    use super::Role;
+
+    pub trait MinRole {
+        const ROLE: Role;
+    }
+
+    pub struct Viewer;
+    pub struct Editor;
+    pub struct Admin;
+
+    impl MinRole for Viewer {
+        const ROLE: Role = Role::Viewer;
+    }
+
+    impl MinRole for Editor {
+        const ROLE: Role = Role::Editor;
+    }
+
+    impl MinRole for Admin {
+        const ROLE: Role = Role::Admin;
+    }
+}
+
+/// Extractor guarding a handler: `_auth: Authorized<require::Editor>` rejects
+/// the request with 401/403 unless the bearer token carries editor or above.
+pub struct Authorized<R: require::MinRole> {
+    pub claims: Claims,
+    _role: PhantomData<R>,
+}
+
+/// The token of an `Authorization: Bearer <token>` header, unverified.
+pub(crate) fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+fn authorize(req: &HttpRequest, required: Role) -> Result<Claims, AuthError> {
+    let state = req
+        .app_data::<web::Data<AppState>>()
+        .expect("AppState must be registered as app data");
+    let token = bearer_token(req.headers()).ok_or(AuthError::MissingToken)?;
+
+    let claims = state.auth.verify(token)?;
+    if claims.role < required {
+        tracing::warn!(
+            subject = %claims.sub,
+            role = claims.role.as_str(),
+            required = required.as_str(),
+            "insufficient role"
+        );
+        return Err(AuthError::Forbidden {
+            required,
+            actual: claims.role,
+        });
+    }
+    Ok(claims)
+}
+
+impl<R: require::MinRole> FromRequest for Authorized<R> {
+    type Error = AuthError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(authorize(req, R::ROLE).map(|claims| Authorized {
+            claims,
+            _role: PhantomData,
+        }))
+    }
+}
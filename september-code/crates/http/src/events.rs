@@ -0,0 +1,167 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use actix_web::web::Bytes;
+use futures_util::stream::{self, Stream};
+use serde::Serialize;
+use tokio::sync::{broadcast, watch};
+use tokio::time::{interval_at, Instant, Interval};
+
+use model::MyObject;
+
+const HISTORY_CAPACITY: usize = 1024;
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Deleted,
+    Restored,
+}
+
+impl ChangeKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ChangeKind::Created => "created",
+            ChangeKind::Updated => "updated",
+            ChangeKind::Deleted => "deleted",
+            ChangeKind::Restored => "restored",
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct ChangeEvent {
+    pub id: u64,
+    pub kind: ChangeKind,
+    pub object: MyObject,
+}
+
+impl ChangeEvent {
+    fn to_sse(&self) -> Bytes {
+        let data = serde_json::to_string(self).unwrap_or_default();
+        Bytes::from(format!(
+            "id: {}\nevent: {}\ndata: {}\n\n",
+            self.id,
+            self.kind.as_str(),
+            data
+        ))
+    }
+}
+
+struct EventLog {
+    next_id: u64,
+    history: VecDeque<ChangeEvent>,
+}
+
+/// Fan-out of object change events. A bounded history is kept so clients
+/// reconnecting with `Last-Event-ID` can catch up on what they missed.
+pub struct EventBus {
+    log: Mutex<EventLog>,
+    sender: broadcast::Sender<ChangeEvent>,
+    closed: watch::Sender<bool>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(HISTORY_CAPACITY);
+        EventBus {
+            log: Mutex::new(EventLog {
+                next_id: 1,
+                history: VecDeque::with_capacity(HISTORY_CAPACITY),
+            }),
+            sender,
+            closed: watch::channel(false).0,
+        }
+    }
+}
+
+impl EventBus {
+    pub fn publish(&self, kind: ChangeKind, object: &MyObject) {
+        // Sending while holding the lock keeps ids ordered for subscribers.
+        let mut log = self.log.lock().unwrap();
+        let event = ChangeEvent {
+            id: log.next_id,
+            kind,
+            object: object.clone(),
+        };
+        log.next_id += 1;
+        if log.history.len() == HISTORY_CAPACITY {
+            log.history.pop_front();
+        }
+        log.history.push_back(event.clone());
+        let _ = self.sender.send(event);
+    }
+
+    /// Ends every open SSE stream, e.g. so shutdown does not wait on them.
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+
+    /// Returns buffered events newer than `last_event_id` plus a receiver for
+    /// everything published afterwards, with no gap or overlap between them.
+    pub fn subscribe(
+        &self,
+        last_event_id: Option<u64>,
+    ) -> (Vec<ChangeEvent>, broadcast::Receiver<ChangeEvent>) {
+        let log = self.log.lock().unwrap();
+        let receiver = self.sender.subscribe();
+        let backlog = match last_event_id {
+            Some(last) => log
+                .history
+                .iter()
+                .filter(|e| e.id > last)
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+        (backlog, receiver)
+    }
+
+    /// SSE body: replayed backlog, then live events interleaved with
+    /// heartbeat comments so idle connections are not dropped by proxies.
+    pub fn sse_stream(
+        &self,
+        last_event_id: Option<u64>,
+        heartbeat: Duration,
+    ) -> impl Stream<Item = Result<Bytes, actix_web::Error>> {
+        let (backlog, receiver) = self.subscribe(last_event_id);
+        let state = SseState {
+            backlog: backlog.into(),
+            receiver,
+            heartbeat: interval_at(Instant::now() + heartbeat, heartbeat),
+            closed: self.closed.subscribe(),
+        };
+
+        stream::unfold(state, |mut state| async move {
+            if *state.closed.borrow() {
+                return None;
+            }
+            if let Some(event) = state.backlog.pop_front() {
+                return Some((Ok(event.to_sse()), state));
+            }
+            let chunk = tokio::select! {
+                received = state.receiver.recv() => match received {
+                    Ok(event) => event.to_sse(),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        Bytes::from(format!(": lagged, {} events skipped\n\n", skipped))
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                },
+                _ = state.heartbeat.tick() => Bytes::from_static(b": heartbeat\n\n"),
+                _ = state.closed.changed() => return None,
+            };
+            Some((Ok(chunk), state))
+        })
+    }
+}
+
+struct SseState {
+    backlog: VecDeque<ChangeEvent>,
+    receiver: broadcast::Receiver<ChangeEvent>,
+    heartbeat: Interval,
+    closed: watch::Receiver<bool>,
+}
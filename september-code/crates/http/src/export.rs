@@ -0,0 +1,117 @@
+use actix_web::web::{self, Bytes};
+use futures_util::stream::{self, Stream};
+use serde::Deserialize;
+
+use model::{MyObject, ObjectId};
+
+use crate::AppState;
+
+/// Objects serialized per repository lock, so writers are never blocked for
+/// the whole export.
+const BATCH_SIZE: usize = 256;
+
+const CSV_HEADER: &str = "id,name,version,created_at,updated_at,deleted_at,attachments\n";
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "objects.csv",
+            ExportFormat::Ndjson => "objects.ndjson",
+        }
+    }
+
+    fn header(self) -> Option<Bytes> {
+        match self {
+            ExportFormat::Csv => Some(Bytes::from_static(CSV_HEADER.as_bytes())),
+            ExportFormat::Ndjson => None,
+        }
+    }
+
+    fn write_row(self, obj: &MyObject, out: &mut String) {
+        match self {
+            ExportFormat::Csv => {
+                let deleted_at = obj.deleted_at.map(|t| t.to_rfc3339()).unwrap_or_default();
+                out.push_str(&format!(
+                    "{},{},{},{},{},{},{}\n",
+                    obj.id,
+                    csv_field(&obj.name),
+                    obj.version,
+                    obj.created_at.to_rfc3339(),
+                    obj.updated_at.to_rfc3339(),
+                    deleted_at,
+                    obj.attachments.len()
+                ));
+            }
+            ExportFormat::Ndjson => {
+                out.push_str(&serde_json::to_string(obj).unwrap_or_default());
+                out.push('\n');
+            }
+        }
+    }
+}
+
+/// Quotes a field when it contains a delimiter, quote or line break (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+enum Cursor {
+    Header,
+    After(Option<ObjectId>),
+    Done,
+}
+
+/// Streams every object in id order, one chunk per batch. Objects written
+/// while the export runs show up if their id has not been passed yet.
+pub fn export_stream(
+    state: web::Data<AppState>,
+    format: ExportFormat,
+    include_deleted: bool,
+) -> impl Stream<Item = Result<Bytes, actix_web::Error>> {
+    stream::unfold(Cursor::Header, move |cursor| {
+        let state = state.clone();
+        async move {
+            match cursor {
+                Cursor::Header => {
+                    let chunk = format.header().unwrap_or_default();
+                    Some((Ok(chunk), Cursor::After(None)))
+                }
+                Cursor::After(after) => {
+                    let batch = state.repository.lock().unwrap().batch_after(
+                        after,
+                        include_deleted,
+                        BATCH_SIZE,
+                    );
+                    let next = match batch.last() {
+                        Some(last) if batch.len() == BATCH_SIZE => Cursor::After(Some(last.id)),
+                        _ => Cursor::Done,
+                    };
+                    let mut chunk = String::new();
+                    for obj in &batch {
+                        format.write_row(obj, &mut chunk);
+                    }
+                    Some((Ok(Bytes::from(chunk)), next))
+                }
+                Cursor::Done => None,
+            }
+        }
+    })
+}
@@ -1,11 +1,160 @@
-use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
+use actix_multipart::Multipart;
+use actix_web::middleware::from_fn;
+use actix_web::{delete, get, patch, post, put, web, HttpRequest, HttpResponse, Responder};
+use serde::Deserialize;
 use serde_json::json;
-use std::sync::{Arc, Mutex};
+use std::sync::Mutex;
 
-use model::MyObject;
+use model::api::v1;
+use model::{MyObject, ObjectId};
+
+pub mod attachments;
+pub mod audit;
+pub mod auth;
+pub mod events;
+pub mod export;
+pub mod rate_limit;
+pub mod repository;
+pub mod request_id;
+pub mod search;
+pub mod security;
+pub mod versioning;
+
+use attachments::{AttachmentConfig, AttachmentStore};
+use audit::{AuditAction, AuditConfig, AuditLog};
+use auth::{require, AuthConfig, Authorized};
+use events::{ChangeKind, EventBus};
+use export::ExportFormat;
+use rate_limit::{RateLimitConfig, RateLimiter};
+use repository::{ObjectRepository, RepositoryError};
+use security::SecurityConfig;
+use versioning::{ApiVersion, VersionedJson};
 
 pub struct AppState {
-    pub objects: Arc<Mutex<Vec<MyObject>>>,
+    pub repository: Mutex<ObjectRepository>,
+    pub events: EventBus,
+    pub attachments: AttachmentStore,
+    pub auth: AuthConfig,
+    pub rate_limiter: RateLimiter,
+    pub audit: AuditLog,
+}
+
+impl AppState {
+    /// Builds the state with settings taken from the environment; use the
+    /// `with_*` methods to override them. The signing secret is passed in
+    /// since reading it from the environment can fail.
+    pub fn new(objects: Vec<MyObject>, auth: AuthConfig) -> Self {
+        AppState {
+            repository: Mutex::new(ObjectRepository::new(objects)),
+            events: EventBus::default(),
+            attachments: AttachmentStore::new(AttachmentConfig::from_env()),
+            auth,
+            rate_limiter: RateLimiter::new(RateLimitConfig::from_env()),
+            audit: AuditLog::new(AuditConfig::from_env()),
+        }
+    }
+
+    pub fn with_attachment_config(mut self, config: AttachmentConfig) -> Self {
+        self.attachments = AttachmentStore::new(config);
+        self
+    }
+
+    pub fn with_rate_limit_config(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limiter = RateLimiter::new(config);
+        self
+    }
+
+    pub fn with_audit_config(mut self, config: AuditConfig) -> Self {
+        self.audit = AuditLog::new(config);
+        self
+    }
+
+    pub fn with_audit_log(mut self, log: AuditLog) -> Self {
+        self.audit = log;
+        self
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+}
+
+#[derive(Deserialize)]
+pub struct ListQuery {
+    #[serde(default)]
+    pub include_deleted: bool,
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub include_deleted: bool,
+}
+
+pub struct ObjectPatch {
+    pub name: Option<String>,
+    pub version: u64,
+}
+
+impl From<v1::ObjectPatch> for ObjectPatch {
+    fn from(patch: v1::ObjectPatch) -> Self {
+        ObjectPatch {
+            name: patch.name,
+            version: patch.version,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DeleteQuery {
+    #[serde(default)]
+    pub hard: bool,
+}
+
+#[derive(Deserialize)]
+pub struct AuditQuery {
+    pub object_id: Option<ObjectId>,
+}
+
+/// Writes the audit entries queued by a write to disk on the blocking
+/// pool, so the fsync runs after the repository lock is released and off
+/// the worker thread. The write itself already happened, so a failing
+/// audit file is logged instead of failing the request.
+async fn persist_audit(data: &web::Data<AppState>) {
+    let state = data.clone();
+    let result = web::block(move || state.audit.flush())
+        .await
+        .unwrap_or_else(|err| Err(std::io::Error::other(err)));
+    if let Err(err) = result {
+        tracing::error!(error = %err, "failed to persist audit entries");
+    }
+}
+
+fn error_response(err: RepositoryError) -> HttpResponse {
+    match err {
+        RepositoryError::NotFound(id) => {
+            HttpResponse::NotFound().body(format!("No object found with id: {}", id))
+        }
+        RepositoryError::AlreadyExists(id) => {
+            HttpResponse::Conflict().body(format!("An object with id {} already exists", id))
+        }
+        RepositoryError::NotDeleted(id) => {
+            HttpResponse::Conflict().body(format!("Object with id {} is not deleted", id))
+        }
+        RepositoryError::VersionConflict {
+            id,
+            expected,
+            actual,
+        } => HttpResponse::Conflict().body(format!(
+            "Version conflict for object {}: expected version {}, current version is {}",
+            id, expected, actual
+        )),
+    }
 }
 
 #[get("/hello")]
@@ -23,66 +172,386 @@ pub async fn manual_hello() -> impl Responder {
 }
 
 #[get("/objects")]
-pub async fn get_all_objects(data: web::Data<AppState>) -> impl Responder {
-    let objects = data.objects.lock().unwrap();
-    HttpResponse::Ok().json(&*objects)
+pub async fn get_all_objects(
+    _auth: Authorized<require::Viewer>,
+    version: ApiVersion,
+    data: web::Data<AppState>,
+    query: web::Query<ListQuery>,
+) -> impl Responder {
+    let objects = data.repository.lock().unwrap().list(query.include_deleted);
+    let total = objects.len();
+    let page = objects
+        .into_iter()
+        .skip(query.offset)
+        .take(query.limit.unwrap_or(usize::MAX));
+    HttpResponse::Ok()
+        .insert_header(("X-Total-Count", total.to_string()))
+        .json(version.objects(page))
+}
+
+// Must be registered before `/objects/{id}` so the literal segment is not parsed as an id.
+#[get("/objects/search")]
+pub async fn search_objects(
+    _auth: Authorized<require::Viewer>,
+    version: ApiVersion,
+    data: web::Data<AppState>,
+    query: web::Query<SearchQuery>,
+) -> impl Responder {
+    if search::tokenize(&query.q).is_empty() {
+        return HttpResponse::BadRequest().body("Query parameter 'q' must contain a search term");
+    }
+
+    let repository = data.repository.lock().unwrap();
+    let results: Vec<_> = repository
+        .search(&query.q)
+        .into_iter()
+        .map(|(obj, score)| json!({"score": score, "object": version.object(obj.clone())}))
+        .collect();
+    HttpResponse::Ok().json(results)
+}
+
+/// Streams all objects as CSV or NDJSON without buffering the full result.
+#[get("/objects/export")]
+pub async fn export_objects(
+    _auth: Authorized<require::Viewer>,
+    data: web::Data<AppState>,
+    query: web::Query<ExportQuery>,
+) -> impl Responder {
+    let ExportQuery {
+        format,
+        include_deleted,
+    } = query.into_inner();
+    HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"{}\"", format.file_name()),
+        ))
+        .streaming(export::export_stream(data, format, include_deleted))
+}
+
+/// Server-sent change events. Reconnecting clients send `Last-Event-ID` to
+/// replay the events they missed.
+#[get("/objects/stream")]
+pub async fn stream_objects(
+    _auth: Authorized<require::Viewer>,
+    req: HttpRequest,
+    data: web::Data<AppState>,
+) -> impl Responder {
+    let last_event_id = req
+        .headers()
+        .get("Last-Event-ID")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok());
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(
+            data.events
+                .sse_stream(last_event_id, events::HEARTBEAT_INTERVAL),
+        )
 }
 
 #[get("/objects/{id}")]
-pub async fn get_object(data: web::Data<AppState>, path: web::Path<u32>) -> impl Responder {
+pub async fn get_object(
+    _auth: Authorized<require::Viewer>,
+    version: ApiVersion,
+    data: web::Data<AppState>,
+    path: web::Path<ObjectId>,
+) -> impl Responder {
     let id = path.into_inner();
-    let objects = data.objects.lock().unwrap();
-    if let Some(obj) = objects.iter().find(|o| o.id == id) {
-        HttpResponse::Ok().json(obj)
-    } else {
-        HttpResponse::NotFound().body(format!("No object found with id: {}", id))
+    let repository = data.repository.lock().unwrap();
+    match repository.get(id) {
+        Some(obj) => HttpResponse::Ok().json(version.object(obj.clone())),
+        None => error_response(RepositoryError::NotFound(id)),
     }
 }
 
 #[post("/objects")]
-pub async fn create_object(data: web::Data<AppState>, obj: web::Json<MyObject>) -> impl Responder {
-    let mut objects = data.objects.lock().unwrap();
-    objects.push(obj.0.clone());
-    HttpResponse::Ok().json(obj.0)
+pub async fn create_object(
+    auth: Authorized<require::Editor>,
+    version: ApiVersion,
+    data: web::Data<AppState>,
+    obj: VersionedJson<MyObject>,
+) -> impl Responder {
+    let result = data
+        .repository
+        .lock()
+        .unwrap()
+        .create(obj.into_inner())
+        .inspect(|created| {
+            data.audit
+                .record(&auth.claims, AuditAction::Create, None, Some(created));
+            data.events.publish(ChangeKind::Created, created);
+        });
+    persist_audit(&data).await;
+    match result {
+        Ok(created) => HttpResponse::Ok().json(version.object(created)),
+        Err(err) => error_response(err),
+    }
 }
 
 #[put("/objects/{id}")]
 pub async fn update_object(
+    auth: Authorized<require::Editor>,
+    version: ApiVersion,
     data: web::Data<AppState>,
-    path: web::Path<u32>,
-    obj_update: web::Json<MyObject>,
+    path: web::Path<ObjectId>,
+    obj_update: VersionedJson<MyObject>,
 ) -> impl Responder {
     let id = path.into_inner();
-    let mut objects = data.objects.lock().unwrap();
-    if let Some(pos) = objects.iter().position(|o| o.id == id) {
-        objects[pos] = obj_update.0.clone();
-        HttpResponse::Ok().json(objects[pos].clone())
-    } else {
-        HttpResponse::NotFound().body(format!("No object found with id: {}", id))
+    let result = {
+        let mut repository = data.repository.lock().unwrap();
+        let old = repository.get(id).cloned();
+        repository
+            .update(id, obj_update.into_inner())
+            .inspect(|obj| {
+                data.audit
+                    .record(&auth.claims, AuditAction::Update, old.as_ref(), Some(obj));
+                data.events.publish(ChangeKind::Updated, obj);
+            })
+    };
+    persist_audit(&data).await;
+    match result {
+        Ok(obj) => HttpResponse::Ok().json(version.object(obj)),
+        Err(err) => error_response(err),
     }
 }
 
+#[patch("/objects/{id}")]
+pub async fn patch_object(
+    auth: Authorized<require::Editor>,
+    version: ApiVersion,
+    data: web::Data<AppState>,
+    path: web::Path<ObjectId>,
+    patch: VersionedJson<ObjectPatch>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let ObjectPatch {
+        name,
+        version: expected,
+    } = patch.into_inner();
+    let result = {
+        let mut repository = data.repository.lock().unwrap();
+        let old = repository.get(id).cloned();
+        repository.patch(id, expected, name).inspect(|obj| {
+            data.audit
+                .record(&auth.claims, AuditAction::Update, old.as_ref(), Some(obj));
+            data.events.publish(ChangeKind::Updated, obj);
+        })
+    };
+    persist_audit(&data).await;
+    match result {
+        Ok(obj) => HttpResponse::Ok().json(version.object(obj)),
+        Err(err) => error_response(err),
+    }
+}
+
+/// Soft delete by default; `?hard=true` removes the object permanently.
 #[delete("/objects/{id}")]
-pub async fn delete_object(data: web::Data<AppState>, path: web::Path<u32>) -> impl Responder {
+pub async fn delete_object(
+    auth: Authorized<require::Admin>,
+    version: ApiVersion,
+    data: web::Data<AppState>,
+    path: web::Path<ObjectId>,
+    query: web::Query<DeleteQuery>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let result = {
+        let mut repository = data.repository.lock().unwrap();
+        let old = repository.get_including_deleted(id).cloned();
+        let result = if query.hard {
+            repository.hard_delete(id)
+        } else {
+            repository.soft_delete(id)
+        };
+        if let Ok(deleted) = &result {
+            let (action, new) = if query.hard {
+                (AuditAction::HardDelete, None)
+            } else {
+                (AuditAction::SoftDelete, Some(deleted))
+            };
+            data.audit.record(&auth.claims, action, old.as_ref(), new);
+        }
+        result
+    };
+    persist_audit(&data).await;
+
+    match result {
+        Ok(deleted_obj) => {
+            // The delete is already committed and audited, so a leftover
+            // attachment directory is only logged instead of failing the request.
+            if query.hard {
+                if let Err(err) = data.attachments.remove_all(id).await {
+                    tracing::warn!(%id, error = ?err, "failed to remove attachments of hard-deleted object");
+                }
+            }
+            data.events.publish(ChangeKind::Deleted, &deleted_obj);
+            HttpResponse::Ok().json(json!({
+                "deleted": version.object(deleted_obj),
+                "hard": query.hard,
+            }))
+        }
+        Err(err) => error_response(err),
+    }
+}
+
+#[post("/objects/{id}/restore")]
+pub async fn restore_object(
+    auth: Authorized<require::Admin>,
+    version: ApiVersion,
+    data: web::Data<AppState>,
+    path: web::Path<ObjectId>,
+) -> impl Responder {
     let id = path.into_inner();
-    let mut objects = data.objects.lock().unwrap();
-    if let Some(pos) = objects.iter().position(|o| o.id == id) {
-        let deleted_obj = objects.remove(pos);
-        HttpResponse::Ok().json(json!({"deleted": deleted_obj}))
-    } else {
-        HttpResponse::NotFound().body(format!("No object found with id: {}", id))
+    let result = {
+        let mut repository = data.repository.lock().unwrap();
+        let old = repository.get_including_deleted(id).cloned();
+        repository.restore(id).inspect(|obj| {
+            data.audit
+                .record(&auth.claims, AuditAction::Restore, old.as_ref(), Some(obj));
+            data.events.publish(ChangeKind::Restored, obj);
+        })
+    };
+    persist_audit(&data).await;
+    match result {
+        Ok(obj) => HttpResponse::Ok().json(version.object(obj)),
+        Err(err) => error_response(err),
     }
 }
 
+#[post("/objects/{id}/attachments")]
+pub async fn upload_attachments(
+    auth: Authorized<require::Editor>,
+    version: ApiVersion,
+    data: web::Data<AppState>,
+    path: web::Path<ObjectId>,
+    payload: Multipart,
+) -> impl Responder {
+    let id = path.into_inner();
+    if data.repository.lock().unwrap().get(id).is_none() {
+        return error_response(RepositoryError::NotFound(id));
+    }
+
+    // The repository lock is not held while streaming the upload to disk.
+    let saved = match data.attachments.save_multipart(id, payload).await {
+        Ok(saved) => saved,
+        Err(err) => return err.to_response(),
+    };
+
+    let result = {
+        let mut repository = data.repository.lock().unwrap();
+        let old = repository.get(id).cloned();
+        repository.add_attachments(id, saved).inspect(|obj| {
+            data.audit
+                .record(&auth.claims, AuditAction::Update, old.as_ref(), Some(obj));
+            data.events.publish(ChangeKind::Updated, obj);
+        })
+    };
+    persist_audit(&data).await;
+    match result {
+        Ok(obj) => HttpResponse::Created().json(version.object(obj)),
+        Err(err) => error_response(err),
+    }
+}
+
+#[get("/objects/{id}/attachments/{name}")]
+pub async fn download_attachment(
+    _auth: Authorized<require::Viewer>,
+    data: web::Data<AppState>,
+    path: web::Path<(ObjectId, String)>,
+) -> impl Responder {
+    let (id, name) = path.into_inner();
+    let attachment = {
+        let repository = data.repository.lock().unwrap();
+        match repository.get(id) {
+            Some(obj) => obj.attachment(&name).cloned(),
+            None => return error_response(RepositoryError::NotFound(id)),
+        }
+    };
+    let Some(attachment) = attachment else {
+        return HttpResponse::NotFound()
+            .body(format!("No attachment named {} on object {}", name, id));
+    };
+
+    match data.attachments.read(id, &attachment.name).await {
+        Ok(bytes) => HttpResponse::Ok()
+            .content_type(attachment.content_type)
+            .insert_header((
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", attachment.name),
+            ))
+            .body(bytes),
+        Err(err) => err.to_response(),
+    }
+}
+
+/// Audit trail of object writes, oldest first; `?object_id=` narrows it to
+/// one object.
+#[get("/audit")]
+pub async fn get_audit_log(
+    _auth: Authorized<require::Admin>,
+    data: web::Data<AppState>,
+    query: web::Query<AuditQuery>,
+) -> impl Responder {
+    HttpResponse::Ok().json(data.audit.entries(query.object_id))
+}
+
+/// Registers all routes with settings from [`SecurityConfig::load`].
 pub fn configure(cfg: &mut web::ServiceConfig) {
-    cfg.service(hello)
-        .service(echo)
-        .service(get_all_objects)
+    configure_with(cfg, &SecurityConfig::load());
+}
+
+/// Registers the objects API, which is served both under every
+/// `/api/v<N>` prefix and at the deprecated unprefixed paths.
+fn configure_objects(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_all_objects)
+        .service(search_objects)
+        .service(export_objects)
+        .service(stream_objects)
         .service(get_object)
         .service(create_object)
         .service(update_object)
+        .service(patch_object)
         .service(delete_object)
-        .route("/hey", web::get().to(manual_hello));
+        .service(restore_object)
+        .service(upload_attachments)
+        .service(download_attachment);
 }
 
-
+/// Registers all routes behind the request id, CORS, security header and
+/// rate limiting middleware (outermost first).
+pub fn configure_with(cfg: &mut web::ServiceConfig, security: &SecurityConfig) {
+    let mut scope = web::scope("")
+        .wrap(from_fn(rate_limit::middleware))
+        .wrap(security.headers())
+        .wrap(security.cors())
+        .wrap(from_fn(request_id::middleware))
+        .service(hello)
+        .service(echo)
+        .service(get_audit_log)
+        .route("/hey", web::get().to(manual_hello));
+    for &version in ApiVersion::SUPPORTED {
+        scope = scope.service(
+            web::scope(&version.prefix())
+                .app_data(version)
+                .wrap(from_fn(versioning::middleware))
+                .configure(configure_objects),
+        );
+    }
+    cfg.service(
+        scope
+            .service(
+                web::scope("/api/{version}")
+                    .default_service(web::to(versioning::unsupported_version)),
+            )
+            // Catches every remaining path, so it must stay last.
+            .service(
+                web::scope("")
+                    .app_data(ApiVersion::LEGACY)
+                    .wrap(from_fn(versioning::legacy_middleware))
+                    .configure(configure_objects),
+            ),
+    );
+}
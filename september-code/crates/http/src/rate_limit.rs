@@ -0,0 +1,123 @@
+use std::time::{Duration, Instant};
+
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{web, Error, HttpResponse};
+use common::config::{env_parse, env_var, parse_bool};
+use serde_json::json;
+
+use crate::auth::{bearer_token, AuthConfig};
+use crate::AppState;
+
+/// `burst` requests may be made at once; afterwards tokens come back at
+/// `refill_per_sec`. Clients with a valid bearer token are limited per token
+/// subject, everyone else per peer IP.
+#[derive(Clone)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub burst: u32,
+    pub refill_per_sec: f64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            enabled: true,
+            burst: 60,
+            refill_per_sec: 10.0,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Reads `RATE_LIMIT_ENABLED`, `RATE_LIMIT_BURST` and
+    /// `RATE_LIMIT_REFILL_PER_SEC`.
+    pub fn from_env() -> Self {
+        let mut config = RateLimitConfig::default();
+        match env_parse("RATE_LIMIT_ENABLED", parse_bool) {
+            Ok(Some(enabled)) => config.enabled = enabled,
+            Ok(None) => {}
+            Err(err) => tracing::warn!(%err, "ignoring RATE_LIMIT_ENABLED"),
+        }
+        match env_var("RATE_LIMIT_BURST") {
+            Ok(Some(burst)) => config.burst = burst,
+            Ok(None) => {}
+            Err(err) => tracing::warn!(%err, "ignoring RATE_LIMIT_BURST"),
+        }
+        match env_var("RATE_LIMIT_REFILL_PER_SEC") {
+            Ok(Some(rate)) => config.refill_per_sec = rate,
+            Ok(None) => {}
+            Err(err) => tracing::warn!(%err, "ignoring RATE_LIMIT_REFILL_PER_SEC"),
+        }
+        config
+    }
+}
+
+/// Per-client token buckets, backed by the shared `common::RateLimiter`.
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    limiter: common::RateLimiter,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        let limiter = common::RateLimiter::new(config.burst, config.refill_per_sec);
+        RateLimiter { config, limiter }
+    }
+
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        self.limiter.check(key)
+    }
+
+    pub fn check_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        self.limiter.check_at(key, now)
+    }
+}
+
+/// Only verified identities are used as keys: anything taken from an
+/// unchecked header would let a client pick a fresh bucket per request.
+fn client_key(req: &ServiceRequest, auth: &AuthConfig) -> String {
+    if let Some(claims) = bearer_token(req.headers()).and_then(|token| auth.verify(token).ok()) {
+        return format!("sub:{}", claims.sub);
+    }
+    // The socket address is used rather than X-Forwarded-For, which clients can forge.
+    match req.peer_addr() {
+        Some(addr) => format!("ip:{}", addr.ip()),
+        None => "ip:unknown".to_string(),
+    }
+}
+
+pub async fn middleware(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let state = req
+        .app_data::<web::Data<AppState>>()
+        .expect("AppState must be registered as app data");
+    let limiter = &state.rate_limiter;
+
+    if limiter.config().enabled {
+        let key = client_key(&req, &state.auth);
+        if let Err(wait) = limiter.check(&key) {
+            let retry_after = wait.as_secs_f64().ceil().min(u32::MAX as f64) as u64;
+            tracing::warn!(client = %key, retry_after, "rate limit exceeded");
+            let response = HttpResponse::TooManyRequests()
+                .insert_header(("Retry-After", retry_after.to_string()))
+                .json(json!({
+                    "error": "rate_limited",
+                    "message": "Too many requests",
+                    "retry_after_secs": retry_after,
+                }));
+            return Ok(req.into_response(response).map_into_right_body());
+        }
+    }
+
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::ops::Bound::{Excluded, Unbounded};
+use std::path::Path;
+
+use chrono::Utc;
+use model::{Attachment, MyObject, ObjectId};
+
+use crate::search::SearchIndex;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RepositoryError {
+    NotFound(ObjectId),
+    NotDeleted(ObjectId),
+    /// The id is taken, by a live or a soft-deleted object.
+    AlreadyExists(ObjectId),
+    VersionConflict {
+        id: ObjectId,
+        expected: u64,
+        actual: u64,
+    },
+}
+
+/// In-memory object store, ordered by id. Soft-deleted objects stay in
+/// `objects` with `deleted_at` set but are hidden from lookups and the
+/// search index.
+pub struct ObjectRepository {
+    objects: BTreeMap<ObjectId, MyObject>,
+    index: SearchIndex,
+}
+
+/// Marks a write: bumps the version and the modification time.
+fn touch(obj: &mut MyObject) {
+    obj.version += 1;
+    obj.updated_at = Utc::now();
+}
+
+impl ObjectRepository {
+    pub fn new(objects: Vec<MyObject>) -> Self {
+        let index = SearchIndex::from_objects(objects.iter().filter(|o| !o.is_deleted()));
+        let objects = objects.into_iter().map(|o| (o.id, o)).collect();
+        ObjectRepository { objects, index }
+    }
+
+    /// Reads a snapshot written by [`ObjectRepository::save_snapshot`];
+    /// `Ok(None)` when the file does not exist yet.
+    pub fn load_snapshot(path: &Path) -> io::Result<Option<Vec<MyObject>>> {
+        match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes all objects, including soft-deleted ones, to `path`. The data is
+    /// written to a temporary file first so a crash never leaves a torn snapshot.
+    pub fn save_snapshot(&self, path: &Path) -> io::Result<usize> {
+        let objects: Vec<&MyObject> = self.objects.values().collect();
+        let json = serde_json::to_vec_pretty(&objects)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, path)?;
+        Ok(self.objects.len())
+    }
+
+    pub fn list(&self, include_deleted: bool) -> Vec<MyObject> {
+        self.objects
+            .values()
+            .filter(|o| include_deleted || !o.is_deleted())
+            .cloned()
+            .collect()
+    }
+
+    /// Up to `limit` objects with ids greater than `after`, in id order. Lets
+    /// callers walk the whole store in batches without holding the lock.
+    pub fn batch_after(
+        &self,
+        after: Option<ObjectId>,
+        include_deleted: bool,
+        limit: usize,
+    ) -> Vec<MyObject> {
+        let range = match after {
+            Some(after) => self.objects.range((Excluded(after), Unbounded)),
+            None => self.objects.range(..),
+        };
+        range
+            .map(|(_, o)| o)
+            .filter(|o| include_deleted || !o.is_deleted())
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    pub fn get(&self, id: ObjectId) -> Option<&MyObject> {
+        self.objects.get(&id).filter(|o| !o.is_deleted())
+    }
+
+    /// Like [`ObjectRepository::get`] but also finds soft-deleted objects.
+    pub fn get_including_deleted(&self, id: ObjectId) -> Option<&MyObject> {
+        self.objects.get(&id)
+    }
+
+    pub fn create(&mut self, mut obj: MyObject) -> Result<MyObject, RepositoryError> {
+        if self.objects.contains_key(&obj.id) {
+            return Err(RepositoryError::AlreadyExists(obj.id));
+        }
+        let now = Utc::now();
+        obj.deleted_at = None;
+        obj.version = 1;
+        obj.created_at = now;
+        obj.updated_at = now;
+        obj.attachments.clear();
+        self.index.insert(&obj);
+        self.objects.insert(obj.id, obj.clone());
+        Ok(obj)
+    }
+
+    /// Replaces the object; `obj.version` must match the stored version.
+    pub fn update(&mut self, id: ObjectId, mut obj: MyObject) -> Result<MyObject, RepositoryError> {
+        let stored = self.live_mut(id)?;
+        check_version(stored, obj.version)?;
+        obj.id = id;
+        obj.version = stored.version;
+        obj.created_at = stored.created_at;
+        obj.deleted_at = None;
+        obj.attachments = std::mem::take(&mut stored.attachments);
+        touch(&mut obj);
+        *stored = obj.clone();
+        self.index.remove(id);
+        self.index.insert(&obj);
+        Ok(obj)
+    }
+
+    /// Applies a partial update of the name, guarded by `expected_version`.
+    pub fn patch(
+        &mut self,
+        id: ObjectId,
+        expected_version: u64,
+        name: Option<String>,
+    ) -> Result<MyObject, RepositoryError> {
+        let obj = self.live_mut(id)?;
+        check_version(obj, expected_version)?;
+        if let Some(name) = name {
+            obj.name = name;
+        }
+        touch(obj);
+        let obj = obj.clone();
+        self.index.insert(&obj);
+        Ok(obj)
+    }
+
+    /// Records uploaded files, replacing metadata of same-named attachments.
+    pub fn add_attachments(
+        &mut self,
+        id: ObjectId,
+        attachments: Vec<Attachment>,
+    ) -> Result<MyObject, RepositoryError> {
+        let obj = self.live_mut(id)?;
+        for attachment in attachments {
+            obj.attachments.retain(|a| a.name != attachment.name);
+            obj.attachments.push(attachment);
+        }
+        touch(obj);
+        Ok(obj.clone())
+    }
+
+    pub fn soft_delete(&mut self, id: ObjectId) -> Result<MyObject, RepositoryError> {
+        let obj = self.live_mut(id)?;
+        obj.deleted_at = Some(Utc::now());
+        touch(obj);
+        let obj = obj.clone();
+        self.index.remove(id);
+        Ok(obj)
+    }
+
+    /// Permanently removes the object, whether or not it was soft-deleted.
+    pub fn hard_delete(&mut self, id: ObjectId) -> Result<MyObject, RepositoryError> {
+        let obj = self
+            .objects
+            .remove(&id)
+            .ok_or(RepositoryError::NotFound(id))?;
+        self.index.remove(id);
+        Ok(obj)
+    }
+
+    pub fn restore(&mut self, id: ObjectId) -> Result<MyObject, RepositoryError> {
+        let obj = self
+            .objects
+            .get_mut(&id)
+            .ok_or(RepositoryError::NotFound(id))?;
+        if !obj.is_deleted() {
+            return Err(RepositoryError::NotDeleted(id));
+        }
+        obj.deleted_at = None;
+        touch(obj);
+        let obj = obj.clone();
+        self.index.insert(&obj);
+        Ok(obj)
+    }
+
+    /// Ranked `(object, score)` matches among live objects.
+    pub fn search(&self, query: &str) -> Vec<(&MyObject, usize)> {
+        self.index
+            .search(query)
+            .into_iter()
+            .filter_map(|(id, score)| self.get(id).map(|obj| (obj, score)))
+            .collect()
+    }
+
+    fn live_mut(&mut self, id: ObjectId) -> Result<&mut MyObject, RepositoryError> {
+        self.objects
+            .get_mut(&id)
+            .filter(|o| !o.is_deleted())
+            .ok_or(RepositoryError::NotFound(id))
+    }
+}
+
+fn check_version(stored: &MyObject, expected: u64) -> Result<(), RepositoryError> {
+    if stored.version == expected {
+        Ok(())
+    } else {
+        Err(RepositoryError::VersionConflict {
+            id: stored.id,
+            expected,
+            actual: stored.version,
+        })
+    }
+}
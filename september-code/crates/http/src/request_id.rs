@@ -0,0 +1,82 @@
+use std::future::{ready, Ready};
+use std::time::Instant;
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
+use tracing::Instrument;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+const MAX_LEN: usize = 128;
+
+/// Id of the current request, available to handlers as an extractor.
+#[derive(Clone, Debug)]
+pub struct RequestId(pub String);
+
+impl FromRequest for RequestId {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let id = req
+            .extensions()
+            .get::<RequestId>()
+            .cloned()
+            .unwrap_or_else(|| RequestId(Uuid::new_v4().to_string()));
+        ready(Ok(id))
+    }
+}
+
+/// Honors a caller supplied id only if it is short printable ASCII, so it
+/// is safe to echo back in headers and log lines.
+fn incoming_id(req: &ServiceRequest) -> Option<String> {
+    let value = req.headers().get(REQUEST_ID_HEADER)?.to_str().ok()?.trim();
+    let valid =
+        !value.is_empty() && value.len() <= MAX_LEN && value.chars().all(|c| c.is_ascii_graphic());
+    valid.then(|| value.to_string())
+}
+
+/// Assigns every request an id, runs it inside a tracing span carrying that
+/// id, echoes it in the `X-Request-Id` response header and writes one access
+/// log line per request.
+pub async fn middleware(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let id = incoming_id(&req).unwrap_or_else(|| Uuid::new_v4().to_string());
+    req.extensions_mut().insert(RequestId(id.clone()));
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %req.method(),
+        path = %req.path(),
+    );
+    let started = Instant::now();
+    let result = next.call(req).instrument(span.clone()).await;
+    let duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+    let _entered = span.enter();
+
+    match result {
+        Ok(mut res) => {
+            let status = res.status().as_u16();
+            if res.status().is_server_error() {
+                tracing::error!(status, duration_ms, "request failed");
+            } else {
+                tracing::info!(status, duration_ms, "request completed");
+            }
+            if let Ok(value) = HeaderValue::from_str(&id) {
+                res.headers_mut()
+                    .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+            }
+            Ok(res)
+        }
+        Err(err) => {
+            tracing::error!(duration_ms, error = %err, "request errored");
+            Err(err)
+        }
+    }
+}
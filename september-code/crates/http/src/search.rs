@@ -0,0 +1,73 @@
+use std::collections::{BTreeMap, HashMap};
+
+use model::{MyObject, ObjectId};
+
+/// Inverted index over object names: token -> (object id -> term frequency).
+#[derive(Default)]
+pub struct SearchIndex {
+    postings: HashMap<String, BTreeMap<ObjectId, usize>>,
+    documents: HashMap<ObjectId, Vec<String>>,
+}
+
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+impl SearchIndex {
+    pub fn from_objects<'a>(objects: impl IntoIterator<Item = &'a MyObject>) -> Self {
+        let mut index = SearchIndex::default();
+        for obj in objects {
+            index.insert(obj);
+        }
+        index
+    }
+
+    /// Indexes `obj`, replacing whatever was previously indexed under its id.
+    pub fn insert(&mut self, obj: &MyObject) {
+        self.remove(obj.id);
+        let tokens = tokenize(&obj.name);
+        for token in &tokens {
+            *self
+                .postings
+                .entry(token.clone())
+                .or_default()
+                .entry(obj.id)
+                .or_default() += 1;
+        }
+        self.documents.insert(obj.id, tokens);
+    }
+
+    pub fn remove(&mut self, id: ObjectId) {
+        let Some(tokens) = self.documents.remove(&id) else {
+            return;
+        };
+        for token in tokens {
+            if let Some(ids) = self.postings.get_mut(&token) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    self.postings.remove(&token);
+                }
+            }
+        }
+    }
+
+    /// Returns `(id, score)` pairs, best match first. The score is the number of
+    /// query token occurrences found in the name; ties are broken by id.
+    pub fn search(&self, query: &str) -> Vec<(ObjectId, usize)> {
+        let mut scores: HashMap<ObjectId, usize> = HashMap::new();
+        for token in tokenize(query) {
+            if let Some(ids) = self.postings.get(&token) {
+                for (id, frequency) in ids {
+                    *scores.entry(*id).or_default() += frequency;
+                }
+            }
+        }
+
+        let mut ranked: Vec<(ObjectId, usize)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked
+    }
+}
@@ -0,0 +1,140 @@
+use std::env;
+use std::fs;
+
+use actix_cors::Cors;
+use actix_web::http::header::HeaderName;
+use actix_web::http::{Method, Uri};
+use actix_web::middleware::DefaultHeaders;
+use serde::Deserialize;
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Exact origins such as `https://app.example.com`; `*` allows any origin.
+    /// An empty list disables cross-origin access.
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    pub max_age_secs: usize,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        CorsConfig {
+            allowed_origins: Vec::new(),
+            allowed_methods: ["GET", "POST", "PUT", "PATCH", "DELETE"]
+                .map(String::from)
+                .to_vec(),
+            allowed_headers: ["Authorization", "Content-Type", "Last-Event-ID"]
+                .map(String::from)
+                .to_vec(),
+            max_age_secs: 3600,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct SecurityConfig {
+    pub cors: CorsConfig,
+    pub content_security_policy: String,
+    pub frame_options: String,
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        SecurityConfig {
+            cors: CorsConfig::default(),
+            content_security_policy: "default-src 'none'; frame-ancestors 'none'".to_string(),
+            frame_options: "DENY".to_string(),
+        }
+    }
+}
+
+fn list_from_env(name: &str) -> Option<Vec<String>> {
+    env::var(name).ok().map(|value| {
+        value
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    })
+}
+
+impl SecurityConfig {
+    /// Starts from the JSON file named by `SECURITY_CONFIG_FILE` (if any) and
+    /// lets `CORS_ALLOWED_ORIGINS`, `CORS_ALLOWED_METHODS`,
+    /// `CORS_ALLOWED_HEADERS` and `CONTENT_SECURITY_POLICY` override it.
+    pub fn load() -> Self {
+        let mut config = match env::var("SECURITY_CONFIG_FILE") {
+            Ok(path) => fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+                .unwrap_or_else(|err| {
+                    tracing::warn!(path = %path, error = %err, "ignoring security config");
+                    SecurityConfig::default()
+                }),
+            Err(_) => SecurityConfig::default(),
+        };
+
+        if let Some(origins) = list_from_env("CORS_ALLOWED_ORIGINS") {
+            config.cors.allowed_origins = origins;
+        }
+        if let Some(methods) = list_from_env("CORS_ALLOWED_METHODS") {
+            config.cors.allowed_methods = methods;
+        }
+        if let Some(headers) = list_from_env("CORS_ALLOWED_HEADERS") {
+            config.cors.allowed_headers = headers;
+        }
+        if let Ok(csp) = env::var("CONTENT_SECURITY_POLICY") {
+            config.content_security_policy = csp;
+        }
+        config
+    }
+
+    pub fn cors(&self) -> Cors {
+        let cors = &self.cors;
+        let mut middleware = Cors::default();
+        if cors.allowed_origins.iter().any(|o| o == "*") {
+            middleware = middleware.allow_any_origin();
+        } else {
+            // A malformed origin would make actix-cors fail at startup.
+            for origin in &cors.allowed_origins {
+                match Uri::try_from(origin.as_str()) {
+                    Ok(_) => middleware = middleware.allowed_origin(origin),
+                    Err(err) => {
+                        tracing::warn!(%origin, error = %err, "ignoring invalid CORS origin")
+                    }
+                }
+            }
+        }
+
+        // Invalid names are skipped here; actix-cors would otherwise fail at startup.
+        let methods: Vec<Method> = cors
+            .allowed_methods
+            .iter()
+            .filter_map(|m| Method::from_bytes(m.to_uppercase().as_bytes()).ok())
+            .collect();
+        let headers: Vec<HeaderName> = cors
+            .allowed_headers
+            .iter()
+            .filter_map(|h| HeaderName::try_from(h.as_str()).ok())
+            .collect();
+
+        middleware
+            .allowed_methods(methods)
+            .allowed_headers(headers)
+            .max_age(cors.max_age_secs)
+    }
+
+    pub fn headers(&self) -> DefaultHeaders {
+        DefaultHeaders::new()
+            .add(("X-Content-Type-Options", "nosniff"))
+            .add(("X-Frame-Options", self.frame_options.as_str()))
+            .add((
+                "Content-Security-Policy",
+                self.content_security_policy.as_str(),
+            ))
+            .add(("Referrer-Policy", "no-referrer"))
+    }
+}
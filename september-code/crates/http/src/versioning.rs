@@ -0,0 +1,226 @@
+//! URL based API versioning.
+//!
+//! The objects API is served under one `/api/v<N>` scope per supported
+//! version and, for clients that predate versioning, at the unprefixed
+//! legacy paths. Each scope carries its [`ApiVersion`] as app data. Legacy
+//! paths behave like [`ApiVersion::LEGACY`] and answer with `Deprecation`
+//! and `Link` headers pointing at their versioned successor.
+//!
+//! Handlers never serialize [`MyObject`] directly: request bodies arrive
+//! through [`VersionedJson`] and responses go out through
+//! [`ApiVersion::object`], both of which convert via the DTOs in
+//! [`model::api`]. Adding a version means adding a variant here, a DTO
+//! module there and the matching arms.
+//!
+//! The export and change stream endpoints still emit the stored
+//! representation and need their own per-version encoders once a version
+//! diverges from it.
+
+use std::fmt;
+use std::future::{ready, Ready};
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{web, Error, FromRequest, HttpRequest, HttpResponse};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+
+use model::api::v1;
+use model::MyObject;
+
+use crate::ObjectPatch;
+
+const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+const SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+/// A released version of the objects API, available to handlers as an
+/// extractor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ApiVersion {
+    V1,
+}
+
+impl ApiVersion {
+    /// All versions still being served, oldest first.
+    pub const SUPPORTED: &'static [ApiVersion] = &[ApiVersion::V1];
+    pub const LATEST: ApiVersion = ApiVersion::V1;
+    /// The version the unprefixed legacy paths are frozen at.
+    pub const LEGACY: ApiVersion = ApiVersion::V1;
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "v1",
+        }
+    }
+
+    /// Path prefix of this version, e.g. `/api/v1`.
+    pub fn prefix(self) -> String {
+        format!("/api/{}", self)
+    }
+
+    /// HTTP date after which a deprecated version stops being served, sent
+    /// in the `Sunset` header. `None` while the version is current.
+    pub fn sunset(self) -> Option<&'static str> {
+        match self {
+            ApiVersion::V1 => None,
+        }
+    }
+
+    /// `obj` in this version's wire format.
+    pub fn object(self, obj: MyObject) -> VersionedObject {
+        match self {
+            ApiVersion::V1 => VersionedObject::V1(obj.into()),
+        }
+    }
+
+    pub fn objects(self, objects: impl IntoIterator<Item = MyObject>) -> Vec<VersionedObject> {
+        objects.into_iter().map(|obj| self.object(obj)).collect()
+    }
+
+    fn of(req: &HttpRequest) -> ApiVersion {
+        req.app_data::<ApiVersion>()
+            .copied()
+            .unwrap_or(ApiVersion::LEGACY)
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct UnsupportedVersion(pub String);
+
+impl fmt::Display for UnsupportedVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unsupported API version: {}", self.0)
+    }
+}
+
+impl UnsupportedVersion {
+    pub fn to_response(&self) -> HttpResponse {
+        let supported: Vec<_> = ApiVersion::SUPPORTED.iter().map(|v| v.as_str()).collect();
+        HttpResponse::NotFound().json(json!({
+            "error": "unsupported_version",
+            "message": self.to_string(),
+            "supported": supported,
+        }))
+    }
+}
+
+/// Taken from the enclosing scope; routes outside the versioned scopes are
+/// treated as legacy routes.
+impl FromRequest for ApiVersion {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(ApiVersion::of(req)))
+    }
+}
+
+/// An object serialized with the DTO of the request's version.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum VersionedObject {
+    V1(v1::Object),
+}
+
+/// A request body type with one wire format per API version.
+pub trait FromVersioned: Sized {
+    type V1: DeserializeOwned + Into<Self> + 'static;
+}
+
+impl FromVersioned for MyObject {
+    type V1 = v1::Object;
+}
+
+impl FromVersioned for ObjectPatch {
+    type V1 = v1::ObjectPatch;
+}
+
+/// JSON body decoded from the wire format of the request's version.
+pub struct VersionedJson<T>(pub T);
+
+impl<T> VersionedJson<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: FromVersioned + 'static> FromRequest for VersionedJson<T> {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        match ApiVersion::of(req) {
+            ApiVersion::V1 => {
+                let body = web::Json::<T::V1>::from_request(req, payload);
+                Box::pin(async move { Ok(VersionedJson(body.await?.into_inner().into())) })
+            }
+        }
+    }
+}
+
+/// Marks responses of deprecated versions with `Deprecation`, `Sunset`
+/// and a `Link` to the same path under the latest version.
+pub async fn middleware(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let version = ApiVersion::of(req.request());
+    let path = req
+        .path()
+        .strip_prefix(&version.prefix())
+        .unwrap_or_default();
+    let successor = format!(
+        "<{}{}>; rel=\"successor-version\"",
+        ApiVersion::LATEST.prefix(),
+        path
+    );
+
+    let mut res = next.call(req).await?;
+    if let Some(sunset) = version.sunset() {
+        deprecate(&mut res, &successor);
+        res.headers_mut()
+            .insert(SUNSET, HeaderValue::from_static(sunset));
+    }
+    Ok(res)
+}
+
+/// Fallback for `/api/{version}` paths whose version is not served.
+pub async fn unsupported_version(req: HttpRequest) -> HttpResponse {
+    let version = req.match_info().get("version").unwrap_or_default();
+    UnsupportedVersion(version.to_string()).to_response()
+}
+
+/// Serves the unprefixed paths as [`ApiVersion::LEGACY`] and links every
+/// response to the same path under the versioned prefix.
+pub async fn legacy_middleware(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let successor = format!(
+        "<{}{}>; rel=\"successor-version\"",
+        ApiVersion::LEGACY.prefix(),
+        req.path()
+    );
+
+    let mut res = next.call(req).await?;
+    deprecate(&mut res, &successor);
+    Ok(res)
+}
+
+fn deprecate<B>(res: &mut ServiceResponse<B>, link: &str) {
+    let headers = res.headers_mut();
+    headers.insert(DEPRECATION, HeaderValue::from_static("true"));
+    if let Ok(value) = HeaderValue::from_str(link) {
+        headers.insert(actix_web::http::header::LINK, value);
+    }
+}
@@ -0,0 +1,8 @@
+//! Wire formats of the HTTP API, one module per version.
+//!
+//! Field names and shapes inside a version module never change once
+//! released. A breaking change to the objects API gets a new module (`v2`)
+//! with its own DTOs and conversions to and from [`MyObject`](crate::MyObject),
+//! so the storage model can evolve without affecting older clients.
+
+pub mod v1;
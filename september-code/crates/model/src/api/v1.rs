@@ -0,0 +1,89 @@
+//! `/api/v1` request and response bodies.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Attachment as StoredAttachment, MyObject, ObjectId};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Attachment {
+    pub name: String,
+    pub content_type: String,
+    pub size: u64,
+    pub uploaded_at: DateTime<Utc>,
+}
+
+/// Body of `POST`/`PUT /objects` and of every object in responses. The
+/// timestamps and attachments are ignored on input.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Object {
+    pub id: ObjectId,
+    pub name: String,
+    #[serde(default)]
+    pub version: u64,
+    #[serde(default)]
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+}
+
+/// Body of `PATCH /objects/{id}`.
+#[derive(Deserialize)]
+pub struct ObjectPatch {
+    pub name: Option<String>,
+    pub version: u64,
+}
+
+impl From<StoredAttachment> for Attachment {
+    fn from(attachment: StoredAttachment) -> Self {
+        Attachment {
+            name: attachment.name,
+            content_type: attachment.content_type,
+            size: attachment.size,
+            uploaded_at: attachment.uploaded_at,
+        }
+    }
+}
+
+impl From<Attachment> for StoredAttachment {
+    fn from(attachment: Attachment) -> Self {
+        StoredAttachment {
+            name: attachment.name,
+            content_type: attachment.content_type,
+            size: attachment.size,
+            uploaded_at: attachment.uploaded_at,
+        }
+    }
+}
+
+impl From<MyObject> for Object {
+    fn from(obj: MyObject) -> Self {
+        Object {
+            id: obj.id,
+            name: obj.name,
+            version: obj.version,
+            created_at: obj.created_at,
+            updated_at: obj.updated_at,
+            deleted_at: obj.deleted_at,
+            attachments: obj.attachments.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<Object> for MyObject {
+    fn from(obj: Object) -> Self {
+        MyObject {
+            id: obj.id,
+            name: obj.name,
+            version: obj.version,
+            created_at: obj.created_at,
+            updated_at: obj.updated_at,
+            deleted_at: obj.deleted_at,
+            attachments: obj.attachments.into_iter().map(Into::into).collect(),
+        }
+    }
+}
@@ -1,9 +1,85 @@
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+pub mod api;
+
+/// Identifier of a [`MyObject`]. Serialized as a plain number.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct ObjectId(pub u32);
+
+impl fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for ObjectId {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(ObjectId)
+    }
+}
+
+impl From<u32> for ObjectId {
+    fn from(id: u32) -> Self {
+        ObjectId(id)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Attachment {
+    pub name: String,
+    pub content_type: String,
+    pub size: u64,
+    pub uploaded_at: DateTime<Utc>,
+}
+
+/// Timestamps are serialized as RFC 3339 strings and are always set by the
+/// server; values sent by clients are ignored.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct MyObject {
-    pub id: u32,
+    pub id: ObjectId,
     pub name: String,
+    /// Incremented on every write. Updates must carry the version they were based on.
+    #[serde(default)]
+    pub version: u64,
+    #[serde(default)]
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub updated_at: DateTime<Utc>,
+    /// Time of the soft delete, `None` while the object is live.
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Metadata of uploaded files; managed by the attachment endpoints only.
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
 }
 
+impl MyObject {
+    pub fn new(id: impl Into<ObjectId>, name: impl Into<String>) -> Self {
+        let now = Utc::now();
+        MyObject {
+            id: id.into(),
+            name: name.into(),
+            version: 1,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            attachments: Vec::new(),
+        }
+    }
 
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    pub fn attachment(&self, name: &str) -> Option<&Attachment> {
+        self.attachments.iter().find(|a| a.name == name)
+    }
+}
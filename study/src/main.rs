@@ -0,0 +1,93 @@
+//! 学习仓库的统一入口
+//!
+//! 在仓库任意位置列出并运行各月份的示例程序，不必先进入对应目录：
+//!
+//! ```text
+//! study list
+//! study run october --size 1M
+//! study run july mutex
+//! ```
+
+use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
+use std::process;
+
+mod months;
+mod runner;
+
+use months::MONTHS;
+
+/// Rust 学习仓库示例启动器
+#[derive(Parser)]
+#[command(name = "study")]
+#[command(about = "列出并运行各月份的示例程序")]
+struct Cli {
+    /// 仓库根目录，默认为编译时 study 所在目录的上一级
+    #[arg(long, env = "STUDY_ROOT", global = true)]
+    root: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// 列出所有月份的示例项目
+    List,
+    /// 运行某个月份的示例程序，其余参数原样转发
+    Run {
+        /// 月份：英文名、前缀、序号或目录名，如 october、oct、10
+        month: String,
+
+        /// 以 release 模式编译运行
+        #[arg(long)]
+        release: bool,
+
+        /// 转发给示例程序的参数
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+}
+
+fn default_root() -> PathBuf {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    manifest_dir.parent().unwrap_or(manifest_dir).to_path_buf()
+}
+
+fn list(root: &Path) {
+    println!("{:>3}  {:<10} {:<16} 简介", "#", "月份", "目录");
+    for month in MONTHS {
+        let missing = if runner::project_dir(root, month).join("Cargo.toml").is_file() {
+            ""
+        } else {
+            "  (目录不存在)"
+        };
+        println!("{:>3}  {:<10} {:<16} {}{}", month.number, month.name, month.dir, month.summary, missing);
+        for example in month.examples {
+            println!("{:>31}study run {} {}", "", month.name, example);
+        }
+    }
+    println!("\n用法: study run <月份> [参数...]");
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let root = cli.root.unwrap_or_else(default_root);
+
+    match cli.command {
+        Commands::List => list(&root),
+        Commands::Run { month, release, args } => {
+            let Some(month) = months::find(&month) else {
+                eprintln!("错误: 未知月份 '{}'，运行 `study list` 查看可用的月份", month);
+                process::exit(2);
+            };
+            match runner::run(&root, month, release, &args) {
+                Ok(code) => process::exit(code),
+                Err(e) => {
+                    eprintln!("错误: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! 各月份示例项目的清单
+
+/// 一个月份的示例项目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Month {
+    /// 月份序号，1 到 12
+    pub number: u32,
+    /// 英文月份名，也是子命令中使用的名字
+    pub name: &'static str,
+    /// 项目目录，相对仓库根目录
+    pub dir: &'static str,
+    /// 项目内容简介
+    pub summary: &'static str,
+    /// 目录是工作区时要运行的包
+    pub package: Option<&'static str>,
+    /// 包里有多个二进制目标时要运行的那个
+    pub bin: Option<&'static str>,
+    /// 常用参数示例
+    pub examples: &'static [&'static str],
+}
+
+/// 按月份顺序排列的全部项目
+pub const MONTHS: &[Month] = &[
+    Month {
+        number: 1,
+        name: "january",
+        dir: "january-code",
+        summary: "基础语法：函数、递归与斐波那契数列",
+        package: None,
+        bin: None,
+        examples: &[],
+    },
+    Month {
+        number: 2,
+        name: "february",
+        dir: "february-code",
+        summary: "所有权与借用：缓存、文本分析和内存示例",
+        package: None,
+        bin: None,
+        examples: &[],
+    },
+    Month {
+        number: 3,
+        name: "march",
+        dir: "march-code",
+        summary: "任务管理命令行工具：存储、提醒、统计报表",
+        package: None,
+        bin: None,
+        examples: &["list", "report --weeks 4", "daemon --once --print"],
+    },
+    Month {
+        number: 4,
+        name: "april",
+        dir: "april-code",
+        summary: "模块系统：计算器与统计库",
+        package: None,
+        bin: None,
+        examples: &[],
+    },
+    Month {
+        number: 5,
+        name: "may",
+        dir: "may-code",
+        summary: "泛型与错误处理：多格式配置管理器",
+        package: None,
+        bin: None,
+        examples: &["formats", "demo", "load --file example.json"],
+    },
+    Month {
+        number: 6,
+        name: "june",
+        dir: "june-code",
+        summary: "闭包与迭代器：购物车、排行榜和数据管道",
+        package: None,
+        bin: None,
+        examples: &[],
+    },
+    Month {
+        number: 7,
+        name: "july",
+        dir: "july-code",
+        summary: "并发：互斥锁、通道、原子操作、工作窃取等",
+        package: None,
+        bin: None,
+        examples: &["mutex", "channels", "stealing"],
+    },
+    Month {
+        number: 8,
+        name: "august",
+        dir: "august-code",
+        summary: "异步编程：HTTP、Web 服务器、数据库、调度器",
+        package: None,
+        bin: None,
+        examples: &["basics", "http", "db", "offline"],
+    },
+    Month {
+        number: 9,
+        name: "september",
+        dir: "september-code",
+        summary: "REST API 服务（actix-web 工作区）",
+        package: Some("rust-rest-api"),
+        bin: None,
+        examples: &[],
+    },
+    Month {
+        number: 10,
+        name: "october",
+        dir: "october-code",
+        summary: "性能优化：优化前后对比与回归检测",
+        package: None,
+        bin: None,
+        examples: &["--size 1M", "--tests avg,freq --iterations 20"],
+    },
+    Month {
+        number: 11,
+        name: "november",
+        dir: "november-code",
+        summary: "宏与高级 trait：声明宏、访问者模式、手写执行器",
+        package: None,
+        bin: Some("main"),
+        examples: &[],
+    },
+];
+
+/// 查找月份，接受英文名（`october`）、至少三个字母的前缀（`oct`）、
+/// 序号（`10`）和目录名（`october-code`），不区分大小写
+pub fn find(query: &str) -> Option<&'static Month> {
+    let query = query.trim().to_lowercase();
+    let query = query.strip_suffix("-code").unwrap_or(&query);
+    if let Ok(number) = query.parse::<u32>() {
+        return MONTHS.iter().find(|month| month.number == number);
+    }
+    if query.len() < 3 {
+        return None;
+    }
+    MONTHS.iter().find(|month| month.name.starts_with(query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_accepts_name_prefix_number_and_dir() {
+        for query in ["october", "Oct", "10", "october-code"] {
+            assert_eq!(find(query).map(|month| month.name), Some("october"), "{}", query);
+        }
+        assert_eq!(find("jul").unwrap().dir, "july-code");
+        assert_eq!(find("jun").unwrap().dir, "june-code");
+    }
+
+    #[test]
+    fn test_find_rejects_unknown_and_ambiguous_short_queries() {
+        assert!(find("ju").is_none());
+        assert!(find("13").is_none());
+        assert!(find("december").is_none());
+    }
+
+    #[test]
+    fn test_months_are_in_order() {
+        for (index, month) in MONTHS.iter().enumerate() {
+            assert_eq!(month.number as usize, index + 1);
+            assert!(month.dir.starts_with(month.name));
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! 通过 `cargo run` 启动某个月份的示例程序
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::months::Month;
+
+/// 月份项目所在目录
+pub fn project_dir(root: &Path, month: &Month) -> PathBuf {
+    root.join(month.dir)
+}
+
+/// 构造运行示例程序的 `cargo run` 命令
+///
+/// 工作目录设为月份项目目录，示例程序里的相对路径（如 `example.json`）
+/// 与在该目录下直接 `cargo run` 时一致；`args` 原样转发给示例程序。
+pub fn cargo_command(root: &Path, month: &Month, release: bool, args: &[String]) -> Command {
+    let dir = project_dir(root, month);
+    let mut command = Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()));
+    command.current_dir(&dir).arg("run").arg("--quiet");
+    command.arg("--manifest-path").arg(dir.join("Cargo.toml"));
+    if let Some(package) = month.package {
+        command.args(["--package", package]);
+    }
+    if let Some(bin) = month.bin {
+        command.args(["--bin", bin]);
+    }
+    if release {
+        command.arg("--release");
+    }
+    command.arg("--").args(args);
+    command
+}
+
+/// 运行示例程序并返回其退出码
+pub fn run(root: &Path, month: &Month, release: bool, args: &[String]) -> Result<i32, String> {
+    let dir = project_dir(root, month);
+    if !dir.join("Cargo.toml").is_file() {
+        return Err(format!("找不到 {}，请用 --root 指定仓库根目录", dir.join("Cargo.toml").display()));
+    }
+    let status = cargo_command(root, month, release, args)
+        .status()
+        .map_err(|e| format!("无法启动 cargo: {}", e))?;
+    // 被信号终止时没有退出码
+    Ok(status.code().unwrap_or(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::months;
+
+    fn args_of(command: &Command) -> Vec<String> {
+        command.get_args().map(|arg| arg.to_string_lossy().into_owned()).collect()
+    }
+
+    #[test]
+    fn test_command_forwards_arguments_after_separator() {
+        let root = Path::new("/repo");
+        let october = months::find("october").unwrap();
+        let args = vec!["--size".to_string(), "1M".to_string()];
+        let command = cargo_command(root, october, true, &args);
+
+        assert_eq!(command.get_current_dir(), Some(Path::new("/repo/october-code")));
+        assert_eq!(
+            args_of(&command),
+            ["run", "--quiet", "--manifest-path", "/repo/october-code/Cargo.toml", "--release", "--", "--size", "1M"]
+        );
+    }
+
+    #[test]
+    fn test_command_selects_package_and_bin() {
+        let root = Path::new("/repo");
+        let september = args_of(&cargo_command(root, months::find("september").unwrap(), false, &[]));
+        assert!(september.windows(2).any(|pair| pair == ["--package", "rust-rest-api"]));
+
+        let november = args_of(&cargo_command(root, months::find("november").unwrap(), false, &[]));
+        assert!(november.windows(2).any(|pair| pair == ["--bin", "main"]));
+        assert!(!november.contains(&"--release".to_string()));
+    }
+}